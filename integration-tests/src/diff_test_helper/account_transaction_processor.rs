@@ -1,6 +1,6 @@
 use crate::models::account_transaction_models::AccountTransaction;
 use anyhow::Result;
-use diesel::{pg::PgConnection, query_dsl::methods::ThenOrderDsl, ExpressionMethods, RunQueryDsl};
+use diesel::{pg::PgConnection, ExpressionMethods, QueryDsl, RunQueryDsl};
 use processor::schema::account_transactions::dsl::*;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -9,7 +9,9 @@ use std::collections::HashMap;
 pub fn load_data(conn: &mut PgConnection) -> Result<HashMap<String, Value>> {
     let mut result_map: HashMap<String, Value> = HashMap::new();
 
+    // The recorded outputs predate the transaction kind columns
     let acc_txn_result = account_transactions
+        .select((transaction_version, account_address, inserted_at))
         .then_order_by(transaction_version.asc())
         .then_order_by(inserted_at.asc())
         .then_order_by(account_address.asc())
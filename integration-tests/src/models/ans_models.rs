@@ -37,6 +37,7 @@ pub struct CurrentAnsLookupV2 {
     pub is_deleted: bool,
     pub inserted_at: chrono::NaiveDateTime,
     pub subdomain_expiration_policy: Option<i64>,
+    pub effective_expiration_timestamp: Option<chrono::NaiveDateTime>,
 }
 
 #[derive(
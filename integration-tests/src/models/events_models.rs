@@ -25,4 +25,8 @@ pub struct Event {
     pub inserted_at: chrono::NaiveDateTime,
     pub event_index: i64,
     pub indexed_type: String,
+    pub module_address: Option<String>,
+    pub module_name: Option<String>,
+    pub struct_name: Option<String>,
+    pub generic_args: Option<String>,
 }
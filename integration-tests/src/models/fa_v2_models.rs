@@ -33,6 +33,9 @@ pub struct FungibleAssetActivity {
     pub transaction_timestamp: chrono::NaiveDateTime,
     pub inserted_at: chrono::NaiveDateTime,
     pub storage_refund_amount: BigDecimal,
+    pub counterparty_label: Option<String>,
+    pub is_migration: bool,
+    pub usd_value: Option<BigDecimal>,
 }
 
 #[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize, Queryable)]
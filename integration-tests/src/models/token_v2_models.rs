@@ -34,6 +34,9 @@ pub struct TokenActivityV2 {
     pub is_fungible_v2: Option<bool>,
     pub transaction_timestamp: chrono::NaiveDateTime,
     pub inserted_at: chrono::NaiveDateTime,
+    pub usd_value: Option<BigDecimal>,
+    pub burner_address: Option<String>,
+    pub is_creator_burn: Option<bool>,
 }
 
 #[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize, Queryable)]
@@ -126,6 +129,10 @@ pub struct CurrentTokenDataV2 {
     // Deprecated, but still here for backwards compatibility
     pub decimals: Option<i64>,
     pub is_deleted_v2: Option<bool>,
+    pub creator_address: Option<String>,
+    pub collection_mutable_description: Option<bool>,
+    pub collection_mutable_uri: Option<bool>,
+    pub royalty_bps: Option<i64>,
 }
 
 #[derive(
@@ -233,4 +240,6 @@ pub struct CurrentTokenPendingClaim {
     pub last_transaction_timestamp: chrono::NaiveDateTime,
     pub token_data_id: String,
     pub collection_id: String,
+    pub status: Option<String>,
+    pub resolution_transaction_version: Option<i64>,
 }
@@ -53,4 +53,5 @@ pub struct UserTransaction {
     pub entry_function_contract_address: Option<String>,
     pub entry_function_module_name: Option<String>,
     pub entry_function_function_name: Option<String>,
+    pub latency_from_expiration: Option<i64>,
 }
@@ -1,60 +1,25 @@
 use ahash::AHashMap;
-use cedra_indexer_processor_sdk::testing_framework::sdk_test_context::SdkTestContext;
-use processor::config::{
-    db_config::{DbConfig, PostgresConfig},
-    indexer_processor_config::IndexerProcessorConfig,
-    processor_config::{DefaultProcessorConfig, ProcessorConfig},
-    processor_mode::{ProcessorMode, TestingConfig},
-};
+use processor::config::processor_config::{DefaultProcessorConfig, ProcessorConfig};
 use std::collections::HashSet;
 
-pub fn setup_account_restoration_processor_config(
-    test_context: &SdkTestContext,
-    db_url: &str,
-) -> (IndexerProcessorConfig, &'static str) {
-    let transaction_stream_config = test_context.create_transaction_stream_config();
-    let postgres_config = PostgresConfig {
-        connection_string: db_url.to_string(),
-        db_pool_size: 100,
-    };
-
-    let db_config = DbConfig::PostgresConfig(postgres_config);
-    let default_processor_config = DefaultProcessorConfig {
+pub fn account_restoration_processor_config() -> ProcessorConfig {
+    ProcessorConfig::AccountRestorationProcessor(DefaultProcessorConfig {
         per_table_chunk_sizes: AHashMap::new(),
         channel_size: 100,
         tables_to_write: HashSet::new(),
-    };
-
-    let processor_config = ProcessorConfig::AccountRestorationProcessor(default_processor_config);
-
-    let processor_name = processor_config.name();
-    (
-        IndexerProcessorConfig {
-            processor_config,
-            transaction_stream_config: transaction_stream_config.clone(),
-            db_config,
-            processor_mode: ProcessorMode::Testing(TestingConfig {
-                override_starting_version: transaction_stream_config.starting_version.unwrap(),
-                ending_version: transaction_stream_config.request_ending_version,
-            }),
-        },
-        processor_name,
-    )
+        current_table_dedup_window: None,
+    })
 }
 
 #[allow(clippy::needless_return)]
 #[cfg(test)]
 mod sdk_account_restoration_processor_tests {
-    use super::setup_account_restoration_processor_config;
+    use super::account_restoration_processor_config;
     use crate::{
         diff_test_helper::account_restoration_processor::load_data,
-        sdk_tests::{
-            run_processor_test, validate_json, PostgresTestDatabase, DEFAULT_OUTPUT_FOLDER,
-        },
-    };
-    use cedra_indexer_processor_sdk::testing_framework::{
-        cli_parser::get_test_config, database::TestDatabase, sdk_test_context::SdkTestContext,
+        sdk_tests::{golden_harness::assert_golden_test_with_db, PostgresTestDatabase},
     };
+    use cedra_indexer_processor_sdk::testing_framework::database::TestDatabase;
     use cedra_indexer_test_transactions::json_transactions::generated_transactions::{
         IMPORTED_MAINNET_TXNS_2200077591_ACCOUNT_RESTORATION_SINGLE_ED25519,
         IMPORTED_MAINNET_TXNS_2200077673_ACCOUNT_RESTORATION_UNVERIFIED_KEY_ROTATION_TO_MULTI_KEY_TXN,
@@ -211,28 +176,9 @@ mod sdk_account_restoration_processor_tests {
         db: &PostgresTestDatabase,
         chunk_size: Option<usize>,
     ) {
-        let mut test_context = SdkTestContext::new(txns);
-        if test_context.init_mock_grpc().await.is_err() {
-            panic!("Failed to initialize mock grpc");
-        };
-        process_transaction_helper(db, &mut test_context, test_case_name, chunk_size).await;
-    }
-
-    async fn process_transaction_helper(
-        db: &PostgresTestDatabase,
-        test_context: &mut SdkTestContext,
-        test_case_name: Option<String>,
-        chunk_size: Option<usize>,
-    ) {
-        let (generate_flag, custom_output_path) = get_test_config();
-        let output_path = custom_output_path.unwrap_or_else(|| DEFAULT_OUTPUT_FOLDER.to_string());
-        let db_url = db.get_db_url();
-        let (mut indexer_processor_config, processor_name) =
-            setup_account_restoration_processor_config(test_context, &db_url);
+        let mut processor_config = account_restoration_processor_config();
         if let Some(chunk_size) = chunk_size {
-            if let ProcessorConfig::AccountRestorationProcessor(ref mut config) =
-                indexer_processor_config.processor_config
-            {
+            if let ProcessorConfig::AccountRestorationProcessor(ref mut config) = processor_config {
                 for table_name in [
                     "auth_key_account_address",
                     "auth_key_multikey_layout",
@@ -244,38 +190,14 @@ mod sdk_account_restoration_processor_tests {
                 }
             }
         }
-        let account_restoration_processor =
-            AccountRestorationProcessor::new(indexer_processor_config)
-                .await
-                .expect("Failed to create AccountRestorationProcessor");
-
-        match run_processor_test(
-            test_context,
-            account_restoration_processor,
+        assert_golden_test_with_db(
+            db,
+            txns,
+            test_case_name,
+            processor_config,
+            AccountRestorationProcessor::new,
             load_data,
-            db_url,
-            generate_flag,
-            output_path.clone(),
-            test_case_name.clone(),
         )
-        .await
-        {
-            Ok(mut db_value) => {
-                let _ = validate_json(
-                    &mut db_value,
-                    0,
-                    processor_name,
-                    output_path.clone(),
-                    test_case_name,
-                );
-            },
-            Err(e) => {
-                panic!(
-                    "Test failed on transactions {:?} due to processor error: {}",
-                    test_context.get_test_transaction_versions(),
-                    e
-                );
-            },
-        }
+        .await;
     }
 }
@@ -1,42 +1,39 @@
+use crate::sdk_tests::golden_harness::test_indexer_processor_config;
 use ahash::AHashMap;
 use cedra_indexer_processor_sdk::testing_framework::sdk_test_context::SdkTestContext;
-use processor::config::{
-    db_config::{DbConfig, PostgresConfig},
-    indexer_processor_config::IndexerProcessorConfig,
-    processor_config::{DefaultProcessorConfig, ProcessorConfig},
-    processor_mode::{ProcessorMode, TestingConfig},
+use processor::{
+    config::{
+        indexer_processor_config::IndexerProcessorConfig,
+        processor_config::{DefaultProcessorConfig, ProcessorConfig},
+    },
+    processors::default::default_processor::DefaultProcessorExtendedConfig,
 };
 use std::collections::HashSet;
 
-pub fn setup_default_processor_config(
-    test_context: &SdkTestContext,
-    db_url: &str,
-) -> (IndexerProcessorConfig, &'static str) {
-    let transaction_stream_config = test_context.create_transaction_stream_config(); // since this will be always 1, we can remove from the arg list
-    let postgres_config = PostgresConfig {
-        connection_string: db_url.to_string(),
-        db_pool_size: 100,
-    };
-
-    let db_config = DbConfig::PostgresConfig(postgres_config);
+pub fn default_processor_config() -> ProcessorConfig {
     let default_processor_config = DefaultProcessorConfig {
         per_table_chunk_sizes: AHashMap::new(),
         channel_size: 100,
         tables_to_write: HashSet::new(),
+        current_table_dedup_window: None,
     };
 
-    let processor_config = ProcessorConfig::DefaultProcessor(default_processor_config);
+    let default_processor_config = DefaultProcessorExtendedConfig {
+        default_config: default_processor_config,
+        table_item_types: vec![],
+    };
+
+    ProcessorConfig::DefaultProcessor(default_processor_config)
+}
+
+pub fn setup_default_processor_config(
+    test_context: &SdkTestContext,
+    db_url: &str,
+) -> (IndexerProcessorConfig, &'static str) {
+    let processor_config = default_processor_config();
     let processor_name = processor_config.name();
     (
-        IndexerProcessorConfig {
-            processor_config,
-            transaction_stream_config: transaction_stream_config.clone(),
-            db_config,
-            processor_mode: ProcessorMode::Testing(TestingConfig {
-                override_starting_version: transaction_stream_config.starting_version.unwrap(),
-                ending_version: transaction_stream_config.request_ending_version,
-            }),
-        },
+        test_indexer_processor_config(test_context, db_url, processor_config),
         processor_name,
     )
 }
@@ -47,13 +44,9 @@ mod tests {
     use crate::{
         diff_test_helper::default_processor::load_data,
         sdk_tests::{
-            default_processor_tests::setup_default_processor_config, run_processor_test,
-            setup_test_environment, validate_json, DEFAULT_OUTPUT_FOLDER,
+            default_processor_tests::default_processor_config, golden_harness::assert_golden_test,
         },
     };
-    use cedra_indexer_processor_sdk::testing_framework::{
-        cli_parser::get_test_config, database::TestDatabase,
-    };
     use cedra_indexer_test_transactions::json_transactions::generated_transactions::{
         IMPORTED_MAINNET_TXNS_155112189_DEFAULT_TABLE_ITEMS,
         IMPORTED_MAINNET_TXNS_1845035942_DEFAULT_CURRENT_TABLE_ITEMS,
@@ -100,46 +93,13 @@ mod tests {
 
     // Helper function to abstract out the single transaction processing
     async fn process_single_mainnet_event_txn(txn: &[u8], test_case_name: Option<String>) {
-        let (generate_flag, custom_output_path) = get_test_config();
-        let output_path = custom_output_path.unwrap_or_else(|| DEFAULT_OUTPUT_FOLDER.to_string());
-
-        let (db, mut test_context) = setup_test_environment(&[txn]).await;
-
-        let db_url = db.get_db_url();
-        let (indexer_processor_config, processor_name) =
-            setup_default_processor_config(&test_context, &db_url);
-
-        let default_processor = DefaultProcessor::new(indexer_processor_config)
-            .await
-            .expect("Failed to create DefaultProcessor");
-
-        match run_processor_test(
-            &mut test_context,
-            default_processor,
+        assert_golden_test(
+            &[txn],
+            test_case_name,
+            default_processor_config(),
+            DefaultProcessor::new,
             load_data,
-            db_url,
-            generate_flag,
-            output_path.clone(),
-            test_case_name.clone(),
         )
-        .await
-        {
-            Ok(mut db_value) => {
-                let _ = validate_json(
-                    &mut db_value,
-                    test_context.get_request_start_version(),
-                    processor_name,
-                    output_path.clone(),
-                    test_case_name,
-                );
-            },
-            Err(e) => {
-                panic!(
-                    "Test failed on transactions {:?} due to processor error: {}",
-                    test_context.get_test_transaction_versions(),
-                    e
-                );
-            },
-        }
+        .await;
     }
 }
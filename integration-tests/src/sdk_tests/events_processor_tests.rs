@@ -1,42 +1,38 @@
+use crate::sdk_tests::golden_harness::test_indexer_processor_config;
 use ahash::AHashMap;
 use cedra_indexer_processor_sdk::testing_framework::sdk_test_context::SdkTestContext;
-use processor::config::{
-    db_config::{DbConfig, PostgresConfig},
-    indexer_processor_config::IndexerProcessorConfig,
-    processor_config::{DefaultProcessorConfig, ProcessorConfig},
-    processor_mode::{ProcessorMode, TestingConfig},
+use processor::{
+    config::{
+        indexer_processor_config::IndexerProcessorConfig,
+        processor_config::{DefaultProcessorConfig, ProcessorConfig},
+    },
+    processors::events::events_processor::EventsProcessorConfig,
 };
 use std::collections::HashSet;
 
+pub fn events_processor_config() -> ProcessorConfig {
+    ProcessorConfig::EventsProcessor(EventsProcessorConfig {
+        default_config: DefaultProcessorConfig {
+            per_table_chunk_sizes: AHashMap::new(),
+            channel_size: 100,
+            tables_to_write: HashSet::new(),
+            current_table_dedup_window: None,
+        },
+        attribute_rules: vec![],
+        sampling: None,
+        compaction: None,
+        pubsub: None,
+    })
+}
+
 pub fn setup_events_processor_config(
     test_context: &SdkTestContext,
     db_url: &str,
 ) -> (IndexerProcessorConfig, &'static str) {
-    let transaction_stream_config = test_context.create_transaction_stream_config(); // since this will be always 1, we can remove from the arg list
-    let postgres_config = PostgresConfig {
-        connection_string: db_url.to_string(),
-        db_pool_size: 100,
-    };
-
-    let db_config = DbConfig::PostgresConfig(postgres_config);
-    let default_processor_config = DefaultProcessorConfig {
-        per_table_chunk_sizes: AHashMap::new(),
-        channel_size: 100,
-        tables_to_write: HashSet::new(),
-    };
-
-    let processor_config = ProcessorConfig::EventsProcessor(default_processor_config);
+    let processor_config = events_processor_config();
     let processor_name = processor_config.name();
     (
-        IndexerProcessorConfig {
-            processor_config,
-            transaction_stream_config: transaction_stream_config.clone(),
-            db_config,
-            processor_mode: ProcessorMode::Testing(TestingConfig {
-                override_starting_version: transaction_stream_config.starting_version.unwrap(),
-                ending_version: transaction_stream_config.request_ending_version,
-            }),
-        },
+        test_indexer_processor_config(test_context, db_url, processor_config),
         processor_name,
     )
 }
@@ -47,15 +43,9 @@ mod tests {
     use crate::{
         diff_test_helper::event_processor::load_data,
         sdk_tests::{
-            events_processor_tests::setup_events_processor_config, run_processor_test,
-            setup_test_environment, validate_json, DEFAULT_OUTPUT_FOLDER,
+            events_processor_tests::events_processor_config, golden_harness::assert_golden_test,
         },
     };
-    use cedra_indexer_processor_sdk::{
-        cedra_protos::transaction::v1::Transaction,
-        testing_framework::{cli_parser::get_test_config, database::TestDatabase},
-        traits::processor_trait::ProcessorTrait,
-    };
     use cedra_indexer_test_transactions::json_transactions::generated_transactions::{
         IMPORTED_DEVNET_TXNS_78753831_TOKEN_V1_MINT_TRANSFER_WITH_V2_EVENTS,
         IMPORTED_DEVNET_TXNS_78753832_TOKEN_V2_MINT_TRANSFER_WITH_V2_EVENTS,
@@ -174,58 +164,17 @@ mod tests {
     // Example 2: Test for multiple transactions handling
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_events_processor_db_output_scenario_testing() {
-        let (generate_flag, custom_output_path) = get_test_config();
-        let output_path = custom_output_path.unwrap_or_else(|| DEFAULT_OUTPUT_FOLDER.to_string());
-
-        let imported = [
-            IMPORTED_TESTNET_TXNS_5523474016_VALIDATOR_TXN,
-            IMPORTED_TESTNET_TXNS_5979639459_COIN_REGISTER,
-        ];
-
-        let (db, mut test_context) = setup_test_environment(&imported).await;
-
-        let transaction_batches: Vec<Transaction> = imported
-            .iter()
-            .map(|txn| serde_json::from_slice(txn).expect("Failed to deserialize transaction"))
-            .collect();
-
-        let starting_version = transaction_batches[0].version;
-
-        let db_url = db.get_db_url();
-        let (indexer_processor_config, _processor_name) =
-            setup_events_processor_config(&test_context, &db_url);
-
-        let events_processor = EventsProcessor::new(indexer_processor_config)
-            .await
-            .expect("Failed to create EventsProcessor");
-        let processor_name = events_processor.name();
-        match run_processor_test(
-            &mut test_context,
-            events_processor,
-            load_data,
-            db_url,
-            generate_flag,
-            output_path.clone(),
+        assert_golden_test(
+            &[
+                IMPORTED_TESTNET_TXNS_5523474016_VALIDATOR_TXN,
+                IMPORTED_TESTNET_TXNS_5979639459_COIN_REGISTER,
+            ],
             Some("multi_txns_handling_test".to_string()),
+            events_processor_config(),
+            EventsProcessor::new,
+            load_data,
         )
-        .await
-        {
-            Ok(mut db_value) => {
-                let _ = validate_json(
-                    &mut db_value,
-                    starting_version,
-                    processor_name,
-                    output_path.clone(),
-                    Some("multi_txns_handling_test".to_string()),
-                );
-            },
-            Err(e) => {
-                eprintln!(
-                    "[ERROR] Failed to run processor for txn version {starting_version}: {e}"
-                );
-                panic!("Test failed due to processor error");
-            },
-        }
+        .await;
     }
 
     async fn process_single_devnet_event_txn(txn: &[u8], test_case_name: Option<String>) {
@@ -242,46 +191,13 @@ mod tests {
 
     // Helper function to abstract out the single transaction processing
     async fn process_single_event_txn(txn: &[u8], test_case_name: Option<String>) {
-        let (generate_flag, custom_output_path) = get_test_config();
-        let output_path = custom_output_path.unwrap_or_else(|| DEFAULT_OUTPUT_FOLDER.to_string());
-
-        let (db, mut test_context) = setup_test_environment(&[txn]).await;
-
-        let db_url = db.get_db_url();
-        let (indexer_processor_config, processor_name) =
-            setup_events_processor_config(&test_context, &db_url);
-
-        let events_processor = EventsProcessor::new(indexer_processor_config)
-            .await
-            .expect("Failed to create EventsProcessor");
-
-        match run_processor_test(
-            &mut test_context,
-            events_processor,
+        assert_golden_test(
+            &[txn],
+            test_case_name,
+            events_processor_config(),
+            EventsProcessor::new,
             load_data,
-            db_url,
-            generate_flag,
-            output_path.clone(),
-            test_case_name.clone(),
         )
-        .await
-        {
-            Ok(mut db_value) => {
-                let _ = validate_json(
-                    &mut db_value,
-                    test_context.get_request_start_version(),
-                    processor_name,
-                    output_path.clone(),
-                    test_case_name,
-                );
-            },
-            Err(e) => {
-                panic!(
-                    "Test failed on transactions {:?} due to processor error: {}",
-                    test_context.get_test_transaction_versions(),
-                    e
-                );
-            },
-        }
+        .await;
     }
 }
@@ -1,43 +1,42 @@
+use crate::sdk_tests::golden_harness::test_indexer_processor_config;
 use ahash::AHashMap;
 use cedra_indexer_processor_sdk::testing_framework::sdk_test_context::SdkTestContext;
-use processor::config::{
-    db_config::{DbConfig, PostgresConfig},
-    indexer_processor_config::IndexerProcessorConfig,
-    processor_config::{DefaultProcessorConfig, ProcessorConfig},
-    processor_mode::{ProcessorMode, TestingConfig},
+use processor::{
+    config::{
+        indexer_processor_config::IndexerProcessorConfig,
+        processor_config::{DefaultProcessorConfig, ProcessorConfig},
+    },
+    processors::fungible_asset::fungible_asset_processor::FungibleAssetProcessorConfig,
 };
 use std::collections::HashSet;
 
-pub fn setup_fa_processor_config(
-    test_context: &SdkTestContext,
-    db_url: &str,
-) -> (IndexerProcessorConfig, &'static str) {
-    let transaction_stream_config = test_context.create_transaction_stream_config();
-    let postgres_config = PostgresConfig {
-        connection_string: db_url.to_string(),
-        db_pool_size: 100,
-    };
-
-    let db_config = DbConfig::PostgresConfig(postgres_config);
+pub fn fa_processor_config() -> ProcessorConfig {
     let default_processor_config = DefaultProcessorConfig {
         per_table_chunk_sizes: AHashMap::new(),
         channel_size: 100,
         tables_to_write: HashSet::new(),
+        current_table_dedup_window: None,
+    };
+
+    let fa_processor_config = FungibleAssetProcessorConfig {
+        default_config: default_processor_config,
+        query_retries: FungibleAssetProcessorConfig::default_query_retries(),
+        query_retry_delay_ms: FungibleAssetProcessorConfig::default_query_retry_delay_ms(),
+        address_labels: None,
+        price_oracle: None,
     };
 
-    let processor_config = ProcessorConfig::FungibleAssetProcessor(default_processor_config);
+    ProcessorConfig::FungibleAssetProcessor(fa_processor_config)
+}
 
+pub fn setup_fa_processor_config(
+    test_context: &SdkTestContext,
+    db_url: &str,
+) -> (IndexerProcessorConfig, &'static str) {
+    let processor_config = fa_processor_config();
     let processor_name = processor_config.name();
     (
-        IndexerProcessorConfig {
-            processor_config,
-            transaction_stream_config: transaction_stream_config.clone(),
-            db_config,
-            processor_mode: ProcessorMode::Testing(TestingConfig {
-                override_starting_version: transaction_stream_config.starting_version.unwrap(),
-                ending_version: transaction_stream_config.request_ending_version,
-            }),
-        },
+        test_indexer_processor_config(test_context, db_url, processor_config),
         processor_name,
     )
 }
@@ -48,15 +47,14 @@ mod sdk_fungible_asset_processor_tests {
     use crate::{
         diff_test_helper::fungible_asset_processor::load_data,
         sdk_tests::{
-            fungible_asset_processor_tests::setup_fa_processor_config, run_processor_test,
-            validate_json, DEFAULT_OUTPUT_FOLDER,
+            fungible_asset_processor_tests::fa_processor_config,
+            golden_harness::{
+                assert_golden_test, assert_golden_test_with_db, run_fixtures_with_db,
+            },
+            PostgresTestDatabase,
         },
     };
-    use cedra_indexer_processor_sdk::testing_framework::{
-        cli_parser::get_test_config,
-        database::{PostgresTestDatabase, TestDatabase},
-        sdk_test_context::SdkTestContext,
-    };
+    use cedra_indexer_processor_sdk::testing_framework::database::TestDatabase;
     use cedra_indexer_test_transactions::json_transactions::generated_transactions::{
         IMPORTED_MAINNET_TXNS_1680592683_FA_MIGRATION_COIN_INFO,
         IMPORTED_MAINNET_TXNS_1737056775_COIN_TRANSFER_BURN_EVENT,
@@ -339,90 +337,39 @@ mod sdk_fungible_asset_processor_tests {
         txn_batches: &[&[&[u8]]],
         output_name: &str,
     ) {
-        let (generate_flag, custom_output_path) = get_test_config();
-        let output_path = custom_output_path.unwrap_or_else(|| DEFAULT_OUTPUT_FOLDER.to_string());
-
         let mut db = PostgresTestDatabase::new();
         db.setup().await.unwrap();
 
-        for (i, txn_batch) in txn_batches.iter().enumerate() {
-            let is_last = i == txn_batches.len() - 1;
-            process_transactions(
-                &mut db,
+        let (last_batch, txn_batches) = txn_batches.split_last().unwrap();
+        for txn_batch in txn_batches {
+            run_fixtures_with_db(
+                &db,
                 txn_batch,
-                output_name,
-                is_last && generate_flag,
-                &output_path,
-                is_last,
+                fa_processor_config(),
+                FungibleAssetProcessor::new,
+                load_data,
             )
-            .await;
-        }
-    }
-
-    async fn process_transactions(
-        db: &mut PostgresTestDatabase,
-        txns: &[&[u8]],
-        transaction_name: &str,
-        generate_flag: bool,
-        output_path: &str,
-        should_validate: bool,
-    ) {
-        let mut test_context = SdkTestContext::new(txns);
-        if test_context.init_mock_grpc().await.is_err() {
-            panic!("Failed to initialize mock grpc");
-        };
-
-        let db_url = db.get_db_url();
-        let (indexer_processor_config, processor_name) =
-            setup_fa_processor_config(&test_context, &db_url);
-
-        let fungible_asset_processor = FungibleAssetProcessor::new(indexer_processor_config)
             .await
-            .expect("Failed to create FungibleAssetProcessor");
-
-        match run_processor_test(
-            &mut test_context,
-            fungible_asset_processor,
+            .unwrap_or_else(|e| panic!("Test failed on {output_name}: {e:#}"));
+        }
+        assert_golden_test_with_db(
+            &db,
+            last_batch,
+            Some(output_name.to_string()),
+            fa_processor_config(),
+            FungibleAssetProcessor::new,
             load_data,
-            db_url,
-            generate_flag,
-            output_path.to_string(),
-            Some(transaction_name.to_string()),
         )
-        .await
-        {
-            Ok(mut db_value) => {
-                if should_validate {
-                    let _ = validate_json(
-                        &mut db_value,
-                        test_context.get_request_start_version(),
-                        processor_name,
-                        output_path.to_string(),
-                        Some(transaction_name.to_string()),
-                    );
-                }
-            },
-            Err(e) => {
-                panic!("Test failed on {transaction_name} due to processor error: {e}");
-            },
-        }
+        .await;
     }
 
-    // Update the existing process_single_batch_txns function to use the new merged function
     async fn process_single_batch_txns(txns: &[&[u8]], test_case_name: Option<String>) {
-        let (generate_flag, custom_output_path) = get_test_config();
-        let output_path = custom_output_path.unwrap_or_else(|| DEFAULT_OUTPUT_FOLDER.to_string());
-
-        let mut db = PostgresTestDatabase::new();
-        db.setup().await.unwrap();
-
-        process_transactions(
-            &mut db,
+        assert_golden_test(
             txns,
-            &test_case_name.unwrap_or_default(),
-            generate_flag,
-            &output_path,
-            true, // Assuming we want to validate for fungible asset transactions
+            test_case_name,
+            fa_processor_config(),
+            FungibleAssetProcessor::new,
+            load_data,
         )
         .await;
     }
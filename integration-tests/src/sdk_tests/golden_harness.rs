@@ -0,0 +1,372 @@
+//! Runs a processor over fixture transactions against a temporary Postgres database and compares
+//! every table it loads with the table's golden file, at
+//! `<output>/<processor>/<case>/<table>.json`, or `<output>/<processor>/<version>/<table>.json`
+//! for unnamed cases.
+//!
+//! Tables are compared as canonical JSON, with sorted object keys and without the columns that
+//! change from run to run, and every table's diff is reported instead of only the first. Set
+//! `UPDATE_GOLDEN=1`, or pass `generate`, to rewrite the golden files from the processor's output
+//! instead:
+//! ```text
+//! UPDATE_GOLDEN=1 cargo test sdk_tests
+//! ```
+
+use crate::{
+    diff_test_helper::remove_transaction_timestamp,
+    sdk_tests::{run_processor_test, DEFAULT_OUTPUT_FOLDER},
+};
+use anyhow::{Context, Result};
+use assert_json_diff::{assert_json_matches_no_panic, CompareMode, Config};
+use cedra_indexer_processor_sdk::{
+    testing_framework::{
+        cli_parser::get_test_config,
+        database::{PostgresTestDatabase, TestDatabase},
+        sdk_test_context::SdkTestContext,
+    },
+    traits::processor_trait::ProcessorTrait,
+};
+use diesel::PgConnection;
+use processor::config::{
+    db_config::{DbConfig, PostgresConfig},
+    indexer_processor_config::IndexerProcessorConfig,
+    processor_config::ProcessorConfig,
+    processor_mode::{ProcessorMode, TestingConfig},
+    supervisor_config::SupervisorConfig,
+};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+};
+
+/// Set to rewrite the golden files instead of comparing against them
+pub const UPDATE_GOLDEN_ENV: &str = "UPDATE_GOLDEN";
+
+/// Config running `processor_config` in testing mode over the test context's transactions
+pub fn test_indexer_processor_config(
+    test_context: &SdkTestContext,
+    db_url: &str,
+    processor_config: ProcessorConfig,
+) -> IndexerProcessorConfig {
+    let transaction_stream_config = test_context.create_transaction_stream_config();
+    let postgres_config = PostgresConfig {
+        connection_string: db_url.to_string(),
+        db_pool_size: 100,
+        ..Default::default()
+    };
+    IndexerProcessorConfig {
+        processor_config,
+        transaction_stream_config: transaction_stream_config.clone(),
+        db_config: DbConfig::PostgresConfig(postgres_config),
+        processor_mode: ProcessorMode::Testing(TestingConfig {
+            override_starting_version: transaction_stream_config.starting_version.unwrap(),
+            ending_version: transaction_stream_config.request_ending_version,
+        }),
+        supervisor_config: SupervisorConfig::default(),
+        local_transactions_config: None,
+        stream_failover_config: None,
+        auth_token_source: None,
+        network: None,
+        table_name_overrides: HashMap::new(),
+        scheduled_jobs: HashMap::new(),
+        log_level: None,
+    }
+}
+
+/// `value` with the keys of every object sorted and without `inserted_at`
+pub fn canonical_json(value: &Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(items.iter().map(canonical_json).collect()),
+        Value::Object(object) => {
+            let mut entries: Vec<_> = object
+                .iter()
+                .filter(|(key, _)| key.as_str() != "inserted_at")
+                .collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), canonical_json(value)))
+                    .collect(),
+            )
+        },
+        value => value.clone(),
+    }
+}
+
+fn golden_dir(output_path: &str, processor_name: &str, case_name: &str) -> PathBuf {
+    Path::new(output_path).join(processor_name).join(case_name)
+}
+
+/// Diff of every loaded table that doesn't match its golden file in `golden_dir`, by table
+pub fn diff_tables(
+    db_values: &HashMap<String, Value>,
+    golden_dir: &Path,
+) -> BTreeMap<String, String> {
+    let mut diffs = BTreeMap::new();
+    for (table_name, db_value) in db_values {
+        let path = golden_dir.join(format!("{table_name}.json"));
+        let golden = match fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))
+            .and_then(|content| {
+                serde_json::from_str::<Value>(&content)
+                    .with_context(|| format!("Failed to parse {}", path.display()))
+            }) {
+            Ok(golden) => golden,
+            Err(e) => {
+                diffs.insert(table_name.clone(), format!("{e:#}"));
+                continue;
+            },
+        };
+        let mut actual = canonical_json(db_value);
+        let mut expected = canonical_json(&golden);
+        remove_transaction_timestamp(&mut actual);
+        remove_transaction_timestamp(&mut expected);
+        if let Err(diff) =
+            assert_json_matches_no_panic(&actual, &expected, Config::new(CompareMode::Strict))
+        {
+            diffs.insert(table_name.clone(), diff);
+        }
+    }
+    diffs
+}
+
+/// Writes every loaded table to its golden file in `golden_dir`, as canonical JSON
+pub fn write_golden_files(db_values: &HashMap<String, Value>, golden_dir: &Path) -> Result<()> {
+    fs::create_dir_all(golden_dir)
+        .with_context(|| format!("Failed to create {}", golden_dir.display()))?;
+    for (table_name, db_value) in db_values {
+        let path = golden_dir.join(format!("{table_name}.json"));
+        let mut content = serde_json::to_string_pretty(&canonical_json(db_value))?;
+        content.push('\n');
+        fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Runs the processor `new_processor` builds over `fixtures` and returns the diff of every table
+/// `load_data` loads against its golden file, empty if they all match or were just updated.
+pub async fn run_golden_test<P, Fut, F>(
+    fixtures: &[&[u8]],
+    case_name: Option<String>,
+    processor_config: ProcessorConfig,
+    new_processor: impl FnOnce(IndexerProcessorConfig) -> Fut,
+    load_data: F,
+) -> Result<BTreeMap<String, String>>
+where
+    P: ProcessorTrait,
+    Fut: Future<Output = Result<P>>,
+    F: Fn(&mut PgConnection) -> Result<HashMap<String, Value>> + Send + Sync + 'static,
+{
+    let mut db = PostgresTestDatabase::new();
+    db.setup().await?;
+    run_golden_test_with_db(
+        &db,
+        fixtures,
+        case_name,
+        processor_config,
+        new_processor,
+        load_data,
+    )
+    .await
+}
+
+/// Same as `run_golden_test`, on `db` as earlier runs left it, for cases spanning several runs
+pub async fn run_golden_test_with_db<P, Fut, F>(
+    db: &PostgresTestDatabase,
+    fixtures: &[&[u8]],
+    case_name: Option<String>,
+    processor_config: ProcessorConfig,
+    new_processor: impl FnOnce(IndexerProcessorConfig) -> Fut,
+    load_data: F,
+) -> Result<BTreeMap<String, String>>
+where
+    P: ProcessorTrait,
+    Fut: Future<Output = Result<P>>,
+    F: Fn(&mut PgConnection) -> Result<HashMap<String, Value>> + Send + Sync + 'static,
+{
+    let (generate_flag, custom_output_path) = get_test_config();
+    let update_golden = generate_flag || std::env::var_os(UPDATE_GOLDEN_ENV).is_some();
+    let output_path = custom_output_path.unwrap_or_else(|| DEFAULT_OUTPUT_FOLDER.to_string());
+    let processor_name = processor_config.name();
+
+    let (case_name, db_values) = run_fixtures(
+        db,
+        fixtures,
+        case_name,
+        &output_path,
+        processor_config,
+        new_processor,
+        load_data,
+    )
+    .await?;
+
+    let golden_dir = golden_dir(&output_path, processor_name, &case_name);
+    if update_golden {
+        write_golden_files(&db_values, &golden_dir)?;
+        return Ok(BTreeMap::new());
+    }
+    Ok(diff_tables(&db_values, &golden_dir))
+}
+
+/// Runs the processor `new_processor` builds over `fixtures` on `db` and returns the tables
+/// `load_data` loads, without comparing them. For the runs before the last one of a case.
+pub async fn run_fixtures_with_db<P, Fut, F>(
+    db: &PostgresTestDatabase,
+    fixtures: &[&[u8]],
+    processor_config: ProcessorConfig,
+    new_processor: impl FnOnce(IndexerProcessorConfig) -> Fut,
+    load_data: F,
+) -> Result<HashMap<String, Value>>
+where
+    P: ProcessorTrait,
+    Fut: Future<Output = Result<P>>,
+    F: Fn(&mut PgConnection) -> Result<HashMap<String, Value>> + Send + Sync + 'static,
+{
+    let (_, custom_output_path) = get_test_config();
+    let output_path = custom_output_path.unwrap_or_else(|| DEFAULT_OUTPUT_FOLDER.to_string());
+    let (_, db_values) = run_fixtures(
+        db,
+        fixtures,
+        None,
+        &output_path,
+        processor_config,
+        new_processor,
+        load_data,
+    )
+    .await?;
+    Ok(db_values)
+}
+
+/// The case name, defaulting to the first version, and the tables loaded after the run
+async fn run_fixtures<P, Fut, F>(
+    db: &PostgresTestDatabase,
+    fixtures: &[&[u8]],
+    case_name: Option<String>,
+    output_path: &str,
+    processor_config: ProcessorConfig,
+    new_processor: impl FnOnce(IndexerProcessorConfig) -> Fut,
+    load_data: F,
+) -> Result<(String, HashMap<String, Value>)>
+where
+    P: ProcessorTrait,
+    Fut: Future<Output = Result<P>>,
+    F: Fn(&mut PgConnection) -> Result<HashMap<String, Value>> + Send + Sync + 'static,
+{
+    let mut test_context = SdkTestContext::new(fixtures);
+    test_context
+        .init_mock_grpc()
+        .await
+        .context("Failed to initialize mock grpc")?;
+    let db_url = db.get_db_url();
+    let processor_name = processor_config.name();
+    let processor = new_processor(test_indexer_processor_config(
+        &test_context,
+        &db_url,
+        processor_config,
+    ))
+    .await
+    .with_context(|| format!("Failed to create {processor_name}"))?;
+    let case_name =
+        case_name.unwrap_or_else(|| test_context.get_request_start_version().to_string());
+    let db_values = run_processor_test(
+        &mut test_context,
+        processor,
+        load_data,
+        db_url,
+        false,
+        output_path.to_string(),
+        Some(case_name.clone()),
+    )
+    .await
+    .with_context(|| {
+        format!(
+            "Failed to run {processor_name} on transactions {:?}",
+            test_context.get_test_transaction_versions()
+        )
+    })?;
+    Ok((case_name, db_values))
+}
+
+/// Runs `run_golden_test` and fails with the diff of every table that doesn't match
+pub async fn assert_golden_test<P, Fut, F>(
+    fixtures: &[&[u8]],
+    case_name: Option<String>,
+    processor_config: ProcessorConfig,
+    new_processor: impl FnOnce(IndexerProcessorConfig) -> Fut,
+    load_data: F,
+) where
+    P: ProcessorTrait,
+    Fut: Future<Output = Result<P>>,
+    F: Fn(&mut PgConnection) -> Result<HashMap<String, Value>> + Send + Sync + 'static,
+{
+    assert_no_diffs(
+        run_golden_test(
+            fixtures,
+            case_name,
+            processor_config,
+            new_processor,
+            load_data,
+        )
+        .await,
+    );
+}
+
+/// Runs `run_golden_test_with_db` and fails with the diff of every table that doesn't match
+pub async fn assert_golden_test_with_db<P, Fut, F>(
+    db: &PostgresTestDatabase,
+    fixtures: &[&[u8]],
+    case_name: Option<String>,
+    processor_config: ProcessorConfig,
+    new_processor: impl FnOnce(IndexerProcessorConfig) -> Fut,
+    load_data: F,
+) where
+    P: ProcessorTrait,
+    Fut: Future<Output = Result<P>>,
+    F: Fn(&mut PgConnection) -> Result<HashMap<String, Value>> + Send + Sync + 'static,
+{
+    assert_no_diffs(
+        run_golden_test_with_db(
+            db,
+            fixtures,
+            case_name,
+            processor_config,
+            new_processor,
+            load_data,
+        )
+        .await,
+    );
+}
+
+fn assert_no_diffs(diffs: Result<BTreeMap<String, String>>) {
+    let diffs = diffs.unwrap_or_else(|e| panic!("{e:#}"));
+    if !diffs.is_empty() {
+        let report: Vec<_> = diffs
+            .iter()
+            .map(|(table_name, diff)| format!("{table_name}:\n{diff}"))
+            .collect();
+        panic!(
+            "{} tables differ from their golden files, rerun with {UPDATE_GOLDEN_ENV}=1 to \
+             update them\n\n{}",
+            diffs.len(),
+            report.join("\n\n")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonical_json() {
+        let value = json!([{"b": 1, "inserted_at": "2024-01-01", "a": {"d": 2, "c": 3}}]);
+        assert_eq!(
+            serde_json::to_string(&canonical_json(&value)).unwrap(),
+            r#"[{"a":{"c":3,"d":2},"b":1}]"#
+        );
+    }
+}
@@ -28,6 +28,8 @@ pub mod events_processor_tests;
 #[cfg(test)]
 pub mod fungible_asset_processor_tests;
 #[cfg(test)]
+pub mod golden_harness;
+#[cfg(test)]
 pub mod objects_processor_tests;
 #[cfg(test)]
 pub mod stake_processor_tests;
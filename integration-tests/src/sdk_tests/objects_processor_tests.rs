@@ -1,31 +1,21 @@
+use crate::sdk_tests::golden_harness::test_indexer_processor_config;
 use ahash::AHashMap;
 use cedra_indexer_processor_sdk::testing_framework::sdk_test_context::SdkTestContext;
 use processor::{
     config::{
-        db_config::{DbConfig, PostgresConfig},
         indexer_processor_config::IndexerProcessorConfig,
         processor_config::{DefaultProcessorConfig, ProcessorConfig},
-        processor_mode::{ProcessorMode, TestingConfig},
     },
     processors::objects::objects_processor::ObjectsProcessorConfig,
 };
 use std::collections::HashSet;
 
-pub fn setup_objects_processor_config(
-    test_context: &SdkTestContext,
-    db_url: &str,
-) -> (IndexerProcessorConfig, &'static str) {
-    let transaction_stream_config = test_context.create_transaction_stream_config();
-    let postgres_config = PostgresConfig {
-        connection_string: db_url.to_string(),
-        db_pool_size: 100,
-    };
-
-    let db_config = DbConfig::PostgresConfig(postgres_config);
+pub fn objects_processor_config() -> ProcessorConfig {
     let default_processor_config = DefaultProcessorConfig {
         per_table_chunk_sizes: AHashMap::new(),
         channel_size: 100,
         tables_to_write: HashSet::new(),
+        current_table_dedup_window: None,
     };
 
     let objects_processor_config = ObjectsProcessorConfig {
@@ -35,19 +25,17 @@ pub fn setup_objects_processor_config(
         query_retry_delay_ms: 100,
     };
 
-    let processor_config = ProcessorConfig::ObjectsProcessor(objects_processor_config);
+    ProcessorConfig::ObjectsProcessor(objects_processor_config)
+}
 
+pub fn setup_objects_processor_config(
+    test_context: &SdkTestContext,
+    db_url: &str,
+) -> (IndexerProcessorConfig, &'static str) {
+    let processor_config = objects_processor_config();
     let processor_name = processor_config.name();
     (
-        IndexerProcessorConfig {
-            processor_config,
-            transaction_stream_config: transaction_stream_config.clone(),
-            db_config,
-            processor_mode: ProcessorMode::Testing(TestingConfig {
-                override_starting_version: transaction_stream_config.starting_version.unwrap(),
-                ending_version: transaction_stream_config.request_ending_version,
-            }),
-        },
+        test_indexer_processor_config(test_context, db_url, processor_config),
         processor_name,
     )
 }
@@ -55,16 +43,12 @@ pub fn setup_objects_processor_config(
 #[allow(clippy::needless_return)]
 #[cfg(test)]
 mod sdk_objects_processor_tests {
-    use super::setup_objects_processor_config;
     use crate::{
         diff_test_helper::objects_processor::load_data,
         sdk_tests::{
-            run_processor_test, setup_test_environment, validate_json, DEFAULT_OUTPUT_FOLDER,
+            golden_harness::assert_golden_test, objects_processor_tests::objects_processor_config,
         },
     };
-    use cedra_indexer_processor_sdk::testing_framework::{
-        cli_parser::get_test_config, database::TestDatabase,
-    };
     use cedra_indexer_test_transactions::json_transactions::generated_transactions::{
         IMPORTED_MAINNET_TXNS_1806220919_OBJECT_UNTRANSFERABLE,
         IMPORTED_MAINNET_TXNS_578318306_OBJECTS_WRITE_RESOURCE,
@@ -102,46 +86,13 @@ mod sdk_objects_processor_tests {
 
     // Helper function to abstract out the transaction processing
     async fn process_object_txns(txns: &[&[u8]], test_case_name: Option<String>) {
-        let (generate_flag, custom_output_path) = get_test_config();
-        let output_path = custom_output_path.unwrap_or_else(|| DEFAULT_OUTPUT_FOLDER.to_string());
-
-        let (db, mut test_context) = setup_test_environment(txns).await;
-
-        let db_url = db.get_db_url();
-        let (indexer_processor_config, processor_name) =
-            setup_objects_processor_config(&test_context, &db_url);
-
-        let objects_processor = ObjectsProcessor::new(indexer_processor_config)
-            .await
-            .expect("Failed to create ObjectsProcessor");
-
-        match run_processor_test(
-            &mut test_context,
-            objects_processor,
+        assert_golden_test(
+            txns,
+            test_case_name,
+            objects_processor_config(),
+            ObjectsProcessor::new,
             load_data,
-            db_url,
-            generate_flag,
-            output_path.clone(),
-            test_case_name.clone(),
         )
-        .await
-        {
-            Ok(mut db_value) => {
-                let _ = validate_json(
-                    &mut db_value,
-                    test_context.get_request_start_version(),
-                    processor_name,
-                    output_path.clone(),
-                    test_case_name,
-                );
-            },
-            Err(e) => {
-                panic!(
-                    "Test failed on transactions {:?} due to processor error: {}",
-                    test_context.get_test_transaction_versions(),
-                    e
-                );
-            },
-        }
+        .await;
     }
 }
@@ -1,50 +1,39 @@
+use crate::sdk_tests::golden_harness::test_indexer_processor_config;
 use ahash::AHashMap;
 use cedra_indexer_processor_sdk::testing_framework::sdk_test_context::SdkTestContext;
 use processor::{
     config::{
-        db_config::{DbConfig, PostgresConfig},
         indexer_processor_config::IndexerProcessorConfig,
         processor_config::{DefaultProcessorConfig, ProcessorConfig},
-        processor_mode::{ProcessorMode, TestingConfig},
     },
     processors::stake::stake_processor::StakeProcessorConfig,
 };
 use std::collections::HashSet;
 
-pub fn setup_stake_processor_config(
-    test_context: &SdkTestContext,
-    db_url: &str,
-) -> (IndexerProcessorConfig, &'static str) {
-    let transaction_stream_config = test_context.create_transaction_stream_config(); // since this will be always 1, we can remove from the arg list
-    let postgres_config = PostgresConfig {
-        connection_string: db_url.to_string(),
-        db_pool_size: 100,
-    };
-
-    let db_config = DbConfig::PostgresConfig(postgres_config);
+pub fn stake_processor_config() -> ProcessorConfig {
     let default_processor_config = StakeProcessorConfig {
         default_config: DefaultProcessorConfig {
             per_table_chunk_sizes: AHashMap::new(),
             channel_size: 100,
             tables_to_write: HashSet::new(),
+            current_table_dedup_window: None,
         },
         // Avoid doing long lookups in tests
         query_retries: 1,
         query_retry_delay_ms: 100,
     };
 
-    let processor_config = ProcessorConfig::StakeProcessor(default_processor_config);
+    ProcessorConfig::StakeProcessor(default_processor_config)
+}
+
+pub fn setup_stake_processor_config(
+    test_context: &SdkTestContext,
+    db_url: &str,
+) -> (IndexerProcessorConfig, &'static str) {
+    let processor_config = stake_processor_config();
     let processor_name = processor_config.name();
     (
-        IndexerProcessorConfig {
-            processor_config,
-            transaction_stream_config: transaction_stream_config.clone(),
-            db_config,
-            processor_mode: ProcessorMode::Testing(TestingConfig {
-                override_starting_version: transaction_stream_config.starting_version.unwrap(),
-                ending_version: transaction_stream_config.request_ending_version,
-            }),
-        },
+        test_indexer_processor_config(test_context, db_url, processor_config),
         processor_name,
     )
 }
@@ -55,14 +44,9 @@ mod tests {
     use crate::{
         diff_test_helper::event_processor::load_data,
         sdk_tests::{
-            run_processor_test, setup_test_environment,
-            stake_processor_tests::setup_stake_processor_config, validate_json,
-            DEFAULT_OUTPUT_FOLDER,
+            golden_harness::assert_golden_test, stake_processor_tests::stake_processor_config,
         },
     };
-    use cedra_indexer_processor_sdk::testing_framework::{
-        cli_parser::get_test_config, database::TestDatabase,
-    };
     use cedra_indexer_test_transactions::json_transactions::generated_transactions::{
         IMPORTED_MAINNET_TXNS_118489_PROPOSAL_VOTE,
         IMPORTED_MAINNET_TXNS_121508544_STAKE_DISTRIBUTE,
@@ -165,46 +149,13 @@ mod tests {
 
     // Helper function to abstract out the single transaction processing
     async fn process_single_mainnet_event_txn(txn: &[u8], test_case_name: Option<String>) {
-        let (generate_flag, custom_output_path) = get_test_config();
-        let output_path = custom_output_path.unwrap_or_else(|| DEFAULT_OUTPUT_FOLDER.to_string());
-
-        let (db, mut test_context) = setup_test_environment(&[txn]).await;
-
-        let db_url = db.get_db_url();
-        let (indexer_processor_config, processor_name) =
-            setup_stake_processor_config(&test_context, &db_url);
-
-        let stake_processor = StakeProcessor::new(indexer_processor_config)
-            .await
-            .expect("Failed to create StakeProcessor");
-
-        match run_processor_test(
-            &mut test_context,
-            stake_processor,
+        assert_golden_test(
+            &[txn],
+            test_case_name,
+            stake_processor_config(),
+            StakeProcessor::new,
             load_data,
-            db_url,
-            generate_flag,
-            output_path.clone(),
-            test_case_name.clone(),
         )
-        .await
-        {
-            Ok(mut db_value) => {
-                let _ = validate_json(
-                    &mut db_value,
-                    test_context.get_request_start_version(),
-                    processor_name,
-                    output_path.clone(),
-                    test_case_name,
-                );
-            },
-            Err(e) => {
-                panic!(
-                    "Test failed on transactions {:?} due to processor error: {}",
-                    test_context.get_test_transaction_versions(),
-                    e
-                );
-            },
-        }
+        .await;
     }
 }
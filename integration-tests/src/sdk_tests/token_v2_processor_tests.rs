@@ -1,52 +1,42 @@
+use crate::sdk_tests::golden_harness::test_indexer_processor_config;
 use ahash::AHashMap;
 use cedra_indexer_processor_sdk::testing_framework::sdk_test_context::SdkTestContext;
 use processor::{
     config::{
-        db_config::{DbConfig, PostgresConfig},
         indexer_processor_config::IndexerProcessorConfig,
         processor_config::{DefaultProcessorConfig, ProcessorConfig},
-        processor_mode::{ProcessorMode, TestingConfig},
     },
     processors::token_v2::token_v2_processor::TokenV2ProcessorConfig,
 };
 use std::collections::HashSet;
 
-pub fn setup_token_v2_processor_config(
-    test_context: &SdkTestContext,
-    db_url: &str,
-) -> (IndexerProcessorConfig, &'static str) {
-    let transaction_stream_config = test_context.create_transaction_stream_config();
-    let postgres_config = PostgresConfig {
-        connection_string: db_url.to_string(),
-        db_pool_size: 100,
-    };
-
-    let db_config = DbConfig::PostgresConfig(postgres_config);
+pub fn token_v2_processor_config() -> ProcessorConfig {
     let default_processor_config = DefaultProcessorConfig {
         per_table_chunk_sizes: AHashMap::new(),
         channel_size: 100,
         tables_to_write: HashSet::new(),
+        current_table_dedup_window: None,
     };
     let token_v2_processor_config = TokenV2ProcessorConfig {
         default_config: default_processor_config,
         // Avoid doing long lookups in tests
         query_retries: 1,
         query_retry_delay_ms: 100,
+        price_oracle: None,
+        mint_modules: vec![],
     };
 
-    let processor_config = ProcessorConfig::TokenV2Processor(token_v2_processor_config);
+    ProcessorConfig::TokenV2Processor(token_v2_processor_config)
+}
 
+pub fn setup_token_v2_processor_config(
+    test_context: &SdkTestContext,
+    db_url: &str,
+) -> (IndexerProcessorConfig, &'static str) {
+    let processor_config = token_v2_processor_config();
     let processor_name = processor_config.name();
     (
-        IndexerProcessorConfig {
-            processor_config,
-            transaction_stream_config: transaction_stream_config.clone(),
-            db_config,
-            processor_mode: ProcessorMode::Testing(TestingConfig {
-                override_starting_version: transaction_stream_config.starting_version.unwrap(),
-                ending_version: transaction_stream_config.request_ending_version,
-            }),
-        },
+        test_indexer_processor_config(test_context, db_url, processor_config),
         processor_name,
     )
 }
@@ -54,16 +44,12 @@ pub fn setup_token_v2_processor_config(
 #[allow(clippy::needless_return)]
 #[cfg(test)]
 mod sdk_token_v2_processor_tests {
-    use super::setup_token_v2_processor_config;
     use crate::{
         diff_test_helper::token_v2_processor::load_data,
         sdk_tests::{
-            run_processor_test, setup_test_environment, validate_json, DEFAULT_OUTPUT_FOLDER,
+            golden_harness::assert_golden_test, token_v2_processor_tests::token_v2_processor_config,
         },
     };
-    use cedra_indexer_processor_sdk::testing_framework::{
-        cli_parser::get_test_config, database::TestDatabase,
-    };
     use cedra_indexer_test_transactions::json_transactions::generated_transactions::{
         IMPORTED_DEVNET_TXNS_19922017_TOKEN_V1_OFFER_CLAIM,
         IMPORTED_DEVNET_TXNS_78753831_TOKEN_V1_MINT_TRANSFER_WITH_V2_EVENTS,
@@ -388,46 +374,13 @@ mod sdk_token_v2_processor_tests {
 
     // Helper function to abstract out the transaction processing
     async fn process_single_transaction(txn: &[u8], test_case_name: Option<String>) {
-        let (generate_flag, custom_output_path) = get_test_config();
-        let output_path = custom_output_path.unwrap_or_else(|| DEFAULT_OUTPUT_FOLDER.to_string());
-
-        let (db, mut test_context) = setup_test_environment(&[txn]).await;
-
-        let db_url = db.get_db_url();
-        let (indexer_processor_config, processor_name) =
-            setup_token_v2_processor_config(&test_context, &db_url);
-
-        let token_v2_processor = TokenV2Processor::new(indexer_processor_config)
-            .await
-            .expect("Failed to create TokenV2Processor");
-
-        match run_processor_test(
-            &mut test_context,
-            token_v2_processor,
+        assert_golden_test(
+            &[txn],
+            test_case_name,
+            token_v2_processor_config(),
+            TokenV2Processor::new,
             load_data,
-            db_url,
-            generate_flag,
-            output_path.clone(),
-            test_case_name.clone(),
         )
-        .await
-        {
-            Ok(mut db_value) => {
-                let _ = validate_json(
-                    &mut db_value,
-                    test_context.get_request_start_version(),
-                    processor_name,
-                    output_path.clone(),
-                    test_case_name,
-                );
-            },
-            Err(e) => {
-                panic!(
-                    "Test failed on transactions {:?} due to processor error: {}",
-                    test_context.get_test_transaction_versions(),
-                    e
-                );
-            },
-        }
+        .await;
     }
 }
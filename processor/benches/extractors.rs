@@ -0,0 +1,149 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Extractor throughput on synthetic transactions, reported in rows per second.
+//!
+//! Run with `cargo bench -p processor --features bench --bench extractors`, and compare against a
+//! baseline with `-- --save-baseline main` and `-- --baseline main`. The token v2 and objects
+//! extractors look up objects and collections in Postgres, so they run against a freshly migrated
+//! test database, where every lookup misses, and are skipped if it can't be started.
+
+use cedra_indexer_processor_sdk::{
+    cedra_protos::transaction::v1::Transaction,
+    postgres::utils::database::{new_db_pool, run_migrations},
+    testing_framework::database::{PostgresTestDatabase, TestDatabase},
+    traits::Processable,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use processor::{
+    processors::{
+        account_transactions::account_transactions_extractor::AccountTransactionsExtractor,
+        default::{default_extractor::DefaultExtractor, models::table_item_types::TableItemTypes},
+        events::{event_attributes_model::EventAttributeRules, EventsExtractor},
+        fungible_asset::fungible_asset_extractor::FungibleAssetExtractor,
+        objects::objects_extractor::ObjectsExtractor,
+        token_v2::{
+            token_v2_extractor::TokenV2Extractor,
+            token_v2_models::v2_token_mints_detail::MintModules,
+        },
+        user_transaction::user_transaction_extractor::UserTransactionExtractor,
+    },
+    utils::{
+        bench::{run_extractor, RowCount, SyntheticTransactionGenerator, TransactionMix},
+        table_flags::TableFlags,
+    },
+    MIGRATIONS,
+};
+use std::collections::HashSet;
+use tokio::runtime::Runtime;
+
+const BATCH_SIZE: usize = 1000;
+const SEED: u64 = 42;
+
+fn mixes() -> [(&'static str, TransactionMix); 4] {
+    let only = |coin_transfers, nft_mints, table_writes| TransactionMix {
+        coin_transfers,
+        nft_mints,
+        table_writes,
+    };
+    [
+        ("default_mix", TransactionMix::default()),
+        ("coin_transfers", only(1, 0, 0)),
+        ("nft_mints", only(0, 1, 0)),
+        ("table_writes", only(0, 0, 1)),
+    ]
+}
+
+fn bench_extractor<E>(c: &mut Criterion, runtime: &Runtime, name: &str, mut extractor: E)
+where
+    E: Processable<Input = Vec<Transaction>>,
+    E::Output: RowCount,
+{
+    let mut group = c.benchmark_group(name);
+    for (mix_name, mix) in mixes() {
+        let batch = SyntheticTransactionGenerator::new(mix, SEED).next_batch(BATCH_SIZE);
+        let rows = runtime
+            .block_on(run_extractor(&mut extractor, batch.clone()))
+            .unwrap();
+        group.throughput(Throughput::Elements(rows as u64));
+        group.bench_function(mix_name, |b| {
+            b.iter_batched(
+                || batch.clone(),
+                |batch| {
+                    runtime
+                        .block_on(run_extractor(&mut extractor, batch))
+                        .unwrap()
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn extractors(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    bench_extractor(c, &runtime, "default_extractor", DefaultExtractor {
+        table_item_types: TableItemTypes::default(),
+    });
+    bench_extractor(
+        c,
+        &runtime,
+        "events_extractor",
+        EventsExtractor::new(EventAttributeRules::default(), None, None),
+    );
+    bench_extractor(
+        c,
+        &runtime,
+        "fungible_asset_extractor",
+        FungibleAssetExtractor::new(),
+    );
+    bench_extractor(
+        c,
+        &runtime,
+        "user_transaction_extractor",
+        UserTransactionExtractor {},
+    );
+    bench_extractor(
+        c,
+        &runtime,
+        "account_transactions_extractor",
+        AccountTransactionsExtractor {
+            address_labels: None,
+        },
+    );
+
+    let mut db = PostgresTestDatabase::new();
+    if let Err(e) = runtime.block_on(db.setup()) {
+        eprintln!("Skipping the token v2 and objects extractors, no test database: {e:?}");
+        return;
+    }
+    let db_url = db.get_db_url();
+    let conn_pool = runtime
+        .block_on(new_db_pool(&db_url, Some(10)))
+        .expect("Failed to create connection pool");
+    runtime.block_on(run_migrations(db_url, conn_pool.clone(), MIGRATIONS));
+    // Query once without delay, a lookup that misses would otherwise wait out the retries
+    bench_extractor(
+        c,
+        &runtime,
+        "token_v2_extractor",
+        TokenV2Extractor::new(
+            1,
+            0,
+            conn_pool.clone(),
+            None,
+            MintModules::new(&[]),
+            TableFlags::from_set(&HashSet::new()),
+        ),
+    );
+    bench_extractor(
+        c,
+        &runtime,
+        "objects_extractor",
+        ObjectsExtractor::new(1, 0, conn_pool),
+    );
+}
+
+criterion_group!(benches, extractors);
+criterion_main!(benches);
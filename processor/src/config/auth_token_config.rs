@@ -0,0 +1,57 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Where the transaction stream's auth token is read from instead of
+/// `transaction_stream_config.auth_token`, for tokens that are rotated while the processor runs.
+///
+/// The token is re-read every `refresh_interval_secs` and whenever the stream fails, e.g. because
+/// the token expired. When it changes, the stream reconnects with the new token at the version
+/// after the last batch received, without restarting the processor. Endpoints of
+/// `stream_failover_config` with their own `auth_token` keep it.
+///
+/// Exactly one of `file` and `env` is set. An environment variable can't change under a running
+/// process, so `env` only keeps the token out of the config file; rotated tokens need `file`.
+///
+/// Example:
+/// ```yaml
+/// auth_token_source:
+///   file: "/var/run/secrets/indexer/auth_token"
+///   refresh_interval_secs: 60
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthTokenSourceConfig {
+    /// File holding the token, e.g. a mounted secret. Surrounding whitespace is ignored.
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+    /// Environment variable holding the token
+    #[serde(default)]
+    pub env: Option<String>,
+    #[serde(default = "AuthTokenSourceConfig::default_refresh_interval_secs")]
+    pub refresh_interval_secs: u64,
+}
+
+impl AuthTokenSourceConfig {
+    pub const fn default_refresh_interval_secs() -> u64 {
+        60
+    }
+
+    pub fn read_token(&self) -> Result<String> {
+        let token = match (&self.file, &self.env) {
+            (Some(file), None) => std::fs::read_to_string(file)
+                .with_context(|| format!("Failed to read auth token from {}", file.display()))?,
+            (None, Some(env)) => std::env::var(env)
+                .with_context(|| format!("Failed to read auth token from ${env}"))?,
+            _ => bail!("auth_token_source needs exactly one of file and env"),
+        };
+        let token = token.trim();
+        if token.is_empty() {
+            bail!("auth_token_source holds an empty auth token");
+        }
+        Ok(token.to_string())
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Uploads parquet files to Azure Blob Storage instead of GCS. `bucket_name` is the container,
+/// and files are laid out under `bucket_root` the same way they are in a GCS bucket. ADLS Gen2
+/// accounts are written through their blob endpoint.
+///
+/// Example:
+/// ```yaml
+/// azure_blob_config:
+///   type: connection_string
+///   connection_string: "DefaultEndpointsProtocol=https;AccountName=cedraetl;AccountKey=KEY;EndpointSuffix=core.windows.net"
+/// ```
+/// or
+/// ```yaml
+/// azure_blob_config:
+///   type: managed_identity
+///   account_name: "cedraetl"
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, tag = "type", rename_all = "snake_case")]
+pub enum AzureBlobConfig {
+    /// A storage account connection string with either an `AccountKey` or a
+    /// `SharedAccessSignature`
+    ConnectionString { connection_string: String },
+    /// Tokens from the managed identity of the VM or container the processor runs on
+    ManagedIdentity {
+        account_name: String,
+        /// Client id of a user-assigned identity, the system-assigned one is used if unset
+        #[serde(default)]
+        client_id: Option<String>,
+        /// Defaults to `https://<account_name>.blob.core.windows.net`
+        #[serde(default)]
+        blob_endpoint: Option<Url>,
+    },
+}
@@ -1,4 +1,14 @@
+use crate::{
+    config::azure_blob_config::AzureBlobConfig,
+    utils::{
+        data_quality::DataQualityRule, file_sink::FileSinkConfig, json_caps::JsonColumnCap,
+        metadata_cache::MetadataCacheConfig, outbox::OutboxConfig, replica::ReplicaConfig,
+    },
+};
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use url::Url;
 
 /// This enum captures the configs for all the different db storages that are defined.
 /// The configs for each db storage should only contain configuration specific to that
@@ -42,12 +52,173 @@ pub struct PostgresConfig {
     // Size of the pool for writes/reads to the DB. Limits maximum number of queries in flight
     #[serde(default = "PostgresConfig::default_db_pool_size")]
     pub db_pool_size: u32,
+    // What to do when the live schema doesn't match the migrations once they've run
+    #[serde(default)]
+    pub schema_drift_check: SchemaDriftCheck,
+    // Tables to range partition by version, keyed by table name
+    #[serde(default)]
+    pub partitioned_tables: HashMap<String, PartitionConfig>,
+    // Publishes a signal for every saved checkpoint to a webhook or queue
+    #[serde(default)]
+    pub outbox: Option<OutboxConfig>,
+    // Cancels statements running longer than this, unset to keep the server's default
+    #[serde(default)]
+    pub statement_timeout_ms: Option<u64>,
+    // Fails statements waiting longer than this for a lock, unset to keep the server's default
+    #[serde(default)]
+    pub lock_timeout_ms: Option<u64>,
+    // Dual-writes every stored batch to a second database, best effort
+    #[serde(default)]
+    pub replica: Option<ReplicaConfig>,
+    // Assertions on the rows of each batch, violations are counted and optionally quarantined
+    #[serde(default)]
+    pub data_quality_rules: Vec<DataQualityRule>,
+    // Caches collection creators and objects looked up in the DB, shared by the processors in
+    // this process
+    #[serde(default)]
+    pub metadata_cache: Option<MetadataCacheConfig>,
+    // Authenticates with short-lived cloud IAM tokens instead of a password in connection_string
+    #[serde(default)]
+    pub iam_auth: Option<IamAuthConfig>,
+    // Replaces JSON values over a size cap with their hash, keeping their start unless redacted
+    #[serde(default)]
+    pub json_column_caps: Vec<JsonColumnCap>,
+    // Fills bytes_written in the table stats, at the cost of serializing every batch written
+    #[serde(default)]
+    pub measure_table_bytes: bool,
+    // Writes the committed rows to gzipped JSON lines files per table and version window too
+    #[serde(default)]
+    pub file_sink: Option<FileSinkConfig>,
+}
+
+/// Authenticates to Postgres with a short-lived IAM token in place of a password, which
+/// `connection_string` then leaves out. Its user is the database user of the IAM identity.
+/// Tokens are fetched when the pool opens a connection and refreshed before they expire, while
+/// connections that are already open stay authenticated.
+///
+/// Example:
+/// ```yaml
+/// iam_auth:
+///   type: aws
+///   region: us-east-1
+/// ```
+/// or
+/// ```yaml
+/// iam_auth:
+///   type: gcp
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, tag = "type", rename_all = "snake_case")]
+pub enum IamAuthConfig {
+    /// RDS auth tokens, signed with the credentials of AWS's default chain, e.g. environment
+    /// variables, a web identity token or the instance profile
+    Aws { region: String },
+    /// Cloud SQL IAM database authentication, with the access token of the service account of
+    /// the VM or GKE workload the processor runs on
+    Gcp,
+}
+
+/// Checks the live schema against `schema.rs` after migrations run, to catch columns, indexes
+/// and keys changed by hand that would otherwise break writes later on.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaDriftCheck {
+    Off,
+    /// Logs a drift report and keeps going
+    #[default]
+    Warn,
+    /// Logs a drift report and fails startup if there's any drift
+    Fail,
+}
+
+/// The serde default of every field, with an empty `connection_string`
+impl Default for PostgresConfig {
+    fn default() -> Self {
+        Self {
+            connection_string: String::new(),
+            db_pool_size: Self::default_db_pool_size(),
+            schema_drift_check: SchemaDriftCheck::default(),
+            partitioned_tables: HashMap::new(),
+            outbox: None,
+            statement_timeout_ms: None,
+            lock_timeout_ms: None,
+            replica: None,
+            data_quality_rules: vec![],
+            metadata_cache: None,
+            iam_auth: None,
+            json_column_caps: vec![],
+            measure_table_bytes: false,
+            file_sink: None,
+        }
+    }
 }
 
 impl PostgresConfig {
     pub const fn default_db_pool_size() -> u32 {
         150
     }
+
+    /// Connection string the processor's pool connects with. The timeouts are passed as startup
+    /// `options`, so they're set for the whole session of every connection the pool checks out.
+    /// PgBouncer in transaction mode rejects startup options, leave them unset behind it.
+    pub fn pool_connection_string(&self) -> anyhow::Result<String> {
+        let settings: Vec<String> = [
+            ("statement_timeout", self.statement_timeout_ms),
+            ("lock_timeout", self.lock_timeout_ms),
+        ]
+        .into_iter()
+        .filter_map(|(name, timeout_ms)| timeout_ms.map(|ms| format!("-c {name}={ms}")))
+        .collect();
+        if settings.is_empty() {
+            return Ok(self.connection_string.clone());
+        }
+        let mut url = Url::parse(&self.connection_string)
+            .context("Timeouts can only be set with a postgresql:// connection string")?;
+        let options: Vec<String> = url
+            .query_pairs()
+            .filter(|(key, _)| key == "options")
+            .map(|(_, value)| value.into_owned())
+            .chain(settings)
+            .collect();
+        let other_pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(key, _)| key != "options")
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(other_pairs)
+            .append_pair("options", &options.join(" "));
+        Ok(url.to_string())
+    }
+}
+
+/// Range partitioning of a version-keyed table. Partitions are created ahead of the versions
+/// being processed, so old ones can be detached or dropped and vacuum only works through the
+/// recent ones.
+///
+/// Only version ranges are supported: Postgres needs the partition key in every unique
+/// constraint, and the upserts conflict on primary keys that don't include timestamps.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PartitionConfig {
+    /// Version column of the primary key, e.g. `version` for `transactions`
+    #[serde(default = "PartitionConfig::default_column")]
+    pub column: String,
+    pub versions_per_partition: u64,
+    /// Partitions kept created past the latest processed version
+    #[serde(default = "PartitionConfig::default_partitions_ahead")]
+    pub partitions_ahead: u64,
+}
+
+impl PartitionConfig {
+    pub fn default_column() -> String {
+        "transaction_version".to_string()
+    }
+
+    pub const fn default_partitions_ahead() -> u64 {
+        2
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -60,8 +231,105 @@ pub struct ParquetConfig {
     // Optional Google application credentials for authentication
     #[serde(default)]
     pub google_application_credentials: Option<String>,
+    // Uploads to Azure Blob Storage instead of GCS if set, with `bucket_name` as the container
+    #[serde(default)]
+    pub azure_blob_config: Option<AzureBlobConfig>,
     #[serde(default)]
     pub bucket_name: String,
     #[serde(default)]
     pub bucket_root: String,
+    #[serde(default)]
+    pub path_layout: ParquetPathLayout,
+    // File format per table name, tables not listed are written as parquet
+    #[serde(default)]
+    pub table_output_formats: HashMap<String, TableOutputFormat>,
+    // Adds a `chain_id` column to every written row, so files of several networks can share a
+    // bucket. Needs the chain id to be checked, so it can't be used with local transactions.
+    #[serde(default)]
+    pub tag_rows_with_chain_id: bool,
+    // Holds back a table's files until the manifest covers every version before their window, so
+    // incremental loads never see a gap that a file uploaded later fills, e.g. by a backfill.
+    // Held files are kept in memory, and the processor status only moves past them once uploaded.
+    #[serde(default)]
+    pub hold_back_noncontiguous_files: bool,
+    // Creates only the tables processor progress is tracked in instead of running the migrations,
+    // for databases that hold nothing but parquet processor status. The fungible asset processor
+    // doesn't bootstrap its coin mappings from Postgres then.
+    #[serde(default)]
+    pub status_tables_only: bool,
+}
+
+/// How parquet files are laid out under `bucket_root`.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetPathLayout {
+    /// `<table>/<start_of_month_ms>/<upload_ms>_<counter>.parquet`
+    #[default]
+    Flat,
+    /// Hive-style partitions, so external tables can prune by table, date and version:
+    /// `table=<table>/dt=<YYYY-MM-DD>/ver_start=<start_version>/<upload_ms>_<counter>.parquet`.
+    /// The date is taken from the block timestamp of the first transaction in the file.
+    Hive,
+}
+
+/// File format a table's buffers are uploaded in.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableOutputFormat {
+    #[default]
+    Parquet,
+    /// Avro object container files, with the schema derived from the same model structs
+    Avro,
+}
+
+impl TableOutputFormat {
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            TableOutputFormat::Parquet => "parquet",
+            TableOutputFormat::Avro => "avro",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_matches_missing_fields() {
+        let config: PostgresConfig = serde_yaml::from_str("connection_string: \"\"").unwrap();
+        assert_eq!(
+            serde_json::to_value(&config).unwrap(),
+            serde_json::to_value(PostgresConfig::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pool_connection_string_sets_timeouts() {
+        let config: PostgresConfig = serde_yaml::from_str(
+            "connection_string: postgresql://postgres:@localhost:5432/example?sslmode=require
+statement_timeout_ms: 30000
+lock_timeout_ms: 5000",
+        )
+        .unwrap();
+        let url = Url::parse(&config.pool_connection_string().unwrap()).unwrap();
+        let pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(pairs, vec![
+            ("sslmode".to_string(), "require".to_string()),
+            (
+                "options".to_string(),
+                "-c statement_timeout=30000 -c lock_timeout=5000".to_string()
+            ),
+        ]);
+
+        let config = PostgresConfig {
+            statement_timeout_ms: None,
+            lock_timeout_ms: None,
+            ..config
+        };
+        assert_eq!(
+            config.pool_connection_string().unwrap(),
+            config.connection_string
+        );
+    }
 }
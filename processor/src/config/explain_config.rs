@@ -0,0 +1,470 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Resolves a processor config the way the processor would at startup, without connecting to
+//! anything, so misconfigured deployments are caught before they're rolled out.
+
+use crate::{
+    config::{
+        db_config::DbConfig,
+        indexer_processor_config::IndexerProcessorConfig,
+        processor_config::{ProcessorConfig, ProcessorName},
+        processor_mode::ProcessorMode,
+    },
+    db::partitioning::validate_partition_config,
+    utils::{
+        data_quality::validate_data_quality_rules,
+        json_caps::validate_json_column_caps,
+        logging::log_filter,
+        scheduler::validate_scheduled_jobs,
+        table_flags::{TableFlags, TableSelection},
+        transaction_source::validate_local_transactions,
+    },
+};
+use anyhow::{bail, Context, Result};
+use std::{collections::HashSet, fmt::Write};
+
+/// Values that are replaced before the config is printed
+const REDACTED_KEYS: [&str; 3] = [
+    "auth_token",
+    "connection_string",
+    "google_application_credentials",
+];
+
+/// Tables a Postgres processor can write, as the flags `tables_to_write` selects them by
+pub(crate) fn processor_table_flags(processor: ProcessorName) -> TableFlags {
+    match processor {
+        ProcessorName::AccountRestorationProcessor => {
+            TableFlags::AUTH_KEY_ACCOUNT_ADDRESSES
+                | TableFlags::PUBLIC_KEY_AUTH_KEYS
+                | TableFlags::AUTH_KEY_SCHEMES
+        },
+        ProcessorName::AccountTransactionsProcessor => TableFlags::ACCOUNT_TRANSACTIONS,
+        ProcessorName::AnsProcessor => {
+            TableFlags::CURRENT_ANS_LOOKUP_V2
+                | TableFlags::CURRENT_ANS_PRIMARY_NAME_V2
+                | TableFlags::ANS_REVENUE_EVENTS
+        },
+        ProcessorName::DefaultProcessor => {
+            TableFlags::BLOCK_METADATA_TRANSACTIONS
+                | TableFlags::TABLE_ITEMS
+                | TableFlags::CURRENT_TABLE_ITEMS
+                | TableFlags::TABLE_METADATA
+                | TableFlags::MOVE_MODULES
+                | TableFlags::WRITE_SET_CHANGE_STATS
+        },
+        ProcessorName::FungibleAssetProcessor => {
+            TableFlags::FUNGIBLE_ASSET_ACTIVITIES
+                | TableFlags::FUNGIBLE_ASSET_METADATA
+                | TableFlags::CURRENT_FUNGIBLE_ASSET_BALANCES
+                | TableFlags::FUNGIBLE_ASSET_TO_COIN_MAPPINGS
+                | TableFlags::COIN_INFO_MUTATIONS
+                | TableFlags::COIN_HOLDER_COUNTS
+                | TableFlags::CURRENT_COIN_BALANCES
+                | TableFlags::COIN_STORE_FREEZE_EVENTS
+        },
+        ProcessorName::UserTransactionProcessor => {
+            TableFlags::USER_TRANSACTIONS
+                | TableFlags::SIGNATURES
+                | TableFlags::TRANSACTION_SIGNERS
+                | TableFlags::SENDER_SEQUENCE_NUMBER_GAPS
+        },
+        ProcessorName::StakeProcessor => {
+            TableFlags::DELEGATED_STAKING_ACTIVITIES
+                | TableFlags::DELEGATED_STAKING_POOLS
+                | TableFlags::DELEGATED_STAKING_POOL_BALANCES
+                | TableFlags::CURRENT_DELEGATED_STAKING_POOL_BALANCES
+                | TableFlags::DELEGATOR_BALANCES
+                | TableFlags::CURRENT_DELEGATOR_BALANCES
+                | TableFlags::CURRENT_DELEGATED_VOTER
+                | TableFlags::CURRENT_STAKING_POOL_VOTER
+                | TableFlags::PROPOSAL_VOTES
+                | TableFlags::EPOCH_END_DELEGATOR_BALANCES
+                | TableFlags::POOL_COMMISSION_HISTORY
+                | TableFlags::POOL_OPERATOR_HISTORY
+                | TableFlags::GOVERNANCE_PARTICIPATION
+        },
+        ProcessorName::TokenV2Processor => {
+            TableFlags::CURRENT_COLLECTIONS_V2
+                | TableFlags::CURRENT_TOKEN_DATAS_V2
+                | TableFlags::CURRENT_TOKEN_OWNERSHIPS_V2
+                | TableFlags::TOKEN_ACTIVITIES_V2
+                | TableFlags::CURRENT_TOKEN_ROYALTY_V1
+                | TableFlags::CURRENT_TOKEN_PENDING_CLAIMS
+                | TableFlags::COLLECTION_VOLUME_LEADERBOARD
+                | TableFlags::TOKEN_MINTS_DETAIL
+                | TableFlags::NFT_TRANSFERS
+                | TableFlags::TOKEN_PROPERTY_VALUES
+                | TableFlags::CURRENT_COLLECTION_STATS_V2
+        },
+        ProcessorName::ObjectsProcessor => {
+            TableFlags::OBJECTS
+                | TableFlags::CURRENT_OBJECTS
+                | TableFlags::DELETED_OBJECTS
+                | TableFlags::CURRENT_OBJECT_COUNTS_BY_OWNER
+        },
+        ProcessorName::GasFeeProcessor => TableFlags::GAS_FEES,
+        ProcessorName::RawTransactionsProcessor => TableFlags::RAW_TRANSACTIONS,
+        ProcessorName::ValidatorTransactionProcessor => {
+            TableFlags::VALIDATOR_TRANSACTIONS | TableFlags::VALIDATOR_TRANSACTION_EVENTS
+        },
+        ProcessorName::ResourceWatcherProcessor => TableFlags::WATCHED_RESOURCES,
+        ProcessorName::TreasuryFlowsProcessor => TableFlags::TREASURY_INFLOWS,
+        _ => TableFlags::empty(),
+    }
+}
+
+/// Steps between the transaction source and the version tracker, in order
+fn processing_steps(processor: ProcessorName) -> Vec<&'static str> {
+    match processor {
+        ProcessorName::AccountRestorationProcessor => {
+            vec!["AccountRestorationExtractor", "AccountRestorationStorer"]
+        },
+        ProcessorName::AccountTransactionsProcessor => {
+            vec!["AccountTransactionsExtractor", "AccountTransactionsStorer"]
+        },
+        ProcessorName::AnsProcessor => vec!["AnsExtractor", "AnsStorer"],
+        ProcessorName::DefaultProcessor => vec![
+            "DefaultExtractor",
+            "PartitionStep",
+            "DefaultStorer",
+            "PendingWritesStep",
+        ],
+        ProcessorName::EventsProcessor => vec!["EventsExtractor", "PartitionStep", "EventsStorer"],
+        ProcessorName::FungibleAssetProcessor => vec![
+            "FungibleAssetExtractor",
+            "PartitionStep",
+            "FungibleAssetStorer",
+        ],
+        ProcessorName::UserTransactionProcessor => vec![
+            "UserTransactionExtractor",
+            "PartitionStep",
+            "UserTransactionStorer",
+        ],
+        ProcessorName::StakeProcessor => vec!["StakeExtractor", "StakeStorer"],
+        ProcessorName::TokenV2Processor => vec![
+            "TokenV2Extractor",
+            "PartitionStep",
+            "TokenV2Storer",
+            "PendingWritesStep",
+        ],
+        ProcessorName::ObjectsProcessor => vec!["ObjectsExtractor", "ObjectsStorer"],
+        ProcessorName::MonitoringProcessor => vec![],
+        ProcessorName::GasFeeProcessor => vec!["gas_fee_extractor", "gas_fee_storer"],
+        ProcessorName::RawTransactionsProcessor => {
+            vec!["raw_transactions_extractor", "raw_transactions_storer"]
+        },
+        ProcessorName::ValidatorTransactionProcessor => vec![
+            "validator_transaction_extractor",
+            "validator_transaction_storer",
+        ],
+        ProcessorName::ResourceWatcherProcessor => {
+            vec!["resource_watcher_extractor", "resource_watcher_storer"]
+        },
+        ProcessorName::TreasuryFlowsProcessor => {
+            vec!["treasury_flows_extractor", "treasury_flows_storer"]
+        },
+        ProcessorName::ParquetDefaultProcessor => {
+            vec!["ParquetDefaultExtractor", "ParquetBufferStep"]
+        },
+        ProcessorName::ParquetObjectsProcessor => {
+            vec!["ParquetObjectsExtractor", "ParquetBufferStep"]
+        },
+        ProcessorName::ParquetUserTransactionProcessor => {
+            vec!["ParquetUserTransactionExtractor", "ParquetBufferStep"]
+        },
+        ProcessorName::ParquetEventsProcessor => {
+            vec!["ParquetEventsExtractor", "ParquetBufferStep"]
+        },
+        ProcessorName::ParquetAnsProcessor => vec!["ParquetAnsExtractor", "ParquetBufferStep"],
+        ProcessorName::ParquetFungibleAssetProcessor => {
+            vec!["ParquetFungibleAssetExtractor", "ParquetBufferStep"]
+        },
+        ProcessorName::ParquetTransactionMetadataProcessor => {
+            vec!["ParquetTransactionMetadataExtractor", "ParquetBufferStep"]
+        },
+        ProcessorName::ParquetAccountTransactionsProcessor => {
+            vec!["ParquetAccountTransactionsExtractor", "ParquetBufferStep"]
+        },
+        ProcessorName::ParquetTokenV2Processor => {
+            vec!["ParquetTokenV2Extractor", "ParquetBufferStep"]
+        },
+        ProcessorName::ParquetStakeProcessor => {
+            vec!["ParquetStakeExtractor", "ParquetBufferStep"]
+        },
+    }
+}
+
+pub(crate) fn is_parquet_processor(processor: ProcessorName) -> bool {
+    let name: &'static str = processor.into();
+    name.starts_with("parquet_")
+}
+
+/// The full step graph, from the transaction source to the version tracker
+pub fn step_graph(config: &IndexerProcessorConfig) -> Vec<&'static str> {
+    let processor = ProcessorName::from(&config.processor_config);
+    let mut steps = vec![
+        if config.local_transactions_config.is_some() {
+            "LocalTransactionStreamStep"
+        } else {
+            "TransactionStreamStep"
+        },
+    ];
+    steps.extend(processing_steps(processor));
+    steps.push(
+        if is_parquet_processor(processor) {
+            "ParquetVersionTrackerStep"
+        } else {
+            "VersionTrackerStep"
+        },
+    );
+    steps
+}
+
+/// Tables the processor writes with this config, following `tables_to_write` or `backfill_table`
+pub fn tables_to_be_written(config: &IndexerProcessorConfig) -> Result<Vec<String>> {
+    let processor = ProcessorName::from(&config.processor_config);
+    let mut tables: Vec<String> = match processor {
+        ProcessorName::EventsProcessor => {
+            let mut tables = vec!["events".to_string(), "event_attributes".to_string()];
+            if let ProcessorConfig::EventsProcessor(events_config) = &config.processor_config {
+                if events_config.compaction.is_some() {
+                    tables.push("events_compacted".to_string());
+                }
+            }
+            tables
+        },
+        _ if is_parquet_processor(processor) => config
+            .processor_config
+            .get_processor_status_table_names()?
+            .into_iter()
+            .map(|table_name| match table_name.split_once('.') {
+                Some((_, table_name)) => table_name.to_string(),
+                None => table_name,
+            })
+            .collect(),
+        _ => {
+            let tables_to_write = config
+                .processor_config
+                .tables_to_write()
+                .map(TableFlags::from_set)
+                .unwrap_or_else(TableFlags::empty);
+            processor_table_flags(processor)
+                .iter_names()
+                .filter(|(_, flag)| {
+                    tables_to_write.contains(*flag)
+                        || (tables_to_write.is_empty() && !TableFlags::OPT_IN.intersects(*flag))
+                })
+                .map(|(name, _)| name.to_lowercase())
+                .collect()
+        },
+    };
+    tables.sort();
+    Ok(tables)
+}
+
+/// Checks the options that can only be told apart once the whole config is known, which would
+/// otherwise only fail once the processor is running.
+pub fn validate_config(config: &IndexerProcessorConfig) -> Result<()> {
+    let processor = ProcessorName::from(&config.processor_config);
+    let processor_name = config.processor_config.name();
+    match (&config.db_config, is_parquet_processor(processor)) {
+        (DbConfig::PostgresConfig(_), true) => {
+            bail!("{processor_name} writes parquet files and needs a parquet_config db_config")
+        },
+        (DbConfig::ParquetConfig(_), false) => {
+            bail!("{processor_name} writes to Postgres and needs a postgres_config db_config")
+        },
+        (DbConfig::PostgresConfig(postgres_config), false) => {
+            for (table_name, partition_config) in &postgres_config.partitioned_tables {
+                validate_partition_config(table_name, partition_config)?;
+            }
+            validate_data_quality_rules(&postgres_config.data_quality_rules)?;
+            validate_json_column_caps(&postgres_config.json_column_caps)?;
+            if let Some(file_sink) = &postgres_config.file_sink {
+                file_sink.validate()?;
+                // Shards checkpoint separately, so none of them knows when a window is done
+                if matches!(config.processor_mode, ProcessorMode::Sharded(_)) {
+                    bail!("file_sink can't be used in sharded mode");
+                }
+            }
+        },
+        (DbConfig::ParquetConfig(parquet_config), true) => {
+            if parquet_config.tag_rows_with_chain_id && config.local_transactions_config.is_some() {
+                bail!("tag_rows_with_chain_id can't be used with local transactions");
+            }
+            let tables: HashSet<String> = tables_to_be_written(config)?.into_iter().collect();
+            for table_name in parquet_config.table_output_formats.keys() {
+                if !tables.contains(table_name) {
+                    bail!("table_output_formats names {table_name}, which {processor_name} doesn't write");
+                }
+            }
+            if let Some(parquet_processor_config) = config.processor_config.parquet_default_config()
+            {
+                let writer_properties = &parquet_processor_config.writer_properties;
+                for table_name in writer_properties.tables.keys() {
+                    if !tables.contains(table_name) {
+                        bail!(
+                            "writer_properties names {table_name}, which {processor_name} doesn't \
+                             write"
+                        );
+                    }
+                }
+                for table_name in &tables {
+                    writer_properties
+                        .for_table(table_name)
+                        .with_context(|| format!("Invalid writer_properties of {table_name}"))?;
+                }
+            }
+        },
+    }
+
+    validate_scheduled_jobs(&config.scheduled_jobs)?;
+    if let Some(log_level) = &config.log_level {
+        log_filter(Some(log_level))?;
+    }
+    validate_local_transactions(config)?;
+
+    if let Some(tables_to_write) = config.processor_config.tables_to_write() {
+        let processor_tables = processor_table_flags(processor);
+        for table_name in TableSelection::parse(tables_to_write).named_tables() {
+            match TableFlags::from_name(table_name) {
+                Some(flag) if processor_tables.contains(flag) => {},
+                Some(_) => bail!(
+                    "tables_to_write names {table_name}, which {processor_name} doesn't write"
+                ),
+                None => bail!(
+                    "tables_to_write names {table_name}, which isn't a table, tables are named \
+                     like {}",
+                    processor_tables
+                        .iter_names()
+                        .map(|(name, _)| name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }
+        }
+    }
+
+    if let ProcessorMode::Default(bootstrap_config) = &config.processor_mode {
+        if bootstrap_config.start_at.is_some() && bootstrap_config.start_at_timestamp.is_some() {
+            bail!("Only one of start_at and start_at_timestamp can be set");
+        }
+        if (bootstrap_config.start_at.is_some() || bootstrap_config.start_at_timestamp.is_some())
+            && config.local_transactions_config.is_some()
+        {
+            bail!("start_at and start_at_timestamp can't be used with local transactions");
+        }
+    }
+    Ok(())
+}
+
+/// Validates the config, then describes it: the config with defaults applied and secrets
+/// redacted, the step graph and the tables written.
+pub fn explain_config(config: &IndexerProcessorConfig) -> Result<String> {
+    validate_config(config)?;
+
+    let mut resolved = serde_yaml::to_value(config)?;
+    redact(&mut resolved);
+    let mut explanation = String::new();
+    writeln!(explanation, "# Effective configuration")?;
+    explanation.push_str(&serde_yaml::to_string(&resolved)?);
+    writeln!(explanation, "\n# Step graph")?;
+    writeln!(explanation, "{}", step_graph(config).join(" -> "))?;
+    writeln!(explanation, "\n# Tables written")?;
+    let tables = tables_to_be_written(config)?;
+    if tables.is_empty() {
+        writeln!(explanation, "(none)")?;
+    }
+    for table_name in tables {
+        writeln!(explanation, "{table_name}")?;
+    }
+    Ok(explanation)
+}
+
+fn redact(value: &mut serde_yaml::Value) {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => {
+            for (key, value) in mapping.iter_mut() {
+                if key.as_str().is_some_and(|key| REDACTED_KEYS.contains(&key)) && !value.is_null()
+                {
+                    *value = serde_yaml::Value::String("<redacted>".to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        },
+        serde_yaml::Value::Sequence(sequence) => sequence.iter_mut().for_each(redact),
+        _ => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(processor_config: &str, db_config: &str) -> IndexerProcessorConfig {
+        serde_yaml::from_str(&format!(
+            "processor_config:
+  {processor_config}
+transaction_stream_config:
+  indexer_grpc_data_service_address: \"https://grpc.mainnet.cedralabs.com:443\"
+  auth_token: \"AUTH_TOKEN\"
+  request_name_header: \"explain\"
+db_config:
+  {db_config}
+processor_mode:
+  type: default
+"
+        ))
+        .unwrap()
+    }
+
+    const POSTGRES: &str = "type: postgres_config
+  connection_string: postgresql://postgres:@localhost:5432/example";
+    const PARQUET: &str = "type: parquet_config
+  connection_string: postgresql://postgres:@localhost:5432/example";
+
+    #[test]
+    fn test_db_config_must_match_processor() {
+        assert!(validate_config(&config("type: events_processor", POSTGRES)).is_ok());
+        assert!(validate_config(&config("type: events_processor", PARQUET)).is_err());
+        assert!(validate_config(&config("type: parquet_events_processor", POSTGRES)).is_err());
+    }
+
+    #[test]
+    fn test_tables_to_write_must_name_processor_tables() {
+        let tables_to_write = |tables: &str| {
+            config(
+                &format!("type: stake_processor\n  tables_to_write: [{tables}]"),
+                POSTGRES,
+            )
+        };
+        assert!(validate_config(&tables_to_write("PROPOSAL_VOTES")).is_ok());
+        assert!(validate_config(&tables_to_write("proposal_votes")).is_err());
+        assert!(validate_config(&tables_to_write("EVENTS")).is_err());
+        assert!(validate_config(&tables_to_write("\"*\", \"!PROPOSAL_VOTES\"")).is_ok());
+        assert!(validate_config(&tables_to_write("\"!EVENTS\"")).is_err());
+    }
+
+    #[test]
+    fn test_explain_config() {
+        let config = config("type: token_v2_processor", POSTGRES);
+        assert_eq!(step_graph(&config), vec![
+            "TransactionStreamStep",
+            "TokenV2Extractor",
+            "PartitionStep",
+            "TokenV2Storer",
+            "PendingWritesStep",
+            "VersionTrackerStep",
+        ]);
+        let tables = tables_to_be_written(&config).unwrap();
+        // Opt-in tables aren't written without being listed
+        assert!(tables.contains(&"token_activities_v2".to_string()));
+        assert!(!tables.contains(&"collection_volume_leaderboard".to_string()));
+
+        let explanation = explain_config(&config).unwrap();
+        assert!(!explanation.contains("AUTH_TOKEN"));
+        assert!(!explanation.contains("postgresql://"));
+    }
+}
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{
-    db_config::DbConfig, processor_config::ProcessorConfig, processor_mode::ProcessorMode,
+    auth_token_config::AuthTokenSourceConfig, db_config::DbConfig,
+    local_transactions_config::LocalTransactionsConfig, processor_config::ProcessorConfig,
+    processor_mode::ProcessorMode, stream_failover_config::StreamFailoverConfig,
+    supervisor_config::SupervisorConfig,
 };
 use crate::{
     parquet_processors::{
@@ -25,9 +28,28 @@ use crate::{
         fungible_asset::fungible_asset_processor::FungibleAssetProcessor,
         gas_fees::gas_fee_processor::GasFeeProcessor,
         monitoring::monitoring_processor::MonitoringProcessor,
-        objects::objects_processor::ObjectsProcessor, stake::stake_processor::StakeProcessor,
-        token_v2::token_v2_processor::TokenV2Processor,
+        objects::objects_processor::ObjectsProcessor,
+        raw_transactions::raw_transactions_processor::RawTransactionsProcessor,
+        resource_watcher::resource_watcher_processor::ResourceWatcherProcessor,
+        stake::stake_processor::StakeProcessor, token_v2::token_v2_processor::TokenV2Processor,
+        treasury_flows::treasury_flows_processor::TreasuryFlowsProcessor,
         user_transaction::user_transaction_processor::UserTransactionProcessor,
+        validator_transactions::validator_transaction_processor::ValidatorTransactionProcessor,
+    },
+    utils::{
+        config_reload::{reloaded_transaction_stream_config, RESTART_REQUESTED},
+        data_quality::set_data_quality_rules,
+        file_sink::start_file_sink,
+        json_caps::set_json_column_caps,
+        metadata_cache::set_metadata_cache_config,
+        outbox::spawn_outbox_relay,
+        ranged_run::{finish_ranged_run, start_ranged_attempt},
+        replica::spawn_replica_writer,
+        scheduler::{set_scheduled_job_configs, ScheduledJobConfig},
+        sharded_run::run_sharded,
+        supervisor::supervise,
+        table_names::TableNames,
+        table_stats::set_measure_table_bytes,
     },
 };
 use anyhow::Result;
@@ -36,6 +58,7 @@ use cedra_indexer_processor_sdk::{
     traits::processor_trait::ProcessorTrait,
 };
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Instant};
 
 pub const QUERY_DEFAULT_RETRIES: u32 = 5;
 pub const QUERY_DEFAULT_RETRY_DELAY_MS: u64 = 500;
@@ -47,11 +70,47 @@ pub struct IndexerProcessorConfig {
     pub transaction_stream_config: TransactionStreamConfig,
     pub db_config: DbConfig,
     pub processor_mode: ProcessorMode,
+    #[serde(default)]
+    pub supervisor_config: SupervisorConfig,
+    /// Reads transactions from local files instead of `transaction_stream_config`'s gRPC stream
+    #[serde(default)]
+    pub local_transactions_config: Option<LocalTransactionsConfig>,
+    /// Endpoints to fail over to when `transaction_stream_config`'s gRPC stream errors
+    #[serde(default)]
+    pub stream_failover_config: Option<StreamFailoverConfig>,
+    /// Reads the gRPC auth token from a file or environment variable, re-reading it as it rotates
+    #[serde(default)]
+    pub auth_token_source: Option<AuthTokenSourceConfig>,
+    /// Name of the network, e.g. `mainnet`, for parquet output merged across networks downstream
+    #[serde(default)]
+    pub network: Option<String>,
+    /// Output tables written under another name, e.g. `events: chain_events_v2`, applied to
+    /// Postgres inserts and parquet file paths. A `schema.table` name writes to another schema.
+    /// The renamed tables have to exist with the same columns.
+    #[serde(default)]
+    pub table_name_overrides: HashMap<String, String>,
+    /// Interval overrides of periodic maintenance jobs, by job name, or `enabled: false` to turn
+    /// a job off
+    #[serde(default)]
+    pub scheduled_jobs: HashMap<String, ScheduledJobConfig>,
+    /// Log filter directives, e.g. `info,processor=debug`, used instead of `RUST_LOG`. Applied
+    /// again when the config is reloaded.
+    #[serde(default)]
+    pub log_level: Option<String>,
 }
 
-#[async_trait::async_trait]
-impl RunnableConfig for IndexerProcessorConfig {
-    async fn run(&self) -> Result<()> {
+impl IndexerProcessorConfig {
+    /// The `table_name_overrides` to write the processor's tables with
+    pub fn table_names(&self) -> TableNames {
+        TableNames::new(self.table_name_overrides.clone())
+    }
+
+    /// Builds the configured processor and runs its step graph once.
+    pub(crate) async fn run_once(mut self) -> Result<()> {
+        // Restarts pick up a rotated token before the chain id check connects
+        if let Some(auth_token_source) = &self.auth_token_source {
+            self.transaction_stream_config.auth_token = auth_token_source.read_token()?;
+        }
         match self.processor_config {
             ProcessorConfig::AccountTransactionsProcessor(_) => {
                 let acc_txns_processor = AccountTransactionsProcessor::new(self.clone()).await?;
@@ -101,6 +160,25 @@ impl RunnableConfig for IndexerProcessorConfig {
                 let gas_fee_processor = GasFeeProcessor::new(self.clone()).await?;
                 gas_fee_processor.run_processor().await
             },
+            ProcessorConfig::RawTransactionsProcessor(_) => {
+                let raw_transactions_processor =
+                    RawTransactionsProcessor::new(self.clone()).await?;
+                raw_transactions_processor.run_processor().await
+            },
+            ProcessorConfig::ValidatorTransactionProcessor(_) => {
+                let validator_transaction_processor =
+                    ValidatorTransactionProcessor::new(self.clone()).await?;
+                validator_transaction_processor.run_processor().await
+            },
+            ProcessorConfig::ResourceWatcherProcessor(_) => {
+                let resource_watcher_processor =
+                    ResourceWatcherProcessor::new(self.clone()).await?;
+                resource_watcher_processor.run_processor().await
+            },
+            ProcessorConfig::TreasuryFlowsProcessor(_) => {
+                let treasury_flows_processor = TreasuryFlowsProcessor::new(self.clone()).await?;
+                treasury_flows_processor.run_processor().await
+            },
             ProcessorConfig::ParquetDefaultProcessor(_) => {
                 let parquet_default_processor = ParquetDefaultProcessor::new(self.clone()).await?;
                 parquet_default_processor.run_processor().await
@@ -147,6 +225,73 @@ impl RunnableConfig for IndexerProcessorConfig {
             },
         }
     }
+}
+
+#[async_trait::async_trait]
+impl RunnableConfig for IndexerProcessorConfig {
+    async fn run(&self) -> Result<()> {
+        set_scheduled_job_configs(&self.scheduled_jobs)?;
+        if let DbConfig::PostgresConfig(ref postgres_config) = self.db_config {
+            set_data_quality_rules(&postgres_config.data_quality_rules, self.table_names())?;
+            set_metadata_cache_config(postgres_config.metadata_cache.as_ref());
+            set_json_column_caps(&postgres_config.json_column_caps)?;
+            set_measure_table_bytes(postgres_config.measure_table_bytes);
+        }
+        // Only backfills, tests, ranged runs and local transactions have an end to finish at
+        let restart_on_completion = matches!(self.processor_mode, ProcessorMode::Default(_))
+            && self.local_transactions_config.is_none();
+        // Outbox entries are only written by the live processor. The relay outlives restarts,
+        // so entries keep being delivered while the processor backs off.
+        if let DbConfig::PostgresConfig(ref postgres_config) = self.db_config {
+            if let (ProcessorMode::Default(_), Some(outbox_config)) =
+                (&self.processor_mode, &postgres_config.outbox)
+            {
+                spawn_outbox_relay(
+                    self.processor_config.name(),
+                    postgres_config.connection_string.clone(),
+                    outbox_config.clone(),
+                );
+            }
+            if let Some(replica_config) = &postgres_config.replica {
+                spawn_replica_writer(self.processor_config.name(), replica_config.clone())?;
+            }
+            if let Some(file_sink_config) = &postgres_config.file_sink {
+                start_file_sink(file_sink_config).await?;
+            }
+        }
+        // Each shard supervises the processor runs of its own ranges
+        if let ProcessorMode::Sharded(sharded_config) = &self.processor_mode {
+            return run_sharded(self, sharded_config).await;
+        }
+        let started_at = Instant::now();
+        let result = supervise(
+            self.processor_config.name(),
+            &self.supervisor_config,
+            restart_on_completion,
+            &RESTART_REQUESTED,
+            || {
+                let mut config = self.clone();
+                if let Some(reloaded) = reloaded_transaction_stream_config() {
+                    config.transaction_stream_config.transaction_filter =
+                        reloaded.transaction_filter;
+                }
+                if let ProcessorMode::Ranged(_) = config.processor_mode {
+                    start_ranged_attempt();
+                }
+                config.run_once()
+            },
+        )
+        .await;
+        match &self.processor_mode {
+            ProcessorMode::Ranged(ranged_config) => finish_ranged_run(
+                self.processor_config.name(),
+                ranged_config,
+                started_at.elapsed(),
+                result,
+            ),
+            _ => result,
+        }
+    }
 
     fn get_server_name(&self) -> String {
         // Get the part before the first _ and trim to 12 characters.
@@ -0,0 +1,41 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Reads transactions from local files instead of the gRPC transaction stream, for air-gapped
+/// environments and deterministic replays.
+///
+/// `path` is a file or a directory of files, read recursively. Supported formats:
+/// - `.json`: a transaction, or an array of transactions, serialized like the integration test
+///   fixtures.
+/// - `.pb`: length-delimited protobuf transactions, back to back.
+///
+/// Files are read one at a time, in path order, as the processor gets to them. A file's
+/// transactions are sorted by version, but each file has to come after the versions of the files
+/// before it. Transactions are restricted to the starting and ending versions of
+/// `transaction_stream_config`, whose other fields are unused, except for its transaction filter,
+/// which can't be set. The chain id check is skipped. The processor stops once every transaction
+/// is processed.
+///
+/// Example:
+/// ```yaml
+/// local_transactions_config:
+///   path: "/data/fixtures/imported_mainnet_txns"
+///   batch_size: 1000
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LocalTransactionsConfig {
+    pub path: PathBuf,
+    /// Number of transactions sent down the step graph per batch.
+    #[serde(default = "LocalTransactionsConfig::default_batch_size")]
+    pub batch_size: usize,
+}
+
+impl LocalTransactionsConfig {
+    pub const fn default_batch_size() -> usize {
+        1000
+    }
+}
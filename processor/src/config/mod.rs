@@ -1,4 +1,10 @@
+pub mod auth_token_config;
+pub mod azure_blob_config;
 pub mod db_config;
+pub mod explain_config;
 pub mod indexer_processor_config;
+pub mod local_transactions_config;
 pub mod processor_config;
 pub mod processor_mode;
+pub mod stream_failover_config;
+pub mod supervisor_config;
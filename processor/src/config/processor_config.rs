@@ -9,7 +9,10 @@ use crate::{
         parquet_utils::util::{format_table_name, NamedTable, VALID_TABLE_NAMES},
     },
     processors::{
-        account_transactions::account_transactions_model::ParquetAccountTransaction,
+        account_transactions::{
+            account_transactions_model::ParquetAccountTransaction,
+            account_transactions_processor::AccountTransactionsProcessorConfig,
+        },
         ans::{
             ans_processor::AnsProcessorConfig,
             models::{
@@ -17,32 +20,45 @@ use crate::{
                 ans_primary_name_v2::{ParquetAnsPrimaryNameV2, ParquetCurrentAnsPrimaryNameV2},
             },
         },
-        default::models::{
-            block_metadata_transactions::ParquetBlockMetadataTransaction,
-            move_modules::ParquetMoveModule,
-            move_resources::ParquetMoveResource,
-            table_items::{ParquetCurrentTableItem, ParquetTableItem, ParquetTableMetadata},
-            transactions::ParquetTransaction,
-            write_set_changes::ParquetWriteSetChange,
+        default::{
+            default_processor::DefaultProcessorExtendedConfig,
+            models::{
+                block_metadata_transactions::ParquetBlockMetadataTransaction,
+                move_modules::ParquetMoveModule,
+                move_resources::ParquetMoveResource,
+                table_items::{ParquetCurrentTableItem, ParquetTableItem, ParquetTableMetadata},
+                transactions::ParquetTransaction,
+                write_set_changes::ParquetWriteSetChange,
+            },
         },
-        events::events_model::ParquetEvent,
-        fungible_asset::fungible_asset_models::{
-            v2_fungible_asset_activities::ParquetFungibleAssetActivity,
-            v2_fungible_asset_balances::{
-                ParquetCurrentFungibleAssetBalance, ParquetCurrentUnifiedFungibleAssetBalance,
-                ParquetFungibleAssetBalance,
+        events::{events_model::ParquetEvent, events_processor::EventsProcessorConfig},
+        fungible_asset::{
+            fungible_asset_models::{
+                v2_fungible_asset_activities::ParquetFungibleAssetActivity,
+                v2_fungible_asset_balances::{
+                    ParquetCurrentFungibleAssetBalance, ParquetCurrentUnifiedFungibleAssetBalance,
+                    ParquetFungibleAssetBalance,
+                },
+                v2_fungible_metadata::ParquetFungibleAssetMetadataModel,
             },
-            v2_fungible_metadata::ParquetFungibleAssetMetadataModel,
+            fungible_asset_processor::FungibleAssetProcessorConfig,
         },
         objects::{
             objects_processor::ObjectsProcessorConfig,
             v2_objects_models::{ParquetCurrentObject, ParquetObject},
         },
+        resource_watcher::resource_watcher_processor::ResourceWatcherProcessorConfig,
         stake::{
             models::{
+                current_delegated_voter::ParquetCurrentDelegatedVoter,
                 delegator_activities::ParquetDelegatedStakingActivity,
                 delegator_balances::{ParquetCurrentDelegatorBalance, ParquetDelegatorBalance},
+                delegator_pools::{
+                    ParquetCurrentDelegatorPoolBalance, ParquetDelegatorPool,
+                    ParquetDelegatorPoolBalance,
+                },
                 proposal_votes::ParquetProposalVote,
+                staking_pool_voter::ParquetCurrentStakingPoolVoter,
             },
             stake_processor::StakeProcessorConfig,
         },
@@ -59,12 +75,19 @@ use crate::{
             },
             token_v2_processor::TokenV2ProcessorConfig,
         },
+        treasury_flows::treasury_flows_processor::TreasuryFlowsProcessorConfig,
         user_transaction::models::user_transactions::ParquetUserTransaction,
     },
+    utils::table_flags::TableSelection,
 };
 use ahash::AHashMap;
+use anyhow::bail;
+use parquet::{
+    basic::{Compression, ZstdLevel},
+    file::properties::WriterProperties,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 /// This enum captures the configs for all the different processors that are defined.
 ///
@@ -101,17 +124,21 @@ use std::collections::HashSet;
 )]
 pub enum ProcessorConfig {
     AccountRestorationProcessor(DefaultProcessorConfig),
-    AccountTransactionsProcessor(DefaultProcessorConfig),
+    AccountTransactionsProcessor(AccountTransactionsProcessorConfig),
     AnsProcessor(AnsProcessorConfig),
-    DefaultProcessor(DefaultProcessorConfig),
-    EventsProcessor(DefaultProcessorConfig),
-    FungibleAssetProcessor(DefaultProcessorConfig),
+    DefaultProcessor(DefaultProcessorExtendedConfig),
+    EventsProcessor(EventsProcessorConfig),
+    FungibleAssetProcessor(FungibleAssetProcessorConfig),
     UserTransactionProcessor(DefaultProcessorConfig),
     StakeProcessor(StakeProcessorConfig),
     TokenV2Processor(TokenV2ProcessorConfig),
     ObjectsProcessor(ObjectsProcessorConfig),
     MonitoringProcessor(DefaultProcessorConfig),
     GasFeeProcessor(DefaultProcessorConfig),
+    RawTransactionsProcessor(DefaultProcessorConfig),
+    ValidatorTransactionProcessor(DefaultProcessorConfig),
+    ResourceWatcherProcessor(ResourceWatcherProcessorConfig),
+    TreasuryFlowsProcessor(TreasuryFlowsProcessorConfig),
     // ParquetProcessor
     ParquetDefaultProcessor(ParquetDefaultProcessorConfig),
     ParquetObjectsProcessor(ParquetDefaultProcessorConfig),
@@ -132,13 +159,51 @@ impl ProcessorConfig {
         self.into()
     }
 
-    // TODO: uncomment after we migrate all parquet processors
-    /// Get the Vec of table names for parquet processors only.
-    ///
-    /// This is a convenience method to map the table names to include the processor name as a prefix, which
-    /// is useful for querying the status from the processor status table in the database.
-    pub fn get_processor_status_table_names(&self) -> anyhow::Result<Vec<String>> {
-        let default_config = match self {
+    /// Get the `tables_to_write` of Postgres processors. Parquet processors select their tables
+    /// with `backfill_table` instead.
+    pub fn tables_to_write(&self) -> Option<&HashSet<String>> {
+        match self {
+            ProcessorConfig::AccountRestorationProcessor(config)
+            | ProcessorConfig::UserTransactionProcessor(config)
+            | ProcessorConfig::MonitoringProcessor(config)
+            | ProcessorConfig::GasFeeProcessor(config)
+            | ProcessorConfig::RawTransactionsProcessor(config)
+            | ProcessorConfig::ValidatorTransactionProcessor(config) => {
+                Some(&config.tables_to_write)
+            },
+            ProcessorConfig::AccountTransactionsProcessor(config) => {
+                Some(&config.default_config.tables_to_write)
+            },
+            ProcessorConfig::FungibleAssetProcessor(config) => {
+                Some(&config.default_config.tables_to_write)
+            },
+            ProcessorConfig::AnsProcessor(config) => Some(&config.default.tables_to_write),
+            ProcessorConfig::DefaultProcessor(config) => {
+                Some(&config.default_config.tables_to_write)
+            },
+            ProcessorConfig::EventsProcessor(config) => {
+                Some(&config.default_config.tables_to_write)
+            },
+            ProcessorConfig::StakeProcessor(config) => Some(&config.default_config.tables_to_write),
+            ProcessorConfig::TokenV2Processor(config) => {
+                Some(&config.default_config.tables_to_write)
+            },
+            ProcessorConfig::ObjectsProcessor(config) => {
+                Some(&config.default_config.tables_to_write)
+            },
+            ProcessorConfig::ResourceWatcherProcessor(config) => {
+                Some(&config.default_config.tables_to_write)
+            },
+            ProcessorConfig::TreasuryFlowsProcessor(config) => {
+                Some(&config.default_config.tables_to_write)
+            },
+            _ => None,
+        }
+    }
+
+    /// Get the config shared by parquet processors, `None` for Postgres processors.
+    pub fn parquet_default_config(&self) -> Option<&ParquetDefaultProcessorConfig> {
+        match self {
             ProcessorConfig::ParquetDefaultProcessor(config)
             | ProcessorConfig::ParquetEventsProcessor(config)
             | ProcessorConfig::ParquetTransactionMetadataProcessor(config)
@@ -147,14 +212,23 @@ impl ProcessorConfig {
             | ProcessorConfig::ParquetStakeProcessor(config)
             | ProcessorConfig::ParquetObjectsProcessor(config)
             | ProcessorConfig::ParquetFungibleAssetProcessor(config)
-            | ProcessorConfig::ParquetUserTransactionProcessor(config) => config,
-            ProcessorConfig::ParquetAnsProcessor(config) => &config.default,
-            _ => {
-                return Err(anyhow::anyhow!(
-                    "Invalid parquet processor config: {:?}",
-                    self
-                ))
-            },
+            | ProcessorConfig::ParquetUserTransactionProcessor(config) => Some(config),
+            ProcessorConfig::ParquetAnsProcessor(config) => Some(&config.default),
+            _ => None,
+        }
+    }
+
+    // TODO: uncomment after we migrate all parquet processors
+    /// Get the Vec of table names for parquet processors only.
+    ///
+    /// This is a convenience method to map the table names to include the processor name as a prefix, which
+    /// is useful for querying the status from the processor status table in the database.
+    pub fn get_processor_status_table_names(&self) -> anyhow::Result<Vec<String>> {
+        let Some(default_config) = self.parquet_default_config() else {
+            return Err(anyhow::anyhow!(
+                "Invalid parquet processor config: {:?}",
+                self
+            ));
         };
 
         // Get the processor name as a prefix
@@ -234,30 +308,50 @@ impl ProcessorConfig {
                 ParquetProposalVote::TABLE_NAME.to_string(),
                 ParquetDelegatorBalance::TABLE_NAME.to_string(),
                 ParquetCurrentDelegatorBalance::TABLE_NAME.to_string(),
+                ParquetCurrentStakingPoolVoter::TABLE_NAME.to_string(),
+                ParquetDelegatorPool::TABLE_NAME.to_string(),
+                ParquetDelegatorPoolBalance::TABLE_NAME.to_string(),
+                ParquetCurrentDelegatorPoolBalance::TABLE_NAME.to_string(),
+                ParquetCurrentDelegatedVoter::TABLE_NAME.to_string(),
             ]),
             _ => HashSet::new(), // Default case for unsupported processors
         }
     }
 
-    /// This is to validate table_name for the backfill table
+    /// This is to validate table_name for the backfill table, and resolve `*` and `!` entries
     #[allow(dead_code)]
     fn validate_backfill_table_names(
         table_names: &HashSet<String>,
         valid_table_names: &HashSet<String>,
     ) -> anyhow::Result<Vec<String>> {
-        table_names
+        let selection = TableSelection::parse(table_names);
+        for table_name in selection.named_tables() {
+            if !valid_table_names.contains(&table_name.to_lowercase()) {
+                return Err(anyhow::anyhow!(
+                    "Invalid table name '{}'. Expected one of: {:?}",
+                    table_name,
+                    valid_table_names
+                ));
+            }
+        }
+        let excluded: HashSet<String> = selection
+            .excluded
             .iter()
-            .map(|table_name| {
-                if !valid_table_names.contains(&table_name.to_lowercase()) {
-                    return Err(anyhow::anyhow!(
-                        "Invalid table name '{}'. Expected one of: {:?}",
-                        table_name,
-                        valid_table_names
-                    ));
-                }
-                Ok(table_name.clone())
-            })
-            .collect()
+            .map(|table_name| table_name.to_lowercase())
+            .collect();
+        let selected: Vec<String> = if selection.all {
+            valid_table_names.iter().cloned().collect()
+        } else {
+            selection
+                .included
+                .iter()
+                .map(|table_name| table_name.to_string())
+                .collect()
+        };
+        Ok(selected
+            .into_iter()
+            .filter(|table_name| !excluded.contains(&table_name.to_lowercase()))
+            .collect())
     }
 }
 
@@ -265,14 +359,22 @@ impl ProcessorConfig {
 #[serde(deny_unknown_fields)]
 pub struct DefaultProcessorConfig {
     // Number of rows to insert, per chunk, for each DB table. Default per table is ~32,768 (2**16/2)
+    // Rows are inserted in primary key order; smaller chunks on large current_* tables hold their
+    // row locks for less time when several processors upsert into the same table.
     #[serde(default = "AHashMap::new")]
     pub per_table_chunk_sizes: AHashMap<String, usize>,
     // Size of channel between steps
     #[serde(default = "DefaultProcessorConfig::default_channel_size")]
     pub channel_size: usize,
-    // String vector for tables to write to DB, by default all tables are written
+    // String vector for tables to write to DB, by default all tables are written.
+    // `"*"` selects all tables and `"!TABLE"` leaves one out, e.g. `["*", "!EVENTS"]`
     #[serde(default)]
     pub tables_to_write: HashSet<String>,
+    // Number of primary keys per current table to remember across batches, so rows whose version
+    // didn't advance since they were last written aren't upserted again. Off by default; only the
+    // fungible asset processor uses it for now.
+    #[serde(default)]
+    pub current_table_dedup_window: Option<usize>,
 }
 
 impl DefaultProcessorConfig {
@@ -287,6 +389,7 @@ impl Default for DefaultProcessorConfig {
             per_table_chunk_sizes: AHashMap::new(),
             channel_size: Self::default_channel_size(),
             tables_to_write: HashSet::new(),
+            current_table_dedup_window: None,
         }
     }
 }
@@ -301,8 +404,12 @@ pub struct ParquetDefaultProcessorConfig {
     #[serde(default = "ParquetDefaultProcessorConfig::default_parquet_upload_interval")]
     pub upload_interval: u64,
     // Set of table name to backfill. Using HashSet for fast lookups, and for future extensibility.
+    // Takes `"*"` and `"!table"` entries like `tables_to_write`
     #[serde(default)]
     pub backfill_table: HashSet<String>,
+    // Compression, dictionary encoding and row group size of the parquet files
+    #[serde(default)]
+    pub writer_properties: ParquetWriterProperties,
 }
 
 impl ParquetDefaultProcessorConfig {
@@ -323,6 +430,97 @@ impl ParquetDefaultProcessorConfig {
     }
 }
 
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParquetWriterProperties {
+    // Settings of every table
+    #[serde(default)]
+    pub default: ParquetWriterConfig,
+    // Per table name, overriding the settings of `default` that are set
+    #[serde(default)]
+    pub tables: HashMap<String, ParquetWriterConfig>,
+}
+
+impl ParquetWriterProperties {
+    /// Writer properties of `table_name`'s parquet files
+    pub fn for_table(&self, table_name: &str) -> anyhow::Result<WriterProperties> {
+        match self.tables.get(table_name) {
+            Some(table_config) => table_config.or(&self.default),
+            None => self.default.clone(),
+        }
+        .build()
+    }
+}
+
+/// Parquet codecs files can be compressed with
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetCompression {
+    Uncompressed,
+    Snappy,
+    #[default]
+    Lz4,
+    Zstd,
+}
+
+/// Settings of the parquet writer, unset ones keep the writer's defaults
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParquetWriterConfig {
+    // Defaults to lz4
+    #[serde(default)]
+    pub compression: Option<ParquetCompression>,
+    // Only for zstd, 1 to 22. Defaults to 1.
+    #[serde(default)]
+    pub compression_level: Option<i32>,
+    // Dictionary encoding of every column, on by default
+    #[serde(default)]
+    pub dictionary_enabled: Option<bool>,
+    // Maximum number of rows per row group
+    #[serde(default)]
+    pub max_row_group_size: Option<usize>,
+}
+
+impl ParquetWriterConfig {
+    /// These settings, with the unset ones taken from `defaults`
+    pub fn or(&self, defaults: &Self) -> Self {
+        Self {
+            compression: self.compression.or(defaults.compression),
+            compression_level: self.compression_level.or(defaults.compression_level),
+            dictionary_enabled: self.dictionary_enabled.or(defaults.dictionary_enabled),
+            max_row_group_size: self.max_row_group_size.or(defaults.max_row_group_size),
+        }
+    }
+
+    pub fn build(&self) -> anyhow::Result<WriterProperties> {
+        let compression = match (self.compression.unwrap_or_default(), self.compression_level) {
+            (ParquetCompression::Zstd, level) => Compression::ZSTD(
+                level
+                    .map(ZstdLevel::try_new)
+                    .transpose()?
+                    .unwrap_or_default(),
+            ),
+            (compression, Some(_)) => {
+                bail!("compression_level is only supported for zstd, not {compression:?}")
+            },
+            (ParquetCompression::Uncompressed, None) => Compression::UNCOMPRESSED,
+            (ParquetCompression::Snappy, None) => Compression::SNAPPY,
+            (ParquetCompression::Lz4, None) => Compression::LZ4,
+        };
+        let mut builder = WriterProperties::builder().set_compression(compression);
+        if let Some(dictionary_enabled) = self.dictionary_enabled {
+            builder = builder.set_dictionary_enabled(dictionary_enabled);
+        }
+        if let Some(max_row_group_size) = self.max_row_group_size {
+            if max_row_group_size == 0 {
+                bail!("max_row_group_size must be positive");
+            }
+            builder = builder.set_max_row_group_size(max_row_group_size);
+        }
+        Ok(builder.build())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -334,6 +532,7 @@ mod tests {
             channel_size: 10,
             max_buffer_size: 100000,
             upload_interval: 1800,
+            ..Default::default()
         });
 
         let result = config.get_processor_status_table_names();
@@ -353,6 +552,7 @@ mod tests {
             channel_size: 10,
             max_buffer_size: 100000,
             upload_interval: 1800,
+            ..Default::default()
         });
 
         let result = config.get_processor_status_table_names();
@@ -370,6 +570,7 @@ mod tests {
             channel_size: 10,
             max_buffer_size: 100000,
             upload_interval: 1800,
+            ..Default::default()
         });
         let result = config.get_processor_status_table_names();
         assert!(result.is_ok());
@@ -400,6 +601,7 @@ mod tests {
             channel_size: 10,
             max_buffer_size: 100000,
             upload_interval: 1800,
+            ..Default::default()
         });
 
         let result = config.get_processor_status_table_names();
@@ -408,4 +610,84 @@ mod tests {
         let table_names = result.unwrap();
         assert_eq!(table_names, vec!["transactions".to_string(),]);
     }
+
+    #[test]
+    fn test_backfill_table_wildcard_and_exclusions() {
+        let config = ProcessorConfig::ParquetDefaultProcessor(ParquetDefaultProcessorConfig {
+            backfill_table: HashSet::from([
+                "*".to_string(),
+                "!transactions".to_string(),
+                "!MOVE_MODULES".to_string(),
+            ]),
+            ..Default::default()
+        });
+
+        let table_names: HashSet<String> = config
+            .get_processor_status_table_names()
+            .unwrap()
+            .into_iter()
+            .collect();
+        let expected_names: HashSet<String> = [
+            "move_resources",
+            "write_set_changes",
+            "table_items",
+            "block_metadata_transactions",
+            "current_table_items",
+            "table_metadata",
+        ]
+        .iter()
+        .map(|table_name| table_name.to_string())
+        .collect();
+        assert_eq!(table_names, expected_names);
+
+        let config = ProcessorConfig::ParquetDefaultProcessor(ParquetDefaultProcessorConfig {
+            backfill_table: HashSet::from(["!InvalidTable".to_string()]),
+            ..Default::default()
+        });
+        assert!(config.get_processor_status_table_names().is_err());
+    }
+
+    #[test]
+    fn test_table_writer_properties() {
+        let config: ParquetDefaultProcessorConfig = serde_yaml::from_str(
+            r#"
+writer_properties:
+  default:
+    compression: zstd
+    compression_level: 3
+  tables:
+    transactions:
+      dictionary_enabled: false
+      max_row_group_size: 1000
+"#,
+        )
+        .unwrap();
+        let column = parquet::schema::types::ColumnPath::from("version");
+
+        let properties = config.writer_properties.for_table("transactions").unwrap();
+        assert_eq!(
+            properties.compression(&column),
+            Compression::ZSTD(ZstdLevel::try_new(3).unwrap())
+        );
+        assert!(!properties.dictionary_enabled(&column));
+        assert_eq!(properties.max_row_group_size(), 1000);
+
+        let properties = config
+            .writer_properties
+            .for_table("move_resources")
+            .unwrap();
+        assert!(properties.dictionary_enabled(&column));
+
+        let properties = ParquetWriterProperties::default()
+            .for_table("transactions")
+            .unwrap();
+        assert_eq!(properties.compression(&column), Compression::LZ4);
+
+        let invalid = ParquetWriterConfig {
+            compression: Some(ParquetCompression::Snappy),
+            compression_level: Some(3),
+            ..Default::default()
+        };
+        assert!(invalid.build().is_err());
+    }
 }
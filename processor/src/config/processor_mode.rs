@@ -10,6 +10,10 @@ use serde::{Deserialize, Serialize};
 /// - Default: The processor will bootstrap from the starting version and track the last successfully
 ///   processed version. Upon restart, it should pick up from the last successfully processed version.1
 /// - Testing: The processor will run in the testing mode. Checkpoints are not saved.
+/// - Ranged: The processor will process the starting version to the ending version once, print a
+///   summary of the run and exit. Checkpoints are not saved.
+/// - Sharded: The processor will split the versions into ranges processed concurrently by several
+///   shards, and track the last version all shards got through like the default mode does.
 ///
 /// Using this subconfig in your main processor config is completely optional.
 /// This subconfig is meant to help you  your processor in these different modes.
@@ -29,12 +33,15 @@ pub enum ProcessorMode {
     Backfill(BackfillConfig),
     Default(BootStrapConfig),
     Testing(TestingConfig),
+    Ranged(RangedConfig),
+    Sharded(ShardedConfig),
+    /// A range of a sharded run, which the shard runs the processor in. Not configurable.
+    #[serde(skip)]
+    Shard(ShardRange),
 }
 impl Default for ProcessorMode {
     fn default() -> Self {
-        ProcessorMode::Default(BootStrapConfig {
-            initial_starting_version: 0,
-        })
+        ProcessorMode::Default(BootStrapConfig::default())
     }
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -51,7 +58,21 @@ pub struct BackfillConfig {
 /// Initial starting version for non-backfill processors. Processors should pick up where it left off
 /// if restarted.
 pub struct BootStrapConfig {
+    #[serde(default)]
     pub initial_starting_version: u64,
+    /// Without a checkpoint, start at the latest transaction instead of `initial_starting_version`
+    #[serde(default)]
+    pub start_at: Option<StartAt>,
+    /// Without a checkpoint, start at the first transaction at or after this time (RFC 3339)
+    #[serde(default)]
+    pub start_at_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartAt {
+    /// The chain tip, for deployments that only need data going forward
+    Latest,
 }
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
@@ -61,3 +82,55 @@ pub struct TestingConfig {
     pub override_starting_version: u64,
     pub ending_version: Option<u64>,
 }
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+/// Use this config to process a version range once, e.g. from a scheduler. The processor prints
+/// a JSON summary of the run, with the rows written per table and the versions it didn't get
+/// through, and exits with an error unless it processed the whole range.
+pub struct RangedConfig {
+    pub starting_version: u64,
+    pub ending_version: u64,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+/// Use this config to catch up faster than a single stream allows. Versions are split into ranges
+/// of `range_size`, dealt round robin to `shard_count` shards that process them concurrently. Each
+/// shard saves its progress to `processor_shard_status`, and `processor_status` moves to the last
+/// version every shard got through, so the processor can be switched back to the default mode
+/// once it's caught up.
+///
+/// Only for Postgres processors whose parsing doesn't read what earlier batches wrote, since
+/// ranges are processed out of order. Other processors are refused. Current tables are written with version guards, so the
+/// latest version wins either way.
+///
+/// Example:
+/// ```yaml
+/// processor_mode:
+///   type: "sharded"
+///   shard_count: 4
+///   range_size: 1000000
+/// ```
+pub struct ShardedConfig {
+    pub shard_count: u64,
+    #[serde(default = "ShardedConfig::default_range_size")]
+    pub range_size: u64,
+    /// Where to start without a saved `processor_status`
+    #[serde(default)]
+    pub initial_starting_version: u64,
+    #[serde(default)]
+    pub ending_version: Option<u64>,
+}
+
+impl ShardedConfig {
+    pub const fn default_range_size() -> u64 {
+        1_000_000
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Versions of a sharded run one shard is processing, inclusive
+pub struct ShardRange {
+    pub shard_index: u64,
+    pub starting_version: u64,
+    pub ending_version: u64,
+}
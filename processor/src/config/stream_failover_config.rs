@@ -0,0 +1,68 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Additional gRPC endpoints to stream transactions from when `transaction_stream_config`'s
+/// endpoint fails. The stream resumes on the next healthy endpoint at the version after the
+/// last batch received, so no version is skipped or sent twice.
+///
+/// An endpoint that errors is skipped for `unhealthy_cooldown_secs` before it's tried again. The
+/// cooldown doubles every time it errors again before a batch is streamed from it, up to
+/// `max_unhealthy_cooldown_secs`. Endpoints are tried in order, starting with
+/// `transaction_stream_config`'s, and while all of them cool down the stream waits for the first
+/// one to be tried again.
+///
+/// Health is only checked passively: an endpoint is found unhealthy when connecting or streaming
+/// fails, and healthy again once a batch is streamed from it. Endpoints that aren't streamed from
+/// aren't probed.
+///
+/// Endpoints can be restricted to a version range, e.g. to send history to an archive node.
+/// An endpoint with a `max_version` is preferred for the versions it covers, and the stream
+/// moves on once it reaches that version. Every other field of `transaction_stream_config`,
+/// like the transaction filter, applies to all endpoints.
+///
+/// Example:
+/// ```yaml
+/// stream_failover_config:
+///   unhealthy_cooldown_secs: 60
+///   max_unhealthy_cooldown_secs: 900
+///   endpoints:
+///     - indexer_grpc_data_service_address: "https://grpc-archive.mainnet.cedralabs.com:443"
+///       max_version: 1000000000
+///     - indexer_grpc_data_service_address: "https://grpc-backup.mainnet.cedralabs.com:443"
+///       auth_token: "AUTH_TOKEN"
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StreamFailoverConfig {
+    pub endpoints: Vec<StreamEndpointConfig>,
+    #[serde(default = "StreamFailoverConfig::default_unhealthy_cooldown_secs")]
+    pub unhealthy_cooldown_secs: u64,
+    #[serde(default = "StreamFailoverConfig::default_max_unhealthy_cooldown_secs")]
+    pub max_unhealthy_cooldown_secs: u64,
+}
+
+impl StreamFailoverConfig {
+    pub const fn default_unhealthy_cooldown_secs() -> u64 {
+        60
+    }
+
+    pub const fn default_max_unhealthy_cooldown_secs() -> u64 {
+        900
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct StreamEndpointConfig {
+    pub indexer_grpc_data_service_address: Url,
+    /// Defaults to `transaction_stream_config`'s
+    #[serde(default)]
+    pub auth_token: Option<String>,
+    #[serde(default)]
+    pub min_version: Option<u64>,
+    #[serde(default)]
+    pub max_version: Option<u64>,
+}
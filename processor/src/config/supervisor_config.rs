@@ -0,0 +1,73 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Controls how the processor is restarted in-process when its step graph fails, instead of
+/// exiting and relying on the orchestrator to reschedule it. Every restart resumes from the
+/// last committed version, as a fresh process would.
+///
+/// Example:
+/// ```yaml
+/// supervisor_config:
+///   max_restarts: 10
+///   initial_backoff_ms: 1000
+///   max_backoff_ms: 60000
+///   healthy_run_secs: 600
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SupervisorConfig {
+    /// Number of consecutive failed runs tolerated before giving up and exiting. 0 disables
+    /// restarts.
+    #[serde(default = "SupervisorConfig::default_max_restarts")]
+    pub max_restarts: u32,
+    #[serde(default = "SupervisorConfig::default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    #[serde(default = "SupervisorConfig::default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+    /// A run lasting at least this long resets the restart count and backoff.
+    #[serde(default = "SupervisorConfig::default_healthy_run_secs")]
+    pub healthy_run_secs: u64,
+}
+
+impl SupervisorConfig {
+    pub const fn default_max_restarts() -> u32 {
+        5
+    }
+
+    pub const fn default_initial_backoff_ms() -> u64 {
+        1000
+    }
+
+    pub const fn default_max_backoff_ms() -> u64 {
+        60_000
+    }
+
+    pub const fn default_healthy_run_secs() -> u64 {
+        300
+    }
+
+    /// Delay before the given restart (1-based), doubling from `initial_backoff_ms` up to
+    /// `max_backoff_ms`.
+    pub fn backoff(&self, restart: u32) -> Duration {
+        let exponent = restart.saturating_sub(1).min(31);
+        let backoff_ms = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << exponent)
+            .min(self.max_backoff_ms);
+        Duration::from_millis(backoff_ms)
+    }
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            max_restarts: Self::default_max_restarts(),
+            initial_backoff_ms: Self::default_initial_backoff_ms(),
+            max_backoff_ms: Self::default_max_backoff_ms(),
+            healthy_run_secs: Self::default_healthy_run_secs(),
+        }
+    }
+}
@@ -0,0 +1,386 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Connection pool of a processor's Postgres database.
+//!
+//! tokio-postgres only knows the `disable`, `prefer` and `require` SSL modes, and never checks
+//! the server's certificate. Connection strings with libpq's `sslmode=verify-ca` or
+//! `verify-full` are connected to over TLS checked against `sslrootcert`, a PEM bundle, or the
+//! system's roots when it's unset. With `iam_auth`, every new connection authenticates with an
+//! IAM token instead of a password. Other connection strings go through the SDK's pool as before.
+
+use crate::config::db_config::{IamAuthConfig, PostgresConfig};
+use anyhow::{bail, Context, Result};
+use aws_config::{default_provider::credentials::DefaultCredentialsChain, Region};
+use aws_credential_types::{provider::ProvideCredentials, Credentials};
+use aws_sigv4::{
+    http_request::{sign, SignableBody, SignableRequest, SignatureLocation, SigningSettings},
+    sign::v4,
+};
+use cedra_indexer_processor_sdk::postgres::utils::database::{new_db_pool, ArcDbPool};
+use diesel::{ConnectionError, ConnectionResult};
+use diesel_async::{
+    pooled_connection::{bb8::Pool, AsyncDieselConnectionManager, ManagerConfig},
+    AsyncPgConnection,
+};
+use futures::FutureExt;
+use native_tls::{Certificate, TlsConnector};
+use postgres_native_tls::MakeTlsConnector;
+use serde::Deserialize;
+use std::{sync::Arc, time::SystemTime};
+use tokio::{
+    sync::{Mutex, OnceCell},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+use url::Url;
+
+const GCP_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+/// RDS auth tokens are valid for 15 minutes
+const RDS_TOKEN_EXPIRY: Duration = Duration::from_secs(900);
+/// IAM tokens are refreshed this long before they expire
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(300);
+
+/// How the server's certificate is checked, from the connection string's `sslmode`
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SslVerification {
+    /// `disable`, `prefer` or `require`, which use TLS without checking the certificate
+    None,
+    /// `verify-ca`: the certificate is signed by a trusted root
+    Ca,
+    /// `verify-full`: also issued for the host connected to
+    Full,
+}
+
+#[derive(Debug, PartialEq)]
+struct SslSettings {
+    /// Connection string tokio-postgres accepts, with the verify modes turned into `require`
+    url: Url,
+    verification: SslVerification,
+    root_cert_path: Option<String>,
+}
+
+impl SslSettings {
+    /// None if the connection string needs nothing tokio-postgres doesn't do itself
+    fn from_connection_string(connection_string: &str) -> Result<Option<Self>> {
+        let Ok(mut url) = Url::parse(connection_string) else {
+            return Ok(None);
+        };
+        let mut verification = SslVerification::None;
+        let mut root_cert_path = None;
+        let mut pairs = vec![];
+        for (key, value) in url.query_pairs().into_owned() {
+            match (key.as_str(), value.as_str()) {
+                ("sslmode", "verify-ca") => verification = SslVerification::Ca,
+                ("sslmode", "verify-full") => verification = SslVerification::Full,
+                ("sslrootcert", _) => root_cert_path = Some(value),
+                _ => pairs.push((key, value)),
+            }
+        }
+        if verification == SslVerification::None {
+            if root_cert_path.is_some() {
+                bail!("sslrootcert is only used with sslmode=verify-ca or verify-full");
+            }
+            return Ok(None);
+        }
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(pairs)
+            .append_pair("sslmode", "require");
+        Ok(Some(Self {
+            url,
+            verification,
+            root_cert_path,
+        }))
+    }
+
+    fn tls_connector(&self) -> Result<TlsConnector> {
+        let mut builder = TlsConnector::builder();
+        match self.verification {
+            SslVerification::None => {
+                builder.danger_accept_invalid_certs(true);
+            },
+            SslVerification::Ca => {
+                builder.danger_accept_invalid_hostnames(true);
+            },
+            SslVerification::Full => {},
+        }
+        if let Some(root_cert_path) = &self.root_cert_path {
+            let bundle = std::fs::read_to_string(root_cert_path)
+                .with_context(|| format!("Failed to read sslrootcert {root_cert_path}"))?;
+            for certificate in pem_certificates(&bundle) {
+                let certificate = Certificate::from_pem(certificate.as_bytes())
+                    .with_context(|| format!("Invalid certificate in {root_cert_path}"))?;
+                builder.add_root_certificate(certificate);
+            }
+        }
+        builder.build().context("Failed to build the TLS connector")
+    }
+}
+
+/// The certificates of a PEM bundle, which `Certificate::from_pem` only reads the first of
+fn pem_certificates(bundle: &str) -> Vec<String> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+    bundle
+        .split_inclusive(END)
+        .filter(|certificate| certificate.ends_with(END))
+        .filter_map(|certificate| certificate.find(BEGIN).map(|start| &certificate[start..]))
+        .map(str::to_string)
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct GcpAccessToken {
+    access_token: String,
+    expires_in: u64,
+}
+
+/// IAM token every new connection authenticates with, kept until it's about to expire
+struct IamToken {
+    config: IamAuthConfig,
+    http_client: reqwest::Client,
+    /// AWS's default credentials chain: environment variables, profiles, web identity and
+    /// container or instance credentials, in that order
+    aws_credentials: OnceCell<DefaultCredentialsChain>,
+    token: Mutex<Option<(String, Instant)>>,
+}
+
+impl IamToken {
+    async fn get(&self, url: &Url) -> Result<String> {
+        let mut token = self.token.lock().await;
+        if let Some((access_token, expires_at)) = token.as_ref() {
+            if *expires_at > Instant::now() + TOKEN_REFRESH_MARGIN {
+                return Ok(access_token.clone());
+            }
+        }
+        let (access_token, expires_in) = match &self.config {
+            IamAuthConfig::Aws { region } => {
+                let credentials = self
+                    .aws_credentials
+                    .get_or_init(|| {
+                        DefaultCredentialsChain::builder()
+                            .region(Region::new(region.clone()))
+                            .build()
+                    })
+                    .await
+                    .provide_credentials()
+                    .await
+                    .context("Failed to get AWS credentials")?;
+                let access_token = rds_auth_token(url, region, credentials, SystemTime::now())?;
+                (access_token, RDS_TOKEN_EXPIRY)
+            },
+            IamAuthConfig::Gcp => {
+                let response: GcpAccessToken = self
+                    .http_client
+                    .get(GCP_TOKEN_URL)
+                    .header("Metadata-Flavor", "Google")
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .context("Failed to request a GCP access token")?
+                    .json()
+                    .await
+                    .context("Invalid GCP access token response")?;
+                (
+                    response.access_token,
+                    Duration::from_secs(response.expires_in),
+                )
+            },
+        };
+        info!("Fetched a new IAM token for Postgres");
+        *token = Some((access_token.clone(), Instant::now() + expires_in));
+        Ok(access_token)
+    }
+}
+
+/// RDS IAM auth token for the user and host of `url`: a SigV4 presigned `connect` request,
+/// the same token `aws rds generate-db-auth-token` prints
+fn rds_auth_token(
+    url: &Url,
+    region: &str,
+    credentials: Credentials,
+    now: SystemTime,
+) -> Result<String> {
+    let Some(host) = url.host_str() else {
+        bail!("IAM auth needs the host in connection_string");
+    };
+    let mut request_url = Url::parse(&format!("https://{host}:{}/", url.port().unwrap_or(5432)))?;
+    request_url
+        .query_pairs_mut()
+        .append_pair("Action", "connect")
+        .append_pair("DBUser", url.username());
+
+    let identity = credentials.into();
+    let mut settings = SigningSettings::default();
+    settings.expires_in = Some(RDS_TOKEN_EXPIRY);
+    settings.signature_location = SignatureLocation::QueryParams;
+    let signing_params = v4::SigningParams::builder()
+        .identity(&identity)
+        .region(region)
+        .name("rds-db")
+        .time(now)
+        .settings(settings)
+        .build()?
+        .into();
+    let request = SignableRequest::new(
+        "GET",
+        request_url.as_str(),
+        std::iter::empty(),
+        SignableBody::Bytes(&[]),
+    )?;
+    let (instructions, _) = sign(request, &signing_params)?.into_parts();
+    for (name, value) in instructions.params() {
+        request_url.query_pairs_mut().append_pair(name, value);
+    }
+    Ok(request_url.as_str()["https://".len()..].to_string())
+}
+
+struct ConnectionSettings {
+    url: Url,
+    tls_connector: TlsConnector,
+    iam_token: Option<IamToken>,
+}
+
+async fn establish_connection(
+    settings: Arc<ConnectionSettings>,
+) -> ConnectionResult<AsyncPgConnection> {
+    let mut url = settings.url.clone();
+    if let Some(iam_token) = &settings.iam_token {
+        let token = iam_token
+            .get(&url)
+            .await
+            .map_err(|e| ConnectionError::BadConnection(format!("{e:#}")))?;
+        url.set_password(Some(&token)).map_err(|_| {
+            ConnectionError::InvalidConnectionUrl("IAM auth needs a host to connect to".to_string())
+        })?;
+    }
+    let (client, connection) = tokio_postgres::connect(
+        url.as_str(),
+        MakeTlsConnector::new(settings.tls_connector.clone()),
+    )
+    .await
+    .map_err(|e| ConnectionError::BadConnection(e.to_string()))?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            warn!(error = ?e, "Postgres connection closed with an error");
+        }
+    });
+    AsyncPgConnection::try_from(client).await
+}
+
+/// Pool of connections to `config`'s database, with the timeouts of `pool_connection_string`
+pub async fn new_postgres_pool(config: &PostgresConfig) -> Result<ArcDbPool> {
+    let connection_string = config.pool_connection_string()?;
+    let ssl_settings = SslSettings::from_connection_string(&connection_string)?;
+    if ssl_settings.is_none() && config.iam_auth.is_none() {
+        return new_db_pool(&connection_string, Some(config.db_pool_size))
+            .await
+            .map_err(|e| anyhow::anyhow!("{e:?}"));
+    }
+
+    let ssl_settings = match ssl_settings {
+        Some(ssl_settings) => ssl_settings,
+        None => SslSettings {
+            url: Url::parse(&connection_string)
+                .context("IAM auth needs a postgresql:// connection string")?,
+            verification: SslVerification::None,
+            root_cert_path: None,
+        },
+    };
+    if config.iam_auth.is_some() && ssl_settings.url.password().is_some() {
+        bail!("Leave the password out of connection_string with iam_auth");
+    }
+    let settings = Arc::new(ConnectionSettings {
+        tls_connector: ssl_settings.tls_connector()?,
+        url: ssl_settings.url,
+        iam_token: config.iam_auth.clone().map(|config| IamToken {
+            config,
+            http_client: reqwest::Client::new(),
+            aws_credentials: OnceCell::new(),
+            token: Mutex::new(None),
+        }),
+    });
+    let manager_config = ManagerConfig::<AsyncPgConnection> {
+        custom_setup: Box::new(move |_| establish_connection(settings.clone()).boxed()),
+        ..Default::default()
+    };
+    let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new_with_config(
+        connection_string,
+        manager_config,
+    );
+    let pool = Pool::builder()
+        .max_size(config.db_pool_size)
+        .build(manager)
+        .await
+        .context("Failed to connect to Postgres")?;
+    Ok(Arc::new(pool))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ssl_settings() {
+        assert_eq!(
+            SslSettings::from_connection_string(
+                "postgresql://indexer@db.example.com:5432/indexer?sslmode=require"
+            )
+            .unwrap(),
+            None
+        );
+        let settings = SslSettings::from_connection_string(
+            "postgresql://indexer@db.example.com:5432/indexer?sslmode=verify-full&sslrootcert=%2Fetc%2Fssl%2Fdb-ca.pem&options=-c%20lock_timeout%3D5000",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(settings.verification, SslVerification::Full);
+        assert_eq!(
+            settings.root_cert_path.as_deref(),
+            Some("/etc/ssl/db-ca.pem")
+        );
+        let pairs: Vec<(String, String)> = settings.url.query_pairs().into_owned().collect();
+        assert_eq!(pairs, vec![
+            ("options".to_string(), "-c lock_timeout=5000".to_string()),
+            ("sslmode".to_string(), "require".to_string()),
+        ]);
+        assert!(SslSettings::from_connection_string(
+            "postgresql://indexer@db.example.com:5432/indexer?sslrootcert=ca.pem"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_rds_auth_token() {
+        let url = Url::parse("postgresql://indexer@db.example.com/indexer").unwrap();
+        let credentials = Credentials::new(
+            "AKIDEXAMPLE",
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            None,
+            None,
+            "test",
+        );
+        // 2024-01-02T03:04:05Z
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_164_645);
+        let token = rds_auth_token(&url, "us-east-1", credentials, now).unwrap();
+        let (host, query) = token.split_once("/?").unwrap();
+        assert_eq!(host, "db.example.com:5432");
+        let params: std::collections::HashMap<String, String> =
+            url::form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect();
+        assert_eq!(params["Action"], "connect");
+        assert_eq!(params["DBUser"], "indexer");
+        assert_eq!(params["X-Amz-Algorithm"], "AWS4-HMAC-SHA256");
+        assert_eq!(
+            params["X-Amz-Credential"],
+            "AKIDEXAMPLE/20240102/us-east-1/rds-db/aws4_request"
+        );
+        assert_eq!(params["X-Amz-Date"], "20240102T030405Z");
+        assert_eq!(params["X-Amz-Expires"], "900");
+        assert_eq!(params["X-Amz-SignedHeaders"], "host");
+        assert_eq!(params["X-Amz-Signature"].len(), 64);
+    }
+}
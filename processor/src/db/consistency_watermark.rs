@@ -0,0 +1,41 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::schema::processor_consistency_watermarks;
+use cedra_indexer_processor_sdk::postgres::utils::database::DbPoolConnection;
+use diesel::{AsChangeset, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable};
+use diesel_async::RunQueryDsl;
+
+#[derive(AsChangeset, Debug, Insertable)]
+#[diesel(table_name = processor_consistency_watermarks)]
+/// Highest version through which every table of a processor has been committed
+pub struct ConsistencyWatermark {
+    pub processor: String,
+    pub consistent_through_version: i64,
+    pub last_transaction_timestamp: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(AsChangeset, Debug, Queryable)]
+#[diesel(table_name = processor_consistency_watermarks)]
+/// Highest version through which every table of a processor has been committed
+pub struct ConsistencyWatermarkQuery {
+    pub processor: String,
+    pub consistent_through_version: i64,
+    pub last_updated: chrono::NaiveDateTime,
+    pub last_transaction_timestamp: Option<chrono::NaiveDateTime>,
+}
+
+impl ConsistencyWatermarkQuery {
+    pub async fn get_by_processor(
+        processor_name: &str,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Self>> {
+        processor_consistency_watermarks::table
+            .filter(processor_consistency_watermarks::processor.eq(processor_name))
+            .first::<Self>(conn)
+            .await
+            .optional()
+    }
+}
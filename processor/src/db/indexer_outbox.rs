@@ -0,0 +1,62 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::schema::indexer_outbox;
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = indexer_outbox)]
+/// Version range a processor committed with one checkpoint, and the tables it wrote rows to
+pub struct IndexerOutboxEntry {
+    pub processor: String,
+    pub start_version: i64,
+    pub end_version: i64,
+    pub tables_touched: Vec<Option<String>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Queryable, Serialize)]
+#[diesel(table_name = indexer_outbox)]
+/// An outbox entry as published to downstream jobs. `id` is unique and increasing per database,
+/// so consumers can drop an entry delivered twice.
+pub struct IndexerOutboxEntryQuery {
+    pub id: i64,
+    pub processor: String,
+    pub start_version: i64,
+    pub end_version: i64,
+    pub tables_touched: Vec<Option<String>>,
+    pub created_at: chrono::NaiveDateTime,
+    pub published_at: Option<chrono::NaiveDateTime>,
+}
+
+impl IndexerOutboxEntryQuery {
+    /// Locks the oldest unpublished entries of a processor. Must be called in a transaction, the
+    /// locks are released when it ends.
+    pub async fn lock_unpublished(
+        processor_name: &str,
+        limit: i64,
+        conn: &mut AsyncPgConnection,
+    ) -> diesel::QueryResult<Vec<Self>> {
+        indexer_outbox::table
+            .filter(indexer_outbox::processor.eq(processor_name))
+            .filter(indexer_outbox::published_at.is_null())
+            .order(indexer_outbox::id)
+            .limit(limit)
+            .for_update()
+            .load::<Self>(conn)
+            .await
+    }
+
+    pub async fn mark_published(
+        ids: &[i64],
+        conn: &mut AsyncPgConnection,
+    ) -> diesel::QueryResult<usize> {
+        diesel::update(indexer_outbox::table.filter(indexer_outbox::id.eq_any(ids)))
+            .set(indexer_outbox::published_at.eq(diesel::dsl::now))
+            .execute(conn)
+            .await
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::schema::{indexer_table_stats, indexer_table_stats_daily, indexer_table_stats_hourly};
+use cedra_indexer_processor_sdk::postgres::utils::database::DbPoolConnection;
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use diesel_async::RunQueryDsl;
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = indexer_table_stats)]
+/// Rows and bytes a processor wrote to a table since the last time stats were saved. Added to
+/// the running totals on upsert.
+pub struct IndexerTableStats {
+    pub processor: String,
+    pub table_name: String,
+    pub rows_written: i64,
+    pub conflicts_ignored: i64,
+    pub bytes_written: i64,
+    pub last_rows_written_version: Option<i64>,
+    pub last_rows_written_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = indexer_table_stats_daily)]
+/// What a processor wrote to a table since the last save, added to the totals of the UTC day
+/// it was saved on.
+pub struct IndexerTableStatsDaily {
+    pub processor: String,
+    pub table_name: String,
+    pub day: chrono::NaiveDate,
+    pub rows_written: i64,
+    pub conflicts_ignored: i64,
+    pub bytes_written: i64,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = indexer_table_stats_hourly)]
+/// Like `IndexerTableStatsDaily`, for the UTC hour the stats were saved in
+pub struct IndexerTableStatsHourly {
+    pub processor: String,
+    pub table_name: String,
+    pub hour: chrono::NaiveDateTime,
+    pub rows_written: i64,
+    pub conflicts_ignored: i64,
+    pub bytes_written: i64,
+}
+
+#[derive(Debug, Queryable)]
+#[diesel(table_name = indexer_table_stats)]
+/// Running totals of what a processor has written to a table
+pub struct IndexerTableStatsQuery {
+    pub processor: String,
+    pub table_name: String,
+    pub rows_written: i64,
+    pub conflicts_ignored: i64,
+    pub bytes_written: i64,
+    pub last_rows_written_version: Option<i64>,
+    pub last_rows_written_at: Option<chrono::NaiveDateTime>,
+    pub last_updated: chrono::NaiveDateTime,
+}
+
+impl IndexerTableStatsQuery {
+    pub async fn get_by_processor(
+        processor_name: &str,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Vec<Self>> {
+        indexer_table_stats::table
+            .filter(indexer_table_stats::processor.eq(processor_name))
+            .order(indexer_table_stats::table_name)
+            .load::<Self>(conn)
+            .await
+    }
+}
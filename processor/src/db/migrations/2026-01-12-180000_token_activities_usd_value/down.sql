@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS asset_usd_prices;
+ALTER TABLE token_activities_v2 DROP COLUMN IF EXISTS usd_value;
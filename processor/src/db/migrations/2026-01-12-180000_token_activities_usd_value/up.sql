@@ -0,0 +1,9 @@
+-- Your SQL goes here
+ALTER TABLE token_activities_v2
+ADD COLUMN IF NOT EXISTS usd_value NUMERIC;
+-- Prices used by the `table` price oracle. Populated out of band by whatever feeds prices.
+CREATE TABLE IF NOT EXISTS asset_usd_prices (
+  asset_id VARCHAR(1000) NOT NULL PRIMARY KEY,
+  usd_price NUMERIC NOT NULL,
+  last_updated TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+-- Highest version through which every table written by a processor is committed.
+-- Downstream readers can safely read any version range ending at or below this watermark.
+CREATE TABLE IF NOT EXISTS processor_consistency_watermarks (
+  processor VARCHAR(100) UNIQUE PRIMARY KEY NOT NULL,
+  consistent_through_version BIGINT NOT NULL,
+  last_updated TIMESTAMP NOT NULL DEFAULT NOW(),
+  last_transaction_timestamp TIMESTAMP NULL
+);
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+-- Values pulled out of events.data by the events processor's attribute extraction rules,
+-- so lookups on specific event fields don't need to index into the JSONB column.
+CREATE TABLE IF NOT EXISTS event_attributes (
+  transaction_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  key VARCHAR(100) NOT NULL,
+  value VARCHAR(1000) NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, event_index, key)
+);
+CREATE INDEX IF NOT EXISTS ea_key_value_index ON event_attributes (key, value);
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- One row per signer role of a user transaction: the primary sender, every multi-agent
+-- secondary signer and the fee payer, if any.
+CREATE TABLE IF NOT EXISTS transaction_signers (
+  transaction_version BIGINT NOT NULL,
+  signer_role VARCHAR(50) NOT NULL,
+  signer_index BIGINT NOT NULL,
+  transaction_block_height BIGINT NOT NULL,
+  signer_address VARCHAR(66) NOT NULL,
+  signature_scheme VARCHAR(50) NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, signer_role, signer_index)
+);
+CREATE INDEX IF NOT EXISTS ts_addr_ver_index ON transaction_signers (signer_address, transaction_version DESC);
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- Active balance of every delegator right after each epoch transition, so rewards can be
+-- audited at epoch boundaries without version range queries over delegator_balances.
+CREATE TABLE IF NOT EXISTS epoch_end_delegator_balances (
+  epoch BIGINT NOT NULL,
+  pool_address VARCHAR(66) NOT NULL,
+  delegator_address VARCHAR(66) NOT NULL,
+  shares NUMERIC NOT NULL,
+  amount NUMERIC NOT NULL,
+  epoch_end_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (epoch, pool_address, delegator_address)
+);
+CREATE INDEX IF NOT EXISTS eedb_da_epoch_index ON epoch_end_delegator_balances (delegator_address, epoch);
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+-- Running totals of what each processor has written to each of its tables. A table whose
+-- last_rows_written_at stops moving while the processor keeps advancing has stopped receiving rows.
+CREATE TABLE IF NOT EXISTS indexer_table_stats (
+  processor VARCHAR(100) NOT NULL,
+  table_name VARCHAR(100) NOT NULL,
+  rows_written BIGINT NOT NULL DEFAULT 0,
+  conflicts_ignored BIGINT NOT NULL DEFAULT 0,
+  bytes_written BIGINT NOT NULL DEFAULT 0,
+  last_rows_written_version BIGINT,
+  last_rows_written_at TIMESTAMP,
+  last_updated TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (processor, table_name)
+);
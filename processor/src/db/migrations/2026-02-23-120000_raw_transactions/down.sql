@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS rt_block_height_index;
+DROP TABLE IF EXISTS raw_transactions;
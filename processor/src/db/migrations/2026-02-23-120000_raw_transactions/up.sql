@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- Protobuf-encoded transactions, so that they can be reprocessed without streaming them again
+CREATE TABLE IF NOT EXISTS raw_transactions (
+  transaction_version BIGINT NOT NULL PRIMARY KEY,
+  block_height BIGINT NOT NULL,
+  epoch BIGINT NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  encoded_transaction BYTEA NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS rt_block_height_index ON raw_transactions (block_height);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS do_last_owner_address_index;
+DROP INDEX IF EXISTS do_object_address_index;
+DROP TABLE IF EXISTS deleted_objects;
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- One row per deletion of an object's ObjectCore, with the state the object had when it was deleted
+CREATE TABLE IF NOT EXISTS deleted_objects (
+  transaction_version BIGINT NOT NULL,
+  write_set_change_index BIGINT NOT NULL,
+  object_address VARCHAR(66) NOT NULL,
+  last_owner_address VARCHAR(66) NOT NULL,
+  state_key_hash VARCHAR(66) NOT NULL,
+  last_guid_creation_num NUMERIC NOT NULL,
+  untransferrable BOOLEAN NOT NULL,
+  is_token_burn BOOLEAN NOT NULL,
+  block_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, object_address)
+);
+CREATE INDEX IF NOT EXISTS do_object_address_index ON deleted_objects (object_address);
+CREATE INDEX IF NOT EXISTS do_last_owner_address_index ON deleted_objects (last_owner_address);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS cim_coin_type_hash_index;
+DROP TABLE IF EXISTS coin_info_mutations;
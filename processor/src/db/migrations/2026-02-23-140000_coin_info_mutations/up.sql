@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- Changes to coin metadata over time. coin_infos only keeps the metadata a coin was created with.
+CREATE TABLE IF NOT EXISTS coin_info_mutations (
+  transaction_version BIGINT NOT NULL,
+  coin_type_hash VARCHAR(64) NOT NULL,
+  mutation_type VARCHAR(50) NOT NULL,
+  coin_type VARCHAR(5000) NOT NULL,
+  name VARCHAR(32),
+  symbol VARCHAR(32),
+  decimals INT,
+  previous_name VARCHAR(32),
+  previous_symbol VARCHAR(32),
+  previous_decimals INT,
+  fungible_asset_metadata_address VARCHAR(66),
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, coin_type_hash, mutation_type)
+);
+CREATE INDEX IF NOT EXISTS cim_coin_type_hash_index ON coin_info_mutations (coin_type_hash, transaction_version DESC);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS wscs_block_timestamp_index;
+DROP TABLE IF EXISTS write_set_change_stats;
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+-- One row per transaction with a write set, summarizing its size and the kinds of changes in it
+CREATE TABLE IF NOT EXISTS write_set_change_stats (
+  transaction_version BIGINT NOT NULL PRIMARY KEY,
+  transaction_block_height BIGINT NOT NULL,
+  num_changes BIGINT NOT NULL,
+  key_bytes BIGINT NOT NULL,
+  value_bytes BIGINT NOT NULL,
+  num_resources_written BIGINT NOT NULL,
+  num_resources_deleted BIGINT NOT NULL,
+  resource_bytes BIGINT NOT NULL,
+  resource_counts_by_module JSONB NOT NULL,
+  num_table_items_written BIGINT NOT NULL,
+  num_table_items_deleted BIGINT NOT NULL,
+  table_item_bytes BIGINT NOT NULL,
+  num_modules_written BIGINT NOT NULL,
+  num_modules_deleted BIGINT NOT NULL,
+  module_bytes BIGINT NOT NULL,
+  block_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS wscs_block_timestamp_index ON write_set_change_stats (block_timestamp);
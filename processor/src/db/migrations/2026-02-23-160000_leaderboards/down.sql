@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS cvl_usd_volume_index;
+DROP INDEX IF EXISTS cvl_activity_count_index;
+DROP TABLE IF EXISTS collection_volume_leaderboard;
+DROP INDEX IF EXISTS chc_holder_count_index;
+DROP TABLE IF EXISTS coin_holder_counts;
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- Running aggregates maintained per batch, indexed for top-N queries
+CREATE TABLE IF NOT EXISTS coin_holder_counts (
+  asset_type VARCHAR(1000) NOT NULL PRIMARY KEY,
+  holder_count BIGINT NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS chc_holder_count_index ON coin_holder_counts (holder_count DESC);
+CREATE TABLE IF NOT EXISTS collection_volume_leaderboard (
+  collection_id VARCHAR(66) NOT NULL PRIMARY KEY,
+  activity_count BIGINT NOT NULL,
+  usd_volume NUMERIC NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS cvl_activity_count_index ON collection_volume_leaderboard (activity_count DESC);
+CREATE INDEX IF NOT EXISTS cvl_usd_volume_index ON collection_volume_leaderboard (usd_volume DESC);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS tmd_token_data_id_index;
+DROP INDEX IF EXISTS tmd_module_stage_index;
+DROP TABLE IF EXISTS token_mints_detail;
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- Mint stage and price from the events of configured mint modules, linked to the mint activity
+CREATE TABLE IF NOT EXISTS token_mints_detail (
+  transaction_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  mint_module_address VARCHAR(66) NOT NULL,
+  event_type VARCHAR(5000) NOT NULL,
+  token_data_id VARCHAR(66),
+  activity_event_index BIGINT,
+  mint_stage VARCHAR(1000),
+  paid_amount NUMERIC,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, event_index)
+);
+CREATE INDEX IF NOT EXISTS tmd_token_data_id_index ON token_mints_detail (token_data_id);
+CREATE INDEX IF NOT EXISTS tmd_module_stage_index ON token_mints_detail (mint_module_address, mint_stage);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE account_transactions
+DROP COLUMN IF EXISTS is_sender,
+DROP COLUMN IF EXISTS is_signer,
+DROP COLUMN IF EXISTS txn_success,
+DROP COLUMN IF EXISTS txn_type;
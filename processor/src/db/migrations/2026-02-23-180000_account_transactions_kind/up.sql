@@ -0,0 +1,8 @@
+-- Your SQL goes here
+-- Lets account activity lists be served without joining back to transactions. Rows written
+-- before this migration keep NULLs until reprocessed.
+ALTER TABLE account_transactions
+ADD COLUMN IF NOT EXISTS is_sender BOOLEAN,
+ADD COLUMN IF NOT EXISTS is_signer BOOLEAN,
+ADD COLUMN IF NOT EXISTS txn_success BOOLEAN,
+ADD COLUMN IF NOT EXISTS txn_type VARCHAR(50);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS pch_pool_address_index;
+DROP INDEX IF EXISTS pch_staker_address_index;
+DROP TABLE IF EXISTS pool_commission_history;
+DROP INDEX IF EXISTS poh_pool_address_index;
+DROP TABLE IF EXISTS pool_operator_history;
@@ -0,0 +1,30 @@
+-- Your SQL goes here
+-- Commission changes of delegation pools and staking contracts, in percent
+CREATE TABLE IF NOT EXISTS pool_commission_history (
+  transaction_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  pool_address VARCHAR(66),
+  staker_address VARCHAR(66) NOT NULL,
+  operator_address VARCHAR(66),
+  old_commission_percentage NUMERIC,
+  new_commission_percentage NUMERIC NOT NULL,
+  event_type VARCHAR(1000) NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, event_index)
+);
+CREATE INDEX IF NOT EXISTS pch_pool_address_index ON pool_commission_history (pool_address, transaction_version);
+CREATE INDEX IF NOT EXISTS pch_staker_address_index ON pool_commission_history (staker_address, transaction_version);
+-- Operator switches of stake pools
+CREATE TABLE IF NOT EXISTS pool_operator_history (
+  transaction_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  pool_address VARCHAR(66) NOT NULL,
+  old_operator_address VARCHAR(66) NOT NULL,
+  new_operator_address VARCHAR(66) NOT NULL,
+  event_type VARCHAR(1000) NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, event_index)
+);
+CREATE INDEX IF NOT EXISTS poh_pool_address_index ON pool_operator_history (pool_address, transaction_version);
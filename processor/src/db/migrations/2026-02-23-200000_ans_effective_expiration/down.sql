@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE current_ans_lookup_v2 DROP COLUMN IF EXISTS effective_expiration_timestamp;
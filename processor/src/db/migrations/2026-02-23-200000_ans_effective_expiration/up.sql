@@ -0,0 +1,17 @@
+-- Your SQL goes here
+-- When the name actually expires: its own expiration, or its domain's for subdomains that follow
+-- the domain's expiration. Null while the domain of such a subdomain isn't indexed.
+ALTER TABLE current_ans_lookup_v2
+ADD COLUMN IF NOT EXISTS effective_expiration_timestamp TIMESTAMP;
+UPDATE current_ans_lookup_v2
+SET effective_expiration_timestamp = expiration_timestamp
+WHERE subdomain = ''
+  OR subdomain_expiration_policy IS DISTINCT FROM 1;
+UPDATE current_ans_lookup_v2 AS sub
+SET effective_expiration_timestamp = dom.expiration_timestamp
+FROM current_ans_lookup_v2 AS dom
+WHERE sub.subdomain != ''
+  AND sub.subdomain_expiration_policy = 1
+  AND dom.domain = sub.domain
+  AND dom.subdomain = ''
+  AND dom.token_standard = sub.token_standard;
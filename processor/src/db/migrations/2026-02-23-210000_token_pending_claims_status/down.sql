@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE current_token_pending_claims
+DROP COLUMN IF EXISTS status,
+DROP COLUMN IF EXISTS resolution_transaction_version;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+-- pending, claimed or canceled. Offers resolved before this was added have no status.
+ALTER TABLE current_token_pending_claims
+ADD COLUMN IF NOT EXISTS status VARCHAR(10),
+ADD COLUMN IF NOT EXISTS resolution_transaction_version BIGINT;
+UPDATE current_token_pending_claims
+SET status = 'pending'
+WHERE amount > 0;
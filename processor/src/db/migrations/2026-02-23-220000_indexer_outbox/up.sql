@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- One row per saved checkpoint of a processor, written in the same transaction as
+-- processor_status, so downstream jobs get exactly one signal per committed version range.
+-- published_at is set by the relay once the entry was delivered.
+CREATE TABLE IF NOT EXISTS indexer_outbox (
+  id BIGSERIAL PRIMARY KEY,
+  processor VARCHAR(100) NOT NULL,
+  start_version BIGINT NOT NULL,
+  end_version BIGINT NOT NULL,
+  tables_touched TEXT[] NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  published_at TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS io_unpublished_index ON indexer_outbox (processor, id)
+WHERE published_at IS NULL;
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS ev_module_index;
+ALTER TABLE events
+DROP COLUMN IF EXISTS module_address,
+DROP COLUMN IF EXISTS module_name,
+DROP COLUMN IF EXISTS struct_name,
+DROP COLUMN IF EXISTS generic_args;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+-- Parts of the event's struct type, null for events of other types and for events indexed
+-- before these were added
+ALTER TABLE events
+ADD COLUMN IF NOT EXISTS module_address VARCHAR(66),
+ADD COLUMN IF NOT EXISTS module_name VARCHAR(255),
+ADD COLUMN IF NOT EXISTS struct_name VARCHAR(255),
+ADD COLUMN IF NOT EXISTS generic_args TEXT;
+CREATE INDEX IF NOT EXISTS ev_module_index ON events (module_address, module_name, struct_name);
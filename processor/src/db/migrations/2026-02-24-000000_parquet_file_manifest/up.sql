@@ -0,0 +1,20 @@
+-- Your SQL goes here
+-- One row per file a parquet processor uploaded, so loaders can pick up new files by id instead
+-- of listing the bucket. Versions and block timestamps are of the rows in the file.
+CREATE TABLE IF NOT EXISTS parquet_file_manifest (
+  id BIGSERIAL PRIMARY KEY,
+  processor VARCHAR(100) NOT NULL,
+  table_name VARCHAR(100) NOT NULL,
+  bucket_name VARCHAR(255) NOT NULL,
+  file_path TEXT NOT NULL,
+  file_format VARCHAR(10) NOT NULL,
+  num_rows BIGINT NOT NULL,
+  min_version BIGINT NOT NULL,
+  max_version BIGINT NOT NULL,
+  min_block_timestamp TIMESTAMP,
+  max_block_timestamp TIMESTAMP,
+  file_size_bytes BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  UNIQUE (bucket_name, file_path)
+);
+CREATE INDEX IF NOT EXISTS pfm_table_index ON parquet_file_manifest (table_name, id);
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- Scheme each auth key was derived with, from the signatures and key rotations it was used in.
+-- public_key_preview is the start of the public key, enough to tell keys apart.
+CREATE TABLE IF NOT EXISTS auth_key_schemes (
+  auth_key VARCHAR(66) PRIMARY KEY NOT NULL,
+  auth_scheme VARCHAR(20) NOT NULL,
+  public_key_preview VARCHAR(66) NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS nt_token_data_id_index;
+DROP INDEX IF EXISTS nt_from_address_index;
+DROP INDEX IF EXISTS nt_to_address_index;
+DROP TABLE IF EXISTS nft_transfers;
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+-- Token transfers between accounts, v1 withdraw/deposit pairs and claims unified with v2 object transfers
+CREATE TABLE IF NOT EXISTS nft_transfers (
+  transaction_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  token_data_id VARCHAR(66) NOT NULL,
+  property_version_v1 NUMERIC NOT NULL,
+  from_address VARCHAR(66) NOT NULL,
+  to_address VARCHAR(66) NOT NULL,
+  token_amount NUMERIC NOT NULL,
+  token_standard VARCHAR(10) NOT NULL,
+  transfer_type VARCHAR(20) NOT NULL,
+  entry_function_id_str VARCHAR(1000),
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, event_index)
+);
+CREATE INDEX IF NOT EXISTS nt_token_data_id_index ON nft_transfers (token_data_id, transaction_version);
+CREATE INDEX IF NOT EXISTS nt_from_address_index ON nft_transfers (from_address, transaction_version);
+CREATE INDEX IF NOT EXISTS nt_to_address_index ON nft_transfers (to_address, transaction_version);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS vte_indexed_type_index;
+DROP TABLE IF EXISTS validator_transaction_events;
+DROP INDEX IF EXISTS vt_epoch_index;
+DROP INDEX IF EXISTS vt_type_index;
+DROP TABLE IF EXISTS validator_transactions;
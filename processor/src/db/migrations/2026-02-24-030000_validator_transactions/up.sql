@@ -0,0 +1,36 @@
+-- Your SQL goes here
+-- Validator transactions (JWK updates, DKG results) with the fields of their payload
+CREATE TABLE IF NOT EXISTS validator_transactions (
+  transaction_version BIGINT NOT NULL PRIMARY KEY,
+  block_height BIGINT NOT NULL,
+  epoch BIGINT NOT NULL,
+  validator_transaction_type VARCHAR(50) NOT NULL,
+  jwk_issuer TEXT,
+  jwk_version BIGINT,
+  num_jwks BIGINT,
+  jwk_signer_indices JSONB,
+  dkg_epoch BIGINT,
+  dkg_author VARCHAR(66),
+  dkg_transcript_bytes BIGINT,
+  num_events BIGINT NOT NULL,
+  is_transaction_success BOOLEAN NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS vt_type_index ON validator_transactions (validator_transaction_type, transaction_version);
+CREATE INDEX IF NOT EXISTS vt_epoch_index ON validator_transactions (epoch);
+-- Events emitted by validator transactions
+CREATE TABLE IF NOT EXISTS validator_transaction_events (
+  transaction_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  sequence_number BIGINT NOT NULL,
+  creation_number BIGINT NOT NULL,
+  account_address VARCHAR(66) NOT NULL,
+  type TEXT NOT NULL,
+  indexed_type VARCHAR(300) NOT NULL,
+  data JSONB NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, event_index)
+);
+CREATE INDEX IF NOT EXISTS vte_indexed_type_index ON validator_transaction_events (indexed_type, transaction_version);
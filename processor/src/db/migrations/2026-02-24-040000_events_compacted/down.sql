@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS ec_indexed_type_window_index;
+DROP TABLE IF EXISTS events_compacted;
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- Last event per account and type in each time window, for event types compacted by the events
+-- processor's compaction rules. window_start is the block timestamp rounded down to the window.
+CREATE TABLE IF NOT EXISTS events_compacted (
+  account_address VARCHAR(66) NOT NULL,
+  type TEXT NOT NULL,
+  window_start TIMESTAMP NOT NULL,
+  transaction_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  sequence_number BIGINT NOT NULL,
+  creation_number BIGINT NOT NULL,
+  transaction_block_height BIGINT NOT NULL,
+  data JSONB NOT NULL,
+  indexed_type VARCHAR(300) NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (account_address, type, window_start)
+);
+CREATE INDEX IF NOT EXISTS ec_indexed_type_window_index ON events_compacted (indexed_type, window_start);
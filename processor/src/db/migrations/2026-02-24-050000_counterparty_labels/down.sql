@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE fungible_asset_activities DROP COLUMN IF EXISTS counterparty_label;
+ALTER TABLE account_transactions DROP COLUMN IF EXISTS counterparty_label;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- Label of the known entity, like an exchange or a bridge, the account dealt with in the
+-- transaction, from the operator's address labels. Rows written before this migration keep NULLs.
+ALTER TABLE account_transactions
+ADD COLUMN IF NOT EXISTS counterparty_label VARCHAR(100);
+ALTER TABLE fungible_asset_activities
+ADD COLUMN IF NOT EXISTS counterparty_label VARCHAR(100);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS csfe_owner_coin_type_index;
+DROP TABLE IF EXISTS coin_store_freeze_events;
+ALTER TABLE current_coin_balances DROP COLUMN IF EXISTS is_frozen;
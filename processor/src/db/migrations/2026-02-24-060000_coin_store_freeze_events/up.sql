@@ -0,0 +1,21 @@
+-- Your SQL goes here
+-- Whether the CoinStore is frozen. Rows written before this migration default to unfrozen.
+ALTER TABLE current_coin_balances
+ADD COLUMN IF NOT EXISTS is_frozen BOOLEAN NOT NULL DEFAULT FALSE;
+-- CoinStores being frozen or unfrozen. The framework emits no event for it, so a row is written
+-- whenever a CoinStore is written with another frozen flag than the last one recorded.
+CREATE TABLE IF NOT EXISTS coin_store_freeze_events (
+  transaction_version BIGINT NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  coin_type_hash VARCHAR(64) NOT NULL,
+  coin_type VARCHAR(5000) NOT NULL,
+  is_frozen BOOLEAN NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, owner_address, coin_type_hash)
+);
+CREATE INDEX IF NOT EXISTS csfe_owner_coin_type_index ON coin_store_freeze_events (
+  owner_address,
+  coin_type_hash,
+  transaction_version DESC
+);
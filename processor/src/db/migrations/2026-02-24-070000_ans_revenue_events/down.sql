@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS are_payer_address_index;
+DROP INDEX IF EXISTS are_transaction_timestamp_index;
+DROP TABLE IF EXISTS ans_revenue_events;
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+-- Fees paid to register and renew ANS v2 names, from RegisterNameEvent and RenewNameEvent. The
+-- payer is the transaction sender. duration_secs is how much the expiration moved, NULL when a
+-- renewed name's previous expiration isn't known.
+CREATE TABLE IF NOT EXISTS ans_revenue_events (
+  transaction_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  revenue_type VARCHAR(20) NOT NULL,
+  payer_address VARCHAR(66) NOT NULL,
+  domain VARCHAR(64) NOT NULL,
+  subdomain VARCHAR(64) NOT NULL,
+  token_name VARCHAR(140) NOT NULL,
+  amount_octas NUMERIC,
+  expiration_timestamp TIMESTAMP NOT NULL,
+  duration_secs BIGINT,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, event_index)
+);
+CREATE INDEX IF NOT EXISTS are_transaction_timestamp_index ON ans_revenue_events (transaction_timestamp);
+CREATE INDEX IF NOT EXISTS are_payer_address_index ON ans_revenue_events (payer_address);
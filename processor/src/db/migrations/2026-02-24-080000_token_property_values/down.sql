@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS tpv_property_key_value_index;
+DROP TABLE IF EXISTS token_property_values;
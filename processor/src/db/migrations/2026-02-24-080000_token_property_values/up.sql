@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- Entries of the current token datas' property maps, one row per key, for filtering tokens by
+-- trait without scanning token_properties. Values are as displayed in token_properties.
+-- property_type is the Move type of the value, e.g. 0x1::string::String or u64.
+CREATE TABLE IF NOT EXISTS token_property_values (
+  token_data_id VARCHAR(66) NOT NULL,
+  property_key VARCHAR(128) NOT NULL,
+  property_type VARCHAR(128),
+  property_value VARCHAR(512) NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (token_data_id, property_key)
+);
+CREATE INDEX IF NOT EXISTS tpv_property_key_value_index ON token_property_values (property_key, property_value);
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- Running counts per owner maintained per batch, so portfolios don't count current_objects
+CREATE TABLE IF NOT EXISTS current_object_counts_by_owner (
+  owner_address VARCHAR(66) NOT NULL PRIMARY KEY,
+  object_count BIGINT NOT NULL,
+  token_count BIGINT NOT NULL,
+  fa_store_count BIGINT NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
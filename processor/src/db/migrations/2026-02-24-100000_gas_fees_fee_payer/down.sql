@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS gf_fee_payer_address_index;
+ALTER TABLE gas_fees
+DROP COLUMN IF EXISTS fee_payer_address,
+DROP COLUMN IF EXISTS is_sponsored;
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- The account the fee is charged to: the fee payer of sponsored transactions, the sender otherwise
+ALTER TABLE gas_fees
+ADD COLUMN IF NOT EXISTS fee_payer_address VARCHAR(66),
+ADD COLUMN IF NOT EXISTS is_sponsored BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE gas_fees
+SET fee_payer_address = COALESCE(gas_fee_payer_address, owner_address),
+  is_sponsored = gas_fee_payer_address IS NOT NULL
+  AND gas_fee_payer_address IS DISTINCT FROM owner_address;
+CREATE INDEX IF NOT EXISTS gf_fee_payer_address_index ON gas_fees (fee_payer_address);
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- Rows kept out of their table by a data quality rule with quarantine set
+CREATE TABLE IF NOT EXISTS quarantined_rows (
+  id BIGSERIAL PRIMARY KEY,
+  table_name VARCHAR(100) NOT NULL,
+  rule_name VARCHAR(100) NOT NULL,
+  row_data JSONB NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS qr_table_name_rule_name_index ON quarantined_rows (table_name, rule_name);
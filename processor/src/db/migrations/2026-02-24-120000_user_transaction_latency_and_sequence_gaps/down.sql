@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS sender_sequence_number_gaps;
+ALTER TABLE user_transactions DROP COLUMN IF EXISTS latency_from_expiration;
@@ -0,0 +1,25 @@
+-- Your SQL goes here
+-- Milliseconds from commit to the transaction's expiration, how long it could still have waited
+ALTER TABLE user_transactions
+ADD COLUMN IF NOT EXISTS latency_from_expiration BIGINT;
+UPDATE user_transactions
+SET latency_from_expiration = FLOOR(
+    EXTRACT(
+      EPOCH
+      FROM (expiration_timestamp_secs - "timestamp")
+    ) * 1000
+  )::BIGINT
+WHERE latency_from_expiration IS NULL;
+-- Sequence numbers a sender skipped between two indexed transactions
+CREATE TABLE IF NOT EXISTS sender_sequence_number_gaps (
+  sender VARCHAR(66) NOT NULL,
+  sequence_number BIGINT NOT NULL,
+  previous_sequence_number BIGINT NOT NULL,
+  missing_count BIGINT NOT NULL,
+  transaction_version BIGINT NOT NULL,
+  previous_transaction_version BIGINT NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (sender, sequence_number)
+);
+CREATE INDEX IF NOT EXISTS ssng_transaction_version_index ON sender_sequence_number_gaps (transaction_version);
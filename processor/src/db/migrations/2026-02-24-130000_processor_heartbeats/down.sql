@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP VIEW IF EXISTS processor_staleness;
+DROP TABLE IF EXISTS processor_heartbeats;
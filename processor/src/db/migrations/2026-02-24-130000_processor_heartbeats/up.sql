@@ -0,0 +1,25 @@
+-- Your SQL goes here
+-- Written on every save of a processor's status, so a stalled processor can be told apart from
+-- one that's keeping up with a quiet chain. Times are UTC.
+CREATE TABLE IF NOT EXISTS processor_heartbeats (
+  processor VARCHAR(100) UNIQUE PRIMARY KEY NOT NULL,
+  last_success_version BIGINT NOT NULL,
+  last_batch_size BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NULL,
+  last_heartbeat_at TIMESTAMP NOT NULL
+);
+CREATE OR REPLACE VIEW processor_staleness AS
+SELECT processor,
+  last_success_version,
+  last_batch_size,
+  last_transaction_timestamp,
+  last_heartbeat_at,
+  EXTRACT(
+    EPOCH
+    FROM (NOW() AT TIME ZONE 'UTC') - last_heartbeat_at
+  )::BIGINT AS seconds_since_heartbeat,
+  EXTRACT(
+    EPOCH
+    FROM (NOW() AT TIME ZONE 'UTC') - last_transaction_timestamp
+  )::BIGINT AS seconds_behind_chain
+FROM processor_heartbeats;
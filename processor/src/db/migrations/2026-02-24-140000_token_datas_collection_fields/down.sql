@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS cur_td2_creator_index;
+ALTER TABLE current_token_datas_v2 DROP COLUMN IF EXISTS creator_address,
+  DROP COLUMN IF EXISTS collection_mutable_description,
+  DROP COLUMN IF EXISTS collection_mutable_uri,
+  DROP COLUMN IF EXISTS royalty_bps;
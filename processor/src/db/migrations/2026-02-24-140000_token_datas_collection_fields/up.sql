@@ -0,0 +1,25 @@
+-- Your SQL goes here
+-- Fields of the token's collection, kept up to date as the collection changes, so that tokens
+-- can be filtered by them without joining current_collections_v2
+ALTER TABLE current_token_datas_v2
+ADD COLUMN IF NOT EXISTS creator_address VARCHAR(66),
+  ADD COLUMN IF NOT EXISTS collection_mutable_description BOOLEAN,
+  ADD COLUMN IF NOT EXISTS collection_mutable_uri BOOLEAN,
+  -- Royalty in basis points, of the token or else of its collection
+  ADD COLUMN IF NOT EXISTS royalty_bps BIGINT;
+UPDATE current_token_datas_v2 AS token
+SET creator_address = collection.creator_address,
+  collection_mutable_description = collection.mutable_description,
+  collection_mutable_uri = collection.mutable_uri
+FROM current_collections_v2 AS collection
+WHERE collection.collection_id = token.collection_id
+  AND token.creator_address IS NULL;
+UPDATE current_token_datas_v2 AS token
+SET royalty_bps = FLOOR(
+    royalty.royalty_points_numerator * 10000 / royalty.royalty_points_denominator
+  )::BIGINT
+FROM current_token_royalty_v1 AS royalty
+WHERE royalty.token_data_id = token.token_data_id
+  AND royalty.royalty_points_denominator > 0
+  AND token.royalty_bps IS NULL;
+CREATE INDEX IF NOT EXISTS cur_td2_creator_index ON current_token_datas_v2 (creator_address);
@@ -0,0 +1,12 @@
+-- Your SQL goes here
+-- Last file each table of a parquet processor uploaded, keyed like the table's processor_status
+-- row. end_version is the last version the file covers, so a processor whose status save failed
+-- after the upload resumes after it instead of uploading the range again.
+CREATE TABLE IF NOT EXISTS parquet_upload_checkpoints (
+  processor VARCHAR(100) UNIQUE PRIMARY KEY NOT NULL,
+  bucket_name VARCHAR(255) NOT NULL,
+  file_path TEXT NOT NULL,
+  max_version BIGINT NOT NULL,
+  end_version BIGINT NOT NULL,
+  last_updated TIMESTAMP NOT NULL DEFAULT NOW()
+);
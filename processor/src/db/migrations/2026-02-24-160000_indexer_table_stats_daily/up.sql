@@ -0,0 +1,16 @@
+-- Your SQL goes here
+-- What each processor wrote to each of its tables per UTC day, next to the running totals in
+-- indexer_table_stats. conflicts_ignored over rows_written + conflicts_ignored is the share of
+-- rows that hit a conflict and were dropped, which points at tables whose primary key or batching
+-- needs tuning.
+CREATE TABLE IF NOT EXISTS indexer_table_stats_daily (
+  processor VARCHAR(100) NOT NULL,
+  table_name VARCHAR(100) NOT NULL,
+  day DATE NOT NULL,
+  rows_written BIGINT NOT NULL DEFAULT 0,
+  conflicts_ignored BIGINT NOT NULL DEFAULT 0,
+  bytes_written BIGINT NOT NULL DEFAULT 0,
+  last_updated TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (processor, table_name, day)
+);
+CREATE INDEX IF NOT EXISTS itsd_day_index ON indexer_table_stats_daily (day);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS chc_owner_count_index;
+ALTER TABLE coin_holder_counts DROP COLUMN IF EXISTS owner_count;
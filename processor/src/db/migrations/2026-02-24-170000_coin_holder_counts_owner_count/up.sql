@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- Distinct owners with a positive balance per asset, next to the number of holding stores. Like
+-- holder_count, it's an absolute count: the fungible asset processor recomputes both from
+-- current_fungible_asset_balances with its first batch, then adds each batch's changes.
+ALTER TABLE coin_holder_counts
+ADD COLUMN IF NOT EXISTS owner_count BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX IF NOT EXISTS chc_owner_count_index ON coin_holder_counts (owner_count DESC);
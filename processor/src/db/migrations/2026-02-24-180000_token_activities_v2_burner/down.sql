@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS ta2_burner_index;
+ALTER TABLE token_activities_v2
+DROP COLUMN IF EXISTS burner_address,
+DROP COLUMN IF EXISTS is_creator_burn;
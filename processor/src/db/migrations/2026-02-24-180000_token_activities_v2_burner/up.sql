@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- Who executed a burn, and whether it went through the creator's burn authority
+ALTER TABLE token_activities_v2
+ADD COLUMN IF NOT EXISTS burner_address VARCHAR(66),
+ADD COLUMN IF NOT EXISTS is_creator_burn BOOLEAN;
+CREATE INDEX IF NOT EXISTS ta2_burner_index ON token_activities_v2 (burner_address)
+WHERE burner_address IS NOT NULL;
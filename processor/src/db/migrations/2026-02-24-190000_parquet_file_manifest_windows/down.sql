@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS pfm_window_index;
+ALTER TABLE parquet_file_manifest
+DROP COLUMN IF EXISTS window_start_version,
+DROP COLUMN IF EXISTS window_end_version;
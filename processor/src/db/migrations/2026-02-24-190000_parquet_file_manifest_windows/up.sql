@@ -0,0 +1,9 @@
+-- Your SQL goes here
+-- Closed [window_start_version, window_end_version] range of transactions each file covers, which
+-- can be wider than its rows. Consecutive files of a table have adjacent windows, so incremental
+-- loads can tell a range with no rows from one that hasn't been uploaded yet. Files uploaded
+-- before this migration have no window.
+ALTER TABLE parquet_file_manifest
+ADD COLUMN IF NOT EXISTS window_start_version BIGINT,
+ADD COLUMN IF NOT EXISTS window_end_version BIGINT;
+CREATE INDEX IF NOT EXISTS pfm_window_index ON parquet_file_manifest (bucket_name, table_name, window_start_version);
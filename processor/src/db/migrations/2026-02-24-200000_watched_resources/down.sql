@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS wr_address_index;
+DROP INDEX IF EXISTS wr_pattern_index;
+DROP TABLE IF EXISTS watched_resources;
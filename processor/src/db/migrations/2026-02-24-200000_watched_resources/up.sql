@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- Resources written or deleted whose type matches a pattern of the resource watcher processor,
+-- with their data decoded as JSON
+CREATE TABLE IF NOT EXISTS watched_resources (
+  transaction_version BIGINT NOT NULL,
+  write_set_change_index BIGINT NOT NULL,
+  transaction_block_height BIGINT NOT NULL,
+  resource_address VARCHAR(66) NOT NULL,
+  resource_type TEXT NOT NULL,
+  matched_pattern TEXT NOT NULL,
+  data JSONB,
+  is_deleted BOOLEAN NOT NULL,
+  state_key_hash VARCHAR(66) NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, write_set_change_index)
+);
+CREATE INDEX IF NOT EXISTS wr_pattern_index ON watched_resources (matched_pattern, transaction_version);
+CREATE INDEX IF NOT EXISTS wr_address_index ON watched_resources (resource_address, transaction_version);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP VIEW IF EXISTS grafana_error_counts_hourly;
+DROP VIEW IF EXISTS grafana_table_rows_hourly;
+DROP VIEW IF EXISTS grafana_processor_lag;
+DROP INDEX IF EXISTS itsh_hour_index;
+DROP TABLE IF EXISTS indexer_table_stats_hourly;
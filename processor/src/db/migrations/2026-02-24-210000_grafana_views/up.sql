@@ -0,0 +1,51 @@
+-- Your SQL goes here
+-- What each processor wrote to each of its tables per UTC hour, like indexer_table_stats_daily
+CREATE TABLE IF NOT EXISTS indexer_table_stats_hourly (
+  processor VARCHAR(100) NOT NULL,
+  table_name VARCHAR(100) NOT NULL,
+  hour TIMESTAMP NOT NULL,
+  rows_written BIGINT NOT NULL DEFAULT 0,
+  conflicts_ignored BIGINT NOT NULL DEFAULT 0,
+  bytes_written BIGINT NOT NULL DEFAULT 0,
+  last_updated TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (processor, table_name, hour)
+);
+CREATE INDEX IF NOT EXISTS itsh_hour_index ON indexer_table_stats_hourly (hour);
+-- Views for Grafana's Postgres data source. Time series views name their time column `time`,
+-- which Grafana picks up without a custom query. Times are UTC.
+--
+-- How far each processor is behind the chain, and behind the processor furthest ahead
+CREATE OR REPLACE VIEW grafana_processor_lag AS
+SELECT ps.processor,
+  ps.last_success_version,
+  MAX(ps.last_success_version) OVER () - ps.last_success_version AS versions_behind_leader,
+  ps.last_transaction_timestamp,
+  EXTRACT(
+    EPOCH
+    FROM (NOW() AT TIME ZONE 'UTC') - ps.last_transaction_timestamp
+  )::BIGINT AS seconds_behind_chain,
+  ph.last_heartbeat_at,
+  EXTRACT(
+    EPOCH
+    FROM (NOW() AT TIME ZONE 'UTC') - ph.last_heartbeat_at
+  )::BIGINT AS seconds_since_heartbeat
+FROM processor_status ps
+  LEFT JOIN processor_heartbeats ph ON ps.processor = ph.processor;
+-- Rows each processor wrote to each of its tables per hour
+CREATE OR REPLACE VIEW grafana_table_rows_hourly AS
+SELECT hour AS time,
+  processor,
+  table_name,
+  rows_written,
+  conflicts_ignored,
+  bytes_written
+FROM indexer_table_stats_hourly;
+-- Rows kept out of their table by data quality rules per hour. Processor failures aren't
+-- stored, they're counted in the indexer_processor_restart_count metric.
+CREATE OR REPLACE VIEW grafana_error_counts_hourly AS
+SELECT date_trunc('hour', inserted_at) AS time,
+  table_name,
+  rule_name,
+  COUNT(*) AS error_count
+FROM quarantined_rows
+GROUP BY 1, 2, 3;
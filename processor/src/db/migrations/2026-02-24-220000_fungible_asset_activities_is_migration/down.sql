@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE fungible_asset_activities DROP COLUMN IF EXISTS is_migration;
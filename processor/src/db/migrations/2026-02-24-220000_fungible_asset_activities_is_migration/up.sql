@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- Deposits made by migrating a coin store to its primary fungible store, which balance change
+-- consumers can leave out of user-visible history
+ALTER TABLE fungible_asset_activities
+ADD COLUMN IF NOT EXISTS is_migration BOOLEAN NOT NULL DEFAULT FALSE;
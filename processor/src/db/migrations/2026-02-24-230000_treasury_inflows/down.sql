@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS ti_source_module_index;
+DROP INDEX IF EXISTS ti_treasury_asset_index;
+DROP TABLE IF EXISTS treasury_inflows;
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+-- Coin and fungible asset deposits into the addresses configured for the treasury flows
+-- processor, attributed to the withdrawing owner and the initiating module
+CREATE TABLE IF NOT EXISTS treasury_inflows (
+  transaction_version BIGINT NOT NULL,
+  event_index BIGINT NOT NULL,
+  treasury_address VARCHAR(66) NOT NULL,
+  asset_type VARCHAR(1000) NOT NULL,
+  amount NUMERIC NOT NULL,
+  token_standard VARCHAR(10) NOT NULL,
+  source_address VARCHAR(66),
+  entry_function_id_str VARCHAR(1000),
+  source_module VARCHAR(1000),
+  block_height BIGINT NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, event_index)
+);
+CREATE INDEX IF NOT EXISTS ti_treasury_asset_index ON treasury_inflows (treasury_address, asset_type, transaction_version);
+CREATE INDEX IF NOT EXISTS ti_source_module_index ON treasury_inflows (source_module, transaction_version);
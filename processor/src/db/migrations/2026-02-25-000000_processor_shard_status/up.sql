@@ -0,0 +1,13 @@
+-- Your SQL goes here
+-- Progress of each shard of a processor running in sharded mode. processor_status holds the last
+-- version all shards got through.
+CREATE TABLE IF NOT EXISTS processor_shard_status (
+  processor VARCHAR(100) NOT NULL,
+  shard_index BIGINT NOT NULL,
+  range_starting_version BIGINT NOT NULL,
+  range_ending_version BIGINT NOT NULL,
+  last_success_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NULL,
+  last_updated TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (processor, shard_index)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS gp_stake_weighted_participation_rate_index;
+DROP TABLE IF EXISTS governance_participation;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- Voting activity of each voter, from proposal_votes. Proposals only count once someone voted
+-- on them, and only from the voter's first vote on. The stake weighted rate weighs each proposal
+-- by the votes cast on it.
+CREATE TABLE IF NOT EXISTS governance_participation (
+  voter_address VARCHAR(66) NOT NULL PRIMARY KEY,
+  proposals_voted BIGINT NOT NULL,
+  proposals_eligible BIGINT NOT NULL,
+  num_votes NUMERIC NOT NULL,
+  participation_rate NUMERIC NOT NULL,
+  stake_weighted_participation_rate NUMERIC NOT NULL,
+  first_proposal_id BIGINT NOT NULL,
+  last_proposal_id BIGINT NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS gp_stake_weighted_participation_rate_index ON governance_participation (stake_weighted_participation_rate);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS cur_col_stats2_creator_index;
+DROP TABLE IF EXISTS current_collection_stats_v2;
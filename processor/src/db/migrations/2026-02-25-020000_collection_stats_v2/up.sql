@@ -0,0 +1,80 @@
+-- Your SQL goes here
+-- Aggregates of each collection, to which the token processor adds what each batch changes.
+-- current_supply and distinct_owners are over the current ownerships with a positive amount,
+-- total_minted and total_burned over the collection's mint and burn activities.
+-- Filled for the existing collections here.
+CREATE TABLE IF NOT EXISTS current_collection_stats_v2 (
+  collection_id VARCHAR(66) NOT NULL PRIMARY KEY,
+  creator_address VARCHAR(66) NOT NULL,
+  collection_name VARCHAR(128) NOT NULL,
+  current_supply NUMERIC NOT NULL,
+  total_minted NUMERIC NOT NULL,
+  total_burned NUMERIC NOT NULL,
+  distinct_owners BIGINT NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+WITH holdings AS (
+  SELECT token.collection_id,
+    SUM(ownership.amount) AS current_supply,
+    COUNT(DISTINCT ownership.owner_address) AS distinct_owners,
+    MAX(ownership.last_transaction_version) AS last_transaction_version,
+    MAX(ownership.last_transaction_timestamp) AS last_transaction_timestamp
+  FROM current_token_datas_v2 AS token
+    JOIN current_token_ownerships_v2 AS ownership USING (token_data_id)
+  WHERE ownership.amount > 0
+  GROUP BY token.collection_id
+),
+supply_changes AS (
+  SELECT token.collection_id,
+    SUM(activity.token_amount) FILTER (
+      WHERE activity.type IN (
+          '0x3::token::MintTokenEvent',
+          '0x3::token::Mint',
+          '0x4::collection::MintEvent',
+          '0x4::collection::Mint'
+        )
+    ) AS total_minted,
+    SUM(activity.token_amount) FILTER (
+      WHERE activity.type IN (
+          '0x3::token::BurnTokenEvent',
+          '0x3::token::Burn',
+          '0x4::collection::BurnEvent',
+          '0x4::collection::Burn'
+        )
+    ) AS total_burned
+  FROM current_token_datas_v2 AS token
+    JOIN token_activities_v2 AS activity USING (token_data_id)
+  GROUP BY token.collection_id
+)
+INSERT INTO current_collection_stats_v2 (
+    collection_id,
+    creator_address,
+    collection_name,
+    current_supply,
+    total_minted,
+    total_burned,
+    distinct_owners,
+    last_transaction_version,
+    last_transaction_timestamp
+  )
+SELECT collection.collection_id,
+  collection.creator_address,
+  collection.collection_name,
+  COALESCE(holdings.current_supply, 0),
+  COALESCE(supply_changes.total_minted, 0),
+  COALESCE(supply_changes.total_burned, 0),
+  COALESCE(holdings.distinct_owners, 0),
+  GREATEST(
+    collection.last_transaction_version,
+    holdings.last_transaction_version
+  ),
+  GREATEST(
+    collection.last_transaction_timestamp,
+    holdings.last_transaction_timestamp
+  )
+FROM current_collections_v2 AS collection
+  LEFT JOIN holdings USING (collection_id)
+  LEFT JOIN supply_changes USING (collection_id) ON CONFLICT (collection_id) DO NOTHING;
+CREATE INDEX IF NOT EXISTS cur_col_stats2_creator_index ON current_collection_stats_v2 (creator_address);
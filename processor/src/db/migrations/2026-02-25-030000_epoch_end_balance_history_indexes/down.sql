@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS db_pool_da_version_index;
+DROP INDEX IF EXISTS dspb_pool_version_index;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Epoch-end snapshots read the latest balances of a batch's pools before its first version
+CREATE INDEX IF NOT EXISTS db_pool_da_version_index ON delegator_balances (pool_address, delegator_address, transaction_version DESC);
+CREATE INDEX IF NOT EXISTS dspb_pool_version_index ON delegated_staking_pool_balances (staking_pool_address, transaction_version DESC);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+-- Balances reclassified as pending_inactive_shares stay so
+ALTER TABLE current_delegated_staking_pool_balances DROP COLUMN IF EXISTS observed_lockup_cycle;
@@ -0,0 +1,54 @@
+-- Your SQL goes here
+-- Lockup cycle whose inactive pool holds the pending_inactive shares, looked up for inactive pools
+-- written without their delegation pool
+ALTER TABLE current_delegated_staking_pool_balances
+ADD COLUMN IF NOT EXISTS observed_lockup_cycle BIGINT;
+
+-- The observed lockup cycle is the highest one in the delegation pool's inactive_shares table, but
+-- only current_table_items has its keys. Pools are only backfilled, and their balances reclassified,
+-- where the default processor writes to this database. Elsewhere they're fixed as they're written.
+CREATE TEMP TABLE observed_lockup_cycle_pools AS
+SELECT DISTINCT ON (table_handle) table_handle AS inactive_table_handle,
+  (decoded_key->>'index')::BIGINT AS lockup_cycle,
+  '0x' || LPAD(
+    SUBSTRING(decoded_value->'shares'->'inner'->>'handle' FROM 3),
+    64,
+    '0'
+  ) AS shares_table_handle
+FROM current_table_items
+WHERE NOT is_deleted
+  AND table_handle IN (
+    SELECT inactive_table_handle
+    FROM current_delegated_staking_pool_balances
+  )
+ORDER BY table_handle,
+  (decoded_key->>'index')::BIGINT DESC;
+
+UPDATE current_delegated_staking_pool_balances pools
+SET observed_lockup_cycle = cycles.lockup_cycle
+FROM observed_lockup_cycle_pools cycles
+WHERE pools.inactive_table_handle = cycles.inactive_table_handle
+  AND pools.observed_lockup_cycle IS NULL;
+
+-- Balances in the observed cycle's pool were stored as inactive_shares. The ones written as
+-- pending_inactive_shares since are newer.
+DELETE FROM current_delegator_balances balances USING observed_lockup_cycle_pools cycles
+WHERE balances.pool_type = 'inactive_shares'
+  AND balances.parent_table_handle = cycles.inactive_table_handle
+  AND balances.table_handle = cycles.shares_table_handle
+  AND EXISTS (
+    SELECT 1
+    FROM current_delegator_balances pending
+    WHERE pending.pool_type = 'pending_inactive_shares'
+      AND pending.delegator_address = balances.delegator_address
+      AND pending.pool_address = balances.pool_address
+      AND pending.table_handle = balances.table_handle
+  );
+UPDATE current_delegator_balances balances
+SET pool_type = 'pending_inactive_shares'
+FROM observed_lockup_cycle_pools cycles
+WHERE balances.pool_type = 'inactive_shares'
+  AND balances.parent_table_handle = cycles.inactive_table_handle
+  AND balances.table_handle = cycles.shares_table_handle;
+
+DROP TABLE observed_lockup_cycle_pools;
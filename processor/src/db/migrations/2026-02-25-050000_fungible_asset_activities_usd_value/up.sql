@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE fungible_asset_activities
+ADD COLUMN IF NOT EXISTS usd_value NUMERIC;
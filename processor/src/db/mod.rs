@@ -1,2 +1,14 @@
 pub mod backfill_processor_status;
+pub mod connection_pool;
+pub mod consistency_watermark;
+pub mod indexer_outbox;
+pub mod indexer_table_stats;
+pub mod parquet_file_manifest;
+pub mod parquet_upload_checkpoint;
+pub mod partitioning;
+pub mod processor_heartbeat;
+pub mod processor_shard_status;
+pub mod quarantined_rows;
 pub mod resources;
+pub mod schema_drift;
+pub mod status_tables;
@@ -0,0 +1,82 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::schema::parquet_file_manifest;
+use diesel::{
+    dsl::{max, min},
+    ExpressionMethods, Insertable, QueryDsl,
+};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = parquet_file_manifest)]
+/// A file a parquet processor uploaded, with the version and block timestamp range of its rows,
+/// and the window of versions it covers
+pub struct ParquetFileManifestEntry {
+    pub processor: String,
+    pub table_name: String,
+    pub bucket_name: String,
+    pub file_path: String,
+    pub file_format: String,
+    pub num_rows: i64,
+    pub min_version: i64,
+    pub max_version: i64,
+    pub min_block_timestamp: Option<chrono::NaiveDateTime>,
+    pub max_block_timestamp: Option<chrono::NaiveDateTime>,
+    pub file_size_bytes: i64,
+    /// First version the file covers, right after the window of the table's previous file
+    pub window_start_version: Option<i64>,
+    /// Last version the file covers, which can be past its last row
+    pub window_end_version: Option<i64>,
+}
+
+impl ParquetFileManifestEntry {
+    /// Files are never overwritten, so an entry already recorded for the path is left as is.
+    pub async fn insert(&self, conn: &mut AsyncPgConnection) -> diesel::QueryResult<usize> {
+        diesel::insert_into(parquet_file_manifest::table)
+            .values(self)
+            .on_conflict((
+                parquet_file_manifest::bucket_name,
+                parquet_file_manifest::file_path,
+            ))
+            .do_nothing()
+            .execute(conn)
+            .await
+    }
+
+    /// Last version through which the windows of `table_name`'s files in `bucket_name` follow one
+    /// another without a gap, starting from its earliest window. None if no file of the table has
+    /// a window.
+    pub async fn contiguous_through(
+        bucket_name: &str,
+        table_name: &str,
+        conn: &mut AsyncPgConnection,
+    ) -> diesel::QueryResult<Option<i64>> {
+        let Some(first_start) = parquet_file_manifest::table
+            .filter(parquet_file_manifest::bucket_name.eq(bucket_name))
+            .filter(parquet_file_manifest::table_name.eq(table_name))
+            .select(min(parquet_file_manifest::window_start_version))
+            .get_result::<Option<i64>>(conn)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let mut covered_through = first_start - 1;
+        // Each round takes in the windows starting within what's covered so far
+        loop {
+            let window_end = parquet_file_manifest::table
+                .filter(parquet_file_manifest::bucket_name.eq(bucket_name))
+                .filter(parquet_file_manifest::table_name.eq(table_name))
+                .filter(parquet_file_manifest::window_start_version.le(covered_through + 1))
+                .select(max(parquet_file_manifest::window_end_version))
+                .get_result::<Option<i64>>(conn)
+                .await?;
+            match window_end {
+                Some(window_end) if window_end > covered_through => covered_through = window_end,
+                _ => return Ok(Some(covered_through)),
+            }
+        }
+    }
+}
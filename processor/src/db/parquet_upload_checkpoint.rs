@@ -0,0 +1,64 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::schema::parquet_upload_checkpoints;
+use cedra_indexer_processor_sdk::postgres::utils::database::DbPoolConnection;
+use diesel::{upsert::excluded, ExpressionMethods, Insertable, OptionalExtension, QueryDsl};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = parquet_upload_checkpoints)]
+/// Last file a table of a parquet processor uploaded, keyed like the table's processor status
+pub struct ParquetUploadCheckpoint {
+    pub processor: String,
+    pub bucket_name: String,
+    pub file_path: String,
+    /// Version of the file's last row
+    pub max_version: i64,
+    /// Last version the file covers, which can be past its last row
+    pub end_version: i64,
+    pub last_updated: chrono::NaiveDateTime,
+}
+
+impl ParquetUploadCheckpoint {
+    /// Only moves the checkpoint forward, so a range uploaded again doesn't take it back.
+    pub async fn upsert(&self, conn: &mut AsyncPgConnection) -> diesel::QueryResult<usize> {
+        diesel::insert_into(parquet_upload_checkpoints::table)
+            .values(self)
+            .on_conflict(parquet_upload_checkpoints::processor)
+            .do_update()
+            .set((
+                parquet_upload_checkpoints::bucket_name
+                    .eq(excluded(parquet_upload_checkpoints::bucket_name)),
+                parquet_upload_checkpoints::file_path
+                    .eq(excluded(parquet_upload_checkpoints::file_path)),
+                parquet_upload_checkpoints::max_version
+                    .eq(excluded(parquet_upload_checkpoints::max_version)),
+                parquet_upload_checkpoints::end_version
+                    .eq(excluded(parquet_upload_checkpoints::end_version)),
+                parquet_upload_checkpoints::last_updated
+                    .eq(excluded(parquet_upload_checkpoints::last_updated)),
+            ))
+            .filter(
+                parquet_upload_checkpoints::end_version
+                    .le(excluded(parquet_upload_checkpoints::end_version)),
+            )
+            .execute(conn)
+            .await
+    }
+
+    /// Last version uploaded for the processor status row `processor`
+    pub async fn get_end_version(
+        processor: &str,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<i64>> {
+        parquet_upload_checkpoints::table
+            .filter(parquet_upload_checkpoints::processor.eq(processor))
+            .select(parquet_upload_checkpoints::end_version)
+            .first::<i64>(conn)
+            .await
+            .optional()
+    }
+}
@@ -0,0 +1,421 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    config::{
+        db_config::{DbConfig, PartitionConfig, PostgresConfig},
+        indexer_processor_config::IndexerProcessorConfig,
+    },
+    db::schema_drift::expected_primary_key,
+};
+use anyhow::{bail, Result};
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::ArcDbPool,
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use diesel::{sql_query, sql_types::Text, QueryableByName};
+use diesel_async::RunQueryDsl;
+use regex::Regex;
+use std::marker::PhantomData;
+use tracing::info;
+
+/// Turns the tables configured in `partitioned_tables` into tables range partitioned by version,
+/// unless they already are. A table's existing rows are kept by attaching it as the `_history`
+/// partition of the new table, up to the partition boundary above its last version. Attaching
+/// scans the old table once to validate the bound.
+pub async fn create_partitioned_tables(
+    postgres_config: &PostgresConfig,
+    db_pool: ArcDbPool,
+) -> Result<()> {
+    let mut tables: Vec<(&String, &PartitionConfig)> =
+        postgres_config.partitioned_tables.iter().collect();
+    tables.sort_by_key(|(table_name, _)| table_name.as_str());
+    let mut conn = db_pool.get().await?;
+    for (table_name, partition_config) in tables {
+        validate_partition_config(table_name, partition_config)?;
+        sql_query(convert_table_query(table_name, partition_config))
+            .execute(&mut conn)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to partition {table_name}: {e}"))?;
+        info!(
+            table_name = table_name.as_str(),
+            column = partition_config.column.as_str(),
+            "Table is range partitioned"
+        );
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_partition_config(
+    table_name: &str,
+    partition_config: &PartitionConfig,
+) -> Result<()> {
+    let Some(primary_key) = expected_primary_key(table_name) else {
+        bail!("Can't partition {table_name}, it isn't a table of the processors");
+    };
+    match primary_key
+        .iter()
+        .find(|(column, _)| column == &partition_config.column)
+    {
+        Some((_, Some("int8"))) => {},
+        Some(_) => bail!(
+            "Can't partition {table_name} by {}, it isn't a version column",
+            partition_config.column
+        ),
+        None => bail!(
+            "Can't partition {table_name} by {}, it isn't in the primary key ({})",
+            partition_config.column,
+            primary_key
+                .iter()
+                .map(|(column, _)| column.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+    if partition_config.versions_per_partition == 0 {
+        bail!("versions_per_partition of {table_name} must be positive");
+    }
+    Ok(())
+}
+
+/// Runs as a single statement, so the table is either fully converted or left as it was. The
+/// advisory lock keeps processors starting together from converting the same table twice.
+///
+/// Indexes and primary keys keep their names on the new table, for later migrations to find them
+/// there. The `_history` partition's are renamed with a `_history` suffix, and become partitions
+/// of the new table's when it's attached.
+fn convert_table_query(table_name: &str, partition_config: &PartitionConfig) -> String {
+    let column = &partition_config.column;
+    let versions_per_partition = partition_config.versions_per_partition;
+    format!(
+        "DO $$
+        DECLARE
+          upper_bound BIGINT;
+          idx RECORD;
+        BEGIN
+          PERFORM pg_advisory_xact_lock(hashtext('partition {table_name}'));
+          IF EXISTS (
+            SELECT 1 FROM pg_partitioned_table WHERE partrelid = '{table_name}'::regclass
+          ) THEN
+            RETURN;
+          END IF;
+          IF EXISTS (
+            SELECT 1 FROM information_schema.view_table_usage
+            WHERE table_schema = current_schema() AND table_name = '{table_name}'
+          ) THEN
+            RAISE EXCEPTION 'Views depend on {table_name}, drop them before partitioning it';
+          END IF;
+          ALTER TABLE {table_name} RENAME TO {table_name}_history;
+          CREATE TABLE {table_name} (LIKE {table_name}_history INCLUDING ALL EXCLUDING INDEXES)
+            PARTITION BY RANGE ({column});
+          FOR idx IN
+            SELECT index_class.relname AS name,
+              pg_get_indexdef(index_class.oid) AS index_definition,
+              pg_get_constraintdef(con.oid) AS constraint_definition
+            FROM pg_index
+            JOIN pg_class AS index_class ON index_class.oid = pg_index.indexrelid
+            LEFT JOIN pg_constraint AS con ON con.conindid = pg_index.indexrelid
+              AND con.conrelid = pg_index.indrelid
+            WHERE pg_index.indrelid = '{table_name}_history'::regclass
+          LOOP
+            EXECUTE format(
+              'ALTER INDEX %I RENAME TO %I', idx.name, left(idx.name, 55) || '_history'
+            );
+            IF idx.constraint_definition IS NOT NULL THEN
+              EXECUTE format(
+                'ALTER TABLE {table_name} ADD CONSTRAINT %I %s',
+                idx.name,
+                idx.constraint_definition
+              );
+            ELSE
+              EXECUTE regexp_replace(
+                idx.index_definition, ' ON \\S+ USING ', ' ON {table_name} USING '
+              );
+            END IF;
+          END LOOP;
+          SELECT COALESCE(
+            (MAX({column}) / {versions_per_partition} + 1) * {versions_per_partition}, 0
+          ) INTO upper_bound FROM {table_name}_history;
+          EXECUTE format(
+            'ALTER TABLE {table_name} ATTACH PARTITION {table_name}_history
+             FOR VALUES FROM (MINVALUE) TO (%s)',
+            upper_bound
+          );
+        END $$"
+    )
+}
+
+#[derive(Debug, QueryableByName)]
+struct PartitionBound {
+    #[diesel(sql_type = Text)]
+    bound: String,
+}
+
+struct PartitionedTable {
+    name: String,
+    versions_per_partition: u64,
+    partitions_ahead: u64,
+    /// Versions below this already have a partition
+    covered_up_to: u64,
+}
+
+/// Creates the partitions of version-keyed tables before versions are written to them. Each
+/// partition covers `versions_per_partition` versions and is named after its first version's
+/// partition number, e.g. `events_p12`. If processing starts past the last partition, a single
+/// partition covers the skipped versions.
+pub struct PartitionManager {
+    db_pool: ArcDbPool,
+    tables: Vec<PartitionedTable>,
+}
+
+impl PartitionManager {
+    /// Manages the partitions of those of `table_names` that are configured as partitioned
+    pub async fn new(
+        db_pool: ArcDbPool,
+        postgres_config: &PostgresConfig,
+        table_names: &[&str],
+    ) -> Result<Self> {
+        let mut tables = vec![];
+        let mut conn = db_pool.get().await?;
+        for table_name in table_names {
+            let Some(partition_config) = postgres_config.partitioned_tables.get(*table_name) else {
+                continue;
+            };
+            let bounds: Vec<PartitionBound> = sql_query(format!(
+                "SELECT pg_get_expr(c.relpartbound, c.oid) AS bound
+                 FROM pg_inherits i
+                 JOIN pg_class c ON c.oid = i.inhrelid
+                 WHERE i.inhparent = '{table_name}'::regclass"
+            ))
+            .load(&mut conn)
+            .await?;
+            tables.push(PartitionedTable {
+                name: table_name.to_string(),
+                versions_per_partition: partition_config.versions_per_partition,
+                partitions_ahead: partition_config.partitions_ahead,
+                covered_up_to: highest_upper_bound(&bounds),
+            });
+        }
+        Ok(Self { db_pool, tables })
+    }
+
+    /// Makes sure every table has partitions up to `partitions_ahead` partitions past `version`
+    pub async fn create_partitions(&mut self, version: u64) -> Result<()> {
+        if self.tables.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.db_pool.get().await?;
+        for table in &mut self.tables {
+            let size = table.versions_per_partition;
+            let current_start = version / size * size;
+            let target = current_start + (table.partitions_ahead + 1) * size;
+            while table.covered_up_to < target {
+                let start = table.covered_up_to;
+                let end = if start < current_start {
+                    current_start
+                } else {
+                    start + size
+                };
+                sql_query(format!(
+                    "CREATE TABLE IF NOT EXISTS {name}_p{number} PARTITION OF {name}
+                     FOR VALUES FROM ({start}) TO ({end})",
+                    name = table.name,
+                    number = start / size,
+                ))
+                .execute(&mut conn)
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to create partition [{start}, {end}) of {}: {e}",
+                        table.name
+                    )
+                })?;
+                info!(
+                    table_name = table.name.as_str(),
+                    start, end, "Created partition"
+                );
+                table.covered_up_to = end;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Upper bound of the partitions, or 0 without partitions
+fn highest_upper_bound(bounds: &[PartitionBound]) -> u64 {
+    let upper_bound_re = Regex::new(r"TO \('?(\d+)'?\)").unwrap();
+    bounds
+        .iter()
+        .filter_map(|bound| upper_bound_re.captures(&bound.bound)?[1].parse().ok())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Creates the partitions a batch is about to be written to, then passes the batch on unchanged.
+/// Goes right before the storer.
+pub struct PartitionStep<T> {
+    partition_manager: PartitionManager,
+    _data: PhantomData<fn() -> T>,
+}
+
+impl<T: Send + 'static> PartitionStep<T> {
+    /// Partitions those of `table_names` configured as partitioned, or none for non-postgres
+    /// configs
+    pub async fn new(
+        config: &IndexerProcessorConfig,
+        db_pool: ArcDbPool,
+        table_names: &[&str],
+    ) -> Result<Self> {
+        let partition_manager = match &config.db_config {
+            DbConfig::PostgresConfig(postgres_config) => {
+                PartitionManager::new(db_pool, postgres_config, table_names).await?
+            },
+            _ => PartitionManager {
+                db_pool,
+                tables: vec![],
+            },
+        };
+        Ok(Self {
+            partition_manager,
+            _data: PhantomData,
+        })
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> Processable for PartitionStep<T> {
+    type Input = T;
+    type Output = T;
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        input: TransactionContext<T>,
+    ) -> Result<Option<TransactionContext<T>>, ProcessorError> {
+        self.partition_manager
+            .create_partitions(input.metadata.end_version)
+            .await
+            .map_err(|e| ProcessorError::DBStoreError {
+                message: format!(
+                    "Failed to create partitions for versions {} to {}: {:?}",
+                    input.metadata.start_version, input.metadata.end_version, e,
+                ),
+                query: None,
+            })?;
+        Ok(Some(input))
+    }
+}
+
+impl<T: Send + 'static> AsyncStep for PartitionStep<T> {}
+
+impl<T: Send + 'static> NamedStep for PartitionStep<T> {
+    fn name(&self) -> String {
+        "PartitionStep".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MIGRATIONS;
+    use cedra_indexer_processor_sdk::{
+        postgres::utils::database::{new_db_pool, run_migrations},
+        testing_framework::database::{PostgresTestDatabase, TestDatabase},
+    };
+
+    fn partition_config(column: &str) -> PartitionConfig {
+        PartitionConfig {
+            column: column.to_string(),
+            versions_per_partition: 1000,
+            partitions_ahead: PartitionConfig::default_partitions_ahead(),
+        }
+    }
+
+    #[test]
+    fn test_validate_partition_config() {
+        assert!(
+            validate_partition_config("events", &partition_config("transaction_version")).is_ok()
+        );
+        assert!(
+            validate_partition_config("user_transactions", &partition_config("version")).is_ok()
+        );
+        // Not in the primary key, so upserts couldn't conflict on it
+        assert!(validate_partition_config("events", &partition_config("inserted_at")).is_err());
+        assert!(validate_partition_config(
+            "coin_activities",
+            &partition_config("event_account_address")
+        )
+        .is_err());
+        assert!(validate_partition_config("not_a_table", &partition_config("version")).is_err());
+    }
+
+    #[derive(QueryableByName)]
+    struct IndexName {
+        #[diesel(sql_type = Text)]
+        tablename: String,
+        #[diesel(sql_type = Text)]
+        indexname: String,
+    }
+
+    #[tokio::test]
+    async fn test_indexes_keep_their_names_on_the_partitioned_table() {
+        let mut db = PostgresTestDatabase::new();
+        db.setup().await.unwrap();
+        let conn_pool = new_db_pool(db.get_db_url().as_str(), Some(10))
+            .await
+            .expect("Failed to create connection pool");
+        run_migrations(db.get_db_url(), conn_pool.clone(), MIGRATIONS).await;
+        let mut conn = conn_pool.get().await.unwrap();
+        sql_query(convert_table_query(
+            "events",
+            &partition_config("transaction_version"),
+        ))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        let indexes: Vec<IndexName> = sql_query(
+            "SELECT tablename::TEXT, indexname::TEXT FROM pg_indexes
+             WHERE tablename IN ('events', 'events_history')",
+        )
+        .load(&mut conn)
+        .await
+        .unwrap();
+        let has_index = |table_name: &str, index_name: &str| {
+            indexes
+                .iter()
+                .any(|index| index.tablename == table_name && index.indexname == index_name)
+        };
+        for index_name in ["events_pkey", "ev_addr_type_index", "ev_itype_index"] {
+            assert!(has_index("events", index_name), "{index_name}");
+            assert!(
+                has_index("events_history", &format!("{index_name}_history")),
+                "{index_name}"
+            );
+        }
+        // Later migrations' index statements apply to the partitioned table
+        sql_query("DROP INDEX ev_itype_index")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_highest_upper_bound() {
+        let bound = |bound: &str| PartitionBound {
+            bound: bound.to_string(),
+        };
+        assert_eq!(highest_upper_bound(&[]), 0);
+        assert_eq!(
+            highest_upper_bound(&[
+                bound("FOR VALUES FROM (MINVALUE) TO ('5000')"),
+                bound("FOR VALUES FROM (6000) TO (7000)"),
+                bound("FOR VALUES FROM (5000) TO (6000)"),
+            ]),
+            7000
+        );
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::schema::processor_heartbeats;
+use cedra_indexer_processor_sdk::postgres::utils::database::DbPoolConnection;
+use diesel::{AsChangeset, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable};
+use diesel_async::RunQueryDsl;
+
+#[derive(AsChangeset, Debug, Insertable, Queryable)]
+#[diesel(table_name = processor_heartbeats)]
+/// Latest save of a processor's status, written whether or not its version moved
+pub struct ProcessorHeartbeat {
+    pub processor: String,
+    pub last_success_version: i64,
+    /// Versions in the last batch saved
+    pub last_batch_size: i64,
+    pub last_transaction_timestamp: Option<chrono::NaiveDateTime>,
+    pub last_heartbeat_at: chrono::NaiveDateTime,
+}
+
+impl ProcessorHeartbeat {
+    pub async fn get_by_processor(
+        processor_name: &str,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Self>> {
+        processor_heartbeats::table
+            .filter(processor_heartbeats::processor.eq(processor_name))
+            .first::<Self>(conn)
+            .await
+            .optional()
+    }
+
+    /// Time since the heartbeat, as of `now`
+    pub fn staleness(&self, now: chrono::NaiveDateTime) -> chrono::Duration {
+        now - self.last_heartbeat_at
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::schema::processor_shard_status;
+use cedra_indexer_processor_sdk::postgres::utils::database::DbPoolConnection;
+use diesel::{ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable};
+use diesel_async::RunQueryDsl;
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = processor_shard_status)]
+/// Progress of a shard through the range it's processing
+pub struct ProcessorShardStatus {
+    pub processor: String,
+    pub shard_index: i64,
+    pub range_starting_version: i64,
+    pub range_ending_version: i64,
+    pub last_success_version: i64,
+    pub last_transaction_timestamp: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Queryable)]
+#[diesel(table_name = processor_shard_status)]
+pub struct ProcessorShardStatusQuery {
+    pub processor: String,
+    pub shard_index: i64,
+    pub range_starting_version: i64,
+    pub range_ending_version: i64,
+    pub last_success_version: i64,
+    pub last_transaction_timestamp: Option<chrono::NaiveDateTime>,
+    pub last_updated: chrono::NaiveDateTime,
+}
+
+impl ProcessorShardStatusQuery {
+    pub async fn get_by_shard(
+        processor_name: &str,
+        shard_index: u64,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Self>> {
+        processor_shard_status::table
+            .filter(processor_shard_status::processor.eq(processor_name))
+            .filter(processor_shard_status::shard_index.eq(shard_index as i64))
+            .first::<Self>(conn)
+            .await
+            .optional()
+    }
+}
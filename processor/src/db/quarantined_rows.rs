@@ -0,0 +1,24 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::schema::quarantined_rows;
+use diesel::{pg::Pg, query_builder::QueryFragment, Insertable};
+use serde::Serialize;
+
+#[derive(Clone, Debug, Insertable, Serialize)]
+#[diesel(table_name = quarantined_rows)]
+/// A row kept out of its table because it violated a data quality rule, as it would have been
+/// written
+pub struct QuarantinedRow {
+    pub table_name: String,
+    pub rule_name: String,
+    pub row_data: serde_json::Value,
+}
+
+pub fn insert_quarantined_rows_query(
+    items_to_insert: Vec<QuarantinedRow>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    diesel::insert_into(quarantined_rows::table).values(items_to_insert)
+}
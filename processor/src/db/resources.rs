@@ -10,7 +10,7 @@ use crate::processors::{
     },
     objects::v2_object_utils::{ObjectCore, Untransferable},
     token_v2::token_v2_models::v2_token_utils::{
-        CedraCollection, Collection, ConcurrentSupply, FixedSupply, PropertyMapModel,
+        CedraCollection, Collection, ConcurrentSupply, FixedSupply, PropertyMapModel, Royalty,
         TokenIdentifiers, TokenV2, UnlimitedSupply,
     },
 };
@@ -40,6 +40,7 @@ pub const TYPE_UNLIMITED_SUPPLY: &str = formatcp!("{TOKEN_V2_ADDR}::collection::
 pub const TYPE_CEDRA_COLLECTION: &str = formatcp!("{TOKEN_V2_ADDR}::cedra_token::CedraCollection");
 pub const TYPE_TOKEN_V2: &str = formatcp!("{TOKEN_V2_ADDR}::token::Token");
 pub const TYPE_TOKEN_IDENTIFIERS: &str = formatcp!("{TOKEN_V2_ADDR}::token::TokenIdentifiers");
+pub const TYPE_ROYALTY: &str = formatcp!("{TOKEN_V2_ADDR}::royalty::Royalty");
 pub const TYPE_PROPERTY_MAP: &str = formatcp!("{TOKEN_V2_ADDR}::property_map::PropertyMap");
 pub const TYPE_ACCOUNT: &str = formatcp!("{COIN_ADDR}::account::Account");
 
@@ -128,6 +129,7 @@ pub enum V2TokenResource {
     FixedSupply(FixedSupply),
     ObjectCore(ObjectCore),
     PropertyMapModel(PropertyMapModel),
+    Royalty(Royalty),
     TokenIdentifiers(TokenIdentifiers),
     TokenV2(TokenV2),
     UnlimitedSupply(UnlimitedSupply),
@@ -171,6 +173,12 @@ impl Resource for PropertyMapModel {
     }
 }
 
+impl Resource for Royalty {
+    fn type_str() -> &'static str {
+        TYPE_ROYALTY
+    }
+}
+
 impl Resource for TokenIdentifiers {
     fn type_str() -> &'static str {
         TYPE_TOKEN_IDENTIFIERS
@@ -212,6 +220,7 @@ impl V2TokenResource {
             TYPE_FIXED_SUPPLY => Self::FixedSupply(write_resource.try_into()?),
             TYPE_OBJECT_CORE => Self::ObjectCore(write_resource.try_into()?),
             TYPE_PROPERTY_MAP => Self::PropertyMapModel(write_resource.try_into()?),
+            TYPE_ROYALTY => Self::Royalty(write_resource.try_into()?),
             TYPE_TOKEN_IDENTIFIERS => Self::TokenIdentifiers(write_resource.try_into()?),
             TYPE_TOKEN_V2 => Self::TokenV2(write_resource.try_into()?),
             TYPE_UNLIMITED_SUPPLY => Self::UnlimitedSupply(write_resource.try_into()?),
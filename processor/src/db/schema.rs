@@ -6,6 +6,13 @@ diesel::table! {
         #[max_length = 66]
         account_address -> Varchar,
         inserted_at -> Timestamp,
+        is_sender -> Nullable<Bool>,
+        is_signer -> Nullable<Bool>,
+        txn_success -> Nullable<Bool>,
+        #[max_length = 50]
+        txn_type -> Nullable<Varchar>,
+        #[max_length = 100]
+        counterparty_label -> Nullable<Varchar>,
     }
 }
 
@@ -84,6 +91,37 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    ans_revenue_events (transaction_version, event_index) {
+        transaction_version -> Int8,
+        event_index -> Int8,
+        #[max_length = 20]
+        revenue_type -> Varchar,
+        #[max_length = 66]
+        payer_address -> Varchar,
+        #[max_length = 64]
+        domain -> Varchar,
+        #[max_length = 64]
+        subdomain -> Varchar,
+        #[max_length = 140]
+        token_name -> Varchar,
+        amount_octas -> Nullable<Numeric>,
+        expiration_timestamp -> Timestamp,
+        duration_secs -> Nullable<Int8>,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    asset_usd_prices (asset_id) {
+        #[max_length = 1000]
+        asset_id -> Varchar,
+        usd_price -> Numeric,
+        last_updated -> Timestamp,
+    }
+}
+
 diesel::table! {
     auth_key_account_addresses (account_address) {
         #[max_length = 66]
@@ -95,6 +133,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    auth_key_schemes (auth_key) {
+        #[max_length = 66]
+        auth_key -> Varchar,
+        #[max_length = 20]
+        auth_scheme -> Varchar,
+        #[max_length = 66]
+        public_key_preview -> Varchar,
+        last_transaction_version -> Int8,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     backfill_processor_status (backfill_alias) {
         #[max_length = 50]
@@ -169,6 +220,43 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    coin_holder_counts (asset_type) {
+        #[max_length = 1000]
+        asset_type -> Varchar,
+        holder_count -> Int8,
+        last_transaction_version -> Int8,
+        inserted_at -> Timestamp,
+        owner_count -> Int8,
+    }
+}
+
+diesel::table! {
+    coin_info_mutations (transaction_version, coin_type_hash, mutation_type) {
+        transaction_version -> Int8,
+        #[max_length = 64]
+        coin_type_hash -> Varchar,
+        #[max_length = 50]
+        mutation_type -> Varchar,
+        #[max_length = 5000]
+        coin_type -> Varchar,
+        #[max_length = 32]
+        name -> Nullable<Varchar>,
+        #[max_length = 32]
+        symbol -> Nullable<Varchar>,
+        decimals -> Nullable<Int4>,
+        #[max_length = 32]
+        previous_name -> Nullable<Varchar>,
+        #[max_length = 32]
+        previous_symbol -> Nullable<Varchar>,
+        previous_decimals -> Nullable<Int4>,
+        #[max_length = 66]
+        fungible_asset_metadata_address -> Nullable<Varchar>,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     coin_infos (coin_type_hash) {
         #[max_length = 64]
@@ -191,6 +279,21 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    coin_store_freeze_events (transaction_version, owner_address, coin_type_hash) {
+        transaction_version -> Int8,
+        #[max_length = 66]
+        owner_address -> Varchar,
+        #[max_length = 64]
+        coin_type_hash -> Varchar,
+        #[max_length = 5000]
+        coin_type -> Varchar,
+        is_frozen -> Bool,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     coin_supply (transaction_version, coin_type_hash) {
         transaction_version -> Int8,
@@ -229,6 +332,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    collection_volume_leaderboard (collection_id) {
+        #[max_length = 66]
+        collection_id -> Varchar,
+        activity_count -> Int8,
+        usd_volume -> Numeric,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     collections_v2 (transaction_version, write_set_change_index) {
         transaction_version -> Int8,
@@ -291,6 +406,7 @@ diesel::table! {
         is_deleted -> Bool,
         inserted_at -> Timestamp,
         subdomain_expiration_policy -> Nullable<Int8>,
+        effective_expiration_timestamp -> Nullable<Timestamp>,
     }
 }
 
@@ -340,6 +456,7 @@ diesel::table! {
         last_transaction_version -> Int8,
         last_transaction_timestamp -> Timestamp,
         inserted_at -> Timestamp,
+        is_frozen -> Bool,
     }
 }
 
@@ -367,6 +484,24 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    current_collection_stats_v2 (collection_id) {
+        #[max_length = 66]
+        collection_id -> Varchar,
+        #[max_length = 66]
+        creator_address -> Varchar,
+        #[max_length = 128]
+        collection_name -> Varchar,
+        current_supply -> Numeric,
+        total_minted -> Numeric,
+        total_burned -> Numeric,
+        distinct_owners -> Int8,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     current_collections_v2 (collection_id) {
         #[max_length = 66]
@@ -407,6 +542,7 @@ diesel::table! {
         inactive_table_handle -> Varchar,
         #[max_length = 66]
         active_table_handle -> Varchar,
+        observed_lockup_cycle -> Nullable<Int8>,
     }
 }
 
@@ -494,6 +630,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    current_object_counts_by_owner (owner_address) {
+        #[max_length = 66]
+        owner_address -> Varchar,
+        object_count -> Int8,
+        token_count -> Int8,
+        fa_store_count -> Int8,
+        last_transaction_version -> Int8,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     current_objects (object_address) {
         #[max_length = 66]
@@ -596,6 +744,11 @@ diesel::table! {
         inserted_at -> Timestamp,
         decimals -> Nullable<Int8>,
         is_deleted_v2 -> Nullable<Bool>,
+        #[max_length = 66]
+        creator_address -> Nullable<Varchar>,
+        collection_mutable_description -> Nullable<Bool>,
+        collection_mutable_uri -> Nullable<Bool>,
+        royalty_bps -> Nullable<Int8>,
     }
 }
 
@@ -674,6 +827,9 @@ diesel::table! {
         token_data_id -> Varchar,
         #[max_length = 66]
         collection_id -> Varchar,
+        #[max_length = 10]
+        status -> Nullable<Varchar>,
+        resolution_transaction_version -> Nullable<Int8>,
     }
 }
 
@@ -763,6 +919,50 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    deleted_objects (transaction_version, object_address) {
+        transaction_version -> Int8,
+        write_set_change_index -> Int8,
+        #[max_length = 66]
+        object_address -> Varchar,
+        #[max_length = 66]
+        last_owner_address -> Varchar,
+        #[max_length = 66]
+        state_key_hash -> Varchar,
+        last_guid_creation_num -> Numeric,
+        untransferrable -> Bool,
+        is_token_burn -> Bool,
+        block_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    epoch_end_delegator_balances (epoch, pool_address, delegator_address) {
+        epoch -> Int8,
+        #[max_length = 66]
+        pool_address -> Varchar,
+        #[max_length = 66]
+        delegator_address -> Varchar,
+        shares -> Numeric,
+        amount -> Numeric,
+        epoch_end_version -> Int8,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    event_attributes (transaction_version, event_index, key) {
+        transaction_version -> Int8,
+        event_index -> Int8,
+        #[max_length = 100]
+        key -> Varchar,
+        #[max_length = 1000]
+        value -> Varchar,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     event_size_info (transaction_version, index) {
         transaction_version -> Int8,
@@ -788,6 +988,33 @@ diesel::table! {
         event_index -> Int8,
         #[max_length = 300]
         indexed_type -> Varchar,
+        #[max_length = 66]
+        module_address -> Nullable<Varchar>,
+        #[max_length = 255]
+        module_name -> Nullable<Varchar>,
+        #[max_length = 255]
+        struct_name -> Nullable<Varchar>,
+        generic_args -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    events_compacted (account_address, type_, window_start) {
+        #[max_length = 66]
+        account_address -> Varchar,
+        #[sql_name = "type"]
+        type_ -> Text,
+        window_start -> Timestamp,
+        transaction_version -> Int8,
+        event_index -> Int8,
+        sequence_number -> Int8,
+        creation_number -> Int8,
+        transaction_block_height -> Int8,
+        data -> Jsonb,
+        #[max_length = 300]
+        indexed_type -> Varchar,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
     }
 }
 
@@ -817,6 +1044,10 @@ diesel::table! {
         transaction_timestamp -> Timestamp,
         inserted_at -> Timestamp,
         storage_refund_amount -> Numeric,
+        #[max_length = 100]
+        counterparty_label -> Nullable<Varchar>,
+        is_migration -> Bool,
+        usd_value -> Nullable<Numeric>,
     }
 }
 
@@ -893,6 +1124,37 @@ diesel::table! {
         block_height -> Int8,
         transaction_timestamp -> Timestamp,
         storage_refund_amount -> Numeric,
+        #[max_length = 66]
+        fee_payer_address -> Nullable<Varchar>,
+        is_sponsored -> Bool,
+    }
+}
+
+    governance_participation (voter_address) {
+        #[max_length = 66]
+        voter_address -> Varchar,
+        proposals_voted -> Int8,
+        proposals_eligible -> Int8,
+        num_votes -> Numeric,
+        participation_rate -> Numeric,
+        stake_weighted_participation_rate -> Numeric,
+        first_proposal_id -> Int8,
+        last_proposal_id -> Int8,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+
+diesel::table! {
+    indexer_outbox (id) {
+        id -> Int8,
+        #[max_length = 100]
+        processor -> Varchar,
+        start_version -> Int8,
+        end_version -> Int8,
+        tables_touched -> Array<Nullable<Text>>,
+        created_at -> Timestamp,
+        published_at -> Nullable<Timestamp>,
     }
 }
 
@@ -905,6 +1167,49 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    indexer_table_stats (processor, table_name) {
+        #[max_length = 100]
+        processor -> Varchar,
+        #[max_length = 100]
+        table_name -> Varchar,
+        rows_written -> Int8,
+        conflicts_ignored -> Int8,
+        bytes_written -> Int8,
+        last_rows_written_version -> Nullable<Int8>,
+        last_rows_written_at -> Nullable<Timestamp>,
+        last_updated -> Timestamp,
+    }
+}
+
+diesel::table! {
+    indexer_table_stats_daily (processor, table_name, day) {
+        #[max_length = 100]
+        processor -> Varchar,
+        #[max_length = 100]
+        table_name -> Varchar,
+        day -> Date,
+        rows_written -> Int8,
+        conflicts_ignored -> Int8,
+        bytes_written -> Int8,
+        last_updated -> Timestamp,
+    }
+}
+
+diesel::table! {
+    indexer_table_stats_hourly (processor, table_name, hour) {
+        #[max_length = 100]
+        processor -> Varchar,
+        #[max_length = 100]
+        table_name -> Varchar,
+        hour -> Timestamp,
+        rows_written -> Int8,
+        conflicts_ignored -> Int8,
+        bytes_written -> Int8,
+        last_updated -> Timestamp,
+    }
+}
+
 diesel::table! {
     ledger_infos (chain_id) {
         chain_id -> Int8,
@@ -961,6 +1266,29 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    nft_transfers (transaction_version, event_index) {
+        transaction_version -> Int8,
+        event_index -> Int8,
+        #[max_length = 66]
+        token_data_id -> Varchar,
+        property_version_v1 -> Numeric,
+        #[max_length = 66]
+        from_address -> Varchar,
+        #[max_length = 66]
+        to_address -> Varchar,
+        token_amount -> Numeric,
+        #[max_length = 10]
+        token_standard -> Varchar,
+        #[max_length = 20]
+        transfer_type -> Varchar,
+        #[max_length = 1000]
+        entry_function_id_str -> Nullable<Varchar>,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     objects (transaction_version, write_set_change_index) {
         transaction_version -> Int8,
@@ -979,6 +1307,113 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    parquet_file_manifest (id) {
+        id -> Int8,
+        #[max_length = 100]
+        processor -> Varchar,
+        #[max_length = 100]
+        table_name -> Varchar,
+        #[max_length = 255]
+        bucket_name -> Varchar,
+        file_path -> Text,
+        #[max_length = 10]
+        file_format -> Varchar,
+        num_rows -> Int8,
+        min_version -> Int8,
+        max_version -> Int8,
+        min_block_timestamp -> Nullable<Timestamp>,
+        max_block_timestamp -> Nullable<Timestamp>,
+        file_size_bytes -> Int8,
+        inserted_at -> Timestamp,
+        window_start_version -> Nullable<Int8>,
+        window_end_version -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    parquet_upload_checkpoints (processor) {
+        #[max_length = 100]
+        processor -> Varchar,
+        #[max_length = 255]
+        bucket_name -> Varchar,
+        file_path -> Text,
+        max_version -> Int8,
+        end_version -> Int8,
+        last_updated -> Timestamp,
+    }
+}
+
+diesel::table! {
+    pool_commission_history (transaction_version, event_index) {
+        transaction_version -> Int8,
+        event_index -> Int8,
+        #[max_length = 66]
+        pool_address -> Nullable<Varchar>,
+        #[max_length = 66]
+        staker_address -> Varchar,
+        #[max_length = 66]
+        operator_address -> Nullable<Varchar>,
+        old_commission_percentage -> Nullable<Numeric>,
+        new_commission_percentage -> Numeric,
+        #[max_length = 1000]
+        event_type -> Varchar,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    pool_operator_history (transaction_version, event_index) {
+        transaction_version -> Int8,
+        event_index -> Int8,
+        #[max_length = 66]
+        pool_address -> Varchar,
+        #[max_length = 66]
+        old_operator_address -> Varchar,
+        #[max_length = 66]
+        new_operator_address -> Varchar,
+        #[max_length = 1000]
+        event_type -> Varchar,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    processor_consistency_watermarks (processor) {
+        #[max_length = 100]
+        processor -> Varchar,
+        consistent_through_version -> Int8,
+        last_updated -> Timestamp,
+        last_transaction_timestamp -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    processor_heartbeats (processor) {
+        #[max_length = 100]
+        processor -> Varchar,
+        last_success_version -> Int8,
+        last_batch_size -> Int8,
+        last_transaction_timestamp -> Nullable<Timestamp>,
+        last_heartbeat_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    processor_shard_status (processor, shard_index) {
+        #[max_length = 100]
+        processor -> Varchar,
+        shard_index -> Int8,
+        range_starting_version -> Int8,
+        range_ending_version -> Int8,
+        last_success_version -> Int8,
+        last_transaction_timestamp -> Nullable<Timestamp>,
+        last_updated -> Timestamp,
+    }
+}
+
 diesel::table! {
     processor_status (processor) {
         #[max_length = 100]
@@ -1021,6 +1456,43 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    quarantined_rows (id) {
+        id -> Int8,
+        #[max_length = 100]
+        table_name -> Varchar,
+        #[max_length = 100]
+        rule_name -> Varchar,
+        row_data -> Jsonb,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    raw_transactions (transaction_version) {
+        transaction_version -> Int8,
+        block_height -> Int8,
+        epoch -> Int8,
+        transaction_timestamp -> Timestamp,
+        encoded_transaction -> Bytea,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    sender_sequence_number_gaps (sender, sequence_number) {
+        #[max_length = 66]
+        sender -> Varchar,
+        sequence_number -> Int8,
+        previous_sequence_number -> Int8,
+        missing_count -> Int8,
+        transaction_version -> Int8,
+        previous_transaction_version -> Int8,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     signatures (transaction_version, multi_agent_index, multi_sig_index, is_sender_primary) {
         transaction_version -> Int8,
@@ -1134,6 +1606,10 @@ diesel::table! {
         is_fungible_v2 -> Nullable<Bool>,
         transaction_timestamp -> Timestamp,
         inserted_at -> Timestamp,
+        usd_value -> Nullable<Numeric>,
+        #[max_length = 66]
+        burner_address -> Nullable<Varchar>,
+        is_creator_burn -> Nullable<Bool>,
     }
 }
 
@@ -1198,6 +1674,25 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    token_mints_detail (transaction_version, event_index) {
+        transaction_version -> Int8,
+        event_index -> Int8,
+        #[max_length = 66]
+        mint_module_address -> Varchar,
+        #[max_length = 5000]
+        event_type -> Varchar,
+        #[max_length = 66]
+        token_data_id -> Nullable<Varchar>,
+        activity_event_index -> Nullable<Int8>,
+        #[max_length = 1000]
+        mint_stage -> Nullable<Varchar>,
+        paid_amount -> Nullable<Numeric>,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     token_ownerships (token_data_id_hash, property_version, transaction_version, table_handle) {
         #[max_length = 64]
@@ -1248,6 +1743,22 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    token_property_values (token_data_id, property_key) {
+        #[max_length = 66]
+        token_data_id -> Varchar,
+        #[max_length = 128]
+        property_key -> Varchar,
+        #[max_length = 128]
+        property_type -> Nullable<Varchar>,
+        #[max_length = 512]
+        property_value -> Varchar,
+        last_transaction_version -> Int8,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     tokens (token_data_id_hash, property_version, transaction_version) {
         #[max_length = 64]
@@ -1268,6 +1779,21 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    transaction_signers (transaction_version, signer_role, signer_index) {
+        transaction_version -> Int8,
+        #[max_length = 50]
+        signer_role -> Varchar,
+        signer_index -> Int8,
+        transaction_block_height -> Int8,
+        #[max_length = 66]
+        signer_address -> Varchar,
+        #[max_length = 50]
+        signature_scheme -> Varchar,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     transaction_size_info (transaction_version) {
         transaction_version -> Int8,
@@ -1305,6 +1831,29 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    treasury_inflows (transaction_version, event_index) {
+        transaction_version -> Int8,
+        event_index -> Int8,
+        #[max_length = 66]
+        treasury_address -> Varchar,
+        #[max_length = 1000]
+        asset_type -> Varchar,
+        amount -> Numeric,
+        #[max_length = 10]
+        token_standard -> Varchar,
+        #[max_length = 66]
+        source_address -> Nullable<Varchar>,
+        #[max_length = 1000]
+        entry_function_id_str -> Nullable<Varchar>,
+        #[max_length = 1000]
+        source_module -> Nullable<Varchar>,
+        block_height -> Int8,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     user_transactions (version) {
         version -> Int8,
@@ -1328,6 +1877,87 @@ diesel::table! {
         entry_function_module_name -> Nullable<Varchar>,
         #[max_length = 255]
         entry_function_function_name -> Nullable<Varchar>,
+        latency_from_expiration -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    validator_transaction_events (transaction_version, event_index) {
+        transaction_version -> Int8,
+        event_index -> Int8,
+        sequence_number -> Int8,
+        creation_number -> Int8,
+        #[max_length = 66]
+        account_address -> Varchar,
+        #[sql_name = "type"]
+        type_ -> Text,
+        #[max_length = 300]
+        indexed_type -> Varchar,
+        data -> Jsonb,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    validator_transactions (transaction_version) {
+        transaction_version -> Int8,
+        block_height -> Int8,
+        epoch -> Int8,
+        #[max_length = 50]
+        validator_transaction_type -> Varchar,
+        jwk_issuer -> Nullable<Text>,
+        jwk_version -> Nullable<Int8>,
+        num_jwks -> Nullable<Int8>,
+        jwk_signer_indices -> Nullable<Jsonb>,
+        dkg_epoch -> Nullable<Int8>,
+        #[max_length = 66]
+        dkg_author -> Nullable<Varchar>,
+        dkg_transcript_bytes -> Nullable<Int8>,
+        num_events -> Int8,
+        is_transaction_success -> Bool,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    watched_resources (transaction_version, write_set_change_index) {
+        transaction_version -> Int8,
+        write_set_change_index -> Int8,
+        transaction_block_height -> Int8,
+        #[max_length = 66]
+        resource_address -> Varchar,
+        resource_type -> Text,
+        matched_pattern -> Text,
+        data -> Nullable<Jsonb>,
+        is_deleted -> Bool,
+        #[max_length = 66]
+        state_key_hash -> Varchar,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    write_set_change_stats (transaction_version) {
+        transaction_version -> Int8,
+        transaction_block_height -> Int8,
+        num_changes -> Int8,
+        key_bytes -> Int8,
+        value_bytes -> Int8,
+        num_resources_written -> Int8,
+        num_resources_deleted -> Int8,
+        resource_bytes -> Int8,
+        resource_counts_by_module -> Jsonb,
+        num_table_items_written -> Int8,
+        num_table_items_deleted -> Int8,
+        table_item_bytes -> Int8,
+        num_modules_written -> Int8,
+        num_modules_deleted -> Int8,
+        module_bytes -> Int8,
+        block_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
     }
 }
 
@@ -1362,14 +1992,21 @@ diesel::allow_tables_to_appear_in_same_query!(
     ans_lookup_v2,
     ans_primary_name,
     ans_primary_name_v2,
+    ans_revenue_events,
+    asset_usd_prices,
     auth_key_account_addresses,
+    auth_key_schemes,
     backfill_processor_status,
     block_metadata_transactions,
     coin_activities,
     coin_balances,
+    coin_holder_counts,
+    coin_info_mutations,
     coin_infos,
+    coin_store_freeze_events,
     coin_supply,
     collection_datas,
+    collection_volume_leaderboard,
     collections_v2,
     current_ans_lookup,
     current_ans_lookup_v2,
@@ -1377,12 +2014,14 @@ diesel::allow_tables_to_appear_in_same_query!(
     current_ans_primary_name_v2,
     current_coin_balances,
     current_collection_datas,
+    current_collection_stats_v2,
     current_collections_v2,
     current_delegated_staking_pool_balances,
     current_delegated_voter,
     current_delegator_balances,
     current_fungible_asset_balances,
     current_fungible_asset_balances_legacy,
+    current_object_counts_by_owner,
     current_objects,
     current_staking_pool_voter,
     current_table_items,
@@ -1397,22 +2036,42 @@ diesel::allow_tables_to_appear_in_same_query!(
     delegated_staking_pool_balances,
     delegated_staking_pools,
     delegator_balances,
+    deleted_objects,
+    epoch_end_delegator_balances,
+    event_attributes,
     event_size_info,
     events,
+    events_compacted,
     fungible_asset_activities,
     fungible_asset_balances,
     fungible_asset_metadata,
     fungible_asset_to_coin_mappings,
     gas_fees,
+    governance_participation,
+    indexer_outbox,
     indexer_status,
+    indexer_table_stats,
+    indexer_table_stats_daily,
+    indexer_table_stats_hourly,
     ledger_infos,
     move_modules,
     move_resources,
     nft_points,
+    nft_transfers,
     objects,
+    parquet_file_manifest,
+    parquet_upload_checkpoints,
+    pool_commission_history,
+    pool_operator_history,
+    processor_consistency_watermarks,
+    processor_heartbeats,
+    processor_shard_status,
     processor_status,
     proposal_votes,
     public_key_auth_keys,
+    quarantined_rows,
+    raw_transactions,
+    sender_sequence_number_gaps,
     signatures,
     spam_assets,
     table_items,
@@ -1421,12 +2080,20 @@ diesel::allow_tables_to_appear_in_same_query!(
     token_activities_v2,
     token_datas,
     token_datas_v2,
+    token_mints_detail,
     token_ownerships,
     token_ownerships_v2,
+    token_property_values,
     tokens,
+    transaction_signers,
     transaction_size_info,
     transactions,
+    treasury_inflows,
     user_transactions,
+    validator_transaction_events,
+    validator_transactions,
+    watched_resources,
+    write_set_change_stats,
     write_set_changes,
     write_set_size_info,
 );
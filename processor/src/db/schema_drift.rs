@@ -0,0 +1,586 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    config::db_config::{PostgresConfig, SchemaDriftCheck},
+    db::partitioning::create_partitioned_tables,
+    MIGRATIONS,
+};
+use ahash::{AHashMap, AHashSet};
+use anyhow::Result;
+use cedra_indexer_processor_sdk::postgres::utils::database::{
+    run_migrations, ArcDbPool, DbPoolConnection,
+};
+use diesel::{
+    migration::MigrationSource,
+    pg::Pg,
+    sql_query,
+    sql_types::{Bool, Int4, Nullable, Text},
+    QueryableByName,
+};
+use diesel_async::RunQueryDsl;
+use regex::Regex;
+use serde::Serialize;
+use tracing::{info, warn};
+
+/// The schema the processors are compiled against, which migrations are expected to produce
+const SCHEMA_RS: &str = include_str!("schema.rs");
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaDriftKind {
+    MissingTable,
+    MissingColumn,
+    /// A column that isn't in `schema.rs`, usually added by hand
+    UnexpectedColumn,
+    ColumnType,
+    ColumnNullability,
+    /// Upserts rely on the primary key for their conflict target
+    PrimaryKey,
+    /// Left behind by a failed concurrent index build, still slows down writes
+    InvalidIndex,
+    /// Applied to the database but unknown to this build, the database is ahead of it
+    UnknownMigration,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct SchemaDrift {
+    pub kind: SchemaDriftKind,
+    pub table: Option<String>,
+    /// Column, index or migration version
+    pub name: Option<String>,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+    /// Suggested fix, never run automatically
+    pub repair: Option<String>,
+}
+
+/// Runs pending migrations and partitions the configured tables, then checks the live schema
+/// against `schema.rs` as configured by `schema_drift_check`.
+pub async fn run_migrations_and_check_drift(
+    postgres_config: &PostgresConfig,
+    db_pool: ArcDbPool,
+) -> Result<()> {
+    run_migrations(
+        postgres_config.connection_string.clone(),
+        db_pool.clone(),
+        MIGRATIONS,
+    )
+    .await;
+    create_partitioned_tables(postgres_config, db_pool.clone()).await?;
+    if postgres_config.schema_drift_check == SchemaDriftCheck::Off {
+        return Ok(());
+    }
+
+    let mut conn = db_pool.get().await?;
+    let drifts = get_schema_drift(&mut conn).await?;
+    if drifts.is_empty() {
+        info!("Live schema matches the migrations");
+        return Ok(());
+    }
+    for drift in &drifts {
+        warn!(
+            kind = ?drift.kind,
+            table = drift.table.as_deref(),
+            name = drift.name.as_deref(),
+            expected = drift.expected.as_deref(),
+            actual = drift.actual.as_deref(),
+            repair = drift.repair.as_deref(),
+            "Schema drift"
+        );
+    }
+    warn!(
+        report = %serde_json::to_string(&drifts)?,
+        "Found {} schema drifts",
+        drifts.len()
+    );
+    if postgres_config.schema_drift_check == SchemaDriftCheck::Fail {
+        anyhow::bail!(
+            "Found {} schema drifts, see the drift report in the logs",
+            drifts.len()
+        );
+    }
+    Ok(())
+}
+
+pub async fn get_schema_drift(conn: &mut DbPoolConnection<'_>) -> Result<Vec<SchemaDrift>> {
+    let columns: Vec<LiveColumn> = sql_query(
+        "SELECT c.table_name::text AS table_name,
+                c.column_name::text AS column_name,
+                c.udt_name::text AS udt_name,
+                c.character_maximum_length::int4 AS character_maximum_length,
+                c.is_nullable = 'YES' AS is_nullable,
+                (c.column_default IS NOT NULL OR c.is_generated = 'ALWAYS') AS has_default,
+                t.table_type = 'VIEW' AS is_view
+         FROM information_schema.columns c
+         JOIN information_schema.tables t
+           ON t.table_schema = c.table_schema AND t.table_name = c.table_name
+         WHERE c.table_schema = current_schema()",
+    )
+    .load(conn)
+    .await?;
+    let primary_key_columns: Vec<LivePrimaryKeyColumn> = sql_query(
+        "SELECT tc.table_name::text AS table_name, kcu.column_name::text AS column_name
+         FROM information_schema.table_constraints tc
+         JOIN information_schema.key_column_usage kcu
+           ON kcu.constraint_schema = tc.constraint_schema
+          AND kcu.constraint_name = tc.constraint_name
+         WHERE tc.constraint_type = 'PRIMARY KEY' AND tc.table_schema = current_schema()
+         ORDER BY tc.table_name, kcu.ordinal_position",
+    )
+    .load(conn)
+    .await?;
+    let invalid_indexes: Vec<LiveIndex> = sql_query(
+        "SELECT t.relname::text AS table_name, c.relname::text AS index_name
+         FROM pg_index i
+         JOIN pg_class c ON c.oid = i.indexrelid
+         JOIN pg_class t ON t.oid = i.indrelid
+         JOIN pg_namespace n ON n.oid = c.relnamespace
+         WHERE n.nspname = current_schema() AND NOT i.indisvalid",
+    )
+    .load(conn)
+    .await?;
+    let applied_migrations: Vec<AppliedMigration> =
+        sql_query("SELECT version::text AS version FROM __diesel_schema_migrations")
+            .load(conn)
+            .await?;
+
+    let mut primary_keys: AHashMap<String, Vec<String>> = AHashMap::new();
+    for column in primary_key_columns {
+        primary_keys
+            .entry(column.table_name)
+            .or_default()
+            .push(column.column_name);
+    }
+    let mut drifts = diff_schema(&parse_schema(SCHEMA_RS), &columns, &primary_keys);
+    drifts.extend(invalid_indexes.into_iter().map(|index| SchemaDrift {
+        kind: SchemaDriftKind::InvalidIndex,
+        repair: Some(format!("REINDEX INDEX CONCURRENTLY {};", index.index_name)),
+        table: Some(index.table_name),
+        name: Some(index.index_name),
+        expected: None,
+        actual: None,
+    }));
+
+    let known_migrations: AHashSet<String> = MigrationSource::<Pg>::migrations(&MIGRATIONS)
+        .map_err(|e| anyhow::anyhow!("Failed to list embedded migrations: {e}"))?
+        .iter()
+        .map(|migration| migration.name().version().to_string())
+        .collect();
+    drifts.extend(
+        applied_migrations
+            .into_iter()
+            .filter(|migration| !known_migrations.contains(&migration.version))
+            .map(|migration| SchemaDrift {
+                kind: SchemaDriftKind::UnknownMigration,
+                table: None,
+                name: Some(migration.version),
+                expected: None,
+                actual: None,
+                repair: None,
+            }),
+    );
+    Ok(drifts)
+}
+
+#[derive(Debug, QueryableByName)]
+struct LiveColumn {
+    #[diesel(sql_type = Text)]
+    table_name: String,
+    #[diesel(sql_type = Text)]
+    column_name: String,
+    #[diesel(sql_type = Text)]
+    udt_name: String,
+    #[diesel(sql_type = Nullable<Int4>)]
+    character_maximum_length: Option<i32>,
+    #[diesel(sql_type = Bool)]
+    is_nullable: bool,
+    #[diesel(sql_type = Bool)]
+    has_default: bool,
+    #[diesel(sql_type = Bool)]
+    is_view: bool,
+}
+
+impl LiveColumn {
+    fn sql_type(&self) -> String {
+        sql_type(&self.udt_name, self.character_maximum_length)
+    }
+}
+
+#[derive(Debug, QueryableByName)]
+struct LivePrimaryKeyColumn {
+    #[diesel(sql_type = Text)]
+    table_name: String,
+    #[diesel(sql_type = Text)]
+    column_name: String,
+}
+
+#[derive(Debug, QueryableByName)]
+struct LiveIndex {
+    #[diesel(sql_type = Text)]
+    table_name: String,
+    #[diesel(sql_type = Text)]
+    index_name: String,
+}
+
+#[derive(Debug, QueryableByName)]
+struct AppliedMigration {
+    #[diesel(sql_type = Text)]
+    version: String,
+}
+
+#[derive(Debug)]
+pub(crate) struct ExpectedTable {
+    pub(crate) name: String,
+    pub(crate) primary_key: Vec<String>,
+    pub(crate) columns: Vec<ExpectedColumn>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ExpectedColumn {
+    pub(crate) name: String,
+    /// Postgres type name, unset for diesel types this check doesn't know
+    pub(crate) udt_name: Option<&'static str>,
+    pub(crate) max_length: Option<i32>,
+    pub(crate) nullable: bool,
+}
+
+impl ExpectedColumn {
+    pub(crate) fn sql_type(&self) -> Option<String> {
+        self.udt_name
+            .map(|udt_name| sql_type(udt_name, self.max_length))
+    }
+}
+
+fn sql_type(udt_name: &str, max_length: Option<i32>) -> String {
+    match max_length {
+        Some(max_length) => format!("{udt_name}({max_length})"),
+        None => udt_name.to_string(),
+    }
+}
+
+/// Primary key of a table in `schema.rs`, with the Postgres type of each column
+pub(crate) fn expected_primary_key(
+    table_name: &str,
+) -> Option<Vec<(String, Option<&'static str>)>> {
+    let table = parse_schema(SCHEMA_RS)
+        .into_iter()
+        .find(|table| table.name == table_name)?;
+    Some(
+        table
+            .primary_key
+            .iter()
+            .map(|key| {
+                let udt_name = table
+                    .columns
+                    .iter()
+                    .find(|column| &column.name == key)
+                    .and_then(|column| column.udt_name);
+                (key.clone(), udt_name)
+            })
+            .collect(),
+    )
+}
+
+/// Tables of `schema.rs`, in the order they're declared
+pub(crate) fn expected_tables() -> Vec<ExpectedTable> {
+    parse_schema(SCHEMA_RS)
+}
+
+/// Reads the tables out of the `diesel::table!` blocks of `schema.rs`
+fn parse_schema(schema: &str) -> Vec<ExpectedTable> {
+    let table_re = Regex::new(r"^    (\w+) \(([\w, ]+)\) \{$").unwrap();
+    let column_re = Regex::new(r"^        (\w+) -> (.+),$").unwrap();
+    let max_length_re = Regex::new(r"^        #\[max_length = (\d+)\]$").unwrap();
+    let sql_name_re = Regex::new(r#"^        #\[sql_name = "(\w+)"\]$"#).unwrap();
+
+    let mut tables = vec![];
+    let mut table: Option<ExpectedTable> = None;
+    let (mut max_length, mut sql_name) = (None, None);
+    for line in schema.lines() {
+        if let Some(captures) = table_re.captures(line) {
+            tables.extend(table.take());
+            table = Some(ExpectedTable {
+                name: captures[1].to_string(),
+                primary_key: captures[2].split(", ").map(str::to_string).collect(),
+                columns: vec![],
+            });
+        } else if let Some(captures) = max_length_re.captures(line) {
+            max_length = captures[1].parse().ok();
+        } else if let Some(captures) = sql_name_re.captures(line) {
+            sql_name = Some(captures[1].to_string());
+        } else if let (Some(captures), Some(table)) = (column_re.captures(line), table.as_mut()) {
+            let (nullable, diesel_type) = match captures[2]
+                .strip_prefix("Nullable<")
+                .and_then(|inner| inner.strip_suffix('>'))
+            {
+                Some(inner) => (true, inner),
+                None => (false, &captures[2]),
+            };
+            table.columns.push(ExpectedColumn {
+                name: sql_name.take().unwrap_or_else(|| captures[1].to_string()),
+                udt_name: udt_name(diesel_type),
+                max_length: max_length.take(),
+                nullable,
+            });
+        }
+    }
+    tables.extend(table);
+    tables
+}
+
+fn udt_name(diesel_type: &str) -> Option<&'static str> {
+    match diesel_type {
+        "Bool" => Some("bool"),
+        "Bytea" => Some("bytea"),
+        "Int2" => Some("int2"),
+        "Int4" => Some("int4"),
+        "Int8" => Some("int8"),
+        "Jsonb" => Some("jsonb"),
+        "Numeric" => Some("numeric"),
+        "Text" => Some("text"),
+        "Timestamp" => Some("timestamp"),
+        "Varchar" => Some("varchar"),
+        "Array<Nullable<Text>>" => Some("_text"),
+        _ => None,
+    }
+}
+
+fn diff_schema(
+    expected_tables: &[ExpectedTable],
+    live_columns: &[LiveColumn],
+    live_primary_keys: &AHashMap<String, Vec<String>>,
+) -> Vec<SchemaDrift> {
+    let mut live_tables: AHashMap<&str, AHashMap<&str, &LiveColumn>> = AHashMap::new();
+    for column in live_columns {
+        live_tables
+            .entry(column.table_name.as_str())
+            .or_default()
+            .insert(column.column_name.as_str(), column);
+    }
+
+    let mut drifts = vec![];
+    let drift = |kind, table: &str, name: Option<&str>| SchemaDrift {
+        kind,
+        table: Some(table.to_string()),
+        name: name.map(str::to_string),
+        expected: None,
+        actual: None,
+        repair: None,
+    };
+    for table in expected_tables {
+        let Some(live_table) = live_tables.get(table.name.as_str()) else {
+            drifts.push(drift(SchemaDriftKind::MissingTable, &table.name, None));
+            continue;
+        };
+        let is_view = live_table.values().any(|column| column.is_view);
+        for column in &table.columns {
+            let Some(live_column) = live_table.get(column.name.as_str()) else {
+                drifts.push(SchemaDrift {
+                    expected: column.sql_type(),
+                    repair: column.sql_type().map(|sql_type| {
+                        format!(
+                            "ALTER TABLE {} ADD COLUMN {} {};",
+                            table.name, column.name, sql_type
+                        )
+                    }),
+                    ..drift(
+                        SchemaDriftKind::MissingColumn,
+                        &table.name,
+                        Some(column.name.as_str()),
+                    )
+                });
+                continue;
+            };
+            if let Some(expected_type) = column.sql_type() {
+                let actual_type = live_column.sql_type();
+                if expected_type != actual_type {
+                    drifts.push(SchemaDrift {
+                        repair: Some(format!(
+                            "ALTER TABLE {} ALTER COLUMN {} TYPE {};",
+                            table.name, column.name, expected_type
+                        )),
+                        expected: Some(expected_type),
+                        actual: Some(actual_type),
+                        ..drift(
+                            SchemaDriftKind::ColumnType,
+                            &table.name,
+                            Some(column.name.as_str()),
+                        )
+                    });
+                }
+            }
+            // Postgres reports every view column as nullable
+            if !is_view && column.nullable != live_column.is_nullable {
+                let nullability = |nullable: bool| if nullable { "NULL" } else { "NOT NULL" };
+                drifts.push(SchemaDrift {
+                    expected: Some(nullability(column.nullable).to_string()),
+                    actual: Some(nullability(live_column.is_nullable).to_string()),
+                    repair: Some(format!(
+                        "ALTER TABLE {} ALTER COLUMN {} {} NOT NULL;",
+                        table.name,
+                        column.name,
+                        if column.nullable { "DROP" } else { "SET" }
+                    )),
+                    ..drift(
+                        SchemaDriftKind::ColumnNullability,
+                        &table.name,
+                        Some(column.name.as_str()),
+                    )
+                });
+            }
+        }
+
+        let expected_columns: AHashSet<&str> = table
+            .columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect();
+        let mut unexpected_columns: Vec<&&LiveColumn> = live_table
+            .iter()
+            .filter(|(name, _)| !expected_columns.contains(*name))
+            .map(|(_, column)| column)
+            .collect();
+        unexpected_columns.sort_by(|a, b| a.column_name.cmp(&b.column_name));
+        for live_column in unexpected_columns {
+            // Only a required column without a default gets in the way of the processor's inserts
+            let blocks_inserts = !live_column.is_nullable && !live_column.has_default;
+            drifts.push(SchemaDrift {
+                actual: Some(live_column.sql_type()),
+                repair: blocks_inserts.then(|| {
+                    format!(
+                        "ALTER TABLE {} ALTER COLUMN {} DROP NOT NULL;",
+                        table.name, live_column.column_name
+                    )
+                }),
+                ..drift(
+                    SchemaDriftKind::UnexpectedColumn,
+                    &table.name,
+                    Some(live_column.column_name.as_str()),
+                )
+            });
+        }
+
+        let live_primary_key = live_primary_keys
+            .get(&table.name)
+            .cloned()
+            .unwrap_or_default();
+        if !is_view && live_primary_key != table.primary_key {
+            drifts.push(SchemaDrift {
+                expected: Some(table.primary_key.join(", ")),
+                actual: Some(live_primary_key.join(", ")),
+                ..drift(SchemaDriftKind::PrimaryKey, &table.name, None)
+            });
+        }
+    }
+    drifts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn live_column(table_name: &str, column_name: &str, udt_name: &str) -> LiveColumn {
+        LiveColumn {
+            table_name: table_name.to_string(),
+            column_name: column_name.to_string(),
+            udt_name: udt_name.to_string(),
+            character_maximum_length: None,
+            is_nullable: false,
+            has_default: false,
+            is_view: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_schema() {
+        let tables = parse_schema(SCHEMA_RS);
+        let events = tables.iter().find(|table| table.name == "events").unwrap();
+        assert_eq!(events.primary_key, vec![
+            "transaction_version",
+            "event_index"
+        ]);
+        let type_column = events
+            .columns
+            .iter()
+            .find(|column| column.name == "type")
+            .unwrap();
+        assert_eq!(type_column.udt_name, Some("text"));
+        assert!(!type_column.nullable);
+        let account_address = events
+            .columns
+            .iter()
+            .find(|column| column.name == "account_address")
+            .unwrap();
+        assert_eq!(account_address.sql_type(), Some("varchar(66)".to_string()));
+    }
+
+    #[test]
+    fn test_diff_schema() {
+        let expected = parse_schema(
+            r#"diesel::table! {
+    balances (owner, asset) {
+        #[max_length = 66]
+        owner -> Varchar,
+        #[max_length = 66]
+        asset -> Varchar,
+        amount -> Numeric,
+        memo -> Nullable<Text>,
+        last_version -> Int8,
+    }
+}
+
+diesel::table! {
+    missing (id) {
+        id -> Int8,
+    }
+}
+"#,
+        );
+        let mut owner = live_column("balances", "owner", "varchar");
+        owner.character_maximum_length = Some(66);
+        let mut asset = live_column("balances", "asset", "varchar");
+        asset.character_maximum_length = Some(66);
+        let live = vec![
+            owner,
+            asset,
+            live_column("balances", "amount", "int8"),
+            live_column("balances", "memo", "text"),
+            live_column("balances", "added_by_hand", "text"),
+        ];
+        let primary_keys: AHashMap<String, Vec<String>> =
+            [("balances".to_string(), vec!["owner".to_string()])]
+                .into_iter()
+                .collect();
+
+        let drifts: Vec<(SchemaDriftKind, Option<String>, Option<String>)> =
+            diff_schema(&expected, &live, &primary_keys)
+                .into_iter()
+                .map(|drift| (drift.kind, drift.name, drift.repair))
+                .collect();
+        assert_eq!(drifts, vec![
+            (
+                SchemaDriftKind::ColumnType,
+                Some("amount".to_string()),
+                Some("ALTER TABLE balances ALTER COLUMN amount TYPE numeric;".to_string())
+            ),
+            (
+                SchemaDriftKind::ColumnNullability,
+                Some("memo".to_string()),
+                Some("ALTER TABLE balances ALTER COLUMN memo DROP NOT NULL;".to_string())
+            ),
+            (
+                SchemaDriftKind::MissingColumn,
+                Some("last_version".to_string()),
+                Some("ALTER TABLE balances ADD COLUMN last_version int8;".to_string())
+            ),
+            (
+                SchemaDriftKind::UnexpectedColumn,
+                Some("added_by_hand".to_string()),
+                Some("ALTER TABLE balances ALTER COLUMN added_by_hand DROP NOT NULL;".to_string())
+            ),
+            (SchemaDriftKind::PrimaryKey, None, None),
+            (SchemaDriftKind::MissingTable, None, None),
+        ]);
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tables parquet processors track their progress in, created without running the migrations.
+//!
+//! The definitions match the tables as the migrations leave them, so parquet processors read and
+//! write them the same way either way. A database bootstrapped this way can't run the migrations
+//! afterwards, since the earlier migrations create these tables unconditionally.
+
+use anyhow::{Context, Result};
+use cedra_indexer_processor_sdk::postgres::utils::database::ArcDbPool;
+use diesel::sql_query;
+use diesel_async::RunQueryDsl;
+use tracing::info;
+
+const STATUS_TABLE_QUERIES: &[&str] = &[
+    "CREATE SCHEMA IF NOT EXISTS processor_metadata",
+    "CREATE TABLE IF NOT EXISTS processor_metadata.processor_status (
+      processor VARCHAR(100) UNIQUE PRIMARY KEY NOT NULL,
+      last_success_version BIGINT NOT NULL,
+      last_updated TIMESTAMP NOT NULL DEFAULT NOW(),
+      last_transaction_timestamp TIMESTAMP NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS processor_metadata.ledger_infos (
+      chain_id BIGINT UNIQUE PRIMARY KEY NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS backfill_processor_status (
+      backfill_alias VARCHAR(100) UNIQUE PRIMARY KEY NOT NULL,
+      backfill_status VARCHAR(50) NOT NULL,
+      last_success_version BIGINT NOT NULL,
+      last_updated TIMESTAMP NOT NULL DEFAULT NOW(),
+      last_transaction_timestamp TIMESTAMP NULL,
+      backfill_start_version BIGINT NOT NULL,
+      backfill_end_version BIGINT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS processor_consistency_watermarks (
+      processor VARCHAR(100) UNIQUE PRIMARY KEY NOT NULL,
+      consistent_through_version BIGINT NOT NULL,
+      last_updated TIMESTAMP NOT NULL DEFAULT NOW(),
+      last_transaction_timestamp TIMESTAMP NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS processor_heartbeats (
+      processor VARCHAR(100) UNIQUE PRIMARY KEY NOT NULL,
+      last_success_version BIGINT NOT NULL,
+      last_batch_size BIGINT NOT NULL,
+      last_transaction_timestamp TIMESTAMP NULL,
+      last_heartbeat_at TIMESTAMP NOT NULL
+    )",
+    "CREATE OR REPLACE VIEW processor_staleness AS
+    SELECT processor,
+      last_success_version,
+      last_batch_size,
+      last_transaction_timestamp,
+      last_heartbeat_at,
+      EXTRACT(EPOCH FROM (NOW() AT TIME ZONE 'UTC') - last_heartbeat_at)::BIGINT
+        AS seconds_since_heartbeat,
+      EXTRACT(EPOCH FROM (NOW() AT TIME ZONE 'UTC') - last_transaction_timestamp)::BIGINT
+        AS seconds_behind_chain
+    FROM processor_heartbeats",
+    "CREATE TABLE IF NOT EXISTS parquet_file_manifest (
+      id BIGSERIAL PRIMARY KEY,
+      processor VARCHAR(100) NOT NULL,
+      table_name VARCHAR(100) NOT NULL,
+      bucket_name VARCHAR(255) NOT NULL,
+      file_path TEXT NOT NULL,
+      file_format VARCHAR(10) NOT NULL,
+      num_rows BIGINT NOT NULL,
+      min_version BIGINT NOT NULL,
+      max_version BIGINT NOT NULL,
+      min_block_timestamp TIMESTAMP,
+      max_block_timestamp TIMESTAMP,
+      file_size_bytes BIGINT NOT NULL,
+      inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+      window_start_version BIGINT,
+      window_end_version BIGINT,
+      UNIQUE (bucket_name, file_path)
+    )",
+    "CREATE INDEX IF NOT EXISTS pfm_table_index ON parquet_file_manifest (table_name, id)",
+    // For manifests bootstrapped before files recorded their version window
+    "ALTER TABLE parquet_file_manifest
+      ADD COLUMN IF NOT EXISTS window_start_version BIGINT,
+      ADD COLUMN IF NOT EXISTS window_end_version BIGINT",
+    "CREATE INDEX IF NOT EXISTS pfm_window_index
+      ON parquet_file_manifest (bucket_name, table_name, window_start_version)",
+    "CREATE TABLE IF NOT EXISTS parquet_upload_checkpoints (
+      processor VARCHAR(100) UNIQUE PRIMARY KEY NOT NULL,
+      bucket_name VARCHAR(255) NOT NULL,
+      file_path TEXT NOT NULL,
+      max_version BIGINT NOT NULL,
+      end_version BIGINT NOT NULL,
+      last_updated TIMESTAMP NOT NULL DEFAULT NOW()
+    )",
+];
+
+/// Creates the status, chain id, backfill, consistency watermark, heartbeat, file manifest and
+/// upload checkpoint tables if they don't exist.
+pub async fn create_status_tables(db_pool: ArcDbPool) -> Result<()> {
+    let mut conn = db_pool.get().await?;
+    for query in STATUS_TABLE_QUERIES {
+        sql_query(*query)
+            .execute(&mut conn)
+            .await
+            .with_context(|| format!("Failed to create status tables with {query}"))?;
+    }
+    info!("Status tables are created");
+    Ok(())
+}
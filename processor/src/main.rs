@@ -1,9 +1,28 @@
 // Copyright © Cedra Foundation
 // SPDX-License-Identifier: Apache-2.0
 use anyhow::Result;
-use cedra_indexer_processor_sdk::server_framework::ServerArgs;
-use clap::Parser;
-use processor::config::indexer_processor_config::IndexerProcessorConfig;
+use cedra_indexer_processor_sdk::server_framework::{
+    load, run_server_with_config, setup_panic_handler, GenericConfig, ServerArgs,
+};
+use clap::{Args, Parser, Subcommand};
+#[cfg(unix)]
+use processor::utils::config_reload::spawn_config_reloader;
+use processor::{
+    config::{explain_config::explain_config, indexer_processor_config::IndexerProcessorConfig},
+    utils::{
+        audit::audit,
+        catalog_export::{
+            dbt_sources_yaml, DEFAULT_PARQUET_SOURCE_NAME, DEFAULT_POSTGRES_SOURCE_NAME,
+        },
+        config_reload::load_server_config,
+        conflict_simulation::explain_conflicts,
+        csv_export::{export_csv, CsvFormat, ExportTarget},
+        data_dictionary::{data_dictionary, DictionaryFormat},
+        logging::{log_filter, set_log_level, setup_logging, LogFormat},
+        transaction_recorder::{parse_version_range, record_transactions},
+    },
+};
+use std::path::PathBuf;
 
 #[cfg(unix)]
 #[global_allocator]
@@ -11,7 +30,187 @@ static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
 const RUNTIME_WORKER_MULTIPLIER: usize = 2;
 
+#[derive(Args)]
+struct ExplainConfigArgs {
+    #[clap(short, long, value_parser)]
+    config_path: PathBuf,
+}
+
+#[derive(Args)]
+struct ExportDbtSourcesArgs {
+    #[clap(long, default_value = DEFAULT_POSTGRES_SOURCE_NAME)]
+    postgres_source_name: String,
+    #[clap(long, default_value = DEFAULT_PARQUET_SOURCE_NAME)]
+    parquet_source_name: String,
+}
+
+#[derive(Args)]
+struct ExplainConflictsArgs {
+    #[clap(short, long, value_parser)]
+    config_path: PathBuf,
+    #[clap(long)]
+    starting_version: u64,
+    #[clap(long)]
+    ending_version: u64,
+}
+
+#[derive(Args)]
+struct RecordArgs {
+    #[clap(short, long, value_parser)]
+    config_path: PathBuf,
+    /// `<start>-<end>`, inclusive, or a single version
+    #[clap(long)]
+    versions: String,
+    #[clap(long, value_parser)]
+    out: PathBuf,
+}
+
+#[derive(Args)]
+struct ExportCsvArgs {
+    #[clap(short, long, value_parser)]
+    config_path: PathBuf,
+    /// `<start>-<end>`, inclusive, or a single version
+    #[clap(long)]
+    versions: String,
+    #[clap(long)]
+    out: String,
+    #[clap(long, value_enum, default_value_t = CsvFormat::Csv)]
+    format: CsvFormat,
+}
+
+#[derive(Args)]
+struct AuditArgs {
+    #[clap(short, long, value_parser)]
+    config_path: PathBuf,
+    /// `<start>..<end>` or `<start>-<end>`, inclusive, or a single version
+    #[clap(long)]
+    versions: String,
+}
+
+#[derive(Args)]
+struct ExportDataDictionaryArgs {
+    #[clap(long, value_enum, default_value_t = DictionaryFormat::Markdown)]
+    format: DictionaryFormat,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// `processor explain-config --config-path <path>` validates the config and prints how it
+    /// resolves, without starting the processor
+    ExplainConfig(ExplainConfigArgs),
+    /// `processor export-dbt-sources` prints a dbt `sources.yml` describing every table the
+    /// processors write
+    ExportDbtSources(ExportDbtSourcesArgs),
+    /// `processor explain-conflicts --config-path <path> --starting-version <v>
+    /// --ending-version <v>` processes the versions without writing, and reports the rows of
+    /// current tables that data of a lower version would overwrite
+    ExplainConflicts(ExplainConflictsArgs),
+    /// `processor record --config-path <path> --versions 1000-1010 --out <dir>` writes the
+    /// transactions of the versions, from the config's transaction stream, as integration test
+    /// fixtures
+    Record(RecordArgs),
+    /// `processor export-csv --config-path <path> --versions 1000-2000 --out <dir or
+    /// gs://bucket/prefix>` processes the versions without writing to the database, and writes
+    /// the rows of every table to a CSV or TSV file instead
+    ExportCsv(ExportCsvArgs),
+    /// `processor audit --config-path <path> --versions 1000..2000` processes the versions
+    /// without writing, and reports the rows of every table that differ from what's stored
+    Audit(AuditArgs),
+    /// `processor export-data-dictionary` prints the tables the processors write, with the
+    /// processors writing each and the type and source proto field of every column
+    ExportDataDictionary(ExportDataDictionaryArgs),
+}
+
+/// The SDK's server args, with how to log. Runs the processor unless a command is given.
+#[derive(Parser)]
+#[clap(args_conflicts_with_subcommands = true)]
+struct ProcessorArgs {
+    #[clap(subcommand)]
+    command: Option<Command>,
+    #[clap(flatten)]
+    server_args: ServerArgs,
+    /// `json`, with the batch being processed in every line, or `text`
+    #[clap(long, value_enum, default_value_t = LogFormat::Json)]
+    log_format: LogFormat,
+}
+
+/// Runs a command to completion, without starting the processor
+fn run_command(command: Command) -> Result<()> {
+    match command {
+        Command::ExplainConfig(args) => {
+            let config = load_server_config(&args.config_path)?;
+            print!("{}", explain_config(&config)?);
+        },
+        Command::ExportDbtSources(args) => {
+            print!(
+                "{}",
+                dbt_sources_yaml(&args.postgres_source_name, &args.parquet_source_name)?
+            );
+        },
+        Command::ExplainConflicts(args) => {
+            let config = load_server_config(&args.config_path)?;
+            let report = tokio::runtime::Runtime::new()?.block_on(explain_conflicts(
+                config,
+                args.starting_version,
+                args.ending_version,
+            ))?;
+            print!("{report}");
+        },
+        Command::Record(args) => {
+            let config = load_server_config(&args.config_path)?;
+            let (starting_version, ending_version) = parse_version_range(&args.versions)?;
+            let paths = tokio::runtime::Runtime::new()?.block_on(record_transactions(
+                &config,
+                starting_version,
+                ending_version,
+                &args.out,
+            ))?;
+            for path in paths {
+                println!("{}", path.display());
+            }
+        },
+        Command::ExportCsv(args) => {
+            let config = load_server_config(&args.config_path)?;
+            let (starting_version, ending_version) = parse_version_range(&args.versions)?;
+            let target = ExportTarget::parse(&args.out)?;
+            let files = tokio::runtime::Runtime::new()?.block_on(export_csv(
+                config,
+                starting_version,
+                ending_version,
+                args.format,
+                &target,
+            ))?;
+            for file in files {
+                println!("{file}");
+            }
+        },
+        Command::Audit(args) => {
+            let config = load_server_config(&args.config_path)?;
+            let (starting_version, ending_version) = parse_version_range(&args.versions)?;
+            let report = tokio::runtime::Runtime::new()?.block_on(audit(
+                config,
+                starting_version,
+                ending_version,
+            ))?;
+            print!("{report}");
+        },
+        Command::ExportDataDictionary(args) => {
+            print!("{}", data_dictionary(args.format)?);
+        },
+    }
+    Ok(())
+}
+
 fn main() -> Result<()> {
+    let ProcessorArgs {
+        command,
+        server_args,
+        log_format,
+    } = ProcessorArgs::parse();
+    if let Some(command) = command {
+        return run_command(command);
+    }
+
     let num_cpus = num_cpus::get();
     let worker_threads = (num_cpus * RUNTIME_WORKER_MULTIPLIER).max(16);
 
@@ -22,9 +221,16 @@ fn main() -> Result<()> {
         .worker_threads(worker_threads)
         .build()
         .unwrap()
-        .block_on(async {
-            let args = ServerArgs::parse();
-            args.run::<IndexerProcessorConfig>(tokio::runtime::Handle::current())
-                .await
+        .block_on(async move {
+            setup_logging(log_format);
+            setup_panic_handler();
+            let config_path = server_args.config_path;
+            #[cfg(unix)]
+            spawn_config_reloader(config_path.clone());
+            let config = load::<GenericConfig<IndexerProcessorConfig>>(&config_path)?;
+            if let Some(log_level) = &config.server_config.log_level {
+                set_log_level(log_filter(Some(log_level))?)?;
+            }
+            run_server_with_config(config, tokio::runtime::Handle::current()).await
         })
 }
@@ -1,10 +1,18 @@
 use crate::{
-    config::db_config::DbConfig,
+    config::{
+        db_config::{DbConfig, ParquetConfig, ParquetPathLayout, TableOutputFormat},
+        processor_config::ParquetWriterProperties,
+    },
+    db::status_tables::create_status_tables,
     parquet_processors::{
         parquet_transaction_metadata::transaction_metadata_models::write_set_size_info::ParquetWriteSetSize,
         parquet_utils::{
-            gcs_uploader::{create_new_writer, GCSUploader},
+            azure_blob_uploader::AzureBlobClient,
+            gcs_uploader::{
+                create_new_writer, with_chain_id_column, GCSUploader, ParquetStorageClient,
+            },
             parquet_buffer_step::ParquetBufferStep,
+            util::HasParquetSchema,
         },
     },
     processors::{
@@ -33,9 +41,15 @@ use crate::{
         },
         objects::v2_objects_models::{ParquetCurrentObject, ParquetObject},
         stake::models::{
+            current_delegated_voter::ParquetCurrentDelegatedVoter,
             delegator_activities::ParquetDelegatedStakingActivity,
             delegator_balances::{ParquetCurrentDelegatorBalance, ParquetDelegatorBalance},
+            delegator_pools::{
+                ParquetCurrentDelegatorPoolBalance, ParquetDelegatorPool,
+                ParquetDelegatorPoolBalance,
+            },
             proposal_votes::ParquetProposalVote,
+            staking_pool_voter::ParquetCurrentStakingPoolVoter,
         },
         token_v2::{
             token_models::{
@@ -54,16 +68,19 @@ use crate::{
             signatures::ParquetSignature, user_transactions::ParquetUserTransaction,
         },
     },
-    utils::table_flags::TableFlags,
+    utils::{processing_context::ProcessingContext, table_flags::TableFlags},
+    MIGRATIONS,
 };
+use anyhow::Context;
 use cedra_indexer_processor_sdk::{
-    postgres::utils::database::{new_db_pool, ArcDbPool},
+    postgres::utils::database::{new_db_pool, run_migrations, ArcDbPool},
+    types::transaction_context::TransactionMetadata,
     utils::errors::ProcessorError,
 };
 use async_trait::async_trait;
 use enum_dispatch::enum_dispatch;
 use google_cloud_storage::client::{Client as GCSClient, ClientConfig as GcsClientConfig};
-use parquet::schema::types::Type;
+use parquet::{file::properties::WriterProperties, schema::types::Type};
 #[allow(unused_imports)]
 use serde::{Deserialize, Serialize};
 use std::{
@@ -155,24 +172,44 @@ pub enum ParquetTypeEnum {
     CurrentDelegatorBalances,
     DelegatorBalances,
     ProposalVotes,
+    CurrentStakingPoolVoter,
+    DelegatedStakingPools,
+    DelegatedStakingPoolBalances,
+    CurrentDelegatedStakingPoolBalances,
+    CurrentDelegatedVoter,
     // Objects
     Objects,
     CurrentObjects,
 }
 
+impl ParquetTypeEnum {
+    /// The flag selecting the table in `backfill_table`
+    pub fn table_flag(&self) -> TableFlags {
+        match self {
+            ParquetTypeEnum::CurrentTokenRoyaltiesV1 => TableFlags::CURRENT_TOKEN_ROYALTY_V1,
+            _ => TableFlags::from_name(&self.to_string().to_uppercase())
+                .unwrap_or_else(|| panic!("No table flag for {self}")),
+        }
+    }
+}
+
 /// Trait for handling various Parquet types.
 #[async_trait]
 #[enum_dispatch]
 pub trait ParquetTypeTrait: std::fmt::Debug + Send + Sync {
     fn parquet_type(&self) -> ParquetTypeEnum;
     fn calculate_size(&self) -> usize;
+    fn is_empty(&self) -> bool;
+    /// Parquet schema of the rows, without the optional `chain_id` column
+    fn schema(&self) -> Arc<Type>;
 
     async fn upload_to_gcs(
         &self,
         uploader: &mut GCSUploader,
         parquet_type: ParquetTypeEnum,
         table_name: &str,
-    ) -> anyhow::Result<()>;
+        buffer_metadata: &TransactionMetadata,
+    ) -> anyhow::Result<Option<TransactionMetadata>>;
 }
 
 /// Macro for implementing ParquetTypeTrait for multiple types.
@@ -188,14 +225,23 @@ macro_rules! impl_parquet_trait {
                 allocative::size_of_unique(self)
             }
 
+            fn is_empty(&self) -> bool {
+                Vec::is_empty(self)
+            }
+
+            fn schema(&self) -> Arc<Type> {
+                <$type>::schema()
+            }
+
             async fn upload_to_gcs(
                 &self,
                 uploader: &mut GCSUploader,
                 parquet_type: ParquetTypeEnum,
                 table_name: &str,
-            ) -> anyhow::Result<()> {
+                buffer_metadata: &TransactionMetadata,
+            ) -> anyhow::Result<Option<TransactionMetadata>> {
                 uploader
-                    .upload_generic(self, parquet_type, table_name)
+                    .upload_generic(self, parquet_type, table_name, buffer_metadata)
                     .await
             }
         }
@@ -290,6 +336,23 @@ impl_parquet_trait!(
 );
 impl_parquet_trait!(ParquetDelegatorBalance, ParquetTypeEnum::DelegatorBalances);
 impl_parquet_trait!(ParquetProposalVote, ParquetTypeEnum::ProposalVotes);
+impl_parquet_trait!(
+    ParquetCurrentStakingPoolVoter,
+    ParquetTypeEnum::CurrentStakingPoolVoter
+);
+impl_parquet_trait!(ParquetDelegatorPool, ParquetTypeEnum::DelegatedStakingPools);
+impl_parquet_trait!(
+    ParquetDelegatorPoolBalance,
+    ParquetTypeEnum::DelegatedStakingPoolBalances
+);
+impl_parquet_trait!(
+    ParquetCurrentDelegatorPoolBalance,
+    ParquetTypeEnum::CurrentDelegatedStakingPoolBalances
+);
+impl_parquet_trait!(
+    ParquetCurrentDelegatedVoter,
+    ParquetTypeEnum::CurrentDelegatedVoter
+);
 impl_parquet_trait!(ParquetObject, ParquetTypeEnum::Objects);
 impl_parquet_trait!(ParquetCurrentObject, ParquetTypeEnum::CurrentObjects);
 impl_parquet_trait!(ParquetCollectionV2, ParquetTypeEnum::CollectionsV2);
@@ -342,6 +405,11 @@ pub enum ParquetTypeStructs {
     CurrentDelegatorBalance(Vec<ParquetCurrentDelegatorBalance>),
     DelegatorBalance(Vec<ParquetDelegatorBalance>),
     ProposalVote(Vec<ParquetProposalVote>),
+    CurrentStakingPoolVoter(Vec<ParquetCurrentStakingPoolVoter>),
+    DelegatorPool(Vec<ParquetDelegatorPool>),
+    DelegatorPoolBalance(Vec<ParquetDelegatorPoolBalance>),
+    CurrentDelegatorPoolBalance(Vec<ParquetCurrentDelegatorPoolBalance>),
+    CurrentDelegatedVoter(Vec<ParquetCurrentDelegatedVoter>),
     // Objects
     Object(Vec<ParquetObject>),
     CurrentObject(Vec<ParquetCurrentObject>),
@@ -419,6 +487,19 @@ impl ParquetTypeStructs {
             },
             ParquetTypeEnum::DelegatorBalances => ParquetTypeStructs::DelegatorBalance(Vec::new()),
             ParquetTypeEnum::ProposalVotes => ParquetTypeStructs::ProposalVote(Vec::new()),
+            ParquetTypeEnum::CurrentStakingPoolVoter => {
+                ParquetTypeStructs::CurrentStakingPoolVoter(Vec::new())
+            },
+            ParquetTypeEnum::DelegatedStakingPools => ParquetTypeStructs::DelegatorPool(Vec::new()),
+            ParquetTypeEnum::DelegatedStakingPoolBalances => {
+                ParquetTypeStructs::DelegatorPoolBalance(Vec::new())
+            },
+            ParquetTypeEnum::CurrentDelegatedStakingPoolBalances => {
+                ParquetTypeStructs::CurrentDelegatorPoolBalance(Vec::new())
+            },
+            ParquetTypeEnum::CurrentDelegatedVoter => {
+                ParquetTypeStructs::CurrentDelegatedVoter(Vec::new())
+            },
             ParquetTypeEnum::Objects => ParquetTypeStructs::Object(Vec::new()),
             ParquetTypeEnum::CurrentObjects => ParquetTypeStructs::CurrentObject(Vec::new()),
             ParquetTypeEnum::CollectionsV2 => ParquetTypeStructs::CollectionV2(Vec::new()),
@@ -643,6 +724,36 @@ impl ParquetTypeStructs {
             ) => {
                 handle_append!(self_data, other_data)
             },
+            (
+                ParquetTypeStructs::CurrentStakingPoolVoter(self_data),
+                ParquetTypeStructs::CurrentStakingPoolVoter(other_data),
+            ) => {
+                handle_append!(self_data, other_data)
+            },
+            (
+                ParquetTypeStructs::DelegatorPool(self_data),
+                ParquetTypeStructs::DelegatorPool(other_data),
+            ) => {
+                handle_append!(self_data, other_data)
+            },
+            (
+                ParquetTypeStructs::DelegatorPoolBalance(self_data),
+                ParquetTypeStructs::DelegatorPoolBalance(other_data),
+            ) => {
+                handle_append!(self_data, other_data)
+            },
+            (
+                ParquetTypeStructs::CurrentDelegatorPoolBalance(self_data),
+                ParquetTypeStructs::CurrentDelegatorPoolBalance(other_data),
+            ) => {
+                handle_append!(self_data, other_data)
+            },
+            (
+                ParquetTypeStructs::CurrentDelegatedVoter(self_data),
+                ParquetTypeStructs::CurrentDelegatedVoter(other_data),
+            ) => {
+                handle_append!(self_data, other_data)
+            },
             (ParquetTypeStructs::Object(self_data), ParquetTypeStructs::Object(other_data)) => {
                 handle_append!(self_data, other_data)
             },
@@ -678,6 +789,20 @@ async fn initialize_gcs_client(credentials: Option<String>) -> Arc<GCSClient> {
     Arc::new(GCSClient::new(gcs_config))
 }
 
+/// Initializes the client of the bucket parquet files are uploaded to.
+async fn initialize_storage_client(
+    parquet_config: &ParquetConfig,
+) -> anyhow::Result<ParquetStorageClient> {
+    Ok(match &parquet_config.azure_blob_config {
+        Some(azure_blob_config) => {
+            ParquetStorageClient::AzureBlob(Arc::new(AzureBlobClient::new(azure_blob_config)?))
+        },
+        None => ParquetStorageClient::Gcs(
+            initialize_gcs_client(parquet_config.google_application_credentials.clone()).await,
+        ),
+    })
+}
+
 /// Initializes the database connection pool.
 async fn initialize_database_pool(config: &DbConfig) -> anyhow::Result<ArcDbPool> {
     match config {
@@ -700,31 +825,85 @@ async fn initialize_database_pool(config: &DbConfig) -> anyhow::Result<ArcDbPool
     }
 }
 
+/// Creates the tables the processor tracks its progress in, either through the migrations or on
+/// their own if `status_tables_only` is set.
+async fn run_parquet_migrations(
+    parquet_config: &ParquetConfig,
+    db_pool: ArcDbPool,
+) -> anyhow::Result<()> {
+    if parquet_config.status_tables_only {
+        create_status_tables(db_pool).await
+    } else {
+        run_migrations(
+            parquet_config.connection_string.clone(),
+            db_pool,
+            MIGRATIONS,
+        )
+        .await;
+        Ok(())
+    }
+}
+
 /// Initializes the Parquet buffer step.
 async fn initialize_parquet_buffer_step(
-    gcs_client: Arc<GCSClient>,
+    storage_client: ParquetStorageClient,
     parquet_type_to_schemas: HashMap<ParquetTypeEnum, Arc<Type>>,
     upload_interval: u64,
     max_buffer_size: usize,
+    writer_properties: &ParquetWriterProperties,
     bucket_name: String,
     bucket_root: String,
-    processor_name: String,
+    path_layout: ParquetPathLayout,
+    table_output_formats: HashMap<String, TableOutputFormat>,
+    tag_rows_with_chain_id: bool,
+    processing_context: ProcessingContext,
+    db_pool: ArcDbPool,
+    hold_back_noncontiguous_files: bool,
 ) -> anyhow::Result<ParquetBufferStep> {
+    let parquet_type_to_schemas = if tag_rows_with_chain_id {
+        parquet_type_to_schemas
+            .into_iter()
+            .map(|(key, schema)| Ok((key, with_chain_id_column(&schema)?)))
+            .collect::<anyhow::Result<_>>()?
+    } else {
+        parquet_type_to_schemas
+    };
+    let parquet_type_to_writer_properties: HashMap<ParquetTypeEnum, Arc<WriterProperties>> =
+        parquet_type_to_schemas
+            .keys()
+            .map(|key| {
+                let table_name = key.to_string();
+                let props = writer_properties
+                    .for_table(&table_name)
+                    .with_context(|| format!("Invalid writer properties of {table_name}"))?;
+                Ok((*key, Arc::new(props)))
+            })
+            .collect::<anyhow::Result<_>>()?;
     let parquet_type_to_writer = parquet_type_to_schemas
         .iter()
         .map(|(key, schema)| {
-            let writer = create_new_writer(schema.clone()).expect("Failed to create writer");
+            let writer = create_new_writer(
+                schema.clone(),
+                parquet_type_to_writer_properties[key].clone(),
+            )
+            .expect("Failed to create writer");
             (*key, writer)
         })
         .collect();
 
     let buffer_uploader = GCSUploader::new(
-        gcs_client,
+        storage_client,
         parquet_type_to_schemas,
         parquet_type_to_writer,
+        parquet_type_to_writer_properties,
         bucket_name,
         bucket_root,
-        processor_name,
+        path_layout,
+        table_output_formats,
+        tag_rows_with_chain_id,
+        processing_context,
+        Some(db_pool),
+        hold_back_noncontiguous_files,
     )?;
 
     let default_size_buffer_step = ParquetBufferStep::new(
@@ -736,16 +915,15 @@ async fn initialize_parquet_buffer_step(
     Ok(default_size_buffer_step)
 }
 
-/// Sets the backfill table flag.
+/// Sets the backfill table flag. Table names are matched case-insensitively, like they're
+/// validated, and `*` and `!` entries select tables like they do in `tables_to_write`.
 fn set_backfill_table_flag(table_names: HashSet<String>) -> TableFlags {
-    let mut backfill_table = TableFlags::empty();
-
-    for table_name in table_names.iter() {
-        if let Some(flag) = TableFlags::from_name(table_name) {
-            println!("Setting backfill table flag: {flag:?}");
-            backfill_table |= flag;
-        }
-    }
+    let table_names: HashSet<String> = table_names
+        .iter()
+        .map(|table_name| table_name.to_uppercase())
+        .collect();
+    let backfill_table = TableFlags::from_set(&table_names);
+    println!("Setting backfill table flag: {backfill_table:?}");
     backfill_table
 }
 
@@ -782,4 +960,13 @@ mod tests {
             assert_eq!(default.parquet_type(), t);
         }
     }
+
+    #[test]
+    fn test_every_parquet_type_has_a_table_flag() {
+        use strum::IntoEnumIterator;
+
+        for parquet_type in ParquetTypeEnum::iter() {
+            assert!(!parquet_type.table_flag().is_empty());
+        }
+    }
 }
@@ -6,6 +6,7 @@ use crate::{
     processors::default::{
         models::{
             block_metadata_transactions::ParquetBlockMetadataTransaction,
+            table_item_types::TableItemTypes,
             table_items::{ParquetCurrentTableItem, ParquetTableItem, ParquetTableMetadata},
         },
         process_transactions, process_transactions_parquet,
@@ -48,7 +49,7 @@ impl Processable for ParquetDefaultExtractor {
             raw_current_table_items,
             raw_table_metadata,
             _move_modules,
-        ) = process_transactions(transactions.data.clone());
+        ) = process_transactions(transactions.data.clone(), &TableItemTypes::default());
 
         let parquet_table_items: Vec<ParquetTableItem> = raw_table_items
             .into_iter()
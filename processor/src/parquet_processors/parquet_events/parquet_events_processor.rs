@@ -4,29 +4,29 @@ use crate::{
         processor_config::ProcessorConfig,
     },
     parquet_processors::{
-        initialize_database_pool, initialize_gcs_client, initialize_parquet_buffer_step,
+        initialize_database_pool, initialize_parquet_buffer_step, initialize_storage_client,
         parquet_events::parquet_events_extractor::ParquetEventsExtractor,
         parquet_processor_status_saver::{
             get_parquet_end_version, get_parquet_starting_version, ParquetProcessorStatusSaver,
         },
         parquet_utils::{
-            parquet_version_tracker_step::ParquetVersionTrackerStep, util::HasParquetSchema,
+            parquet_version_tracker_step::ParquetVersionTrackerStep,
+            util::{opted_in_parquet_types, HasParquetSchema},
         },
-        set_backfill_table_flag, ParquetTypeEnum,
+        run_parquet_migrations, set_backfill_table_flag, ParquetTypeEnum,
     },
     processors::events::events_model::ParquetEvent,
-    MIGRATIONS,
+    utils::{
+        processing_context::ProcessingContext,
+        supervisor::supervise_step_tasks,
+        transaction_source::{check_chain_id, TransactionSourceStep},
+    },
 };
 use cedra_indexer_processor_sdk::{
-    cedra_indexer_transaction_stream::TransactionStreamConfig,
     builder::ProcessorBuilder,
-    common_steps::{TransactionStreamStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS},
-    postgres::utils::{
-        checkpoint::PostgresChainIdChecker,
-        database::{run_migrations, ArcDbPool},
-    },
+    common_steps::DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
+    postgres::utils::database::ArcDbPool,
     traits::{processor_trait::ProcessorTrait, IntoRunnableStep},
-    utils::chain_id_check::check_or_update_chain_id,
 };
 use parquet::schema::types::Type;
 use std::{collections::HashMap, sync::Arc};
@@ -53,12 +53,7 @@ impl ProcessorTrait for ParquetEventsProcessor {
         // Run Migrations
         let parquet_db_config = match self.config.db_config {
             DbConfig::ParquetConfig(ref parquet_config) => {
-                run_migrations(
-                    parquet_config.connection_string.clone(),
-                    self.db_pool.clone(),
-                    MIGRATIONS,
-                )
-                .await;
+                run_parquet_migrations(parquet_config, self.db_pool.clone()).await?;
                 parquet_config
             },
             _ => {
@@ -70,11 +65,7 @@ impl ProcessorTrait for ParquetEventsProcessor {
         };
 
         // Check and update the ledger chain id to ensure we're indexing the correct chain
-        check_or_update_chain_id(
-            &self.config.transaction_stream_config,
-            &PostgresChainIdChecker::new(self.db_pool.clone()),
-        )
-        .await?;
+        let chain_id = check_chain_id(&self.config, self.db_pool.clone()).await?;
 
         let parquet_processor_config = match self.config.processor_config.clone() {
             ProcessorConfig::ParquetEventsProcessor(parquet_processor_config) => {
@@ -94,34 +85,37 @@ impl ProcessorTrait for ParquetEventsProcessor {
         );
 
         // Define processor transaction stream config
-        let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
-            starting_version,
-            request_ending_version: ending_version,
-            ..self.config.transaction_stream_config.clone()
-        })
-        .await?;
+        let transaction_stream =
+            TransactionSourceStep::new(&self.config, starting_version, ending_version).await?;
 
         let backfill_table = set_backfill_table_flag(parquet_processor_config.backfill_table);
         let parquet_events_extractor = ParquetEventsExtractor {
             opt_in_tables: backfill_table,
         };
 
-        let gcs_client =
-            initialize_gcs_client(parquet_db_config.google_application_credentials.clone()).await;
+        let storage_client = initialize_storage_client(&parquet_db_config).await?;
 
         let parquet_type_to_schemas: HashMap<ParquetTypeEnum, Arc<Type>> =
             [(ParquetTypeEnum::Events, ParquetEvent::schema())]
                 .into_iter()
                 .collect();
 
+        let written_tables =
+            opted_in_parquet_types(backfill_table, parquet_type_to_schemas.keys().copied());
         let default_size_buffer_step = initialize_parquet_buffer_step(
-            gcs_client.clone(),
+            storage_client,
             parquet_type_to_schemas,
             parquet_processor_config.upload_interval,
             parquet_processor_config.max_buffer_size,
+            &parquet_processor_config.writer_properties,
             parquet_db_config.bucket_name.clone(),
             parquet_db_config.bucket_root.clone(),
-            self.name().to_string(),
+            parquet_db_config.path_layout,
+            parquet_db_config.table_output_formats.clone(),
+            parquet_db_config.tag_rows_with_chain_id,
+            ProcessingContext::new(&self.config, chain_id),
+            self.db_pool.clone(),
+            parquet_db_config.hold_back_noncontiguous_files,
         )
         .await
         .unwrap_or_else(|e| {
@@ -130,13 +124,14 @@ impl ProcessorTrait for ParquetEventsProcessor {
 
         let parquet_version_tracker_step = ParquetVersionTrackerStep::new(
             ParquetProcessorStatusSaver::new(self.config.clone(), self.db_pool.clone()),
+            written_tables,
             DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
         );
 
         let channel_size = parquet_processor_config.channel_size;
 
         // Connect processor steps together
-        let (_, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
+        let (processor_builder, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
             transaction_stream.into_runnable_step(),
         )
         .connect_to(parquet_events_extractor.into_runnable_step(), channel_size)
@@ -146,6 +141,7 @@ impl ProcessorTrait for ParquetEventsProcessor {
             channel_size,
         )
         .end_and_return_output_receiver(channel_size);
+        supervise_step_tasks(processor_builder.join_handles);
 
         loop {
             match buffer_receiver.recv().await {
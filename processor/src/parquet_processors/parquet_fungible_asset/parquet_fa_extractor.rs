@@ -13,7 +13,9 @@ use crate::{
             },
             v2_fungible_metadata::ParquetFungibleAssetMetadataModel,
         },
-        fungible_asset_processor_helpers::{get_fa_to_coin_mapping, parse_v2_coin},
+        fungible_asset_processor_helpers::{
+            get_fa_to_coin_mapping, parse_v2_coin, FungibleAssetBatch,
+        },
     },
     utils::table_flags::TableFlags,
 };
@@ -71,14 +73,13 @@ impl Processable for ParquetFungibleAssetExtractor {
         // Merge the mappings
         self.fa_to_coin_mapping.extend(new_fa_to_coin_mapping);
 
-        let (
-            raw_fungible_asset_activities,
-            raw_fungible_asset_metadata,
-            raw_fungible_asset_balances,
-            _,
-            _raw_coin_supply,
-            raw_fa_to_coin_mappings,
-        ) = parse_v2_coin(&transactions.data, Some(&self.fa_to_coin_mapping)).await;
+        let FungibleAssetBatch {
+            fungible_asset_activities: raw_fungible_asset_activities,
+            fungible_asset_metadata: raw_fungible_asset_metadata,
+            fungible_asset_balances: raw_fungible_asset_balances,
+            fungible_asset_to_coin_mappings: raw_fa_to_coin_mappings,
+            ..
+        } = parse_v2_coin(&transactions.data, Some(&self.fa_to_coin_mapping)).await;
 
         let parquet_fungible_asset_activities: Vec<ParquetFungibleAssetActivity> =
             raw_fungible_asset_activities
@@ -38,7 +38,7 @@ impl Processable for ParquetObjectsExtractor {
         &mut self,
         transactions: TransactionContext<Self::Input>,
     ) -> anyhow::Result<Option<TransactionContext<ParquetTypeMap>>, ProcessorError> {
-        let (raw_all_objects, raw_all_current_objects) =
+        let (raw_all_objects, raw_all_current_objects, _) =
             process_objects(transactions.data, &mut None).await;
         let parquet_objects: Vec<ParquetObject> = raw_all_objects
             .into_iter()
@@ -4,13 +4,19 @@ use super::parquet_utils::{
 use crate::{
     config::{
         indexer_processor_config::IndexerProcessorConfig,
-        processor_mode::{BackfillConfig, BootStrapConfig, ProcessorMode, TestingConfig},
+        processor_mode::{BackfillConfig, ProcessorMode, RangedConfig, TestingConfig},
     },
-    db::backfill_processor_status::{
-        BackfillProcessorStatus, BackfillProcessorStatusQuery, BackfillStatus,
+    db::{
+        backfill_processor_status::{
+            BackfillProcessorStatus, BackfillProcessorStatusQuery, BackfillStatus,
+        },
+        parquet_upload_checkpoint::ParquetUploadCheckpoint,
+    },
+    processors::processor_status_saver::{
+        log_ascii_warning, save_consistency_watermark, save_heartbeat, save_processor_status,
     },
-    processors::processor_status_saver::{log_ascii_warning, save_processor_status},
     schema::backfill_processor_status,
+    utils::transaction_source::resolve_bootstrap_version,
 };
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
@@ -50,6 +56,27 @@ impl ParquetProcessorStatusSaverTrait for ParquetProcessorStatusSaver {
             last_success_batch,
             self.db_pool.clone(),
         )
+        .await?;
+        save_heartbeat(
+            &processor_id,
+            &self.config.processor_mode,
+            last_success_batch,
+            self.db_pool.clone(),
+        )
+        .await;
+        Ok(())
+    }
+
+    async fn save_parquet_consistency_watermark(
+        &self,
+        consistent_through: &TransactionContext<()>,
+    ) -> Result<(), ProcessorError> {
+        save_consistency_watermark(
+            self.config.processor_config.name(),
+            &self.config.processor_mode,
+            consistent_through,
+            self.db_pool.clone(),
+        )
         .await
     }
 }
@@ -70,9 +97,7 @@ pub async fn get_parquet_starting_version(
         })?;
 
     match &config.processor_mode {
-        ProcessorMode::Default(BootStrapConfig {
-            initial_starting_version,
-        }) => {
+        ProcessorMode::Default(bootstrap_config) => {
             let min_processed_version =
                 get_min_processed_version_from_db(db_pool.clone(), table_names)
                     .await
@@ -82,12 +107,19 @@ pub async fn get_parquet_starting_version(
                         ),
                     })?;
 
-            // If there's no last success version saved, start with the version from config
-            Ok(Some(
-                min_processed_version.map_or(*initial_starting_version, |version: u64| {
-                    std::cmp::max(version, *initial_starting_version)
-                }),
-            ))
+            match min_processed_version {
+                Some(version) => Ok(Some(std::cmp::max(
+                    version,
+                    bootstrap_config.initial_starting_version,
+                ))),
+                // If there's no last success version saved, start where the config says
+                None => resolve_bootstrap_version(config, bootstrap_config)
+                    .await
+                    .map(Some)
+                    .map_err(|e| ProcessorError::ProcessError {
+                        message: format!("Failed to resolve the starting version. {e:?}"),
+                    }),
+            }
         },
         ProcessorMode::Backfill(BackfillConfig {
             backfill_id,
@@ -190,6 +222,12 @@ pub async fn get_parquet_starting_version(
             // Always start from the override_starting_version.
             Ok(Some(*override_starting_version))
         },
+        ProcessorMode::Ranged(RangedConfig {
+            starting_version, ..
+        }) => Ok(Some(*starting_version)),
+        ProcessorMode::Sharded(_) | ProcessorMode::Shard(_) => Err(ProcessorError::ProcessError {
+            message: "Sharded mode only runs processors writing to Postgres".to_string(),
+        }),
     }
 }
 
@@ -230,6 +268,10 @@ pub async fn get_parquet_end_version(
             // If no ending version is provided, use the override_starting_version so testing mode only processes 1 transaction at a time.
             Ok(Some(ending_version.unwrap_or(*override_starting_version)))
         },
+        ProcessorMode::Ranged(RangedConfig { ending_version, .. }) => Ok(Some(*ending_version)),
+        ProcessorMode::Sharded(_) | ProcessorMode::Shard(_) => Err(ProcessorError::ProcessError {
+            message: "Sharded mode only runs processors writing to Postgres".to_string(),
+        }),
     }
 }
 
@@ -237,6 +279,8 @@ pub async fn get_parquet_end_version(
 ///
 /// This should return the minimum of the last success version of the processors in the list.
 /// If any of the tables handled by the parquet processor has no entry, it should use 0 as a default value.
+/// A table's version is the later of its status and the last file it uploaded, so a status save
+/// that failed after an upload doesn't have the file uploaded again.
 /// To avoid skipping any versions, the minimum of the last success version should be used as the starting version.
 async fn get_min_processed_version_from_db(
     db_pool: ArcDbPool,
@@ -256,11 +300,23 @@ async fn get_min_processed_version_from_db(
                 .map_err(|err| ProcessorError::ProcessError {
                     message: format!("Failed to get database connection. {err:?}"),
                 })?;
-            ProcessorStatusQuery::get_by_processor(&processor_name, &mut conn)
+            let status = ProcessorStatusQuery::get_by_processor(&processor_name, &mut conn)
                 .await
                 .map_err(|e| ProcessorError::ProcessError {
                     message: format!("Failed to query processor_status table. {e:?}"),
-                })
+                })?;
+            let uploaded_through =
+                ParquetUploadCheckpoint::get_end_version(&processor_name, &mut conn)
+                    .await
+                    .map_err(|e| ProcessorError::ProcessError {
+                        message: format!("Failed to query parquet_upload_checkpoints table. {e:?}"),
+                    })?;
+            // A file can be uploaded without the status save after it going through
+            Ok::<_, ProcessorError>(
+                status
+                    .map(|status| status.last_success_version)
+                    .max(uploaded_through),
+            )
         };
 
         queries.push(query);
@@ -274,9 +330,9 @@ async fn get_min_processed_version_from_db(
         .filter_map(|res| {
             match res {
                 // If the result is `Ok`, proceed to check the status
-                Ok(Some(status)) => {
+                Ok(Some(version)) => {
                     // Return the version if the status contains a version
-                    Some(status.last_success_version as u64)
+                    Some(version as u64)
                 },
                 // Handle specific cases where `Ok` contains `None` (no status found)
                 Ok(None) => None,
@@ -352,9 +408,11 @@ mod tests {
     use super::*;
     use crate::{
         config::{
-            db_config::{DbConfig, ParquetConfig},
+            db_config::{DbConfig, ParquetConfig, ParquetPathLayout},
             indexer_processor_config::IndexerProcessorConfig,
             processor_config::{ParquetDefaultProcessorConfig, ProcessorConfig},
+            processor_mode::BootStrapConfig,
+            supervisor_config::SupervisorConfig,
         },
         db::backfill_processor_status::{BackfillProcessorStatus, BackfillStatus},
         MIGRATIONS,
@@ -370,6 +428,7 @@ mod tests {
         testing_framework::database::{PostgresTestDatabase, TestDatabase},
     };
     use diesel_async::RunQueryDsl;
+    use std::collections::HashMap;
     use url::Url;
 
     fn create_indexer_config(
@@ -382,14 +441,28 @@ mod tests {
             connection_string: db_url.to_string(),
             db_pool_size: 100,
             google_application_credentials: None,
+            azure_blob_config: None,
             bucket_name: "test".to_string(),
             bucket_root: "test".to_string(),
+            path_layout: ParquetPathLayout::Flat,
+            table_output_formats: HashMap::new(),
+            tag_rows_with_chain_id: false,
+            hold_back_noncontiguous_files: false,
+            status_tables_only: false,
         };
         let db_config = DbConfig::ParquetConfig(postgres_config);
         IndexerProcessorConfig {
             processor_config,
             db_config,
             processor_mode,
+            supervisor_config: SupervisorConfig::default(),
+            local_transactions_config: None,
+            stream_failover_config: None,
+            auth_token_source: None,
+            network: None,
+            table_name_overrides: HashMap::new(),
+            scheduled_jobs: HashMap::new(),
+            log_level: None,
             transaction_stream_config: TransactionStreamConfig {
                 indexer_grpc_data_service_address: Url::parse("https://test.com").unwrap(),
                 starting_version: None,
@@ -417,6 +490,7 @@ mod tests {
             db.get_db_url(),
             ProcessorMode::Default(BootStrapConfig {
                 initial_starting_version,
+                ..Default::default()
             }),
         );
         let conn_pool = new_db_pool(db.get_db_url().as_str(), Some(10))
@@ -447,6 +521,7 @@ mod tests {
             db.get_db_url(),
             ProcessorMode::Default(BootStrapConfig {
                 initial_starting_version: 0,
+                ..Default::default()
             }),
         );
 
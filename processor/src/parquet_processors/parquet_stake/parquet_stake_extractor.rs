@@ -5,11 +5,17 @@ use crate::{
     },
     processors::stake::{
         models::{
+            current_delegated_voter::ParquetCurrentDelegatedVoter,
             delegator_activities::ParquetDelegatedStakingActivity,
             delegator_balances::{ParquetCurrentDelegatorBalance, ParquetDelegatorBalance},
+            delegator_pools::{
+                ParquetCurrentDelegatorPoolBalance, ParquetDelegatorPool,
+                ParquetDelegatorPoolBalance,
+            },
             proposal_votes::ParquetProposalVote,
+            staking_pool_voter::ParquetCurrentStakingPoolVoter,
         },
-        parse_stake_data,
+        parse_stake_data, StakeBatch,
     },
     utils::table_flags::TableFlags,
 };
@@ -43,17 +49,18 @@ impl Processable for ParquetStakeExtractor {
         &mut self,
         transactions: TransactionContext<Self::Input>,
     ) -> anyhow::Result<Option<TransactionContext<ParquetTypeMap>>, ProcessorError> {
-        let (
-            _,
-            raw_all_proposal_votes,
-            raw_all_delegator_activities,
-            raw_all_delegator_balances,
-            raw_all_current_delegator_balances,
-            _,
-            _,
-            _,
-            _,
-        ) = match parse_stake_data(&transactions.data, None, 0, 0).await {
+        let StakeBatch {
+            proposal_votes: raw_all_proposal_votes,
+            delegated_staking_activities: raw_all_delegator_activities,
+            delegator_balances: raw_all_delegator_balances,
+            current_delegator_balances: raw_all_current_delegator_balances,
+            current_stake_pool_voters: raw_all_current_stake_pool_voters,
+            delegator_pools: raw_all_delegator_pools,
+            delegator_pool_balances: raw_all_delegator_pool_balances,
+            current_delegator_pool_balances: raw_all_current_delegator_pool_balances,
+            current_delegated_voters: raw_all_current_delegated_voters,
+            ..
+        } = match parse_stake_data(&transactions.data, None, 0, 0).await {
             Ok(data) => data,
             Err(e) => {
                 error!(
@@ -85,6 +92,26 @@ impl Processable for ParquetStakeExtractor {
             .into_iter()
             .map(ParquetProposalVote::from)
             .collect::<Vec<_>>();
+        let all_current_stake_pool_voters = raw_all_current_stake_pool_voters
+            .into_iter()
+            .map(ParquetCurrentStakingPoolVoter::from)
+            .collect::<Vec<_>>();
+        let all_delegator_pools = raw_all_delegator_pools
+            .into_iter()
+            .map(ParquetDelegatorPool::from)
+            .collect::<Vec<_>>();
+        let all_delegator_pool_balances = raw_all_delegator_pool_balances
+            .into_iter()
+            .map(ParquetDelegatorPoolBalance::from)
+            .collect::<Vec<_>>();
+        let all_current_delegator_pool_balances = raw_all_current_delegator_pool_balances
+            .into_iter()
+            .map(ParquetCurrentDelegatorPoolBalance::from)
+            .collect::<Vec<_>>();
+        let all_current_delegated_voters = raw_all_current_delegated_voters
+            .into_iter()
+            .map(ParquetCurrentDelegatedVoter::from)
+            .collect::<Vec<_>>();
 
         // Print the size of each extracted data type
         debug!("Processed data sizes:");
@@ -98,6 +125,23 @@ impl Processable for ParquetStakeExtractor {
             " - CurrentDelegatorBalance: {}",
             all_current_delegator_balances.len()
         );
+        debug!(
+            " - CurrentStakingPoolVoter: {}",
+            all_current_stake_pool_voters.len()
+        );
+        debug!(" - DelegatorPool: {}", all_delegator_pools.len());
+        debug!(
+            " - DelegatorPoolBalance: {}",
+            all_delegator_pool_balances.len()
+        );
+        debug!(
+            " - CurrentDelegatorPoolBalance: {}",
+            all_current_delegator_pool_balances.len()
+        );
+        debug!(
+            " - CurrentDelegatedVoter: {}",
+            all_current_delegated_voters.len()
+        );
 
         let mut map: HashMap<ParquetTypeEnum, ParquetTypeStructs> = HashMap::new();
 
@@ -122,6 +166,33 @@ impl Processable for ParquetStakeExtractor {
                 ParquetTypeEnum::CurrentDelegatorBalances,
                 ParquetTypeStructs::CurrentDelegatorBalance(all_current_delegator_balances),
             ),
+            (
+                TableFlags::CURRENT_STAKING_POOL_VOTER,
+                ParquetTypeEnum::CurrentStakingPoolVoter,
+                ParquetTypeStructs::CurrentStakingPoolVoter(all_current_stake_pool_voters),
+            ),
+            (
+                TableFlags::DELEGATED_STAKING_POOLS,
+                ParquetTypeEnum::DelegatedStakingPools,
+                ParquetTypeStructs::DelegatorPool(all_delegator_pools),
+            ),
+            (
+                TableFlags::DELEGATED_STAKING_POOL_BALANCES,
+                ParquetTypeEnum::DelegatedStakingPoolBalances,
+                ParquetTypeStructs::DelegatorPoolBalance(all_delegator_pool_balances),
+            ),
+            (
+                TableFlags::CURRENT_DELEGATED_STAKING_POOL_BALANCES,
+                ParquetTypeEnum::CurrentDelegatedStakingPoolBalances,
+                ParquetTypeStructs::CurrentDelegatorPoolBalance(
+                    all_current_delegator_pool_balances,
+                ),
+            ),
+            (
+                TableFlags::CURRENT_DELEGATED_VOTER,
+                ParquetTypeEnum::CurrentDelegatedVoter,
+                ParquetTypeStructs::CurrentDelegatedVoter(all_current_delegated_voters),
+            ),
         ];
 
         // Populate the map based on opt-in tables
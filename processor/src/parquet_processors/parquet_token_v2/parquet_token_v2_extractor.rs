@@ -15,7 +15,7 @@ use crate::{
             v2_token_metadata::ParquetCurrentTokenV2Metadata,
             v2_token_ownerships::{ParquetCurrentTokenOwnershipV2, ParquetTokenOwnershipV2},
         },
-        token_v2_processor_helpers::parse_v2_token,
+        token_v2_processor_helpers::{parse_v2_token, TokenV2Batch},
     },
     utils::table_flags::TableFlags,
 };
@@ -53,20 +53,20 @@ impl Processable for ParquetTokenV2Extractor {
         let table_handle_to_owner: ahash::AHashMap<String, TableMetadataForToken> =
             TableMetadataForToken::get_table_handle_to_owner_from_transactions(&transactions.data);
 
-        let (
+        let TokenV2Batch {
             collections_v2,
-            raw_token_datas_v2,
-            raw_token_ownerships_v2,
-            _current_collections_v2,
-            raw_current_token_datas_v2,
-            raw_current_deleted_token_datas_v2,
-            raw_current_token_ownerships_v2,
-            raw_current_deleted_token_ownerships_v2,
-            raw_token_activities_v2,
-            raw_current_token_v2_metadata,
-            raw_current_token_royalties_v1,
-            raw_current_token_claims,
-        ) = parse_v2_token(&transactions.data, &table_handle_to_owner, &mut None).await;
+            token_datas_v2: raw_token_datas_v2,
+            token_ownerships_v2: raw_token_ownerships_v2,
+            current_token_datas_v2: raw_current_token_datas_v2,
+            current_deleted_token_datas_v2: raw_current_deleted_token_datas_v2,
+            current_token_ownerships_v2: raw_current_token_ownerships_v2,
+            current_deleted_token_ownerships_v2: raw_current_deleted_token_ownerships_v2,
+            token_activities_v2: raw_token_activities_v2,
+            current_token_v2_metadata: raw_current_token_v2_metadata,
+            current_token_royalties_v1: raw_current_token_royalties_v1,
+            current_token_pending_claims: raw_current_token_claims,
+            ..
+        } = parse_v2_token(&transactions.data, &table_handle_to_owner, &mut None).await;
 
         let parquet_current_token_claims: Vec<ParquetCurrentTokenPendingClaim> =
             raw_current_token_claims
@@ -0,0 +1,311 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Avro object container files of the parquet models. The Avro schema is derived from the parquet
+//! schema the models already generate, and row values are taken from their serde representation,
+//! so any table the parquet processors write can be written as Avro as well.
+
+use anyhow::{bail, Context, Result};
+use apache_avro::{types::Value as AvroValue, Schema, Writer};
+use parquet::{
+    basic::{ConvertedType, LogicalType, Repetition, TimeUnit, Type as PhysicalType},
+    schema::types::Type,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+const NAMESPACE: &str = "cedra.indexer";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum AvroType {
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    String,
+    Bytes,
+    TimestampMillis,
+    TimestampMicros,
+}
+
+impl AvroType {
+    fn from_parquet(field: &Type) -> Result<Self> {
+        if !field.is_primitive() {
+            bail!("Nested field {} can't be written as avro", field.name());
+        }
+        let info = field.get_basic_info();
+        let avro_type = match field.get_physical_type() {
+            PhysicalType::BOOLEAN => Self::Boolean,
+            PhysicalType::INT32 => Self::Int,
+            PhysicalType::INT64 => match (info.logical_type(), info.converted_type()) {
+                (Some(LogicalType::Timestamp { unit, .. }), _) => match unit {
+                    TimeUnit::MILLIS(_) => Self::TimestampMillis,
+                    TimeUnit::MICROS(_) => Self::TimestampMicros,
+                    TimeUnit::NANOS(_) => Self::Long,
+                },
+                (_, ConvertedType::TIMESTAMP_MILLIS) => Self::TimestampMillis,
+                (_, ConvertedType::TIMESTAMP_MICROS) => Self::TimestampMicros,
+                _ => Self::Long,
+            },
+            PhysicalType::FLOAT => Self::Float,
+            PhysicalType::DOUBLE => Self::Double,
+            PhysicalType::BYTE_ARRAY | PhysicalType::FIXED_LEN_BYTE_ARRAY => {
+                match (info.logical_type(), info.converted_type()) {
+                    (Some(LogicalType::String | LogicalType::Json | LogicalType::Enum), _)
+                    | (_, ConvertedType::UTF8 | ConvertedType::JSON | ConvertedType::ENUM) => {
+                        Self::String
+                    },
+                    _ => Self::Bytes,
+                }
+            },
+            PhysicalType::INT96 => bail!("INT96 field {} can't be written as avro", field.name()),
+        };
+        Ok(avro_type)
+    }
+
+    fn to_json(self) -> Value {
+        match self {
+            Self::Boolean => json!("boolean"),
+            Self::Int => json!("int"),
+            Self::Long => json!("long"),
+            Self::Float => json!("float"),
+            Self::Double => json!("double"),
+            Self::String => json!("string"),
+            Self::Bytes => json!("bytes"),
+            Self::TimestampMillis => json!({"type": "long", "logicalType": "timestamp-millis"}),
+            Self::TimestampMicros => json!({"type": "long", "logicalType": "timestamp-micros"}),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct AvroField {
+    name: String,
+    avro_type: AvroType,
+    /// Written as a `["null", type]` union
+    optional: bool,
+}
+
+/// Avro record schema of one table
+#[derive(Clone, Debug)]
+pub struct AvroSchema {
+    name: String,
+    fields: Vec<AvroField>,
+    schema: Schema,
+}
+
+impl AvroSchema {
+    /// Maps every column of a parquet schema to an Avro field of the same name, optional columns
+    /// becoming nullable unions
+    pub fn from_parquet_schema(name: &str, schema: &Type) -> Result<Self> {
+        let fields = schema
+            .get_fields()
+            .iter()
+            .map(|field| {
+                let info = field.get_basic_info();
+                let optional = info.has_repetition() && info.repetition() == Repetition::OPTIONAL;
+                if info.has_repetition() && info.repetition() == Repetition::REPEATED {
+                    bail!("Repeated field {} can't be written as avro", field.name());
+                }
+                Ok(AvroField {
+                    name: field.name().to_string(),
+                    avro_type: AvroType::from_parquet(field)?,
+                    optional,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let schema = Schema::parse(&schema_json(name, &fields))
+            .with_context(|| format!("Invalid avro schema of {name}"))?;
+        Ok(Self {
+            name: name.to_string(),
+            fields,
+            schema,
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        schema_json(&self.name, &self.fields)
+    }
+
+    fn to_avro_record<T: Serialize>(&self, row: &T) -> Result<AvroValue> {
+        let value = serde_json::to_value(row).context("Failed to serialize row")?;
+        let object = value
+            .as_object()
+            .context("Row didn't serialize to an object")?;
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| {
+                let value = object.get(&field.name).unwrap_or(&Value::Null);
+                let avro_value = match (field.optional, value) {
+                    (true, Value::Null) => AvroValue::Union(0, Box::new(AvroValue::Null)),
+                    (true, value) => AvroValue::Union(1, Box::new(to_avro_value(field, value)?)),
+                    (false, value) => to_avro_value(field, value)?,
+                };
+                Ok((field.name.clone(), avro_value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(AvroValue::Record(fields))
+    }
+}
+
+fn schema_json(name: &str, fields: &[AvroField]) -> Value {
+    let fields: Vec<Value> = fields
+        .iter()
+        .map(|field| {
+            if field.optional {
+                json!({
+                    "name": field.name,
+                    "type": ["null", field.avro_type.to_json()],
+                    "default": null,
+                })
+            } else {
+                json!({"name": field.name, "type": field.avro_type.to_json()})
+            }
+        })
+        .collect();
+    json!({
+        "type": "record",
+        "name": name,
+        "namespace": NAMESPACE,
+        "fields": fields,
+    })
+}
+
+fn to_avro_value(field: &AvroField, value: &Value) -> Result<AvroValue> {
+    let invalid = || anyhow::anyhow!("Invalid value for avro field {}: {}", field.name, value);
+    let number = || {
+        value
+            .as_i64()
+            .or_else(|| value.as_u64().map(|number| number as i64))
+            .ok_or_else(invalid)
+    };
+    let avro_value = match field.avro_type {
+        AvroType::Boolean => AvroValue::Boolean(value.as_bool().ok_or_else(invalid)?),
+        AvroType::Int => AvroValue::Int(i32::try_from(number()?).map_err(|_| invalid())?),
+        AvroType::Long => AvroValue::Long(number()?),
+        AvroType::Float => AvroValue::Float(value.as_f64().ok_or_else(invalid)? as f32),
+        AvroType::Double => AvroValue::Double(value.as_f64().ok_or_else(invalid)?),
+        AvroType::String => match value {
+            Value::String(string) => AvroValue::String(string.clone()),
+            // JSON columns are serialized values rather than strings
+            _ => AvroValue::String(value.to_string()),
+        },
+        AvroType::Bytes => AvroValue::Bytes(
+            value
+                .as_array()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|byte| byte.as_u64().map(|byte| byte as u8))
+                .collect::<Option<Vec<u8>>>()
+                .ok_or_else(invalid)?,
+        ),
+        AvroType::TimestampMillis | AvroType::TimestampMicros => {
+            let timestamp = value
+                .as_str()
+                .and_then(|timestamp| timestamp.parse::<chrono::NaiveDateTime>().ok())
+                .ok_or_else(invalid)?
+                .and_utc();
+            if field.avro_type == AvroType::TimestampMillis {
+                AvroValue::TimestampMillis(timestamp.timestamp_millis())
+            } else {
+                AvroValue::TimestampMicros(timestamp.timestamp_micros())
+            }
+        },
+    };
+    Ok(avro_value)
+}
+
+/// Writes the rows as an uncompressed Avro object container file
+pub fn write_avro_file<T: Serialize>(schema: &AvroSchema, rows: &[T]) -> Result<Vec<u8>> {
+    let mut writer = Writer::new(&schema.schema, Vec::new());
+    for row in rows {
+        writer
+            .append(schema.to_avro_record(row)?)
+            .with_context(|| format!("Failed to write {} row as avro", schema.name))?;
+    }
+    writer
+        .into_inner()
+        .with_context(|| format!("Failed to write {} rows as avro", schema.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apache_avro::Reader;
+    use parquet::schema::parser::parse_message_type;
+
+    #[derive(Serialize)]
+    struct Row {
+        txn_version: i64,
+        name: Option<String>,
+        block_timestamp: chrono::NaiveDateTime,
+    }
+
+    fn schema() -> AvroSchema {
+        let parquet_schema = parse_message_type(
+            "message rust_schema {
+                REQUIRED INT64 txn_version;
+                OPTIONAL BYTE_ARRAY name (UTF8);
+                REQUIRED INT64 block_timestamp (TIMESTAMP_MILLIS);
+            }",
+        )
+        .unwrap();
+        AvroSchema::from_parquet_schema("events", &parquet_schema).unwrap()
+    }
+
+    fn row(txn_version: i64, name: Option<&str>) -> Row {
+        Row {
+            txn_version,
+            name: name.map(str::to_string),
+            block_timestamp: chrono::DateTime::from_timestamp(1, 0).unwrap().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_schema_from_parquet() {
+        assert_eq!(
+            schema().to_json(),
+            json!({
+                "type": "record",
+                "name": "events",
+                "namespace": "cedra.indexer",
+                "fields": [
+                    {"name": "txn_version", "type": "long"},
+                    {"name": "name", "type": ["null", "string"], "default": null},
+                    {
+                        "name": "block_timestamp",
+                        "type": {"type": "long", "logicalType": "timestamp-millis"},
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let file = write_avro_file(&schema(), &[row(1, Some("ab")), row(-1, None)]).unwrap();
+        let rows = Reader::new(file.as_slice())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let record = |txn_version, name| {
+            AvroValue::Record(vec![
+                ("txn_version".to_string(), AvroValue::Long(txn_version)),
+                ("name".to_string(), name),
+                (
+                    "block_timestamp".to_string(),
+                    AvroValue::TimestampMillis(1000),
+                ),
+            ])
+        };
+        assert_eq!(rows, vec![
+            record(
+                1,
+                AvroValue::Union(1, Box::new(AvroValue::String("ab".to_string())))
+            ),
+            record(-1, AvroValue::Union(0, Box::new(AvroValue::Null))),
+        ]);
+    }
+}
@@ -0,0 +1,362 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    config::azure_blob_config::AzureBlobConfig,
+    parquet_processors::parquet_utils::{
+        gcs_uploader::{INITIAL_DELAY_MS, MAX_RETRIES, TIMEOUT_SECONDS},
+        util::ParquetProcessorError,
+    },
+    utils::counters::PARQUET_BUFFER_SIZE,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use hmac::{Hmac, Mac};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::{collections::HashMap, path::Path};
+use tokio::{
+    sync::Mutex,
+    time::{sleep, timeout, Duration, Instant},
+};
+use tracing::{debug, error, info};
+use url::Url;
+
+const STORAGE_API_VERSION: &str = "2021-08-06";
+const BLOB_CONTENT_TYPE: &str = "application/octet-stream";
+const MANAGED_IDENTITY_TOKEN_URL: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+const STORAGE_RESOURCE: &str = "https://storage.azure.com/";
+/// Managed identity tokens are refreshed this long before they expire
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(300);
+
+enum AzureCredential {
+    SharedKey {
+        account_name: String,
+        account_key: Vec<u8>,
+    },
+    SharedAccessSignature(String),
+    ManagedIdentity {
+        client_id: Option<String>,
+        token: Mutex<Option<(String, Instant)>>,
+    },
+}
+
+#[derive(Deserialize)]
+struct ManagedIdentityToken {
+    access_token: String,
+    expires_in: String,
+}
+
+/// Uploads blobs through the Blob Storage REST API.
+pub struct AzureBlobClient {
+    http_client: reqwest::Client,
+    blob_endpoint: Url,
+    credential: AzureCredential,
+}
+
+impl AzureBlobClient {
+    pub fn new(config: &AzureBlobConfig) -> Result<Self> {
+        let (blob_endpoint, credential) = match config {
+            AzureBlobConfig::ConnectionString { connection_string } => {
+                parse_connection_string(connection_string)?
+            },
+            AzureBlobConfig::ManagedIdentity {
+                account_name,
+                client_id,
+                blob_endpoint,
+            } => {
+                let blob_endpoint = match blob_endpoint {
+                    Some(blob_endpoint) => blob_endpoint.clone(),
+                    None => Url::parse(&format!("https://{account_name}.blob.core.windows.net"))?,
+                };
+                (blob_endpoint, AzureCredential::ManagedIdentity {
+                    client_id: client_id.clone(),
+                    token: Mutex::new(None),
+                })
+            },
+        };
+        let http_client = reqwest::Client::builder()
+            .build()
+            .context("Failed to build Azure Blob Storage http client")?;
+        Ok(Self {
+            http_client,
+            blob_endpoint,
+            credential,
+        })
+    }
+
+    /// Uploads `body` as a block blob, replacing any blob with the same name
+    pub async fn put_block_blob(
+        &self,
+        container: &str,
+        blob_name: &str,
+        body: Vec<u8>,
+    ) -> Result<()> {
+        let mut url = self.blob_endpoint.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Invalid blob endpoint {}", self.blob_endpoint))?
+            .pop_if_empty()
+            .push(container)
+            .extend(blob_name.split('/'));
+        if let AzureCredential::SharedAccessSignature(sas) = &self.credential {
+            url.set_query(Some(sas));
+        }
+
+        let date = chrono::Utc::now()
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string();
+        let mut request = self
+            .http_client
+            .put(url.clone())
+            .header("x-ms-blob-type", "BlockBlob")
+            .header("x-ms-date", &date)
+            .header("x-ms-version", STORAGE_API_VERSION)
+            .header(CONTENT_TYPE, BLOB_CONTENT_TYPE);
+        match &self.credential {
+            AzureCredential::SharedKey {
+                account_name,
+                account_key,
+            } => {
+                let signature =
+                    shared_key_signature(account_name, account_key, &url, body.len(), &date)?;
+                request = request.header(
+                    AUTHORIZATION,
+                    format!("SharedKey {account_name}:{signature}"),
+                );
+            },
+            AzureCredential::SharedAccessSignature(_) => {},
+            AzureCredential::ManagedIdentity { client_id, token } => {
+                let access_token = self
+                    .managed_identity_token(client_id.as_deref(), token)
+                    .await?;
+                request = request.bearer_auth(access_token);
+            },
+        }
+
+        request
+            .body(body)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("Failed to put blob {container}/{blob_name}"))?;
+        Ok(())
+    }
+
+    /// The cached token, or a new one from the instance metadata service if it's about to expire
+    async fn managed_identity_token(
+        &self,
+        client_id: Option<&str>,
+        token: &Mutex<Option<(String, Instant)>>,
+    ) -> Result<String> {
+        let mut token = token.lock().await;
+        if let Some((access_token, expires_at)) = token.as_ref() {
+            if *expires_at > Instant::now() + TOKEN_REFRESH_MARGIN {
+                return Ok(access_token.clone());
+            }
+        }
+
+        let mut query = vec![
+            ("api-version", "2018-02-01"),
+            ("resource", STORAGE_RESOURCE),
+        ];
+        if let Some(client_id) = client_id {
+            query.push(("client_id", client_id));
+        }
+        let response: ManagedIdentityToken = self
+            .http_client
+            .get(MANAGED_IDENTITY_TOKEN_URL)
+            .query(&query)
+            .header("Metadata", "true")
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .context("Failed to get a managed identity token")?
+            .json()
+            .await
+            .context("Failed to parse the managed identity token")?;
+        let expires_in: u64 = response
+            .expires_in
+            .parse()
+            .context("Invalid managed identity token expiry")?;
+        *token = Some((
+            response.access_token.clone(),
+            Instant::now() + Duration::from_secs(expires_in),
+        ));
+        Ok(response.access_token)
+    }
+}
+
+/// Blob endpoint and credential of a storage account connection string
+fn parse_connection_string(connection_string: &str) -> Result<(Url, AzureCredential)> {
+    let settings: HashMap<&str, &str> = connection_string
+        .split(';')
+        .filter(|setting| !setting.trim().is_empty())
+        .map(|setting| {
+            setting
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .context("Connection string settings must be key=value pairs")
+        })
+        .collect::<Result<_>>()?;
+
+    let account_name = settings.get("AccountName").copied();
+    let blob_endpoint = match (settings.get("BlobEndpoint"), account_name) {
+        (Some(blob_endpoint), _) => Url::parse(blob_endpoint)?,
+        (None, Some(account_name)) => Url::parse(&format!(
+            "{}://{account_name}.blob.{}",
+            settings.get("DefaultEndpointsProtocol").unwrap_or(&"https"),
+            settings
+                .get("EndpointSuffix")
+                .unwrap_or(&"core.windows.net"),
+        ))?,
+        (None, None) => bail!("Connection string needs an AccountName or a BlobEndpoint"),
+    };
+
+    let credential = match (account_name, settings.get("AccountKey")) {
+        (Some(account_name), Some(account_key)) => AzureCredential::SharedKey {
+            account_name: account_name.to_string(),
+            account_key: base64::decode(account_key).context("AccountKey isn't base64")?,
+        },
+        _ => match settings.get("SharedAccessSignature") {
+            Some(sas) => {
+                AzureCredential::SharedAccessSignature(sas.trim_start_matches('?').to_string())
+            },
+            None => bail!(
+                "Connection string needs an AccountName and AccountKey, or a SharedAccessSignature"
+            ),
+        },
+    };
+    Ok((blob_endpoint, credential))
+}
+
+/// Shared Key signature of a Put Blob request, see
+/// https://learn.microsoft.com/rest/api/storageservices/authorize-with-shared-key
+fn shared_key_signature(
+    account_name: &str,
+    account_key: &[u8],
+    url: &Url,
+    content_length: usize,
+    date: &str,
+) -> Result<String> {
+    let content_length = content_length.to_string();
+    // Content-Encoding, Content-Language, Content-MD5, Date and the conditional headers are unset
+    let standard_headers = [
+        "PUT",
+        "",
+        "",
+        content_length.as_str(),
+        "",
+        BLOB_CONTENT_TYPE,
+        "",
+        "",
+        "",
+        "",
+        "",
+        "",
+    ];
+    let string_to_sign = format!(
+        "{}\nx-ms-blob-type:BlockBlob\nx-ms-date:{date}\nx-ms-version:{STORAGE_API_VERSION}\n/{account_name}{}",
+        standard_headers.join("\n"),
+        url.path(),
+    );
+    let mut mac = Hmac::<Sha256>::new_from_slice(account_key).context("Invalid AccountKey")?;
+    mac.update(string_to_sign.as_bytes());
+    Ok(base64::encode(mac.finalize().into_bytes()))
+}
+
+pub async fn upload_parquet_to_azure_blob(
+    client: &AzureBlobClient,
+    buffer: Vec<u8>,
+    table_name: &str,
+    container: &str,
+    object_name: &Path,
+    processor_name: String,
+) -> Result<(), ParquetProcessorError> {
+    if buffer.is_empty() {
+        error!("The file is empty and has no data to upload.",);
+        return Err(ParquetProcessorError::Other(
+            "The file is empty and has no data to upload.".to_string(),
+        ));
+    }
+
+    let blob_name = object_name.to_str().unwrap().to_owned();
+    let mut retry_count = 0;
+    let mut delay = INITIAL_DELAY_MS;
+
+    loop {
+        PARQUET_BUFFER_SIZE
+            .with_label_values(&[&processor_name, table_name])
+            .set(buffer.len() as i64);
+
+        let upload_result = timeout(
+            Duration::from_secs(TIMEOUT_SECONDS),
+            client.put_block_blob(container, &blob_name, buffer.clone()),
+        )
+        .await;
+
+        match upload_result {
+            Ok(Ok(())) => {
+                info!(
+                    table_name = table_name,
+                    file_name = blob_name,
+                    "File uploaded successfully to Azure Blob Storage",
+                );
+                return Ok(());
+            },
+            Ok(Err(e)) => {
+                error!("Failed to upload file to Azure Blob Storage: {:?}", e);
+                if retry_count >= MAX_RETRIES {
+                    return Err(ParquetProcessorError::Other(format!("{e:?}")));
+                }
+            },
+            Err(e) => {
+                error!("Upload timed out: {}", e);
+                if retry_count >= MAX_RETRIES {
+                    return Err(ParquetProcessorError::TimeoutError(e));
+                }
+            },
+        }
+
+        retry_count += 1;
+        sleep(Duration::from_millis(delay)).await;
+        delay *= 2;
+        debug!("Retrying upload operation. Retry count: {}", retry_count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_connection_string() {
+        let (blob_endpoint, credential) = parse_connection_string(
+            "DefaultEndpointsProtocol=https;AccountName=cedraetl;AccountKey=a2V5;EndpointSuffix=core.windows.net",
+        )
+        .unwrap();
+        assert_eq!(
+            blob_endpoint.as_str(),
+            "https://cedraetl.blob.core.windows.net/"
+        );
+        assert!(matches!(
+            credential,
+            AzureCredential::SharedKey { account_name, account_key }
+                if account_name == "cedraetl" && account_key == b"key"
+        ));
+
+        let (blob_endpoint, credential) = parse_connection_string(
+            "BlobEndpoint=https://cedraetl.blob.core.windows.net/;SharedAccessSignature=sv=2021-08-06&sig=abc%3D",
+        )
+        .unwrap();
+        assert_eq!(
+            blob_endpoint.as_str(),
+            "https://cedraetl.blob.core.windows.net/"
+        );
+        assert!(matches!(
+            credential,
+            AzureCredential::SharedAccessSignature(sas) if sas == "sv=2021-08-06&sig=abc%3D"
+        ));
+
+        assert!(parse_connection_string("AccountName=cedraetl").is_err());
+    }
+}
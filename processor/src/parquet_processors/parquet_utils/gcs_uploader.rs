@@ -1,12 +1,25 @@
 use crate::{
+    config::db_config::{ParquetPathLayout, TableOutputFormat},
+    db::{
+        parquet_file_manifest::ParquetFileManifestEntry,
+        parquet_upload_checkpoint::ParquetUploadCheckpoint,
+    },
     parquet_processors::{
-        parquet_utils::util::{HasParquetSchema, HasVersion, ParquetProcessorError},
+        parquet_utils::{
+            avro_writer::{write_avro_file, AvroSchema},
+            azure_blob_uploader::{upload_parquet_to_azure_blob, AzureBlobClient},
+            util::{format_table_name, HasParquetSchema, HasVersion, ParquetProcessorError},
+        },
         ParquetTypeEnum, ParquetTypeStructs, ParquetTypeTrait,
     },
-    utils::counters::PARQUET_BUFFER_SIZE,
+    utils::{counters::PARQUET_BUFFER_SIZE, processing_context::ProcessingContext},
+};
+use anyhow::{bail, Context, Result};
+use cedra_indexer_processor_sdk::{
+    cedra_indexer_transaction_stream::utils::time::parse_timestamp,
+    postgres::utils::database::ArcDbPool, types::transaction_context::TransactionMetadata,
+    utils::errors::ProcessorError,
 };
-use anyhow::{Context, Result};
-use cedra_indexer_processor_sdk::utils::errors::ProcessorError;
 use async_trait::async_trait;
 use chrono::{Datelike, Timelike};
 use google_cloud_storage::{
@@ -15,36 +28,120 @@ use google_cloud_storage::{
 };
 use hyper::{body::HttpBody, Body};
 use parquet::{
+    basic::{Repetition, Type as PhysicalType},
+    data_type::Int64Type,
     file::{properties::WriterProperties, writer::SerializedFileWriter},
+    format::KeyValue,
     record::RecordWriter,
     schema::types::Type,
 };
+use serde::Serialize;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
     sync::Arc,
 };
 use tokio::time::{sleep, timeout, Duration};
 use tracing::{debug, error, info};
 
-const MAX_RETRIES: usize = 3;
-const INITIAL_DELAY_MS: u64 = 500;
-const TIMEOUT_SECONDS: u64 = 300;
+pub(crate) const MAX_RETRIES: usize = 3;
+pub(crate) const INITIAL_DELAY_MS: u64 = 500;
+pub(crate) const TIMEOUT_SECONDS: u64 = 300;
+/// Column added to every row when `tag_rows_with_chain_id` is set
+pub const CHAIN_ID_COLUMN: &str = "chain_id";
+/// Footer metadata keys of the closed version window a parquet file covers
+pub const WINDOW_START_VERSION_KEY: &str = "window_start_version";
+pub const WINDOW_END_VERSION_KEY: &str = "window_end_version";
+
+/// Where parquet files are uploaded to
+#[derive(Clone)]
+pub enum ParquetStorageClient {
+    Gcs(Arc<GCSClient>),
+    AzureBlob(Arc<AzureBlobClient>),
+}
+
+impl ParquetStorageClient {
+    async fn upload(
+        &self,
+        buffer: Vec<u8>,
+        table_name: &str,
+        bucket_name: &str,
+        object_name: &Path,
+        processor_name: String,
+    ) -> Result<(), ParquetProcessorError> {
+        match self {
+            Self::Gcs(client) => {
+                upload_parquet_to_gcs(
+                    client,
+                    buffer,
+                    table_name,
+                    bucket_name,
+                    object_name,
+                    processor_name,
+                )
+                .await
+            },
+            Self::AzureBlob(client) => {
+                upload_parquet_to_azure_blob(
+                    client,
+                    buffer,
+                    table_name,
+                    bucket_name,
+                    object_name,
+                    processor_name,
+                )
+                .await
+            },
+        }
+    }
+}
+
 pub struct GCSUploader {
-    gcs_client: Arc<GCSClient>,
+    storage_client: ParquetStorageClient,
     parquet_type_to_schemas: HashMap<ParquetTypeEnum, Arc<Type>>,
     parquet_type_to_writer: HashMap<ParquetTypeEnum, SerializedFileWriter<Vec<u8>>>,
+    parquet_type_to_writer_properties: HashMap<ParquetTypeEnum, Arc<WriterProperties>>,
     pub bucket_name: String,
     pub bucket_root: String,
-    pub processor_name: String,
+    pub path_layout: ParquetPathLayout,
+    pub table_output_formats: HashMap<String, TableOutputFormat>,
+    pub tag_rows_with_chain_id: bool,
+    pub processing_context: ProcessingContext,
+    /// Every uploaded file is recorded in `parquet_file_manifest` if set, and checkpointed in
+    /// `parquet_upload_checkpoints` if the processing context says so
+    manifest_db_pool: Option<ArcDbPool>,
+    /// Holds back files until the manifest covers the versions before their window
+    pub hold_back_noncontiguous_files: bool,
+    /// First version of the next file's window per table, right after the previous file's
+    window_starts: HashMap<String, u64>,
+    /// Buffers per table waiting for the manifest to catch up with the first one, in order
+    held_files: HashMap<String, Vec<HeldFile>>,
+    /// Tables whose files of this run continue what the manifest covers
+    contiguous_tables: HashSet<String>,
+}
+
+/// A file ready to be uploaded
+struct PreparedFile {
+    contents: Vec<u8>,
+    object_name: PathBuf,
+    manifest_entry: ParquetFileManifestEntry,
+}
+
+/// A buffer held back with its file, or without one if it had no rows
+struct HeldFile {
+    buffer_metadata: TransactionMetadata,
+    file: Option<PreparedFile>,
 }
 
 #[async_trait]
 pub trait Uploadable {
+    /// Returns the metadata of the buffers uploaded, which can include buffers held back before
+    /// this one, or None if this one is held back.
     async fn upload_buffer(
         &mut self,
         buffer: ParquetTypeStructs,
-    ) -> anyhow::Result<(), ProcessorError>;
+        buffer_metadata: &TransactionMetadata,
+    ) -> anyhow::Result<Option<TransactionMetadata>, ProcessorError>;
 }
 
 #[async_trait]
@@ -52,49 +149,126 @@ impl Uploadable for GCSUploader {
     async fn upload_buffer(
         &mut self,
         buffer: ParquetTypeStructs,
-    ) -> anyhow::Result<(), ProcessorError> {
+        buffer_metadata: &TransactionMetadata,
+    ) -> anyhow::Result<Option<TransactionMetadata>, ProcessorError> {
         let parquet_type = buffer.parquet_type();
         let table_name = parquet_type.to_string();
 
-        let result = buffer.upload_to_gcs(self, parquet_type, &table_name).await;
-        if let Err(e) = result {
-            error!("Failed to upload buffer: {}", e);
-            return Err(ProcessorError::ProcessError {
-                message: format!("Failed to upload buffer: {e}"),
-            });
-        }
-        Ok(())
+        buffer
+            .upload_to_gcs(self, parquet_type, &table_name, buffer_metadata)
+            .await
+            .map_err(|e| {
+                error!("Failed to upload buffer: {}", e);
+                ProcessorError::ProcessError {
+                    message: format!("Failed to upload buffer: {e}"),
+                }
+            })
     }
 }
 
-pub fn create_new_writer(schema: Arc<Type>) -> anyhow::Result<SerializedFileWriter<Vec<u8>>> {
-    let props = WriterProperties::builder()
-        .set_compression(parquet::basic::Compression::LZ4)
-        .build();
-    let props_arc = Arc::new(props);
+pub fn create_new_writer(
+    schema: Arc<Type>,
+    props: Arc<WriterProperties>,
+) -> anyhow::Result<SerializedFileWriter<Vec<u8>>> {
+    SerializedFileWriter::new(Vec::new(), schema, props).context("Failed to create new writer")
+}
 
-    SerializedFileWriter::new(Vec::new(), schema, props_arc).context("Failed to create new writer")
+/// The schema with a required `chain_id` column appended, which is written after the model's own
+/// columns
+pub fn with_chain_id_column(schema: &Type) -> Result<Arc<Type>> {
+    let mut fields = schema.get_fields().to_vec();
+    if fields.iter().any(|field| field.name() == CHAIN_ID_COLUMN) {
+        bail!("{} already has a {CHAIN_ID_COLUMN} column", schema.name());
+    }
+    fields.push(Arc::new(
+        Type::primitive_type_builder(CHAIN_ID_COLUMN, PhysicalType::INT64)
+            .with_repetition(Repetition::REQUIRED)
+            .build()?,
+    ));
+    Ok(Arc::new(
+        Type::group_type_builder(schema.name())
+            .with_fields(fields)
+            .build()?,
+    ))
+}
+
+/// Window of the file of `buffer_metadata`'s rows, which starts right after the previous file of
+/// the table, or at the first buffer of the table. Buffers without rows get no file, so their
+/// versions are covered by the next file's window.
+fn file_window(
+    window_starts: &mut HashMap<String, u64>,
+    table_name: &str,
+    buffer_metadata: &TransactionMetadata,
+    has_rows: bool,
+) -> TransactionMetadata {
+    let window_start = *window_starts
+        .entry(table_name.to_string())
+        .or_insert(buffer_metadata.start_version);
+    if has_rows {
+        window_starts.insert(table_name.to_string(), buffer_metadata.end_version + 1);
+    }
+    TransactionMetadata {
+        start_version: window_start,
+        ..buffer_metadata.clone()
+    }
+}
+
+/// A row with the chain id added, for Avro files
+#[derive(Serialize)]
+struct ChainIdRow<'a, T> {
+    #[serde(flatten)]
+    row: &'a T,
+    chain_id: i64,
 }
 
 impl GCSUploader {
     pub fn new(
-        gcs_client: Arc<GCSClient>,
+        storage_client: ParquetStorageClient,
         parquet_type_to_schemas: HashMap<ParquetTypeEnum, Arc<Type>>,
         parquet_type_to_writer: HashMap<ParquetTypeEnum, SerializedFileWriter<Vec<u8>>>,
+        parquet_type_to_writer_properties: HashMap<ParquetTypeEnum, Arc<WriterProperties>>,
         bucket_name: String,
         bucket_root: String,
-        processor_name: String,
+        path_layout: ParquetPathLayout,
+        table_output_formats: HashMap<String, TableOutputFormat>,
+        tag_rows_with_chain_id: bool,
+        processing_context: ProcessingContext,
+        manifest_db_pool: Option<ArcDbPool>,
+        hold_back_noncontiguous_files: bool,
     ) -> anyhow::Result<Self> {
+        if tag_rows_with_chain_id && processing_context.chain_id.is_none() {
+            bail!("tag_rows_with_chain_id needs a chain id, local transactions don't have one");
+        }
+        if hold_back_noncontiguous_files && manifest_db_pool.is_none() {
+            bail!("hold_back_noncontiguous_files needs the file manifest");
+        }
         Ok(Self {
-            gcs_client,
+            storage_client,
             parquet_type_to_schemas,
             parquet_type_to_writer,
+            parquet_type_to_writer_properties,
             bucket_name,
             bucket_root,
-            processor_name,
+            path_layout,
+            table_output_formats,
+            tag_rows_with_chain_id,
+            processing_context,
+            manifest_db_pool,
+            hold_back_noncontiguous_files,
+            window_starts: HashMap::new(),
+            held_files: HashMap::new(),
+            contiguous_tables: HashSet::new(),
         })
     }
 
+    /// Chain id written to the `chain_id` column, if rows are tagged
+    fn chain_id_column(&self) -> Option<i64> {
+        self.tag_rows_with_chain_id
+            .then_some(self.processing_context.chain_id)
+            .flatten()
+            .map(|chain_id| chain_id as i64)
+    }
+
     fn create_new_writer(
         &self,
         parquet_type: ParquetTypeEnum,
@@ -104,8 +278,13 @@ impl GCSUploader {
             .get(&parquet_type)
             .context("Parquet type not found in schemas")?
             .clone();
+        let props = self
+            .parquet_type_to_writer_properties
+            .get(&parquet_type)
+            .context("Parquet type not found in writer properties")?
+            .clone();
 
-        create_new_writer(schema)
+        create_new_writer(schema, props)
     }
 
     /// # Context: Why we replace our writer
@@ -130,22 +309,290 @@ impl GCSUploader {
         Ok(old_writer)
     }
 
-    // Generic upload function to handle any data type
+    // Generic upload function to handle any data type. Returns the metadata of the buffers
+    // uploaded, None while they're held back.
     pub async fn upload_generic<ParquetType>(
         &mut self,
         data: &[ParquetType],
         parquet_type: ParquetTypeEnum,
         table_name: &str,
-    ) -> anyhow::Result<()>
+        buffer_metadata: &TransactionMetadata,
+    ) -> anyhow::Result<Option<TransactionMetadata>>
     where
-        ParquetType: HasVersion + HasParquetSchema,
+        ParquetType: HasVersion + HasParquetSchema + Serialize,
         for<'a> &'a [ParquetType]: RecordWriter<ParquetType>,
     {
-        if data.is_empty() {
+        let window = file_window(
+            &mut self.window_starts,
+            table_name,
+            buffer_metadata,
+            !data.is_empty(),
+        );
+        let file = if data.is_empty() {
             println!("Buffer is empty, skipping upload.");
-            return Ok(());
+            None
+        } else {
+            Some(self.prepare_file(data, parquet_type, table_name, &window)?)
+        };
+
+        if !self.hold_back_noncontiguous_files {
+            if let Some(file) = file {
+                self.upload_file(file, table_name, buffer_metadata).await?;
+            }
+            return Ok(Some(buffer_metadata.clone()));
+        }
+        self.held_files
+            .entry(table_name.to_string())
+            .or_default()
+            .push(HeldFile {
+                buffer_metadata: buffer_metadata.clone(),
+                file,
+            });
+        self.upload_held_files(table_name).await
+    }
+
+    /// Uploads the held files of `table_name` once the manifest covers every version before the
+    /// first one. Files after the first one of a run continue its window, so only the first needs
+    /// to wait.
+    async fn upload_held_files(
+        &mut self,
+        table_name: &str,
+    ) -> anyhow::Result<Option<TransactionMetadata>> {
+        let Some(first) = self
+            .held_files
+            .get(table_name)
+            .and_then(|held_files| held_files.first())
+        else {
+            return Ok(None);
+        };
+        if !self.contiguous_tables.contains(table_name) {
+            let manifest_db_pool = self
+                .manifest_db_pool
+                .as_ref()
+                .context("Holding back files needs the file manifest")?;
+            let mut conn = manifest_db_pool
+                .get()
+                .await
+                .context("Failed to get a connection for the parquet file manifest")?;
+            let contiguous_through = ParquetFileManifestEntry::contiguous_through(
+                &self.bucket_name,
+                &self
+                    .processing_context
+                    .table_names
+                    .output_table_name(table_name),
+                &mut conn,
+            )
+            .await
+            .with_context(|| format!("Failed to look up the file windows of {table_name}"))?;
+            let window_start = first.buffer_metadata.start_version as i64;
+            if let Some(contiguous_through) = contiguous_through {
+                if contiguous_through + 1 < window_start {
+                    info!(
+                        table_name = table_name,
+                        contiguous_through = contiguous_through,
+                        window_start = window_start,
+                        "Holding back files until the versions before them are uploaded"
+                    );
+                    return Ok(None);
+                }
+            }
+            self.contiguous_tables.insert(table_name.to_string());
+        }
+
+        let held_files = self.held_files.remove(table_name).unwrap_or_default();
+        let mut uploaded: Option<TransactionMetadata> = None;
+        for held_file in held_files {
+            if let Some(file) = held_file.file {
+                self.upload_file(file, table_name, &held_file.buffer_metadata)
+                    .await?;
+            }
+            uploaded = Some(match uploaded {
+                Some(uploaded) => TransactionMetadata {
+                    start_version: uploaded.start_version,
+                    start_transaction_timestamp: uploaded.start_transaction_timestamp,
+                    ..held_file.buffer_metadata
+                },
+                None => held_file.buffer_metadata,
+            });
+        }
+        Ok(uploaded)
+    }
+
+    /// Writes the rows in the table's output format, named after their version window
+    fn prepare_file<ParquetType>(
+        &mut self,
+        data: &[ParquetType],
+        parquet_type: ParquetTypeEnum,
+        table_name: &str,
+        window: &TransactionMetadata,
+    ) -> anyhow::Result<PreparedFile>
+    where
+        ParquetType: HasVersion + HasParquetSchema + Serialize,
+        for<'a> &'a [ParquetType]: RecordWriter<ParquetType>,
+    {
+        let output_format = self
+            .table_output_formats
+            .get(table_name)
+            .copied()
+            .unwrap_or_default();
+        let contents = match output_format {
+            TableOutputFormat::Parquet => self.write_parquet(data, parquet_type, window)?,
+            TableOutputFormat::Avro => {
+                let schema = self
+                    .parquet_type_to_schemas
+                    .get(&parquet_type)
+                    .context("Parquet type not found in schemas")?;
+                let avro_schema = AvroSchema::from_parquet_schema(table_name, schema)?;
+                match self.chain_id_column() {
+                    Some(chain_id) => {
+                        let rows: Vec<_> = data
+                            .iter()
+                            .map(|row| ChainIdRow { row, chain_id })
+                            .collect();
+                        write_avro_file(&avro_schema, &rows)?
+                    },
+                    None => write_avro_file(&avro_schema, data)?,
+                }
+            },
+        };
+
+        let bucket_root = PathBuf::from(&self.bucket_root);
+        let output_table_name = self
+            .processing_context
+            .table_names
+            .output_table_name(table_name);
+        let object_name = generate_parquet_file_path(
+            self.path_layout,
+            &bucket_root,
+            &output_table_name,
+            output_format.extension(),
+            window,
+            chrono::Utc::now(),
+        );
+        let manifest_entry = self.manifest_entry(
+            data,
+            &output_table_name,
+            output_format,
+            &object_name,
+            contents.len() as i64,
+            window,
+        );
+        Ok(PreparedFile {
+            contents,
+            object_name,
+            manifest_entry,
+        })
+    }
+
+    async fn upload_file(
+        &self,
+        file: PreparedFile,
+        table_name: &str,
+        buffer_metadata: &TransactionMetadata,
+    ) -> anyhow::Result<()> {
+        let PreparedFile {
+            contents,
+            object_name,
+            manifest_entry,
+        } = file;
+        self.storage_client
+            .upload(
+                contents,
+                table_name,
+                &self.bucket_name,
+                &object_name,
+                self.processing_context.processor_name.clone(),
+            )
+            .await?;
+
+        debug!(
+            "Uploaded {} for table: {}, start_version: {}, end_version: {}",
+            manifest_entry.file_format,
+            table_name,
+            manifest_entry.min_version,
+            manifest_entry.max_version
+        );
+
+        if let Some(manifest_db_pool) = &self.manifest_db_pool {
+            let mut conn = manifest_db_pool
+                .get()
+                .await
+                .context("Failed to get a connection for the parquet file manifest")?;
+            manifest_entry.insert(&mut conn).await.with_context(|| {
+                format!(
+                    "Failed to record {} in the file manifest",
+                    manifest_entry.file_path
+                )
+            })?;
+            if self.processing_context.checkpoint_uploads {
+                let checkpoint = ParquetUploadCheckpoint {
+                    processor: format_table_name(
+                        &self.processing_context.processor_name,
+                        table_name,
+                    ),
+                    bucket_name: manifest_entry.bucket_name,
+                    file_path: manifest_entry.file_path,
+                    max_version: manifest_entry.max_version,
+                    end_version: buffer_metadata.end_version as i64,
+                    last_updated: chrono::Utc::now().naive_utc(),
+                };
+                checkpoint.upsert(&mut conn).await.with_context(|| {
+                    format!(
+                        "Failed to checkpoint the upload of {}",
+                        checkpoint.file_path
+                    )
+                })?;
+            }
         }
 
+        Ok(())
+    }
+
+    /// Manifest entry of an uploaded file. Versions are taken from the rows, the window and block
+    /// timestamps from the buffer, which only spans the transactions the rows were extracted from.
+    fn manifest_entry<ParquetType: HasVersion>(
+        &self,
+        data: &[ParquetType],
+        table_name: &str,
+        output_format: TableOutputFormat,
+        object_name: &Path,
+        file_size_bytes: i64,
+        window: &TransactionMetadata,
+    ) -> ParquetFileManifestEntry {
+        let versions = data.iter().map(|row| row.version());
+        ParquetFileManifestEntry {
+            processor: self.processing_context.processor_name.clone(),
+            table_name: table_name.to_string(),
+            bucket_name: self.bucket_name.clone(),
+            file_path: object_name.to_string_lossy().into_owned(),
+            file_format: output_format.extension().to_string(),
+            num_rows: data.len() as i64,
+            min_version: versions.clone().min().unwrap_or_default(),
+            max_version: versions.max().unwrap_or_default(),
+            min_block_timestamp: window
+                .start_transaction_timestamp
+                .as_ref()
+                .map(|t| parse_timestamp(t, window.start_version as i64).naive_utc()),
+            max_block_timestamp: window
+                .end_transaction_timestamp
+                .as_ref()
+                .map(|t| parse_timestamp(t, window.end_version as i64).naive_utc()),
+            file_size_bytes,
+            window_start_version: Some(window.start_version as i64),
+            window_end_version: Some(window.end_version as i64),
+        }
+    }
+
+    fn write_parquet<ParquetType>(
+        &mut self,
+        data: &[ParquetType],
+        parquet_type: ParquetTypeEnum,
+        window: &TransactionMetadata,
+    ) -> anyhow::Result<Vec<u8>>
+    where
+        for<'a> &'a [ParquetType]: RecordWriter<ParquetType>,
+    {
+        let chain_id = self.chain_id_column();
         let writer = self
             .parquet_type_to_writer
             .get_mut(&parquet_type)
@@ -155,37 +602,36 @@ impl GCSUploader {
 
         data.write_to_row_group(&mut row_group_writer)
             .context("Failed to write to row group")?;
+        if let Some(chain_id) = chain_id {
+            let mut column_writer = row_group_writer
+                .next_column()?
+                .context("Schema has no chain_id column")?;
+            column_writer.typed::<Int64Type>().write_batch(
+                &vec![chain_id; data.len()],
+                None,
+                None,
+            )?;
+            column_writer.close()?;
+        }
 
         row_group_writer
             .close()
             .context("Failed to close row group")?;
+        writer.append_key_value_metadata(KeyValue::new(
+            WINDOW_START_VERSION_KEY.to_string(),
+            window.start_version.to_string(),
+        ));
+        writer.append_key_value_metadata(KeyValue::new(
+            WINDOW_END_VERSION_KEY.to_string(),
+            window.end_version.to_string(),
+        ));
 
         let old_writer = self
             .get_and_replace_writer(parquet_type)
             .context("Failed to close writer")?;
-        let upload_buffer = old_writer
+        old_writer
             .into_inner()
-            .context("Failed to get inner buffer")?;
-
-        let bucket_root = PathBuf::from(&self.bucket_root);
-        upload_parquet_to_gcs(
-            &self.gcs_client,
-            upload_buffer,
-            table_name,
-            &self.bucket_name,
-            &bucket_root,
-            self.processor_name.clone(),
-        )
-        .await?;
-
-        debug!(
-            "Uploaded parquet to GCS for table: {}, start_version: {}, end_version: {}",
-            table_name,
-            data[0].version(),
-            data[data.len() - 1].version()
-        );
-
-        Ok(())
+            .context("Failed to get inner buffer")
     }
 }
 
@@ -194,7 +640,7 @@ pub async fn upload_parquet_to_gcs(
     buffer: Vec<u8>,
     table_name: &str,
     bucket_name: &str,
-    bucket_root: &Path,
+    object_name: &Path,
     processor_name: String,
 ) -> Result<(), ParquetProcessorError> {
     if buffer.is_empty() {
@@ -204,24 +650,6 @@ pub async fn upload_parquet_to_gcs(
         ));
     }
 
-    let now = chrono::Utc::now();
-    let start_of_month = now
-        .with_day(1)
-        .unwrap()
-        .with_hour(0)
-        .unwrap()
-        .with_minute(0)
-        .unwrap()
-        .with_second(0)
-        .unwrap()
-        .with_nanosecond(0)
-        .unwrap();
-    let highwater_s = start_of_month.timestamp_millis();
-    let highwater_ms = now.timestamp_millis();
-    let counter = 0; // THIS NEED TO BE REPLACED OR REIMPLEMENTED WITH AN ACTUAL LOGIC TO ENSURE FILE UNIQUENESS.
-    let object_name: PathBuf =
-        generate_parquet_file_path(bucket_root, table_name, highwater_s, highwater_ms, counter);
-
     let file_name = object_name.to_str().unwrap().to_owned();
     let upload_type: UploadType = UploadType::Simple(Media::new(file_name.clone()));
 
@@ -278,13 +706,160 @@ pub async fn upload_parquet_to_gcs(
 }
 
 fn generate_parquet_file_path(
+    path_layout: ParquetPathLayout,
     gcs_bucket_root: &Path,
     table: &str,
-    highwater_s: i64,
-    highwater_ms: i64,
-    counter: u32,
+    extension: &str,
+    buffer_metadata: &TransactionMetadata,
+    now: chrono::DateTime<chrono::Utc>,
 ) -> PathBuf {
-    gcs_bucket_root.join(format!(
-        "{table}/{highwater_s}/{highwater_ms}_{counter}.parquet"
-    ))
+    let highwater_ms = now.timestamp_millis();
+    let counter = 0; // THIS NEED TO BE REPLACED OR REIMPLEMENTED WITH AN ACTUAL LOGIC TO ENSURE FILE UNIQUENESS.
+    match path_layout {
+        ParquetPathLayout::Flat => {
+            let start_of_month = now
+                .with_day(1)
+                .unwrap()
+                .with_hour(0)
+                .unwrap()
+                .with_minute(0)
+                .unwrap()
+                .with_second(0)
+                .unwrap()
+                .with_nanosecond(0)
+                .unwrap();
+            let highwater_s = start_of_month.timestamp_millis();
+            gcs_bucket_root.join(format!(
+                "{table}/{highwater_s}/{highwater_ms}_{counter}.{extension}"
+            ))
+        },
+        ParquetPathLayout::Hive => {
+            let start_version = buffer_metadata.start_version;
+            // Every buffer should carry the timestamp of its first transaction; fall back to the
+            // upload time rather than failing the upload if it doesn't.
+            let dt = buffer_metadata
+                .start_transaction_timestamp
+                .as_ref()
+                .map(|t| parse_timestamp(t, start_version as i64))
+                .unwrap_or(now)
+                .format("%Y-%m-%d");
+            gcs_bucket_root.join(format!(
+                "table={table}/dt={dt}/ver_start={start_version}/{highwater_ms}_{counter}.{extension}"
+            ))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cedra_indexer_processor_sdk::cedra_protos::util::timestamp::Timestamp;
+    use chrono::TimeZone;
+
+    fn buffer_metadata() -> TransactionMetadata {
+        TransactionMetadata {
+            start_version: 1000,
+            end_version: 1999,
+            // 2024-05-01T12:00:00Z
+            start_transaction_timestamp: Some(Timestamp {
+                seconds: 1714564800,
+                nanos: 0,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_with_chain_id_column() {
+        let schema = parquet::schema::parser::parse_message_type(
+            "message events {
+                REQUIRED INT64 txn_version;
+            }",
+        )
+        .unwrap();
+        let tagged = with_chain_id_column(&schema).unwrap();
+        let names: Vec<&str> = tagged.get_fields().iter().map(|f| f.name()).collect();
+        assert_eq!(names, vec!["txn_version", CHAIN_ID_COLUMN]);
+        assert!(with_chain_id_column(&tagged).is_err());
+    }
+
+    #[test]
+    fn test_file_windows_cover_buffers_without_rows() {
+        let mut window_starts = HashMap::new();
+        let buffer = |start_version, end_version| TransactionMetadata {
+            start_version,
+            end_version,
+            ..Default::default()
+        };
+        let window = file_window(&mut window_starts, "events", &buffer(100, 199), true);
+        assert_eq!((window.start_version, window.end_version), (100, 199));
+        file_window(&mut window_starts, "events", &buffer(200, 299), false);
+        let window = file_window(&mut window_starts, "events", &buffer(300, 399), true);
+        assert_eq!((window.start_version, window.end_version), (200, 399));
+
+        // The first buffer of a table has no rows
+        file_window(&mut window_starts, "signatures", &buffer(100, 199), false);
+        let window = file_window(&mut window_starts, "signatures", &buffer(200, 299), true);
+        assert_eq!((window.start_version, window.end_version), (100, 299));
+    }
+
+    #[test]
+    fn test_flat_parquet_file_path() {
+        let now = chrono::Utc.with_ymd_and_hms(2024, 6, 15, 8, 30, 0).unwrap();
+        let path = generate_parquet_file_path(
+            ParquetPathLayout::Flat,
+            Path::new("root"),
+            "events",
+            "parquet",
+            &buffer_metadata(),
+            now,
+        );
+        assert_eq!(
+            path,
+            PathBuf::from(format!(
+                "root/events/1717200000000/{}_0.parquet",
+                now.timestamp_millis()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_hive_parquet_file_path() {
+        let now = chrono::Utc.with_ymd_and_hms(2024, 6, 15, 8, 30, 0).unwrap();
+        let path = generate_parquet_file_path(
+            ParquetPathLayout::Hive,
+            Path::new("root"),
+            "events",
+            "parquet",
+            &buffer_metadata(),
+            now,
+        );
+        assert_eq!(
+            path,
+            PathBuf::from(format!(
+                "root/table=events/dt=2024-05-01/ver_start=1000/{}_0.parquet",
+                now.timestamp_millis()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_avro_file_path() {
+        let now = chrono::Utc.with_ymd_and_hms(2024, 6, 15, 8, 30, 0).unwrap();
+        let path = generate_parquet_file_path(
+            ParquetPathLayout::Hive,
+            Path::new("root"),
+            "events",
+            TableOutputFormat::Avro.extension(),
+            &buffer_metadata(),
+            now,
+        );
+        assert_eq!(
+            path,
+            PathBuf::from(format!(
+                "root/table=events/dt=2024-05-01/ver_start=1000/{}_0.avro",
+                now.timestamp_millis()
+            ))
+        );
+    }
 }
@@ -1,3 +1,5 @@
+pub mod avro_writer;
+pub mod azure_blob_uploader;
 pub mod gcs_uploader;
 pub mod parquet_buffer_step;
 pub mod parquet_version_tracker_step;
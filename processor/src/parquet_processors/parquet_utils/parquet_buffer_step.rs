@@ -134,7 +134,9 @@ impl ParquetBufferStep {
         );
 
         // If the current buffer size + new batch exceeds max size, upload the buffer
-        if buffer.buffer_size_bytes + curr_batch_size_bytes > self.buffer_max_size {
+        if buffer.current_batch_metadata.is_some()
+            && buffer.buffer_size_bytes + curr_batch_size_bytes > self.buffer_max_size
+        {
             println!(
                 "Buffer size {} + batch size {} exceeds max size {}. Uploading buffer for {:?}.",
                 buffer.buffer_size_bytes, curr_batch_size_bytes, self.buffer_max_size, parquet_type
@@ -145,11 +147,15 @@ impl ParquetBufferStep {
                 &mut buffer.buffer,
                 ParquetTypeStructs::default_for_type(&parquet_type),
             );
-            self.buffer_uploader.upload_buffer(struct_buffer).await?;
-
-            // update this metadata before insert
-            upload_metadata_map
-                .insert(parquet_type, buffer.current_batch_metadata.clone().unwrap());
+            let buffer_metadata = buffer.current_batch_metadata.clone().unwrap();
+            // Nothing is reported while the uploader holds the buffer back
+            if let Some(uploaded_metadata) = self
+                .buffer_uploader
+                .upload_buffer(struct_buffer, &buffer_metadata)
+                .await?
+            {
+                upload_metadata_map.insert(parquet_type, uploaded_metadata);
+            }
             buffer.buffer_size_bytes = 0;
             buffer.current_batch_metadata = None;
         }
@@ -158,6 +164,25 @@ impl ParquetBufferStep {
         Self::append_to_buffer(buffer, parquet_data)?;
         buffer.update_current_batch_metadata(cur_batch_metadata)?;
 
+        // A table without rows to upload is caught up through this batch. It's reported right
+        // away, since nothing would upload it otherwise, and its watermark would hold back the
+        // consistency watermark.
+        if buffer.buffer.is_empty() {
+            let struct_buffer = std::mem::replace(
+                &mut buffer.buffer,
+                ParquetTypeStructs::default_for_type(&parquet_type),
+            );
+            let buffer_metadata = buffer.current_batch_metadata.take().unwrap();
+            if let Some(uploaded_metadata) = self
+                .buffer_uploader
+                .upload_buffer(struct_buffer, &buffer_metadata)
+                .await?
+            {
+                upload_metadata_map.insert(parquet_type, uploaded_metadata);
+            }
+            buffer.buffer_size_bytes = 0;
+        }
+
         debug!(
             "Updated buffer size for {:?}: {} bytes",
             parquet_type, buffer.buffer_size_bytes,
@@ -214,11 +239,15 @@ impl Processable for ParquetBufferStep {
                     ParquetTypeStructs::default_for_type(&parquet_type),
                 );
 
-                self.buffer_uploader.upload_buffer(struct_buffer).await?;
-
-                if let Some(buffer_metadata) = &mut buffer.current_batch_metadata {
-                    buffer_metadata.total_size_in_bytes = buffer.buffer_size_bytes as u64;
-                    metadata_map.insert(parquet_type, buffer_metadata.clone());
+                if let Some(buffer_metadata) = &buffer.current_batch_metadata {
+                    if let Some(mut uploaded_metadata) = self
+                        .buffer_uploader
+                        .upload_buffer(struct_buffer, buffer_metadata)
+                        .await?
+                    {
+                        uploaded_metadata.total_size_in_bytes = buffer.buffer_size_bytes as u64;
+                        metadata_map.insert(parquet_type, uploaded_metadata);
+                    }
                 } else {
                     // This should never happen
                     panic!("Buffer metadata is missing for ParquetTypeEnum: {parquet_type:?}");
@@ -259,10 +288,14 @@ impl PollableAsyncStep for ParquetBufferStep {
                     ParquetTypeStructs::default_for_type(&parquet_type),
                 );
 
-                self.buffer_uploader.upload_buffer(struct_buffer).await?;
-
                 let metadata = buffer.current_batch_metadata.clone().unwrap();
-                metadata_map.insert(parquet_type, metadata);
+                if let Some(uploaded_metadata) = self
+                    .buffer_uploader
+                    .upload_buffer(struct_buffer, &metadata)
+                    .await?
+                {
+                    metadata_map.insert(parquet_type, uploaded_metadata);
+                }
 
                 buffer.buffer_size_bytes = 0;
                 buffer.current_batch_metadata = None;
@@ -287,20 +320,24 @@ impl NamedStep for ParquetBufferStep {
 #[cfg(test)]
 mod tests {
     use crate::{
-        config::db_config::ParquetConfig,
-        parquet_processors::parquet_utils::{
-            gcs_uploader::{create_new_writer, GCSUploader},
-            parquet_buffer_step::{ParquetBufferStep, ParquetTypeEnum, ParquetTypeStructs},
-            util::HasParquetSchema,
+        config::db_config::{ParquetConfig, ParquetPathLayout},
+        parquet_processors::{
+            parquet_utils::{
+                gcs_uploader::{create_new_writer, GCSUploader, ParquetStorageClient},
+                parquet_buffer_step::{ParquetBufferStep, ParquetTypeEnum, ParquetTypeStructs},
+                util::HasParquetSchema,
+            },
+            ParquetTypeTrait,
         },
         processors::default::models::move_resources::ParquetMoveResource,
+        utils::processing_context::ProcessingContext,
     };
     use cedra_indexer_processor_sdk::{
         traits::Processable,
         types::transaction_context::{TransactionContext, TransactionMetadata},
     };
     use google_cloud_storage::client::{Client as GCSClient, ClientConfig as GcsClientConfig};
-    use parquet::schema::types::Type;
+    use parquet::{file::properties::WriterProperties, schema::types::Type};
     use std::{collections::HashMap, sync::Arc, time::Duration};
 
     #[tokio::test]
@@ -309,12 +346,9 @@ mod tests {
         let db_config = create_parquet_db_config();
         let buffer_uploader = create_parquet_uploader(&db_config).await?;
         let mut parquet_step =
-            ParquetBufferStep::new(Duration::from_secs(10), buffer_uploader, 100);
+            ParquetBufferStep::new(Duration::from_secs(10), buffer_uploader, 100_000);
 
-        let data = HashMap::from([(
-            ParquetTypeEnum::MoveResources,
-            ParquetTypeStructs::default_for_type(&ParquetTypeEnum::MoveResources),
-        )]);
+        let data = HashMap::from([(ParquetTypeEnum::MoveResources, move_resources())]);
         let metadata = TransactionMetadata::default();
 
         let result = parquet_step
@@ -330,10 +364,41 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_parquet_buffer_step_reports_empty_tables() -> anyhow::Result<()> {
+        let db_config = create_parquet_db_config();
+        let buffer_uploader = create_parquet_uploader(&db_config).await?;
+        let mut parquet_step =
+            ParquetBufferStep::new(Duration::from_secs(10), buffer_uploader, 100_000);
+
+        for (start_version, end_version) in [(0, 9), (10, 19)] {
+            let data = HashMap::from([(
+                ParquetTypeEnum::MoveResources,
+                ParquetTypeStructs::default_for_type(&ParquetTypeEnum::MoveResources),
+            )]);
+            let metadata = TransactionMetadata {
+                start_version,
+                end_version,
+                ..Default::default()
+            };
+            let result = parquet_step
+                .process(TransactionContext { data, metadata })
+                .await
+                .unwrap()
+                .expect("Expected tables without rows to be reported every batch");
+            let reported = &result.data[&ParquetTypeEnum::MoveResources];
+            assert_eq!(reported.start_version, start_version);
+            assert_eq!(reported.end_version, end_version);
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     #[allow(clippy::needless_return)]
     async fn test_parquet_buffer_step_trigger_upload() -> anyhow::Result<()> {
-        let buffer_max_size = 25; // Default ParquetTypeStructs for MoveResource is 24 bytes
+        // Room for one batch but not two
+        let buffer_max_size = move_resources().calculate_size() + 1;
         let db_config = create_parquet_db_config();
 
         let buffer_uploader = create_parquet_uploader(&db_config).await?;
@@ -341,10 +406,7 @@ mod tests {
             ParquetBufferStep::new(Duration::from_secs(10), buffer_uploader, buffer_max_size);
 
         // Test data below `buffer_max_size`
-        let data = HashMap::from([(
-            ParquetTypeEnum::MoveResources,
-            ParquetTypeStructs::default_for_type(&ParquetTypeEnum::MoveResources),
-        )]);
+        let data = HashMap::from([(ParquetTypeEnum::MoveResources, move_resources())]);
         let metadata = TransactionMetadata::default();
 
         let result = parquet_step
@@ -357,10 +419,7 @@ mod tests {
         );
 
         // Test buffer + data > `buffer_max_size`
-        let data = HashMap::from([(
-            ParquetTypeEnum::MoveResources,
-            ParquetTypeStructs::default_for_type(&ParquetTypeEnum::MoveResources),
-        )]);
+        let data = HashMap::from([(ParquetTypeEnum::MoveResources, move_resources())]);
         let metadata = TransactionMetadata::default();
 
         let result = parquet_step
@@ -376,6 +435,10 @@ mod tests {
         Ok(())
     }
 
+    fn move_resources() -> ParquetTypeStructs {
+        ParquetTypeStructs::MoveResource(vec![ParquetMoveResource::default()])
+    }
+
     async fn create_parquet_uploader(db_config: &ParquetConfig) -> anyhow::Result<GCSUploader> {
         let gcs_config = GcsClientConfig::default()
             .with_auth()
@@ -390,21 +453,39 @@ mod tests {
         .into_iter()
         .collect();
 
+        let parquet_type_to_writer_properties: HashMap<ParquetTypeEnum, Arc<WriterProperties>> =
+            parquet_type_to_schemas
+                .keys()
+                .map(|key| (*key, Arc::new(WriterProperties::default())))
+                .collect();
         let parquet_type_to_writer = parquet_type_to_schemas
             .iter()
             .map(|(key, schema)| {
-                let writer = create_new_writer(schema.clone()).expect("Failed to create writer");
+                let writer = create_new_writer(
+                    schema.clone(),
+                    parquet_type_to_writer_properties[key].clone(),
+                )
+                .expect("Failed to create writer");
                 (*key, writer)
             })
             .collect();
 
         GCSUploader::new(
-            gcs_client,
+            ParquetStorageClient::Gcs(gcs_client),
             parquet_type_to_schemas,
             parquet_type_to_writer,
+            parquet_type_to_writer_properties,
             db_config.bucket_name.clone(),
             db_config.bucket_root.clone(),
-            "processor_name".to_string(),
+            db_config.path_layout,
+            db_config.table_output_formats.clone(),
+            db_config.tag_rows_with_chain_id,
+            ProcessingContext {
+                processor_name: "processor_name".to_string(),
+                ..Default::default()
+            },
+            None,
+            db_config.hold_back_noncontiguous_files,
         )
     }
 
@@ -414,7 +495,13 @@ mod tests {
             db_pool_size: 10,
             bucket_name: "bucket_name".to_string(),
             bucket_root: "bucket_root".to_string(),
+            path_layout: ParquetPathLayout::Flat,
+            table_output_formats: HashMap::new(),
+            tag_rows_with_chain_id: false,
+            hold_back_noncontiguous_files: false,
             google_application_credentials: None,
+            azure_blob_config: None,
+            status_tables_only: false,
         }
     }
 }
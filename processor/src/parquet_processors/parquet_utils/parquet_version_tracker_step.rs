@@ -1,4 +1,7 @@
-use crate::parquet_processors::ParquetTypeEnum;
+use crate::{
+    parquet_processors::ParquetTypeEnum,
+    utils::{counters::PARQUET_STATUS_SAVE_FAILURES_COUNT, table_watermarks::TableWatermarks},
+};
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
     traits::{
@@ -8,8 +11,8 @@ use cedra_indexer_processor_sdk::{
     utils::errors::ProcessorError,
 };
 use async_trait::async_trait;
-use std::collections::HashMap;
-use tracing::debug;
+use std::{collections::HashMap, time::Duration};
+use tracing::{debug, warn};
 
 /// The ParquetProcessorStatusSaverTrait intended to save
 /// the latest successfully processed transaction version to storage,
@@ -21,8 +24,20 @@ pub trait ParquetProcessorStatusSaverTrait {
         last_success_batch: &TransactionContext<()>,
         table_name: &str,
     ) -> Result<(), ProcessorError>;
+
+    /// Saves the version through which every parquet table of the processor has been uploaded.
+    async fn save_parquet_consistency_watermark(
+        &self,
+        consistent_through: &TransactionContext<()>,
+    ) -> Result<(), ProcessorError>;
 }
 
+/// Status saves that can fail in a row, e.g. while the DB is unreachable, before the step fails.
+/// At the default poll interval of a second, that's about a minute.
+const MAX_CONSECUTIVE_SAVE_FAILURES: u32 = 60;
+/// Attempts at saving the status when the step shuts down
+const CLEANUP_SAVE_ATTEMPTS: u64 = 5;
+
 /// Tracks the versioned processing of sequential transactions, ensuring no gaps
 /// occur between them.
 ///
@@ -33,10 +48,12 @@ where
     Self: Sized + Send + 'static,
     S: ParquetProcessorStatusSaverTrait + Send + 'static,
 {
-    // Last successful batch of sequentially processed transactions. Includes metadata to write to storage.
-    last_success_batch: HashMap<ParquetTypeEnum, TransactionContext<()>>,
+    // Last successful batch of sequentially processed transactions per table. Includes metadata to write to storage.
+    last_success_batch: TableWatermarks<ParquetTypeEnum>,
     polling_interval_secs: u64,
     processor_status_saver: S,
+    /// Saves that failed since the last one that went through
+    consecutive_save_failures: u32,
 }
 
 impl<S> ParquetVersionTrackerStep<S>
@@ -44,23 +61,92 @@ where
     Self: Sized + Send + 'static,
     S: ParquetProcessorStatusSaverTrait + Send + 'static,
 {
-    pub fn new(processor_status_saver: S, polling_interval_secs: u64) -> Self {
+    /// `tables` are the ones the processor writes. The consistency watermark waits for each of
+    /// them to be uploaded once.
+    pub fn new(
+        processor_status_saver: S,
+        tables: impl IntoIterator<Item = ParquetTypeEnum>,
+        polling_interval_secs: u64,
+    ) -> Self {
         Self {
-            last_success_batch: HashMap::new(),
+            last_success_batch: TableWatermarks::new(tables),
             processor_status_saver,
             polling_interval_secs,
+            consecutive_save_failures: 0,
         }
     }
 
-    async fn save_processor_status(&mut self) -> Result<(), ProcessorError> {
-        for (parquet_type, last_success_batch) in &self.last_success_batch {
+    /// Saves the status of every table, and the consistency watermark, even if one of them
+    /// fails. Returns the first failure.
+    async fn try_save_processor_status(&self) -> Result<(), ProcessorError> {
+        let mut first_error = None;
+        for (parquet_type, last_success_batch) in self.last_success_batch.iter() {
             let table_name = parquet_type.to_string();
-            self.processor_status_saver
+            if let Err(e) = self
+                .processor_status_saver
                 .save_parquet_processor_status(last_success_batch, &table_name)
-                .await?;
+                .await
+            {
+                PARQUET_STATUS_SAVE_FAILURES_COUNT
+                    .with_label_values(&[&table_name])
+                    .inc();
+                first_error.get_or_insert(e);
+            }
+        }
+        // Tables are uploaded independently, so only the slowest one bounds what is safe to read.
+        if let Some(consistent_through) = self.last_success_batch.consistent_through() {
+            if let Err(e) = self
+                .processor_status_saver
+                .save_parquet_consistency_watermark(consistent_through)
+                .await
+            {
+                PARQUET_STATUS_SAVE_FAILURES_COUNT
+                    .with_label_values(&["processor_consistency_watermarks"])
+                    .inc();
+                first_error.get_or_insert(e);
+            }
         }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// A failed save keeps the watermarks to be saved again on the next poll, and only fails the
+    /// step after `MAX_CONSECUTIVE_SAVE_FAILURES` in a row.
+    async fn save_processor_status(&mut self) -> Result<(), ProcessorError> {
+        let Err(e) = self.try_save_processor_status().await else {
+            self.consecutive_save_failures = 0;
+            return Ok(());
+        };
+        self.consecutive_save_failures += 1;
+        if self.consecutive_save_failures >= MAX_CONSECUTIVE_SAVE_FAILURES {
+            return Err(e);
+        }
+        warn!(
+            consecutive_failures = self.consecutive_save_failures,
+            error = ?e,
+            "Failed to save parquet processor status, it's retried on the next poll"
+        );
         Ok(())
     }
+
+    /// Saves the status before the step shuts down, when there's no next poll to retry on
+    async fn save_processor_status_with_retries(&mut self) -> Result<(), ProcessorError> {
+        let mut attempt = 1;
+        loop {
+            match self.try_save_processor_status().await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt >= CLEANUP_SAVE_ATTEMPTS => return Err(e),
+                Err(e) => {
+                    warn!(
+                        attempt = attempt,
+                        error = ?e,
+                        "Failed to save parquet processor status before shutting down, retrying"
+                    );
+                    tokio::time::sleep(Duration::from_secs(attempt)).await;
+                    attempt += 1;
+                },
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -105,15 +191,7 @@ where
 
             // Update last_success_batch for the current key
             self.last_success_batch
-                .entry(*parquet_type)
-                .and_modify(|e| {
-                    e.data = ();
-                    e.metadata = current_metadata.clone();
-                })
-                .or_insert(TransactionContext {
-                    data: (),
-                    metadata: current_metadata.clone(),
-                });
+                .record(*parquet_type, current_metadata);
         }
 
         // Pass through the current batch with updated metadata
@@ -127,7 +205,7 @@ where
         &mut self,
     ) -> Result<Option<Vec<TransactionContext<Self::Output>>>, ProcessorError> {
         // Save the last successful batch to the database
-        self.save_processor_status().await?;
+        self.save_processor_status_with_retries().await?;
         Ok(None)
     }
 }
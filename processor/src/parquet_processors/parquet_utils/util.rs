@@ -131,6 +131,20 @@ pub fn add_to_map_if_opted_in_for_backfill(
     }
 }
 
+/// The types of `parquet_types` written with `opt_in_tables`, the ones
+/// `add_to_map_if_opted_in_for_backfill` keeps.
+pub fn opted_in_parquet_types(
+    opt_in_tables: TableFlags,
+    parquet_types: impl IntoIterator<Item = ParquetTypeEnum>,
+) -> Vec<ParquetTypeEnum> {
+    parquet_types
+        .into_iter()
+        .filter(|parquet_type| {
+            opt_in_tables.is_empty() || opt_in_tables.contains(parquet_type.table_flag())
+        })
+        .collect()
+}
+
 /// helper function to format the table name with the processor name.
 pub fn format_table_name(prefix: &str, table_name: &str) -> String {
     format!("{prefix}.{table_name}")
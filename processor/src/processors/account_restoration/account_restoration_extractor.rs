@@ -3,7 +3,8 @@
 
 use crate::processors::account_restoration::{
     account_restoration_models::{
-        auth_key_account_addresses::AuthKeyAccountAddress, public_key_auth_keys::PublicKeyAuthKey,
+        auth_key_account_addresses::AuthKeyAccountAddress, auth_key_schemes::AuthKeyScheme,
+        public_key_auth_keys::PublicKeyAuthKey,
     },
     account_restoration_processor_helpers::parse_account_restoration_models,
 };
@@ -22,18 +23,26 @@ where
 #[async_trait]
 impl Processable for AccountRestorationExtractor {
     type Input = Vec<Transaction>;
-    type Output = (Vec<AuthKeyAccountAddress>, Vec<PublicKeyAuthKey>);
+    type Output = (
+        Vec<AuthKeyAccountAddress>,
+        Vec<PublicKeyAuthKey>,
+        Vec<AuthKeyScheme>,
+    );
     type RunType = AsyncRunType;
 
     async fn process(
         &mut self,
         transactions: TransactionContext<Self::Input>,
     ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
-        let (auth_key_account_addresses, public_key_auth_keys) =
+        let (auth_key_account_addresses, public_key_auth_keys, auth_key_schemes) =
             parse_account_restoration_models(&transactions.data);
 
         Ok(Some(TransactionContext {
-            data: (auth_key_account_addresses, public_key_auth_keys),
+            data: (
+                auth_key_account_addresses,
+                public_key_auth_keys,
+                auth_key_schemes,
+            ),
             metadata: transactions.metadata,
         }))
     }
@@ -0,0 +1,189 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    account_restoration_utils::KeyRotationToPublicKeyEvent,
+    public_key_auth_keys::{
+        AnyPublicKeyStruct, PublicKeyAuthKeyHelper, ED25519_SCHEME, MULTI_ED25519_SCHEME,
+        MULTI_KEY_SCHEME, SINGLE_KEY_SCHEME,
+    },
+};
+use crate::schema::auth_key_schemes;
+use ahash::AHashMap;
+use cedra_indexer_processor_sdk::cedra_protos::transaction::v1::{
+    account_signature::Signature as AccountSignature, any_public_key::Type as AnyPublicKeyEnum,
+    signature::Signature as SignatureEnum, Signature,
+};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+pub type AuthKeySchemeMapping = AHashMap<String, AuthKeyScheme>;
+
+/// Enough of a public key to tell it apart, i.e. the whole key for ed25519
+pub const PUBLIC_KEY_PREVIEW_MAX_LENGTH: usize = 66;
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    FieldCount,
+    Identifiable,
+    Insertable,
+    Serialize,
+    PartialEq,
+    Eq,
+)]
+#[diesel(primary_key(auth_key))]
+#[diesel(table_name = auth_key_schemes)]
+pub struct AuthKeyScheme {
+    pub auth_key: String,
+    pub auth_scheme: String,
+    pub public_key_preview: String,
+    pub last_transaction_version: i64,
+}
+
+impl AuthKeyScheme {
+    fn new(auth_key: &str, auth_scheme: &str, public_key: &str, transaction_version: i64) -> Self {
+        Self {
+            auth_key: auth_key.to_string(),
+            auth_scheme: auth_scheme.to_string(),
+            public_key_preview: public_key
+                .chars()
+                .take(PUBLIC_KEY_PREVIEW_MAX_LENGTH)
+                .collect(),
+            last_transaction_version: transaction_version,
+        }
+    }
+
+    /// Scheme of the key the sender signed with. Account abstraction has no key, so it's skipped.
+    pub fn from_signature(s: &Signature, auth_key: &str, transaction_version: i64) -> Option<Self> {
+        let multi_key_preview = || {
+            PublicKeyAuthKeyHelper::get_multi_key_from_signature(s, transaction_version)
+                .map(|helper| helper.account_public_key)
+        };
+        let account_signature = match s.signature.as_ref()? {
+            SignatureEnum::Ed25519(sig) => {
+                return Some(Self::new(
+                    auth_key,
+                    "ed25519",
+                    &format!("0x{}", hex::encode(sig.public_key.as_slice())),
+                    transaction_version,
+                ));
+            },
+            SignatureEnum::MultiEd25519(_) => {
+                return multi_key_preview().map(|public_key| {
+                    Self::new(auth_key, "multi_ed25519", &public_key, transaction_version)
+                });
+            },
+            SignatureEnum::MultiAgent(sig) => sig.sender.as_ref()?,
+            SignatureEnum::FeePayer(sig) => sig.sender.as_ref()?,
+            SignatureEnum::SingleSender(sig) => sig.sender.as_ref()?,
+        };
+
+        match account_signature.signature.as_ref()? {
+            AccountSignature::Ed25519(sig) => Some(Self::new(
+                auth_key,
+                "ed25519",
+                &format!("0x{}", hex::encode(sig.public_key.as_slice())),
+                transaction_version,
+            )),
+            AccountSignature::MultiEd25519(_) => multi_key_preview().map(|public_key| {
+                Self::new(auth_key, "multi_ed25519", &public_key, transaction_version)
+            }),
+            AccountSignature::SingleKeySignature(sig) => {
+                let public_key = sig.public_key.as_ref()?;
+                let auth_scheme = match public_key.r#type() {
+                    AnyPublicKeyEnum::Keyless => "keyless",
+                    AnyPublicKeyEnum::FederatedKeyless => "federated_keyless",
+                    _ => "single_key",
+                };
+                Some(Self::new(
+                    auth_key,
+                    auth_scheme,
+                    &format!("0x{}", hex::encode(public_key.public_key.as_slice())),
+                    transaction_version,
+                ))
+            },
+            AccountSignature::MultiKeySignature(_) => multi_key_preview().map(|public_key| {
+                Self::new(auth_key, "multi_key", &public_key, transaction_version)
+            }),
+            AccountSignature::Abstraction(_) => None,
+        }
+    }
+
+    /// Scheme of the key an account was rotated to
+    pub fn from_key_rotation_event(
+        event: &KeyRotationToPublicKeyEvent,
+        auth_key: &str,
+        transaction_version: i64,
+    ) -> Option<Self> {
+        let public_key = format!("0x{}", hex::encode(event.public_key.as_slice()));
+        let auth_scheme = match event.public_key_scheme {
+            ED25519_SCHEME => "ed25519",
+            MULTI_ED25519_SCHEME => "multi_ed25519",
+            SINGLE_KEY_SCHEME => {
+                let any_public_key: AnyPublicKeyStruct = bcs::from_bytes(&event.public_key)
+                    .map_err(|e| {
+                        tracing::warn!(
+                            transaction_version,
+                            error = ?e,
+                            "Failed to deserialize rotated single key"
+                        );
+                    })
+                    .ok()?;
+                let auth_scheme = match any_public_key {
+                    AnyPublicKeyStruct::Keyless { .. } => "keyless",
+                    AnyPublicKeyStruct::FederatedKeyless { .. } => "federated_keyless",
+                    _ => "single_key",
+                };
+                return Some(Self::new(
+                    auth_key,
+                    auth_scheme,
+                    &any_public_key.to_string_without_variant(),
+                    transaction_version,
+                ));
+            },
+            MULTI_KEY_SCHEME => "multi_key",
+            _ => return None,
+        };
+        Some(Self::new(
+            auth_key,
+            auth_scheme,
+            &public_key,
+            transaction_version,
+        ))
+    }
+}
+
+impl Ord for AuthKeyScheme {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.auth_key.cmp(&other.auth_key)
+    }
+}
+
+impl PartialOrd for AuthKeyScheme {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_key_rotation_event_truncates_preview() {
+        let event = KeyRotationToPublicKeyEvent {
+            new_auth_key: vec![],
+            old_auth_key: vec![],
+            // Two ed25519 keys and a threshold
+            public_key: [vec![0xAB; 32], vec![0xCD; 32], vec![1]].concat(),
+            public_key_scheme: MULTI_ED25519_SCHEME,
+            verified_public_key_bit_map: vec![0xC0, 0, 0, 0],
+        };
+        let scheme = AuthKeyScheme::from_key_rotation_event(&event, "0x1", 1).unwrap();
+        assert_eq!(scheme.auth_scheme, "multi_ed25519");
+        assert_eq!(scheme.public_key_preview, format!("0x{}", "ab".repeat(32)));
+    }
+}
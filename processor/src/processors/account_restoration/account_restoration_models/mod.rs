@@ -3,4 +3,5 @@
 
 pub mod account_restoration_utils;
 pub mod auth_key_account_addresses;
+pub mod auth_key_schemes;
 pub mod public_key_auth_keys;
@@ -24,10 +24,10 @@ use serde::{Deserialize, Serialize};
 
 pub type PublicKeyAuthKeyMapping = AHashMap<(String, String), PublicKeyAuthKey>;
 
-const ED25519_SCHEME: u8 = 0;
-const MULTI_ED25519_SCHEME: u8 = 1;
-const SINGLE_KEY_SCHEME: u8 = 2;
-const MULTI_KEY_SCHEME: u8 = 3;
+pub const ED25519_SCHEME: u8 = 0;
+pub const MULTI_ED25519_SCHEME: u8 = 1;
+pub const SINGLE_KEY_SCHEME: u8 = 2;
+pub const MULTI_KEY_SCHEME: u8 = 3;
 const MAX_ACCOUNT_PUBLIC_KEY_LENGTH: usize = 3000;
 
 #[derive(
@@ -8,7 +8,9 @@ use crate::{
     db::resources::V2TokenResource,
     processors::account_restoration::account_restoration_models::{
         account_restoration_utils::KeyRotationToPublicKeyEvent,
-        auth_key_account_addresses::AuthKeyAccountAddress, public_key_auth_keys::PublicKeyAuthKey,
+        auth_key_account_addresses::AuthKeyAccountAddress,
+        auth_key_schemes::{AuthKeyScheme, AuthKeySchemeMapping},
+        public_key_auth_keys::PublicKeyAuthKey,
     },
 };
 use ahash::AHashMap;
@@ -52,15 +54,21 @@ impl TryFrom<&WriteResource> for Account {
 
 pub fn parse_account_restoration_models(
     transactions: &Vec<Transaction>,
-) -> (Vec<AuthKeyAccountAddress>, Vec<PublicKeyAuthKey>) {
+) -> (
+    Vec<AuthKeyAccountAddress>,
+    Vec<PublicKeyAuthKey>,
+    Vec<AuthKeyScheme>,
+) {
     let mut all_auth_key_account_addresses = AHashMap::new();
     let mut all_public_key_auth_keys: PublicKeyAuthKeyMapping = AHashMap::new();
+    let mut all_auth_key_schemes: AuthKeySchemeMapping = AHashMap::new();
 
     let data: Vec<_> = transactions
         .par_iter()
         .map(|txn| {
             let mut auth_key_account_addresses = AHashMap::new();
             let mut public_key_auth_keys: PublicKeyAuthKeyMapping = AHashMap::new();
+            let mut auth_key_schemes: AuthKeySchemeMapping = AHashMap::new();
 
             let txn_version = txn.version as i64;
             let (entry_function_id_str, signature, sender) = match &txn.txn_data {
@@ -80,7 +88,11 @@ pub fn parse_account_restoration_models(
 
             let transaction_info = txn.info.as_ref().expect("Transaction info doesn't exist!");
             if !transaction_info.success {
-                return (auth_key_account_addresses, public_key_auth_keys);
+                return (
+                    auth_key_account_addresses,
+                    public_key_auth_keys,
+                    auth_key_schemes,
+                );
             }
 
             // At the end of this loop we'll get all account addresses and their corresponding auth keys
@@ -133,6 +145,25 @@ pub fn parse_account_restoration_models(
                             || multi_key_helper.is_some()
                             || key_rotation_event.is_some()
                         {
+                            let auth_key_scheme = match &key_rotation_event {
+                                Some(event) => AuthKeyScheme::from_key_rotation_event(
+                                    event,
+                                    &auth_key,
+                                    txn_version,
+                                ),
+                                // Signed with the key the account was rotated away from
+                                None if ROTATE_AUTH_KEY_ENTRY_FUNCTIONS
+                                    .contains(&entry_function_id_str.as_deref().unwrap_or("")) =>
+                                {
+                                    None
+                                },
+                                None => signature.as_ref().and_then(|sig| {
+                                    AuthKeyScheme::from_signature(sig, &auth_key, txn_version)
+                                }),
+                            };
+                            if let Some(auth_key_scheme) = auth_key_scheme {
+                                auth_key_schemes.insert(auth_key.clone(), auth_key_scheme);
+                            }
                             auth_key_account_addresses.insert(
                                 account_address.clone(),
                                 AuthKeyAccountAddress {
@@ -169,12 +200,17 @@ pub fn parse_account_restoration_models(
                 }
             }
 
-            (auth_key_account_addresses, public_key_auth_keys)
+            (
+                auth_key_account_addresses,
+                public_key_auth_keys,
+                auth_key_schemes,
+            )
         })
         .collect();
-    for (auth_key_account_addresses, public_key_auth_keys) in data {
+    for (auth_key_account_addresses, public_key_auth_keys, auth_key_schemes) in data {
         all_auth_key_account_addresses.extend(auth_key_account_addresses);
         all_public_key_auth_keys.extend(public_key_auth_keys);
+        all_auth_key_schemes.extend(auth_key_schemes);
     }
 
     let mut all_auth_key_account_addresses = all_auth_key_account_addresses
@@ -183,9 +219,17 @@ pub fn parse_account_restoration_models(
     let mut all_public_key_auth_keys = all_public_key_auth_keys
         .into_values()
         .collect::<Vec<PublicKeyAuthKey>>();
+    let mut all_auth_key_schemes = all_auth_key_schemes
+        .into_values()
+        .collect::<Vec<AuthKeyScheme>>();
 
     all_auth_key_account_addresses.sort();
     all_public_key_auth_keys.sort();
+    all_auth_key_schemes.sort();
 
-    (all_auth_key_account_addresses, all_public_key_auth_keys)
+    (
+        all_auth_key_account_addresses,
+        all_public_key_auth_keys,
+        all_auth_key_schemes,
+    )
 }
@@ -2,15 +2,21 @@ use crate::{
     config::processor_config::DefaultProcessorConfig,
     filter_datasets,
     processors::account_restoration::account_restoration_models::{
-        auth_key_account_addresses::AuthKeyAccountAddress, public_key_auth_keys::PublicKeyAuthKey,
+        auth_key_account_addresses::AuthKeyAccountAddress, auth_key_schemes::AuthKeyScheme,
+        public_key_auth_keys::PublicKeyAuthKey,
     },
     schema,
-    utils::table_flags::{filter_data, TableFlags},
+    utils::{
+        table_flags::{filter_data, TableFlags},
+        table_names::TableNames,
+        table_stats::execute_in_chunks_with_stats,
+    },
+    version_guard,
 };
 use ahash::AHashMap;
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    postgres::utils::database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
     traits::{AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
@@ -28,6 +34,7 @@ where
     Self: Sized + Send + 'static,
 {
     conn_pool: ArcDbPool,
+    table_names: TableNames,
     processor_config: DefaultProcessorConfig,
     tables_to_write: TableFlags,
 }
@@ -35,11 +42,13 @@ where
 impl AccountRestorationStorer {
     pub fn new(
         conn_pool: ArcDbPool,
+        table_names: TableNames,
         processor_config: DefaultProcessorConfig,
         tables_to_write: TableFlags,
     ) -> Self {
         Self {
             conn_pool,
+            table_names,
             processor_config,
             tables_to_write,
         }
@@ -48,7 +57,11 @@ impl AccountRestorationStorer {
 
 #[async_trait]
 impl Processable for AccountRestorationStorer {
-    type Input = (Vec<AuthKeyAccountAddress>, Vec<PublicKeyAuthKey>);
+    type Input = (
+        Vec<AuthKeyAccountAddress>,
+        Vec<PublicKeyAuthKey>,
+        Vec<AuthKeyScheme>,
+    );
     type Output = ();
     type RunType = AsyncRunType;
 
@@ -56,36 +69,57 @@ impl Processable for AccountRestorationStorer {
         &mut self,
         input: TransactionContext<Self::Input>,
     ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
-        let (auth_key_address, public_key_auth_key) = input.data;
+        let (auth_key_address, public_key_auth_key, auth_key_scheme) = input.data;
 
         let per_table_chunk_sizes: AHashMap<String, usize> =
             self.processor_config.per_table_chunk_sizes.clone();
 
-        let (auth_key_address, public_key_auth_key) = filter_datasets!(self, {
+        let (auth_key_address, public_key_auth_key, auth_key_scheme) = filter_datasets!(self, {
             auth_key_address => TableFlags::AUTH_KEY_ACCOUNT_ADDRESSES,
             public_key_auth_key => TableFlags::PUBLIC_KEY_AUTH_KEYS,
+            auth_key_scheme => TableFlags::AUTH_KEY_SCHEMES,
         });
 
-        let auth_key_address_res = execute_in_chunks(
+        let auth_key_address_res = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_auth_key_account_addresses_query,
             &auth_key_address,
+            "auth_key_account_address",
             get_config_table_chunk_size::<AuthKeyAccountAddress>(
                 "auth_key_account_address",
                 &per_table_chunk_sizes,
             ),
         );
-        let public_key_auth_key_res = execute_in_chunks(
+        let public_key_auth_key_res = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_public_key_auth_keys_query,
             &public_key_auth_key,
+            "public_key_auth_keys",
             get_config_table_chunk_size::<PublicKeyAuthKey>(
                 "public_key_auth_keys",
                 &per_table_chunk_sizes,
             ),
         );
 
-        futures::try_join!(auth_key_address_res, public_key_auth_key_res)?;
+        let auth_key_scheme_res = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_auth_key_schemes_query,
+            &auth_key_scheme,
+            "auth_key_schemes",
+            get_config_table_chunk_size::<AuthKeyScheme>(
+                "auth_key_schemes",
+                &per_table_chunk_sizes,
+            ),
+        );
+
+        futures::try_join!(
+            auth_key_address_res,
+            public_key_auth_key_res,
+            auth_key_scheme_res
+        )?;
 
         Ok(Some(TransactionContext {
             data: (),
@@ -116,7 +150,7 @@ pub fn insert_auth_key_account_addresses_query(
             last_transaction_version.eq(excluded(last_transaction_version)),
             is_auth_key_used.eq(excluded(is_auth_key_used)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
 }
 
 pub fn insert_public_key_auth_keys_query(
@@ -138,5 +172,22 @@ pub fn insert_public_key_auth_keys_query(
             )
             .otherwise(excluded(is_public_key_used))),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
+}
+
+pub fn insert_auth_key_schemes_query(
+    items_to_insert: Vec<AuthKeyScheme>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::auth_key_schemes::dsl::*;
+
+    diesel::insert_into(schema::auth_key_schemes::table)
+        .values(items_to_insert)
+        .on_conflict(auth_key)
+        .do_update()
+        .set((
+            auth_scheme.eq(excluded(auth_scheme)),
+            public_key_preview.eq(excluded(public_key_preview)),
+            last_transaction_version.eq(excluded(last_transaction_version)),
+        ))
+        .filter(version_guard!(last_transaction_version))
 }
@@ -1,5 +1,8 @@
-use crate::processors::account_transactions::{
-    account_transactions_model::PostgresAccountTransaction, parse_account_transactions,
+use crate::{
+    processors::account_transactions::{
+        account_transactions_model::PostgresAccountTransaction, parse_account_transactions,
+    },
+    utils::address_labels::{counterparty_label, AddressLabels},
 };
 use cedra_indexer_processor_sdk::{
     cedra_protos::transaction::v1::Transaction,
@@ -11,7 +14,10 @@ use async_trait::async_trait;
 
 pub struct AccountTransactionsExtractor
 where
-    Self: Sized + Send + 'static, {}
+    Self: Sized + Send + 'static,
+{
+    pub address_labels: Option<AddressLabels>,
+}
 
 #[async_trait]
 impl Processable for AccountTransactionsExtractor {
@@ -23,10 +29,26 @@ impl Processable for AccountTransactionsExtractor {
         &mut self,
         input: TransactionContext<Vec<Transaction>>,
     ) -> Result<Option<TransactionContext<Vec<PostgresAccountTransaction>>>, ProcessorError> {
-        let acc_txns: Vec<PostgresAccountTransaction> = parse_account_transactions(input.data)
+        let mut acc_txns: Vec<PostgresAccountTransaction> = parse_account_transactions(input.data)
             .into_iter()
             .map(PostgresAccountTransaction::from)
             .collect();
+        if let Some(address_labels) = &self.address_labels {
+            let transaction_labels =
+                address_labels.transaction_labels(acc_txns.iter().map(|acc_txn| {
+                    (
+                        acc_txn.transaction_version,
+                        acc_txn.account_address.as_str(),
+                    )
+                }));
+            for acc_txn in &mut acc_txns {
+                acc_txn.counterparty_label = counterparty_label(
+                    &transaction_labels,
+                    acc_txn.transaction_version,
+                    &acc_txn.account_address,
+                );
+            }
+        }
         Ok(Some(TransactionContext {
             data: acc_txns,
             metadata: input.metadata,
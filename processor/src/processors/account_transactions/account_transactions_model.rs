@@ -15,11 +15,15 @@ use crate::{
     schema::account_transactions,
     utils::counters::PROCESSOR_UNKNOWN_TYPE_COUNT,
 };
-use ahash::AHashSet;
+use ahash::{AHashMap, AHashSet};
 use allocative_derive::Allocative;
 use cedra_indexer_processor_sdk::{
     cedra_indexer_transaction_stream::utils::time::parse_timestamp,
-    cedra_protos::transaction::v1::{transaction::TxnData, write_set_change::Change, Transaction},
+    cedra_protos::transaction::v1::{
+        transaction::{TransactionType, TxnData},
+        write_set_change::Change,
+        Event, Transaction,
+    },
     utils::convert::standardize_address,
 };
 use field_count::FieldCount;
@@ -33,6 +37,12 @@ pub struct AccountTransaction {
     pub transaction_version: i64,
     pub account_address: String,
     pub block_timestamp: chrono::NaiveDateTime,
+    pub is_sender: bool,
+    pub is_signer: bool,
+    pub txn_success: bool,
+    pub txn_type: String,
+    /// Events of the transaction emitted under the account's event keys
+    pub event_count: i64,
 }
 
 impl AccountTransaction {
@@ -63,30 +73,10 @@ impl AccountTransaction {
             .as_ref()
             .unwrap_or_else(|| panic!("Transaction info doesn't exist for version {txn_version}"));
         let wscs = &transaction_info.changes;
-        let (events, signatures) = match txn_data {
-            TxnData::User(inner) => (
-                &inner.events,
-                UserTransaction::get_signatures(
-                    inner.request.as_ref().unwrap_or_else(|| {
-                        panic!("User request doesn't exist for version {txn_version}")
-                    }),
-                    txn_version,
-                    transaction.block_height as i64,
-                    parse_timestamp(transaction.timestamp.as_ref().unwrap(), txn_version)
-                        .naive_utc(),
-                ),
-            ),
-            TxnData::Genesis(inner) => (&inner.events, vec![]),
-            TxnData::BlockMetadata(inner) => (&inner.events, vec![]),
-            TxnData::Validator(inner) => (&inner.events, vec![]),
-            _ => {
-                return AHashSet::new();
-            },
+        let Some(events) = Self::get_events(txn_data) else {
+            return AHashSet::new();
         };
-        let mut accounts = AHashSet::new();
-        for sig in signatures {
-            accounts.insert(sig.signer);
-        }
+        let (_, mut accounts) = Self::get_sender_and_signers(transaction);
         for event in events {
             // Record event account address. We don't really have to worry about objects here
             // because it'll be taken care of in the resource section.
@@ -117,6 +107,62 @@ impl AccountTransaction {
         }
         accounts
     }
+
+    /// Events of the transaction types accounts are recorded for
+    fn get_events(txn_data: &TxnData) -> Option<&Vec<Event>> {
+        match txn_data {
+            TxnData::User(inner) => Some(&inner.events),
+            TxnData::Genesis(inner) => Some(&inner.events),
+            TxnData::BlockMetadata(inner) => Some(&inner.events),
+            TxnData::Validator(inner) => Some(&inner.events),
+            _ => None,
+        }
+    }
+
+    /// Number of events per account address of their event key
+    pub fn get_event_counts(transaction: &Transaction) -> AHashMap<String, i64> {
+        let mut event_counts = AHashMap::new();
+        let Some(events) = transaction.txn_data.as_ref().and_then(Self::get_events) else {
+            return event_counts;
+        };
+        for event in events {
+            let account_address =
+                standardize_address(event.key.as_ref().unwrap().account_address.as_str());
+            *event_counts.entry(account_address).or_insert(0) += 1;
+        }
+        event_counts
+    }
+
+    /// Sender and signers of a user transaction, signers including the sender, secondary signers
+    /// and the fee payer. Other transactions have neither.
+    pub fn get_sender_and_signers(transaction: &Transaction) -> (Option<String>, AHashSet<String>) {
+        let txn_version = transaction.version as i64;
+        let Some(TxnData::User(inner)) = transaction.txn_data.as_ref() else {
+            return (None, AHashSet::new());
+        };
+        let request = inner
+            .request
+            .as_ref()
+            .unwrap_or_else(|| panic!("User request doesn't exist for version {txn_version}"));
+        let signers = UserTransaction::get_signatures(
+            request,
+            txn_version,
+            transaction.block_height as i64,
+            parse_timestamp(transaction.timestamp.as_ref().unwrap(), txn_version).naive_utc(),
+        )
+        .into_iter()
+        .map(|sig| sig.signer)
+        .collect();
+        (Some(standardize_address(&request.sender)), signers)
+    }
+
+    /// Name of the transaction type, as recorded in `transactions.type`
+    pub fn get_txn_type(transaction: &Transaction) -> String {
+        TransactionType::try_from(transaction.r#type)
+            .unwrap_or(TransactionType::Unspecified)
+            .as_str_name()
+            .to_string()
+    }
 }
 
 // Parquet Model
@@ -128,6 +174,11 @@ pub struct ParquetAccountTransaction {
     pub account_address: String,
     #[allocative(skip)]
     pub block_timestamp: chrono::NaiveDateTime,
+    pub is_sender: bool,
+    pub is_signer: bool,
+    pub txn_success: bool,
+    pub txn_type: String,
+    pub event_count: i64,
 }
 
 impl NamedTable for ParquetAccountTransaction {
@@ -146,6 +197,11 @@ impl From<AccountTransaction> for ParquetAccountTransaction {
             txn_version: acc_txn.transaction_version,
             account_address: acc_txn.account_address,
             block_timestamp: acc_txn.block_timestamp,
+            is_sender: acc_txn.is_sender,
+            is_signer: acc_txn.is_signer,
+            txn_success: acc_txn.txn_success,
+            txn_type: acc_txn.txn_type,
+            event_count: acc_txn.event_count,
         }
     }
 }
@@ -157,6 +213,11 @@ impl From<AccountTransaction> for ParquetAccountTransaction {
 pub struct PostgresAccountTransaction {
     pub transaction_version: i64,
     pub account_address: String,
+    pub is_sender: bool,
+    pub is_signer: bool,
+    pub txn_success: bool,
+    pub txn_type: String,
+    pub counterparty_label: Option<String>,
 }
 
 impl From<AccountTransaction> for PostgresAccountTransaction {
@@ -164,6 +225,11 @@ impl From<AccountTransaction> for PostgresAccountTransaction {
         Self {
             transaction_version: acc_txn.transaction_version,
             account_address: acc_txn.account_address,
+            is_sender: acc_txn.is_sender,
+            is_signer: acc_txn.is_signer,
+            txn_success: acc_txn.txn_success,
+            txn_type: acc_txn.txn_type,
+            counterparty_label: None,
         }
     }
 }
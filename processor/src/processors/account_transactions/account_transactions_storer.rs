@@ -5,12 +5,16 @@ use crate::{
     config::processor_config::DefaultProcessorConfig,
     processors::account_transactions::account_transactions_model::PostgresAccountTransaction,
     schema,
-    utils::table_flags::{filter_data, TableFlags},
+    utils::{
+        table_flags::{filter_data, TableFlags},
+        table_names::TableNames,
+        table_stats::execute_in_chunks_with_stats,
+    },
 };
 use ahash::AHashMap;
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    postgres::utils::database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
@@ -24,6 +28,7 @@ where
     Self: Sized + Send + 'static,
 {
     conn_pool: ArcDbPool,
+    table_names: TableNames,
     processor_config: DefaultProcessorConfig,
     tables_to_write: TableFlags,
 }
@@ -31,11 +36,13 @@ where
 impl AccountTransactionsStorer {
     pub fn new(
         conn_pool: ArcDbPool,
+        table_names: TableNames,
         processor_config: DefaultProcessorConfig,
         tables_to_write: TableFlags,
     ) -> Self {
         Self {
             conn_pool,
+            table_names,
             processor_config,
             tables_to_write,
         }
@@ -61,10 +68,12 @@ impl Processable for AccountTransactionsStorer {
             input.data,
         );
 
-        let res = execute_in_chunks(
+        let res = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_account_transactions_query,
             &account_transactions,
+            "account_transactions",
             get_config_table_chunk_size::<PostgresAccountTransaction>(
                 "account_transactions",
                 &per_table_chunk_sizes,
@@ -20,12 +20,21 @@ pub fn parse_account_transactions(txns: Vec<Transaction>) -> Vec<AccountTransact
             let block_timestamp =
                 parse_timestamp(txn.timestamp.as_ref().unwrap(), transaction_version).naive_utc();
             let accounts = AccountTransaction::get_accounts(&txn);
+            let (sender, signers) = AccountTransaction::get_sender_and_signers(&txn);
+            let txn_success = txn.info.as_ref().is_some_and(|info| info.success);
+            let txn_type = AccountTransaction::get_txn_type(&txn);
+            let event_counts = AccountTransaction::get_event_counts(&txn);
             accounts
                 .into_iter()
                 .map(|account_address| AccountTransaction {
                     transaction_version,
-                    account_address,
                     block_timestamp,
+                    is_sender: sender.as_ref() == Some(&account_address),
+                    is_signer: signers.contains(&account_address),
+                    txn_success,
+                    txn_type: txn_type.clone(),
+                    event_count: event_counts.get(&account_address).copied().unwrap_or(0),
+                    account_address,
                 })
                 .collect()
         })
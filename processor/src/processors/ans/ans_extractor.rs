@@ -11,6 +11,7 @@ use crate::{
             ans_primary_name_v2::{
                 AnsPrimaryNameV2, CurrentAnsPrimaryNameV2, PostgresCurrentAnsPrimaryNameV2,
             },
+            ans_revenue_events::PostgresAnsRevenueEvent,
             ans_utils::{RenewNameEvent, SubdomainExtV2},
         },
     },
@@ -59,6 +60,7 @@ impl Processable for AnsExtractor {
     type Output = (
         Vec<PostgresCurrentAnsLookupV2>,
         Vec<PostgresCurrentAnsPrimaryNameV2>,
+        Vec<PostgresAnsRevenueEvent>,
     );
     type RunType = AsyncRunType;
 
@@ -70,6 +72,7 @@ impl Processable for AnsExtractor {
             TransactionContext<(
                 Vec<PostgresCurrentAnsLookupV2>,
                 Vec<PostgresCurrentAnsPrimaryNameV2>,
+                Vec<PostgresAnsRevenueEvent>,
             )>,
         >,
         ProcessorError,
@@ -98,10 +101,16 @@ impl Processable for AnsExtractor {
                 .map(PostgresCurrentAnsPrimaryNameV2::from)
                 .collect();
 
+        let ans_revenue_events = PostgresAnsRevenueEvent::from_transactions(
+            &input.data,
+            &self.config.ans_v2_contract_address,
+        );
+
         Ok(Some(TransactionContext {
             data: (
                 postgres_current_ans_lookups_v2,
                 postgres_current_ans_primary_names_v2,
+                ans_revenue_events,
             ),
             metadata: input.metadata,
         }))
@@ -8,15 +8,25 @@ use crate::{
         models::{
             ans_lookup_v2::PostgresCurrentAnsLookupV2,
             ans_primary_name_v2::PostgresCurrentAnsPrimaryNameV2,
+            ans_revenue_events::{AnsNamePK, PostgresAnsRevenueEvent, RENEWAL_REVENUE},
+            ans_utils::SUBDOMAIN_POLICY_LOOKUP_DOMAIN_EXPIRATION,
         },
+        token_v2::token_v2_models::v2_token_utils::TokenStandard,
     },
     schema,
-    utils::table_flags::{filter_data, TableFlags},
+    utils::{
+        dedup::dedup_by_pk_keep_latest,
+        table_flags::{filter_data, should_write, TableFlags},
+        table_names::TableNames,
+        table_stats::execute_in_chunks_with_stats,
+        write_sink::write_sinks,
+    },
+    version_guard,
 };
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    postgres::utils::database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
@@ -25,15 +35,19 @@ use async_trait::async_trait;
 use diesel::{
     pg::{upsert::excluded, Pg},
     query_builder::QueryFragment,
-    query_dsl::methods::FilterDsl,
+    query_dsl::methods::{FilterDsl, SelectDsl},
+    sql_query,
+    sql_types::{Array, Text},
     ExpressionMethods,
 };
+use diesel_async::RunQueryDsl;
 
 pub struct AnsStorer
 where
     Self: Sized + Send + 'static,
 {
     conn_pool: ArcDbPool,
+    table_names: TableNames,
     processor_config: AnsProcessorConfig,
     tables_to_write: TableFlags,
 }
@@ -41,11 +55,13 @@ where
 impl AnsStorer {
     pub fn new(
         conn_pool: ArcDbPool,
+        table_names: TableNames,
         processor_config: AnsProcessorConfig,
         tables_to_write: TableFlags,
     ) -> Self {
         Self {
             conn_pool,
+            table_names,
             processor_config,
             tables_to_write,
         }
@@ -57,6 +73,7 @@ impl Processable for AnsStorer {
     type Input = (
         Vec<PostgresCurrentAnsLookupV2>,
         Vec<PostgresCurrentAnsPrimaryNameV2>,
+        Vec<PostgresAnsRevenueEvent>,
     );
     type Output = ();
     type RunType = AsyncRunType;
@@ -66,9 +83,11 @@ impl Processable for AnsStorer {
         input: TransactionContext<(
             Vec<PostgresCurrentAnsLookupV2>,
             Vec<PostgresCurrentAnsPrimaryNameV2>,
+            Vec<PostgresAnsRevenueEvent>,
         )>,
     ) -> Result<Option<TransactionContext<()>>, ProcessorError> {
-        let (current_ans_lookups_v2, current_ans_primary_names_v2) = input.data;
+        let (current_ans_lookups_v2, current_ans_primary_names_v2, mut ans_revenue_events) =
+            input.data;
 
         let per_table_chunk_sizes: AHashMap<String, usize> =
             self.processor_config.default.per_table_chunk_sizes.clone();
@@ -78,26 +97,76 @@ impl Processable for AnsStorer {
             current_ans_primary_names_v2 => TableFlags::CURRENT_ANS_PRIMARY_NAME_V2,
         });
 
-        let cal_v2 = execute_in_chunks(
+        let current_ans_lookups_v2 = dedup_by_pk_keep_latest(current_ans_lookups_v2);
+        let current_ans_primary_names_v2 = dedup_by_pk_keep_latest(current_ans_primary_names_v2);
+
+        // Read from the stored names, so this has to run before they're overwritten
+        if should_write(&self.tables_to_write, TableFlags::ANS_REVENUE_EVENTS) {
+            self.fill_renewal_durations(&mut ans_revenue_events)
+                .await
+                .map_err(|e| ProcessorError::DBStoreError {
+                    message: format!(
+                        "Failed to get renewal durations for versions {} to {}: {:?}",
+                        input.metadata.start_version, input.metadata.end_version, e,
+                    ),
+                    query: None,
+                })?;
+        } else {
+            ans_revenue_events.clear();
+        }
+
+        let cal_v2 = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_current_ans_lookups_v2_query,
             &current_ans_lookups_v2,
+            "current_ans_lookup_v2",
             get_config_table_chunk_size::<PostgresCurrentAnsLookupV2>(
                 "current_ans_lookup_v2",
                 &per_table_chunk_sizes,
             ),
         );
-        let capn_v2 = execute_in_chunks(
+        let capn_v2 = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_current_ans_primary_names_v2_query,
             &current_ans_primary_names_v2,
+            "current_ans_primary_name_v2",
             get_config_table_chunk_size::<PostgresCurrentAnsPrimaryNameV2>(
                 "current_ans_primary_name_v2",
                 &per_table_chunk_sizes,
             ),
         );
 
-        futures::try_join!(cal_v2, capn_v2)?;
+        let are = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_ans_revenue_events_query,
+            &ans_revenue_events,
+            "ans_revenue_events",
+            get_config_table_chunk_size::<PostgresAnsRevenueEvent>(
+                "ans_revenue_events",
+                &per_table_chunk_sizes,
+            ),
+        );
+
+        futures::try_join!(cal_v2, capn_v2, are)?;
+
+        // Subdomains that follow their domain's expiration are only resolved once both are
+        // written, including subdomains of renewed domains that weren't part of this batch
+        let domains: AHashSet<String> = current_ans_lookups_v2
+            .iter()
+            .map(|lookup| lookup.domain.clone())
+            .collect();
+        self.update_effective_expirations(domains.into_iter().collect())
+            .await
+            .map_err(|e| ProcessorError::DBStoreError {
+                message: format!(
+                    "Failed to update effective expirations for versions {} to {}: {:?}",
+                    input.metadata.start_version, input.metadata.end_version, e,
+                ),
+                query: None,
+            })?;
 
         Ok(Some(TransactionContext {
             data: (),
@@ -106,6 +175,77 @@ impl Processable for AnsStorer {
     }
 }
 
+impl AnsStorer {
+    /// Renewals extend the name's expiration as stored before the batch, or as left by an earlier
+    /// registration or renewal in the batch
+    async fn fill_renewal_durations(
+        &self,
+        ans_revenue_events: &mut [PostgresAnsRevenueEvent],
+    ) -> Result<()> {
+        let renewed_names: AHashSet<AnsNamePK> = ans_revenue_events
+            .iter()
+            .filter(|revenue_event| revenue_event.revenue_type == RENEWAL_REVENUE)
+            .map(PostgresAnsRevenueEvent::name_pk)
+            .collect();
+        if renewed_names.is_empty() {
+            return Ok(());
+        }
+        let domains: Vec<String> = renewed_names
+            .iter()
+            .map(|(domain, _)| domain.clone())
+            .collect::<AHashSet<_>>()
+            .into_iter()
+            .collect();
+        let mut conn = self.conn_pool.get().await?;
+        let stored_names: Vec<(String, String, chrono::NaiveDateTime)> =
+            schema::current_ans_lookup_v2::table
+                .filter(schema::current_ans_lookup_v2::domain.eq_any(domains))
+                .filter(
+                    schema::current_ans_lookup_v2::token_standard.eq(TokenStandard::V2.to_string()),
+                )
+                .select((
+                    schema::current_ans_lookup_v2::domain,
+                    schema::current_ans_lookup_v2::subdomain,
+                    schema::current_ans_lookup_v2::expiration_timestamp,
+                ))
+                .load(&mut conn)
+                .await?;
+        let mut previous_expirations = stored_names
+            .into_iter()
+            .map(|(domain, subdomain, expiration)| ((domain, subdomain), expiration))
+            .filter(|(name_pk, _)| renewed_names.contains(name_pk))
+            .collect();
+        PostgresAnsRevenueEvent::fill_renewal_durations(
+            ans_revenue_events,
+            &mut previous_expirations,
+        );
+        Ok(())
+    }
+
+    async fn update_effective_expirations(&self, domains: Vec<String>) -> Result<()> {
+        if domains.is_empty() || write_sinks().replaces_database() {
+            return Ok(());
+        }
+        let mut conn = self.conn_pool.get().await?;
+        sql_query(format!(
+            "UPDATE current_ans_lookup_v2 AS sub
+             SET effective_expiration_timestamp = dom.expiration_timestamp
+             FROM current_ans_lookup_v2 AS dom
+             WHERE sub.domain = ANY($1)
+               AND sub.subdomain != ''
+               AND sub.subdomain_expiration_policy = {SUBDOMAIN_POLICY_LOOKUP_DOMAIN_EXPIRATION}
+               AND dom.domain = sub.domain
+               AND dom.subdomain = ''
+               AND dom.token_standard = sub.token_standard
+               AND sub.effective_expiration_timestamp IS DISTINCT FROM dom.expiration_timestamp"
+        ))
+        .bind::<Array<Text>, _>(domains)
+        .execute(&mut conn)
+        .await?;
+        Ok(())
+    }
+}
+
 impl AsyncStep for AnsStorer {}
 
 impl NamedStep for AnsStorer {
@@ -131,8 +271,9 @@ pub fn insert_current_ans_lookups_v2_query(
             is_deleted.eq(excluded(is_deleted)),
             inserted_at.eq(excluded(inserted_at)),
             subdomain_expiration_policy.eq(excluded(subdomain_expiration_policy)),
+            effective_expiration_timestamp.eq(excluded(effective_expiration_timestamp)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
 }
 
 pub fn insert_current_ans_primary_names_v2_query(
@@ -152,5 +293,16 @@ pub fn insert_current_ans_primary_names_v2_query(
             last_transaction_version.eq(excluded(last_transaction_version)),
             inserted_at.eq(excluded(inserted_at)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
+}
+
+pub fn insert_ans_revenue_events_query(
+    item_to_insert: Vec<PostgresAnsRevenueEvent>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::ans_revenue_events::dsl::*;
+
+    diesel::insert_into(schema::ans_revenue_events::table)
+        .values(item_to_insert)
+        .on_conflict((transaction_version, event_index))
+        .do_nothing()
 }
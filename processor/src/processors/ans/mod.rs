@@ -1,5 +1,4 @@
 pub mod ans_extractor;
 pub mod ans_processor;
-pub mod ans_processor_queries;
 pub mod ans_storer;
 pub mod models;
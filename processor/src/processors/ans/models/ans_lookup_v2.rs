@@ -10,11 +10,15 @@ use crate::{
     processors::{
         ans::models::{
             ans_lookup::{AnsLookup, CurrentAnsLookup},
-            ans_utils::{get_token_name, NameRecordV2, SubdomainExtV2},
+            ans_utils::{
+                get_token_name, NameRecordV2, SubdomainExtV2,
+                SUBDOMAIN_POLICY_LOOKUP_DOMAIN_EXPIRATION,
+            },
         },
         token_v2::token_v2_models::v2_token_utils::TokenStandard,
     },
     schema::{ans_lookup_v2, current_ans_lookup_v2},
+    utils::dedup::CurrentTableRow,
 };
 use ahash::AHashMap;
 use allocative::Allocative;
@@ -58,6 +62,7 @@ pub struct CurrentAnsLookupV2 {
     pub token_name: String,
     pub is_deleted: bool,
     pub subdomain_expiration_policy: Option<i64>,
+    pub effective_expiration_timestamp: Option<chrono::NaiveDateTime>,
 }
 
 impl Ord for CurrentAnsLookupV2 {
@@ -218,6 +223,9 @@ pub struct PostgresCurrentAnsLookupV2 {
     pub token_name: String,
     pub is_deleted: bool,
     pub subdomain_expiration_policy: Option<i64>,
+    /// Left unset for subdomains that follow their domain's expiration, the storer fills it in
+    /// from the domain once both are written
+    pub effective_expiration_timestamp: Option<chrono::NaiveDateTime>,
 }
 
 impl From<CurrentAnsLookupV2> for PostgresCurrentAnsLookupV2 {
@@ -232,10 +240,27 @@ impl From<CurrentAnsLookupV2> for PostgresCurrentAnsLookupV2 {
             token_name: raw_item.token_name,
             is_deleted: raw_item.is_deleted,
             subdomain_expiration_policy: raw_item.subdomain_expiration_policy,
+            effective_expiration_timestamp: raw_item.effective_expiration_timestamp,
         }
     }
 }
 
+impl CurrentTableRow for PostgresCurrentAnsLookupV2 {
+    type PrimaryKey = (String, String, String);
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        (
+            self.domain.clone(),
+            self.subdomain.clone(),
+            self.token_standard.clone(),
+        )
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
+
 impl CurrentAnsLookupV2 {
     pub fn pk(&self) -> CurrentAnsLookupV2PK {
         (
@@ -245,6 +270,22 @@ impl CurrentAnsLookupV2 {
         )
     }
 
+    /// The name's own expiration, unless it's a subdomain that expires with its domain, whose
+    /// expiration isn't known here
+    pub fn get_effective_expiration_timestamp(
+        subdomain: &str,
+        subdomain_expiration_policy: Option<i64>,
+        expiration_timestamp: chrono::NaiveDateTime,
+    ) -> Option<chrono::NaiveDateTime> {
+        if !subdomain.is_empty()
+            && subdomain_expiration_policy == Some(SUBDOMAIN_POLICY_LOOKUP_DOMAIN_EXPIRATION)
+        {
+            None
+        } else {
+            Some(expiration_timestamp)
+        }
+    }
+
     pub fn get_v2_from_v1(
         v1_current_ans_lookup: CurrentAnsLookup,
         v1_ans_lookup: AnsLookup,
@@ -261,6 +302,7 @@ impl CurrentAnsLookupV2 {
                 token_name: v1_current_ans_lookup.token_name,
                 is_deleted: v1_current_ans_lookup.is_deleted,
                 subdomain_expiration_policy: None,
+                effective_expiration_timestamp: Some(v1_current_ans_lookup.expiration_timestamp),
             },
             AnsLookupV2 {
                 transaction_version: v1_ans_lookup.transaction_version,
@@ -314,6 +356,11 @@ impl CurrentAnsLookupV2 {
                     last_transaction_version: txn_version,
                     is_deleted: false,
                     subdomain_expiration_policy,
+                    effective_expiration_timestamp: Self::get_effective_expiration_timestamp(
+                        &subdomain_name,
+                        subdomain_expiration_policy,
+                        inner.get_expiration_time(),
+                    ),
                 },
                 AnsLookupV2 {
                     transaction_version: txn_version,
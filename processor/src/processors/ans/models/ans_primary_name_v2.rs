@@ -15,6 +15,7 @@ use crate::{
         token_v2::token_v2_models::v2_token_utils::TokenStandard,
     },
     schema::{ans_primary_name_v2, current_ans_primary_name_v2},
+    utils::dedup::CurrentTableRow,
 };
 use allocative_derive::Allocative;
 use cedra_indexer_processor_sdk::cedra_protos::transaction::v1::Event;
@@ -207,6 +208,18 @@ impl From<CurrentAnsPrimaryNameV2> for PostgresCurrentAnsPrimaryNameV2 {
     }
 }
 
+impl CurrentTableRow for PostgresCurrentAnsPrimaryNameV2 {
+    type PrimaryKey = (String, String);
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        (self.registered_address.clone(), self.token_standard.clone())
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
+
 impl CurrentAnsPrimaryNameV2 {
     pub fn pk(&self) -> CurrentAnsPrimaryNameV2PK {
         (self.registered_address.clone(), self.token_standard.clone())
@@ -0,0 +1,137 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use super::ans_utils::{get_token_name, V2AnsEvent};
+use crate::schema::ans_revenue_events;
+use ahash::AHashMap;
+use bigdecimal::BigDecimal;
+use cedra_indexer_processor_sdk::{
+    cedra_indexer_transaction_stream::utils::time::parse_timestamp,
+    cedra_protos::transaction::v1::{transaction::TxnData, Transaction},
+    utils::convert::standardize_address,
+};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+pub const REGISTRATION_REVENUE: &str = "registration";
+pub const RENEWAL_REVENUE: &str = "renewal";
+
+/// (domain, subdomain)
+pub type AnsNamePK = (String, String);
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, event_index))]
+#[diesel(table_name = ans_revenue_events)]
+pub struct PostgresAnsRevenueEvent {
+    pub transaction_version: i64,
+    pub event_index: i64,
+    pub revenue_type: String,
+    pub payer_address: String,
+    pub domain: String,
+    pub subdomain: String,
+    pub token_name: String,
+    pub amount_octas: Option<BigDecimal>,
+    pub expiration_timestamp: chrono::NaiveDateTime,
+    /// Filled in by the storer for renewals, from the name's previous expiration
+    pub duration_secs: Option<i64>,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl PostgresAnsRevenueEvent {
+    /// Registrations and renewals of ANS v2 names, paid for by the transaction sender
+    pub fn from_transactions(
+        transactions: &[Transaction],
+        ans_v2_contract_address: &str,
+    ) -> Vec<Self> {
+        let mut revenue_events = vec![];
+        for txn in transactions {
+            let txn_version = txn.version as i64;
+            let Some(TxnData::User(user_txn)) = txn.txn_data.as_ref() else {
+                continue;
+            };
+            let Some(request) = user_txn.request.as_ref() else {
+                continue;
+            };
+            let payer_address = standardize_address(&request.sender);
+            let txn_timestamp =
+                parse_timestamp(txn.timestamp.as_ref().unwrap(), txn_version).naive_utc();
+
+            for (event_index, event) in user_txn.events.iter().enumerate() {
+                let (revenue_type, domain, subdomain, amount_octas, expiration_timestamp) =
+                    match V2AnsEvent::from_event(event, ans_v2_contract_address, txn_version) {
+                        Ok(Some(V2AnsEvent::RegisterNameEvent(inner))) => (
+                            REGISTRATION_REVENUE,
+                            inner.get_domain_trunc(),
+                            inner.get_subdomain_trunc(),
+                            inner.get_registration_fee_octas(),
+                            inner.get_expiration_time(),
+                        ),
+                        Ok(Some(V2AnsEvent::RenewNameEvent(inner))) => (
+                            RENEWAL_REVENUE,
+                            inner.get_domain_trunc(),
+                            inner.get_subdomain_trunc(),
+                            inner.get_renewal_fee_octas(),
+                            inner.get_expiration_time(),
+                        ),
+                        Ok(_) => continue,
+                        Err(e) => {
+                            tracing::error!(
+                                transaction_version = txn_version,
+                                event_index = event_index,
+                                error = ?e,
+                                "[Parser] error parsing ANS revenue event"
+                            );
+                            continue;
+                        },
+                    };
+                let duration_secs = (revenue_type == REGISTRATION_REVENUE).then(|| {
+                    expiration_timestamp
+                        .signed_duration_since(txn_timestamp)
+                        .num_seconds()
+                });
+                revenue_events.push(Self {
+                    transaction_version: txn_version,
+                    event_index: event_index as i64,
+                    revenue_type: revenue_type.to_string(),
+                    payer_address: payer_address.clone(),
+                    token_name: get_token_name(&domain, &subdomain),
+                    domain,
+                    subdomain,
+                    amount_octas,
+                    expiration_timestamp,
+                    duration_secs,
+                    transaction_timestamp: txn_timestamp,
+                });
+            }
+        }
+        revenue_events
+    }
+
+    pub fn name_pk(&self) -> AnsNamePK {
+        (self.domain.clone(), self.subdomain.clone())
+    }
+
+    /// Sets the duration of renewals to how much they moved the expiration. `previous_expirations`
+    /// holds the names' expirations before the batch and is updated as events extend them.
+    /// Events must be in version order.
+    pub fn fill_renewal_durations(
+        revenue_events: &mut [Self],
+        previous_expirations: &mut AHashMap<AnsNamePK, chrono::NaiveDateTime>,
+    ) {
+        for revenue_event in revenue_events {
+            let name_pk = revenue_event.name_pk();
+            if revenue_event.revenue_type == RENEWAL_REVENUE {
+                revenue_event.duration_secs = previous_expirations.get(&name_pk).map(|previous| {
+                    revenue_event
+                        .expiration_timestamp
+                        .signed_duration_since(*previous)
+                        .num_seconds()
+                });
+            }
+            previous_expirations.insert(name_pk, revenue_event.expiration_timestamp);
+        }
+    }
+}
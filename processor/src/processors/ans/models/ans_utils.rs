@@ -17,6 +17,9 @@ use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
 
 pub const DOMAIN_LENGTH: usize = 64;
+/// `subdomain_expiration_policy` of subdomains that expire with their domain. Subdomains with the
+/// other policy (0) expire at their own, manually set, expiration.
+pub const SUBDOMAIN_POLICY_LOOKUP_DOMAIN_EXPIRATION: i64 = 1;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct OptionalString {
@@ -240,9 +243,33 @@ pub struct RenewNameEvent {
     is_primary_name: bool,
     subdomain_name: OptionalString,
     target_address: OptionalString,
+    #[serde(default)]
+    renewal_fee_octas: Option<BigDecimalWrapper>,
 }
 
 impl RenewNameEvent {
+    pub fn get_domain_trunc(&self) -> String {
+        truncate_str(self.domain_name.as_str(), DOMAIN_LENGTH)
+    }
+
+    pub fn get_subdomain_trunc(&self) -> String {
+        truncate_str(
+            self.subdomain_name
+                .get_string()
+                .unwrap_or_default()
+                .as_str(),
+            DOMAIN_LENGTH,
+        )
+    }
+
+    pub fn get_expiration_time(&self) -> chrono::NaiveDateTime {
+        parse_timestamp_secs(bigdecimal_to_u64(&self.expiration_time_secs), 0).naive_utc()
+    }
+
+    pub fn get_renewal_fee_octas(&self) -> Option<BigDecimal> {
+        self.renewal_fee_octas.as_ref().map(|fee| fee.0.clone())
+    }
+
     pub fn from_event(
         event: &Event,
         ans_v2_contract_address: &str,
@@ -258,6 +285,42 @@ impl RenewNameEvent {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RegisterNameEvent {
+    domain_name: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    expiration_time_secs: BigDecimal,
+    subdomain_name: OptionalString,
+    #[serde(default)]
+    registration_fee_octas: Option<BigDecimalWrapper>,
+}
+
+impl RegisterNameEvent {
+    pub fn get_domain_trunc(&self) -> String {
+        truncate_str(self.domain_name.as_str(), DOMAIN_LENGTH)
+    }
+
+    pub fn get_subdomain_trunc(&self) -> String {
+        truncate_str(
+            self.subdomain_name
+                .get_string()
+                .unwrap_or_default()
+                .as_str(),
+            DOMAIN_LENGTH,
+        )
+    }
+
+    pub fn get_expiration_time(&self) -> chrono::NaiveDateTime {
+        parse_timestamp_secs(bigdecimal_to_u64(&self.expiration_time_secs), 0).naive_utc()
+    }
+
+    pub fn get_registration_fee_octas(&self) -> Option<BigDecimal> {
+        self.registration_fee_octas
+            .as_ref()
+            .map(|fee| fee.0.clone())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SetReverseLookupEvent {
     account_addr: String,
@@ -319,6 +382,7 @@ impl SetReverseLookupEvent {
 pub enum V2AnsEvent {
     SetReverseLookupEvent(SetReverseLookupEvent),
     RenewNameEvent(RenewNameEvent),
+    RegisterNameEvent(RegisterNameEvent),
 }
 
 impl V2AnsEvent {
@@ -326,6 +390,7 @@ impl V2AnsEvent {
         [
             format!("{ans_v2_contract_address}::v2_1_domains::SetReverseLookupEvent"),
             format!("{ans_v2_contract_address}::v2_1_domains::RenewNameEvent"),
+            format!("{ans_v2_contract_address}::v2_1_domains::RegisterNameEvent"),
         ]
         .contains(&event_type.to_string())
     }
@@ -349,6 +414,9 @@ impl V2AnsEvent {
             x if x == format!("{ans_v2_contract_address}::v2_1_domains::RenewNameEvent") => {
                 serde_json::from_str(data).map(|inner| Some(Self::RenewNameEvent(inner)))
             },
+            x if x == format!("{ans_v2_contract_address}::v2_1_domains::RegisterNameEvent") => {
+                serde_json::from_str(data).map(|inner| Some(Self::RegisterNameEvent(inner)))
+            },
             _ => Ok(None),
         }
         .context(format!(
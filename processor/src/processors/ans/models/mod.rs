@@ -1,4 +1,5 @@
 pub mod ans_lookup;
 pub mod ans_lookup_v2;
 pub mod ans_primary_name_v2;
+pub mod ans_revenue_events;
 pub mod ans_utils;
@@ -2,7 +2,9 @@ use super::models::move_modules::PostgresMoveModule;
 use crate::processors::default::{
     models::{
         block_metadata_transactions::PostgresBlockMetadataTransaction,
+        table_item_types::TableItemTypes,
         table_items::{PostgresCurrentTableItem, PostgresTableItem, PostgresTableMetadata},
+        write_set_change_stats::PostgresWriteSetChangeStats,
     },
     process_transactions,
 };
@@ -16,7 +18,10 @@ use async_trait::async_trait;
 
 pub struct DefaultExtractor
 where
-    Self: Sized + Send + 'static, {}
+    Self: Sized + Send + 'static,
+{
+    pub table_item_types: TableItemTypes,
+}
 
 #[async_trait]
 impl Processable for DefaultExtractor {
@@ -27,6 +32,7 @@ impl Processable for DefaultExtractor {
         Vec<PostgresCurrentTableItem>,
         Vec<PostgresTableMetadata>,
         Vec<PostgresMoveModule>,
+        Vec<PostgresWriteSetChangeStats>,
     );
     type RunType = AsyncRunType;
 
@@ -41,6 +47,7 @@ impl Processable for DefaultExtractor {
                 Vec<PostgresCurrentTableItem>,
                 Vec<PostgresTableMetadata>,
                 Vec<PostgresMoveModule>,
+                Vec<PostgresWriteSetChangeStats>,
             )>,
         >,
         ProcessorError,
@@ -51,7 +58,7 @@ impl Processable for DefaultExtractor {
             raw_current_table_items,
             raw_table_metadata,
             raw_move_modules,
-        ) = process_transactions(transactions.data.clone());
+        ) = process_transactions(transactions.data.clone(), &self.table_item_types);
 
         let postgres_table_items: Vec<PostgresTableItem> = raw_table_items
             .into_iter()
@@ -74,6 +81,11 @@ impl Processable for DefaultExtractor {
             .into_iter()
             .map(PostgresMoveModule::from)
             .collect();
+        let postgres_write_set_change_stats: Vec<PostgresWriteSetChangeStats> = transactions
+            .data
+            .iter()
+            .filter_map(PostgresWriteSetChangeStats::from_transaction)
+            .collect();
 
         Ok(Some(TransactionContext {
             data: (
@@ -82,6 +94,7 @@ impl Processable for DefaultExtractor {
                 postgres_current_table_items,
                 postgres_table_metadata,
                 postgres_move_modules,
+                postgres_write_set_change_stats,
             ),
             metadata: transactions.metadata,
         }))
@@ -1,34 +1,52 @@
 use crate::{
     config::{
-        db_config::DbConfig, indexer_processor_config::IndexerProcessorConfig,
-        processor_config::ProcessorConfig,
+        db_config::DbConfig,
+        indexer_processor_config::IndexerProcessorConfig,
+        processor_config::{DefaultProcessorConfig, ProcessorConfig},
+    },
+    db::{
+        connection_pool::new_postgres_pool, partitioning::PartitionStep,
+        schema_drift::run_migrations_and_check_drift,
     },
     processors::{
-        default::{default_extractor::DefaultExtractor, default_storer::DefaultStorer},
+        default::{
+            default_extractor::DefaultExtractor,
+            default_storer::DefaultStorer,
+            models::table_item_types::{TableItemType, TableItemTypes},
+        },
         processor_status_saver::{
             get_end_version, get_starting_version, PostgresProcessorStatusSaver,
         },
     },
-    utils::table_flags::TableFlags,
-    MIGRATIONS,
+    utils::{
+        logging::BatchSpanStep,
+        supervisor::supervise_step_tasks,
+        table_flags::TableFlags,
+        table_writers::PendingWritesStep,
+        transaction_source::{check_chain_id, TransactionSourceStep},
+    },
 };
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    cedra_indexer_transaction_stream::TransactionStreamConfig,
     builder::ProcessorBuilder,
-    common_steps::{
-        TransactionStreamStep, VersionTrackerStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
-    },
-    postgres::utils::{
-        checkpoint::PostgresChainIdChecker,
-        database::{new_db_pool, run_migrations, ArcDbPool},
-    },
+    common_steps::{VersionTrackerStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS},
+    postgres::utils::database::ArcDbPool,
     traits::{processor_trait::ProcessorTrait, IntoRunnableStep},
-    utils::chain_id_check::check_or_update_chain_id,
 };
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DefaultProcessorExtendedConfig {
+    #[serde(flatten)]
+    pub default_config: DefaultProcessorConfig,
+    // Types of table handles to decode items of when the node sends them undecoded
+    #[serde(default)]
+    pub table_item_types: Vec<TableItemType>,
+}
+
 pub struct DefaultProcessor {
     pub config: IndexerProcessorConfig,
     pub db_pool: ArcDbPool,
@@ -38,12 +56,7 @@ impl DefaultProcessor {
     pub async fn new(config: IndexerProcessorConfig) -> Result<Self> {
         match config.db_config {
             DbConfig::PostgresConfig(ref postgres_config) => {
-                let conn_pool = new_db_pool(
-                    &postgres_config.connection_string,
-                    Some(postgres_config.db_pool_size),
-                )
-                .await
-                .map_err(|e| {
+                let conn_pool = new_postgres_pool(postgres_config).await.map_err(|e| {
                     anyhow::anyhow!(
                         "Failed to create connection pool for PostgresConfig: {:?}",
                         e
@@ -72,12 +85,7 @@ impl ProcessorTrait for DefaultProcessor {
     async fn run_processor(&self) -> Result<()> {
         // Run migrations
         if let DbConfig::PostgresConfig(ref postgres_config) = self.config.db_config {
-            run_migrations(
-                postgres_config.connection_string.clone(),
-                self.db_pool.clone(),
-                MIGRATIONS,
-            )
-            .await;
+            run_migrations_and_check_drift(postgres_config, self.db_pool.clone()).await?;
         }
 
         //  Merge the starting version from config and the latest processed version from the DB
@@ -87,11 +95,7 @@ impl ProcessorTrait for DefaultProcessor {
         );
 
         // Check and update the ledger chain id to ensure we're indexing the correct chain
-        check_or_update_chain_id(
-            &self.config.transaction_stream_config,
-            &PostgresChainIdChecker::new(self.db_pool.clone()),
-        )
-        .await?;
+        check_chain_id(&self.config, self.db_pool.clone()).await?;
 
         let processor_config = match self.config.processor_config.clone() {
             ProcessorConfig::DefaultProcessor(processor_config) => processor_config,
@@ -102,32 +106,48 @@ impl ProcessorTrait for DefaultProcessor {
                 ))
             },
         };
+        let table_item_types = TableItemTypes::new(&processor_config.table_item_types)?;
+        let processor_config = processor_config.default_config;
         let channel_size = processor_config.channel_size;
         let tables_to_write = TableFlags::from_set(&processor_config.tables_to_write);
 
         // Define processor steps
-        let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
-            starting_version,
-            request_ending_version: ending_version,
-            ..self.config.transaction_stream_config.clone()
-        })
+        let transaction_stream =
+            TransactionSourceStep::new(&self.config, starting_version, ending_version).await?;
+        let default_extractor = DefaultExtractor { table_item_types };
+        let partition_step = PartitionStep::new(&self.config, self.db_pool.clone(), &[
+            "block_metadata_transactions",
+            "table_items",
+        ])
         .await?;
-        let default_extractor = DefaultExtractor {};
-        let default_storer =
-            DefaultStorer::new(self.db_pool.clone(), processor_config, tables_to_write);
+        let default_storer = DefaultStorer::new(
+            self.db_pool.clone(),
+            self.config.table_names(),
+            processor_config,
+            tables_to_write,
+        );
         let version_tracker = VersionTrackerStep::new(
             PostgresProcessorStatusSaver::new(self.config.clone(), self.db_pool.clone()),
             DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
         );
 
         // Connect processor steps together
-        let (_, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
+        let (processor_builder, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
             transaction_stream.into_runnable_step(),
         )
-        .connect_to(default_extractor.into_runnable_step(), channel_size)
-        .connect_to(default_storer.into_runnable_step(), channel_size)
+        .connect_to(
+            BatchSpanStep::new(default_extractor, self.name()).into_runnable_step(),
+            channel_size,
+        )
+        .connect_to(partition_step.into_runnable_step(), channel_size)
+        .connect_to(
+            BatchSpanStep::new(default_storer, self.name()).into_runnable_step(),
+            channel_size,
+        )
+        .connect_to(PendingWritesStep.into_runnable_step(), channel_size)
         .connect_to(version_tracker.into_runnable_step(), channel_size)
         .end_and_return_output_receiver(channel_size);
+        supervise_step_tasks(processor_builder.join_handles);
 
         // (Optional) Parse the results
         loop {
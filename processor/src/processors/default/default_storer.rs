@@ -8,14 +8,21 @@ use crate::{
         block_metadata_transactions::PostgresBlockMetadataTransaction,
         move_modules::PostgresMoveModule,
         table_items::{PostgresCurrentTableItem, PostgresTableItem, PostgresTableMetadata},
+        write_set_change_stats::PostgresWriteSetChangeStats,
     },
     schema,
-    utils::table_flags::{filter_data, TableFlags},
+    utils::{
+        dedup::dedup_by_pk_keep_latest,
+        table_flags::{filter_data, TableFlags},
+        table_names::TableNames,
+        table_writers::{PendingWrites, TableWriters},
+    },
+    version_guard,
 };
 use ahash::AHashMap;
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    postgres::utils::database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
@@ -32,21 +39,24 @@ pub struct DefaultStorer
 where
     Self: Sized + Send + 'static,
 {
-    conn_pool: ArcDbPool,
     processor_config: DefaultProcessorConfig,
     tables_to_write: TableFlags,
+    table_writers: TableWriters,
 }
 
 impl DefaultStorer {
     pub fn new(
         conn_pool: ArcDbPool,
+        table_names: TableNames,
         processor_config: DefaultProcessorConfig,
         tables_to_write: TableFlags,
     ) -> Self {
+        let table_writers =
+            TableWriters::new(conn_pool, table_names, processor_config.channel_size);
         Self {
-            conn_pool,
             processor_config,
             tables_to_write,
+            table_writers,
         }
     }
 }
@@ -59,17 +69,17 @@ impl Processable for DefaultStorer {
         Vec<PostgresCurrentTableItem>,
         Vec<PostgresTableMetadata>,
         Vec<PostgresMoveModule>,
+        Vec<PostgresWriteSetChangeStats>,
     );
-    type Output = ();
+    type Output = PendingWrites;
     type RunType = AsyncRunType;
 
     /// Processes a batch of transactions and inserts the extracted data into the database.
     ///
     /// This function takes a `TransactionContext` containing vectors of block metadata transactions,
-    /// table items, current table items, and table metadata. It processes these vectors by executing
-    /// database insertion operations in chunks to handle large datasets efficiently. The function
-    /// uses `futures::try_join!` to run the insertion operations concurrently and ensures that all
-    /// operations complete successfully.
+    /// table items, current table items, and table metadata. It queues each vector on its table's
+    /// writer, which inserts it in chunks to handle large datasets efficiently. Tables are written
+    /// independently of each other, `PendingWritesStep` waits for all of them to complete.
     ///
     /// # Arguments
     ///
@@ -78,11 +88,13 @@ impl Processable for DefaultStorer {
     ///   * `Vec<TableItem>` - A vector of table items.
     ///   * `Vec<CurrentTableItem>` - A vector of current table items.
     ///   * `Vec<TableMetadata>` - A vector of table metadata.
+    ///   * `Vec<MoveModule>` - A vector of move modules.
+    ///   * `Vec<WriteSetChangeStats>` - A vector of per transaction write set summaries.
     ///
     /// # Returns
     ///
-    /// * `Result<Option<TransactionContext<()>>, ProcessorError>` - Returns `Ok(Some(TransactionContext))`
-    ///   if all insertion operations complete successfully. Returns an error if any of the operations fail.
+    /// * `Result<Option<TransactionContext<PendingWrites>>, ProcessorError>` - Returns `Ok(Some(TransactionContext))`
+    ///   with the queued writes. Returns an error if any of the writes couldn't be queued.
     async fn process(
         &mut self,
         input: TransactionContext<(
@@ -91,14 +103,16 @@ impl Processable for DefaultStorer {
             Vec<PostgresCurrentTableItem>,
             Vec<PostgresTableMetadata>,
             Vec<PostgresMoveModule>,
+            Vec<PostgresWriteSetChangeStats>,
         )>,
-    ) -> Result<Option<TransactionContext<()>>, ProcessorError> {
+    ) -> Result<Option<TransactionContext<PendingWrites>>, ProcessorError> {
         let (
             block_metadata_transactions,
             table_items,
             current_table_items,
             table_metadata,
             move_modules,
+            write_set_change_stats,
         ) = input.data;
 
         let (
@@ -107,74 +121,96 @@ impl Processable for DefaultStorer {
             current_table_items,
             table_metadata,
             move_modules,
+            write_set_change_stats,
         ) = filter_datasets!(self, {
             block_metadata_transactions => TableFlags::BLOCK_METADATA_TRANSACTIONS,
             table_items => TableFlags::TABLE_ITEMS,
             current_table_items => TableFlags::CURRENT_TABLE_ITEMS,
             table_metadata => TableFlags::TABLE_METADATA,
             move_modules => TableFlags::MOVE_MODULES,
+            write_set_change_stats => TableFlags::WRITE_SET_CHANGE_STATS,
         });
 
+        let current_table_items = dedup_by_pk_keep_latest(current_table_items);
+
         let per_table_chunk_sizes: AHashMap<String, usize> =
             self.processor_config.per_table_chunk_sizes.clone();
 
-        let bmt_res = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_block_metadata_transactions_query,
-            &block_metadata_transactions,
-            get_config_table_chunk_size::<PostgresBlockMetadataTransaction>(
+        let mut pending_writes = PendingWrites::default();
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_block_metadata_transactions_query,
+                block_metadata_transactions,
                 "block_metadata_transactions",
-                &per_table_chunk_sizes,
-            ),
-        );
-
-        let table_items_res = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_table_items_query,
-            &table_items,
-            get_config_table_chunk_size::<PostgresTableItem>("table_items", &per_table_chunk_sizes),
-        );
-
-        let current_table_items_res = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_current_table_items_query,
-            &current_table_items,
-            get_config_table_chunk_size::<PostgresCurrentTableItem>(
+                get_config_table_chunk_size::<PostgresBlockMetadataTransaction>(
+                    "block_metadata_transactions",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_table_items_query,
+                table_items,
+                "table_items",
+                get_config_table_chunk_size::<PostgresTableItem>(
+                    "table_items",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_current_table_items_query,
+                current_table_items,
                 "current_table_items",
-                &per_table_chunk_sizes,
-            ),
-        );
-
-        let table_metadata_res = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_table_metadata_query,
-            &table_metadata,
-            get_config_table_chunk_size::<PostgresTableMetadata>(
+                get_config_table_chunk_size::<PostgresCurrentTableItem>(
+                    "current_table_items",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_table_metadata_query,
+                table_metadata,
                 "table_metadata",
-                &per_table_chunk_sizes,
-            ),
-        );
-
-        let move_modules_res = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_move_modules_query,
-            &move_modules,
-            get_config_table_chunk_size::<PostgresMoveModule>(
+                get_config_table_chunk_size::<PostgresTableMetadata>(
+                    "table_metadata",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_move_modules_query,
+                move_modules,
                 "move_modules",
-                &per_table_chunk_sizes,
-            ),
-        );
-
-        futures::try_join!(
-            bmt_res,
-            table_items_res,
-            current_table_items_res,
-            table_metadata_res,
-            move_modules_res,
-        )?;
-
+                get_config_table_chunk_size::<PostgresMoveModule>(
+                    "move_modules",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_write_set_change_stats_query,
+                write_set_change_stats,
+                "write_set_change_stats",
+                get_config_table_chunk_size::<PostgresWriteSetChangeStats>(
+                    "write_set_change_stats",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
         Ok(Some(TransactionContext {
-            data: (),
+            data: pending_writes,
             metadata: input.metadata,
         }))
     }
@@ -227,11 +263,9 @@ pub fn insert_current_table_items_query(
             last_transaction_version.eq(excluded(last_transaction_version)),
             inserted_at.eq(excluded(inserted_at)),
         ))
-        .filter(
-            schema::current_table_items::last_transaction_version.le(excluded(
-                schema::current_table_items::last_transaction_version,
-            )),
-        )
+        .filter(version_guard!(
+            schema::current_table_items::last_transaction_version
+        ))
 }
 
 pub fn insert_table_metadata_query(
@@ -255,3 +289,14 @@ pub fn insert_move_modules_query(
         .on_conflict((transaction_version, write_set_change_index))
         .do_nothing()
 }
+
+pub fn insert_write_set_change_stats_query(
+    items_to_insert: Vec<PostgresWriteSetChangeStats>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::write_set_change_stats::dsl::*;
+
+    diesel::insert_into(schema::write_set_change_stats::table)
+        .values(items_to_insert)
+        .on_conflict(transaction_version)
+        .do_nothing()
+}
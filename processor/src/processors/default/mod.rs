@@ -8,6 +8,7 @@ use crate::{
         block_metadata_transactions::BlockMetadataTransactionModel,
         move_modules::ParquetMoveModule,
         move_resources::ParquetMoveResource,
+        table_item_types::TableItemTypes,
         table_items::{CurrentTableItem, TableItem, TableMetadata},
         transactions::{ParquetTransaction, TransactionModel},
         write_set_changes::{ParquetWriteSetChange, WriteSetChangeDetail},
@@ -32,6 +33,7 @@ use models::move_modules::MoveModule;
 /// # Arguments
 ///
 /// * `transactions` - A vector of `Transaction` objects to be processed.
+/// * `table_item_types` - Types of table handles whose items may arrive without decoded data.
 ///
 /// # Returns
 ///
@@ -42,6 +44,7 @@ use models::move_modules::MoveModule;
 /// * `Vec<RawTableMetadata>` - A vector of table metadata, sorted by primary key.
 pub fn process_transactions(
     transactions: Vec<Transaction>,
+    table_item_types: &TableItemTypes,
 ) -> (
     Vec<BlockMetadataTransactionModel>,
     Vec<TableItem>,
@@ -103,6 +106,8 @@ pub fn process_transactions(
                 .expect("WriteSetChange must have a change")
             {
                 WriteSetChangeEnum::WriteTableItem(inner) => {
+                    let filled = table_item_types.fill_missing_data(inner);
+                    let inner = filled.as_ref().unwrap_or(inner);
                     let (ti, cti) = TableItem::from_write_table_item(
                         inner,
                         index as i64,
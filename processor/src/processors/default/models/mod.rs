@@ -1,6 +1,8 @@
 pub mod block_metadata_transactions;
 pub mod move_modules;
 pub mod move_resources;
+pub mod table_item_types;
 pub mod table_items;
 pub mod transactions;
+pub mod write_set_change_stats;
 pub mod write_set_changes;
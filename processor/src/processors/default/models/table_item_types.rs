@@ -0,0 +1,250 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use ahash::AHashMap;
+use anyhow::{bail, Context};
+use bigdecimal::num_bigint::{BigInt, Sign};
+use cedra_indexer_processor_sdk::{
+    cedra_protos::transaction::v1::{WriteTableData, WriteTableItem},
+    utils::convert::standardize_address,
+};
+use serde::{Deserialize, Serialize};
+
+/// Key and value types of a table handle, for table items the node streams without decoded
+/// data. The key is decoded from its BCS bytes into JSON the way the node would have. The
+/// stream doesn't carry the value's bytes, so its `decoded_value` is JSON `null`.
+///
+/// Keys can be made of `bool`, integers, `address`, `vector<T>`, `0x1::string::String`,
+/// `0x1::option::Option<T>` and `0x1::object::Object<T>`.
+///
+/// Example:
+/// ```yaml
+/// table_item_types:
+///   - table_handle: "0x5e9a1f7a7a4e0c4b1a4b6ebe0f2a1c1f8a1e2d3c4b5a69788796a5b4c3d2e1f0"
+///     key_type: "address"
+///     value_type: "0xcafe::vault::Position"
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TableItemType {
+    pub table_handle: String,
+    pub key_type: String,
+    pub value_type: String,
+}
+
+/// Registered table item types indexed by standardized table handle.
+#[derive(Clone, Debug, Default)]
+pub struct TableItemTypes {
+    types_by_handle: AHashMap<String, (TableItemType, KeyLayout)>,
+}
+
+impl TableItemTypes {
+    pub fn new(types: &[TableItemType]) -> anyhow::Result<Self> {
+        let mut types_by_handle = AHashMap::new();
+        for table_item_type in types {
+            let layout = KeyLayout::parse(&table_item_type.key_type).with_context(|| {
+                format!(
+                    "Unsupported key type for table handle {}",
+                    table_item_type.table_handle
+                )
+            })?;
+            types_by_handle.insert(
+                standardize_address(&table_item_type.table_handle),
+                (table_item_type.clone(), layout),
+            );
+        }
+        Ok(Self { types_by_handle })
+    }
+
+    /// The table item with its data decoded from the registered types, if the node sent it
+    /// without data and its handle is registered.
+    pub fn fill_missing_data(&self, table_item: &WriteTableItem) -> Option<WriteTableItem> {
+        if table_item.data.is_some() {
+            return None;
+        }
+        let (table_item_type, layout) = self
+            .types_by_handle
+            .get(&standardize_address(&table_item.handle))?;
+        let key = hex::decode(table_item.key.trim_start_matches("0x"))
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| layout.decode(&bytes))
+            .map_err(|e| {
+                tracing::warn!(
+                    table_handle = %table_item.handle,
+                    key = %table_item.key,
+                    error = ?e,
+                    "Failed to decode table item key"
+                );
+            })
+            .ok()?;
+        Some(WriteTableItem {
+            data: Some(WriteTableData {
+                key: key.to_string(),
+                key_type: table_item_type.key_type.clone(),
+                value: serde_json::Value::Null.to_string(),
+                value_type: table_item_type.value_type.clone(),
+            }),
+            ..table_item.clone()
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum KeyLayout {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    String,
+    Vector(Box<KeyLayout>),
+    Option(Box<KeyLayout>),
+}
+
+impl KeyLayout {
+    fn parse(move_type: &str) -> anyhow::Result<Self> {
+        let move_type = move_type.trim();
+        let generic_argument = |prefix: &str| {
+            move_type
+                .strip_prefix(prefix)
+                .and_then(|rest| rest.strip_suffix('>'))
+        };
+        Ok(match move_type {
+            "bool" => Self::Bool,
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "u128" => Self::U128,
+            "u256" => Self::U256,
+            "address" => Self::Address,
+            "0x1::string::String" => Self::String,
+            _ => {
+                if let Some(inner) = generic_argument("vector<") {
+                    Self::Vector(Box::new(Self::parse(inner)?))
+                } else if let Some(inner) = generic_argument("0x1::option::Option<") {
+                    Self::Option(Box::new(Self::parse(inner)?))
+                } else if generic_argument("0x1::object::Object<").is_some() {
+                    Self::Address
+                } else {
+                    bail!("Unsupported Move type {move_type}")
+                }
+            },
+        })
+    }
+
+    /// JSON of a BCS value, formatted like the node's API formats it
+    fn decode(&self, bytes: &[u8]) -> anyhow::Result<serde_json::Value> {
+        let mut cursor = bytes;
+        let value = self.decode_next(&mut cursor)?;
+        if !cursor.is_empty() {
+            bail!("{} trailing bytes after the key", cursor.len());
+        }
+        Ok(value)
+    }
+
+    fn decode_next(&self, cursor: &mut &[u8]) -> anyhow::Result<serde_json::Value> {
+        Ok(match self {
+            Self::Bool => match take(cursor, 1)?[0] {
+                0 => false.into(),
+                1 => true.into(),
+                byte => bail!("Invalid bool byte {byte}"),
+            },
+            Self::U8 => take(cursor, 1)?[0].into(),
+            Self::U16 => u16::from_le_bytes(take(cursor, 2)?.try_into()?).into(),
+            Self::U32 => u32::from_le_bytes(take(cursor, 4)?.try_into()?).into(),
+            Self::U64 => u64::from_le_bytes(take(cursor, 8)?.try_into()?)
+                .to_string()
+                .into(),
+            Self::U128 => u128::from_le_bytes(take(cursor, 16)?.try_into()?)
+                .to_string()
+                .into(),
+            Self::U256 => BigInt::from_bytes_le(Sign::Plus, take(cursor, 32)?)
+                .to_string()
+                .into(),
+            Self::Address => standardize_address(&hex::encode(take(cursor, 32)?)).into(),
+            Self::String => {
+                let length = read_uleb128(cursor)?;
+                String::from_utf8(take(cursor, length)?.to_vec())?.into()
+            },
+            Self::Vector(inner) if **inner == Self::U8 => {
+                let length = read_uleb128(cursor)?;
+                format!("0x{}", hex::encode(take(cursor, length)?)).into()
+            },
+            Self::Vector(inner) => {
+                let length = read_uleb128(cursor)?;
+                (0..length)
+                    .map(|_| inner.decode_next(cursor))
+                    .collect::<anyhow::Result<Vec<_>>>()?
+                    .into()
+            },
+            Self::Option(inner) => {
+                let length = read_uleb128(cursor)?;
+                if length > 1 {
+                    bail!("Option with {length} values");
+                }
+                let values = (0..length)
+                    .map(|_| inner.decode_next(cursor))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                serde_json::json!({ "vec": values })
+            },
+        })
+    }
+}
+
+fn take<'a>(cursor: &mut &'a [u8], length: usize) -> anyhow::Result<&'a [u8]> {
+    if cursor.len() < length {
+        bail!("Expected {length} more bytes, found {}", cursor.len());
+    }
+    let (taken, rest) = cursor.split_at(length);
+    *cursor = rest;
+    Ok(taken)
+}
+
+fn read_uleb128(cursor: &mut &[u8]) -> anyhow::Result<usize> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = take(cursor, 1)?[0];
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(usize::try_from(value)?);
+        }
+    }
+    bail!("ULEB128 length overflows u64")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_keys() {
+        let decode = |move_type: &str, bytes: &[u8]| {
+            KeyLayout::parse(move_type)
+                .unwrap()
+                .decode(bytes)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(decode("u64", &42u64.to_le_bytes()), "\"42\"");
+        assert_eq!(decode("0x1::string::String", b"\x03abc"), "\"abc\"");
+        assert_eq!(decode("vector<u8>", &[2, 0xAB, 0xCD]), "\"0xabcd\"");
+        assert_eq!(decode("vector<u16>", &[2, 1, 0, 2, 0]), "[1,2]");
+        assert_eq!(
+            decode("0x1::option::Option<bool>", &[1, 1]),
+            "{\"vec\":[true]}"
+        );
+        assert_eq!(
+            decode(
+                "0x1::object::Object<0x1::fungible_asset::Metadata>",
+                &[0xAA; 32]
+            ),
+            format!("\"0x{}\"", "aa".repeat(32))
+        );
+        assert!(KeyLayout::parse("0xcafe::vault::Key").is_err());
+        assert!(KeyLayout::parse("u64").unwrap().decode(&[0; 9]).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     parquet_processors::parquet_utils::util::{HasVersion, NamedTable},
     schema::{current_table_items, table_items, table_metadatas},
+    utils::dedup::CurrentTableRow,
 };
 use allocative_derive::Allocative;
 use cedra_indexer_processor_sdk::{
@@ -265,6 +266,18 @@ impl From<CurrentTableItem> for PostgresCurrentTableItem {
     }
 }
 
+impl CurrentTableRow for PostgresCurrentTableItem {
+    type PrimaryKey = (String, String);
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        (self.table_handle.clone(), self.key_hash.clone())
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(transaction_version, write_set_change_index))]
 #[diesel(table_name = table_items)]
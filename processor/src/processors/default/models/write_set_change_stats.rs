@@ -0,0 +1,200 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::schema::write_set_change_stats;
+use cedra_indexer_processor_sdk::{
+    cedra_protos::transaction::v1::{
+        write_set_change::Change as WriteSetChangeEnum, MoveStructTag, Transaction,
+    },
+    utils::convert::standardize_address,
+};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Size and shape of a transaction's write set, for chain growth dashboards. Bytes come from the
+/// transaction's size info, where write ops are in the same order as the write set changes.
+#[derive(Clone, Debug, Default, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version))]
+#[diesel(table_name = write_set_change_stats)]
+pub struct PostgresWriteSetChangeStats {
+    pub transaction_version: i64,
+    pub transaction_block_height: i64,
+    pub num_changes: i64,
+    pub key_bytes: i64,
+    pub value_bytes: i64,
+    pub num_resources_written: i64,
+    pub num_resources_deleted: i64,
+    pub resource_bytes: i64,
+    /// Resources written or deleted, keyed by `address::module` of the resource type
+    pub resource_counts_by_module: serde_json::Value,
+    pub num_table_items_written: i64,
+    pub num_table_items_deleted: i64,
+    pub table_item_bytes: i64,
+    pub num_modules_written: i64,
+    pub num_modules_deleted: i64,
+    pub module_bytes: i64,
+    pub block_timestamp: chrono::NaiveDateTime,
+}
+
+impl PostgresWriteSetChangeStats {
+    pub fn from_transaction(transaction: &Transaction) -> Option<Self> {
+        let info = transaction.info.as_ref()?;
+        if info.changes.is_empty() {
+            return None;
+        }
+        let timestamp = transaction
+            .timestamp
+            .as_ref()
+            .expect("Transaction timestamp doesn't exist!");
+        #[allow(deprecated)]
+        let block_timestamp =
+            chrono::NaiveDateTime::from_timestamp_opt(timestamp.seconds, timestamp.nanos as u32)
+                .expect("Txn Timestamp is invalid!");
+        let write_op_size_info = transaction
+            .size_info
+            .as_ref()
+            .map(|size_info| size_info.write_op_size_info.as_slice())
+            .unwrap_or_default();
+
+        let mut stats = Self {
+            transaction_version: transaction.version as i64,
+            transaction_block_height: transaction.block_height as i64,
+            num_changes: info.changes.len() as i64,
+            block_timestamp,
+            ..Default::default()
+        };
+        let mut resource_counts_by_module: BTreeMap<String, i64> = BTreeMap::new();
+        for (index, wsc) in info.changes.iter().enumerate() {
+            let (key_bytes, value_bytes) = write_op_size_info.get(index).map_or((0, 0), |size| {
+                (size.key_bytes as i64, size.value_bytes as i64)
+            });
+            stats.key_bytes += key_bytes;
+            stats.value_bytes += value_bytes;
+            let bytes = key_bytes + value_bytes;
+            match wsc.change.as_ref() {
+                Some(WriteSetChangeEnum::WriteResource(inner)) => {
+                    stats.num_resources_written += 1;
+                    stats.resource_bytes += bytes;
+                    if let Some(struct_tag) = inner.r#type.as_ref() {
+                        *resource_counts_by_module
+                            .entry(module_id(struct_tag))
+                            .or_default() += 1;
+                    }
+                },
+                Some(WriteSetChangeEnum::DeleteResource(inner)) => {
+                    stats.num_resources_deleted += 1;
+                    stats.resource_bytes += bytes;
+                    if let Some(struct_tag) = inner.r#type.as_ref() {
+                        *resource_counts_by_module
+                            .entry(module_id(struct_tag))
+                            .or_default() += 1;
+                    }
+                },
+                Some(WriteSetChangeEnum::WriteTableItem(_)) => {
+                    stats.num_table_items_written += 1;
+                    stats.table_item_bytes += bytes;
+                },
+                Some(WriteSetChangeEnum::DeleteTableItem(_)) => {
+                    stats.num_table_items_deleted += 1;
+                    stats.table_item_bytes += bytes;
+                },
+                Some(WriteSetChangeEnum::WriteModule(_)) => {
+                    stats.num_modules_written += 1;
+                    stats.module_bytes += bytes;
+                },
+                Some(WriteSetChangeEnum::DeleteModule(_)) => {
+                    stats.num_modules_deleted += 1;
+                    stats.module_bytes += bytes;
+                },
+                None => {},
+            }
+        }
+        stats.resource_counts_by_module = serde_json::to_value(resource_counts_by_module)
+            .expect("Resource counts should serialize");
+        Some(stats)
+    }
+}
+
+fn module_id(struct_tag: &MoveStructTag) -> String {
+    format!(
+        "{}::{}",
+        standardize_address(&struct_tag.address),
+        struct_tag.module
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cedra_indexer_processor_sdk::cedra_protos::{
+        transaction::v1::{
+            TransactionInfo, TransactionSizeInfo, WriteOpSizeInfo, WriteResource, WriteSetChange,
+            WriteTableItem,
+        },
+        util::timestamp::Timestamp,
+    };
+
+    fn write_resource(module: &str) -> WriteSetChange {
+        WriteSetChange {
+            change: Some(WriteSetChangeEnum::WriteResource(WriteResource {
+                r#type: Some(MoveStructTag {
+                    address: "0x1".to_string(),
+                    module: module.to_string(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_stats_from_transaction() {
+        let transaction = Transaction {
+            version: 7,
+            timestamp: Some(Timestamp::default()),
+            info: Some(TransactionInfo {
+                changes: vec![
+                    write_resource("coin"),
+                    write_resource("coin"),
+                    write_resource("account"),
+                    WriteSetChange {
+                        change: Some(WriteSetChangeEnum::WriteTableItem(WriteTableItem::default())),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }),
+            size_info: Some(TransactionSizeInfo {
+                write_op_size_info: (1..=4)
+                    .map(|i| WriteOpSizeInfo {
+                        key_bytes: i,
+                        value_bytes: 10 * i,
+                    })
+                    .collect(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let stats = PostgresWriteSetChangeStats::from_transaction(&transaction).unwrap();
+        assert_eq!(stats.num_changes, 4);
+        assert_eq!(stats.key_bytes, 10);
+        assert_eq!(stats.value_bytes, 100);
+        assert_eq!(stats.num_resources_written, 3);
+        assert_eq!(stats.resource_bytes, 66);
+        assert_eq!(stats.num_table_items_written, 1);
+        assert_eq!(stats.table_item_bytes, 44);
+        assert_eq!(
+            stats.resource_counts_by_module,
+            serde_json::json!({
+                "0x0000000000000000000000000000000000000000000000000000000000000001::account": 1,
+                "0x0000000000000000000000000000000000000000000000000000000000000001::coin": 2,
+            })
+        );
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::{processors::events::events_model::PostgresEvent, schema::event_attributes};
+use ahash::AHashMap;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::warn;
+
+pub const EVENT_ATTRIBUTE_KEY_MAX_LENGTH: usize = 100;
+pub const EVENT_ATTRIBUTE_VALUE_MAX_LENGTH: usize = 1000;
+
+/// Pulls fields out of the data of a given event type into `event_attributes` at write time.
+///
+/// Example:
+/// ```yaml
+/// attribute_rules:
+///   - event_type: "0x1::fungible_asset::Deposit"
+///     attributes:
+///       store: "/store"
+///       amount: "/amount"
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventAttributeRule {
+    /// Full event type, including generic type arguments if any.
+    pub event_type: String,
+    /// Attribute key to a JSON pointer (RFC 6901) into the event data, e.g. `/metadata/inner`.
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// Extraction rules indexed by event type.
+#[derive(Clone, Debug, Default)]
+pub struct EventAttributeRules {
+    rules_by_type: AHashMap<String, Vec<(String, String)>>,
+}
+
+impl EventAttributeRules {
+    pub fn new(rules: &[EventAttributeRule]) -> anyhow::Result<Self> {
+        let mut rules_by_type: AHashMap<String, Vec<(String, String)>> = AHashMap::new();
+        for rule in rules {
+            for (key, pointer) in &rule.attributes {
+                if key.is_empty() || key.len() > EVENT_ATTRIBUTE_KEY_MAX_LENGTH {
+                    anyhow::bail!(
+                        "Invalid attribute key '{}' for event type {}: must be 1 to {} characters",
+                        key,
+                        rule.event_type,
+                        EVENT_ATTRIBUTE_KEY_MAX_LENGTH
+                    );
+                }
+                if !pointer.starts_with('/') {
+                    anyhow::bail!(
+                        "Invalid JSON pointer '{}' for attribute '{}' of event type {}: must start with '/'",
+                        pointer,
+                        key,
+                        rule.event_type
+                    );
+                }
+                rules_by_type
+                    .entry(rule.event_type.clone())
+                    .or_default()
+                    .push((key.clone(), pointer.clone()));
+            }
+        }
+        Ok(Self { rules_by_type })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules_by_type.is_empty()
+    }
+
+    /// Returns the attributes configured for the event's type. Paths missing from the event data
+    /// are skipped, as are values too long to index.
+    pub fn extract(&self, event: &PostgresEvent) -> Vec<PostgresEventAttribute> {
+        let Some(rules) = self.rules_by_type.get(&event.type_) else {
+            return vec![];
+        };
+        rules
+            .iter()
+            .filter_map(|(key, pointer)| {
+                let value = match event.data.pointer(pointer)? {
+                    serde_json::Value::Null => return None,
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                if value.len() > EVENT_ATTRIBUTE_VALUE_MAX_LENGTH {
+                    warn!(
+                        transaction_version = event.transaction_version,
+                        event_index = event.event_index,
+                        key = key,
+                        "Event attribute value too long, skipping"
+                    );
+                    return None;
+                }
+                Some(PostgresEventAttribute {
+                    transaction_version: event.transaction_version,
+                    event_index: event.event_index,
+                    key: key.clone(),
+                    value,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, event_index, key))]
+#[diesel(table_name = event_attributes)]
+pub struct PostgresEventAttribute {
+    pub transaction_version: i64,
+    pub event_index: i64,
+    pub key: String,
+    pub value: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(type_: &str, data: serde_json::Value) -> PostgresEvent {
+        PostgresEvent {
+            sequence_number: 0,
+            creation_number: 0,
+            account_address: "0x1".to_string(),
+            transaction_version: 100,
+            transaction_block_height: 10,
+            type_: type_.to_string(),
+            data,
+            event_index: 2,
+            indexed_type: type_.to_string(),
+            module_address: None,
+            module_name: None,
+            struct_name: None,
+            generic_args: None,
+        }
+    }
+
+    #[test]
+    fn test_extract_configured_paths() {
+        let rules = EventAttributeRules::new(&[EventAttributeRule {
+            event_type: "0x1::fungible_asset::Deposit".to_string(),
+            attributes: BTreeMap::from([
+                ("amount".to_string(), "/amount".to_string()),
+                ("inner".to_string(), "/metadata/inner".to_string()),
+                ("missing".to_string(), "/missing".to_string()),
+            ]),
+        }])
+        .unwrap();
+
+        let attributes = rules.extract(&event(
+            "0x1::fungible_asset::Deposit",
+            json!({"amount": "500", "metadata": {"inner": "0xa"}}),
+        ));
+        let extracted: Vec<(&str, &str)> = attributes
+            .iter()
+            .map(|a| (a.key.as_str(), a.value.as_str()))
+            .collect();
+        assert_eq!(extracted, vec![("amount", "500"), ("inner", "0xa")]);
+        assert!(attributes
+            .iter()
+            .all(|a| a.transaction_version == 100 && a.event_index == 2));
+
+        let other = rules.extract(&event("0x1::coin::DepositEvent", json!({"amount": "1"})));
+        assert!(other.is_empty());
+    }
+
+    #[test]
+    fn test_reject_invalid_pointer() {
+        let result = EventAttributeRules::new(&[EventAttributeRule {
+            event_type: "0x1::fungible_asset::Deposit".to_string(),
+            attributes: BTreeMap::from([("amount".to_string(), "amount".to_string())]),
+        }]);
+        assert!(result.is_err());
+    }
+}
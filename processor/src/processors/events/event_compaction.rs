@@ -0,0 +1,259 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::{processors::events::events_model::Event, schema::events_compacted};
+use ahash::AHashMap;
+use chrono::{DateTime, NaiveDateTime};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+/// Keeps only the last event per account and type in each time window for high-frequency event
+/// types, like oracle price updates, in `events_compacted`. Events of the listed types can be
+/// left out of `events` with `keep_raw_events: false`.
+///
+/// Example:
+/// ```yaml
+/// compaction:
+///   rules:
+///     - event_type: "0xabc::oracle::PriceUpdate"
+///       window_secs: 60
+///   keep_raw_events: false
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventCompactionConfig {
+    pub rules: Vec<EventCompactionRule>,
+    /// Whether events compacted by a rule are also written to `events`
+    #[serde(default = "EventCompactionConfig::default_keep_raw_events")]
+    pub keep_raw_events: bool,
+}
+
+impl EventCompactionConfig {
+    pub const fn default_keep_raw_events() -> bool {
+        true
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventCompactionRule {
+    /// Event type to compact. A type without generic type arguments also matches its generic
+    /// instantiations.
+    pub event_type: String,
+    /// Length of the windows, aligned to the unix epoch
+    pub window_secs: u64,
+}
+
+/// Compaction windows indexed by event type.
+#[derive(Clone, Debug)]
+pub struct EventCompactor {
+    window_secs_by_type: AHashMap<String, i64>,
+    keep_raw_events: bool,
+}
+
+impl EventCompactor {
+    pub fn new(config: &EventCompactionConfig) -> anyhow::Result<Self> {
+        let mut window_secs_by_type = AHashMap::new();
+        for rule in &config.rules {
+            if rule.window_secs == 0 || rule.window_secs > i64::MAX as u64 {
+                anyhow::bail!(
+                    "Invalid window_secs {} for event type {}: must be positive",
+                    rule.window_secs,
+                    rule.event_type
+                );
+            }
+            if window_secs_by_type
+                .insert(rule.event_type.clone(), rule.window_secs as i64)
+                .is_some()
+            {
+                anyhow::bail!(
+                    "Event type {} has more than one compaction rule",
+                    rule.event_type
+                );
+            }
+        }
+        Ok(Self {
+            window_secs_by_type,
+            keep_raw_events: config.keep_raw_events,
+        })
+    }
+
+    fn window_secs(&self, event_type: &str) -> Option<i64> {
+        self.window_secs_by_type
+            .get(event_type)
+            .copied()
+            .or_else(|| {
+                let (base_type, _) = event_type.split_once('<')?;
+                self.window_secs_by_type.get(base_type).copied()
+            })
+    }
+
+    /// Whether the event is still written to `events`
+    pub fn keep_raw(&self, event: &Event) -> bool {
+        self.keep_raw_events || self.window_secs(&event.type_).is_none()
+    }
+
+    /// The last event of every account, type and window among `events`. Events without a block
+    /// timestamp can't be placed in a window and are skipped.
+    pub fn compact(&self, events: &[Event]) -> Vec<PostgresCompactedEvent> {
+        let mut last_events: AHashMap<(&str, &str, i64), &Event> = AHashMap::new();
+        for event in events {
+            let (Some(window_secs), Some(block_timestamp)) =
+                (self.window_secs(&event.type_), event.block_timestamp)
+            else {
+                continue;
+            };
+            let timestamp = block_timestamp.and_utc().timestamp();
+            let window_start = timestamp - timestamp.rem_euclid(window_secs);
+            last_events
+                .entry((
+                    event.account_address.as_str(),
+                    event.type_.as_str(),
+                    window_start,
+                ))
+                .and_modify(|last| {
+                    if (event.transaction_version, event.event_index)
+                        > (last.transaction_version, last.event_index)
+                    {
+                        *last = event;
+                    }
+                })
+                .or_insert(event);
+        }
+        last_events
+            .into_iter()
+            .map(|((_, _, window_start), event)| {
+                PostgresCompactedEvent::new(
+                    event,
+                    DateTime::from_timestamp(window_start, 0)
+                        .unwrap_or_default()
+                        .naive_utc(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(account_address, type_, window_start))]
+#[diesel(table_name = events_compacted)]
+pub struct PostgresCompactedEvent {
+    pub account_address: String,
+    pub type_: String,
+    pub window_start: NaiveDateTime,
+    pub transaction_version: i64,
+    pub event_index: i64,
+    pub sequence_number: i64,
+    pub creation_number: i64,
+    pub transaction_block_height: i64,
+    pub data: serde_json::Value,
+    pub indexed_type: String,
+    pub transaction_timestamp: NaiveDateTime,
+}
+
+impl PostgresCompactedEvent {
+    fn new(event: &Event, window_start: NaiveDateTime) -> Self {
+        Self {
+            account_address: event.account_address.clone(),
+            type_: event.type_.clone(),
+            window_start,
+            transaction_version: event.transaction_version,
+            event_index: event.event_index,
+            sequence_number: event.sequence_number,
+            creation_number: event.creation_number,
+            transaction_block_height: event.transaction_block_height,
+            data: serde_json::from_str(&event.data).unwrap(),
+            indexed_type: event.indexed_type.clone(),
+            transaction_timestamp: event.block_timestamp.unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(transaction_version: i64, account_address: &str, timestamp: i64) -> Event {
+        Event {
+            sequence_number: 0,
+            creation_number: 0,
+            account_address: account_address.to_string(),
+            transaction_version,
+            transaction_block_height: 0,
+            type_: "0xabc::oracle::PriceUpdate<0x1::cedra_coin::CedraCoin>".to_string(),
+            data: format!("{{\"price\": \"{transaction_version}\"}}"),
+            event_index: 0,
+            indexed_type: "0xabc::oracle::PriceUpdate<0x1::cedra_coin::CedraCoin>".to_string(),
+            block_timestamp: DateTime::from_timestamp(timestamp, 0).map(|t| t.naive_utc()),
+            type_tag_bytes: None,
+            total_bytes: None,
+            module_address: None,
+            module_name: None,
+            struct_name: None,
+            generic_args: None,
+        }
+    }
+
+    #[test]
+    fn test_keeps_last_event_per_account_and_window() {
+        let compactor = EventCompactor::new(&EventCompactionConfig {
+            rules: vec![EventCompactionRule {
+                event_type: "0xabc::oracle::PriceUpdate".to_string(),
+                window_secs: 60,
+            }],
+            keep_raw_events: false,
+        })
+        .unwrap();
+        let events = vec![
+            event(1, "0x1", 120),
+            event(3, "0x1", 179),
+            event(2, "0x1", 150),
+            event(4, "0x1", 180),
+            event(5, "0x2", 130),
+        ];
+
+        let mut compacted: Vec<(String, i64, i64)> = compactor
+            .compact(&events)
+            .into_iter()
+            .map(|e| {
+                (
+                    e.account_address,
+                    e.window_start.and_utc().timestamp(),
+                    e.transaction_version,
+                )
+            })
+            .collect();
+        compacted.sort();
+        assert_eq!(compacted, vec![
+            ("0x1".to_string(), 120, 3),
+            ("0x1".to_string(), 180, 4),
+            ("0x2".to_string(), 120, 5),
+        ]);
+
+        let mut other = event(6, "0x1", 120);
+        other.type_ = "0x1::coin::CoinDeposit".to_string();
+        assert!(compactor.compact(&[other.clone()]).is_empty());
+        assert!(compactor.keep_raw(&other));
+        assert!(!compactor.keep_raw(&events[0]));
+    }
+
+    #[test]
+    fn test_reject_invalid_rules() {
+        let rule = |window_secs| EventCompactionRule {
+            event_type: "0xabc::oracle::PriceUpdate".to_string(),
+            window_secs,
+        };
+        assert!(EventCompactor::new(&EventCompactionConfig {
+            rules: vec![rule(0)],
+            keep_raw_events: true,
+        })
+        .is_err());
+        assert!(EventCompactor::new(&EventCompactionConfig {
+            rules: vec![rule(60), rule(300)],
+            keep_raw_events: true,
+        })
+        .is_err());
+    }
+}
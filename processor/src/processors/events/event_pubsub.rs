@@ -0,0 +1,354 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::processors::events::{
+    event_attributes_model::PostgresEventAttribute, event_compaction::PostgresCompactedEvent,
+    events_model::PostgresEvent,
+};
+use ahash::AHashMap;
+use anyhow::Context;
+use async_trait::async_trait;
+use cedra_indexer_processor_sdk::{
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use google_cloud_googleapis::pubsub::v1::PubsubMessage;
+use google_cloud_pubsub::{
+    client::{Client as PubSubClient, ClientConfig as PubSubClientConfig},
+    publisher::Publisher,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Publishes the processed events to Pub/Sub, as JSON messages of the rows, with the event's
+/// account address as ordering key so a subscription with ordering enabled receives an account's
+/// events in order. Messages have the row's table in the `table` attribute and its version in
+/// `transaction_version`. Credentials are read from the environment.
+///
+/// Batches are published before they're written, and again when they're reprocessed, so
+/// delivery is at least once. Consumers can drop duplicates by `transaction_version` and
+/// `event_index`.
+///
+/// Example:
+/// ```yaml
+/// pubsub:
+///   type: per_table
+///   topics:
+///     events: indexer-events
+///     event_attributes: indexer-event-attributes
+/// ```
+/// or
+/// ```yaml
+/// pubsub:
+///   type: per_event_type
+///   topics:
+///     "0x1::fungible_asset::Deposit": fa-deposits
+///     "0x1::fungible_asset::Withdraw": fa-withdrawals
+///   default_topic: indexer-events
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventPubSubConfig {
+    /// Publishes the rows of `events`, `event_attributes` and `events_compacted` to the topic of
+    /// their table. Tables without a topic aren't published.
+    PerTable { topics: HashMap<String, String> },
+    /// Publishes `events` rows to the topic of their type. A type without generic type arguments
+    /// also matches its generic instantiations. Events of other types are published to
+    /// `default_topic`, or not at all without one.
+    PerEventType {
+        topics: HashMap<String, String>,
+        #[serde(default)]
+        default_topic: Option<String>,
+    },
+}
+
+const PUBLISHED_TABLES: [&str; 3] = ["events", "event_attributes", "events_compacted"];
+
+impl EventPubSubConfig {
+    fn topics(&self) -> impl Iterator<Item = &String> {
+        match self {
+            Self::PerTable { topics } => topics.values().chain(None),
+            Self::PerEventType {
+                topics,
+                default_topic,
+            } => topics.values().chain(default_topic.as_ref()),
+        }
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if let Self::PerTable { topics } = self {
+            if let Some(table) = topics
+                .keys()
+                .find(|table| !PUBLISHED_TABLES.contains(&table.as_str()))
+            {
+                anyhow::bail!(
+                    "pubsub names {table}, which isn't one of {}",
+                    PUBLISHED_TABLES.join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Topic of a table's rows when routing per table
+    fn table_topic(&self, table: &str) -> Option<&str> {
+        match self {
+            Self::PerTable { topics } => topics.get(table).map(String::as_str),
+            Self::PerEventType { .. } => None,
+        }
+    }
+
+    /// Topic of an event
+    fn event_topic(&self, event_type: &str) -> Option<&str> {
+        match self {
+            Self::PerTable { topics } => topics.get("events").map(String::as_str),
+            Self::PerEventType {
+                topics,
+                default_topic,
+            } => topics
+                .get(event_type)
+                .or_else(|| {
+                    let (base_type, _) = event_type.split_once('<')?;
+                    topics.get(base_type)
+                })
+                .or(default_topic.as_ref())
+                .map(String::as_str),
+        }
+    }
+}
+
+/// A message and the topic it's published to
+type TopicMessage<'a> = (&'a str, PubsubMessage);
+
+fn row_message<T: Serialize>(
+    table: &str,
+    row: &T,
+    transaction_version: i64,
+    ordering_key: &str,
+) -> Result<PubsubMessage, serde_json::Error> {
+    Ok(PubsubMessage {
+        data: serde_json::to_vec(row)?,
+        attributes: HashMap::from([
+            ("table".to_string(), table.to_string()),
+            (
+                "transaction_version".to_string(),
+                transaction_version.to_string(),
+            ),
+        ]),
+        ordering_key: ordering_key.to_string(),
+        ..Default::default()
+    })
+}
+
+/// Messages of a batch with their topics, in the order the rows are in the batch
+fn batch_messages<'a>(
+    config: &'a EventPubSubConfig,
+    events: &[PostgresEvent],
+    event_attributes: &[PostgresEventAttribute],
+    compacted_events: &[PostgresCompactedEvent],
+) -> Result<Vec<TopicMessage<'a>>, serde_json::Error> {
+    let mut messages = vec![];
+    for event in events {
+        if let Some(topic) = config.event_topic(&event.type_) {
+            let message = row_message(
+                "events",
+                event,
+                event.transaction_version,
+                &event.account_address,
+            )?;
+            messages.push((topic, message));
+        }
+    }
+    if let Some(topic) = config.table_topic("event_attributes") {
+        // Attributes are ordered by the account of their event
+        let account_addresses: AHashMap<(i64, i64), &str> = events
+            .iter()
+            .map(|event| {
+                (
+                    (event.transaction_version, event.event_index),
+                    event.account_address.as_str(),
+                )
+            })
+            .collect();
+        for attribute in event_attributes {
+            let ordering_key = account_addresses
+                .get(&(attribute.transaction_version, attribute.event_index))
+                .copied()
+                .unwrap_or_default();
+            let message = row_message(
+                "event_attributes",
+                attribute,
+                attribute.transaction_version,
+                ordering_key,
+            )?;
+            messages.push((topic, message));
+        }
+    }
+    if let Some(topic) = config.table_topic("events_compacted") {
+        for event in compacted_events {
+            let message = row_message(
+                "events_compacted",
+                event,
+                event.transaction_version,
+                &event.account_address,
+            )?;
+            messages.push((topic, message));
+        }
+    }
+    Ok(messages)
+}
+
+/// Publishes the rows of every batch it passes on, see `EventPubSubConfig`
+pub struct EventsPubSubPublisher {
+    config: Option<EventPubSubConfig>,
+    publishers: HashMap<String, Publisher>,
+}
+
+impl EventsPubSubPublisher {
+    /// Connects to the topics of `config`, or publishes nothing without one
+    pub async fn new(config: Option<EventPubSubConfig>) -> anyhow::Result<Self> {
+        let mut publishers = HashMap::new();
+        if let Some(config) = &config {
+            config.validate()?;
+            let client_config = PubSubClientConfig::default()
+                .with_auth()
+                .await
+                .context("Failed to authenticate events Pub/Sub client")?;
+            let client = PubSubClient::new(client_config)
+                .await
+                .context("Failed to create events Pub/Sub client")?;
+            for topic in config.topics() {
+                publishers
+                    .entry(topic.clone())
+                    .or_insert_with(|| client.topic(topic).new_publisher(None));
+            }
+        }
+        Ok(Self { config, publishers })
+    }
+}
+
+type EventRows = (
+    Vec<PostgresEvent>,
+    Vec<PostgresEventAttribute>,
+    Vec<PostgresCompactedEvent>,
+);
+
+#[async_trait]
+impl Processable for EventsPubSubPublisher {
+    type Input = EventRows;
+    type Output = EventRows;
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        input: TransactionContext<EventRows>,
+    ) -> Result<Option<TransactionContext<EventRows>>, ProcessorError> {
+        let Some(config) = &self.config else {
+            return Ok(Some(input));
+        };
+        let (events, event_attributes, compacted_events) = &input.data;
+        let messages =
+            batch_messages(config, events, event_attributes, compacted_events).map_err(|e| {
+                ProcessorError::ProcessError {
+                    message: format!("Failed to serialize events for Pub/Sub: {e:?}"),
+                }
+            })?;
+        let mut awaiters = Vec::with_capacity(messages.len());
+        for (topic, message) in messages {
+            awaiters.push(self.publishers[topic].publish(message).await);
+        }
+        for awaiter in awaiters {
+            awaiter
+                .get()
+                .await
+                .map_err(|e| ProcessorError::ProcessError {
+                    message: format!(
+                        "Failed to publish events versions {} to {} to Pub/Sub: {e:?}",
+                        input.metadata.start_version, input.metadata.end_version,
+                    ),
+                })?;
+        }
+        Ok(Some(input))
+    }
+}
+
+impl AsyncStep for EventsPubSubPublisher {}
+
+impl NamedStep for EventsPubSubPublisher {
+    fn name(&self) -> String {
+        "EventsPubSubPublisher".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(account_address: &str, type_: &str, event_index: i64) -> PostgresEvent {
+        PostgresEvent {
+            sequence_number: 0,
+            creation_number: 0,
+            account_address: account_address.to_string(),
+            transaction_version: 10,
+            transaction_block_height: 1,
+            type_: type_.to_string(),
+            data: json!({}),
+            event_index,
+            indexed_type: type_.to_string(),
+            module_address: None,
+            module_name: None,
+            struct_name: None,
+            generic_args: None,
+        }
+    }
+
+    #[test]
+    fn test_batch_messages() {
+        let events = vec![
+            event(
+                "0xa",
+                "0x1::coin::CoinDeposit<0x1::cedra_coin::CedraCoin>",
+                0,
+            ),
+            event("0xb", "0x1::fungible_asset::Withdraw", 1),
+        ];
+        let attributes = vec![PostgresEventAttribute {
+            transaction_version: 10,
+            event_index: 1,
+            key: "amount".to_string(),
+            value: "5".to_string(),
+        }];
+
+        let per_table = EventPubSubConfig::PerTable {
+            topics: HashMap::from([
+                ("events".to_string(), "events-topic".to_string()),
+                (
+                    "event_attributes".to_string(),
+                    "attributes-topic".to_string(),
+                ),
+            ]),
+        };
+        let messages = batch_messages(&per_table, &events, &attributes, &[]).unwrap();
+        let routed: Vec<(&str, &str)> = messages
+            .iter()
+            .map(|(topic, message)| (*topic, message.ordering_key.as_str()))
+            .collect();
+        assert_eq!(routed, vec![
+            ("events-topic", "0xa"),
+            ("events-topic", "0xb"),
+            ("attributes-topic", "0xb"),
+        ]);
+        assert_eq!(messages[2].1.attributes["table"], "event_attributes");
+
+        let per_event_type = EventPubSubConfig::PerEventType {
+            topics: HashMap::from([("0x1::coin::CoinDeposit".to_string(), "deposits".to_string())]),
+            default_topic: None,
+        };
+        let messages = batch_messages(&per_event_type, &events, &attributes, &[]).unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].0, "deposits");
+        assert_eq!(messages[0].1.attributes["transaction_version"], "10");
+    }
+}
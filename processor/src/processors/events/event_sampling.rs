@@ -0,0 +1,149 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::processors::events::events_model::PostgresEvent;
+use ahash::AHashSet;
+use serde::{Deserialize, Serialize};
+
+/// Keeps every event of the listed types and only a fraction of all other events, for
+/// deployments that want signal without storing every event.
+///
+/// Example:
+/// ```yaml
+/// sampling:
+///   keep_event_types:
+///     - "0x1::fungible_asset::Deposit"
+///     - "0x1::fungible_asset::Withdraw"
+///   sample_rate: 0.01
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventSamplingConfig {
+    /// Event types that are always kept. A type without generic type arguments also matches
+    /// its generic instantiations.
+    #[serde(default)]
+    pub keep_event_types: Vec<String>,
+    /// Fraction of the other events to keep, from 0.0 (none) to 1.0 (all).
+    pub sample_rate: f64,
+}
+
+/// Decides which events are kept. The decision only depends on the event's version and index,
+/// so reprocessing a range keeps the same events.
+#[derive(Clone, Debug)]
+pub struct EventSampler {
+    keep_event_types: AHashSet<String>,
+    threshold: u64,
+}
+
+impl EventSampler {
+    pub fn new(config: &EventSamplingConfig) -> anyhow::Result<Self> {
+        if !(0.0..=1.0).contains(&config.sample_rate) {
+            anyhow::bail!(
+                "Invalid event sample_rate {}: must be between 0.0 and 1.0",
+                config.sample_rate
+            );
+        }
+        Ok(Self {
+            keep_event_types: config.keep_event_types.iter().cloned().collect(),
+            threshold: (config.sample_rate * u64::MAX as f64) as u64,
+        })
+    }
+
+    pub fn keep(&self, event: &PostgresEvent) -> bool {
+        if self.keep_event_types.contains(&event.type_) {
+            return true;
+        }
+        if let Some((base_type, _)) = event.type_.split_once('<') {
+            if self.keep_event_types.contains(base_type) {
+                return true;
+            }
+        }
+        // 1.0 rounds to u64::MAX, which a hash can equal
+        self.threshold == u64::MAX
+            || mix(event.transaction_version as u64, event.event_index as u64) < self.threshold
+    }
+}
+
+/// splitmix64 finalizer over the event's position, spreads consecutive positions uniformly.
+fn mix(transaction_version: u64, event_index: u64) -> u64 {
+    let mut z = transaction_version
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        .wrapping_add(event_index);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(transaction_version: i64, event_index: i64, type_: &str) -> PostgresEvent {
+        PostgresEvent {
+            sequence_number: 0,
+            creation_number: 0,
+            account_address: "0x1".to_string(),
+            transaction_version,
+            transaction_block_height: 0,
+            type_: type_.to_string(),
+            data: serde_json::Value::Null,
+            event_index,
+            indexed_type: type_.to_string(),
+            module_address: None,
+            module_name: None,
+            struct_name: None,
+            generic_args: None,
+        }
+    }
+
+    #[test]
+    fn test_keeps_configured_types_and_samples_the_rest() {
+        let sampler = EventSampler::new(&EventSamplingConfig {
+            keep_event_types: vec![
+                "0x1::fungible_asset::Deposit".to_string(),
+                "0x1::coin::CoinDeposit".to_string(),
+            ],
+            sample_rate: 0.01,
+        })
+        .unwrap();
+
+        assert!(sampler.keep(&event(1, 0, "0x1::fungible_asset::Deposit")));
+        assert!(sampler.keep(&event(
+            1,
+            1,
+            "0x1::coin::CoinDeposit<0x1::cedra_coin::CedraCoin>"
+        )));
+
+        let kept = (0..10_000)
+            .filter(|version| sampler.keep(&event(*version, 0, "0x1::other::Event")))
+            .count();
+        assert!((50..=150).contains(&kept), "kept {kept} of 10000");
+        assert_eq!(
+            sampler.keep(&event(42, 3, "0x1::other::Event")),
+            sampler.keep(&event(42, 3, "0x1::other::Event"))
+        );
+    }
+
+    #[test]
+    fn test_sample_rate_bounds() {
+        let all = EventSampler::new(&EventSamplingConfig {
+            keep_event_types: vec![],
+            sample_rate: 1.0,
+        })
+        .unwrap();
+        let none = EventSampler::new(&EventSamplingConfig {
+            keep_event_types: vec![],
+            sample_rate: 0.0,
+        })
+        .unwrap();
+        for version in 0..1000 {
+            assert!(all.keep(&event(version, 0, "0x1::other::Event")));
+            assert!(!none.keep(&event(version, 0, "0x1::other::Event")));
+        }
+        assert!(EventSampler::new(&EventSamplingConfig {
+            keep_event_types: vec![],
+            sample_rate: 1.5,
+        })
+        .is_err());
+    }
+}
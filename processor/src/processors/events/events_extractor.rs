@@ -1,4 +1,10 @@
-use crate::processors::events::{events_model::PostgresEvent, parse_events};
+use crate::processors::events::{
+    event_attributes_model::{EventAttributeRules, PostgresEventAttribute},
+    event_compaction::{EventCompactor, PostgresCompactedEvent},
+    event_sampling::EventSampler,
+    events_model::{Event, PostgresEvent},
+    parse_events,
+};
 use cedra_indexer_processor_sdk::{
     cedra_protos::transaction::v1::Transaction,
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
@@ -10,27 +16,76 @@ use rayon::prelude::*;
 
 pub struct EventsExtractor
 where
-    Self: Sized + Send + 'static, {}
+    Self: Sized + Send + 'static,
+{
+    attribute_rules: EventAttributeRules,
+    sampler: Option<EventSampler>,
+    compactor: Option<EventCompactor>,
+}
+
+impl EventsExtractor {
+    pub fn new(
+        attribute_rules: EventAttributeRules,
+        sampler: Option<EventSampler>,
+        compactor: Option<EventCompactor>,
+    ) -> Self {
+        Self {
+            attribute_rules,
+            sampler,
+            compactor,
+        }
+    }
+}
 
 #[async_trait]
 impl Processable for EventsExtractor {
     type Input = Vec<Transaction>;
-    type Output = Vec<PostgresEvent>;
+    type Output = (
+        Vec<PostgresEvent>,
+        Vec<PostgresEventAttribute>,
+        Vec<PostgresCompactedEvent>,
+    );
     type RunType = AsyncRunType;
 
     async fn process(
         &mut self,
         item: TransactionContext<Vec<Transaction>>,
-    ) -> Result<Option<TransactionContext<Vec<PostgresEvent>>>, ProcessorError> {
-        let events: Vec<PostgresEvent> = item
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        let raw_events: Vec<Event> = item
             .data
             .par_iter()
             .map(|txn| parse_events(txn, self.name().as_str()))
             .flatten()
+            .collect();
+        // Compacted before sampling, so the last event of a window is never sampled out
+        let compacted_events = match &self.compactor {
+            Some(compactor) => compactor.compact(&raw_events),
+            None => vec![],
+        };
+        let events: Vec<PostgresEvent> = raw_events
+            .into_par_iter()
+            .filter(|e| {
+                self.compactor
+                    .as_ref()
+                    .map_or(true, |compactor| compactor.keep_raw(e))
+            })
             .map(|e| e.into())
+            .filter(|e| {
+                self.sampler
+                    .as_ref()
+                    .map_or(true, |sampler| sampler.keep(e))
+            })
             .collect();
+        let event_attributes: Vec<PostgresEventAttribute> = if self.attribute_rules.is_empty() {
+            vec![]
+        } else {
+            events
+                .par_iter()
+                .flat_map(|event| self.attribute_rules.extract(event))
+                .collect()
+        };
         Ok(Some(TransactionContext {
-            data: events,
+            data: (events, event_attributes, compacted_events),
             metadata: item.metadata,
         }))
     }
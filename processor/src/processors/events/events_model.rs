@@ -20,6 +20,39 @@ use tracing::warn;
 
 /// P99 currently is 303 so using 300 as a safe max length
 pub const EVENT_TYPE_MAX_LENGTH: usize = 300;
+/// Max length of the module and struct names of an event type
+pub const EVENT_TYPE_IDENTIFIER_MAX_LENGTH: usize = 255;
+
+/// Struct type of an event split into its parts, so events can be queried by module without
+/// matching on `indexed_type`. Events of other types, e.g. `vector<u8>`, have no parts.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EventTypeParts {
+    pub module_address: Option<String>,
+    pub module_name: Option<String>,
+    pub struct_name: Option<String>,
+    /// Type arguments as written in the type, e.g. `0x1::cedra_coin::CedraCoin` for
+    /// `0x1::coin::CoinDeposit<0x1::cedra_coin::CedraCoin>`
+    pub generic_args: Option<String>,
+}
+
+impl EventTypeParts {
+    pub fn from_type_str(type_str: &str) -> Self {
+        let (base, generic_args) = match type_str.split_once('<') {
+            Some((base, generic_args)) => (base, generic_args.strip_suffix('>')),
+            None => (type_str, None),
+        };
+        let mut parts = base.splitn(3, "::");
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(address), Some(module_name), Some(struct_name)) => Self {
+                module_address: Some(standardize_address(address)),
+                module_name: Some(truncate_str(module_name, EVENT_TYPE_IDENTIFIER_MAX_LENGTH)),
+                struct_name: Some(truncate_str(struct_name, EVENT_TYPE_IDENTIFIER_MAX_LENGTH)),
+                generic_args: generic_args.map(str::to_string),
+            },
+            _ => Self::default(),
+        }
+    }
+}
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Event {
@@ -35,6 +68,10 @@ pub struct Event {
     pub block_timestamp: Option<chrono::NaiveDateTime>,
     pub type_tag_bytes: Option<i64>,
     pub total_bytes: Option<i64>,
+    pub module_address: Option<String>,
+    pub module_name: Option<String>,
+    pub struct_name: Option<String>,
+    pub generic_args: Option<String>,
 }
 
 impl Event {
@@ -49,6 +86,7 @@ impl Event {
         let type_tag_bytes = size_info.map_or(0, |info| info.type_tag_bytes as i64);
         let total_bytes = size_info.map_or(0, |info| info.total_bytes as i64);
         let event_type = event.type_str.to_string();
+        let type_parts = EventTypeParts::from_type_str(&event_type);
 
         Event {
             sequence_number: event.sequence_number as i64,
@@ -65,6 +103,10 @@ impl Event {
             block_timestamp,
             type_tag_bytes: Some(type_tag_bytes),
             total_bytes: Some(total_bytes),
+            module_address: type_parts.module_address,
+            module_name: type_parts.module_name,
+            struct_name: type_parts.struct_name,
+            generic_args: type_parts.generic_args,
         }
     }
 }
@@ -139,6 +181,10 @@ pub struct ParquetEvent {
     pub total_bytes: i64,
     #[allocative(skip)]
     pub block_timestamp: chrono::NaiveDateTime,
+    pub module_address: Option<String>,
+    pub module_name: Option<String>,
+    pub struct_name: Option<String>,
+    pub generic_args: Option<String>,
 }
 
 impl NamedTable for ParquetEvent {
@@ -166,6 +212,10 @@ impl From<Event> for ParquetEvent {
             type_tag_bytes: raw_event.type_tag_bytes.unwrap_or(0),
             total_bytes: raw_event.total_bytes.unwrap_or(0),
             block_timestamp: raw_event.block_timestamp.unwrap(),
+            module_address: raw_event.module_address,
+            module_name: raw_event.module_name,
+            struct_name: raw_event.struct_name,
+            generic_args: raw_event.generic_args,
         }
     }
 }
@@ -183,6 +233,10 @@ pub struct PostgresEvent {
     pub data: serde_json::Value,
     pub event_index: i64,
     pub indexed_type: String,
+    pub module_address: Option<String>,
+    pub module_name: Option<String>,
+    pub struct_name: Option<String>,
+    pub generic_args: Option<String>,
 }
 
 impl From<Event> for PostgresEvent {
@@ -197,6 +251,36 @@ impl From<Event> for PostgresEvent {
             data: serde_json::from_str(&raw_event.data).unwrap(),
             event_index: raw_event.event_index,
             indexed_type: raw_event.indexed_type,
+            module_address: raw_event.module_address,
+            module_name: raw_event.module_name,
+            struct_name: raw_event.struct_name,
+            generic_args: raw_event.generic_args,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_type_parts() {
+        assert_eq!(
+            EventTypeParts::from_type_str("0x1::coin::CoinDeposit<0x1::cedra_coin::CedraCoin>"),
+            EventTypeParts {
+                module_address: Some(standardize_address("0x1")),
+                module_name: Some("coin".to_string()),
+                struct_name: Some("CoinDeposit".to_string()),
+                generic_args: Some("0x1::cedra_coin::CedraCoin".to_string()),
+            }
+        );
+        assert_eq!(
+            EventTypeParts::from_type_str("0x1::fungible_asset::Deposit").generic_args,
+            None
+        );
+        assert_eq!(
+            EventTypeParts::from_type_str("vector<u8>"),
+            EventTypeParts::default()
+        );
+    }
+}
@@ -1,32 +1,61 @@
 use crate::{
     config::{
-        db_config::DbConfig, indexer_processor_config::IndexerProcessorConfig,
-        processor_config::ProcessorConfig,
+        db_config::DbConfig,
+        indexer_processor_config::IndexerProcessorConfig,
+        processor_config::{DefaultProcessorConfig, ProcessorConfig},
+    },
+    db::{
+        connection_pool::new_postgres_pool, partitioning::PartitionStep,
+        schema_drift::run_migrations_and_check_drift,
     },
     processors::{
-        events::{events_extractor::EventsExtractor, events_storer::EventsStorer},
+        events::{
+            event_attributes_model::{EventAttributeRule, EventAttributeRules},
+            event_compaction::{EventCompactionConfig, EventCompactor},
+            event_pubsub::{EventPubSubConfig, EventsPubSubPublisher},
+            event_sampling::{EventSampler, EventSamplingConfig},
+            events_extractor::EventsExtractor,
+            events_storer::EventsStorer,
+        },
         processor_status_saver::{
             get_end_version, get_starting_version, PostgresProcessorStatusSaver,
         },
     },
-    MIGRATIONS,
+    utils::{
+        logging::BatchSpanStep,
+        supervisor::supervise_step_tasks,
+        transaction_source::{check_chain_id, TransactionSourceStep},
+    },
 };
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    cedra_indexer_transaction_stream::TransactionStreamConfig,
     builder::ProcessorBuilder,
-    common_steps::{
-        TransactionStreamStep, VersionTrackerStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
-    },
-    postgres::utils::{
-        checkpoint::PostgresChainIdChecker,
-        database::{new_db_pool, run_migrations, ArcDbPool},
-    },
+    common_steps::{VersionTrackerStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS},
+    postgres::utils::database::ArcDbPool,
     traits::{processor_trait::ProcessorTrait, IntoRunnableStep},
-    utils::chain_id_check::check_or_update_chain_id,
 };
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventsProcessorConfig {
+    #[serde(flatten)]
+    pub default_config: DefaultProcessorConfig,
+    // Rules for pulling fields out of events.data into event_attributes
+    #[serde(default)]
+    pub attribute_rules: Vec<EventAttributeRule>,
+    // Only keep a sample of the events, all events are kept if unset
+    #[serde(default)]
+    pub sampling: Option<EventSamplingConfig>,
+    // Keeps the last event per account and type in each window in events_compacted
+    #[serde(default)]
+    pub compaction: Option<EventCompactionConfig>,
+    // Publishes the processed events to Pub/Sub topics per table or per event type
+    #[serde(default)]
+    pub pubsub: Option<EventPubSubConfig>,
+}
+
 pub struct EventsProcessor {
     pub config: IndexerProcessorConfig,
     pub db_pool: ArcDbPool,
@@ -36,12 +65,7 @@ impl EventsProcessor {
     pub async fn new(config: IndexerProcessorConfig) -> Result<Self> {
         match config.db_config {
             DbConfig::PostgresConfig(ref postgres_config) => {
-                let conn_pool = new_db_pool(
-                    &postgres_config.connection_string,
-                    Some(postgres_config.db_pool_size),
-                )
-                .await
-                .map_err(|e| {
+                let conn_pool = new_postgres_pool(postgres_config).await.map_err(|e| {
                     anyhow::anyhow!(
                         "Failed to create connection pool for PostgresConfig: {:?}",
                         e
@@ -70,12 +94,7 @@ impl ProcessorTrait for EventsProcessor {
     async fn run_processor(&self) -> Result<()> {
         // Run migrations
         if let DbConfig::PostgresConfig(ref postgres_config) = self.config.db_config {
-            run_migrations(
-                postgres_config.connection_string.clone(),
-                self.db_pool.clone(),
-                MIGRATIONS,
-            )
-            .await;
+            run_migrations_and_check_drift(postgres_config, self.db_pool.clone()).await?;
         }
 
         //  Merge the starting version from config and the latest processed version from the DB
@@ -85,11 +104,7 @@ impl ProcessorTrait for EventsProcessor {
         );
 
         // Check and update the ledger chain id to ensure we're indexing the correct chain
-        check_or_update_chain_id(
-            &self.config.transaction_stream_config,
-            &PostgresChainIdChecker::new(self.db_pool.clone()),
-        )
-        .await?;
+        check_chain_id(&self.config, self.db_pool.clone()).await?;
 
         let processor_config = match self.config.processor_config.clone() {
             ProcessorConfig::EventsProcessor(processor_config) => processor_config,
@@ -100,30 +115,53 @@ impl ProcessorTrait for EventsProcessor {
                 ))
             },
         };
-        let channel_size = processor_config.channel_size;
+        let channel_size = processor_config.default_config.channel_size;
+        let attribute_rules = EventAttributeRules::new(&processor_config.attribute_rules)?;
+        let sampler = processor_config
+            .sampling
+            .as_ref()
+            .map(EventSampler::new)
+            .transpose()?;
+        let compactor = processor_config
+            .compaction
+            .as_ref()
+            .map(EventCompactor::new)
+            .transpose()?;
 
         // Define processor steps
-        let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
-            starting_version,
-            request_ending_version: ending_version,
-            ..self.config.transaction_stream_config.clone()
-        })
-        .await?;
-        let events_extractor = EventsExtractor {};
-        let events_storer = EventsStorer::new(self.db_pool.clone(), processor_config);
+        let transaction_stream =
+            TransactionSourceStep::new(&self.config, starting_version, ending_version).await?;
+        let events_extractor = EventsExtractor::new(attribute_rules, sampler, compactor);
+        let partition_step =
+            PartitionStep::new(&self.config, self.db_pool.clone(), &["events"]).await?;
+        let pubsub_publisher = EventsPubSubPublisher::new(processor_config.pubsub.clone()).await?;
+        let events_storer = EventsStorer::new(
+            self.db_pool.clone(),
+            self.config.table_names(),
+            processor_config.default_config,
+        );
         let version_tracker = VersionTrackerStep::new(
             PostgresProcessorStatusSaver::new(self.config.clone(), self.db_pool.clone()),
             DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
         );
 
         // Connect processor steps together
-        let (_, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
+        let (processor_builder, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
             transaction_stream.into_runnable_step(),
         )
-        .connect_to(events_extractor.into_runnable_step(), channel_size)
-        .connect_to(events_storer.into_runnable_step(), channel_size)
+        .connect_to(
+            BatchSpanStep::new(events_extractor, self.name()).into_runnable_step(),
+            channel_size,
+        )
+        .connect_to(partition_step.into_runnable_step(), channel_size)
+        .connect_to(pubsub_publisher.into_runnable_step(), channel_size)
+        .connect_to(
+            BatchSpanStep::new(events_storer, self.name()).into_runnable_step(),
+            channel_size,
+        )
         .connect_to(version_tracker.into_runnable_step(), channel_size)
         .end_and_return_output_receiver(channel_size);
+        supervise_step_tasks(processor_builder.join_handles);
 
         // (Optional) Parse the results
         loop {
@@ -1,11 +1,15 @@
 use crate::{
     config::processor_config::DefaultProcessorConfig,
-    processors::events::events_model::PostgresEvent,
+    processors::events::{
+        event_attributes_model::PostgresEventAttribute, event_compaction::PostgresCompactedEvent,
+        events_model::PostgresEvent,
+    },
+    utils::{table_names::TableNames, table_stats::execute_in_chunks_with_stats},
 };
 use ahash::AHashMap;
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    postgres::utils::database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
@@ -18,13 +22,19 @@ where
     Self: Sized + Send + 'static,
 {
     conn_pool: ArcDbPool,
+    table_names: TableNames,
     processor_config: DefaultProcessorConfig,
 }
 
 impl EventsStorer {
-    pub fn new(conn_pool: ArcDbPool, processor_config: DefaultProcessorConfig) -> Self {
+    pub fn new(
+        conn_pool: ArcDbPool,
+        table_names: TableNames,
+        processor_config: DefaultProcessorConfig,
+    ) -> Self {
         Self {
             conn_pool,
+            table_names,
             processor_config,
         }
     }
@@ -32,43 +42,73 @@ impl EventsStorer {
 
 #[async_trait]
 impl Processable for EventsStorer {
-    type Input = Vec<PostgresEvent>;
+    type Input = (
+        Vec<PostgresEvent>,
+        Vec<PostgresEventAttribute>,
+        Vec<PostgresCompactedEvent>,
+    );
     type Output = ();
     type RunType = AsyncRunType;
 
     async fn process(
         &mut self,
-        events: TransactionContext<Vec<PostgresEvent>>,
+        input: TransactionContext<Self::Input>,
     ) -> Result<Option<TransactionContext<()>>, ProcessorError> {
+        let (events, event_attributes, compacted_events) = input.data;
         let per_table_chunk_sizes: AHashMap<String, usize> =
             self.processor_config.per_table_chunk_sizes.clone();
-        let execute_res = execute_in_chunks(
+        let events_res = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_events_query,
-            &events.data,
+            &events,
+            "events",
             get_config_table_chunk_size::<PostgresEvent>("events", &per_table_chunk_sizes),
-        )
-        .await;
-        match execute_res {
-            Ok(_) => {
-                debug!(
-                    "Events version [{}, {}] stored successfully",
-                    events.metadata.start_version, events.metadata.end_version
-                );
-                Ok(Some(TransactionContext {
-                    data: (),
-                    metadata: events.metadata,
-                }))
-            },
-            Err(e) => Err(ProcessorError::DBStoreError {
-                message: format!(
-                    "Failed to store events versions {} to {}: {:?}",
-                    events.metadata.start_version, events.metadata.end_version, e,
-                ),
-                // TODO: fix it with a debug_query.
-                query: None,
-            }),
+        );
+        let event_attributes_res = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_event_attributes_query,
+            &event_attributes,
+            "event_attributes",
+            get_config_table_chunk_size::<PostgresEventAttribute>(
+                "event_attributes",
+                &per_table_chunk_sizes,
+            ),
+        );
+        let compacted_events_res = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_compacted_events_query,
+            &compacted_events,
+            "events_compacted",
+            get_config_table_chunk_size::<PostgresCompactedEvent>(
+                "events_compacted",
+                &per_table_chunk_sizes,
+            ),
+        );
+        let (events_res, event_attributes_res, compacted_events_res) =
+            tokio::join!(events_res, event_attributes_res, compacted_events_res);
+        for res in [events_res, event_attributes_res, compacted_events_res] {
+            if let Err(e) = res {
+                return Err(ProcessorError::DBStoreError {
+                    message: format!(
+                        "Failed to store events versions {} to {}: {:?}",
+                        input.metadata.start_version, input.metadata.end_version, e,
+                    ),
+                    // TODO: fix it with a debug_query.
+                    query: None,
+                });
+            }
         }
+        debug!(
+            "Events version [{}, {}] stored successfully",
+            input.metadata.start_version, input.metadata.end_version
+        );
+        Ok(Some(TransactionContext {
+            data: (),
+            metadata: input.metadata,
+        }))
     }
 }
 
@@ -84,6 +124,7 @@ use crate::schema;
 use diesel::{
     pg::{upsert::excluded, Pg},
     query_builder::QueryFragment,
+    query_dsl::methods::FilterDsl,
     ExpressionMethods,
 };
 
@@ -99,5 +140,48 @@ pub fn insert_events_query(
         .set((
             inserted_at.eq(excluded(inserted_at)),
             indexed_type.eq(excluded(indexed_type)),
+            module_address.eq(excluded(module_address)),
+            module_name.eq(excluded(module_name)),
+            struct_name.eq(excluded(struct_name)),
+            generic_args.eq(excluded(generic_args)),
+        ))
+}
+
+pub fn insert_event_attributes_query(
+    items_to_insert: Vec<PostgresEventAttribute>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::event_attributes::dsl::*;
+
+    diesel::insert_into(schema::event_attributes::table)
+        .values(items_to_insert)
+        .on_conflict((transaction_version, event_index, key))
+        .do_update()
+        .set((
+            value.eq(excluded(value)),
+            inserted_at.eq(excluded(inserted_at)),
+        ))
+}
+
+/// Keeps the latest event of a window, windows can span batches.
+pub fn insert_compacted_events_query(
+    items_to_insert: Vec<PostgresCompactedEvent>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::events_compacted::dsl::*;
+
+    diesel::insert_into(schema::events_compacted::table)
+        .values(items_to_insert)
+        .on_conflict((account_address, type_, window_start))
+        .do_update()
+        .set((
+            transaction_version.eq(excluded(transaction_version)),
+            event_index.eq(excluded(event_index)),
+            sequence_number.eq(excluded(sequence_number)),
+            creation_number.eq(excluded(creation_number)),
+            transaction_block_height.eq(excluded(transaction_block_height)),
+            data.eq(excluded(data)),
+            indexed_type.eq(excluded(indexed_type)),
+            transaction_timestamp.eq(excluded(transaction_timestamp)),
+            inserted_at.eq(excluded(inserted_at)),
         ))
+        .filter(transaction_version.le(excluded(transaction_version)))
 }
@@ -1,3 +1,7 @@
+pub mod event_attributes_model;
+pub mod event_compaction;
+pub mod event_pubsub;
+pub mod event_sampling;
 pub mod events_extractor;
 pub mod events_processor;
 pub mod events_storer;
@@ -9,10 +9,13 @@ use super::coin_utils::{CoinInfoType, CoinResource};
 use crate::{
     processors::fungible_asset::fungible_asset_models::v2_fungible_asset_activities::EventToCoinType,
     schema::{coin_balances, current_coin_balances},
+    utils::dedup::CurrentTableRow,
 };
 use ahash::AHashMap;
 use cedra_indexer_processor_sdk::{
-    cedra_protos::transaction::v1::WriteResource, utils::convert::standardize_address,
+    cedra_indexer_transaction_stream::utils::time::parse_timestamp,
+    cedra_protos::transaction::v1::{write_set_change::Change, Transaction, WriteResource},
+    utils::convert::standardize_address,
 };
 use bigdecimal::BigDecimal;
 use field_count::FieldCount;
@@ -42,6 +45,7 @@ pub struct CurrentCoinBalance {
     pub amount: BigDecimal,
     pub last_transaction_version: i64,
     pub last_transaction_timestamp: chrono::NaiveDateTime,
+    pub is_frozen: bool,
 }
 
 impl CoinBalance {
@@ -76,6 +80,7 @@ impl CoinBalance {
                     amount: inner.coin.value.clone(),
                     last_transaction_version: txn_version,
                     last_transaction_timestamp: txn_timestamp,
+                    is_frozen: inner.frozen,
                 };
                 let event_to_coin_mapping: EventToCoinType = AHashMap::from([
                     (
@@ -97,3 +102,60 @@ impl CoinBalance {
         }
     }
 }
+
+impl CurrentCoinBalance {
+    /// Every CoinStore write in the transactions, in version order
+    pub fn from_transactions(transactions: &[Transaction]) -> Vec<Self> {
+        let mut current_coin_balances = vec![];
+        for txn in transactions {
+            let txn_version = txn.version as i64;
+            let txn_timestamp =
+                parse_timestamp(txn.timestamp.as_ref().unwrap(), txn_version).naive_utc();
+            let changes = &txn
+                .info
+                .as_ref()
+                .unwrap_or_else(|| {
+                    panic!("Transaction info doesn't exist! Transaction {txn_version}")
+                })
+                .changes;
+
+            for (index, wsc) in changes.iter().enumerate() {
+                let Some(Change::WriteResource(write_resource)) = wsc.change.as_ref() else {
+                    continue;
+                };
+                match CoinBalance::from_write_resource(
+                    write_resource,
+                    txn_version,
+                    txn_timestamp,
+                    index as i64,
+                ) {
+                    Ok(Some((_, current_coin_balance, _))) => {
+                        current_coin_balances.push(current_coin_balance)
+                    },
+                    Ok(None) => {},
+                    Err(e) => {
+                        tracing::error!(
+                            transaction_version = txn_version,
+                            index = index,
+                            error = ?e,
+                            "[Parser] error parsing coin store"
+                        );
+                    },
+                }
+            }
+        }
+        current_coin_balances
+    }
+}
+
+impl CurrentTableRow for CurrentCoinBalance {
+    type PrimaryKey = (String, String);
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        (self.owner_address.clone(), self.coin_type_hash.clone())
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
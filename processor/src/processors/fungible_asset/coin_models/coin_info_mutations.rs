@@ -0,0 +1,218 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use super::coin_utils::{CoinInfoType, CoinResource};
+use crate::{
+    db::resources::COIN_ADDR, processors::token_v2::token_models::token_utils::TypeInfo,
+    schema::coin_info_mutations,
+};
+use ahash::AHashMap;
+use cedra_indexer_processor_sdk::{
+    cedra_indexer_transaction_stream::utils::time::parse_timestamp,
+    cedra_protos::transaction::v1::{transaction::TxnData, write_set_change::Change, Transaction},
+    postgres::utils::database::DbPoolConnection,
+    utils::{
+        convert::{standardize_address, truncate_str},
+        extract::hash_str,
+    },
+};
+use const_format::formatcp;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+/// CoinInfo was written with a name, symbol or decimals that differ from the last ones recorded.
+/// The first row of a coin is the baseline and has no previous values.
+pub const METADATA_MUTATION: &str = "metadata";
+/// The coin got its paired fungible asset, the start of its migration to FA.
+pub const FA_PAIR_CREATION_MUTATION: &str = "fa_pair_creation";
+
+const PAIR_CREATION_EVENT_TYPE: &str = formatcp!("{COIN_ADDR}::coin::PairCreation");
+const COIN_TYPE_MAX_LENGTH: usize = 5000;
+
+type CoinTypeHash = String;
+
+/// Name, symbol and decimals of a coin
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CoinMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: i32,
+}
+
+/// Last recorded metadata of every coin, used to tell which CoinInfo writes change it
+pub type LatestCoinMetadata = AHashMap<CoinTypeHash, CoinMetadata>;
+
+#[derive(Deserialize)]
+struct PairCreation {
+    coin_type: TypeInfo,
+    fungible_asset_metadata_address: String,
+}
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, coin_type_hash, mutation_type))]
+#[diesel(table_name = coin_info_mutations)]
+pub struct PostgresCoinInfoMutation {
+    pub transaction_version: i64,
+    pub coin_type_hash: String,
+    pub mutation_type: String,
+    pub coin_type: String,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<i32>,
+    pub previous_name: Option<String>,
+    pub previous_symbol: Option<String>,
+    pub previous_decimals: Option<i32>,
+    pub fungible_asset_metadata_address: Option<String>,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl PostgresCoinInfoMutation {
+    /// Transactions must be in version order, `latest_metadata` is updated as mutations are found.
+    pub fn from_transactions(
+        transactions: &[Transaction],
+        latest_metadata: &mut LatestCoinMetadata,
+    ) -> Vec<Self> {
+        let mut mutations = vec![];
+        for txn in transactions {
+            let txn_version = txn.version as i64;
+            let txn_timestamp =
+                parse_timestamp(txn.timestamp.as_ref().unwrap(), txn_version).naive_utc();
+            let changes = &txn
+                .info
+                .as_ref()
+                .unwrap_or_else(|| {
+                    panic!("Transaction info doesn't exist! Transaction {txn_version}")
+                })
+                .changes;
+
+            for (index, wsc) in changes.iter().enumerate() {
+                let Some(Change::WriteResource(write_resource)) = wsc.change.as_ref() else {
+                    continue;
+                };
+                let coin_info = match CoinResource::from_write_resource(
+                    write_resource,
+                    txn_version,
+                    txn_timestamp,
+                ) {
+                    Ok(Some(CoinResource::CoinInfoResource(inner))) => inner,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        tracing::error!(
+                            transaction_version = txn_version,
+                            index = index,
+                            error = ?e,
+                            "[Parser] error parsing coin info"
+                        );
+                        continue;
+                    },
+                };
+                let coin_info_type = CoinInfoType::from_move_type(
+                    &write_resource.r#type.as_ref().unwrap().generic_type_params[0],
+                    write_resource.type_str.as_ref(),
+                    txn_version,
+                    index as i64,
+                );
+                let metadata = CoinMetadata {
+                    name: coin_info.get_name_trunc(),
+                    symbol: coin_info.get_symbol_trunc(),
+                    decimals: coin_info.decimals,
+                };
+                let coin_type_hash = coin_info_type.to_hash();
+                let previous = latest_metadata.get(&coin_type_hash);
+                if previous == Some(&metadata) {
+                    continue;
+                }
+                mutations.push(Self {
+                    transaction_version: txn_version,
+                    coin_type_hash: coin_type_hash.clone(),
+                    mutation_type: METADATA_MUTATION.to_string(),
+                    coin_type: coin_info_type.get_coin_type_trunc(),
+                    name: Some(metadata.name.clone()),
+                    symbol: Some(metadata.symbol.clone()),
+                    decimals: Some(metadata.decimals),
+                    previous_name: previous.map(|p| p.name.clone()),
+                    previous_symbol: previous.map(|p| p.symbol.clone()),
+                    previous_decimals: previous.map(|p| p.decimals),
+                    fungible_asset_metadata_address: None,
+                    transaction_timestamp: txn_timestamp,
+                });
+                latest_metadata.insert(coin_type_hash, metadata);
+            }
+
+            let Some(TxnData::User(user_txn)) = txn.txn_data.as_ref() else {
+                continue;
+            };
+            for event in &user_txn.events {
+                if event.type_str != PAIR_CREATION_EVENT_TYPE {
+                    continue;
+                }
+                let pair_creation: PairCreation = match serde_json::from_str(&event.data) {
+                    Ok(pair_creation) => pair_creation,
+                    Err(e) => {
+                        tracing::error!(
+                            transaction_version = txn_version,
+                            error = ?e,
+                            "[Parser] error parsing coin pair creation event"
+                        );
+                        continue;
+                    },
+                };
+                // Formatted like the coin type in CoinInfo's type string, so the hashes match
+                let coin_type = pair_creation.coin_type.to_string();
+                mutations.push(Self {
+                    transaction_version: txn_version,
+                    coin_type_hash: hash_str(&coin_type),
+                    mutation_type: FA_PAIR_CREATION_MUTATION.to_string(),
+                    coin_type: truncate_str(&coin_type, COIN_TYPE_MAX_LENGTH),
+                    name: None,
+                    symbol: None,
+                    decimals: None,
+                    previous_name: None,
+                    previous_symbol: None,
+                    previous_decimals: None,
+                    fungible_asset_metadata_address: Some(standardize_address(
+                        &pair_creation.fungible_asset_metadata_address,
+                    )),
+                    transaction_timestamp: txn_timestamp,
+                });
+            }
+        }
+        mutations
+    }
+
+    /// Loads the last recorded metadata of every coin
+    pub async fn get_latest_metadata(
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<LatestCoinMetadata> {
+        let rows = coin_info_mutations::table
+            .filter(coin_info_mutations::mutation_type.eq(METADATA_MUTATION))
+            .distinct_on(coin_info_mutations::coin_type_hash)
+            .order((
+                coin_info_mutations::coin_type_hash,
+                coin_info_mutations::transaction_version.desc(),
+            ))
+            .select((
+                coin_info_mutations::coin_type_hash,
+                coin_info_mutations::name,
+                coin_info_mutations::symbol,
+                coin_info_mutations::decimals,
+            ))
+            .load::<(String, Option<String>, Option<String>, Option<i32>)>(conn)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(coin_type_hash, name, symbol, decimals)| {
+                Some((coin_type_hash, CoinMetadata {
+                    name: name?,
+                    symbol: symbol?,
+                    decimals: decimals?,
+                }))
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,135 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use super::coin_balances::CurrentCoinBalance;
+use crate::schema::coin_store_freeze_events;
+use ahash::AHashSet;
+use cedra_indexer_processor_sdk::postgres::utils::database::DbPoolConnection;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+type OwnerAddress = String;
+type CoinTypeHash = String;
+
+/// CoinStores frozen as of the last recorded freeze event, used to tell which CoinStore writes
+/// freeze or unfreeze them. CoinStores are created unfrozen, so unlisted ones aren't frozen.
+pub type FrozenCoinStores = AHashSet<(OwnerAddress, CoinTypeHash)>;
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, owner_address, coin_type_hash))]
+#[diesel(table_name = coin_store_freeze_events)]
+pub struct PostgresCoinStoreFreezeEvent {
+    pub transaction_version: i64,
+    pub owner_address: String,
+    pub coin_type_hash: String,
+    pub coin_type: String,
+    pub is_frozen: bool,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl PostgresCoinStoreFreezeEvent {
+    /// CoinStore writes that change its frozen flag. `coin_balances` must be in version order,
+    /// `frozen_coin_stores` is updated as changes are found.
+    pub fn from_coin_balances(
+        coin_balances: &[CurrentCoinBalance],
+        frozen_coin_stores: &mut FrozenCoinStores,
+    ) -> Vec<Self> {
+        let mut freeze_events = vec![];
+        for coin_balance in coin_balances {
+            let key = (
+                coin_balance.owner_address.clone(),
+                coin_balance.coin_type_hash.clone(),
+            );
+            let changed = if coin_balance.is_frozen {
+                frozen_coin_stores.insert(key)
+            } else {
+                frozen_coin_stores.remove(&key)
+            };
+            if changed {
+                freeze_events.push(Self {
+                    transaction_version: coin_balance.last_transaction_version,
+                    owner_address: coin_balance.owner_address.clone(),
+                    coin_type_hash: coin_balance.coin_type_hash.clone(),
+                    coin_type: coin_balance.coin_type.clone(),
+                    is_frozen: coin_balance.is_frozen,
+                    transaction_timestamp: coin_balance.last_transaction_timestamp,
+                });
+            }
+        }
+        freeze_events
+    }
+
+    /// Loads the CoinStores whose last recorded freeze event froze them
+    pub async fn get_frozen_coin_stores(
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<FrozenCoinStores> {
+        let rows = coin_store_freeze_events::table
+            .distinct_on((
+                coin_store_freeze_events::owner_address,
+                coin_store_freeze_events::coin_type_hash,
+            ))
+            .order((
+                coin_store_freeze_events::owner_address,
+                coin_store_freeze_events::coin_type_hash,
+                coin_store_freeze_events::transaction_version.desc(),
+            ))
+            .select((
+                coin_store_freeze_events::owner_address,
+                coin_store_freeze_events::coin_type_hash,
+                coin_store_freeze_events::is_frozen,
+            ))
+            .load::<(String, String, bool)>(conn)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .filter(|(_, _, is_frozen)| *is_frozen)
+            .map(|(owner_address, coin_type_hash, _)| (owner_address, coin_type_hash))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+
+    fn coin_balance(version: i64, owner_address: &str, is_frozen: bool) -> CurrentCoinBalance {
+        CurrentCoinBalance {
+            owner_address: owner_address.to_string(),
+            coin_type_hash: "hash".to_string(),
+            coin_type: "0x1::cedra_coin::CedraCoin".to_string(),
+            amount: BigDecimal::from(version),
+            last_transaction_version: version,
+            last_transaction_timestamp: chrono::NaiveDateTime::default(),
+            is_frozen,
+        }
+    }
+
+    #[test]
+    fn test_freeze_events_on_frozen_flag_changes() {
+        let mut frozen_coin_stores =
+            FrozenCoinStores::from([("0x2".to_string(), "hash".to_string())]);
+        let freeze_events = PostgresCoinStoreFreezeEvent::from_coin_balances(
+            &[
+                coin_balance(1, "0x1", false),
+                coin_balance(2, "0x1", true),
+                coin_balance(3, "0x1", true),
+                coin_balance(4, "0x2", true),
+                coin_balance(5, "0x2", false),
+                coin_balance(6, "0x1", false),
+            ],
+            &mut frozen_coin_stores,
+        );
+        let freeze_events: Vec<(i64, bool)> = freeze_events
+            .into_iter()
+            .map(|e| (e.transaction_version, e.is_frozen))
+            .collect();
+        assert_eq!(freeze_events, vec![(2, true), (5, false), (6, false)]);
+        assert!(frozen_coin_stores.is_empty());
+    }
+}
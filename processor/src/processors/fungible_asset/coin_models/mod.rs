@@ -3,6 +3,8 @@
 
 pub mod coin_activities;
 pub mod coin_balances;
+pub mod coin_info_mutations;
 pub mod coin_infos;
+pub mod coin_store_freeze_events;
 pub mod coin_supply;
 pub mod coin_utils;
@@ -1,19 +1,32 @@
-use crate::processors::fungible_asset::{
-    coin_models::coin_supply::CoinSupply,
-    fungible_asset_models::{
-        v2_fungible_asset_activities::PostgresFungibleAssetActivity,
-        v2_fungible_asset_balances::{
-            PostgresCurrentUnifiedFungibleAssetBalance, PostgresFungibleAssetBalance,
+use crate::{
+    processors::fungible_asset::{
+        coin_models::{
+            coin_balances::CurrentCoinBalance,
+            coin_info_mutations::{LatestCoinMetadata, PostgresCoinInfoMutation},
+            coin_store_freeze_events::{FrozenCoinStores, PostgresCoinStoreFreezeEvent},
+            coin_supply::CoinSupply,
         },
-        v2_fungible_asset_to_coin_mappings::{
-            FungibleAssetToCoinMapping, FungibleAssetToCoinMappings,
-            PostgresFungibleAssetToCoinMapping,
+        fungible_asset_models::{
+            v2_fungible_asset_activities::PostgresFungibleAssetActivity,
+            v2_fungible_asset_balances::{
+                PostgresCurrentUnifiedFungibleAssetBalance, PostgresFungibleAssetBalance,
+            },
+            v2_fungible_asset_to_coin_mappings::{
+                FungibleAssetToCoinMapping, FungibleAssetToCoinMappings,
+                PostgresFungibleAssetToCoinMapping,
+            },
+            v2_fungible_metadata::PostgresFungibleAssetMetadataModel,
         },
-        v2_fungible_metadata::PostgresFungibleAssetMetadataModel,
+        fungible_asset_processor_helpers::{
+            get_fa_to_coin_mapping, parse_v2_coin, FungibleAssetBatch,
+        },
+    },
+    utils::{
+        address_labels::{counterparty_label, AddressLabels},
+        price_oracle::PriceOracle,
     },
-    fungible_asset_processor_helpers::{get_fa_to_coin_mapping, parse_v2_coin},
 };
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
     cedra_protos::transaction::v1::Transaction,
@@ -24,18 +37,27 @@ use cedra_indexer_processor_sdk::{
 };
 use async_trait::async_trait;
 
-/// Extracts fungible asset events, metadata, balances, and v1 supply from transactions
+/// Extracts fungible asset events, metadata, balances, v1 supply, and coin store balances and
+/// freezes from transactions
 pub struct FungibleAssetExtractor
 where
     Self: Sized + Send + 'static,
 {
     pub fa_to_coin_mapping: FungibleAssetToCoinMappings,
+    pub latest_coin_metadata: LatestCoinMetadata,
+    pub frozen_coin_stores: FrozenCoinStores,
+    pub address_labels: Option<AddressLabels>,
+    pub price_oracle: Option<PriceOracle>,
 }
 
 impl FungibleAssetExtractor {
     pub fn new() -> Self {
         Self {
             fa_to_coin_mapping: AHashMap::new(),
+            latest_coin_metadata: AHashMap::new(),
+            frozen_coin_stores: AHashSet::new(),
+            address_labels: None,
+            price_oracle: None,
         }
     }
 
@@ -52,6 +74,28 @@ impl FungibleAssetExtractor {
         );
         Ok(())
     }
+
+    pub async fn bootstrap_latest_coin_metadata(&mut self, db_pool: ArcDbPool) -> Result<()> {
+        let mut conn = db_pool.get().await?;
+        self.latest_coin_metadata =
+            PostgresCoinInfoMutation::get_latest_metadata(&mut conn).await?;
+        tracing::info!(
+            item_count = self.latest_coin_metadata.len(),
+            "Finished bootstrapping latest coin metadata"
+        );
+        Ok(())
+    }
+
+    pub async fn bootstrap_frozen_coin_stores(&mut self, db_pool: ArcDbPool) -> Result<()> {
+        let mut conn = db_pool.get().await?;
+        self.frozen_coin_stores =
+            PostgresCoinStoreFreezeEvent::get_frozen_coin_stores(&mut conn).await?;
+        tracing::info!(
+            item_count = self.frozen_coin_stores.len(),
+            "Finished bootstrapping frozen coin stores"
+        );
+        Ok(())
+    }
 }
 
 impl Default for FungibleAssetExtractor {
@@ -73,6 +117,9 @@ impl Processable for FungibleAssetExtractor {
         ),
         Vec<CoinSupply>,
         Vec<PostgresFungibleAssetToCoinMapping>,
+        Vec<PostgresCoinInfoMutation>,
+        Vec<CurrentCoinBalance>,
+        Vec<PostgresCoinStoreFreezeEvent>,
     );
     type RunType = AsyncRunType;
 
@@ -91,6 +138,9 @@ impl Processable for FungibleAssetExtractor {
                 ),
                 Vec<CoinSupply>,
                 Vec<PostgresFungibleAssetToCoinMapping>,
+                Vec<PostgresCoinInfoMutation>,
+                Vec<CurrentCoinBalance>,
+                Vec<PostgresCoinStoreFreezeEvent>,
             )>,
         >,
         ProcessorError,
@@ -98,20 +148,59 @@ impl Processable for FungibleAssetExtractor {
         let new_fa_to_coin_mapping = get_fa_to_coin_mapping(&transactions.data).await;
         // Merge the mappings
         self.fa_to_coin_mapping.extend(new_fa_to_coin_mapping);
-        let (
-            raw_fungible_asset_activities,
-            raw_fungible_asset_metadata,
-            raw_fungible_asset_balances,
-            (raw_current_unified_fab_v1, raw_current_unified_fab_v2),
+        let FungibleAssetBatch {
+            fungible_asset_activities: raw_fungible_asset_activities,
+            fungible_asset_metadata: raw_fungible_asset_metadata,
+            fungible_asset_balances: raw_fungible_asset_balances,
+            current_unified_fungible_asset_balances_v1: raw_current_unified_fab_v1,
+            current_unified_fungible_asset_balances_v2: raw_current_unified_fab_v2,
             coin_supply,
-            fa_to_coin_mappings,
-        ) = parse_v2_coin(&transactions.data, Some(&self.fa_to_coin_mapping)).await;
+            fungible_asset_to_coin_mappings: fa_to_coin_mappings,
+            ..
+        } = parse_v2_coin(&transactions.data, Some(&self.fa_to_coin_mapping)).await;
+        let coin_info_mutations = PostgresCoinInfoMutation::from_transactions(
+            &transactions.data,
+            &mut self.latest_coin_metadata,
+        );
+        let current_coin_balances = CurrentCoinBalance::from_transactions(&transactions.data);
+        let coin_store_freeze_events = PostgresCoinStoreFreezeEvent::from_coin_balances(
+            &current_coin_balances,
+            &mut self.frozen_coin_stores,
+        );
 
-        let postgres_fungible_asset_activities: Vec<PostgresFungibleAssetActivity> =
+        let mut postgres_fungible_asset_activities: Vec<PostgresFungibleAssetActivity> =
             raw_fungible_asset_activities
                 .into_iter()
                 .map(PostgresFungibleAssetActivity::from)
                 .collect();
+        if let Some(address_labels) = &self.address_labels {
+            let transaction_labels = address_labels.transaction_labels(
+                postgres_fungible_asset_activities
+                    .iter()
+                    .filter_map(|activity| {
+                        Some((
+                            activity.transaction_version,
+                            activity.owner_address.as_deref()?,
+                        ))
+                    }),
+            );
+            for activity in &mut postgres_fungible_asset_activities {
+                if let Some(owner_address) = &activity.owner_address {
+                    activity.counterparty_label = counterparty_label(
+                        &transaction_labels,
+                        activity.transaction_version,
+                        owner_address,
+                    );
+                }
+            }
+        }
+        if let Some(price_oracle) = &self.price_oracle {
+            PostgresFungibleAssetActivity::set_usd_values(
+                &mut postgres_fungible_asset_activities,
+                price_oracle,
+            )
+            .await;
+        }
 
         let postgres_fungible_asset_metadata: Vec<PostgresFungibleAssetMetadataModel> =
             raw_fungible_asset_metadata
@@ -152,6 +241,9 @@ impl Processable for FungibleAssetExtractor {
                 ),
                 coin_supply,
                 postgres_fa_to_coin_mappings,
+                coin_info_mutations,
+                current_coin_balances,
+                coin_store_freeze_events,
             ),
             metadata: transactions.metadata,
         }))
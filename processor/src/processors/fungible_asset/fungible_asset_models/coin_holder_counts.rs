@@ -0,0 +1,472 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use super::v2_fungible_asset_balances::PostgresCurrentUnifiedFungibleAssetBalance;
+use crate::schema::{coin_holder_counts, current_fungible_asset_balances};
+use ahash::{AHashMap, AHashSet};
+use bigdecimal::{BigDecimal, Zero};
+use cedra_indexer_processor_sdk::postgres::utils::database::DbPoolConnection;
+use diesel::{
+    prelude::*,
+    query_builder::SqlQuery,
+    sql_query,
+    sql_types::{Array, Text},
+};
+use diesel_async::RunQueryDsl;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+/// Number of stores, and of distinct owners, with a positive balance per asset type. Both counts
+/// are absolute: the storer recomputes them from `current_fungible_asset_balances` with its first
+/// batch once the table is written, then adds each batch's changes in the same transaction as
+/// the batch's balances.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(asset_type))]
+#[diesel(table_name = coin_holder_counts)]
+pub struct PostgresCoinHolderCount {
+    pub asset_type: String,
+    /// Change in the number of holders, added to the stored count on upsert
+    pub holder_count: i64,
+    /// Change in the number of owners holding the asset in any of their stores
+    pub owner_count: i64,
+    pub last_transaction_version: i64,
+}
+
+/// `(owner_address, asset_type)` of owners holding an asset in a store a batch doesn't write
+pub type OtherHolders = AHashSet<(String, String)>;
+
+#[derive(QueryableByName)]
+struct OwnerHolding {
+    #[diesel(sql_type = Text)]
+    owner_address: String,
+    #[diesel(sql_type = Text)]
+    asset_type: String,
+}
+
+/// The parts of a `current_fungible_asset_balances` row that decide whether it's a holder
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HolderBalance {
+    pub owner_address: String,
+    pub asset_type_v1: Option<String>,
+    pub asset_type_v2: Option<String>,
+    pub amount_v1: Option<BigDecimal>,
+    pub amount_v2: Option<BigDecimal>,
+    pub last_transaction_version_v1: Option<i64>,
+    pub last_transaction_version_v2: Option<i64>,
+}
+
+impl HolderBalance {
+    /// Mirrors the generated `asset_type` column
+    fn asset_type(&self) -> Option<&String> {
+        self.asset_type_v1.as_ref().or(self.asset_type_v2.as_ref())
+    }
+
+    fn is_holder(&self) -> bool {
+        let amount =
+            self.amount_v1.clone().unwrap_or_default() + self.amount_v2.clone().unwrap_or_default();
+        amount > BigDecimal::zero()
+    }
+
+    /// Applies the v1 half of a balance upsert, skipped like the upsert for older versions
+    fn apply_v1(&mut self, balance: &PostgresCurrentUnifiedFungibleAssetBalance) {
+        if self.last_transaction_version_v1 <= balance.last_transaction_version_v1 {
+            self.owner_address.clone_from(&balance.owner_address);
+            self.asset_type_v1.clone_from(&balance.asset_type_v1);
+            self.amount_v1.clone_from(&balance.amount_v1);
+            self.last_transaction_version_v1 = balance.last_transaction_version_v1;
+        }
+    }
+
+    /// Applies the v2 half of a balance upsert, which doesn't clear `asset_type_v1`
+    fn apply_v2(&mut self, balance: &PostgresCurrentUnifiedFungibleAssetBalance) {
+        if self.last_transaction_version_v2 <= balance.last_transaction_version_v2 {
+            self.owner_address.clone_from(&balance.owner_address);
+            if balance.asset_type_v1.is_some() {
+                self.asset_type_v1.clone_from(&balance.asset_type_v1);
+            }
+            self.asset_type_v2.clone_from(&balance.asset_type_v2);
+            self.amount_v2.clone_from(&balance.amount_v2);
+            self.last_transaction_version_v2 = balance.last_transaction_version_v2;
+        }
+    }
+}
+
+impl PostgresCoinHolderCount {
+    /// Holder count changes from writing a batch's balances over `previous`, the stored balances
+    /// of the same stores. An owner only stops or starts counting when none of its stores in
+    /// `other_holders` holds the asset. Replaying a batch whose balances are already stored
+    /// changes nothing.
+    pub fn from_balance_changes(
+        previous: &AHashMap<String, HolderBalance>,
+        other_holders: &OtherHolders,
+        balances_v1: &[PostgresCurrentUnifiedFungibleAssetBalance],
+        balances_v2: &[PostgresCurrentUnifiedFungibleAssetBalance],
+    ) -> Vec<Self> {
+        let mut updated: AHashMap<&str, (HolderBalance, i64)> = AHashMap::new();
+        for (balance, is_v1) in balances_v1
+            .iter()
+            .map(|balance| (balance, true))
+            .chain(balances_v2.iter().map(|balance| (balance, false)))
+        {
+            let (holder_balance, version) = updated
+                .entry(balance.storage_id.as_str())
+                .or_insert_with(|| {
+                    (
+                        previous
+                            .get(&balance.storage_id)
+                            .cloned()
+                            .unwrap_or_default(),
+                        0,
+                    )
+                });
+            if is_v1 {
+                holder_balance.apply_v1(balance);
+            } else {
+                holder_balance.apply_v2(balance);
+            }
+            *version = (*version).max(
+                balance
+                    .last_transaction_version_v1
+                    .or(balance.last_transaction_version_v2)
+                    .unwrap_or_default(),
+            );
+        }
+
+        let mut changes: AHashMap<String, Self> = AHashMap::new();
+        let mut add = |asset_type: &str, holder_delta: i64, owner_delta: i64, version: i64| {
+            let change = changes
+                .entry(asset_type.to_string())
+                .or_insert_with(|| Self {
+                    asset_type: asset_type.to_string(),
+                    holder_count: 0,
+                    owner_count: 0,
+                    last_transaction_version: version,
+                });
+            change.holder_count += holder_delta;
+            change.owner_count += owner_delta;
+            change.last_transaction_version = change.last_transaction_version.max(version);
+        };
+        // Owners holding the asset in a store of the batch, before and after the batch
+        let mut owners_before: AHashMap<(String, String), i64> = AHashMap::new();
+        let mut owners_after: AHashMap<(String, String), i64> = AHashMap::new();
+        for (storage_id, (new_balance, version)) in &updated {
+            let old_balance = previous
+                .get(*storage_id)
+                .filter(|balance| balance.is_holder());
+            let old_holder = old_balance.and_then(HolderBalance::asset_type);
+            let new_holder = Some(new_balance)
+                .filter(|balance| balance.is_holder())
+                .and_then(HolderBalance::asset_type);
+            if let (Some(old_balance), Some(asset_type)) = (old_balance, old_holder) {
+                let owner = (old_balance.owner_address.clone(), asset_type.clone());
+                let owner_version = owners_before.entry(owner).or_default();
+                *owner_version = (*owner_version).max(*version);
+            }
+            if let Some(asset_type) = new_holder {
+                let owner = (new_balance.owner_address.clone(), asset_type.clone());
+                let owner_version = owners_after.entry(owner).or_default();
+                *owner_version = (*owner_version).max(*version);
+            }
+            if old_holder == new_holder {
+                continue;
+            }
+            if let Some(asset_type) = old_holder {
+                add(asset_type, -1, 0, *version);
+            }
+            if let Some(asset_type) = new_holder {
+                add(asset_type, 1, 0, *version);
+            }
+        }
+        for (owner, version) in &owners_before {
+            if !owners_after.contains_key(owner) && !other_holders.contains(owner) {
+                add(&owner.1, 0, -1, *version);
+            }
+        }
+        for (owner, version) in &owners_after {
+            if !owners_before.contains_key(owner) && !other_holders.contains(owner) {
+                add(&owner.1, 0, 1, *version);
+            }
+        }
+        changes
+            .into_values()
+            .filter(|change| change.holder_count != 0 || change.owner_count != 0)
+            .collect()
+    }
+
+    /// Loads the stored balances of the stores written in a batch
+    pub async fn get_previous_balances(
+        conn: &mut DbPoolConnection<'_>,
+        balances_v1: &[PostgresCurrentUnifiedFungibleAssetBalance],
+        balances_v2: &[PostgresCurrentUnifiedFungibleAssetBalance],
+    ) -> diesel::QueryResult<AHashMap<String, HolderBalance>> {
+        use current_fungible_asset_balances::dsl;
+
+        let storage_ids: Vec<&String> = balances_v1
+            .iter()
+            .chain(balances_v2)
+            .map(|balance| &balance.storage_id)
+            .collect();
+        let rows = dsl::current_fungible_asset_balances
+            .filter(dsl::storage_id.eq_any(storage_ids))
+            .select((
+                dsl::storage_id,
+                dsl::owner_address,
+                dsl::asset_type_v1,
+                dsl::asset_type_v2,
+                dsl::amount_v1,
+                dsl::amount_v2,
+                dsl::last_transaction_version_v1,
+                dsl::last_transaction_version_v2,
+            ))
+            .load::<(
+                String,
+                String,
+                Option<String>,
+                Option<String>,
+                Option<BigDecimal>,
+                Option<BigDecimal>,
+                Option<i64>,
+                Option<i64>,
+            )>(conn)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(
+                    storage_id,
+                    owner_address,
+                    asset_type_v1,
+                    asset_type_v2,
+                    amount_v1,
+                    amount_v2,
+                    last_transaction_version_v1,
+                    last_transaction_version_v2,
+                )| {
+                    (storage_id, HolderBalance {
+                        owner_address,
+                        asset_type_v1,
+                        asset_type_v2,
+                        amount_v1,
+                        amount_v2,
+                        last_transaction_version_v1,
+                        last_transaction_version_v2,
+                    })
+                },
+            )
+            .collect())
+    }
+
+    /// Loads the owners of a batch's stores, before and after it, that hold the same asset in a
+    /// store the batch doesn't write. Only the `(owner_address, asset_type)` pairs of the batch
+    /// are looked up.
+    pub async fn get_other_holders(
+        conn: &mut DbPoolConnection<'_>,
+        previous: &AHashMap<String, HolderBalance>,
+        balances_v1: &[PostgresCurrentUnifiedFungibleAssetBalance],
+        balances_v2: &[PostgresCurrentUnifiedFungibleAssetBalance],
+    ) -> diesel::QueryResult<OtherHolders> {
+        let balances = balances_v1.iter().chain(balances_v2);
+        let storage_ids: Vec<&String> = balances
+            .clone()
+            .map(|balance| &balance.storage_id)
+            .collect();
+        let owners: AHashSet<(&String, &String)> = balances
+            .filter_map(|balance| {
+                let asset_type = balance
+                    .asset_type_v1
+                    .as_ref()
+                    .or(balance.asset_type_v2.as_ref())?;
+                Some((&balance.owner_address, asset_type))
+            })
+            .chain(
+                previous
+                    .values()
+                    .filter_map(|balance| Some((&balance.owner_address, balance.asset_type()?))),
+            )
+            .collect();
+        let (owner_addresses, asset_types): (Vec<&String>, Vec<&String>) =
+            owners.into_iter().unzip();
+        let rows: Vec<OwnerHolding> = sql_query(
+            r#"SELECT DISTINCT balances.owner_address, balances.asset_type
+             FROM "current_fungible_asset_balances" AS balances
+             JOIN UNNEST($1::TEXT[], $2::TEXT[]) AS owners(owner_address, asset_type)
+               ON balances.owner_address = owners.owner_address
+              AND balances.asset_type = owners.asset_type
+             WHERE balances.amount > 0 AND balances.storage_id <> ALL($3)"#,
+        )
+        .bind::<Array<Text>, _>(owner_addresses)
+        .bind::<Array<Text>, _>(asset_types)
+        .bind::<Array<Text>, _>(storage_ids)
+        .load(conn)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.owner_address, row.asset_type))
+            .collect())
+    }
+
+    /// Replaces the stored counts with the ones of `current_fungible_asset_balances`. Run before
+    /// the first batch's changes are added, in the same transaction.
+    pub fn seed_queries() -> [SqlQuery; 2] {
+        [
+            sql_query(r#"DELETE FROM "coin_holder_counts""#),
+            sql_query(
+                r#"INSERT INTO "coin_holder_counts"
+                    (asset_type, holder_count, owner_count, last_transaction_version)
+                SELECT asset_type, COUNT(*), COUNT(DISTINCT owner_address),
+                    MAX(GREATEST(
+                        COALESCE(last_transaction_version_v1, 0),
+                        COALESCE(last_transaction_version_v2, 0)
+                    ))
+                FROM "current_fungible_asset_balances"
+                WHERE amount > 0 AND asset_type IS NOT NULL
+                GROUP BY asset_type"#,
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance_v2(
+        storage_id: &str,
+        asset_type: &str,
+        amount: i64,
+        version: i64,
+    ) -> PostgresCurrentUnifiedFungibleAssetBalance {
+        PostgresCurrentUnifiedFungibleAssetBalance {
+            storage_id: storage_id.to_string(),
+            asset_type_v2: Some(asset_type.to_string()),
+            amount_v2: Some(BigDecimal::from(amount)),
+            last_transaction_version_v2: Some(version),
+            ..Default::default()
+        }
+    }
+
+    fn holder_counts(changes: Vec<PostgresCoinHolderCount>) -> Vec<(String, i64)> {
+        let mut counts: Vec<(String, i64)> = changes
+            .into_iter()
+            .map(|change| (change.asset_type, change.holder_count))
+            .collect();
+        counts.sort();
+        counts
+    }
+
+    #[test]
+    fn test_holder_count_changes() {
+        let previous: AHashMap<String, HolderBalance> =
+            [("0xa", 5, 1), ("0xb", 0, 1), ("0xc", 3, 1)]
+                .into_iter()
+                .map(|(storage_id, amount, version)| {
+                    (storage_id.to_string(), HolderBalance {
+                        asset_type_v2: Some("0xfa".to_string()),
+                        amount_v2: Some(BigDecimal::from(amount)),
+                        last_transaction_version_v2: Some(version),
+                        ..Default::default()
+                    })
+                })
+                .collect();
+        let balances = vec![
+            // Emptied
+            balance_v2("0xa", "0xfa", 0, 10),
+            // Funded
+            balance_v2("0xb", "0xfa", 2, 10),
+            // Still holding
+            balance_v2("0xc", "0xfa", 1, 10),
+            // New stores
+            balance_v2("0xd", "0xfa", 4, 11),
+            balance_v2("0xe", "0xother", 4, 11),
+            balance_v2("0xf", "0xother", 0, 11),
+        ];
+
+        let changes = PostgresCoinHolderCount::from_balance_changes(
+            &previous,
+            &OtherHolders::new(),
+            &[],
+            &balances,
+        );
+        assert_eq!(holder_counts(changes), vec![
+            ("0xfa".to_string(), 1),
+            ("0xother".to_string(), 1)
+        ]);
+    }
+
+    #[test]
+    fn test_replayed_and_stale_balances_change_nothing() {
+        let previous: AHashMap<String, HolderBalance> = [("0xa".to_string(), HolderBalance {
+            asset_type_v2: Some("0xfa".to_string()),
+            amount_v2: Some(BigDecimal::from(5)),
+            last_transaction_version_v2: Some(10),
+            ..Default::default()
+        })]
+        .into_iter()
+        .collect();
+
+        let replayed = vec![balance_v2("0xa", "0xfa", 5, 10)];
+        assert!(PostgresCoinHolderCount::from_balance_changes(
+            &previous,
+            &OtherHolders::new(),
+            &[],
+            &replayed
+        )
+        .is_empty());
+        let stale = vec![balance_v2("0xa", "0xfa", 0, 9)];
+        assert!(PostgresCoinHolderCount::from_balance_changes(
+            &previous,
+            &OtherHolders::new(),
+            &[],
+            &stale
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_owner_count_changes() {
+        let previous: AHashMap<String, HolderBalance> =
+            [("0xa", "0x1", 5), ("0xb", "0x2", 5), ("0xc", "0x3", 5)]
+                .into_iter()
+                .map(|(storage_id, owner_address, amount)| {
+                    (storage_id.to_string(), HolderBalance {
+                        owner_address: owner_address.to_string(),
+                        asset_type_v2: Some("0xfa".to_string()),
+                        amount_v2: Some(BigDecimal::from(amount)),
+                        last_transaction_version_v2: Some(1),
+                        ..Default::default()
+                    })
+                })
+                .collect();
+        // 0x2 still holds 0xfa in a store outside the batch
+        let other_holders = OtherHolders::from_iter([("0x2".to_string(), "0xfa".to_string())]);
+        let with_owner = |storage_id: &str, owner_address: &str, amount: i64| {
+            PostgresCurrentUnifiedFungibleAssetBalance {
+                owner_address: owner_address.to_string(),
+                ..balance_v2(storage_id, "0xfa", amount, 10)
+            }
+        };
+        let balances = vec![
+            // Last store of 0x1 emptied
+            with_owner("0xa", "0x1", 0),
+            // Another store of 0x2 emptied
+            with_owner("0xb", "0x2", 0),
+            // Second store of 0x3 funded
+            with_owner("0xc", "0x3", 5),
+            with_owner("0xd", "0x3", 5),
+        ];
+
+        let changes = PostgresCoinHolderCount::from_balance_changes(
+            &previous,
+            &other_holders,
+            &[],
+            &balances,
+        );
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].holder_count, -1);
+        assert_eq!(changes[0].owner_count, -1);
+        assert_eq!(changes[0].last_transaction_version, 10);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod coin_holder_counts;
 pub mod v2_fungible_asset_activities;
 pub mod v2_fungible_asset_balances;
 pub mod v2_fungible_asset_to_coin_mappings;
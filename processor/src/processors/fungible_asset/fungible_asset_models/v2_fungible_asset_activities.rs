@@ -23,8 +23,9 @@ use crate::{
         token_v2::token_v2_models::v2_token_utils::TokenStandard,
     },
     schema::fungible_asset_activities,
+    utils::price_oracle::PriceOracle,
 };
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 use allocative::Allocative;
 use anyhow::Context;
 use cedra_indexer_processor_sdk::{
@@ -33,8 +34,10 @@ use cedra_indexer_processor_sdk::{
 };
 use bigdecimal::{BigDecimal, Zero};
 use field_count::FieldCount;
+use itertools::Itertools;
 use parquet_derive::ParquetRecordWriter;
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 pub const GAS_FEE_EVENT: &str = "0x1::cedra_coin::GasFeeEvent";
 // We will never have a negative number on chain so this will avoid collision in postgres
@@ -68,6 +71,9 @@ pub struct FungibleAssetActivity {
     pub token_standard: String,
     pub transaction_timestamp: chrono::NaiveDateTime,
     pub storage_refund_amount: BigDecimal,
+    /// Deposit of the coins moved from a coin store into its primary fungible store when the
+    /// store is migrated, rather than a transfer
+    pub is_migration: bool,
 }
 
 impl FungibleAssetActivity {
@@ -80,6 +86,7 @@ impl FungibleAssetActivity {
         entry_function_id_str: &Option<String>,
         object_aggregated_data_mapping: &ObjectAggregatedDataMapping,
         store_address_to_deleted_fa_store_events: &StoreAddressToDeletedFungibleAssetStoreEvent,
+        migrated_store_ids: &AHashSet<String>,
     ) -> anyhow::Result<Option<Self>> {
         let event_type = event.type_str.clone();
         if let Some(fa_event) =
@@ -154,6 +161,12 @@ impl FungibleAssetActivity {
                 },
             }
 
+            let is_deposit = matches!(
+                fa_event,
+                FungibleAssetEvent::DepositEvent(_) | FungibleAssetEvent::DepositEventV2(_)
+            );
+            let is_migration = is_deposit && migrated_store_ids.contains(&storage_id);
+
             return Ok(Some(Self {
                 transaction_version: txn_version,
                 event_index,
@@ -171,6 +184,7 @@ impl FungibleAssetActivity {
                 token_standard: TokenStandard::V2.to_string(),
                 transaction_timestamp: txn_timestamp,
                 storage_refund_amount: BigDecimal::zero(),
+                is_migration,
             }));
         }
         Ok(None)
@@ -263,6 +277,7 @@ impl FungibleAssetActivity {
                 token_standard: TokenStandard::V1.to_string(),
                 transaction_timestamp,
                 storage_refund_amount: BigDecimal::zero(),
+                is_migration: false,
             }))
         } else {
             Ok(None)
@@ -311,6 +326,7 @@ impl FungibleAssetActivity {
             token_standard: TokenStandard::V1.to_string(),
             transaction_timestamp,
             storage_refund_amount: v1_activity.storage_refund_amount,
+            is_migration: false,
         }
     }
 }
@@ -337,6 +353,7 @@ pub struct ParquetFungibleAssetActivity {
     #[allocative(skip)]
     pub block_timestamp: chrono::NaiveDateTime,
     pub storage_refund_octa: u64,
+    pub is_migration: bool,
 }
 
 impl NamedTable for ParquetFungibleAssetActivity {
@@ -368,6 +385,7 @@ impl From<FungibleAssetActivity> for ParquetFungibleAssetActivity {
             token_standard: raw.token_standard,
             block_timestamp: raw.transaction_timestamp,
             storage_refund_octa: bigdecimal_to_u64(&raw.storage_refund_amount),
+            is_migration: raw.is_migration,
         }
     }
 }
@@ -393,6 +411,9 @@ pub struct PostgresFungibleAssetActivity {
     pub token_standard: String,
     pub transaction_timestamp: chrono::NaiveDateTime,
     pub storage_refund_amount: BigDecimal,
+    pub counterparty_label: Option<String>,
+    pub is_migration: bool,
+    pub usd_value: Option<BigDecimal>,
 }
 
 impl From<FungibleAssetActivity> for PostgresFungibleAssetActivity {
@@ -414,6 +435,40 @@ impl From<FungibleAssetActivity> for PostgresFungibleAssetActivity {
             token_standard: raw.token_standard,
             transaction_timestamp: raw.transaction_timestamp,
             storage_refund_amount: raw.storage_refund_amount,
+            counterparty_label: None,
+            is_migration: raw.is_migration,
+            usd_value: None,
+        }
+    }
+}
+
+impl PostgresFungibleAssetActivity {
+    /// Fills in `usd_value` as `amount * price` for every activity whose asset type the oracle
+    /// has a price for. Oracle failures are logged and leave `usd_value` unset, like for token
+    /// activities.
+    pub async fn set_usd_values(activities: &mut [Self], price_oracle: &PriceOracle) {
+        let asset_types: Vec<String> = activities
+            .iter()
+            .filter_map(|activity| activity.asset_type.clone())
+            .unique()
+            .collect();
+        match price_oracle.get_usd_prices(&asset_types).await {
+            Ok(prices) => {
+                for activity in activities.iter_mut() {
+                    activity.usd_value = activity
+                        .asset_type
+                        .as_ref()
+                        .and_then(|asset_type| prices.get(asset_type))
+                        .zip(activity.amount.as_ref())
+                        .map(|(price, amount)| amount * price);
+                }
+            },
+            Err(e) => {
+                warn!(
+                    error = ?e,
+                    "Failed to fetch usd prices for fungible asset activities, skipping usd_value"
+                );
+            },
         }
     }
 }
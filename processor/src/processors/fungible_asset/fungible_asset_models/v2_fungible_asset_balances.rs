@@ -30,6 +30,7 @@ use crate::{
         current_fungible_asset_balances, current_fungible_asset_balances_legacy,
         fungible_asset_balances,
     },
+    utils::dedup::CurrentTableRow,
 };
 use ahash::AHashMap;
 use allocative_derive::Allocative;
@@ -609,6 +610,21 @@ impl From<CurrentUnifiedFungibleAssetBalance> for PostgresCurrentUnifiedFungible
     }
 }
 
+impl CurrentTableRow for PostgresCurrentUnifiedFungibleAssetBalance {
+    type PrimaryKey = String;
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        self.storage_id.clone()
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        // v1 and v2 rows are stored separately, so only one of these is set on any given row
+        self.last_transaction_version_v1
+            .max(self.last_transaction_version_v2)
+            .unwrap_or_default()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
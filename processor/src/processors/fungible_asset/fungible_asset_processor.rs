@@ -6,6 +6,10 @@ use crate::{
         },
         processor_config::{DefaultProcessorConfig, ProcessorConfig},
     },
+    db::{
+        connection_pool::new_postgres_pool, partitioning::PartitionStep,
+        schema_drift::run_migrations_and_check_drift,
+    },
     processors::{
         fungible_asset::{
             fungible_asset_extractor::FungibleAssetExtractor,
@@ -15,22 +19,21 @@ use crate::{
             get_end_version, get_starting_version, PostgresProcessorStatusSaver,
         },
     },
-    utils::table_flags::TableFlags,
-    MIGRATIONS,
+    utils::{
+        address_labels::{AddressLabels, AddressLabelsConfig},
+        logging::BatchSpanStep,
+        price_oracle::{PriceOracle, PriceOracleConfig},
+        supervisor::supervise_step_tasks,
+        table_flags::TableFlags,
+        transaction_source::{check_chain_id, TransactionSourceStep},
+    },
 };
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    cedra_indexer_transaction_stream::TransactionStreamConfig,
     builder::ProcessorBuilder,
-    common_steps::{
-        TransactionStreamStep, VersionTrackerStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
-    },
-    postgres::utils::{
-        checkpoint::PostgresChainIdChecker,
-        database::{new_db_pool, run_migrations, ArcDbPool},
-    },
+    common_steps::{VersionTrackerStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS},
+    postgres::utils::database::ArcDbPool,
     traits::{processor_trait::ProcessorTrait, IntoRunnableStep},
-    utils::chain_id_check::check_or_update_chain_id,
 };
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
@@ -44,6 +47,12 @@ pub struct FungibleAssetProcessorConfig {
     pub query_retries: u32,
     #[serde(default = "FungibleAssetProcessorConfig::default_query_retry_delay_ms")]
     pub query_retry_delay_ms: u64,
+    // Labels of known entities the activities are tagged with in counterparty_label
+    #[serde(default)]
+    pub address_labels: Option<AddressLabelsConfig>,
+    // Optional price source used to populate fungible_asset_activities.usd_value
+    #[serde(default)]
+    pub price_oracle: Option<PriceOracleConfig>,
 }
 
 impl FungibleAssetProcessorConfig {
@@ -65,12 +74,7 @@ impl FungibleAssetProcessor {
     pub async fn new(config: IndexerProcessorConfig) -> Result<Self> {
         match config.db_config {
             DbConfig::PostgresConfig(ref postgres_config) => {
-                let conn_pool = new_db_pool(
-                    &postgres_config.connection_string,
-                    Some(postgres_config.db_pool_size),
-                )
-                .await
-                .map_err(|e| {
+                let conn_pool = new_postgres_pool(postgres_config).await.map_err(|e| {
                     anyhow::anyhow!(
                         "Failed to create connection pool for PostgresConfig: {:?}",
                         e
@@ -99,12 +103,7 @@ impl ProcessorTrait for FungibleAssetProcessor {
     async fn run_processor(&self) -> Result<()> {
         //  Run migrations
         if let DbConfig::PostgresConfig(ref postgres_config) = self.config.db_config {
-            run_migrations(
-                postgres_config.connection_string.clone(),
-                self.db_pool.clone(),
-                MIGRATIONS,
-            )
-            .await;
+            run_migrations_and_check_drift(postgres_config, self.db_pool.clone()).await?;
         }
 
         // Merge the starting version from config and the latest processed version from the DB
@@ -114,34 +113,48 @@ impl ProcessorTrait for FungibleAssetProcessor {
         );
 
         // Check and update the ledger chain id to ensure we're indexing the correct chain
-        check_or_update_chain_id(
-            &self.config.transaction_stream_config,
-            &PostgresChainIdChecker::new(self.db_pool.clone()),
-        )
-        .await?;
+        check_chain_id(&self.config, self.db_pool.clone()).await?;
 
         let processor_config = match &self.config.processor_config {
             ProcessorConfig::FungibleAssetProcessor(processor_config) => processor_config,
             _ => return Err(anyhow::anyhow!("Processor config is wrong type")),
         };
-        let channel_size = processor_config.channel_size;
-        let deprecated_table_flags = TableFlags::from_set(&processor_config.tables_to_write);
+        let channel_size = processor_config.default_config.channel_size;
+        let deprecated_table_flags =
+            TableFlags::from_set(&processor_config.default_config.tables_to_write);
 
         // Define processor steps
-        let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
-            starting_version,
-            request_ending_version: ending_version,
-            ..self.config.transaction_stream_config.clone()
-        })
-        .await?;
+        let transaction_stream =
+            TransactionSourceStep::new(&self.config, starting_version, ending_version).await?;
 
         let mut fa_extractor = FungibleAssetExtractor::new();
+        fa_extractor.address_labels = processor_config
+            .address_labels
+            .as_ref()
+            .map(AddressLabels::load)
+            .transpose()?;
+        fa_extractor.price_oracle = processor_config
+            .price_oracle
+            .as_ref()
+            .map(|price_oracle_config| PriceOracle::new(price_oracle_config, self.db_pool.clone()))
+            .transpose()?;
         fa_extractor
             .bootstrap_fa_to_coin_mapping(self.db_pool.clone())
             .await?;
+        fa_extractor
+            .bootstrap_latest_coin_metadata(self.db_pool.clone())
+            .await?;
+        fa_extractor
+            .bootstrap_frozen_coin_stores(self.db_pool.clone())
+            .await?;
+        let partition_step = PartitionStep::new(&self.config, self.db_pool.clone(), &[
+            "fungible_asset_activities",
+        ])
+        .await?;
         let fa_storer = FungibleAssetStorer::new(
             self.db_pool.clone(),
-            processor_config.clone(),
+            self.config.table_names(),
+            processor_config.default_config.clone(),
             deprecated_table_flags,
         );
         let version_tracker = VersionTrackerStep::new(
@@ -149,13 +162,21 @@ impl ProcessorTrait for FungibleAssetProcessor {
             DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
         );
         // Connect processor steps together
-        let (_, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
+        let (processor_builder, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
             transaction_stream.into_runnable_step(),
         )
-        .connect_to(fa_extractor.into_runnable_step(), channel_size)
-        .connect_to(fa_storer.into_runnable_step(), channel_size)
+        .connect_to(
+            BatchSpanStep::new(fa_extractor, self.name()).into_runnable_step(),
+            channel_size,
+        )
+        .connect_to(partition_step.into_runnable_step(), channel_size)
+        .connect_to(
+            BatchSpanStep::new(fa_storer, self.name()).into_runnable_step(),
+            channel_size,
+        )
         .connect_to(version_tracker.into_runnable_step(), channel_size)
         .end_and_return_output_receiver(channel_size);
+        supervise_step_tasks(processor_builder.join_handles);
 
         // (Optional) Parse the results
         loop {
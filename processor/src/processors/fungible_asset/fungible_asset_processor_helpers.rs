@@ -26,7 +26,7 @@ use crate::{
     },
     utils::counters::PROCESSOR_UNKNOWN_TYPE_COUNT,
 };
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 use cedra_indexer_processor_sdk::{
     cedra_protos::transaction::v1::{transaction::TxnData, write_set_change::Change, Transaction},
     utils::{convert::standardize_address, extract::get_entry_function_from_user_request},
@@ -84,6 +84,20 @@ pub async fn get_fa_to_coin_mapping(transactions: &[Transaction]) -> FungibleAss
     kv_mapping
 }
 
+/// Fungible asset and coin rows parsed from a batch of transactions, by table. Fields may be
+/// added as tables are, so the struct can't be built or destructured outside this crate.
+#[derive(Default)]
+#[non_exhaustive]
+pub struct FungibleAssetBatch {
+    pub fungible_asset_activities: Vec<FungibleAssetActivity>,
+    pub fungible_asset_metadata: Vec<FungibleAssetMetadataModel>,
+    pub fungible_asset_balances: Vec<FungibleAssetBalance>,
+    pub current_unified_fungible_asset_balances_v1: Vec<CurrentUnifiedFungibleAssetBalance>,
+    pub current_unified_fungible_asset_balances_v2: Vec<CurrentUnifiedFungibleAssetBalance>,
+    pub coin_supply: Vec<CoinSupply>,
+    pub fungible_asset_to_coin_mappings: Vec<FungibleAssetToCoinMapping>,
+}
+
 /// TODO: After the migration is complete, we can move this to common models folder
 /// V2 coin is called fungible assets and this flow includes all data from V1 in coin_processor
 pub async fn parse_v2_coin(
@@ -91,17 +105,7 @@ pub async fn parse_v2_coin(
     // This mapping is only applied to SDK processor. The old processor will use the hardcoded mapping
     // METADATA_TO_COIN_TYPE_MAPPING
     persisted_fa_to_coin_mapping: Option<&FungibleAssetToCoinMappings>,
-) -> (
-    Vec<FungibleAssetActivity>,
-    Vec<FungibleAssetMetadataModel>,
-    Vec<FungibleAssetBalance>,
-    (
-        Vec<CurrentUnifiedFungibleAssetBalance>,
-        Vec<CurrentUnifiedFungibleAssetBalance>,
-    ),
-    Vec<CoinSupply>,
-    Vec<FungibleAssetToCoinMapping>,
-) {
+) -> FungibleAssetBatch {
     let mut fungible_asset_activities: Vec<FungibleAssetActivity> = vec![];
     let mut fungible_asset_balances: Vec<FungibleAssetBalance> = vec![];
     let mut all_coin_supply: Vec<CoinSupply> = vec![];
@@ -177,6 +181,9 @@ pub async fn parse_v2_coin(
             // Same as above but for fungible asset store deletions. Now we have an event that contains all metadata
             // about the deleted store.
             let mut store_address_to_deleted_fa_store_events: StoreAddressToDeletedFungibleAssetStoreEvent = AHashMap::new();
+            // Primary stores paired with a coin store deleted in this transaction. Coin stores are
+            // deleted when they're migrated, and their coins deposited into the primary store.
+            let mut migrated_store_ids: AHashSet<String> = AHashSet::new();
             // Loop 1: to get all object addresses
             // Need to do a first pass to get all the object addresses and insert them into the helper
             for wsc in transaction_info.changes.iter() {
@@ -275,6 +282,7 @@ pub async fn parse_v2_coin(
                         )
                         .unwrap()
                     {
+                        migrated_store_ids.insert(balance.storage_id.clone());
                         fungible_asset_balances.push(balance);
                         owner_address_to_deleted_coin_type.extend(single_deleted_coin_type);
                     }
@@ -330,6 +338,7 @@ pub async fn parse_v2_coin(
                     &entry_function_id_str,
                     &fungible_asset_object_helper,
                     &store_address_to_deleted_fa_store_events,
+                    &migrated_store_ids,
                 )
                 .unwrap_or_else(|e| {
                     tracing::error!(
@@ -487,12 +496,13 @@ pub async fn parse_v2_coin(
     current_unified_fab_v1.sort_by(|a, b| a.storage_id.cmp(&b.storage_id));
     current_unified_fab_v2.sort_by(|a, b| a.storage_id.cmp(&b.storage_id));
     fa_to_coin_mapping.sort_by(|a, b| a.coin_type.cmp(&b.coin_type));
-    (
+    FungibleAssetBatch {
         fungible_asset_activities,
         fungible_asset_metadata,
         fungible_asset_balances,
-        (current_unified_fab_v1, current_unified_fab_v2),
-        all_coin_supply,
-        fa_to_coin_mapping,
-    )
+        current_unified_fungible_asset_balances_v1: current_unified_fab_v1,
+        current_unified_fungible_asset_balances_v2: current_unified_fab_v2,
+        coin_supply: all_coin_supply,
+        fungible_asset_to_coin_mappings: fa_to_coin_mapping,
+    }
 }
@@ -5,8 +5,12 @@ use crate::{
     config::processor_config::DefaultProcessorConfig,
     filter_datasets,
     processors::fungible_asset::{
-        coin_models::coin_supply::CoinSupply,
+        coin_models::{
+            coin_balances::CurrentCoinBalance, coin_info_mutations::PostgresCoinInfoMutation,
+            coin_store_freeze_events::PostgresCoinStoreFreezeEvent, coin_supply::CoinSupply,
+        },
         fungible_asset_models::{
+            coin_holder_counts::PostgresCoinHolderCount,
             v2_fungible_asset_activities::PostgresFungibleAssetActivity,
             v2_fungible_asset_balances::{
                 PostgresCurrentUnifiedFungibleAssetBalance, PostgresFungibleAssetBalance,
@@ -16,12 +20,23 @@ use crate::{
         },
     },
     schema,
-    utils::table_flags::{filter_data, TableFlags},
+    utils::{
+        dedup::{dedup_by_pk_keep_latest, filter_recent_writes, RecentWrites},
+        logging::table_span,
+        table_flags::{filter_data, should_write, TableFlags},
+        table_names::TableNames,
+        table_stats::{
+            execute_in_chunks_with_stats, record_table_stats, write_batch, PrimaryKeyOrd,
+            TableWriteStats,
+        },
+        write_sink::{write_sinks, OwnedWrite, TableBatch, TableRows},
+    },
+    version_guard,
 };
 use ahash::AHashMap;
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    postgres::utils::database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
@@ -33,28 +48,44 @@ use diesel::{
     query_builder::QueryFragment,
     query_dsl::methods::FilterDsl,
     sql_types::{Nullable, Text},
-    BoolExpressionMethods, ExpressionMethods,
+    ExpressionMethods,
 };
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use futures::FutureExt;
+use serde_json::Value;
+use tracing::Instrument;
 
 pub struct FungibleAssetStorer
 where
     Self: Sized + Send + 'static,
 {
     conn_pool: ArcDbPool,
+    table_names: TableNames,
     processor_config: DefaultProcessorConfig,
     tables_to_write: TableFlags,
+    // Shared stores are rewritten in most batches, so these see the bulk of repeat upserts
+    recent_fab_v1_writes: Option<RecentWrites<String>>,
+    recent_fab_v2_writes: Option<RecentWrites<String>>,
+    /// Whether `coin_holder_counts` was recomputed from the balances since it was turned on
+    holder_counts_seeded: bool,
 }
 
 impl FungibleAssetStorer {
     pub fn new(
         conn_pool: ArcDbPool,
+        table_names: TableNames,
         processor_config: DefaultProcessorConfig,
         tables_to_write: TableFlags,
     ) -> Self {
+        let window = processor_config.current_table_dedup_window;
         Self {
             conn_pool,
+            table_names,
             processor_config,
             tables_to_write,
+            recent_fab_v1_writes: window.map(RecentWrites::new),
+            recent_fab_v2_writes: window.map(RecentWrites::new),
+            holder_counts_seeded: false,
         }
     }
 }
@@ -71,6 +102,9 @@ impl Processable for FungibleAssetStorer {
         ),
         Vec<CoinSupply>,
         Vec<PostgresFungibleAssetToCoinMapping>,
+        Vec<PostgresCoinInfoMutation>,
+        Vec<CurrentCoinBalance>,
+        Vec<PostgresCoinStoreFreezeEvent>,
     );
     type Output = ();
     type RunType = AsyncRunType;
@@ -87,6 +121,9 @@ impl Processable for FungibleAssetStorer {
             ),
             Vec<CoinSupply>,
             Vec<PostgresFungibleAssetToCoinMapping>,
+            Vec<PostgresCoinInfoMutation>,
+            Vec<CurrentCoinBalance>,
+            Vec<PostgresCoinStoreFreezeEvent>,
         )>,
     ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
         let (
@@ -96,6 +133,9 @@ impl Processable for FungibleAssetStorer {
             (current_unified_fab_v1, current_unified_fab_v2),
             _coin_supply, // TODO: remove this from parsing logic
             fa_to_coin_mappings,
+            coin_info_mutations,
+            current_coin_balances,
+            coin_store_freeze_events,
         ) = input.data;
 
         let per_table_chunk_sizes: AHashMap<String, usize> =
@@ -107,62 +147,137 @@ impl Processable for FungibleAssetStorer {
             fungible_asset_activities,
             fungible_asset_metadata,
             fa_to_coin_mappings,
+            coin_info_mutations,
+            current_coin_balances,
+            coin_store_freeze_events,
         ) = filter_datasets!(self, {
             current_unified_fab_v1 => TableFlags::CURRENT_FUNGIBLE_ASSET_BALANCES,
             current_unified_fab_v2 => TableFlags::CURRENT_FUNGIBLE_ASSET_BALANCES,
             fungible_asset_activities => TableFlags::FUNGIBLE_ASSET_ACTIVITIES,
             fungible_asset_metadata => TableFlags::FUNGIBLE_ASSET_METADATA,
             fa_to_coin_mappings => TableFlags::FUNGIBLE_ASSET_TO_COIN_MAPPINGS,
+            coin_info_mutations => TableFlags::COIN_INFO_MUTATIONS,
+            current_coin_balances => TableFlags::CURRENT_COIN_BALANCES,
+            coin_store_freeze_events => TableFlags::COIN_STORE_FREEZE_EVENTS,
         });
 
-        let faa = execute_in_chunks(
+        let current_unified_fab_v1 = dedup_by_pk_keep_latest(current_unified_fab_v1);
+        let current_unified_fab_v2 = dedup_by_pk_keep_latest(current_unified_fab_v2);
+        let current_coin_balances = dedup_by_pk_keep_latest(current_coin_balances);
+        let current_unified_fab_v1 =
+            filter_recent_writes(&self.recent_fab_v1_writes, current_unified_fab_v1);
+        let current_unified_fab_v2 =
+            filter_recent_writes(&self.recent_fab_v2_writes, current_unified_fab_v2);
+
+        let faa = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_fungible_asset_activities_query,
             &fungible_asset_activities,
+            "fungible_asset_activities",
             get_config_table_chunk_size::<PostgresFungibleAssetActivity>(
                 "fungible_asset_activities",
                 &per_table_chunk_sizes,
             ),
         );
-        let fam = execute_in_chunks(
+        let fam = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_fungible_asset_metadata_query,
             &fungible_asset_metadata,
+            "fungible_asset_metadata",
             get_config_table_chunk_size::<PostgresFungibleAssetMetadataModel>(
                 "fungible_asset_metadata",
                 &per_table_chunk_sizes,
             ),
         );
-        let cufab_v1 = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_current_unified_fungible_asset_balances_v1_query,
-            &current_unified_fab_v1,
+        let counting_holders = should_write(&self.tables_to_write, TableFlags::COIN_HOLDER_COUNTS);
+        let balances_chunk_size =
             get_config_table_chunk_size::<PostgresCurrentUnifiedFungibleAssetBalance>(
                 "current_unified_fungible_asset_balances",
                 &per_table_chunk_sizes,
+            );
+        let cufab = async {
+            if counting_holders {
+                self.write_balances_with_holder_counts(
+                    &current_unified_fab_v1,
+                    &current_unified_fab_v2,
+                    balances_chunk_size,
+                    get_config_table_chunk_size::<PostgresCoinHolderCount>(
+                        "coin_holder_counts",
+                        &per_table_chunk_sizes,
+                    ),
+                )
+                .await
+            } else {
+                let cufab_v1 = execute_in_chunks_with_stats(
+                    self.conn_pool.clone(),
+                    &self.table_names,
+                    insert_current_unified_fungible_asset_balances_v1_query,
+                    &current_unified_fab_v1,
+                    "current_unified_fungible_asset_balances",
+                    balances_chunk_size,
+                );
+                let cufab_v2 = execute_in_chunks_with_stats(
+                    self.conn_pool.clone(),
+                    &self.table_names,
+                    insert_current_unified_fungible_asset_balances_v2_query,
+                    &current_unified_fab_v2,
+                    "current_unified_fungible_asset_balances",
+                    balances_chunk_size,
+                );
+                futures::try_join!(cufab_v1, cufab_v2).map(|_| ())
+            }
+        };
+        let fatcm = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_fungible_asset_to_coin_mappings_query,
+            &fa_to_coin_mappings,
+            "fungible_asset_to_coin_mappings",
+            get_config_table_chunk_size::<PostgresFungibleAssetToCoinMapping>(
+                "fungible_asset_to_coin_mappings",
+                &per_table_chunk_sizes,
             ),
         );
-        let cufab_v2 = execute_in_chunks(
+        let cim = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
-            insert_current_unified_fungible_asset_balances_v2_query,
-            &current_unified_fab_v2,
-            get_config_table_chunk_size::<PostgresCurrentUnifiedFungibleAssetBalance>(
-                "current_unified_fungible_asset_balances",
+            &self.table_names,
+            insert_coin_info_mutations_query,
+            &coin_info_mutations,
+            "coin_info_mutations",
+            get_config_table_chunk_size::<PostgresCoinInfoMutation>(
+                "coin_info_mutations",
                 &per_table_chunk_sizes,
             ),
         );
-        let fatcm = execute_in_chunks(
+        let ccb = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
-            insert_fungible_asset_to_coin_mappings_query,
-            &fa_to_coin_mappings,
-            get_config_table_chunk_size::<PostgresFungibleAssetToCoinMapping>(
-                "fungible_asset_to_coin_mappings",
+            &self.table_names,
+            insert_current_coin_balances_query,
+            &current_coin_balances,
+            "current_coin_balances",
+            get_config_table_chunk_size::<CurrentCoinBalance>(
+                "current_coin_balances",
+                &per_table_chunk_sizes,
+            ),
+        );
+        let csfe = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_coin_store_freeze_events_query,
+            &coin_store_freeze_events,
+            "coin_store_freeze_events",
+            get_config_table_chunk_size::<PostgresCoinStoreFreezeEvent>(
+                "coin_store_freeze_events",
                 &per_table_chunk_sizes,
             ),
         );
-        let (faa_res, fam_res, cufab1_res, cufab2_res, fatcm_res) =
-            tokio::join!(faa, fam, cufab_v1, cufab_v2, fatcm);
-        for res in [faa_res, fam_res, cufab1_res, cufab2_res, fatcm_res] {
+        let (faa_res, fam_res, cufab_res, fatcm_res, cim_res, ccb_res, csfe_res) =
+            tokio::join!(faa, fam, cufab, fatcm, cim, ccb, csfe);
+        for res in [
+            faa_res, fam_res, cufab_res, fatcm_res, cim_res, ccb_res, csfe_res,
+        ] {
             match res {
                 Ok(_) => {},
                 Err(e) => {
@@ -177,6 +292,15 @@ impl Processable for FungibleAssetStorer {
             }
         }
 
+        // The first batch written with the counts turned on recomputed them
+        self.holder_counts_seeded = counting_holders;
+        if let Some(window) = self.recent_fab_v1_writes.as_mut() {
+            window.record(&current_unified_fab_v1);
+        }
+        if let Some(window) = self.recent_fab_v2_writes.as_mut() {
+            window.record(&current_unified_fab_v2);
+        }
+
         Ok(Some(TransactionContext {
             data: (),
             metadata: input.metadata,
@@ -184,6 +308,173 @@ impl Processable for FungibleAssetStorer {
     }
 }
 
+impl FungibleAssetStorer {
+    /// Writes a batch's balances and the holder count changes they make in one transaction, so
+    /// the counts never miss nor repeat a batch. The balances go through the `WriteSink`s like
+    /// any other batch, and the rows a sink holds back aren't counted.
+    async fn write_balances_with_holder_counts(
+        &self,
+        current_unified_fab_v1: &[PostgresCurrentUnifiedFungibleAssetBalance],
+        current_unified_fab_v2: &[PostgresCurrentUnifiedFungibleAssetBalance],
+        chunk_size: usize,
+        holder_counts_chunk_size: usize,
+    ) -> Result<(), ProcessorError> {
+        let mut balances_v1: Vec<_> = current_unified_fab_v1.iter().collect();
+        balances_v1.sort_by(|a, b| a.cmp_primary_key(b));
+        let mut balances_v2: Vec<_> = current_unified_fab_v2.iter().collect();
+        balances_v2.sort_by(|a, b| a.cmp_primary_key(b));
+        let rows = BalancesWithHolderCounts {
+            rows: balances_v1
+                .into_iter()
+                .map(|balance| (balance, true))
+                .chain(balances_v2.into_iter().map(|balance| (balance, false)))
+                .collect(),
+            seed_holder_counts: !self.holder_counts_seeded,
+            chunk_size: chunk_size.max(1),
+            holder_counts_chunk_size: holder_counts_chunk_size.max(1),
+            table_names: self.table_names.clone(),
+        };
+        if rows.rows.is_empty() {
+            // Nothing to write, but the counts may still have to be seeded
+            return if rows.seed_holder_counts && !write_sinks().replaces_database() {
+                rows.write(&[])(self.conn_pool.clone()).await.map(|_| ())
+            } else {
+                Ok(())
+            };
+        }
+        write_batch(
+            self.conn_pool.clone(),
+            &write_sinks(),
+            TableBatch::new("current_unified_fungible_asset_balances", &rows),
+        )
+        .instrument(table_span("current_unified_fungible_asset_balances"))
+        .await
+    }
+}
+
+/// A batch's `current_fungible_asset_balances` rows, flagged v1 or v2, written in one transaction
+/// with the `coin_holder_counts` changes they make
+struct BalancesWithHolderCounts<'a> {
+    rows: Vec<(&'a PostgresCurrentUnifiedFungibleAssetBalance, bool)>,
+    /// Recomputes the counts from the stored balances before adding the batch's changes
+    seed_holder_counts: bool,
+    chunk_size: usize,
+    holder_counts_chunk_size: usize,
+    table_names: TableNames,
+}
+
+impl TableRows for BalancesWithHolderCounts<'_> {
+    fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn to_json(&self) -> serde_json::Result<Vec<Value>> {
+        self.rows
+            .iter()
+            .map(|(balance, _)| serde_json::to_value(balance))
+            .collect()
+    }
+
+    fn write(&self, indices: &[usize]) -> OwnedWrite {
+        let (balances_v1, balances_v2): (Vec<_>, Vec<_>) = indices
+            .iter()
+            .map(|index| self.rows[*index])
+            .partition(|(_, is_v1)| *is_v1);
+        let balances_v1: Vec<_> = balances_v1
+            .into_iter()
+            .map(|(balance, _)| balance.clone())
+            .collect();
+        let balances_v2: Vec<_> = balances_v2
+            .into_iter()
+            .map(|(balance, _)| balance.clone())
+            .collect();
+        let seed_holder_counts = self.seed_holder_counts;
+        let chunk_size = self.chunk_size;
+        let holder_counts_chunk_size = self.holder_counts_chunk_size;
+        let table_names = self.table_names.clone();
+        Box::new(move |conn_pool: ArcDbPool| {
+            async move {
+                let mut conn = conn_pool
+                    .get()
+                    .await
+                    .map_err(|e| ProcessorError::DBStoreError {
+                        message: format!("Failed to get connection from pool: {e:?}"),
+                        query: None,
+                    })?;
+                let (rows_written, holder_counts) = conn
+                    .transaction::<_, diesel::result::Error, _>(|conn| {
+                        async move {
+                            if seed_holder_counts {
+                                for query in PostgresCoinHolderCount::seed_queries() {
+                                    table_names.rename(query).execute(conn).await?;
+                                }
+                            }
+                            let previous = PostgresCoinHolderCount::get_previous_balances(
+                                conn,
+                                &balances_v1,
+                                &balances_v2,
+                            )
+                            .await?;
+                            let other_holders = PostgresCoinHolderCount::get_other_holders(
+                                conn,
+                                &previous,
+                                &balances_v1,
+                                &balances_v2,
+                            )
+                            .await?;
+                            let holder_counts = PostgresCoinHolderCount::from_balance_changes(
+                                &previous,
+                                &other_holders,
+                                &balances_v1,
+                                &balances_v2,
+                            );
+                            let mut rows_written = 0;
+                            for chunk in balances_v1.chunks(chunk_size) {
+                                rows_written += table_names
+                                    .rename(
+                                        insert_current_unified_fungible_asset_balances_v1_query(
+                                            chunk.to_vec(),
+                                        ),
+                                    )
+                                    .execute(conn)
+                                    .await?;
+                            }
+                            for chunk in balances_v2.chunks(chunk_size) {
+                                rows_written += table_names
+                                    .rename(
+                                        insert_current_unified_fungible_asset_balances_v2_query(
+                                            chunk.to_vec(),
+                                        ),
+                                    )
+                                    .execute(conn)
+                                    .await?;
+                            }
+                            for chunk in holder_counts.chunks(holder_counts_chunk_size) {
+                                table_names
+                                    .rename(insert_coin_holder_counts_query(chunk.to_vec()))
+                                    .execute(conn)
+                                    .await?;
+                            }
+                            Ok((rows_written, holder_counts.len()))
+                        }
+                        .scope_boxed()
+                    })
+                    .await
+                    .map_err(|e| ProcessorError::DBStoreError {
+                        message: format!("Failed to write balances and holder counts: {e:?}"),
+                        query: None,
+                    })?;
+                record_table_stats("coin_holder_counts", TableWriteStats {
+                    rows_written: holder_counts as i64,
+                    ..Default::default()
+                });
+                Ok(rows_written)
+            }
+            .boxed()
+        })
+    }
+}
+
 impl AsyncStep for FungibleAssetStorer {}
 
 impl NamedStep for FungibleAssetStorer {
@@ -201,7 +492,11 @@ pub fn insert_fungible_asset_activities_query(
         .values(items_to_insert)
         .on_conflict((transaction_version, event_index))
         .do_update()
-        .set(storage_id.eq(excluded(storage_id)))
+        .set((
+            storage_id.eq(excluded(storage_id)),
+            is_migration.eq(excluded(is_migration)),
+            usd_value.eq(excluded(usd_value)),
+        ))
 }
 
 pub fn insert_fungible_asset_metadata_query(
@@ -230,10 +525,9 @@ pub fn insert_fungible_asset_metadata_query(
             supply_v2.eq(excluded(supply_v2)),
             maximum_v2.eq(excluded(maximum_v2)),
         ))
-        .filter(
+        .filter(version_guard!(
             schema::fungible_asset_metadata::last_transaction_version
-                .le(excluded(last_transaction_version)),
-        )
+        ))
 }
 
 pub fn insert_fungible_asset_balances_query(
@@ -265,11 +559,7 @@ pub fn insert_current_unified_fungible_asset_balances_v1_query(
             last_transaction_version_v1.eq(excluded(last_transaction_version_v1)),
             inserted_at.eq(excluded(inserted_at)),
         ))
-        .filter(
-            last_transaction_version_v1
-                .is_null()
-                .or(last_transaction_version_v1.le(excluded(last_transaction_version_v1))),
-        )
+        .filter(version_guard!(nullable last_transaction_version_v1))
 }
 
 pub fn insert_current_unified_fungible_asset_balances_v2_query(
@@ -295,11 +585,7 @@ pub fn insert_current_unified_fungible_asset_balances_v2_query(
             last_transaction_version_v2.eq(excluded(last_transaction_version_v2)),
             inserted_at.eq(excluded(inserted_at)),
         ))
-        .filter(
-            last_transaction_version_v2
-                .is_null()
-                .or(last_transaction_version_v2.le(excluded(last_transaction_version_v2))),
-        )
+        .filter(version_guard!(nullable last_transaction_version_v2))
 }
 
 pub fn insert_fungible_asset_to_coin_mappings_query(
@@ -315,5 +601,64 @@ pub fn insert_fungible_asset_to_coin_mappings_query(
             coin_type.eq(excluded(coin_type)),
             last_transaction_version.eq(excluded(last_transaction_version)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
+}
+
+pub fn insert_coin_info_mutations_query(
+    items_to_insert: Vec<PostgresCoinInfoMutation>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::coin_info_mutations::dsl::*;
+
+    diesel::insert_into(schema::coin_info_mutations::table)
+        .values(items_to_insert)
+        .on_conflict((transaction_version, coin_type_hash, mutation_type))
+        .do_nothing()
+}
+
+pub fn insert_coin_holder_counts_query(
+    items_to_insert: Vec<PostgresCoinHolderCount>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::coin_holder_counts::dsl::*;
+
+    // Counts are changes. They're written in the same transaction as the balances they're
+    // diffed against, so a retried batch finds its balances stored and changes nothing.
+    diesel::insert_into(schema::coin_holder_counts::table)
+        .values(items_to_insert)
+        .on_conflict(asset_type)
+        .do_update()
+        .set((
+            holder_count.eq(holder_count + excluded(holder_count)),
+            owner_count.eq(owner_count + excluded(owner_count)),
+            last_transaction_version.eq(excluded(last_transaction_version)),
+        ))
+}
+
+pub fn insert_current_coin_balances_query(
+    items_to_insert: Vec<CurrentCoinBalance>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::current_coin_balances::dsl::*;
+
+    diesel::insert_into(schema::current_coin_balances::table)
+        .values(items_to_insert)
+        .on_conflict((owner_address, coin_type_hash))
+        .do_update()
+        .set((
+            amount.eq(excluded(amount)),
+            is_frozen.eq(excluded(is_frozen)),
+            last_transaction_version.eq(excluded(last_transaction_version)),
+            last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
+            inserted_at.eq(excluded(inserted_at)),
+        ))
+        .filter(version_guard!(last_transaction_version))
+}
+
+pub fn insert_coin_store_freeze_events_query(
+    items_to_insert: Vec<PostgresCoinStoreFreezeEvent>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::coin_store_freeze_events::dsl::*;
+
+    diesel::insert_into(schema::coin_store_freeze_events::table)
+        .values(items_to_insert)
+        .on_conflict((transaction_version, owner_address, coin_type_hash))
+        .do_nothing()
 }
@@ -5,12 +5,16 @@ use super::models::GasFee;
 use crate::{
     config::processor_config::DefaultProcessorConfig,
     schema,
-    utils::table_flags::{filter_data, TableFlags},
+    utils::{
+        table_flags::{filter_data, TableFlags},
+        table_names::TableNames,
+        table_stats::execute_in_chunks_with_stats,
+    },
 };
 use ahash::AHashMap;
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    postgres::utils::database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
@@ -23,6 +27,7 @@ where
     Self: Sized + Send + 'static,
 {
     conn_pool: ArcDbPool,
+    table_names: TableNames,
     processor_config: DefaultProcessorConfig,
     tables_to_write: TableFlags,
 }
@@ -30,11 +35,13 @@ where
 impl GasFeeStorer {
     pub fn new(
         conn_pool: ArcDbPool,
+        table_names: TableNames,
         processor_config: DefaultProcessorConfig,
         tables_to_write: TableFlags,
     ) -> Self {
         Self {
             conn_pool,
+            table_names,
             processor_config,
             tables_to_write,
         }
@@ -58,10 +65,12 @@ impl Processable for GasFeeStorer {
 
         let gas_fees = filter_data(&self.tables_to_write, TableFlags::GAS_FEES, gas_fees);
 
-        let gf = execute_in_chunks(
+        let gf = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_gas_fee_query,
             &gas_fees,
+            "gas_fees",
             get_config_table_chunk_size::<GasFee>("gas_fees", &per_table_chunk_sizes),
         );
 
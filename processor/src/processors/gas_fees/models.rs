@@ -33,6 +33,10 @@ pub struct GasFee {
     pub block_height: i64,
     pub transaction_timestamp: NaiveDateTime,
     pub storage_refund_amount: BigDecimal,
+    /// The account charged the fee, the fee payer of sponsored transactions or else the sender
+    pub fee_payer_address: Option<String>,
+    /// Whether the fee was paid by an account other than the sender
+    pub is_sponsored: bool,
 }
 
 impl GasFee {
@@ -93,12 +97,13 @@ impl GasFee {
             Some(signature) => get_fee_payer_address(signature, transaction_version),
             None => None,
         };
+        let owner_address = standardize_address(&user_transaction_request.sender.to_string());
+        let (fee_payer_address, is_sponsored) =
+            Self::attribute_fee(&owner_address, gas_fee_payer_address.as_deref());
 
         Self {
             transaction_version,
-            owner_address: Some(standardize_address(
-                &user_transaction_request.sender.to_string(),
-            )),
+            owner_address: Some(owner_address),
             amount: Some(cedra_coin_burned),
             gas_fee_payer_address,
             is_transaction_success: txn_info.success,
@@ -108,6 +113,38 @@ impl GasFee {
             storage_refund_amount: fee_statement
                 .map(|fs| u64_to_bigdecimal(fs.storage_fee_refund_octas))
                 .unwrap_or(BigDecimal::zero()),
+            fee_payer_address: Some(fee_payer_address),
+            is_sponsored,
         }
     }
+
+    /// The account paying the fee and whether it's sponsored. A fee payer signature naming the
+    /// sender itself isn't a sponsorship.
+    fn attribute_fee(sender: &str, gas_fee_payer_address: Option<&str>) -> (String, bool) {
+        match gas_fee_payer_address {
+            Some(fee_payer) => (fee_payer.to_string(), fee_payer != sender),
+            None => (sender.to_string(), false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attribute_fee() {
+        assert_eq!(
+            GasFee::attribute_fee("0xsender", Some("0xsponsor")),
+            ("0xsponsor".to_string(), true)
+        );
+        assert_eq!(
+            GasFee::attribute_fee("0xsender", Some("0xsender")),
+            ("0xsender".to_string(), false)
+        );
+        assert_eq!(
+            GasFee::attribute_fee("0xsender", None),
+            ("0xsender".to_string(), false)
+        );
+    }
 }
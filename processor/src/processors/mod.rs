@@ -8,6 +8,10 @@ pub mod gas_fees;
 pub mod monitoring;
 pub mod objects;
 pub mod processor_status_saver;
+pub mod raw_transactions;
+pub mod resource_watcher;
 pub mod stake;
 pub mod token_v2;
+pub mod treasury_flows;
 pub mod user_transaction;
+pub mod validator_transactions;
@@ -0,0 +1,129 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use super::v2_objects_models::Object;
+use crate::{
+    processors::token_v2::token_v2_models::v2_token_utils::{Burn, BurnEvent},
+    schema::deleted_objects,
+};
+use ahash::AHashMap;
+use bigdecimal::BigDecimal;
+use cedra_indexer_processor_sdk::cedra_protos::transaction::v1::{
+    transaction::TxnData, Transaction,
+};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+type TokenAddress = String;
+type PreviousOwnerAddress = Option<String>;
+
+/// Tombstone of an object whose ObjectCore was deleted, kept so that the object's last state
+/// isn't lost once `current_objects` only says `is_deleted`.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, object_address))]
+#[diesel(table_name = deleted_objects)]
+pub struct PostgresDeletedObject {
+    pub transaction_version: i64,
+    pub write_set_change_index: i64,
+    pub object_address: String,
+    pub last_owner_address: String,
+    pub state_key_hash: String,
+    pub last_guid_creation_num: BigDecimal,
+    pub untransferrable: bool,
+    /// Whether a token burn event for the object was emitted by the deleting transaction
+    pub is_token_burn: bool,
+    pub block_timestamp: chrono::NaiveDateTime,
+}
+
+impl PostgresDeletedObject {
+    /// Builds the tombstone from the `objects` row written for the deletion. The burn event's
+    /// previous owner is preferred over the last owner we had indexed, it is the owner at the
+    /// time of the burn.
+    pub fn from_deleted_object(object: &Object, burned_tokens: &BurnedTokens) -> Self {
+        let burn = burned_tokens.get(&object.object_address);
+        let last_owner_address = burn
+            .cloned()
+            .flatten()
+            .unwrap_or_else(|| object.owner_address.clone());
+        Self {
+            transaction_version: object.transaction_version,
+            write_set_change_index: object.write_set_change_index,
+            object_address: object.object_address.clone(),
+            last_owner_address,
+            state_key_hash: object.state_key_hash.clone(),
+            last_guid_creation_num: object.guid_creation_num.clone(),
+            untransferrable: object.untransferrable,
+            is_token_burn: burn.is_some(),
+            block_timestamp: object.block_timestamp,
+        }
+    }
+}
+
+/// Tokens burned by a transaction, with the owner at the time of the burn if the event has it
+pub type BurnedTokens = AHashMap<TokenAddress, PreviousOwnerAddress>;
+
+pub fn get_burned_tokens(transaction: &Transaction, txn_version: i64) -> BurnedTokens {
+    let events = match transaction.txn_data.as_ref() {
+        Some(TxnData::User(user_txn)) => &user_txn.events,
+        _ => return BurnedTokens::new(),
+    };
+    let mut burned_tokens = BurnedTokens::new();
+    for event in events {
+        if let Some(burn) = Burn::from_event(event, txn_version).unwrap() {
+            burned_tokens.insert(burn.get_token_address(), burn.get_previous_owner_address());
+        } else if let Some(burn_event) = BurnEvent::from_event(event, txn_version).unwrap() {
+            burned_tokens
+                .entry(burn_event.get_token_address())
+                .or_insert(None);
+        }
+    }
+    burned_tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deleted_object(object_address: &str) -> Object {
+        Object {
+            transaction_version: 10,
+            write_set_change_index: 2,
+            object_address: object_address.to_string(),
+            owner_address: "0xindexed_owner".to_string(),
+            state_key_hash: "0xhash".to_string(),
+            guid_creation_num: BigDecimal::from(3),
+            allow_ungated_transfer: false,
+            is_deleted: true,
+            untransferrable: false,
+            block_timestamp: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn test_tombstone_uses_burn_previous_owner() {
+        let burned_tokens = BurnedTokens::from_iter([
+            ("0xburned".to_string(), Some("0xburn_owner".to_string())),
+            ("0xold_burn".to_string(), None),
+        ]);
+
+        let burned =
+            PostgresDeletedObject::from_deleted_object(&deleted_object("0xburned"), &burned_tokens);
+        assert!(burned.is_token_burn);
+        assert_eq!(burned.last_owner_address, "0xburn_owner");
+
+        let old_burn = PostgresDeletedObject::from_deleted_object(
+            &deleted_object("0xold_burn"),
+            &burned_tokens,
+        );
+        assert!(old_burn.is_token_burn);
+        assert_eq!(old_burn.last_owner_address, "0xindexed_owner");
+
+        let deleted =
+            PostgresDeletedObject::from_deleted_object(&deleted_object("0xother"), &burned_tokens);
+        assert!(!deleted.is_token_burn);
+        assert_eq!(deleted.last_owner_address, "0xindexed_owner");
+    }
+}
@@ -1,5 +1,7 @@
 // Copyright © Cedra Foundation
 // SPDX-License-Identifier: Apache-2.0
+pub mod deleted_objects_models;
+pub mod object_counts_by_owner_models;
 pub mod objects_extractor;
 pub mod objects_processor;
 pub mod objects_storer;
@@ -9,11 +11,13 @@ pub mod v2_objects_models;
 use crate::{
     db::resources::FromWriteResource,
     processors::objects::{
+        deleted_objects_models::{get_burned_tokens, PostgresDeletedObject},
         v2_object_utils::{
             ObjectAggregatedData, ObjectAggregatedDataMapping, ObjectWithMetadata, Untransferable,
         },
         v2_objects_models::{CurrentObject, Object},
     },
+    utils::metadata_cache::CURRENT_OBJECTS,
 };
 use ahash::AHashMap;
 use cedra_indexer_processor_sdk::{
@@ -26,11 +30,12 @@ use cedra_indexer_processor_sdk::{
 pub async fn process_objects(
     transactions: Vec<Transaction>,
     db_context: &mut Option<DbContext<'_>>,
-) -> (Vec<Object>, Vec<CurrentObject>) {
+) -> (Vec<Object>, Vec<CurrentObject>, Vec<PostgresDeletedObject>) {
     // Moving object handling here because we need a single object
     // map through transactions for lookups
     let mut all_objects = vec![];
     let mut all_current_objects = AHashMap::new();
+    let mut all_deleted_objects = vec![];
     let mut object_metadata_helper: ObjectAggregatedDataMapping = AHashMap::new();
 
     for txn in &transactions {
@@ -43,6 +48,7 @@ pub async fn process_objects(
 
         let txn_timestamp =
             parse_timestamp(txn.timestamp.as_ref().unwrap(), txn_version).naive_utc();
+        let burned_tokens = get_burned_tokens(txn, txn_version);
 
         // First pass to get all the object cores
         for wsc in changes.iter() {
@@ -69,6 +75,7 @@ pub async fn process_objects(
                         concurrent_fungible_asset_supply: None,
                         concurrent_fungible_asset_balance: None,
                         token_identifier: None,
+                        royalty: None,
                     });
                 }
             }
@@ -121,6 +128,10 @@ pub async fn process_objects(
                     .await
                     .unwrap()
                     {
+                        all_deleted_objects.push(PostgresDeletedObject::from_deleted_object(
+                            &object,
+                            &burned_tokens,
+                        ));
                         all_objects.push(object.clone());
                         all_current_objects
                             .insert(object.object_address.clone(), current_object.clone());
@@ -131,11 +142,16 @@ pub async fn process_objects(
         }
     }
 
+    // Later batches deleting these objects find their owner in the cache instead of the DB
+    for current_object in all_current_objects.values() {
+        CURRENT_OBJECTS.insert(&current_object.object_address, current_object.clone());
+    }
+
     // Sort by PK
     let mut all_current_objects = all_current_objects
         .into_values()
         .collect::<Vec<CurrentObject>>();
     all_current_objects.sort_by(|a, b| a.object_address.cmp(&b.object_address));
 
-    (all_objects, all_current_objects)
+    (all_objects, all_current_objects, all_deleted_objects)
 }
@@ -0,0 +1,317 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use super::{deleted_objects_models::get_burned_tokens, v2_objects_models::PostgresCurrentObject};
+use crate::{
+    db::resources::FromWriteResource,
+    processors::{
+        fungible_asset::fungible_asset_models::v2_fungible_asset_utils::{
+            FungibleAssetStore, FungibleAssetStoreDeletionEvent,
+        },
+        token_v2::token_v2_models::v2_token_utils::TokenV2,
+    },
+    schema::{current_object_counts_by_owner, current_objects},
+};
+use ahash::AHashMap;
+use cedra_indexer_processor_sdk::{
+    cedra_protos::transaction::v1::{transaction::TxnData, write_set_change::Change, Transaction},
+    postgres::utils::database::DbPoolConnection,
+    utils::convert::standardize_address,
+};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+/// What an object holds, which decides the counts it's part of
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ObjectKind {
+    pub is_token: bool,
+    pub is_fungible_store: bool,
+}
+
+/// Kinds of the objects written or deleted in a batch, by object address
+pub type ObjectKinds = AHashMap<String, ObjectKind>;
+
+/// Number of live objects, tokens and fungible stores per owner, kept up to date by adding the
+/// change of every batch. Counts start from the objects at the time the table is turned on, so
+/// seed it from `current_objects` when enabling it on an indexed chain.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(owner_address))]
+#[diesel(table_name = current_object_counts_by_owner)]
+pub struct PostgresObjectCountByOwner {
+    pub owner_address: String,
+    /// Changes in the counts, added to the stored counts on upsert
+    pub object_count: i64,
+    pub token_count: i64,
+    pub fa_store_count: i64,
+    pub last_transaction_version: i64,
+}
+
+/// The parts of a `current_objects` row that decide who it's counted for
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredObject {
+    pub owner_address: String,
+    pub is_deleted: bool,
+    pub last_transaction_version: i64,
+}
+
+/// Finds which objects of the transactions are tokens or fungible stores. Objects are written as
+/// a resource group, so a token's `0x4::token::Token` and a store's `FungibleStore` come with
+/// every write of the object. Deleted objects are recognized by their burn or store deletion
+/// event instead.
+pub fn get_object_kinds(transactions: &[Transaction]) -> ObjectKinds {
+    let mut object_kinds = ObjectKinds::new();
+    for txn in transactions {
+        let txn_version = txn.version as i64;
+        if let Some(info) = txn.info.as_ref() {
+            for wsc in &info.changes {
+                let Some(Change::WriteResource(write_resource)) = wsc.change.as_ref() else {
+                    continue;
+                };
+                let is_token = matches!(TokenV2::from_write_resource(write_resource), Ok(Some(_)));
+                let is_fungible_store = matches!(
+                    FungibleAssetStore::from_write_resource(write_resource),
+                    Ok(Some(_))
+                );
+                if is_token || is_fungible_store {
+                    let kind = object_kinds
+                        .entry(standardize_address(&write_resource.address))
+                        .or_default();
+                    kind.is_token |= is_token;
+                    kind.is_fungible_store |= is_fungible_store;
+                }
+            }
+        }
+        for token_address in get_burned_tokens(txn, txn_version).into_keys() {
+            object_kinds.entry(token_address).or_default().is_token = true;
+        }
+        if let Some(TxnData::User(user_txn)) = txn.txn_data.as_ref() {
+            for event in &user_txn.events {
+                if let Some(deletion) = FungibleAssetStoreDeletionEvent::from_event(
+                    event.type_str.as_str(),
+                    &event.data,
+                    txn_version,
+                ) {
+                    object_kinds
+                        .entry(standardize_address(&deletion.store))
+                        .or_default()
+                        .is_fungible_store = true;
+                }
+            }
+        }
+    }
+    object_kinds
+}
+
+impl PostgresObjectCountByOwner {
+    /// Count changes from writing a batch's current objects over `previous`, the stored rows of
+    /// the same objects. Replaying a batch whose objects are already stored changes nothing.
+    pub fn from_object_changes(
+        previous: &AHashMap<String, StoredObject>,
+        current_objects: &[PostgresCurrentObject],
+        object_kinds: &ObjectKinds,
+    ) -> Vec<Self> {
+        let mut changes: AHashMap<&str, Self> = AHashMap::new();
+        let mut add = |owner_address: &str, kind: ObjectKind, delta: i64, version: i64| {
+            let change = changes.entry(owner_address).or_insert_with(|| Self {
+                owner_address: owner_address.to_string(),
+                object_count: 0,
+                token_count: 0,
+                fa_store_count: 0,
+                last_transaction_version: version,
+            });
+            change.object_count += delta;
+            change.token_count += if kind.is_token { delta } else { 0 };
+            change.fa_store_count += if kind.is_fungible_store { delta } else { 0 };
+            change.last_transaction_version = change.last_transaction_version.max(version);
+        };
+        for current_object in current_objects {
+            let stored = previous.get(&current_object.object_address);
+            // Skipped like the upsert of the object itself
+            if stored.is_some_and(|stored| {
+                stored.last_transaction_version > current_object.last_transaction_version
+            }) {
+                continue;
+            }
+            let old_owner = stored
+                .filter(|stored| !stored.is_deleted)
+                .map(|stored| stored.owner_address.as_str());
+            let new_owner =
+                (!current_object.is_deleted).then_some(current_object.owner_address.as_str());
+            if old_owner == new_owner {
+                continue;
+            }
+            let kind = object_kinds
+                .get(&current_object.object_address)
+                .copied()
+                .unwrap_or_default();
+            let version = current_object.last_transaction_version;
+            if let Some(owner_address) = old_owner {
+                add(owner_address, kind, -1, version);
+            }
+            if let Some(owner_address) = new_owner {
+                add(owner_address, kind, 1, version);
+            }
+        }
+        changes
+            .into_values()
+            .filter(|change| {
+                change.object_count != 0 || change.token_count != 0 || change.fa_store_count != 0
+            })
+            .collect()
+    }
+
+    /// Loads the stored rows of the objects written in a batch
+    pub async fn get_previous_objects(
+        conn: &mut DbPoolConnection<'_>,
+        current_objects: &[PostgresCurrentObject],
+    ) -> diesel::QueryResult<AHashMap<String, StoredObject>> {
+        use current_objects::dsl;
+
+        let object_addresses: Vec<&String> = current_objects
+            .iter()
+            .map(|current_object| &current_object.object_address)
+            .collect();
+        let rows = dsl::current_objects
+            .filter(dsl::object_address.eq_any(object_addresses))
+            .select((
+                dsl::object_address,
+                dsl::owner_address,
+                dsl::is_deleted,
+                dsl::last_transaction_version,
+            ))
+            .load::<(String, String, bool, i64)>(conn)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(
+                |(object_address, owner_address, is_deleted, last_transaction_version)| {
+                    (object_address, StoredObject {
+                        owner_address,
+                        is_deleted,
+                        last_transaction_version,
+                    })
+                },
+            )
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+
+    fn current_object(
+        object_address: &str,
+        owner_address: &str,
+        is_deleted: bool,
+        version: i64,
+    ) -> PostgresCurrentObject {
+        PostgresCurrentObject {
+            object_address: object_address.to_string(),
+            owner_address: owner_address.to_string(),
+            state_key_hash: "0xhash".to_string(),
+            allow_ungated_transfer: true,
+            last_guid_creation_num: BigDecimal::from(1),
+            last_transaction_version: version,
+            is_deleted,
+            untransferrable: false,
+        }
+    }
+
+    fn stored(owner_address: &str, version: i64) -> StoredObject {
+        StoredObject {
+            owner_address: owner_address.to_string(),
+            is_deleted: false,
+            last_transaction_version: version,
+        }
+    }
+
+    fn counts(changes: Vec<PostgresObjectCountByOwner>) -> Vec<(String, i64, i64, i64)> {
+        let mut counts: Vec<_> = changes
+            .into_iter()
+            .map(|change| {
+                (
+                    change.owner_address,
+                    change.object_count,
+                    change.token_count,
+                    change.fa_store_count,
+                )
+            })
+            .collect();
+        counts.sort();
+        counts
+    }
+
+    #[test]
+    fn test_object_count_changes() {
+        let previous: AHashMap<String, StoredObject> = [
+            ("0xtoken".to_string(), stored("0xalice", 1)),
+            ("0xstore".to_string(), stored("0xalice", 1)),
+            ("0xburned".to_string(), stored("0xbob", 1)),
+        ]
+        .into_iter()
+        .collect();
+        let object_kinds = ObjectKinds::from_iter([
+            ("0xtoken".to_string(), ObjectKind {
+                is_token: true,
+                is_fungible_store: false,
+            }),
+            ("0xstore".to_string(), ObjectKind {
+                is_token: false,
+                is_fungible_store: true,
+            }),
+            ("0xburned".to_string(), ObjectKind {
+                is_token: true,
+                is_fungible_store: false,
+            }),
+        ]);
+        let current_objects = vec![
+            // Transferred
+            current_object("0xtoken", "0xbob", false, 10),
+            // Unchanged owner
+            current_object("0xstore", "0xalice", false, 10),
+            // Burned
+            current_object("0xburned", "0xbob", true, 10),
+            // Created
+            current_object("0xplain", "0xbob", false, 11),
+        ];
+
+        let changes = PostgresObjectCountByOwner::from_object_changes(
+            &previous,
+            &current_objects,
+            &object_kinds,
+        );
+        assert_eq!(counts(changes), vec![
+            ("0xalice".to_string(), -1, -1, 0),
+            ("0xbob".to_string(), 1, 0, 0),
+        ]);
+    }
+
+    #[test]
+    fn test_replayed_and_stale_objects_change_nothing() {
+        let previous: AHashMap<String, StoredObject> = [("0xa".to_string(), stored("0xalice", 10))]
+            .into_iter()
+            .collect();
+
+        let replayed = vec![current_object("0xa", "0xalice", false, 10)];
+        assert!(PostgresObjectCountByOwner::from_object_changes(
+            &previous,
+            &replayed,
+            &ObjectKinds::new()
+        )
+        .is_empty());
+        let stale = vec![current_object("0xa", "0xbob", false, 9)];
+        assert!(PostgresObjectCountByOwner::from_object_changes(
+            &previous,
+            &stale,
+            &ObjectKinds::new()
+        )
+        .is_empty());
+    }
+}
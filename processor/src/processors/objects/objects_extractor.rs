@@ -1,4 +1,6 @@
 use crate::processors::objects::{
+    deleted_objects_models::PostgresDeletedObject,
+    object_counts_by_owner_models::{get_object_kinds, ObjectKinds},
     process_objects,
     v2_objects_models::{PostgresCurrentObject, PostgresObject},
 };
@@ -34,14 +36,26 @@ impl ObjectsExtractor {
 #[async_trait]
 impl Processable for ObjectsExtractor {
     type Input = Vec<Transaction>;
-    type Output = (Vec<PostgresObject>, Vec<PostgresCurrentObject>);
+    type Output = (
+        Vec<PostgresObject>,
+        Vec<PostgresCurrentObject>,
+        Vec<PostgresDeletedObject>,
+        ObjectKinds,
+    );
     type RunType = AsyncRunType;
 
     async fn process(
         &mut self,
         transactions: TransactionContext<Vec<Transaction>>,
     ) -> Result<
-        Option<TransactionContext<(Vec<PostgresObject>, Vec<PostgresCurrentObject>)>>,
+        Option<
+            TransactionContext<(
+                Vec<PostgresObject>,
+                Vec<PostgresCurrentObject>,
+                Vec<PostgresDeletedObject>,
+                ObjectKinds,
+            )>,
+        >,
         ProcessorError,
     > {
         let conn = self
@@ -60,7 +74,8 @@ impl Processable for ObjectsExtractor {
             query_retry_delay_ms,
         };
 
-        let (raw_objects, raw_all_current_objects) =
+        let object_kinds = get_object_kinds(&transactions.data);
+        let (raw_objects, raw_all_current_objects, deleted_objects) =
             process_objects(transactions.data, &mut Some(db_connection)).await;
 
         let postgres_objects: Vec<PostgresObject> =
@@ -72,7 +87,12 @@ impl Processable for ObjectsExtractor {
             .collect();
 
         Ok(Some(TransactionContext {
-            data: (postgres_objects, postgres_all_current_objects),
+            data: (
+                postgres_objects,
+                postgres_all_current_objects,
+                deleted_objects,
+                object_kinds,
+            ),
             metadata: transactions.metadata,
         }))
     }
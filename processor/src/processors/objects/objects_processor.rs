@@ -6,28 +6,26 @@ use crate::{
         },
         processor_config::{DefaultProcessorConfig, ProcessorConfig},
     },
+    db::{connection_pool::new_postgres_pool, schema_drift::run_migrations_and_check_drift},
     processors::{
         objects::{objects_extractor::ObjectsExtractor, objects_storer::ObjectsStorer},
         processor_status_saver::{
             get_end_version, get_starting_version, PostgresProcessorStatusSaver,
         },
     },
-    utils::table_flags::TableFlags,
-    MIGRATIONS,
+    utils::{
+        logging::BatchSpanStep,
+        supervisor::supervise_step_tasks,
+        table_flags::TableFlags,
+        transaction_source::{check_chain_id, TransactionSourceStep},
+    },
 };
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    cedra_indexer_transaction_stream::TransactionStreamConfig,
     builder::ProcessorBuilder,
-    common_steps::{
-        TransactionStreamStep, VersionTrackerStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
-    },
-    postgres::utils::{
-        checkpoint::PostgresChainIdChecker,
-        database::{new_db_pool, run_migrations, ArcDbPool},
-    },
+    common_steps::{VersionTrackerStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS},
+    postgres::utils::database::ArcDbPool,
     traits::{processor_trait::ProcessorTrait, IntoRunnableStep},
-    utils::chain_id_check::check_or_update_chain_id,
 };
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
@@ -61,12 +59,7 @@ impl ObjectsProcessor {
     pub async fn new(config: IndexerProcessorConfig) -> Result<Self> {
         match config.db_config {
             DbConfig::PostgresConfig(ref postgres_config) => {
-                let conn_pool = new_db_pool(
-                    &postgres_config.connection_string,
-                    Some(postgres_config.db_pool_size),
-                )
-                .await
-                .map_err(|e| {
+                let conn_pool = new_postgres_pool(postgres_config).await.map_err(|e| {
                     anyhow::anyhow!(
                         "Failed to create connection pool for PostgresConfig: {:?}",
                         e
@@ -95,12 +88,7 @@ impl ProcessorTrait for ObjectsProcessor {
     async fn run_processor(&self) -> Result<()> {
         //  Run migrations
         if let DbConfig::PostgresConfig(ref postgres_config) = self.config.db_config {
-            run_migrations(
-                postgres_config.connection_string.clone(),
-                self.db_pool.clone(),
-                MIGRATIONS,
-            )
-            .await;
+            run_migrations_and_check_drift(postgres_config, self.db_pool.clone()).await?;
         }
 
         // Merge the starting version from config and the latest processed version from the DB
@@ -110,11 +98,7 @@ impl ProcessorTrait for ObjectsProcessor {
         );
 
         // Check and update the ledger chain id to ensure we're indexing the correct chain
-        check_or_update_chain_id(
-            &self.config.transaction_stream_config,
-            &PostgresChainIdChecker::new(self.db_pool.clone()),
-        )
-        .await?;
+        check_chain_id(&self.config, self.db_pool.clone()).await?;
 
         let processor_config = match &self.config.processor_config {
             ProcessorConfig::ObjectsProcessor(processor_config) => processor_config,
@@ -124,12 +108,8 @@ impl ProcessorTrait for ObjectsProcessor {
         let per_table_chunk_sizes = &processor_config.default_config.per_table_chunk_sizes;
 
         // Define processor steps
-        let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
-            starting_version,
-            request_ending_version: ending_version,
-            ..self.config.transaction_stream_config.clone()
-        })
-        .await?;
+        let transaction_stream =
+            TransactionSourceStep::new(&self.config, starting_version, ending_version).await?;
         let objects_extractor = ObjectsExtractor::new(
             processor_config.query_retries,
             processor_config.query_retry_delay_ms,
@@ -138,6 +118,7 @@ impl ProcessorTrait for ObjectsProcessor {
         let opt_in_tables = TableFlags::from_set(&processor_config.default_config.tables_to_write);
         let objects_storer = ObjectsStorer::new(
             self.db_pool.clone(),
+            self.config.table_names(),
             per_table_chunk_sizes.clone(),
             opt_in_tables,
         );
@@ -147,13 +128,20 @@ impl ProcessorTrait for ObjectsProcessor {
             DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
         );
         // Connect processor steps together
-        let (_, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
+        let (processor_builder, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
             transaction_stream.into_runnable_step(),
         )
-        .connect_to(objects_extractor.into_runnable_step(), channel_size)
-        .connect_to(objects_storer.into_runnable_step(), channel_size)
+        .connect_to(
+            BatchSpanStep::new(objects_extractor, self.name()).into_runnable_step(),
+            channel_size,
+        )
+        .connect_to(
+            BatchSpanStep::new(objects_storer, self.name()).into_runnable_step(),
+            channel_size,
+        )
         .connect_to(version_tracker.into_runnable_step(), channel_size)
         .end_and_return_output_receiver(channel_size);
+        supervise_step_tasks(processor_builder.join_handles);
 
         loop {
             match buffer_receiver.recv().await {
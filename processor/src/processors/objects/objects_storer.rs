@@ -1,13 +1,23 @@
 use crate::{
     filter_datasets,
-    processors::objects::v2_objects_models::{PostgresCurrentObject, PostgresObject},
+    processors::objects::{
+        deleted_objects_models::PostgresDeletedObject,
+        object_counts_by_owner_models::{ObjectKinds, PostgresObjectCountByOwner},
+        v2_objects_models::{PostgresCurrentObject, PostgresObject},
+    },
     schema,
-    utils::table_flags::{filter_data, TableFlags},
+    utils::{
+        dedup::dedup_by_pk_keep_latest,
+        table_flags::{filter_data, should_write, TableFlags},
+        table_names::TableNames,
+        table_stats::execute_in_chunks_with_stats,
+    },
+    version_guard,
 };
 use ahash::AHashMap;
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    postgres::utils::database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
@@ -25,6 +35,7 @@ where
     Self: Sized + Send + 'static,
 {
     conn_pool: ArcDbPool,
+    table_names: TableNames,
     per_table_chunk_sizes: AHashMap<String, usize>,
     tables_to_write: TableFlags,
 }
@@ -32,11 +43,13 @@ where
 impl ObjectsStorer {
     pub fn new(
         conn_pool: ArcDbPool,
+        table_names: TableNames,
         per_table_chunk_sizes: AHashMap<String, usize>,
         tables_to_write: TableFlags,
     ) -> Self {
         Self {
             conn_pool,
+            table_names,
             per_table_chunk_sizes,
             tables_to_write,
         }
@@ -45,15 +58,25 @@ impl ObjectsStorer {
 
 #[async_trait]
 impl Processable for ObjectsStorer {
-    type Input = (Vec<PostgresObject>, Vec<PostgresCurrentObject>);
+    type Input = (
+        Vec<PostgresObject>,
+        Vec<PostgresCurrentObject>,
+        Vec<PostgresDeletedObject>,
+        ObjectKinds,
+    );
     type Output = ();
     type RunType = AsyncRunType;
 
     async fn process(
         &mut self,
-        input: TransactionContext<(Vec<PostgresObject>, Vec<PostgresCurrentObject>)>,
+        input: TransactionContext<(
+            Vec<PostgresObject>,
+            Vec<PostgresCurrentObject>,
+            Vec<PostgresDeletedObject>,
+            ObjectKinds,
+        )>,
     ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
-        let (objects, current_objects) = input.data;
+        let (objects, current_objects, deleted_objects, object_kinds) = input.data;
 
         let objects = filter_data(&self.tables_to_write, TableFlags::OBJECTS, objects);
 
@@ -63,30 +86,79 @@ impl Processable for ObjectsStorer {
             current_objects,
         );
 
-        let (objects, current_objects) = filter_datasets!(self, {
+        let (objects, current_objects, deleted_objects) = filter_datasets!(self, {
             objects => TableFlags::OBJECTS,
             current_objects => TableFlags::CURRENT_OBJECTS,
+            deleted_objects => TableFlags::DELETED_OBJECTS,
         });
 
-        let io = execute_in_chunks(
+        let current_objects = dedup_by_pk_keep_latest(current_objects);
+
+        // Diffed against the stored objects, so this has to run before they're overwritten
+        let object_counts_by_owner = if should_write(
+            &self.tables_to_write,
+            TableFlags::CURRENT_OBJECT_COUNTS_BY_OWNER,
+        ) {
+            self.get_object_counts_by_owner(&current_objects, &object_kinds)
+                .await
+                .map_err(|e| ProcessorError::DBStoreError {
+                    message: format!(
+                        "Failed to get object counts for versions {} to {}: {:?}",
+                        input.metadata.start_version, input.metadata.end_version, e,
+                    ),
+                    query: None,
+                })?
+        } else {
+            vec![]
+        };
+
+        let io = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_objects_query,
             &objects,
+            "objects",
             get_config_table_chunk_size::<PostgresObject>("objects", &self.per_table_chunk_sizes),
         );
 
-        let co = execute_in_chunks(
+        let co = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_current_objects_query,
             &current_objects,
+            "current_objects",
             get_config_table_chunk_size::<PostgresCurrentObject>(
                 "current_objects",
                 &self.per_table_chunk_sizes,
             ),
         );
 
-        let (io_res, co_res) = tokio::join!(io, co);
-        for res in [io_res, co_res] {
+        let dobj = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_deleted_objects_query,
+            &deleted_objects,
+            "deleted_objects",
+            get_config_table_chunk_size::<PostgresDeletedObject>(
+                "deleted_objects",
+                &self.per_table_chunk_sizes,
+            ),
+        );
+
+        let cocbo = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_current_object_counts_by_owner_query,
+            &object_counts_by_owner,
+            "current_object_counts_by_owner",
+            get_config_table_chunk_size::<PostgresObjectCountByOwner>(
+                "current_object_counts_by_owner",
+                &self.per_table_chunk_sizes,
+            ),
+        );
+
+        let (io_res, co_res, dobj_res, cocbo_res) = tokio::join!(io, co, dobj, cocbo);
+        for res in [io_res, co_res, dobj_res, cocbo_res] {
             match res {
                 Ok(_) => {},
                 Err(e) => {
@@ -108,6 +180,26 @@ impl Processable for ObjectsStorer {
     }
 }
 
+impl ObjectsStorer {
+    async fn get_object_counts_by_owner(
+        &self,
+        current_objects: &[PostgresCurrentObject],
+        object_kinds: &ObjectKinds,
+    ) -> Result<Vec<PostgresObjectCountByOwner>> {
+        if current_objects.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut conn = self.conn_pool.get().await?;
+        let previous =
+            PostgresObjectCountByOwner::get_previous_objects(&mut conn, current_objects).await?;
+        Ok(PostgresObjectCountByOwner::from_object_changes(
+            &previous,
+            current_objects,
+            object_kinds,
+        ))
+    }
+}
+
 impl AsyncStep for ObjectsStorer {}
 
 impl NamedStep for ObjectsStorer {
@@ -145,5 +237,34 @@ pub fn insert_current_objects_query(
             inserted_at.eq(excluded(inserted_at)),
             untransferrable.eq(excluded(untransferrable)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
+}
+
+pub fn insert_deleted_objects_query(
+    items_to_insert: Vec<PostgresDeletedObject>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::deleted_objects::dsl::*;
+    diesel::insert_into(schema::deleted_objects::table)
+        .values(items_to_insert)
+        .on_conflict((transaction_version, object_address))
+        .do_nothing()
+}
+
+pub fn insert_current_object_counts_by_owner_query(
+    items_to_insert: Vec<PostgresObjectCountByOwner>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::current_object_counts_by_owner::dsl::*;
+
+    // Counts are changes, a retried batch must not add them twice
+    diesel::insert_into(schema::current_object_counts_by_owner::table)
+        .values(items_to_insert)
+        .on_conflict(owner_address)
+        .do_update()
+        .set((
+            object_count.eq(object_count + excluded(object_count)),
+            token_count.eq(token_count + excluded(token_count)),
+            fa_store_count.eq(fa_store_count + excluded(fa_store_count)),
+            last_transaction_version.eq(excluded(last_transaction_version)),
+        ))
+        .filter(last_transaction_version.lt(excluded(last_transaction_version)))
 }
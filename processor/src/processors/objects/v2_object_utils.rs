@@ -13,8 +13,8 @@ use crate::{
             FungibleAssetStore, FungibleAssetSupply,
         },
         token_v2::token_v2_models::v2_token_utils::{
-            CedraCollection, ConcurrentSupply, FixedSupply, PropertyMapModel, TokenIdentifiers,
-            TokenV2, TransferEvent, UnlimitedSupply,
+            CedraCollection, ConcurrentSupply, FixedSupply, PropertyMapModel, Royalty,
+            TokenIdentifiers, TokenV2, TransferEvent, UnlimitedSupply,
         },
     },
 };
@@ -56,6 +56,7 @@ pub struct ObjectAggregatedData {
     pub unlimited_supply: Option<UnlimitedSupply>,
     pub concurrent_supply: Option<ConcurrentSupply>,
     pub token_identifier: Option<TokenIdentifiers>,
+    pub royalty: Option<Royalty>,
 }
 
 impl Default for ObjectAggregatedData {
@@ -83,10 +84,27 @@ impl Default for ObjectAggregatedData {
             unlimited_supply: None,
             concurrent_supply: None,
             token_identifier: None,
+            royalty: None,
         }
     }
 }
 
+impl ObjectAggregatedData {
+    /// Whether the object can never be transferred again, i.e. has an `Untransferable` resource,
+    /// or can only be transferred by whoever holds its `TransferRef`.
+    ///
+    /// Only the former is truly soulbound, but `is_soulbound_v2` has always covered both.
+    pub fn is_soulbound(&self) -> bool {
+        self.untransferable.is_some() || self.non_transferrable_by_owner()
+    }
+
+    /// Whether the owner can't transfer the object themselves, because ungated transfers are
+    /// disabled through its `TransferRef` or it is untransferable.
+    pub fn non_transferrable_by_owner(&self) -> bool {
+        !self.object.object_core.allow_ungated_transfer
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ObjectCore {
     pub allow_ungated_transfer: bool,
@@ -149,3 +167,30 @@ impl TryFrom<&WriteResource> for Untransferable {
         serde_json::from_str(write_resource.data.as_str()).map_err(anyhow::Error::msg)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object_data(allow_ungated_transfer: bool, untransferable: bool) -> ObjectAggregatedData {
+        let mut data = ObjectAggregatedData::default();
+        data.object.object_core.allow_ungated_transfer = allow_ungated_transfer;
+        data.untransferable = untransferable.then_some(Untransferable { dummy_field: false });
+        data
+    }
+
+    #[test]
+    fn test_transfer_restrictions() {
+        let transferable = object_data(true, false);
+        assert!(!transferable.is_soulbound());
+        assert!(!transferable.non_transferrable_by_owner());
+
+        let gated = object_data(false, false);
+        assert!(gated.is_soulbound());
+        assert!(gated.non_transferrable_by_owner());
+
+        let untransferable = object_data(false, true);
+        assert!(untransferable.is_soulbound());
+        assert!(untransferable.non_transferrable_by_owner());
+    }
+}
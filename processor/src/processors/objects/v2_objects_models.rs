@@ -10,6 +10,7 @@ use crate::{
     parquet_processors::parquet_utils::util::{HasVersion, NamedTable},
     processors::default::models::move_resources::MoveResource,
     schema::{current_objects, objects},
+    utils::{dedup::CurrentTableRow, log_throttle::throttle_log, metadata_cache::CURRENT_OBJECTS},
 };
 use ahash::AHashMap;
 use allocative_derive::Allocative;
@@ -83,11 +84,7 @@ impl Object {
             let object_with_metadata = object_aggregated_metadata.object.clone();
             let object_core = object_with_metadata.object_core;
 
-            let untransferrable = if object_aggregated_metadata.untransferable.as_ref().is_some() {
-                true
-            } else {
-                !object_core.allow_ungated_transfer
-            };
+            let untransferrable = object_aggregated_metadata.is_soulbound();
             Ok(Some((
                 Self {
                     transaction_version: txn_version,
@@ -194,12 +191,17 @@ impl Object {
                     {
                         Ok(object) => object,
                         Err(_) => {
-                            tracing::error!(
-                            transaction_version = txn_version,
-                            lookup_key = &resource.resource_address,
-                            "Missing current_object for object_address: {}. You probably should backfill db.",
-                            resource.resource_address,
-                        );
+                            if let Some(suppressed) =
+                                throttle_log("missing_current_object", &resource.resource_address)
+                            {
+                                tracing::error!(
+                                    transaction_version = txn_version,
+                                    lookup_key = &resource.resource_address,
+                                    suppressed = suppressed,
+                                    "Missing current_object for object_address: {}. You probably should backfill db.",
+                                    resource.resource_address,
+                                );
+                            }
                             return Ok(None);
                         },
                     }
@@ -250,12 +252,15 @@ impl Object {
         query_retries: u32,
         query_retry_delay_ms: u64,
     ) -> anyhow::Result<CurrentObject> {
+        if let Some(current_object) = CURRENT_OBJECTS.get(object_address) {
+            return Ok(current_object);
+        }
         let mut tried = 0;
         while tried < query_retries {
             tried += 1;
             match CurrentObjectQuery::get_by_address(object_address, conn).await {
                 Ok(res) => {
-                    return Ok(CurrentObject {
+                    let current_object = CurrentObject {
                         object_address: res.object_address,
                         owner_address: res.owner_address,
                         state_key_hash: res.state_key_hash,
@@ -265,7 +270,9 @@ impl Object {
                         is_deleted: res.is_deleted,
                         untransferrable: res.untransferrable,
                         block_timestamp: chrono::NaiveDateTime::default(), // this won't be used
-                    });
+                    };
+                    CURRENT_OBJECTS.insert(object_address, current_object.clone());
+                    return Ok(current_object);
                 },
                 Err(_) => {
                     if tried < query_retries {
@@ -440,3 +447,15 @@ impl From<CurrentObject> for PostgresCurrentObject {
         }
     }
 }
+
+impl CurrentTableRow for PostgresCurrentObject {
+    type PrimaryKey = String;
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        self.object_address.clone()
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
@@ -1,14 +1,36 @@
 use crate::{
     config::{
+        db_config::DbConfig,
         indexer_processor_config::IndexerProcessorConfig,
-        processor_mode::{BackfillConfig, BootStrapConfig, ProcessorMode, TestingConfig},
+        processor_mode::{BackfillConfig, ProcessorMode, RangedConfig, ShardRange, TestingConfig},
     },
-    db::backfill_processor_status::{
-        BackfillProcessorStatus, BackfillProcessorStatusQuery, BackfillStatus,
+    db::{
+        backfill_processor_status::{
+            BackfillProcessorStatus, BackfillProcessorStatusQuery, BackfillStatus,
+        },
+        consistency_watermark::ConsistencyWatermark,
+        indexer_outbox::IndexerOutboxEntry,
+        indexer_table_stats::{IndexerTableStats, IndexerTableStatsDaily, IndexerTableStatsHourly},
+        processor_heartbeat::ProcessorHeartbeat,
+        processor_shard_status::{ProcessorShardStatus, ProcessorShardStatusQuery},
+    },
+    schema::{
+        backfill_processor_status, indexer_outbox, indexer_table_stats, indexer_table_stats_daily,
+        indexer_table_stats_hourly, processor_consistency_watermarks, processor_heartbeats,
+        processor_shard_status,
+    },
+    utils::{
+        counters::{PROCESSOR_CONSISTENCY_WATERMARK, PROCESSOR_HEARTBEAT_FAILURES_COUNT},
+        file_sink::{finish_file_sink_windows, sync_file_sink},
+        ranged_run::{record_ranged_status, record_ranged_table_stats},
+        replica::replicate,
+        sharded_run::record_shard_status,
+        table_stats::{take_pending_table_stats, TableWriteStats},
+        transaction_source::resolve_bootstrap_version,
     },
-    schema::backfill_processor_status,
 };
 use anyhow::Result;
+use async_trait::async_trait;
 use cedra_indexer_processor_sdk::{
     cedra_indexer_transaction_stream::utils::time::parse_timestamp,
     common_steps::ProcessorStatusSaver,
@@ -20,8 +42,15 @@ use cedra_indexer_processor_sdk::{
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
 };
-use async_trait::async_trait;
-use diesel::{query_dsl::methods::FilterDsl, upsert::excluded, ExpressionMethods};
+use chrono::{NaiveDateTime, Timelike};
+use diesel::{
+    query_dsl::methods::{FilterDsl, LockingDsl, SelectDsl},
+    upsert::excluded,
+    ExpressionMethods, OptionalExtension,
+};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection, RunQueryDsl};
+use futures::FutureExt;
+use tracing::warn;
 
 /// A trait implementation of ProcessorStatusSaver for Postgres.
 pub struct PostgresProcessorStatusSaver {
@@ -41,16 +70,82 @@ impl ProcessorStatusSaver for PostgresProcessorStatusSaver {
         &self,
         last_success_batch: &TransactionContext<()>,
     ) -> Result<(), ProcessorError> {
-        save_processor_status(
+        let table_stats = take_pending_table_stats();
+        // The files have to hold every version the status covers before it's saved
+        sync_file_sink()
+            .await
+            .map_err(|e| ProcessorError::ProcessError {
+                message: format!("Failed to sync the file sink: {e:?}"),
+            })?;
+        match (&self.config.processor_mode, &self.config.db_config) {
+            (
+                ProcessorMode::Default(bootstrap_config),
+                DbConfig::PostgresConfig(postgres_config),
+            ) if postgres_config.outbox.is_some() => {
+                save_processor_status_with_outbox(
+                    self.config.processor_config.name(),
+                    bootstrap_config.initial_starting_version,
+                    last_success_batch,
+                    &table_stats,
+                    self.db_pool.clone(),
+                )
+                .await?
+            },
+            _ => {
+                save_processor_status(
+                    self.config.processor_config.name(),
+                    self.config.processor_mode.clone(),
+                    last_success_batch,
+                    self.db_pool.clone(),
+                )
+                .await?
+            },
+        }
+        save_heartbeat(
             self.config.processor_config.name(),
-            self.config.processor_mode.clone(),
+            &self.config.processor_mode,
             last_success_batch,
             self.db_pool.clone(),
         )
-        .await
+        .await;
+        if let ProcessorMode::Default(_) = self.config.processor_mode {
+            replicate_processor_status(self.config.processor_config.name(), last_success_batch);
+        }
+        // Storers commit every table of a batch before it reaches the version tracker, so the
+        // last success version is also the version all tables are consistent through.
+        save_consistency_watermark(
+            self.config.processor_config.name(),
+            &self.config.processor_mode,
+            last_success_batch,
+            self.db_pool.clone(),
+        )
+        .await?;
+        save_table_stats(
+            self.config.processor_config.name(),
+            &self.config.processor_mode,
+            last_success_batch,
+            table_stats,
+            self.db_pool.clone(),
+        )
+        .await?;
+        // Windows are finished after the checkpoint, so a restart never reopens a finished one
+        finish_file_sink_windows(last_success_batch.metadata.end_version)
+            .await
+            .map_err(|e| ProcessorError::ProcessError {
+                message: format!("Failed to finish file sink windows: {e:?}"),
+            })
     }
 }
 
+fn get_last_transaction_timestamp(batch: &TransactionContext<()>) -> Option<NaiveDateTime> {
+    batch
+        .metadata
+        .end_transaction_timestamp
+        .as_ref()
+        .map(|t| parse_timestamp(t, batch.metadata.end_version as i64))
+        .map(|t| t.naive_utc())
+}
+
 pub async fn save_processor_status(
     processor_id: &str,
     processor_mode: ProcessorMode,
@@ -58,12 +153,7 @@ pub async fn save_processor_status(
     db_pool: ArcDbPool,
 ) -> Result<(), ProcessorError> {
     let last_success_version = last_success_batch.metadata.end_version as i64;
-    let last_transaction_timestamp = last_success_batch
-        .metadata
-        .end_transaction_timestamp
-        .as_ref()
-        .map(|t| parse_timestamp(t, last_success_batch.metadata.end_version as i64))
-        .map(|t| t.naive_utc());
+    let last_transaction_timestamp = get_last_transaction_timestamp(last_success_batch);
     let status = ProcessorStatus {
         processor: processor_id.to_string(),
         last_success_version,
@@ -153,7 +243,517 @@ pub async fn save_processor_status(
         ProcessorMode::Testing(_) => {
             // In testing mode, the last success version is not stored.
         },
+        ProcessorMode::Ranged(_) => {
+            // Ranged runs only report the last success version in their summary.
+            record_ranged_status(processor_id, last_success_version as u64);
+        },
+        ProcessorMode::Sharded(_) => {
+            // Sharded runs save their status through the shards they run.
+        },
+        ProcessorMode::Shard(ShardRange {
+            shard_index,
+            starting_version,
+            ending_version,
+        }) => {
+            let shard_status = ProcessorShardStatus {
+                processor: processor_id.to_string(),
+                shard_index: shard_index as i64,
+                range_starting_version: starting_version as i64,
+                range_ending_version: ending_version as i64,
+                last_success_version,
+                last_transaction_timestamp,
+            };
+            execute_with_better_error(
+                db_pool.clone(),
+                diesel::insert_into(processor_shard_status::table)
+                    .values(&shard_status)
+                    .on_conflict((
+                        processor_shard_status::processor,
+                        processor_shard_status::shard_index,
+                    ))
+                    .do_update()
+                    .set((
+                        processor_shard_status::range_starting_version
+                            .eq(excluded(processor_shard_status::range_starting_version)),
+                        processor_shard_status::range_ending_version
+                            .eq(excluded(processor_shard_status::range_ending_version)),
+                        processor_shard_status::last_success_version
+                            .eq(excluded(processor_shard_status::last_success_version)),
+                        processor_shard_status::last_transaction_timestamp
+                            .eq(excluded(processor_shard_status::last_transaction_timestamp)),
+                        processor_shard_status::last_updated
+                            .eq(excluded(processor_shard_status::last_updated)),
+                    )),
+            )
+            .await?;
+            if let Some((watermark, timestamp)) = record_shard_status(
+                shard_index,
+                last_success_version as u64,
+                last_transaction_timestamp,
+            ) {
+                save_merged_watermark(processor_id, watermark, timestamp, db_pool)
+                    .await
+                    .map_err(|e| ProcessorError::ProcessError {
+                        message: format!("Failed to save the merged watermark. {e:?}"),
+                    })?;
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Saves the version every shard of a sharded run got through as the processor's status and
+/// consistency watermark, so the processor resumes from it whichever mode it's restarted in.
+pub async fn save_merged_watermark(
+    processor_id: &str,
+    watermark: u64,
+    last_transaction_timestamp: Option<NaiveDateTime>,
+    db_pool: ArcDbPool,
+) -> Result<()> {
+    let status = ProcessorStatus {
+        processor: processor_id.to_string(),
+        last_success_version: watermark as i64,
+        last_transaction_timestamp,
+    };
+    execute_with_better_error(
+        db_pool.clone(),
+        diesel::insert_into(processor_status::table)
+            .values(&status)
+            .on_conflict(processor_status::processor)
+            .do_update()
+            .set((
+                processor_status::last_success_version
+                    .eq(excluded(processor_status::last_success_version)),
+                processor_status::last_updated.eq(excluded(processor_status::last_updated)),
+                processor_status::last_transaction_timestamp
+                    .eq(excluded(processor_status::last_transaction_timestamp)),
+            ))
+            .filter(
+                processor_status::last_success_version
+                    .le(excluded(processor_status::last_success_version)),
+            ),
+    )
+    .await?;
+    let watermark = ConsistencyWatermark {
+        processor: processor_id.to_string(),
+        consistent_through_version: watermark as i64,
+        last_transaction_timestamp,
+    };
+    execute_with_better_error(
+        db_pool,
+        diesel::insert_into(processor_consistency_watermarks::table)
+            .values(&watermark)
+            .on_conflict(processor_consistency_watermarks::processor)
+            .do_update()
+            .set((
+                processor_consistency_watermarks::consistent_through_version.eq(excluded(
+                    processor_consistency_watermarks::consistent_through_version,
+                )),
+                processor_consistency_watermarks::last_updated
+                    .eq(excluded(processor_consistency_watermarks::last_updated)),
+                processor_consistency_watermarks::last_transaction_timestamp.eq(excluded(
+                    processor_consistency_watermarks::last_transaction_timestamp,
+                )),
+            ))
+            .filter(
+                processor_consistency_watermarks::consistent_through_version.le(excluded(
+                    processor_consistency_watermarks::consistent_through_version,
+                )),
+            ),
+    )
+    .await?;
+    PROCESSOR_CONSISTENCY_WATERMARK
+        .with_label_values(&[processor_id])
+        .set(watermark.consistent_through_version);
+    Ok(())
+}
+
+/// Records that the processor saved its status, whether or not its version moved, so a stalled
+/// processor can be told apart from a quiet chain. Backfills heartbeat under their backfill
+/// alias, and shards of a sharded run under the processor name suffixed with their index. A
+/// failed write is logged and counted instead of failing the save, since the processor is making
+/// progress either way, and the next save writes the heartbeat again.
+pub async fn save_heartbeat(
+    processor_id: &str,
+    processor_mode: &ProcessorMode,
+    last_success_batch: &TransactionContext<()>,
+    db_pool: ArcDbPool,
+) {
+    let processor = match processor_mode {
+        ProcessorMode::Default(_) => processor_id.to_string(),
+        ProcessorMode::Backfill(BackfillConfig { backfill_id, .. }) => {
+            format!("{processor_id}_{backfill_id}")
+        },
+        ProcessorMode::Shard(ShardRange { shard_index, .. }) => {
+            format!("{processor_id}_shard_{shard_index}")
+        },
+        ProcessorMode::Testing(_) | ProcessorMode::Ranged(_) | ProcessorMode::Sharded(_) => return,
+    };
+    let metadata = &last_success_batch.metadata;
+    let heartbeat = ProcessorHeartbeat {
+        processor,
+        last_success_version: metadata.end_version as i64,
+        last_batch_size: (metadata.end_version + 1).saturating_sub(metadata.start_version) as i64,
+        last_transaction_timestamp: get_last_transaction_timestamp(last_success_batch),
+        last_heartbeat_at: chrono::Utc::now().naive_utc(),
+    };
+    let result = execute_with_better_error(
+        db_pool,
+        diesel::insert_into(processor_heartbeats::table)
+            .values(&heartbeat)
+            .on_conflict(processor_heartbeats::processor)
+            .do_update()
+            .set((
+                processor_heartbeats::last_success_version
+                    .eq(excluded(processor_heartbeats::last_success_version)),
+                processor_heartbeats::last_batch_size
+                    .eq(excluded(processor_heartbeats::last_batch_size)),
+                processor_heartbeats::last_transaction_timestamp
+                    .eq(excluded(processor_heartbeats::last_transaction_timestamp)),
+                processor_heartbeats::last_heartbeat_at
+                    .eq(excluded(processor_heartbeats::last_heartbeat_at)),
+            )),
+    )
+    .await;
+    if let Err(e) = result {
+        warn!(
+            processor = heartbeat.processor.as_str(),
+            error = ?e,
+            "Failed to save heartbeat, it's retried on the next status save"
+        );
+        PROCESSOR_HEARTBEAT_FAILURES_COUNT
+            .with_label_values(&[processor_id])
+            .inc();
+    }
+}
+
+/// Queues the processor status for the replica behind the batches it covers, so the replica's
+/// status only moves past versions whose writes were queued for it.
+fn replicate_processor_status(processor_id: &str, last_success_batch: &TransactionContext<()>) {
+    let status = ProcessorStatus {
+        processor: processor_id.to_string(),
+        last_success_version: last_success_batch.metadata.end_version as i64,
+        last_transaction_timestamp: get_last_transaction_timestamp(last_success_batch),
+    };
+    replicate("processor_status", move |replica_pool| {
+        async move {
+            execute_with_better_error(
+                replica_pool,
+                diesel::insert_into(processor_status::table)
+                    .values(status)
+                    .on_conflict(processor_status::processor)
+                    .do_update()
+                    .set((
+                        processor_status::last_success_version
+                            .eq(excluded(processor_status::last_success_version)),
+                        processor_status::last_updated.eq(excluded(processor_status::last_updated)),
+                        processor_status::last_transaction_timestamp
+                            .eq(excluded(processor_status::last_transaction_timestamp)),
+                    ))
+                    .filter(
+                        processor_status::last_success_version
+                            .le(excluded(processor_status::last_success_version)),
+                    ),
+            )
+            .await
+            .map(|_| ())
+            .map_err(|e| anyhow::anyhow!("{e:?}"))
+        }
+        .boxed()
+    });
+}
+
+/// Saves the processor status of the live processor together with an `indexer_outbox` entry for
+/// the versions committed since the previous checkpoint, in one transaction. Every committed
+/// version range gets exactly one entry, even when the processor restarts in between. The first
+/// entry of a processor starts at `initial_starting_version`.
+///
+/// `table_stats` are the tables written since the previous checkpoint; the ones that got rows
+/// are listed in the entry.
+pub async fn save_processor_status_with_outbox(
+    processor_id: &str,
+    initial_starting_version: u64,
+    last_success_batch: &TransactionContext<()>,
+    table_stats: &[(&'static str, TableWriteStats)],
+    db_pool: ArcDbPool,
+) -> Result<(), ProcessorError> {
+    let last_success_version = last_success_batch.metadata.end_version as i64;
+    let status = ProcessorStatus {
+        processor: processor_id.to_string(),
+        last_success_version,
+        last_transaction_timestamp: get_last_transaction_timestamp(last_success_batch),
+    };
+    let tables_touched: Vec<_> = table_stats
+        .iter()
+        .filter(|(_, stats)| stats.rows_written > 0)
+        .map(|(table_name, _)| Some(table_name.to_string()))
+        .collect();
+
+    let mut conn = db_pool
+        .get()
+        .await
+        .map_err(|e| ProcessorError::DBStoreError {
+            message: format!("Failed to get database connection. {e:?}"),
+            query: None,
+        })?;
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        async move {
+            // Locking the status row keeps the previous checkpoint from moving until this one
+            // is committed.
+            let previous_version = processor_status::table
+                .filter(processor_status::processor.eq(processor_id))
+                .select(processor_status::last_success_version)
+                .for_update()
+                .first::<i64>(conn)
+                .await
+                .optional()?;
+            let start_version = match previous_version {
+                Some(previous_version) if previous_version >= last_success_version => return Ok(()),
+                Some(previous_version) => previous_version + 1,
+                None => initial_starting_version as i64,
+            };
+            diesel::insert_into(processor_status::table)
+                .values(&status)
+                .on_conflict(processor_status::processor)
+                .do_update()
+                .set((
+                    processor_status::last_success_version
+                        .eq(excluded(processor_status::last_success_version)),
+                    processor_status::last_updated.eq(excluded(processor_status::last_updated)),
+                    processor_status::last_transaction_timestamp
+                        .eq(excluded(processor_status::last_transaction_timestamp)),
+                ))
+                .execute(conn)
+                .await?;
+            diesel::insert_into(indexer_outbox::table)
+                .values(IndexerOutboxEntry {
+                    processor: processor_id.to_string(),
+                    start_version,
+                    end_version: last_success_version,
+                    tables_touched,
+                })
+                .execute(conn)
+                .await?;
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await
+    .map_err(|e| ProcessorError::DBStoreError {
+        message: format!(
+            "Failed to save processor status and outbox entry at version {last_success_version}. \
+             {e:?}"
+        ),
+        query: None,
+    })
+}
+
+/// Saves the version through which every table written by the processor has been committed.
+/// Downstream readers can use it to know when a version range is safe to read across tables.
+///
+/// Only the live processor moves the watermark; backfills and tests leave it untouched.
+pub async fn save_consistency_watermark(
+    processor_id: &str,
+    processor_mode: &ProcessorMode,
+    consistent_through: &TransactionContext<()>,
+    db_pool: ArcDbPool,
+) -> Result<(), ProcessorError> {
+    if !matches!(processor_mode, ProcessorMode::Default(_)) {
+        return Ok(());
     }
+    let watermark = ConsistencyWatermark {
+        processor: processor_id.to_string(),
+        consistent_through_version: consistent_through.metadata.end_version as i64,
+        last_transaction_timestamp: get_last_transaction_timestamp(consistent_through),
+    };
+    execute_with_better_error(
+        db_pool,
+        diesel::insert_into(processor_consistency_watermarks::table)
+            .values(&watermark)
+            .on_conflict(processor_consistency_watermarks::processor)
+            .do_update()
+            .set((
+                processor_consistency_watermarks::consistent_through_version.eq(excluded(
+                    processor_consistency_watermarks::consistent_through_version,
+                )),
+                processor_consistency_watermarks::last_updated
+                    .eq(excluded(processor_consistency_watermarks::last_updated)),
+                processor_consistency_watermarks::last_transaction_timestamp.eq(excluded(
+                    processor_consistency_watermarks::last_transaction_timestamp,
+                )),
+            ))
+            .filter(
+                processor_consistency_watermarks::consistent_through_version.le(excluded(
+                    processor_consistency_watermarks::consistent_through_version,
+                )),
+            ),
+    )
+    .await?;
+    PROCESSOR_CONSISTENCY_WATERMARK
+        .with_label_values(&[processor_id])
+        .set(watermark.consistent_through_version);
+    Ok(())
+}
+
+/// Adds what the storers wrote since the last save to the processor's running totals in
+/// `indexer_table_stats`, and to the day's and hour's totals in `indexer_table_stats_daily` and
+/// `indexer_table_stats_hourly`. Only tables that were written to get a new
+/// `last_rows_written_version`, so a table that stops receiving rows keeps an old one.
+///
+/// Backfills and tests don't count towards the stats. Ranged runs count them in their summary
+/// instead.
+pub async fn save_table_stats(
+    processor_id: &str,
+    processor_mode: &ProcessorMode,
+    last_success_batch: &TransactionContext<()>,
+    pending: Vec<(&'static str, TableWriteStats)>,
+    db_pool: ArcDbPool,
+) -> Result<(), ProcessorError> {
+    if let ProcessorMode::Ranged(_) = processor_mode {
+        record_ranged_table_stats(&pending);
+        return Ok(());
+    }
+    if !matches!(processor_mode, ProcessorMode::Default(_)) || pending.is_empty() {
+        return Ok(());
+    }
+    let last_success_version = last_success_batch.metadata.end_version as i64;
+    let now = chrono::Utc::now().naive_utc();
+    let daily: Vec<_> = pending
+        .iter()
+        .map(|(table_name, stats)| IndexerTableStatsDaily {
+            processor: processor_id.to_string(),
+            table_name: table_name.to_string(),
+            day: now.date(),
+            rows_written: stats.rows_written,
+            conflicts_ignored: stats.conflicts_ignored,
+            bytes_written: stats.bytes_written,
+        })
+        .collect();
+    let hour = now.date().and_hms_opt(now.hour(), 0, 0).unwrap();
+    let hourly: Vec<_> = pending
+        .iter()
+        .map(|(table_name, stats)| IndexerTableStatsHourly {
+            processor: processor_id.to_string(),
+            table_name: table_name.to_string(),
+            hour,
+            rows_written: stats.rows_written,
+            conflicts_ignored: stats.conflicts_ignored,
+            bytes_written: stats.bytes_written,
+        })
+        .collect();
+    let (written, not_written): (Vec<_>, Vec<_>) = pending
+        .into_iter()
+        .map(|(table_name, stats)| {
+            let has_rows = stats.rows_written > 0;
+            IndexerTableStats {
+                processor: processor_id.to_string(),
+                table_name: table_name.to_string(),
+                rows_written: stats.rows_written,
+                conflicts_ignored: stats.conflicts_ignored,
+                bytes_written: stats.bytes_written,
+                last_rows_written_version: has_rows.then_some(last_success_version),
+                last_rows_written_at: has_rows.then_some(now),
+            }
+        })
+        .partition(|stats| stats.last_rows_written_version.is_some());
+
+    if !written.is_empty() {
+        execute_with_better_error(
+            db_pool.clone(),
+            diesel::insert_into(indexer_table_stats::table)
+                .values(written)
+                .on_conflict((
+                    indexer_table_stats::processor,
+                    indexer_table_stats::table_name,
+                ))
+                .do_update()
+                .set((
+                    indexer_table_stats::rows_written.eq(indexer_table_stats::rows_written
+                        + excluded(indexer_table_stats::rows_written)),
+                    indexer_table_stats::conflicts_ignored
+                        .eq(indexer_table_stats::conflicts_ignored
+                            + excluded(indexer_table_stats::conflicts_ignored)),
+                    indexer_table_stats::bytes_written.eq(indexer_table_stats::bytes_written
+                        + excluded(indexer_table_stats::bytes_written)),
+                    indexer_table_stats::last_rows_written_version
+                        .eq(excluded(indexer_table_stats::last_rows_written_version)),
+                    indexer_table_stats::last_rows_written_at
+                        .eq(excluded(indexer_table_stats::last_rows_written_at)),
+                    indexer_table_stats::last_updated
+                        .eq(excluded(indexer_table_stats::last_updated)),
+                )),
+        )
+        .await?;
+    }
+    if !not_written.is_empty() {
+        execute_with_better_error(
+            db_pool.clone(),
+            diesel::insert_into(indexer_table_stats::table)
+                .values(not_written)
+                .on_conflict((
+                    indexer_table_stats::processor,
+                    indexer_table_stats::table_name,
+                ))
+                .do_update()
+                .set((
+                    indexer_table_stats::conflicts_ignored
+                        .eq(indexer_table_stats::conflicts_ignored
+                            + excluded(indexer_table_stats::conflicts_ignored)),
+                    indexer_table_stats::bytes_written.eq(indexer_table_stats::bytes_written
+                        + excluded(indexer_table_stats::bytes_written)),
+                    indexer_table_stats::last_updated
+                        .eq(excluded(indexer_table_stats::last_updated)),
+                )),
+        )
+        .await?;
+    }
+    execute_with_better_error(
+        db_pool.clone(),
+        diesel::insert_into(indexer_table_stats_daily::table)
+            .values(daily)
+            .on_conflict((
+                indexer_table_stats_daily::processor,
+                indexer_table_stats_daily::table_name,
+                indexer_table_stats_daily::day,
+            ))
+            .do_update()
+            .set((
+                indexer_table_stats_daily::rows_written.eq(indexer_table_stats_daily::rows_written
+                    + excluded(indexer_table_stats_daily::rows_written)),
+                indexer_table_stats_daily::conflicts_ignored
+                    .eq(indexer_table_stats_daily::conflicts_ignored
+                        + excluded(indexer_table_stats_daily::conflicts_ignored)),
+                indexer_table_stats_daily::bytes_written
+                    .eq(indexer_table_stats_daily::bytes_written
+                        + excluded(indexer_table_stats_daily::bytes_written)),
+                indexer_table_stats_daily::last_updated.eq(diesel::dsl::now),
+            )),
+    )
+    .await?;
+    execute_with_better_error(
+        db_pool,
+        diesel::insert_into(indexer_table_stats_hourly::table)
+            .values(hourly)
+            .on_conflict((
+                indexer_table_stats_hourly::processor,
+                indexer_table_stats_hourly::table_name,
+                indexer_table_stats_hourly::hour,
+            ))
+            .do_update()
+            .set((
+                indexer_table_stats_hourly::rows_written
+                    .eq(indexer_table_stats_hourly::rows_written
+                        + excluded(indexer_table_stats_hourly::rows_written)),
+                indexer_table_stats_hourly::conflicts_ignored
+                    .eq(indexer_table_stats_hourly::conflicts_ignored
+                        + excluded(indexer_table_stats_hourly::conflicts_ignored)),
+                indexer_table_stats_hourly::bytes_written
+                    .eq(indexer_table_stats_hourly::bytes_written
+                        + excluded(indexer_table_stats_hourly::bytes_written)),
+                indexer_table_stats_hourly::last_updated.eq(diesel::dsl::now),
+            )),
+    )
+    .await?;
     Ok(())
 }
 
@@ -170,22 +770,26 @@ pub async fn get_starting_version(
         })?;
 
     match &config.processor_mode {
-        ProcessorMode::Default(BootStrapConfig {
-            initial_starting_version,
-        }) => {
+        ProcessorMode::Default(bootstrap_config) => {
             let status = ProcessorStatusQuery::get_by_processor(processor_name, &mut conn)
                 .await
                 .map_err(|e| ProcessorError::ProcessError {
                     message: format!("Failed to query processor_status table. {e:?}"),
                 })?;
 
-            // If there's no last success version saved, start with the version from config
-            Ok(Some(status.map_or(*initial_starting_version, |status| {
-                std::cmp::max(
+            match status {
+                Some(status) => Ok(Some(std::cmp::max(
                     status.last_success_version as u64,
-                    *initial_starting_version,
-                )
-            })))
+                    bootstrap_config.initial_starting_version,
+                ))),
+                // If there's no last success version saved, start where the config says
+                None => resolve_bootstrap_version(config, bootstrap_config)
+                    .await
+                    .map(Some)
+                    .map_err(|e| ProcessorError::ProcessError {
+                        message: format!("Failed to resolve the starting version. {e:?}"),
+                    }),
+            }
         },
         ProcessorMode::Backfill(BackfillConfig {
             backfill_id,
@@ -272,6 +876,36 @@ pub async fn get_starting_version(
             // Always start from the override_starting_version.
             Ok(Some(*override_starting_version))
         },
+        ProcessorMode::Ranged(RangedConfig {
+            starting_version, ..
+        }) => Ok(Some(*starting_version)),
+        ProcessorMode::Shard(ShardRange {
+            shard_index,
+            starting_version,
+            ending_version,
+        }) => {
+            let shard_status =
+                ProcessorShardStatusQuery::get_by_shard(processor_name, *shard_index, &mut conn)
+                    .await
+                    .map_err(|e| ProcessorError::ProcessError {
+                        message: format!("Failed to query processor_shard_status table. {e:?}"),
+                    })?;
+            // Resume a retried range where the shard left off, but not a range of an earlier run
+            match shard_status {
+                Some(status)
+                    if status.range_starting_version as u64 == *starting_version
+                        && status.last_success_version as u64 >= *starting_version =>
+                {
+                    Ok(Some(
+                        (status.last_success_version as u64 + 1).min(*ending_version),
+                    ))
+                },
+                _ => Ok(Some(*starting_version)),
+            }
+        },
+        ProcessorMode::Sharded(_) => Err(ProcessorError::ProcessError {
+            message: "Sharded runs start their shards at the versions of their ranges".to_string(),
+        }),
     }
 }
 
@@ -311,6 +945,11 @@ pub async fn get_end_version(
             // If no ending version is provided, use the override_starting_version so testing mode only processes 1 transaction at a time.
             Ok(Some(ending_version.unwrap_or(*override_starting_version)))
         },
+        ProcessorMode::Ranged(RangedConfig { ending_version, .. }) => Ok(Some(*ending_version)),
+        ProcessorMode::Shard(ShardRange { ending_version, .. }) => Ok(Some(*ending_version)),
+        ProcessorMode::Sharded(_) => Err(ProcessorError::ProcessError {
+            message: "Sharded runs end their shards at the versions of their ranges".to_string(),
+        }),
     }
 }
 
@@ -339,8 +978,11 @@ mod tests {
             db_config::{DbConfig, PostgresConfig},
             indexer_processor_config::IndexerProcessorConfig,
             processor_config::{DefaultProcessorConfig, ProcessorConfig},
+            processor_mode::BootStrapConfig,
+            supervisor_config::SupervisorConfig,
         },
         db::backfill_processor_status::{BackfillProcessorStatus, BackfillStatus},
+        processors::default::default_processor::DefaultProcessorExtendedConfig,
         MIGRATIONS,
     };
     use ahash::AHashMap;
@@ -356,7 +998,7 @@ mod tests {
         testing_framework::database::{PostgresTestDatabase, TestDatabase},
     };
     use diesel_async::RunQueryDsl;
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
     use url::Url;
 
     fn create_indexer_config(
@@ -367,17 +1009,30 @@ mod tests {
             per_table_chunk_sizes: AHashMap::new(),
             channel_size: 100,
             tables_to_write: HashSet::new(),
+            current_table_dedup_window: None,
         };
-        let processor_config = ProcessorConfig::DefaultProcessor(default_processor_config);
+        let processor_config = ProcessorConfig::DefaultProcessor(DefaultProcessorExtendedConfig {
+            default_config: default_processor_config,
+            table_item_types: vec![],
+        });
         let postgres_config = PostgresConfig {
             connection_string: db_url.to_string(),
             db_pool_size: 100,
+            ..Default::default()
         };
         let db_config = DbConfig::PostgresConfig(postgres_config);
         IndexerProcessorConfig {
             processor_config,
             db_config,
             processor_mode,
+            supervisor_config: SupervisorConfig::default(),
+            local_transactions_config: None,
+            stream_failover_config: None,
+            auth_token_source: None,
+            network: None,
+            table_name_overrides: HashMap::new(),
+            scheduled_jobs: HashMap::new(),
+            log_level: None,
             transaction_stream_config: TransactionStreamConfig {
                 indexer_grpc_data_service_address: Url::parse("https://test.com").unwrap(),
                 starting_version: None,
@@ -405,6 +1060,7 @@ mod tests {
             db.get_db_url(),
             ProcessorMode::Default(BootStrapConfig {
                 initial_starting_version,
+                ..Default::default()
             }),
         );
         let conn_pool = new_db_pool(db.get_db_url().as_str(), Some(10))
@@ -435,6 +1091,7 @@ mod tests {
             db.get_db_url(),
             ProcessorMode::Default(BootStrapConfig {
                 initial_starting_version: 0,
+                ..Default::default()
             }),
         );
 
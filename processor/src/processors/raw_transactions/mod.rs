@@ -0,0 +1,4 @@
+pub mod models;
+pub mod raw_transactions_extractor;
+pub mod raw_transactions_processor;
+pub mod raw_transactions_storer;
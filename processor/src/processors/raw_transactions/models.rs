@@ -0,0 +1,80 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::schema::raw_transactions;
+use cedra_indexer_processor_sdk::{
+    cedra_indexer_transaction_stream::utils::time::parse_timestamp,
+    cedra_protos::transaction::v1::Transaction,
+};
+use chrono::NaiveDateTime;
+use field_count::FieldCount;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+/// A transaction archived as received from the stream, so that it can be run through the
+/// parsers again without being streamed again.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version))]
+#[diesel(table_name = raw_transactions)]
+pub struct RawTransaction {
+    pub transaction_version: i64,
+    pub block_height: i64,
+    pub epoch: i64,
+    pub transaction_timestamp: NaiveDateTime,
+    /// The protobuf encoding of the `Transaction`
+    pub encoded_transaction: Vec<u8>,
+}
+
+impl RawTransaction {
+    pub fn from_transaction(transaction: &Transaction) -> Self {
+        let transaction_version = transaction.version as i64;
+        Self {
+            transaction_version,
+            block_height: transaction.block_height as i64,
+            epoch: transaction.epoch as i64,
+            transaction_timestamp: parse_timestamp(
+                transaction.timestamp.as_ref().unwrap(),
+                transaction_version,
+            )
+            .naive_utc(),
+            encoded_transaction: transaction.encode_to_vec(),
+        }
+    }
+
+    pub fn decode(&self) -> anyhow::Result<Transaction> {
+        Transaction::decode(self.encoded_transaction.as_slice()).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to decode raw transaction {}: {:?}",
+                self.transaction_version,
+                e
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cedra_indexer_processor_sdk::cedra_protos::util::timestamp::Timestamp;
+
+    #[test]
+    fn test_round_trip() {
+        let transaction = Transaction {
+            version: 42,
+            block_height: 7,
+            epoch: 3,
+            timestamp: Some(Timestamp {
+                seconds: 1_700_000_000,
+                nanos: 0,
+            }),
+            ..Default::default()
+        };
+        let raw_transaction = RawTransaction::from_transaction(&transaction);
+        assert_eq!(raw_transaction.transaction_version, 42);
+        assert_eq!(raw_transaction.block_height, 7);
+        assert_eq!(raw_transaction.decode().unwrap(), transaction);
+    }
+}
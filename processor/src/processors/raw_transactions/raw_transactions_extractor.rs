@@ -0,0 +1,48 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::processors::raw_transactions::models::RawTransaction;
+use anyhow::Result;
+use async_trait::async_trait;
+use cedra_indexer_processor_sdk::{
+    cedra_protos::transaction::v1::Transaction,
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+
+/// Encodes every transaction for the raw transaction archive
+pub struct RawTransactionsExtractor
+where
+    Self: Sized + Send + 'static, {}
+
+#[async_trait]
+impl Processable for RawTransactionsExtractor {
+    type Input = Vec<Transaction>;
+    type Output = Vec<RawTransaction>;
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        transactions: TransactionContext<Vec<Transaction>>,
+    ) -> Result<Option<TransactionContext<Vec<RawTransaction>>>, ProcessorError> {
+        let raw_transactions = transactions
+            .data
+            .iter()
+            .map(RawTransaction::from_transaction)
+            .collect();
+
+        Ok(Some(TransactionContext {
+            data: raw_transactions,
+            metadata: transactions.metadata,
+        }))
+    }
+}
+
+impl AsyncStep for RawTransactionsExtractor {}
+
+impl NamedStep for RawTransactionsExtractor {
+    fn name(&self) -> String {
+        "raw_transactions_extractor".to_string()
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::models::RawTransaction;
+use crate::{
+    config::processor_config::DefaultProcessorConfig,
+    schema,
+    utils::{
+        table_flags::{filter_data, TableFlags},
+        table_names::TableNames,
+        table_stats::execute_in_chunks_with_stats,
+    },
+};
+use ahash::AHashMap;
+use anyhow::Result;
+use async_trait::async_trait;
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use diesel::{pg::Pg, query_builder::QueryFragment};
+
+pub struct RawTransactionsStorer
+where
+    Self: Sized + Send + 'static,
+{
+    conn_pool: ArcDbPool,
+    table_names: TableNames,
+    processor_config: DefaultProcessorConfig,
+    tables_to_write: TableFlags,
+}
+
+impl RawTransactionsStorer {
+    pub fn new(
+        conn_pool: ArcDbPool,
+        table_names: TableNames,
+        processor_config: DefaultProcessorConfig,
+        tables_to_write: TableFlags,
+    ) -> Self {
+        Self {
+            conn_pool,
+            table_names,
+            processor_config,
+            tables_to_write,
+        }
+    }
+}
+
+#[async_trait]
+impl Processable for RawTransactionsStorer {
+    type Input = Vec<RawTransaction>;
+    type Output = ();
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        input: TransactionContext<Vec<RawTransaction>>,
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        let per_table_chunk_sizes: AHashMap<String, usize> =
+            self.processor_config.per_table_chunk_sizes.clone();
+
+        let raw_transactions = filter_data(
+            &self.tables_to_write,
+            TableFlags::RAW_TRANSACTIONS,
+            input.data,
+        );
+
+        execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_raw_transactions_query,
+            &raw_transactions,
+            "raw_transactions",
+            get_config_table_chunk_size::<RawTransaction>(
+                "raw_transactions",
+                &per_table_chunk_sizes,
+            ),
+        )
+        .await
+        .map_err(|e| ProcessorError::DBStoreError {
+            message: format!(
+                "Failed to store versions {} to {}: {:?}",
+                input.metadata.start_version, input.metadata.end_version, e,
+            ),
+            query: None,
+        })?;
+
+        Ok(Some(TransactionContext {
+            data: (),
+            metadata: input.metadata,
+        }))
+    }
+}
+
+impl NamedStep for RawTransactionsStorer {
+    fn name(&self) -> String {
+        "raw_transactions_storer".to_string()
+    }
+}
+
+impl AsyncStep for RawTransactionsStorer {}
+
+fn insert_raw_transactions_query(
+    items_to_insert: Vec<RawTransaction>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::raw_transactions::dsl::*;
+
+    diesel::insert_into(schema::raw_transactions::table)
+        .values(items_to_insert)
+        .on_conflict(transaction_version)
+        .do_nothing()
+}
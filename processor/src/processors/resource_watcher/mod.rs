@@ -0,0 +1,4 @@
+pub mod models;
+pub mod resource_watcher_extractor;
+pub mod resource_watcher_processor;
+pub mod resource_watcher_storer;
@@ -0,0 +1,216 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::schema::watched_resources;
+use anyhow::{bail, Context, Result};
+use cedra_indexer_processor_sdk::{
+    cedra_indexer_transaction_stream::utils::time::parse_timestamp,
+    cedra_protos::transaction::v1::{write_set_change::Change, MoveStructTag, Transaction},
+    utils::convert::standardize_address,
+};
+use chrono::NaiveDateTime;
+use field_count::FieldCount;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A glob pattern of resource types, like `0xabc::vault::*`. `*` matches any characters,
+/// including `::` and generic type arguments, and `?` matches a single one. The address the
+/// pattern starts with can be short, it's compared with the resource's in its long form.
+#[derive(Clone, Debug)]
+pub struct ResourceTypePattern {
+    pattern: String,
+    regex: Regex,
+}
+
+impl ResourceTypePattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let Some((address, rest)) = pattern.split_once("::") else {
+            bail!(
+                "Resource type pattern {pattern} doesn't look like <address>::<module>::<struct>"
+            );
+        };
+        let address = if address.contains(['*', '?']) {
+            address.to_string()
+        } else {
+            standardize_address(address)
+        };
+        let mut regex = String::from("^");
+        for c in format!("{address}::{rest}").chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                c => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        regex.push('$');
+        Ok(Self {
+            pattern: pattern.to_string(),
+            regex: Regex::new(&regex)
+                .with_context(|| format!("Invalid resource type pattern {pattern}"))?,
+        })
+    }
+}
+
+/// Resource types the resource watcher processor indexes.
+///
+/// Example:
+/// ```yaml
+/// processor_config:
+///   type: resource_watcher_processor
+///   resource_type_patterns:
+///     - "0xabc::vault::*"
+///     - "0x1::coin::CoinStore<0xabc::*>"
+/// ```
+#[derive(Clone, Debug)]
+pub struct ResourceWatcher {
+    patterns: Vec<ResourceTypePattern>,
+}
+
+impl ResourceWatcher {
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        if patterns.is_empty() {
+            bail!("resource_type_patterns must list at least one pattern");
+        }
+        Ok(Self {
+            patterns: patterns
+                .iter()
+                .map(|pattern| ResourceTypePattern::new(pattern))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// First pattern matching a resource of type `type_str`, whose outer struct is `struct_tag`
+    pub fn matching_pattern(&self, struct_tag: &MoveStructTag, type_str: &str) -> Option<&str> {
+        let type_args = type_str.find('<').map_or("", |index| &type_str[index..]);
+        let resource_type = format!(
+            "{}::{}::{}{}",
+            standardize_address(&struct_tag.address),
+            struct_tag.module,
+            struct_tag.name,
+            type_args
+        );
+        self.patterns
+            .iter()
+            .find(|pattern| pattern.regex.is_match(&resource_type))
+            .map(|pattern| pattern.pattern.as_str())
+    }
+}
+
+/// A resource written or deleted whose type matches a pattern of the resource watcher
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, write_set_change_index))]
+#[diesel(table_name = watched_resources)]
+pub struct WatchedResource {
+    pub transaction_version: i64,
+    pub write_set_change_index: i64,
+    pub transaction_block_height: i64,
+    pub resource_address: String,
+    pub resource_type: String,
+    pub matched_pattern: String,
+    /// Data of written resources, None for deleted ones
+    pub data: Option<serde_json::Value>,
+    pub is_deleted: bool,
+    pub state_key_hash: String,
+    pub transaction_timestamp: NaiveDateTime,
+}
+
+impl WatchedResource {
+    pub fn from_transaction(transaction: &Transaction, watcher: &ResourceWatcher) -> Vec<Self> {
+        let Some(transaction_info) = transaction.info.as_ref() else {
+            return vec![];
+        };
+        let txn_version = transaction.version as i64;
+        let transaction_block_height = transaction.block_height as i64;
+        let transaction_timestamp =
+            parse_timestamp(transaction.timestamp.as_ref().unwrap(), txn_version).naive_utc();
+
+        let mut watched_resources = vec![];
+        for (index, wsc) in transaction_info.changes.iter().enumerate() {
+            let (struct_tag, type_str, address, state_key_hash, data, is_deleted) =
+                match wsc.change.as_ref() {
+                    Some(Change::WriteResource(resource)) => (
+                        resource.r#type.as_ref(),
+                        &resource.type_str,
+                        &resource.address,
+                        &resource.state_key_hash,
+                        serde_json::from_str(&resource.data).ok(),
+                        false,
+                    ),
+                    Some(Change::DeleteResource(resource)) => (
+                        resource.r#type.as_ref(),
+                        &resource.type_str,
+                        &resource.address,
+                        &resource.state_key_hash,
+                        None,
+                        true,
+                    ),
+                    _ => continue,
+                };
+            let Some(matched_pattern) =
+                struct_tag.and_then(|struct_tag| watcher.matching_pattern(struct_tag, type_str))
+            else {
+                continue;
+            };
+            watched_resources.push(Self {
+                transaction_version: txn_version,
+                write_set_change_index: index as i64,
+                transaction_block_height,
+                resource_address: standardize_address(address),
+                resource_type: type_str.clone(),
+                matched_pattern: matched_pattern.to_string(),
+                data,
+                is_deleted,
+                state_key_hash: standardize_address(&hex::encode(state_key_hash)),
+                transaction_timestamp,
+            });
+        }
+        watched_resources
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn struct_tag(address: &str, module: &str, name: &str) -> MoveStructTag {
+        MoveStructTag {
+            address: address.to_string(),
+            module: module.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_matching_pattern() {
+        let watcher = ResourceWatcher::new(&[
+            "0xabc::vault::*".to_string(),
+            "0x1::coin::CoinStore<0xabc::*>".to_string(),
+        ])
+        .unwrap();
+        let vault = struct_tag(&standardize_address("0xabc"), "vault", "Vault");
+        assert_eq!(
+            watcher.matching_pattern(&vault, "0xabc::vault::Vault"),
+            Some("0xabc::vault::*")
+        );
+        let other_module = struct_tag("0xabc", "pool", "Pool");
+        assert_eq!(
+            watcher.matching_pattern(&other_module, "0xabc::pool::Pool"),
+            None
+        );
+        let coin_store = struct_tag("0x1", "coin", "CoinStore");
+        assert_eq!(
+            watcher.matching_pattern(&coin_store, "0x1::coin::CoinStore<0xabc::token::Token>"),
+            Some("0x1::coin::CoinStore<0xabc::*>")
+        );
+        assert_eq!(
+            watcher.matching_pattern(&coin_store, "0x1::coin::CoinStore<0x1::cedra_coin::Coin>"),
+            None
+        );
+        assert!(ResourceWatcher::new(&["vault".to_string()]).is_err());
+        assert!(ResourceWatcher::new(&[]).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::models::{ResourceWatcher, WatchedResource};
+use anyhow::Result;
+use cedra_indexer_processor_sdk::{
+    cedra_protos::transaction::v1::Transaction,
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+
+/// Extracts the resources written or deleted whose type matches a watched pattern
+pub struct ResourceWatcherExtractor
+where
+    Self: Sized + Send + 'static,
+{
+    watcher: ResourceWatcher,
+}
+
+impl ResourceWatcherExtractor {
+    pub fn new(watcher: ResourceWatcher) -> Self {
+        Self { watcher }
+    }
+}
+
+#[async_trait]
+impl Processable for ResourceWatcherExtractor {
+    type Input = Vec<Transaction>;
+    type Output = Vec<WatchedResource>;
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        transactions: TransactionContext<Vec<Transaction>>,
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        let watched_resources = transactions
+            .data
+            .iter()
+            .flat_map(|transaction| WatchedResource::from_transaction(transaction, &self.watcher))
+            .collect();
+
+        Ok(Some(TransactionContext {
+            data: watched_resources,
+            metadata: transactions.metadata,
+        }))
+    }
+}
+
+impl AsyncStep for ResourceWatcherExtractor {}
+
+impl NamedStep for ResourceWatcherExtractor {
+    fn name(&self) -> String {
+        "resource_watcher_extractor".to_string()
+    }
+}
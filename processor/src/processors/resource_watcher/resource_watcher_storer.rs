@@ -0,0 +1,109 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::models::WatchedResource;
+use crate::{
+    config::processor_config::DefaultProcessorConfig,
+    schema,
+    utils::{
+        table_flags::{filter_data, TableFlags},
+        table_names::TableNames,
+        table_stats::execute_in_chunks_with_stats,
+    },
+};
+use ahash::AHashMap;
+use anyhow::Result;
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use diesel::{pg::Pg, query_builder::QueryFragment};
+
+pub struct ResourceWatcherStorer
+where
+    Self: Sized + Send + 'static,
+{
+    conn_pool: ArcDbPool,
+    table_names: TableNames,
+    processor_config: DefaultProcessorConfig,
+    tables_to_write: TableFlags,
+}
+
+impl ResourceWatcherStorer {
+    pub fn new(
+        conn_pool: ArcDbPool,
+        table_names: TableNames,
+        processor_config: DefaultProcessorConfig,
+        tables_to_write: TableFlags,
+    ) -> Self {
+        Self {
+            conn_pool,
+            table_names,
+            processor_config,
+            tables_to_write,
+        }
+    }
+}
+
+#[async_trait]
+impl Processable for ResourceWatcherStorer {
+    type Input = Vec<WatchedResource>;
+    type Output = ();
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        input: TransactionContext<Self::Input>,
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        let watched_resources = input.data;
+
+        let per_table_chunk_sizes: AHashMap<String, usize> =
+            self.processor_config.per_table_chunk_sizes.clone();
+
+        let watched_resources = filter_data(
+            &self.tables_to_write,
+            TableFlags::WATCHED_RESOURCES,
+            watched_resources,
+        );
+
+        execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_watched_resources_query,
+            &watched_resources,
+            "watched_resources",
+            get_config_table_chunk_size::<WatchedResource>(
+                "watched_resources",
+                &per_table_chunk_sizes,
+            ),
+        )
+        .await?;
+
+        Ok(Some(TransactionContext {
+            data: (),
+            metadata: input.metadata,
+        }))
+    }
+}
+
+impl AsyncStep for ResourceWatcherStorer {}
+
+impl NamedStep for ResourceWatcherStorer {
+    fn name(&self) -> String {
+        "resource_watcher_storer".to_string()
+    }
+}
+
+pub fn insert_watched_resources_query(
+    items_to_insert: Vec<WatchedResource>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::watched_resources::dsl::*;
+
+    diesel::insert_into(schema::watched_resources::table)
+        .values(items_to_insert)
+        .on_conflict((transaction_version, write_set_change_index))
+        .do_nothing()
+}
@@ -9,7 +9,10 @@ pub mod stake_storer;
 use crate::processors::stake::models::{
     current_delegated_voter::CurrentDelegatedVoter,
     delegator_activities::DelegatedStakingActivity,
-    delegator_balances::{CurrentDelegatorBalance, CurrentDelegatorBalanceMap, DelegatorBalance},
+    delegator_balances::{
+        CurrentDelegatorBalance, CurrentDelegatorBalanceMap, DelegatorBalance, INACTIVE_SHARES,
+        PENDING_INACTIVE_SHARES,
+    },
     delegator_pools::{
         CurrentDelegatorPoolBalance, DelegatorPool, DelegatorPoolBalance, DelegatorPoolMap,
     },
@@ -25,30 +28,42 @@ use cedra_indexer_processor_sdk::{
     utils::convert::standardize_address,
 };
 
+/// Staking and delegation rows parsed from a batch of transactions, by table. Fields may be added
+/// as tables are, so the struct can't be built or destructured outside this crate.
+#[derive(Default)]
+#[non_exhaustive]
+pub struct StakeBatch {
+    pub current_stake_pool_voters: Vec<CurrentStakingPoolVoter>,
+    pub proposal_votes: Vec<ProposalVote>,
+    pub delegated_staking_activities: Vec<DelegatedStakingActivity>,
+    pub delegator_balances: Vec<DelegatorBalance>,
+    pub current_delegator_balances: Vec<CurrentDelegatorBalance>,
+    pub delegator_pools: Vec<DelegatorPool>,
+    pub delegator_pool_balances: Vec<DelegatorPoolBalance>,
+    pub current_delegator_pool_balances: Vec<CurrentDelegatorPoolBalance>,
+    pub current_delegated_voters: Vec<CurrentDelegatedVoter>,
+    /// Share table handles of inactive pools whose lockup cycle ended in the batch. Their
+    /// pending_inactive balances stored by earlier batches have to be moved to inactive_shares.
+    pub ended_pending_inactive_share_handles: Vec<String>,
+}
+
+/// Parses the staking tables out of `transactions`. Current tables are deduplicated and sorted by
+/// primary key. Delegator balances and default voters look up what earlier batches wrote through
+/// `conn`, and are left empty without it. Without `conn`, delegated voters are only parsed for pools
+/// whose governance records are written in the same batch.
 pub async fn parse_stake_data(
     transactions: &Vec<Transaction>,
     mut conn: Option<DbPoolConnection<'_>>,
     query_retries: u32,
     query_retry_delay_ms: u64,
-) -> Result<
-    (
-        Vec<CurrentStakingPoolVoter>,
-        Vec<ProposalVote>,
-        Vec<DelegatedStakingActivity>,
-        Vec<DelegatorBalance>,
-        Vec<CurrentDelegatorBalance>,
-        Vec<DelegatorPool>,
-        Vec<DelegatorPoolBalance>,
-        Vec<CurrentDelegatorPoolBalance>,
-        Vec<CurrentDelegatedVoter>,
-    ),
-    anyhow::Error,
-> {
+) -> Result<StakeBatch, anyhow::Error> {
     let mut all_current_stake_pool_voters: StakingPoolVoterMap = AHashMap::new();
     let mut all_proposal_votes = vec![];
     let mut all_delegator_activities = vec![];
     let mut all_delegator_balances = vec![];
     let mut all_current_delegator_balances: CurrentDelegatorBalanceMap = AHashMap::new();
+    // Version the lockup cycle of an inactive pool ended at, by share table handle
+    let mut all_ended_pending_inactive_share_handles: AHashMap<String, i64> = AHashMap::new();
     let mut all_delegator_pools: DelegatorPoolMap = AHashMap::new();
     let mut all_delegator_pool_balances = vec![];
     let mut all_current_delegator_pool_balances = AHashMap::new();
@@ -115,9 +130,28 @@ pub async fn parse_stake_data(
             }
         }
 
+        // this write table item indexing is to get delegator address, table handle, and voter & pending voter
+        for wsc in &transaction_info.changes {
+            if let Change::WriteTableItem(write_table_item) = wsc.change.as_ref().unwrap() {
+                let voter_map = CurrentDelegatedVoter::from_write_table_item(
+                    write_table_item,
+                    txn_version,
+                    txn_timestamp,
+                    &all_vote_delegation_handle_to_pool_address,
+                    conn.as_mut(),
+                    query_retries,
+                    query_retry_delay_ms,
+                )
+                .await
+                .unwrap();
+
+                all_current_delegated_voter.extend(voter_map);
+            }
+        }
+
         if let Some(ref mut conn) = conn {
             // Add delegator balances
-            let (mut delegator_balances, current_delegator_balances) =
+            let (mut delegator_balances, current_delegator_balances, ended_handles) =
                 CurrentDelegatorBalance::from_transaction(
                     txn,
                     &active_pool_to_staking_pool,
@@ -129,24 +163,10 @@ pub async fn parse_stake_data(
                 .unwrap();
             all_delegator_balances.append(&mut delegator_balances);
             all_current_delegator_balances.extend(current_delegator_balances);
-
-            // this write table item indexing is to get delegator address, table handle, and voter & pending voter
-            for wsc in &transaction_info.changes {
-                if let Change::WriteTableItem(write_table_item) = wsc.change.as_ref().unwrap() {
-                    let voter_map = CurrentDelegatedVoter::from_write_table_item(
-                        write_table_item,
-                        txn_version,
-                        txn_timestamp,
-                        &all_vote_delegation_handle_to_pool_address,
-                        conn,
-                        query_retries,
-                        query_retry_delay_ms,
-                    )
-                    .await
-                    .unwrap();
-
-                    all_current_delegated_voter.extend(voter_map);
-                }
+            for handle in ended_handles {
+                all_ended_pending_inactive_share_handles
+                    .entry(handle)
+                    .or_insert(txn_version);
             }
 
             // we need one last loop to prefill delegators that got in before the delegated voting contract was deployed
@@ -173,6 +193,35 @@ pub async fn parse_stake_data(
         }
     }
 
+    // Pending_inactive balances written before their lockup cycle ended in the same batch
+    let ended_balance_keys = all_current_delegator_balances
+        .iter()
+        .filter(|(_, balance)| {
+            balance.pool_type == PENDING_INACTIVE_SHARES
+                && all_ended_pending_inactive_share_handles
+                    .get(&balance.table_handle)
+                    .is_some_and(|version| *version > balance.last_transaction_version)
+        })
+        .map(|(key, _)| key.clone())
+        .collect::<Vec<_>>();
+    for key in ended_balance_keys {
+        let mut balance = all_current_delegator_balances.remove(&key).unwrap();
+        balance.pool_type = INACTIVE_SHARES.to_string();
+        let key = (
+            balance.delegator_address.clone(),
+            balance.pool_address.clone(),
+            balance.pool_type.clone(),
+        );
+        if all_current_delegator_balances
+            .get(&key)
+            .map_or(true, |existing| {
+                existing.last_transaction_version < balance.last_transaction_version
+            })
+        {
+            all_current_delegator_balances.insert(key, balance);
+        }
+    }
+
     // Getting list of values and sorting by pk in order to avoid postgres deadlock since we're doing multi threaded db writes
     let mut all_current_stake_pool_voters = all_current_stake_pool_voters
         .into_values()
@@ -206,15 +255,18 @@ pub async fn parse_stake_data(
         .sort_by(|a, b| a.staking_pool_address.cmp(&b.staking_pool_address));
     all_current_delegated_voter.sort();
 
-    Ok((
-        all_current_stake_pool_voters,
-        all_proposal_votes,
-        all_delegator_activities,
-        all_delegator_balances,
-        all_current_delegator_balances,
-        all_delegator_pools,
-        all_delegator_pool_balances,
-        all_current_delegator_pool_balances,
-        all_current_delegated_voter,
-    ))
+    Ok(StakeBatch {
+        current_stake_pool_voters: all_current_stake_pool_voters,
+        proposal_votes: all_proposal_votes,
+        delegated_staking_activities: all_delegator_activities,
+        delegator_balances: all_delegator_balances,
+        current_delegator_balances: all_current_delegator_balances,
+        delegator_pools: all_delegator_pools,
+        delegator_pool_balances: all_delegator_pool_balances,
+        current_delegator_pool_balances: all_current_delegator_pool_balances,
+        current_delegated_voters: all_current_delegated_voter,
+        ended_pending_inactive_share_handles: all_ended_pending_inactive_share_handles
+            .into_keys()
+            .collect(),
+    })
 }
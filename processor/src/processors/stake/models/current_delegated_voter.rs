@@ -6,12 +6,15 @@
 
 use super::delegator_balances::ShareToStakingPoolMapping;
 use crate::{
+    parquet_processors::parquet_utils::util::{HasVersion, NamedTable},
     processors::stake::models::{
         delegator_balances::CurrentDelegatorBalance, stake_utils::VoteDelegationTableItem,
     },
     schema::current_delegated_voter,
+    utils::{dedup::CurrentTableRow, log_throttle::throttle_log},
 };
 use ahash::AHashMap;
+use allocative::Allocative;
 use cedra_indexer_processor_sdk::{
     cedra_protos::transaction::v1::WriteTableItem, postgres::utils::database::DbPoolConnection,
     utils::convert::standardize_address,
@@ -19,6 +22,7 @@ use cedra_indexer_processor_sdk::{
 use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
 use field_count::FieldCount;
+use parquet_derive::ParquetRecordWriter;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Identifiable, Queryable)]
@@ -53,6 +57,21 @@ pub struct CurrentDelegatedVoter {
     pub last_transaction_timestamp: chrono::NaiveDateTime,
 }
 
+impl CurrentTableRow for CurrentDelegatedVoter {
+    type PrimaryKey = (String, String);
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        (
+            self.delegator_address.clone(),
+            self.delegation_pool_address.clone(),
+        )
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
+
 // (delegation_pool_address, delegator_address)
 type CurrentDelegatedVoterPK = (String, String);
 type CurrentDelegatedVoterMap = AHashMap<CurrentDelegatedVoterPK, CurrentDelegatedVoter>;
@@ -77,7 +96,7 @@ impl CurrentDelegatedVoter {
         txn_version: i64,
         txn_timestamp: chrono::NaiveDateTime,
         vote_delegation_handle_to_pool_address: &VoteDelegationTableHandleToPool,
-        conn: &mut DbPoolConnection<'_>,
+        conn: Option<&mut DbPoolConnection<'_>>,
         query_retries: u32,
         query_retry_delay_ms: u64,
     ) -> anyhow::Result<CurrentDelegatedVoterMap> {
@@ -94,17 +113,22 @@ impl CurrentDelegatedVoter {
         {
             let pool_address = match vote_delegation_handle_to_pool_address.get(&table_handle) {
                 Some(pool_address) => pool_address.clone(),
-                None => {
+                None => match conn {
                     // look up from db
-                    Self::get_delegation_pool_address_by_table_handle(conn, &table_handle, query_retries, query_retry_delay_ms).await
+                    Some(conn) => Self::get_delegation_pool_address_by_table_handle(conn, &table_handle, query_retries, query_retry_delay_ms).await
                         .unwrap_or_else(|_| {
-                            tracing::error!(
-                                transaction_version = txn_version,
-                                lookup_key = &table_handle,
-                                "Missing pool address for table handle. You probably should backfill db.",
-                            );
+                            if let Some(suppressed) = throttle_log("missing_delegated_voter_pool", &table_handle) {
+                                tracing::error!(
+                                    transaction_version = txn_version,
+                                    lookup_key = &table_handle,
+                                    suppressed = suppressed,
+                                    "Missing pool address for table handle. You probably should backfill db.",
+                                );
+                            }
                             "".to_string()
-                        })
+                        }),
+                    // Without a db, only pools whose governance records are in the batch are known
+                    None => "".to_string(),
                 },
             };
             if !pool_address.is_empty() {
@@ -267,6 +291,45 @@ impl CurrentDelegatedVoterQuery {
     }
 }
 
+// Parquet models
+#[derive(
+    Allocative, Clone, Debug, Default, Deserialize, FieldCount, ParquetRecordWriter, Serialize,
+)]
+pub struct ParquetCurrentDelegatedVoter {
+    pub delegation_pool_address: String,
+    pub delegator_address: String,
+    pub table_handle: Option<String>,
+    pub voter: Option<String>,
+    pub pending_voter: Option<String>,
+    pub last_transaction_version: i64,
+    #[allocative(skip)]
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl From<CurrentDelegatedVoter> for ParquetCurrentDelegatedVoter {
+    fn from(base: CurrentDelegatedVoter) -> Self {
+        Self {
+            delegation_pool_address: base.delegation_pool_address,
+            delegator_address: base.delegator_address,
+            table_handle: base.table_handle,
+            voter: base.voter,
+            pending_voter: base.pending_voter,
+            last_transaction_version: base.last_transaction_version,
+            last_transaction_timestamp: base.last_transaction_timestamp,
+        }
+    }
+}
+
+impl HasVersion for ParquetCurrentDelegatedVoter {
+    fn version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
+
+impl NamedTable for ParquetCurrentDelegatedVoter {
+    const TABLE_NAME: &'static str = "current_delegated_voter";
+}
+
 impl Ord for CurrentDelegatedVoter {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         self.delegator_address.cmp(&other.delegator_address).then(
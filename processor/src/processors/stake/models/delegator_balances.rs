@@ -8,9 +8,11 @@ use crate::{
         default::models::table_items::{PostgresTableItem, TableItem},
         stake::models::delegator_pools::{
             DelegatorPool, DelegatorPoolBalanceMetadata, PoolBalanceMetadata,
+            PostgresCurrentDelegatorPoolBalance,
         },
     },
     schema::{current_delegator_balances, delegator_balances},
+    utils::{dedup::CurrentTableRow, log_throttle::throttle_log},
 };
 use ahash::AHashMap;
 use allocative::Allocative;
@@ -38,6 +40,9 @@ pub type ShareToPoolMapping = AHashMap<TableHandle, PoolBalanceMetadata>;
 pub type CurrentDelegatorBalancePK = (Address, Address, String);
 pub type CurrentDelegatorBalanceMap = AHashMap<CurrentDelegatorBalancePK, CurrentDelegatorBalance>;
 
+pub const PENDING_INACTIVE_SHARES: &str = "pending_inactive_shares";
+pub const INACTIVE_SHARES: &str = "inactive_shares";
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct CurrentDelegatorBalance {
     pub delegator_address: String,
@@ -145,8 +150,52 @@ impl CurrentDelegatorBalance {
         }
     }
 
+    /// Pool type of the shares in an inactive pool. The pool of the delegation pool's observed
+    /// lockup cycle holds pending_inactive shares, the pools of ended cycles hold inactive ones.
+    /// When the delegation pool isn't written in the same transaction, its observed lockup cycle is
+    /// the one stored in `current_delegated_staking_pool_balances`.
+    async fn get_inactive_pool_type(
+        pool_balance: &PoolBalanceMetadata,
+        staking_pool: Option<&DelegatorPoolBalanceMetadata>,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<&'static str> {
+        let observed_lockup_cycle = match staking_pool {
+            Some(staking_pool) => Some(staking_pool.observed_lockup_cycle),
+            None => PostgresCurrentDelegatorPoolBalance::get_observed_lockup_cycle(
+                conn,
+                &pool_balance.parent_table_handle,
+            )
+            .await
+            .context(format!(
+                "Failed to get the observed lockup cycle of inactive pool {}",
+                pool_balance.parent_table_handle
+            ))?,
+        };
+        Ok(Self::inactive_pool_type(
+            pool_balance.lockup_cycle,
+            observed_lockup_cycle,
+        ))
+    }
+
+    /// Pools whose delegation pool's observed lockup cycle isn't known are taken as inactive
+    fn inactive_pool_type(
+        lockup_cycle: Option<u64>,
+        observed_lockup_cycle: Option<u64>,
+    ) -> &'static str {
+        match (lockup_cycle, observed_lockup_cycle) {
+            (Some(lockup_cycle), Some(observed_lockup_cycle))
+                if lockup_cycle == observed_lockup_cycle =>
+            {
+                PENDING_INACTIVE_SHARES
+            },
+            _ => INACTIVE_SHARES,
+        }
+    }
+
     /// Getting inactive share balances. There could be multiple inactive pool per staking pool so we have
     /// 2 layers of mapping (table w/ all inactive pools -> staking pool, table w/ delegator inactive shares -> each inactive pool)
+    /// Shares in the pool of the current lockup cycle are pending_inactive, see `get_inactive_pool_type`.
+    /// Every pool has its own scaling factor.
     pub async fn get_inactive_share_from_write_table_item(
         write_table_item: &WriteTableItem,
         txn_version: i64,
@@ -163,9 +212,10 @@ impl CurrentDelegatorBalance {
         if let Some(pool_balance) = inactive_share_to_pool.get(&table_handle) {
             // If it is, we need to get the inactive staking pool handle and use it to look up the staking pool
             let inactive_pool_handle = pool_balance.parent_table_handle.clone();
+            let staking_pool = inactive_pool_to_staking_pool.get(&inactive_pool_handle);
+            let pool_type = Self::get_inactive_pool_type(pool_balance, staking_pool, conn).await?;
 
-            let pool_address = match inactive_pool_to_staking_pool
-                .get(&inactive_pool_handle)
+            let pool_address = match staking_pool
                 .map(|metadata| metadata.staking_pool_address.clone())
             {
                 Some(pool_address) => pool_address,
@@ -180,11 +230,16 @@ impl CurrentDelegatorBalance {
                     {
                         Ok(pool) => pool,
                         Err(_) => {
-                            tracing::error!(
-                                transaction_version = txn_version,
-                                lookup_key = &inactive_pool_handle,
-                                "Failed to get staking pool address from inactive share handle. You probably should backfill db.",
-                            );
+                            if let Some(suppressed) =
+                                throttle_log("missing_inactive_share_pool", &inactive_pool_handle)
+                            {
+                                tracing::error!(
+                                    transaction_version = txn_version,
+                                    lookup_key = &inactive_pool_handle,
+                                    suppressed = suppressed,
+                                    "Failed to get staking pool address from inactive share handle. You probably should backfill db.",
+                                );
+                            }
                             return Ok(None);
                         },
                     }
@@ -222,7 +277,7 @@ impl CurrentDelegatorBalance {
                     write_set_change_index,
                     delegator_address: delegator_address.clone(),
                     pool_address: pool_address.clone(),
-                    pool_type: "inactive_shares".to_string(),
+                    pool_type: pool_type.to_string(),
                     table_handle: table_handle.clone(),
                     shares: shares.clone(),
                     parent_table_handle: inactive_pool_handle.clone(),
@@ -231,7 +286,7 @@ impl CurrentDelegatorBalance {
                 Self {
                     delegator_address,
                     pool_address,
-                    pool_type: "inactive_shares".to_string(),
+                    pool_type: pool_type.to_string(),
                     table_handle: table_handle.clone(),
                     last_transaction_version: txn_version,
                     shares,
@@ -302,9 +357,10 @@ impl CurrentDelegatorBalance {
         if let Some(pool_balance) = inactive_share_to_pool.get(&table_handle) {
             // If it is, we need to get the inactive staking pool handle and use it to look up the staking pool
             let inactive_pool_handle = pool_balance.parent_table_handle.clone();
+            let staking_pool = inactive_pool_to_staking_pool.get(&inactive_pool_handle);
+            let pool_type = Self::get_inactive_pool_type(pool_balance, staking_pool, conn).await?;
 
-            let pool_address = match inactive_pool_to_staking_pool
-                .get(&inactive_pool_handle)
+            let pool_address = match staking_pool
                 .map(|metadata| metadata.staking_pool_address.clone())
             {
                 Some(pool_address) => pool_address,
@@ -327,7 +383,7 @@ impl CurrentDelegatorBalance {
                     write_set_change_index,
                     delegator_address: delegator_address.clone(),
                     pool_address: pool_address.clone(),
-                    pool_type: "inactive_shares".to_string(),
+                    pool_type: pool_type.to_string(),
                     table_handle: table_handle.clone(),
                     shares: BigDecimal::zero(),
                     parent_table_handle: inactive_pool_handle.clone(),
@@ -336,7 +392,7 @@ impl CurrentDelegatorBalance {
                 Self {
                     delegator_address,
                     pool_address,
-                    pool_type: "inactive_shares".to_string(),
+                    pool_type: pool_type.to_string(),
                     table_handle: table_handle.clone(),
                     last_transaction_version: txn_version,
                     shares: BigDecimal::zero(),
@@ -435,13 +491,19 @@ impl CurrentDelegatorBalance {
         ))
     }
 
+    /// Also returns the share table handles of the inactive pools whose lockup cycle ended by this
+    /// transaction. Balances stored as pending_inactive in those pools are inactive from now on.
     pub async fn from_transaction(
         transaction: &Transaction,
         active_pool_to_staking_pool: &ShareToStakingPoolMapping,
         conn: &mut DbPoolConnection<'_>,
         query_retries: u32,
         query_retry_delay_ms: u64,
-    ) -> anyhow::Result<(Vec<DelegatorBalance>, CurrentDelegatorBalanceMap)> {
+    ) -> anyhow::Result<(
+        Vec<DelegatorBalance>,
+        CurrentDelegatorBalanceMap,
+        Vec<TableHandle>,
+    )> {
         let mut inactive_pool_to_staking_pool: ShareToStakingPoolMapping = AHashMap::new();
         let mut inactive_share_to_pool: ShareToPoolMapping = AHashMap::new();
         let mut current_delegator_balances: CurrentDelegatorBalanceMap = AHashMap::new();
@@ -473,6 +535,21 @@ impl CurrentDelegatorBalance {
                 }
             }
         }
+        let ended_pending_inactive_share_handles = inactive_share_to_pool
+            .values()
+            .filter(|pool_balance| {
+                match (
+                    pool_balance.lockup_cycle,
+                    inactive_pool_to_staking_pool.get(&pool_balance.parent_table_handle),
+                ) {
+                    (Some(lockup_cycle), Some(staking_pool)) => {
+                        lockup_cycle < staking_pool.observed_lockup_cycle
+                    },
+                    _ => false,
+                }
+            })
+            .map(|pool_balance| pool_balance.shares_table_handle.clone())
+            .collect();
         // Now make a pass through table items to get the actual delegator balances
         for (index, wsc) in changes.iter().enumerate() {
             let maybe_delegator_balance = match wsc.change.as_ref().unwrap() {
@@ -547,7 +624,11 @@ impl CurrentDelegatorBalance {
                 );
             }
         }
-        Ok((delegator_balances, current_delegator_balances))
+        Ok((
+            delegator_balances,
+            current_delegator_balances,
+            ended_pending_inactive_share_handles,
+        ))
     }
 }
 
@@ -561,6 +642,18 @@ impl CurrentDelegatorBalanceQuery {
             .first::<Self>(conn)
             .await
     }
+
+    /// Balances stored as pending_inactive in the inactive pools with these share table handles
+    pub async fn get_pending_inactive_by_share_handles(
+        conn: &mut DbPoolConnection<'_>,
+        table_handles: &[String],
+    ) -> diesel::QueryResult<Vec<Self>> {
+        current_delegator_balances::table
+            .filter(current_delegator_balances::pool_type.eq(PENDING_INACTIVE_SHARES))
+            .filter(current_delegator_balances::table_handle.eq_any(table_handles))
+            .load::<Self>(conn)
+            .await
+    }
 }
 
 // Parquet models
@@ -674,6 +767,22 @@ impl From<CurrentDelegatorBalance> for PostgresCurrentDelegatorBalance {
     }
 }
 
+impl CurrentTableRow for PostgresCurrentDelegatorBalance {
+    type PrimaryKey = (String, String, String);
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        (
+            self.delegator_address.clone(),
+            self.pool_address.clone(),
+            self.pool_type.clone(),
+        )
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(transaction_version, write_set_change_index))]
 #[diesel(table_name = delegator_balances)]
@@ -702,3 +811,32 @@ impl From<DelegatorBalance> for PostgresDelegatorBalance {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processors::stake::models::stake_utils::ObservedLockupCycle;
+
+    #[test]
+    fn test_inactive_pool_type_follows_lockup_cycle() {
+        let lockup_cycle = |key: &str| {
+            serde_json::from_str::<ObservedLockupCycle>(key)
+                .ok()
+                .map(|lockup_cycle| lockup_cycle.index)
+        };
+        let current_cycle = lockup_cycle(r#"{"index":"3"}"#);
+        assert_eq!(
+            CurrentDelegatorBalance::inactive_pool_type(current_cycle, Some(3)),
+            PENDING_INACTIVE_SHARES
+        );
+        assert_eq!(
+            CurrentDelegatorBalance::inactive_pool_type(current_cycle, None),
+            INACTIVE_SHARES
+        );
+        let ended_cycle = lockup_cycle(r#"{"index":"2"}"#);
+        assert_eq!(
+            CurrentDelegatorBalance::inactive_pool_type(ended_cycle, Some(3)),
+            INACTIVE_SHARES
+        );
+    }
+}
@@ -4,24 +4,30 @@
 // This is required because a diesel macro makes clippy sad
 #![allow(clippy::extra_unused_lifetimes)]
 
-use super::stake_utils::{StakeResource, StakeTableItem};
+use super::stake_utils::{ObservedLockupCycle, StakeResource, StakeTableItem};
 use crate::{
+    parquet_processors::parquet_utils::util::{HasVersion, NamedTable},
     schema::{
         current_delegated_staking_pool_balances, delegated_staking_pool_balances,
         delegated_staking_pools,
     },
-    utils::counters::PROCESSOR_UNKNOWN_TYPE_COUNT,
+    utils::{counters::PROCESSOR_UNKNOWN_TYPE_COUNT, dedup::CurrentTableRow},
 };
 use ahash::AHashMap;
+use allocative::Allocative;
 use cedra_indexer_processor_sdk::{
     cedra_indexer_transaction_stream::utils::time::parse_timestamp,
     cedra_protos::transaction::v1::{
         transaction::TxnData, write_set_change::Change, Transaction, WriteResource, WriteTableItem,
     },
+    postgres::utils::database::DbPoolConnection,
     utils::convert::standardize_address,
 };
 use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
 use field_count::FieldCount;
+use parquet_derive::ParquetRecordWriter;
 use serde::{Deserialize, Serialize};
 
 type StakingPoolAddress = String;
@@ -48,6 +54,8 @@ pub struct DelegatorPoolBalanceMetadata {
     pub operator_commission_percentage: BigDecimal,
     pub active_share_table_handle: String,
     pub inactive_share_table_handle: String,
+    /// Lockup cycle whose inactive pool holds the pending_inactive shares
+    pub observed_lockup_cycle: u64,
 }
 
 // Similar metadata but specifically for 0x1::pool_u64_unbound::Pool
@@ -59,6 +67,8 @@ pub struct PoolBalanceMetadata {
     pub scaling_factor: BigDecimal,
     pub shares_table_handle: String,
     pub parent_table_handle: String,
+    /// Lockup cycle of an inactive pool, the key it's stored under in `inactive_shares`
+    pub lockup_cycle: Option<u64>,
 }
 pub trait PoolBalanceMetadataConvertible {
     fn from_base(base: PoolBalanceMetadata) -> Self;
@@ -93,6 +103,7 @@ pub struct CurrentDelegatorPoolBalance {
     pub operator_commission_percentage: BigDecimal,
     pub inactive_table_handle: String,
     pub active_table_handle: String,
+    pub observed_lockup_cycle: i64,
 }
 
 impl DelegatorPool {
@@ -181,6 +192,7 @@ impl DelegatorPool {
                 operator_commission_percentage: inner.operator_commission_percentage.clone(),
                 active_share_table_handle: inner.active_shares.shares.inner.get_handle(),
                 inactive_share_table_handle: inner.inactive_shares.get_handle(),
+                observed_lockup_cycle: inner.observed_lockup_cycle.index,
             }))
         } else {
             Ok(None)
@@ -207,6 +219,9 @@ impl DelegatorPool {
                 scaling_factor: inner.scaling_factor.clone(),
                 shares_table_handle: inner.shares.inner.get_handle(),
                 parent_table_handle: standardize_address(&write_table_item.handle.to_string()),
+                lockup_cycle: serde_json::from_str::<ObservedLockupCycle>(&table_item_data.key)
+                    .ok()
+                    .map(|lockup_cycle| lockup_cycle.index),
             }))
         } else {
             Ok(None)
@@ -249,6 +264,7 @@ impl DelegatorPool {
                     operator_commission_percentage: balance.operator_commission_percentage.clone(),
                     inactive_table_handle: balance.inactive_share_table_handle.clone(),
                     active_table_handle: balance.active_share_table_handle.clone(),
+                    observed_lockup_cycle: balance.observed_lockup_cycle as i64,
                 },
             )))
         } else {
@@ -257,6 +273,108 @@ impl DelegatorPool {
     }
 }
 
+// Parquet models
+#[derive(
+    Allocative, Clone, Debug, Default, Deserialize, FieldCount, ParquetRecordWriter, Serialize,
+)]
+pub struct ParquetDelegatorPool {
+    pub staking_pool_address: String,
+    pub first_transaction_version: i64,
+}
+
+impl From<DelegatorPool> for ParquetDelegatorPool {
+    fn from(base: DelegatorPool) -> Self {
+        Self {
+            staking_pool_address: base.staking_pool_address,
+            first_transaction_version: base.first_transaction_version,
+        }
+    }
+}
+
+impl HasVersion for ParquetDelegatorPool {
+    fn version(&self) -> i64 {
+        self.first_transaction_version
+    }
+}
+
+impl NamedTable for ParquetDelegatorPool {
+    const TABLE_NAME: &'static str = "delegated_staking_pools";
+}
+
+#[derive(
+    Allocative, Clone, Debug, Default, Deserialize, FieldCount, ParquetRecordWriter, Serialize,
+)]
+pub struct ParquetDelegatorPoolBalance {
+    pub transaction_version: i64,
+    pub staking_pool_address: String,
+    pub total_coins: String,                    // BigDecimal
+    pub total_shares: String,                   // BigDecimal
+    pub operator_commission_percentage: String, // BigDecimal
+    pub inactive_table_handle: String,
+    pub active_table_handle: String,
+}
+
+impl From<DelegatorPoolBalance> for ParquetDelegatorPoolBalance {
+    fn from(base: DelegatorPoolBalance) -> Self {
+        Self {
+            transaction_version: base.transaction_version,
+            staking_pool_address: base.staking_pool_address,
+            total_coins: base.total_coins.to_string(),
+            total_shares: base.total_shares.to_string(),
+            operator_commission_percentage: base.operator_commission_percentage.to_string(),
+            inactive_table_handle: base.inactive_table_handle,
+            active_table_handle: base.active_table_handle,
+        }
+    }
+}
+
+impl HasVersion for ParquetDelegatorPoolBalance {
+    fn version(&self) -> i64 {
+        self.transaction_version
+    }
+}
+
+impl NamedTable for ParquetDelegatorPoolBalance {
+    const TABLE_NAME: &'static str = "delegated_staking_pool_balances";
+}
+
+#[derive(
+    Allocative, Clone, Debug, Default, Deserialize, FieldCount, ParquetRecordWriter, Serialize,
+)]
+pub struct ParquetCurrentDelegatorPoolBalance {
+    pub staking_pool_address: String,
+    pub total_coins: String,  // BigDecimal
+    pub total_shares: String, // BigDecimal
+    pub last_transaction_version: i64,
+    pub operator_commission_percentage: String, // BigDecimal
+    pub inactive_table_handle: String,
+    pub active_table_handle: String,
+}
+
+impl From<CurrentDelegatorPoolBalance> for ParquetCurrentDelegatorPoolBalance {
+    fn from(base: CurrentDelegatorPoolBalance) -> Self {
+        Self {
+            staking_pool_address: base.staking_pool_address,
+            total_coins: base.total_coins.to_string(),
+            total_shares: base.total_shares.to_string(),
+            last_transaction_version: base.last_transaction_version,
+            operator_commission_percentage: base.operator_commission_percentage.to_string(),
+            inactive_table_handle: base.inactive_table_handle,
+            active_table_handle: base.active_table_handle,
+        }
+    }
+}
+
+impl HasVersion for ParquetCurrentDelegatorPoolBalance {
+    fn version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
+
+impl NamedTable for ParquetCurrentDelegatorPoolBalance {
+    const TABLE_NAME: &'static str = "current_delegated_staking_pool_balances";
+}
+
 // Postgres models
 
 // Metadata to fill pool balances and delegator balance
@@ -351,6 +469,7 @@ pub struct PostgresCurrentDelegatorPoolBalance {
     pub operator_commission_percentage: BigDecimal,
     pub inactive_table_handle: String,
     pub active_table_handle: String,
+    pub observed_lockup_cycle: i64,
 }
 
 impl From<CurrentDelegatorPoolBalance> for PostgresCurrentDelegatorPoolBalance {
@@ -363,6 +482,41 @@ impl From<CurrentDelegatorPoolBalance> for PostgresCurrentDelegatorPoolBalance {
             operator_commission_percentage: base.operator_commission_percentage,
             inactive_table_handle: base.inactive_table_handle,
             active_table_handle: base.active_table_handle,
+            observed_lockup_cycle: base.observed_lockup_cycle,
         }
     }
 }
+
+impl PostgresCurrentDelegatorPoolBalance {
+    /// Observed lockup cycle stored for the delegation pool whose inactive pools are in
+    /// `inactive_table_handle`. None for pools stored before it was.
+    pub async fn get_observed_lockup_cycle(
+        conn: &mut DbPoolConnection<'_>,
+        inactive_table_handle: &str,
+    ) -> diesel::QueryResult<Option<u64>> {
+        let observed_lockup_cycle = current_delegated_staking_pool_balances::table
+            .filter(
+                current_delegated_staking_pool_balances::inactive_table_handle
+                    .eq(inactive_table_handle),
+            )
+            .select(current_delegated_staking_pool_balances::observed_lockup_cycle)
+            .first::<Option<i64>>(conn)
+            .await
+            .optional()?;
+        Ok(observed_lockup_cycle
+            .flatten()
+            .map(|observed_lockup_cycle| observed_lockup_cycle as u64))
+    }
+}
+
+impl CurrentTableRow for PostgresCurrentDelegatorPoolBalance {
+    type PrimaryKey = String;
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        self.staking_pool_address.clone()
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use cedra_indexer_processor_sdk::cedra_protos::transaction::v1::{
+    transaction::TxnData, Transaction,
+};
+use diesel::{
+    pg::Pg,
+    query_builder::{QueryFragment, QueryId},
+    sql_query,
+    sql_types::BigInt,
+};
+use serde::{Deserialize, Serialize};
+
+const NEW_EPOCH_EVENT_TYPES: [&str; 2] = [
+    "0x1::reconfiguration::NewEpochEvent",
+    "0x1::reconfiguration::NewEpoch",
+];
+
+/// A reconfiguration ending `epoch`, emitted in the transaction at `transaction_version`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EpochTransition {
+    pub epoch: i64,
+    pub transaction_version: i64,
+}
+
+impl EpochTransition {
+    pub fn from_transaction(transaction: &Transaction) -> Option<Self> {
+        let events = match transaction.txn_data.as_ref()? {
+            TxnData::User(txn) => &txn.events,
+            TxnData::BlockMetadata(txn) => &txn.events,
+            TxnData::Validator(txn) => &txn.events,
+            _ => return None,
+        };
+        // The reconfiguration runs as the last transaction of the epoch it ends
+        events
+            .iter()
+            .any(|event| NEW_EPOCH_EVENT_TYPES.contains(&event.type_str.as_str()))
+            .then(|| Self {
+                epoch: transaction.epoch as i64,
+                transaction_version: transaction.version as i64,
+            })
+    }
+
+    /// Snapshots the active balance of every delegator of every pool as of the end of the
+    /// epoch, from the balance histories. Nothing in the batch limits which pools are
+    /// snapshotted, and reprocessing the transition upserts the same balances again. The
+    /// histories must hold every change up to the transition, so this runs once the batch's
+    /// own changes are written. Shares are converted to coins at the pool's share price,
+    /// rounded down like the pool does.
+    pub fn snapshot_query(&self) -> impl QueryFragment<Pg> + QueryId + Send {
+        sql_query(
+            r#"INSERT INTO "epoch_end_delegator_balances"
+                (epoch, pool_address, delegator_address, shares, amount, epoch_end_version)
+            SELECT $1, balances.pool_address, balances.delegator_address, balances.shares,
+                CASE WHEN pools.total_shares > 0
+                    THEN TRUNC(balances.shares * pools.total_coins / pools.total_shares)
+                    ELSE 0
+                END,
+                $2
+            FROM (
+                SELECT DISTINCT ON (pool_address, delegator_address)
+                    pool_address, delegator_address, shares
+                FROM "delegator_balances"
+                WHERE pool_type = 'active_shares' AND transaction_version <= $2
+                ORDER BY pool_address, delegator_address, transaction_version DESC,
+                    write_set_change_index DESC
+            ) AS balances
+            JOIN (
+                SELECT DISTINCT ON (staking_pool_address)
+                    staking_pool_address, total_coins, total_shares
+                FROM "delegated_staking_pool_balances"
+                WHERE transaction_version <= $2
+                ORDER BY staking_pool_address, transaction_version DESC
+            ) AS pools ON pools.staking_pool_address = balances.pool_address
+            WHERE balances.shares > 0
+            ON CONFLICT (epoch, pool_address, delegator_address) DO UPDATE
+            SET shares = EXCLUDED.shares,
+                amount = EXCLUDED.amount,
+                epoch_end_version = EXCLUDED.epoch_end_version,
+                inserted_at = NOW()"#,
+        )
+        .bind::<BigInt, _>(self.epoch)
+        .bind::<BigInt, _>(self.transaction_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        processors::stake::{
+            models::{
+                delegator_balances::PostgresDelegatorBalance,
+                delegator_pools::PostgresDelegatorPoolBalance,
+            },
+            stake_storer::{insert_delegator_balances_query, insert_delegator_pool_balances_query},
+        },
+        schema::epoch_end_delegator_balances,
+        MIGRATIONS,
+    };
+    use bigdecimal::BigDecimal;
+    use cedra_indexer_processor_sdk::{
+        postgres::utils::database::{new_db_pool, run_migrations},
+        testing_framework::database::{PostgresTestDatabase, TestDatabase},
+    };
+    use diesel::{ExpressionMethods, QueryDsl};
+    use diesel_async::RunQueryDsl;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    fn delegator_balance(
+        version: i64,
+        pool: &str,
+        delegator: &str,
+        shares: &str,
+    ) -> PostgresDelegatorBalance {
+        PostgresDelegatorBalance {
+            transaction_version: version,
+            write_set_change_index: 0,
+            delegator_address: delegator.to_string(),
+            pool_address: pool.to_string(),
+            pool_type: "active_shares".to_string(),
+            table_handle: format!("{pool}_handle"),
+            shares: dec(shares),
+            parent_table_handle: format!("{pool}_handle"),
+        }
+    }
+
+    fn pool_balance(version: i64, pool: &str, total_coins: &str) -> PostgresDelegatorPoolBalance {
+        PostgresDelegatorPoolBalance {
+            transaction_version: version,
+            staking_pool_address: pool.to_string(),
+            total_coins: dec(total_coins),
+            total_shares: dec("300"),
+            operator_commission_percentage: dec("10"),
+            inactive_table_handle: format!("{pool}_inactive"),
+            active_table_handle: format!("{pool}_handle"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_includes_pools_changed_in_earlier_batches() {
+        let mut db = PostgresTestDatabase::new();
+        db.setup().await.unwrap();
+        let conn_pool = new_db_pool(db.get_db_url().as_str(), Some(10))
+            .await
+            .expect("Failed to create connection pool");
+        run_migrations(db.get_db_url(), conn_pool.clone(), MIGRATIONS).await;
+        let mut conn = conn_pool.get().await.unwrap();
+
+        // An earlier batch changes both pools, the batch ending epoch 7 changes neither
+        insert_delegator_balances_query(vec![
+            delegator_balance(5, "0xpool1", "0xa", "100"),
+            delegator_balance(6, "0xpool1", "0xb", "200"),
+            delegator_balance(7, "0xpool2", "0xc", "50"),
+            delegator_balance(8, "0xpool1", "0xb", "0"),
+        ])
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        insert_delegator_pool_balances_query(vec![
+            pool_balance(5, "0xpool1", "300"),
+            pool_balance(7, "0xpool2", "600"),
+        ])
+        .execute(&mut conn)
+        .await
+        .unwrap();
+        // Changes after the transition aren't part of the epoch
+        insert_delegator_balances_query(vec![delegator_balance(25, "0xpool1", "0xa", "1")])
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let transition = EpochTransition {
+            epoch: 7,
+            transaction_version: 20,
+        };
+        // Reprocessing the transition upserts the same snapshot
+        for _ in 0..2 {
+            transition
+                .snapshot_query()
+                .execute(&mut conn)
+                .await
+                .unwrap();
+        }
+
+        let snapshot: Vec<(String, String, BigDecimal, BigDecimal)> =
+            epoch_end_delegator_balances::table
+                .filter(epoch_end_delegator_balances::epoch.eq(7))
+                .order((
+                    epoch_end_delegator_balances::pool_address,
+                    epoch_end_delegator_balances::delegator_address,
+                ))
+                .select((
+                    epoch_end_delegator_balances::pool_address,
+                    epoch_end_delegator_balances::delegator_address,
+                    epoch_end_delegator_balances::shares,
+                    epoch_end_delegator_balances::amount,
+                ))
+                .load(&mut conn)
+                .await
+                .unwrap();
+        let row = |pool: &str, delegator: &str, shares: &str, amount: &str| {
+            (
+                pool.to_string(),
+                delegator.to_string(),
+                dec(shares),
+                dec(amount),
+            )
+        };
+        assert_eq!(snapshot, vec![
+            row("0xpool1", "0xa", "100", "100"),
+            row("0xpool2", "0xc", "50", "100"),
+        ]);
+    }
+}
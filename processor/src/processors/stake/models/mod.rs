@@ -2,6 +2,8 @@ pub mod current_delegated_voter;
 pub mod delegator_activities;
 pub mod delegator_balances;
 pub mod delegator_pools;
+pub mod epoch_end_delegator_balances;
+pub mod pool_history;
 pub mod proposal_votes;
 pub mod stake_utils;
 pub mod staking_pool_voter;
@@ -0,0 +1,210 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::{
+    processors::stake::models::stake_utils::StakeEvent,
+    schema::{pool_commission_history, pool_operator_history},
+};
+use bigdecimal::BigDecimal;
+use cedra_indexer_processor_sdk::{
+    cedra_indexer_transaction_stream::utils::time::parse_timestamp,
+    cedra_protos::transaction::v1::{transaction::TxnData, Transaction},
+    utils::convert::{standardize_address, u64_to_bigdecimal},
+};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+/// A commission change, in percent. Delegation pools name the pool and apply the change from the
+/// next lockup cycle, staking contracts name the staker and operator instead of the pool.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, event_index))]
+#[diesel(table_name = pool_commission_history)]
+pub struct PostgresPoolCommissionChange {
+    pub transaction_version: i64,
+    pub event_index: i64,
+    pub pool_address: Option<String>,
+    pub staker_address: String,
+    pub operator_address: Option<String>,
+    pub old_commission_percentage: Option<BigDecimal>,
+    pub new_commission_percentage: BigDecimal,
+    pub event_type: String,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, event_index))]
+#[diesel(table_name = pool_operator_history)]
+pub struct PostgresPoolOperatorChange {
+    pub transaction_version: i64,
+    pub event_index: i64,
+    pub pool_address: String,
+    pub old_operator_address: String,
+    pub new_operator_address: String,
+    pub event_type: String,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+/// Commission and operator changes of a transaction's staking events
+pub fn parse_pool_history(
+    transaction: &Transaction,
+) -> anyhow::Result<(
+    Vec<PostgresPoolCommissionChange>,
+    Vec<PostgresPoolOperatorChange>,
+)> {
+    let (mut commission_changes, mut operator_changes) = (vec![], vec![]);
+    let events = match transaction.txn_data.as_ref() {
+        Some(TxnData::User(txn)) => &txn.events,
+        Some(TxnData::Genesis(txn)) => &txn.events,
+        _ => return Ok((commission_changes, operator_changes)),
+    };
+    let txn_version = transaction.version as i64;
+    let transaction_timestamp =
+        parse_timestamp(transaction.timestamp.as_ref().unwrap(), txn_version).naive_utc();
+    for (index, event) in events.iter().enumerate() {
+        let event_index = index as i64;
+        let event_type = event.type_str.clone();
+        match StakeEvent::from_event(event.type_str.as_str(), &event.data, txn_version)? {
+            Some(StakeEvent::UpdateCommissionEvent(inner)) => {
+                commission_changes.push(PostgresPoolCommissionChange {
+                    transaction_version: txn_version,
+                    event_index,
+                    pool_address: None,
+                    staker_address: standardize_address(&inner.staker),
+                    operator_address: Some(standardize_address(&inner.operator)),
+                    old_commission_percentage: Some(u64_to_bigdecimal(
+                        inner.old_commission_percentage,
+                    )),
+                    new_commission_percentage: u64_to_bigdecimal(inner.new_commission_percentage),
+                    event_type,
+                    transaction_timestamp,
+                })
+            },
+            Some(StakeEvent::CommissionPercentageChangeEvent(inner)) => {
+                commission_changes.push(PostgresPoolCommissionChange {
+                    transaction_version: txn_version,
+                    event_index,
+                    pool_address: Some(standardize_address(&inner.pool_address)),
+                    staker_address: standardize_address(&inner.owner),
+                    operator_address: None,
+                    old_commission_percentage: None,
+                    new_commission_percentage: u64_to_bigdecimal(
+                        inner.commission_percentage_next_lockup_cycle,
+                    ) / BigDecimal::from(100),
+                    event_type,
+                    transaction_timestamp,
+                })
+            },
+            Some(StakeEvent::SetOperatorEvent(inner)) => {
+                operator_changes.push(PostgresPoolOperatorChange {
+                    transaction_version: txn_version,
+                    event_index,
+                    pool_address: standardize_address(&inner.pool_address),
+                    old_operator_address: standardize_address(&inner.old_operator),
+                    new_operator_address: standardize_address(&inner.new_operator),
+                    event_type,
+                    transaction_timestamp,
+                })
+            },
+            Some(StakeEvent::SwitchOperatorEvent(inner)) => {
+                operator_changes.push(PostgresPoolOperatorChange {
+                    transaction_version: txn_version,
+                    event_index,
+                    pool_address: standardize_address(&inner.pool_address),
+                    old_operator_address: standardize_address(&inner.old_operator),
+                    new_operator_address: standardize_address(&inner.new_operator),
+                    event_type,
+                    transaction_timestamp,
+                })
+            },
+            _ => {},
+        }
+    }
+    Ok((commission_changes, operator_changes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cedra_indexer_processor_sdk::cedra_protos::{
+        transaction::v1::{Event, UserTransaction},
+        util::timestamp::Timestamp,
+    };
+
+    fn event(type_str: &str, data: serde_json::Value) -> Event {
+        Event {
+            type_str: type_str.to_string(),
+            data: data.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_pool_history() {
+        let transaction = Transaction {
+            version: 7,
+            timestamp: Some(Timestamp::default()),
+            txn_data: Some(TxnData::User(UserTransaction {
+                events: vec![
+                    event(
+                        "0x1::delegation_pool::CommissionPercentageChange",
+                        serde_json::json!({
+                            "pool_address": "0xa",
+                            "owner": "0xb",
+                            "commission_percentage_next_lockup_cycle": "1050",
+                        }),
+                    ),
+                    event(
+                        "0x1::staking_contract::UpdateCommission",
+                        serde_json::json!({
+                            "staker": "0xb",
+                            "operator": "0xc",
+                            "old_commission_percentage": "10",
+                            "new_commission_percentage": "12",
+                        }),
+                    ),
+                    event(
+                        "0x1::stake::SetOperator",
+                        serde_json::json!({
+                            "pool_address": "0xa",
+                            "old_operator": "0xc",
+                            "new_operator": "0xd",
+                        }),
+                    ),
+                ],
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+
+        let (commission_changes, operator_changes) = parse_pool_history(&transaction).unwrap();
+        let commissions: Vec<_> = commission_changes
+            .iter()
+            .map(|change| {
+                (
+                    change.event_index,
+                    change.pool_address.clone(),
+                    change.old_commission_percentage.clone(),
+                    change.new_commission_percentage.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(commissions, vec![
+            (
+                0,
+                Some(standardize_address("0xa")),
+                None,
+                BigDecimal::from(1050) / BigDecimal::from(100),
+            ),
+            (1, None, Some(BigDecimal::from(10)), BigDecimal::from(12)),
+        ]);
+        assert_eq!(operator_changes.len(), 1);
+        assert_eq!(operator_changes[0].event_index, 2);
+        assert_eq!(
+            operator_changes[0].new_operator_address,
+            standardize_address("0xd")
+        );
+    }
+}
@@ -37,6 +37,15 @@ pub struct DelegationPoolResource {
     pub inactive_shares: Table,
     #[serde(deserialize_with = "deserialize_from_string")]
     pub operator_commission_percentage: BigDecimal,
+    pub observed_lockup_cycle: ObservedLockupCycle,
+}
+
+/// Key of the pools in `DelegationPool.inactive_shares`. The pool of the current lockup cycle
+/// holds the pending_inactive shares, the pools of earlier cycles the inactive ones.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ObservedLockupCycle {
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub index: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -105,6 +114,39 @@ pub struct ReactivateStakeEvent {
     pub pool_address: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UpdateCommissionEvent {
+    pub staker: String,
+    pub operator: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub old_commission_percentage: u64,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub new_commission_percentage: u64,
+}
+
+/// Delegation pool commission, in hundredths of a percent, applied from the next lockup cycle
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CommissionPercentageChangeEvent {
+    pub pool_address: String,
+    pub owner: String,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub commission_percentage_next_lockup_cycle: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SetOperatorEvent {
+    pub pool_address: String,
+    pub old_operator: String,
+    pub new_operator: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SwitchOperatorEvent {
+    pub old_operator: String,
+    pub new_operator: String,
+    pub pool_address: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum StakeTableItem {
     Pool(PoolResource),
@@ -208,6 +250,10 @@ pub enum StakeEvent {
     UnlockStakeEvent(UnlockStakeEvent),
     WithdrawStakeEvent(WithdrawStakeEvent),
     ReactivateStakeEvent(ReactivateStakeEvent),
+    UpdateCommissionEvent(UpdateCommissionEvent),
+    CommissionPercentageChangeEvent(CommissionPercentageChangeEvent),
+    SetOperatorEvent(SetOperatorEvent),
+    SwitchOperatorEvent(SwitchOperatorEvent),
 }
 
 impl StakeEvent {
@@ -232,6 +278,18 @@ impl StakeEvent {
             "0x1::delegation_pool::ReactivateStakeEvent"
             | "0x1::delegation_pool::ReactivateStake" => serde_json::from_str(data)
                 .map(|inner| Some(StakeEvent::ReactivateStakeEvent(inner))),
+            "0x1::staking_contract::UpdateCommissionEvent"
+            | "0x1::staking_contract::UpdateCommission" => serde_json::from_str(data)
+                .map(|inner| Some(StakeEvent::UpdateCommissionEvent(inner))),
+            "0x1::delegation_pool::CommissionPercentageChange" => serde_json::from_str(data)
+                .map(|inner| Some(StakeEvent::CommissionPercentageChangeEvent(inner))),
+            "0x1::stake::SetOperatorEvent" | "0x1::stake::SetOperator" => {
+                serde_json::from_str(data).map(|inner| Some(StakeEvent::SetOperatorEvent(inner)))
+            },
+            "0x1::staking_contract::SwitchOperatorEvent"
+            | "0x1::staking_contract::SwitchOperator" => {
+                serde_json::from_str(data).map(|inner| Some(StakeEvent::SwitchOperatorEvent(inner)))
+            },
             _ => Ok(None),
         }
         .context(format!(
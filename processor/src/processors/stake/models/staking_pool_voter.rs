@@ -5,15 +5,20 @@
 #![allow(clippy::extra_unused_lifetimes)]
 
 use crate::{
-    processors::stake::models::stake_utils::StakeResource, schema::current_staking_pool_voter,
+    parquet_processors::parquet_utils::util::{HasVersion, NamedTable},
+    processors::stake::models::stake_utils::StakeResource,
+    schema::current_staking_pool_voter,
+    utils::dedup::CurrentTableRow,
 };
 use ahash::AHashMap;
+use allocative::Allocative;
 use cedra_indexer_processor_sdk::{
     cedra_indexer_transaction_stream::utils::time::parse_timestamp,
     cedra_protos::transaction::v1::{write_set_change::Change, Transaction},
     utils::convert::standardize_address,
 };
 use field_count::FieldCount;
+use parquet_derive::ParquetRecordWriter;
 use serde::{Deserialize, Serialize};
 
 type StakingPoolAddress = String;
@@ -61,6 +66,41 @@ impl CurrentStakingPoolVoter {
     }
 }
 
+// Parquet models
+#[derive(
+    Allocative, Clone, Debug, Default, Deserialize, FieldCount, ParquetRecordWriter, Serialize,
+)]
+pub struct ParquetCurrentStakingPoolVoter {
+    pub staking_pool_address: String,
+    pub voter_address: String,
+    pub last_transaction_version: i64,
+    pub operator_address: String,
+    #[allocative(skip)]
+    pub block_timestamp: chrono::NaiveDateTime,
+}
+
+impl From<CurrentStakingPoolVoter> for ParquetCurrentStakingPoolVoter {
+    fn from(base: CurrentStakingPoolVoter) -> Self {
+        Self {
+            staking_pool_address: base.staking_pool_address,
+            voter_address: base.voter_address,
+            last_transaction_version: base.last_transaction_version,
+            operator_address: base.operator_address,
+            block_timestamp: base.block_timestamp,
+        }
+    }
+}
+
+impl HasVersion for ParquetCurrentStakingPoolVoter {
+    fn version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
+
+impl NamedTable for ParquetCurrentStakingPoolVoter {
+    const TABLE_NAME: &'static str = "current_staking_pool_voter";
+}
+
 // Postgres models
 #[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
 #[diesel(primary_key(staking_pool_address))]
@@ -82,3 +122,15 @@ impl From<CurrentStakingPoolVoter> for PostgresCurrentStakingPoolVoter {
         }
     }
 }
+
+impl CurrentTableRow for PostgresCurrentStakingPoolVoter {
+    type PrimaryKey = String;
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        self.staking_pool_address.clone()
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
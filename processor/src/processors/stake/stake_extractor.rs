@@ -6,10 +6,14 @@ use crate::processors::stake::{
         delegator_pools::{
             DelegatorPool, PostgresCurrentDelegatorPoolBalance, PostgresDelegatorPoolBalance,
         },
+        epoch_end_delegator_balances::EpochTransition,
+        pool_history::{
+            parse_pool_history, PostgresPoolCommissionChange, PostgresPoolOperatorChange,
+        },
         proposal_votes::PostgresProposalVote,
         staking_pool_voter::PostgresCurrentStakingPoolVoter,
     },
-    parse_stake_data,
+    parse_stake_data, StakeBatch,
 };
 use cedra_indexer_processor_sdk::{
     cedra_protos::transaction::v1::Transaction,
@@ -53,6 +57,10 @@ impl Processable for StakeExtractor {
         Vec<PostgresDelegatorPoolBalance>,
         Vec<PostgresCurrentDelegatorPoolBalance>,
         Vec<CurrentDelegatedVoter>,
+        Vec<EpochTransition>,
+        Vec<PostgresPoolCommissionChange>,
+        Vec<PostgresPoolOperatorChange>,
+        Vec<String>,
     );
     type RunType = AsyncRunType;
 
@@ -65,22 +73,7 @@ impl Processable for StakeExtractor {
     async fn process(
         &mut self,
         transactions: TransactionContext<Vec<Transaction>>,
-    ) -> Result<
-        Option<
-            TransactionContext<(
-                Vec<PostgresCurrentStakingPoolVoter>,
-                Vec<PostgresProposalVote>,
-                Vec<PostgresDelegatedStakingActivity>,
-                Vec<PostgresDelegatorBalance>,
-                Vec<PostgresCurrentDelegatorBalance>,
-                Vec<DelegatorPool>,
-                Vec<PostgresDelegatorPoolBalance>,
-                Vec<PostgresCurrentDelegatorPoolBalance>,
-                Vec<CurrentDelegatedVoter>,
-            )>,
-        >,
-        ProcessorError,
-    > {
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
         let conn = self
             .conn_pool
             .get()
@@ -90,17 +83,19 @@ impl Processable for StakeExtractor {
                 query: None,
             })?;
 
-        let (
-            raw_all_current_stake_pool_voters,
-            raw_all_proposal_votes,
-            raw_all_delegator_activities,
-            raw_all_delegator_balances,
-            raw_all_current_delegator_balances,
-            all_delegator_pools,
-            raw_all_delegator_pool_balances,
-            raw_all_current_delegator_pool_balances,
-            all_current_delegated_voter,
-        ) = match parse_stake_data(
+        let StakeBatch {
+            current_stake_pool_voters: raw_all_current_stake_pool_voters,
+            proposal_votes: raw_all_proposal_votes,
+            delegated_staking_activities: raw_all_delegator_activities,
+            delegator_balances: raw_all_delegator_balances,
+            current_delegator_balances: raw_all_current_delegator_balances,
+            delegator_pools: all_delegator_pools,
+            delegator_pool_balances: raw_all_delegator_pool_balances,
+            current_delegator_pool_balances: raw_all_current_delegator_pool_balances,
+            current_delegated_voters: all_current_delegated_voter,
+            ended_pending_inactive_share_handles,
+            ..
+        } = match parse_stake_data(
             &transactions.data,
             Some(conn),
             self.query_retries,
@@ -123,6 +118,22 @@ impl Processable for StakeExtractor {
             },
         };
 
+        let epoch_transitions = transactions
+            .data
+            .iter()
+            .filter_map(EpochTransition::from_transaction)
+            .collect::<Vec<_>>();
+
+        let (mut all_pool_commission_changes, mut all_pool_operator_changes) = (vec![], vec![]);
+        for transaction in &transactions.data {
+            let (commission_changes, operator_changes) =
+                parse_pool_history(transaction).map_err(|e| ProcessorError::ProcessError {
+                    message: format!("Error parsing pool history: {e:?}"),
+                })?;
+            all_pool_commission_changes.extend(commission_changes);
+            all_pool_operator_changes.extend(operator_changes);
+        }
+
         let all_delegator_balances: Vec<PostgresDelegatorBalance> = raw_all_delegator_balances
             .into_iter()
             .map(PostgresDelegatorBalance::from)
@@ -163,6 +174,10 @@ impl Processable for StakeExtractor {
                 all_delegator_pool_balances,
                 all_current_delegator_pool_balances,
                 all_current_delegated_voter,
+                epoch_transitions,
+                all_pool_commission_changes,
+                all_pool_operator_changes,
+                ended_pending_inactive_share_handles,
             ),
             metadata: transactions.metadata,
         }))
@@ -4,22 +4,36 @@ use crate::{
         models::{
             current_delegated_voter::CurrentDelegatedVoter,
             delegator_activities::PostgresDelegatedStakingActivity,
-            delegator_balances::{PostgresCurrentDelegatorBalance, PostgresDelegatorBalance},
+            delegator_balances::{
+                CurrentDelegatorBalanceQuery, PostgresCurrentDelegatorBalance,
+                PostgresDelegatorBalance, INACTIVE_SHARES,
+            },
             delegator_pools::{
                 DelegatorPool, PostgresCurrentDelegatorPoolBalance, PostgresDelegatorPoolBalance,
             },
+            epoch_end_delegator_balances::EpochTransition,
+            pool_history::{PostgresPoolCommissionChange, PostgresPoolOperatorChange},
             proposal_votes::PostgresProposalVote,
             staking_pool_voter::PostgresCurrentStakingPoolVoter,
         },
         stake_processor::StakeProcessorConfig,
     },
     schema,
-    utils::table_flags::{filter_data, TableFlags},
+    utils::{
+        dedup::dedup_by_pk_keep_latest,
+        table_flags::{filter_data, TableFlags},
+        table_names::TableNames,
+        table_stats::{execute_in_chunks_with_stats, record_table_stats, TableWriteStats},
+        write_sink::write_sinks,
+    },
+    version_guard,
 };
 use ahash::AHashMap;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use cedra_indexer_processor_sdk::{
-    postgres::utils::database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+    postgres::utils::database::{
+        execute_with_better_error, get_config_table_chunk_size, ArcDbPool,
+    },
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
@@ -29,14 +43,17 @@ use diesel::{
     pg::{upsert::excluded, Pg},
     query_builder::QueryFragment,
     query_dsl::methods::FilterDsl,
-    ExpressionMethods,
+    sql_query, ExpressionMethods,
 };
+use diesel_async::RunQueryDsl;
+use itertools::Itertools;
 
 pub struct StakeStorer
 where
     Self: Sized + Send + 'static,
 {
     conn_pool: ArcDbPool,
+    table_names: TableNames,
     processor_config: StakeProcessorConfig,
     tables_to_write: TableFlags,
 }
@@ -44,17 +61,187 @@ where
 impl StakeStorer {
     pub fn new(
         conn_pool: ArcDbPool,
+        table_names: TableNames,
         processor_config: StakeProcessorConfig,
         tables_to_write: TableFlags,
     ) -> Self {
         Self {
             conn_pool,
+            table_names,
             processor_config,
             tables_to_write,
         }
     }
 }
 
+impl StakeStorer {
+    /// Snapshots delegator balances at the epoch transitions of this batch, once its balance
+    /// changes are written. The snapshots are read from `delegator_balances` and
+    /// `delegated_staking_pool_balances`, so they're skipped while a `WriteSink` replaces the
+    /// database.
+    async fn snapshot_epoch_end_delegator_balances(
+        &self,
+        epoch_transitions: &[EpochTransition],
+    ) -> Result<(), ProcessorError> {
+        if epoch_transitions.is_empty() || write_sinks().replaces_database() {
+            return Ok(());
+        }
+        for transition in epoch_transitions {
+            let rows = execute_with_better_error(
+                self.conn_pool.clone(),
+                self.table_names.rename(transition.snapshot_query()),
+            )
+            .await?;
+            record_table_stats("epoch_end_delegator_balances", TableWriteStats {
+                rows_written: rows as i64,
+                ..Default::default()
+            });
+        }
+        Ok(())
+    }
+
+    /// Moves the pending_inactive balances stored in the pools whose lockup cycle ended to
+    /// inactive_shares. They aren't written when the cycle ends, only the pool is. The balances
+    /// are upserted as inactive_shares before their pending_inactive rows are deleted, so a failed
+    /// batch can move them again. The deletes count as writes in the table's stats.
+    async fn move_ended_pending_inactive_balances(
+        &self,
+        table_handles: Vec<String>,
+        chunk_size: usize,
+    ) -> Result<(), ProcessorError> {
+        if table_handles.is_empty() {
+            return Ok(());
+        }
+        let pending_inactive_balances = self
+            .get_pending_inactive_balances(&table_handles)
+            .await
+            .map_err(|e| ProcessorError::DBStoreError {
+                message: format!("Failed to get ended pending_inactive balances: {e:?}"),
+                query: None,
+            })?;
+        let inactive_balances: Vec<PostgresCurrentDelegatorBalance> = pending_inactive_balances
+            .iter()
+            .map(|balance| PostgresCurrentDelegatorBalance {
+                pool_type: INACTIVE_SHARES.to_string(),
+                ..balance.clone()
+            })
+            .collect();
+        execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_current_delegator_balances_query,
+            &inactive_balances,
+            "current_delegator_balances",
+            chunk_size,
+        )
+        .await?;
+        execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            delete_current_delegator_balances_query,
+            &pending_inactive_balances,
+            "current_delegator_balances",
+            chunk_size,
+        )
+        .await
+    }
+
+    async fn get_pending_inactive_balances(
+        &self,
+        table_handles: &[String],
+    ) -> Result<Vec<PostgresCurrentDelegatorBalance>> {
+        let mut conn = self
+            .conn_pool
+            .get()
+            .await
+            .context("Failed to get connection from pool")?;
+        let balances = CurrentDelegatorBalanceQuery::get_pending_inactive_by_share_handles(
+            &mut conn,
+            table_handles,
+        )
+        .await?;
+        Ok(balances
+            .into_iter()
+            .map(|balance| PostgresCurrentDelegatorBalance {
+                delegator_address: balance.delegator_address,
+                pool_address: balance.pool_address,
+                pool_type: balance.pool_type,
+                table_handle: balance.table_handle,
+                last_transaction_version: balance.last_transaction_version,
+                shares: balance.shares,
+                parent_table_handle: balance.parent_table_handle,
+            })
+            .collect())
+    }
+
+    /// Recomputes `governance_participation` from `proposal_votes` once this batch's votes are
+    /// written. A vote changes the turnout of its proposal, which weighs the stake weighted rate
+    /// of every voter, so all voters are recomputed, but only for batches with votes. Skipped
+    /// while a `WriteSink` replaces the database.
+    async fn update_governance_participation(&self) -> Result<()> {
+        if write_sinks().replaces_database() {
+            return Ok(());
+        }
+        let mut conn = self
+            .conn_pool
+            .get()
+            .await
+            .context("Failed to get connection from pool")?;
+        sql_query(
+            "WITH turnout AS (
+                SELECT proposal_id, SUM(num_votes) AS total_votes
+                FROM proposal_votes
+                GROUP BY proposal_id
+             ), voter_proposals AS (
+                SELECT voter_address, proposal_id, SUM(num_votes) AS num_votes,
+                    MAX(transaction_version) AS last_transaction_version,
+                    MAX(transaction_timestamp) AS last_transaction_timestamp
+                FROM proposal_votes
+                GROUP BY voter_address, proposal_id
+             ), voters AS (
+                SELECT voter_address, COUNT(*) AS proposals_voted,
+                    SUM(voter_proposals.num_votes) AS num_votes,
+                    SUM(turnout.total_votes) AS voted_turnout,
+                    MIN(proposal_id) AS first_proposal_id, MAX(proposal_id) AS last_proposal_id,
+                    MAX(last_transaction_version) AS last_transaction_version,
+                    MAX(last_transaction_timestamp) AS last_transaction_timestamp
+                FROM voter_proposals JOIN turnout USING (proposal_id)
+                GROUP BY voter_address
+             ), eligible AS (
+                SELECT voter_address, COUNT(*) AS proposals_eligible,
+                    SUM(turnout.total_votes) AS eligible_turnout
+                FROM voters JOIN turnout ON turnout.proposal_id >= voters.first_proposal_id
+                GROUP BY voter_address
+             )
+             INSERT INTO governance_participation (voter_address, proposals_voted,
+                 proposals_eligible, num_votes, participation_rate,
+                 stake_weighted_participation_rate, first_proposal_id, last_proposal_id,
+                 last_transaction_version, last_transaction_timestamp)
+             SELECT voter_address, proposals_voted, proposals_eligible, num_votes,
+                 proposals_voted::NUMERIC / proposals_eligible,
+                 CASE WHEN eligible_turnout > 0 THEN voted_turnout / eligible_turnout ELSE 0 END,
+                 first_proposal_id, last_proposal_id, last_transaction_version,
+                 last_transaction_timestamp
+             FROM voters JOIN eligible USING (voter_address)
+             ON CONFLICT (voter_address) DO UPDATE
+             SET proposals_voted = EXCLUDED.proposals_voted,
+                 proposals_eligible = EXCLUDED.proposals_eligible,
+                 num_votes = EXCLUDED.num_votes,
+                 participation_rate = EXCLUDED.participation_rate,
+                 stake_weighted_participation_rate = EXCLUDED.stake_weighted_participation_rate,
+                 first_proposal_id = EXCLUDED.first_proposal_id,
+                 last_proposal_id = EXCLUDED.last_proposal_id,
+                 last_transaction_version = EXCLUDED.last_transaction_version,
+                 last_transaction_timestamp = EXCLUDED.last_transaction_timestamp,
+                 inserted_at = NOW()",
+        )
+        .execute(&mut conn)
+        .await
+        .context("Failed to update governance participation")?;
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl Processable for StakeStorer {
     type Input = (
@@ -67,23 +254,17 @@ impl Processable for StakeStorer {
         Vec<PostgresDelegatorPoolBalance>,
         Vec<PostgresCurrentDelegatorPoolBalance>,
         Vec<CurrentDelegatedVoter>,
+        Vec<EpochTransition>,
+        Vec<PostgresPoolCommissionChange>,
+        Vec<PostgresPoolOperatorChange>,
+        Vec<String>,
     );
     type Output = ();
     type RunType = AsyncRunType;
 
     async fn process(
         &mut self,
-        input: TransactionContext<(
-            Vec<PostgresCurrentStakingPoolVoter>,
-            Vec<PostgresProposalVote>,
-            Vec<PostgresDelegatedStakingActivity>,
-            Vec<PostgresDelegatorBalance>,
-            Vec<PostgresCurrentDelegatorBalance>,
-            Vec<DelegatorPool>,
-            Vec<PostgresDelegatorPoolBalance>,
-            Vec<PostgresCurrentDelegatorPoolBalance>,
-            Vec<CurrentDelegatedVoter>,
-        )>,
+        input: TransactionContext<Self::Input>,
     ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
         let per_table_chunk_sizes: AHashMap<String, usize> = self
             .processor_config
@@ -101,8 +282,18 @@ impl Processable for StakeStorer {
             delegator_pool_balances,
             current_delegator_pool_balances,
             current_delegated_voter,
+            epoch_transitions,
+            pool_commission_changes,
+            pool_operator_changes,
+            ended_pending_inactive_share_handles,
         ) = input.data;
 
+        // Participation is read back from proposal_votes, so check for votes before filtering
+        let has_governance_votes = !proposal_votes.is_empty()
+            && self
+                .tables_to_write
+                .contains(TableFlags::GOVERNANCE_PARTICIPATION);
+
         let (
             current_stake_pool_voters,
             proposal_votes,
@@ -113,6 +304,9 @@ impl Processable for StakeStorer {
             delegator_pool_balances,
             current_delegator_pool_balances,
             current_delegated_voter,
+            epoch_transitions,
+            pool_commission_changes,
+            pool_operator_changes,
         ) = filter_datasets!(self, {
             current_stake_pool_voters => TableFlags::CURRENT_STAKING_POOL_VOTER,
             proposal_votes => TableFlags::PROPOSAL_VOTES,
@@ -123,91 +317,177 @@ impl Processable for StakeStorer {
             delegator_pool_balances => TableFlags::DELEGATED_STAKING_POOL_BALANCES,
             current_delegator_pool_balances => TableFlags::CURRENT_DELEGATED_STAKING_POOL_BALANCES,
             current_delegated_voter => TableFlags::CURRENT_DELEGATED_VOTER,
+            epoch_transitions => TableFlags::EPOCH_END_DELEGATOR_BALANCES,
+            pool_commission_changes => TableFlags::POOL_COMMISSION_HISTORY,
+            pool_operator_changes => TableFlags::POOL_OPERATOR_HISTORY,
         });
 
-        let cspv = execute_in_chunks(
+        // Before the upserts, so balances written after a cycle ended aren't moved
+        self.move_ended_pending_inactive_balances(
+            filter_data(
+                &self.tables_to_write,
+                TableFlags::CURRENT_DELEGATOR_BALANCES,
+                ended_pending_inactive_share_handles,
+            ),
+            get_config_table_chunk_size::<PostgresCurrentDelegatorBalance>(
+                "current_delegator_balances",
+                &per_table_chunk_sizes,
+            ),
+        )
+        .await
+        .map_err(|e| ProcessorError::DBStoreError {
+            message: format!(
+                "Failed to move ended pending_inactive balances for versions {} to {}: {:?}",
+                input.metadata.start_version, input.metadata.end_version, e,
+            ),
+            query: None,
+        })?;
+
+        let current_stake_pool_voters = dedup_by_pk_keep_latest(current_stake_pool_voters);
+        let current_delegator_balances = dedup_by_pk_keep_latest(current_delegator_balances);
+        let current_delegator_pool_balances =
+            dedup_by_pk_keep_latest(current_delegator_pool_balances);
+        let current_delegated_voter = dedup_by_pk_keep_latest(current_delegated_voter);
+
+        let cspv = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_current_stake_pool_voter_query,
             &current_stake_pool_voters,
+            "current_staking_pool_voter",
             get_config_table_chunk_size::<PostgresCurrentStakingPoolVoter>(
                 "current_staking_pool_voter",
                 &per_table_chunk_sizes,
             ),
         );
-        let pv = execute_in_chunks(
+        let pv = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_proposal_votes_query,
             &proposal_votes,
+            "proposal_votes",
             get_config_table_chunk_size::<PostgresProposalVote>(
                 "proposal_votes",
                 &per_table_chunk_sizes,
             ),
         );
-        let da = execute_in_chunks(
+        let da = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_delegator_activities_query,
             &delegator_activities,
+            "delegated_staking_activities",
             get_config_table_chunk_size::<PostgresDelegatedStakingActivity>(
                 "delegated_staking_activities",
                 &per_table_chunk_sizes,
             ),
         );
-        let db = execute_in_chunks(
+        let db = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_delegator_balances_query,
             &delegator_balances,
+            "delegator_balances",
             get_config_table_chunk_size::<PostgresDelegatorBalance>(
                 "delegator_balances",
                 &per_table_chunk_sizes,
             ),
         );
-        let cdb = execute_in_chunks(
+        let cdb = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_current_delegator_balances_query,
             &current_delegator_balances,
+            "current_delegator_balances",
             get_config_table_chunk_size::<PostgresCurrentDelegatorBalance>(
                 "current_delegator_balances",
                 &per_table_chunk_sizes,
             ),
         );
-        let dp = execute_in_chunks(
+        let dp = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_delegator_pools_query,
             &delegator_pools,
+            "delegated_staking_pools",
             get_config_table_chunk_size::<DelegatorPool>(
                 "delegated_staking_pools",
                 &per_table_chunk_sizes,
             ),
         );
-        let dpb = execute_in_chunks(
+        let dpb = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_delegator_pool_balances_query,
             &delegator_pool_balances,
+            "delegated_staking_pool_balances",
             get_config_table_chunk_size::<PostgresDelegatorPoolBalance>(
                 "delegated_staking_pool_balances",
                 &per_table_chunk_sizes,
             ),
         );
-        let cdpb = execute_in_chunks(
+        let cdpb = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_current_delegator_pool_balances_query,
             &current_delegator_pool_balances,
+            "current_delegated_staking_pool_balances",
             get_config_table_chunk_size::<PostgresCurrentDelegatorPoolBalance>(
                 "current_delegated_staking_pool_balances",
                 &per_table_chunk_sizes,
             ),
         );
-        let cdv = execute_in_chunks(
+        let cdv = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_current_delegated_voter_query,
             &current_delegated_voter,
+            "current_delegated_voter",
             get_config_table_chunk_size::<CurrentDelegatedVoter>(
                 "current_delegated_voter",
                 &per_table_chunk_sizes,
             ),
         );
 
-        futures::try_join!(cspv, pv, da, db, cdb, dp, dpb, cdpb, cdv)?;
+        let pch = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_pool_commission_history_query,
+            &pool_commission_changes,
+            "pool_commission_history",
+            get_config_table_chunk_size::<PostgresPoolCommissionChange>(
+                "pool_commission_history",
+                &per_table_chunk_sizes,
+            ),
+        );
+        let poh = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_pool_operator_history_query,
+            &pool_operator_changes,
+            "pool_operator_history",
+            get_config_table_chunk_size::<PostgresPoolOperatorChange>(
+                "pool_operator_history",
+                &per_table_chunk_sizes,
+            ),
+        );
+
+        futures::try_join!(cspv, pv, da, db, cdb, dp, dpb, cdpb, cdv, pch, poh)?;
+
+        self.snapshot_epoch_end_delegator_balances(&epoch_transitions)
+            .await?;
+
+        if has_governance_votes {
+            self.update_governance_participation().await.map_err(|e| {
+                ProcessorError::DBStoreError {
+                    message: format!(
+                        "Failed to update governance participation for versions {} to {}: {:?}",
+                        input.metadata.start_version, input.metadata.end_version, e,
+                    ),
+                    query: None,
+                }
+            })?;
+        }
 
         Ok(Some(TransactionContext {
             data: (),
@@ -240,7 +520,7 @@ pub fn insert_current_stake_pool_voter_query(
             inserted_at.eq(excluded(inserted_at)),
             operator_address.eq(excluded(operator_address)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
 }
 
 pub fn insert_proposal_votes_query(
@@ -291,7 +571,39 @@ pub fn insert_current_delegator_balances_query(
             shares.eq(excluded(shares)),
             parent_table_handle.eq(excluded(parent_table_handle)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
+}
+
+/// Deletes the balances' rows. Matches every combination of their delegators, pools, pool types
+/// and table handles, so only use it for all the rows of these pool types in these tables.
+pub fn delete_current_delegator_balances_query(
+    items_to_delete: Vec<PostgresCurrentDelegatorBalance>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::current_delegator_balances::dsl::*;
+
+    let (delegator_addresses, pool_addresses, pool_types, table_handles): (
+        Vec<String>,
+        Vec<String>,
+        Vec<String>,
+        Vec<String>,
+    ) = items_to_delete
+        .into_iter()
+        .map(|balance| {
+            (
+                balance.delegator_address,
+                balance.pool_address,
+                balance.pool_type,
+                balance.table_handle,
+            )
+        })
+        .multiunzip();
+    diesel::delete(
+        current_delegator_balances
+            .filter(delegator_address.eq_any(delegator_addresses))
+            .filter(pool_address.eq_any(pool_addresses))
+            .filter(pool_type.eq_any(pool_types))
+            .filter(table_handle.eq_any(table_handles)),
+    )
 }
 
 pub fn insert_delegator_pools_query(
@@ -338,8 +650,9 @@ pub fn insert_current_delegator_pool_balances_query(
             operator_commission_percentage.eq(excluded(operator_commission_percentage)),
             inactive_table_handle.eq(excluded(inactive_table_handle)),
             active_table_handle.eq(excluded(active_table_handle)),
+            observed_lockup_cycle.eq(excluded(observed_lockup_cycle)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
 }
 
 pub fn insert_current_delegated_voter_query(
@@ -359,5 +672,27 @@ pub fn insert_current_delegated_voter_query(
             table_handle.eq(excluded(table_handle)),
             inserted_at.eq(excluded(inserted_at)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
+}
+
+pub fn insert_pool_commission_history_query(
+    items_to_insert: Vec<PostgresPoolCommissionChange>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::pool_commission_history::dsl::*;
+
+    diesel::insert_into(schema::pool_commission_history::table)
+        .values(items_to_insert)
+        .on_conflict((transaction_version, event_index))
+        .do_nothing()
+}
+
+pub fn insert_pool_operator_history_query(
+    items_to_insert: Vec<PostgresPoolOperatorChange>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::pool_operator_history::dsl::*;
+
+    diesel::insert_into(schema::pool_operator_history::table)
+        .values(items_to_insert)
+        .on_conflict((transaction_version, event_index))
+        .do_nothing()
 }
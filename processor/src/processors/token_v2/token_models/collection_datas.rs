@@ -9,7 +9,10 @@ use super::{
     token_utils::{CollectionDataIdType, TokenWriteSet},
     tokens::TableHandleToOwner,
 };
-use crate::schema::{collection_datas, current_collection_datas};
+use crate::{
+    schema::{collection_datas, current_collection_datas},
+    utils::{log_throttle::throttle_log, metadata_cache::COLLECTION_CREATORS},
+};
 use cedra_indexer_processor_sdk::{
     cedra_protos::transaction::v1::WriteTableItem, postgres::utils::database::DbPoolConnection,
     utils::convert::standardize_address,
@@ -116,11 +119,16 @@ impl CollectionData {
                 {
                     Ok(creator) => creator,
                     Err(_) => {
-                        tracing::error!(
-                            transaction_version = txn_version,
-                            lookup_key = &table_handle,
-                            "Failed to get collection creator for table handle. You probably should backfill db."
-                        );
+                        if let Some(suppressed) =
+                            throttle_log("missing_collection_creator", &table_handle)
+                        {
+                            tracing::error!(
+                                transaction_version = txn_version,
+                                lookup_key = &table_handle,
+                                suppressed = suppressed,
+                                "Failed to get collection creator for table handle. You probably should backfill db."
+                            );
+                        }
                         return Ok(None);
                     },
                 },
@@ -178,11 +186,18 @@ impl CollectionData {
         query_retries: u32,
         query_retry_delay_ms: u64,
     ) -> anyhow::Result<String> {
+        if let Some(creator) = COLLECTION_CREATORS.get(table_handle) {
+            return Ok(creator);
+        }
         let mut tried = 0;
         while tried < query_retries {
             tried += 1;
             match CurrentCollectionDataQuery::get_by_table_handle(conn, table_handle).await {
-                Ok(current_collection_data) => return Ok(current_collection_data.creator_address),
+                Ok(current_collection_data) => {
+                    let creator = current_collection_data.creator_address;
+                    COLLECTION_CREATORS.insert(table_handle, creator.clone());
+                    return Ok(creator);
+                },
                 Err(_) => {
                     if tried < query_retries {
                         tokio::time::sleep(std::time::Duration::from_millis(query_retry_delay_ms))
@@ -12,6 +12,7 @@ use crate::{
         token_v2_models::v2_token_activities::TokenActivityHelperV1,
     },
     schema::current_token_pending_claims,
+    utils::{dedup::CurrentTableRow, log_throttle::throttle_log},
 };
 use ahash::AHashMap;
 use allocative_derive::Allocative;
@@ -23,11 +24,31 @@ use bigdecimal::{BigDecimal, ToPrimitive, Zero};
 use field_count::FieldCount;
 use parquet_derive::ParquetRecordWriter;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 // Map to keep track of the metadata of token offers that were claimed. The key is the token data id of the offer.
-// Potentially it'd also be useful to keep track of offers that were canceled.
 pub type TokenV1Claimed = AHashMap<String, TokenActivityHelperV1>;
 
+/// Whether an offer is still pending, or how it was resolved. Resolved offers keep their row with
+/// a zero amount.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PendingClaimStatus {
+    Pending,
+    Claimed,
+    Canceled,
+}
+
+impl fmt::Display for PendingClaimStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let res = match self {
+            PendingClaimStatus::Pending => "pending",
+            PendingClaimStatus::Claimed => "claimed",
+            PendingClaimStatus::Canceled => "canceled",
+        };
+        write!(f, "{res}")
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CurrentTokenPendingClaim {
     pub token_data_id_hash: String,
@@ -44,6 +65,8 @@ pub struct CurrentTokenPendingClaim {
     pub last_transaction_timestamp: chrono::NaiveDateTime,
     pub token_data_id: String,
     pub collection_id: String,
+    pub status: String,
+    pub resolution_transaction_version: Option<i64>,
 }
 
 impl Ord for CurrentTokenPendingClaim {
@@ -122,11 +145,16 @@ impl CurrentTokenPendingClaim {
                         last_transaction_timestamp: txn_timestamp,
                         token_data_id,
                         collection_id,
+                        status: PendingClaimStatus::Pending.to_string(),
+                        resolution_transaction_version: None,
                     }));
-                } else {
+                } else if let Some(suppressed) =
+                    throttle_log("missing_token_claim_metadata", &table_handle)
+                {
                     tracing::warn!(
                         transaction_version = txn_version,
                         table_handle = table_handle,
+                        suppressed = suppressed,
                         "Missing table handle metadata for TokenClaim. {:?}",
                         table_handle_to_owner
                     );
@@ -170,11 +198,18 @@ impl CurrentTokenPendingClaim {
                 .map(|table_metadata| table_metadata.get_owner_address());
 
             // If table handle isn't in TableHandleToOwner, try to find owner from token v1 claim events
+            let token_claimed = tokens_claimed.get(&token_data_id);
             if maybe_owner_address.is_none() {
-                if let Some(token_claimed) = tokens_claimed.get(&token_data_id) {
+                if let Some(token_claimed) = token_claimed {
                     maybe_owner_address = token_claimed.from_address.clone();
                 }
             }
+            // Offers are removed by either a claim or a cancel, and only claims emit claim events
+            let status = if token_claimed.is_some() {
+                PendingClaimStatus::Claimed
+            } else {
+                PendingClaimStatus::Canceled
+            };
 
             let owner_address = maybe_owner_address.unwrap_or_else(|| {
                 panic!(
@@ -210,6 +245,8 @@ impl CurrentTokenPendingClaim {
                 last_transaction_timestamp: txn_timestamp,
                 token_data_id,
                 collection_id,
+                status: status.to_string(),
+                resolution_transaction_version: Some(txn_version),
             }));
         }
         Ok(None)
@@ -236,6 +273,8 @@ pub struct ParquetCurrentTokenPendingClaim {
     pub last_transaction_timestamp: chrono::NaiveDateTime,
     pub token_data_id: String,
     pub collection_id: String,
+    pub status: String,
+    pub resolution_transaction_version: Option<i64>,
 }
 
 impl NamedTable for ParquetCurrentTokenPendingClaim {
@@ -268,6 +307,8 @@ impl From<CurrentTokenPendingClaim> for ParquetCurrentTokenPendingClaim {
             last_transaction_timestamp: raw_item.last_transaction_timestamp,
             token_data_id: raw_item.token_data_id,
             collection_id: raw_item.collection_id,
+            status: raw_item.status,
+            resolution_transaction_version: raw_item.resolution_transaction_version,
         }
     }
 }
@@ -293,6 +334,8 @@ pub struct PostgresCurrentTokenPendingClaim {
     pub last_transaction_timestamp: chrono::NaiveDateTime,
     pub token_data_id: String,
     pub collection_id: String,
+    pub status: String,
+    pub resolution_transaction_version: Option<i64>,
 }
 
 impl Ord for PostgresCurrentTokenPendingClaim {
@@ -328,6 +371,25 @@ impl From<CurrentTokenPendingClaim> for PostgresCurrentTokenPendingClaim {
             last_transaction_timestamp: raw_item.last_transaction_timestamp,
             token_data_id: raw_item.token_data_id,
             collection_id: raw_item.collection_id,
+            status: raw_item.status,
+            resolution_transaction_version: raw_item.resolution_transaction_version,
         }
     }
 }
+
+impl CurrentTableRow for PostgresCurrentTokenPendingClaim {
+    type PrimaryKey = (String, BigDecimal, String, String);
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        (
+            self.token_data_id_hash.clone(),
+            self.property_version.clone(),
+            self.from_address.clone(),
+            self.to_address.clone(),
+        )
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
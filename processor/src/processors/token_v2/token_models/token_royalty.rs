@@ -9,6 +9,7 @@ use crate::{
     parquet_processors::parquet_utils::util::{HasVersion, NamedTable},
     processors::token_v2::token_models::token_utils::TokenWriteSet,
     schema::current_token_royalty_v1,
+    utils::dedup::CurrentTableRow,
 };
 use allocative_derive::Allocative;
 use cedra_indexer_processor_sdk::cedra_protos::transaction::v1::WriteTableItem;
@@ -174,3 +175,15 @@ impl From<CurrentTokenRoyaltyV1> for PostgresCurrentTokenRoyaltyV1 {
         }
     }
 }
+
+impl CurrentTableRow for PostgresCurrentTokenRoyaltyV1 {
+    type PrimaryKey = String;
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        self.token_data_id.clone()
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
@@ -13,7 +13,7 @@ use cedra_indexer_processor_sdk::utils::{
     },
     extract::{deserialize_property_map_from_bcs_hexstring, hash_str},
 };
-use bigdecimal::BigDecimal;
+use bigdecimal::{BigDecimal, ToPrimitive, Zero};
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Formatter};
 
@@ -172,6 +172,23 @@ impl RoyaltyType {
     pub fn get_payee_address(&self) -> String {
         standardize_address(&self.payee_address)
     }
+
+    pub fn get_bps(&self) -> Option<i64> {
+        royalty_bps(
+            &self.royalty_points_numerator,
+            &self.royalty_points_denominator,
+        )
+    }
+}
+
+/// Royalty in basis points, rounded down. None without a denominator.
+pub fn royalty_bps(numerator: &BigDecimal, denominator: &BigDecimal) -> Option<i64> {
+    if denominator.is_zero() {
+        return None;
+    }
+    (numerator * BigDecimal::from(10_000) / denominator)
+        .with_scale(0)
+        .to_i64()
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -1,14 +1,25 @@
-use crate::processors::token_v2::{
-    token_models::{
-        token_claims::PostgresCurrentTokenPendingClaim,
-        token_royalty::PostgresCurrentTokenRoyaltyV1, tokens::TableMetadataForToken,
+use crate::{
+    processors::token_v2::{
+        token_models::{
+            token_claims::PostgresCurrentTokenPendingClaim,
+            token_royalty::PostgresCurrentTokenRoyaltyV1, tokens::TableMetadataForToken,
+        },
+        token_v2_models::{
+            v2_collection_volumes::{CollectionIdCache, CollectionVolumes},
+            v2_collections::CurrentCollectionV2,
+            v2_nft_transfers::PostgresNftTransfer,
+            v2_token_activities::PostgresTokenActivityV2,
+            v2_token_datas::PostgresCurrentTokenDataV2,
+            v2_token_mints_detail::{MintModules, PostgresTokenMintDetail},
+            v2_token_ownerships::PostgresCurrentTokenOwnershipV2,
+            v2_token_property_values::TokenPropertyValues,
+        },
+        token_v2_processor_helpers::{parse_v2_token, TokenV2Batch},
     },
-    token_v2_models::{
-        v2_collections::CurrentCollectionV2, v2_token_activities::PostgresTokenActivityV2,
-        v2_token_datas::PostgresCurrentTokenDataV2,
-        v2_token_ownerships::PostgresCurrentTokenOwnershipV2,
+    utils::{
+        price_oracle::PriceOracle,
+        table_flags::{should_write, TableFlags},
     },
-    token_v2_processor_helpers::parse_v2_token,
 };
 use cedra_indexer_processor_sdk::{
     cedra_protos::transaction::v1::Transaction,
@@ -27,16 +38,60 @@ where
     query_retries: u32,
     query_retry_delay_ms: u64,
     conn_pool: ArcDbPool,
+    price_oracle: Option<PriceOracle>,
+    mint_modules: MintModules,
+    tables_to_write: TableFlags,
+    collection_ids: CollectionIdCache,
 }
 
 impl TokenV2Extractor {
-    pub fn new(query_retries: u32, query_retry_delay_ms: u64, conn_pool: ArcDbPool) -> Self {
+    pub fn new(
+        query_retries: u32,
+        query_retry_delay_ms: u64,
+        conn_pool: ArcDbPool,
+        price_oracle: Option<PriceOracle>,
+        mint_modules: MintModules,
+        tables_to_write: TableFlags,
+    ) -> Self {
         Self {
             query_retries,
             query_retry_delay_ms,
             conn_pool,
+            price_oracle,
+            mint_modules,
+            tables_to_write,
+            collection_ids: CollectionIdCache::default(),
         }
     }
+
+    async fn get_collection_volumes(
+        &mut self,
+        token_activities: &[PostgresTokenActivityV2],
+        current_token_datas: &[PostgresCurrentTokenDataV2],
+    ) -> Result<CollectionVolumes, ProcessorError> {
+        if token_activities.is_empty() {
+            return Ok(CollectionVolumes::default());
+        }
+        let mut conn = self
+            .conn_pool
+            .get()
+            .await
+            .map_err(|e| ProcessorError::DBStoreError {
+                message: format!("Failed to get connection from pool: {e:?}"),
+                query: None,
+            })?;
+        self.collection_ids
+            .load(&mut conn, token_activities, current_token_datas)
+            .await
+            .map_err(|e| ProcessorError::DBStoreError {
+                message: format!("Failed to load collection ids of token activities: {e:?}"),
+                query: None,
+            })?;
+        Ok(CollectionVolumes::from_activities(
+            token_activities,
+            &self.collection_ids,
+        ))
+    }
 }
 
 #[async_trait]
@@ -51,6 +106,10 @@ impl Processable for TokenV2Extractor {
         Vec<PostgresTokenActivityV2>,
         Vec<PostgresCurrentTokenRoyaltyV1>,
         Vec<PostgresCurrentTokenPendingClaim>,
+        CollectionVolumes,
+        Vec<PostgresTokenMintDetail>,
+        Vec<PostgresNftTransfer>,
+        Vec<TokenPropertyValues>,
     );
     type RunType = AsyncRunType;
 
@@ -68,6 +127,10 @@ impl Processable for TokenV2Extractor {
                 Vec<PostgresTokenActivityV2>,
                 Vec<PostgresCurrentTokenRoyaltyV1>,
                 Vec<PostgresCurrentTokenPendingClaim>,
+                CollectionVolumes,
+                Vec<PostgresTokenMintDetail>,
+                Vec<PostgresNftTransfer>,
+                Vec<TokenPropertyValues>,
             )>,
         >,
         ProcessorError,
@@ -93,20 +156,18 @@ impl Processable for TokenV2Extractor {
 
         // Token v2 processor only writes to current tables. If you need to write to non-current
         // tables, modify TokenV2Storer step to include the tables you want to write to.
-        let (
-            _,
-            _,
-            _,
+        let TokenV2Batch {
             current_collections_v2,
-            raw_current_token_datas_v2,
-            raw_current_deleted_token_datas_v2,
-            raw_current_token_ownerships_v2,
-            raw_current_deleted_token_ownerships_v2,
-            raw_token_activities_v2,
-            _,
-            raw_current_token_royalties_v1,
-            raw_current_token_claims,
-        ) = parse_v2_token(
+            current_token_datas_v2: raw_current_token_datas_v2,
+            current_deleted_token_datas_v2: raw_current_deleted_token_datas_v2,
+            current_token_ownerships_v2: raw_current_token_ownerships_v2,
+            current_deleted_token_ownerships_v2: raw_current_deleted_token_ownerships_v2,
+            token_activities_v2: raw_token_activities_v2,
+            current_token_royalties_v1: raw_current_token_royalties_v1,
+            current_token_pending_claims: raw_current_token_claims,
+            token_property_values,
+            ..
+        } = parse_v2_token(
             &transactions.data,
             &table_handle_to_owner,
             &mut Some(db_connection),
@@ -125,10 +186,26 @@ impl Processable for TokenV2Extractor {
                 .map(PostgresCurrentTokenRoyaltyV1::from)
                 .collect();
 
-        let postgres_token_activities_v2: Vec<PostgresTokenActivityV2> = raw_token_activities_v2
-            .into_iter()
-            .map(PostgresTokenActivityV2::from)
-            .collect();
+        let mut postgres_token_activities_v2: Vec<PostgresTokenActivityV2> =
+            raw_token_activities_v2
+                .into_iter()
+                .map(PostgresTokenActivityV2::from)
+                .collect();
+        if let Some(price_oracle) = &self.price_oracle {
+            PostgresTokenActivityV2::set_usd_values(
+                &mut postgres_token_activities_v2,
+                price_oracle,
+            )
+            .await;
+        }
+
+        let token_mints_detail = PostgresTokenMintDetail::from_transactions(
+            &transactions.data,
+            &postgres_token_activities_v2,
+            &self.mint_modules,
+        );
+        let nft_transfers =
+            PostgresNftTransfer::from_token_activities(&postgres_token_activities_v2);
 
         let postgres_current_token_datas_v2: Vec<PostgresCurrentTokenDataV2> =
             raw_current_token_datas_v2
@@ -154,6 +231,19 @@ impl Processable for TokenV2Extractor {
                 .map(PostgresCurrentTokenOwnershipV2::from)
                 .collect();
 
+        let collection_volumes = if should_write(
+            &self.tables_to_write,
+            TableFlags::COLLECTION_VOLUME_LEADERBOARD,
+        ) {
+            self.get_collection_volumes(
+                &postgres_token_activities_v2,
+                &postgres_current_token_datas_v2,
+            )
+            .await?
+        } else {
+            CollectionVolumes::default()
+        };
+
         Ok(Some(TransactionContext {
             data: (
                 current_collections_v2,
@@ -164,6 +254,10 @@ impl Processable for TokenV2Extractor {
                 postgres_token_activities_v2,
                 postgres_current_token_royalties_v1,
                 postgres_current_token_claims,
+                collection_volumes,
+                token_mints_detail,
+                nft_transfers,
+                token_property_values,
             ),
             metadata: transactions.metadata,
         }))
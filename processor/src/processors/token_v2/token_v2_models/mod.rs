@@ -1,6 +1,10 @@
+pub mod v2_collection_volumes;
 pub mod v2_collections;
+pub mod v2_nft_transfers;
 pub mod v2_token_activities;
 pub mod v2_token_datas;
 pub mod v2_token_metadata;
+pub mod v2_token_mints_detail;
 pub mod v2_token_ownerships;
+pub mod v2_token_property_values;
 pub mod v2_token_utils;
@@ -0,0 +1,207 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use super::{
+    v2_token_activities::PostgresTokenActivityV2, v2_token_datas::PostgresCurrentTokenDataV2,
+};
+use crate::schema::{collection_volume_leaderboard, current_token_datas_v2};
+use ahash::{AHashMap, AHashSet};
+use bigdecimal::{BigDecimal, Zero};
+use cedra_indexer_processor_sdk::postgres::utils::database::DbPoolConnection;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+/// Collections of recently seen tokens kept in memory, past this the cache starts over
+const COLLECTION_ID_CACHE_SIZE: usize = 100_000;
+
+/// Token activity per collection, added up batch by batch for leaderboards.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(collection_id))]
+#[diesel(table_name = collection_volume_leaderboard)]
+pub struct PostgresCollectionVolume {
+    pub collection_id: String,
+    /// Activities in the batch, added to the stored count on upsert
+    pub activity_count: i64,
+    /// Sum of the activities' `usd_value` in the batch, added to the stored volume on upsert
+    pub usd_volume: BigDecimal,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+/// Bounded token data id to collection id lookup, so activities of tokens that weren't written
+/// in the same batch rarely need a query
+#[derive(Debug, Default)]
+pub struct CollectionIdCache {
+    collection_ids: AHashMap<String, String>,
+}
+
+impl CollectionIdCache {
+    fn insert(&mut self, token_data_id: String, collection_id: String) {
+        if self.collection_ids.len() >= COLLECTION_ID_CACHE_SIZE {
+            self.collection_ids.clear();
+        }
+        self.collection_ids.insert(token_data_id, collection_id);
+    }
+
+    /// Makes sure the collection of every activity's token is cached, if it's known at all
+    pub async fn load(
+        &mut self,
+        conn: &mut DbPoolConnection<'_>,
+        activities: &[PostgresTokenActivityV2],
+        current_token_datas: &[PostgresCurrentTokenDataV2],
+    ) -> diesel::QueryResult<()> {
+        for token_data in current_token_datas {
+            self.insert(
+                token_data.token_data_id.clone(),
+                token_data.collection_id.clone(),
+            );
+        }
+        let missing: AHashSet<&String> = activities
+            .iter()
+            .map(|activity| &activity.token_data_id)
+            .filter(|token_data_id| !self.collection_ids.contains_key(*token_data_id))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let rows = current_token_datas_v2::table
+            .filter(current_token_datas_v2::token_data_id.eq_any(missing))
+            .select((
+                current_token_datas_v2::token_data_id,
+                current_token_datas_v2::collection_id,
+            ))
+            .load::<(String, String)>(conn)
+            .await?;
+        for (token_data_id, collection_id) in rows {
+            self.insert(token_data_id, collection_id);
+        }
+        Ok(())
+    }
+}
+
+/// A batch's volumes per collection, and the activities of tokens whose collection wasn't cached,
+/// which are added to their collection's volume once it's looked up in the database on write
+#[derive(Clone, Debug, Default)]
+pub struct CollectionVolumes {
+    pub volumes: Vec<PostgresCollectionVolume>,
+    pub uncached_activities: Vec<PostgresTokenActivityV2>,
+}
+
+impl CollectionVolumes {
+    pub fn from_activities(
+        activities: &[PostgresTokenActivityV2],
+        collection_ids: &CollectionIdCache,
+    ) -> Self {
+        let mut volumes: AHashMap<&String, PostgresCollectionVolume> = AHashMap::new();
+        let mut uncached_activities = vec![];
+        for activity in activities {
+            let Some(collection_id) = collection_ids.collection_ids.get(&activity.token_data_id)
+            else {
+                uncached_activities.push(activity.clone());
+                continue;
+            };
+            let volume = volumes
+                .entry(collection_id)
+                .or_insert_with(|| PostgresCollectionVolume {
+                    collection_id: collection_id.clone(),
+                    activity_count: 0,
+                    usd_volume: BigDecimal::zero(),
+                    last_transaction_version: activity.transaction_version,
+                    last_transaction_timestamp: activity.transaction_timestamp,
+                });
+            volume.activity_count += 1;
+            if let Some(usd_value) = &activity.usd_value {
+                volume.usd_volume += usd_value;
+            }
+            if activity.transaction_version > volume.last_transaction_version {
+                volume.last_transaction_version = activity.transaction_version;
+                volume.last_transaction_timestamp = activity.transaction_timestamp;
+            }
+        }
+        Self {
+            volumes: volumes.into_values().collect(),
+            uncached_activities,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.volumes.is_empty() && self.uncached_activities.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(
+        transaction_version: i64,
+        token_data_id: &str,
+        usd_value: Option<i64>,
+    ) -> PostgresTokenActivityV2 {
+        PostgresTokenActivityV2 {
+            transaction_version,
+            event_index: 0,
+            event_account_address: "0x1".to_string(),
+            token_data_id: token_data_id.to_string(),
+            property_version_v1: BigDecimal::zero(),
+            type_: "0x4::collection::Mint".to_string(),
+            from_address: None,
+            to_address: None,
+            token_amount: BigDecimal::from(1),
+            before_value: None,
+            after_value: None,
+            entry_function_id_str: None,
+            token_standard: "v2".to_string(),
+            is_fungible_v2: None,
+            transaction_timestamp: chrono::NaiveDateTime::default(),
+            usd_value: usd_value.map(BigDecimal::from),
+            burner_address: None,
+            is_creator_burn: None,
+        }
+    }
+
+    #[test]
+    fn test_volumes_per_collection() {
+        let mut cache = CollectionIdCache::default();
+        cache.insert("0xt1".to_string(), "0xc1".to_string());
+        cache.insert("0xt2".to_string(), "0xc1".to_string());
+        cache.insert("0xt3".to_string(), "0xc2".to_string());
+        let activities = vec![
+            activity(1, "0xt1", Some(10)),
+            activity(2, "0xt2", None),
+            activity(3, "0xt3", Some(5)),
+            activity(4, "0xunknown", Some(100)),
+        ];
+
+        let CollectionVolumes {
+            mut volumes,
+            uncached_activities,
+        } = CollectionVolumes::from_activities(&activities, &cache);
+        volumes.sort_by(|a, b| a.collection_id.cmp(&b.collection_id));
+        let volumes: Vec<(String, i64, BigDecimal, i64)> = volumes
+            .into_iter()
+            .map(|volume| {
+                (
+                    volume.collection_id,
+                    volume.activity_count,
+                    volume.usd_volume,
+                    volume.last_transaction_version,
+                )
+            })
+            .collect();
+        assert_eq!(volumes, vec![
+            ("0xc1".to_string(), 2, BigDecimal::from(10), 2),
+            ("0xc2".to_string(), 1, BigDecimal::from(5), 3),
+        ]);
+        let uncached: Vec<i64> = uncached_activities
+            .iter()
+            .map(|activity| activity.transaction_version)
+            .collect();
+        assert_eq!(uncached, vec![4]);
+    }
+}
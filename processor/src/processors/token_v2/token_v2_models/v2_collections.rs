@@ -20,6 +20,9 @@ use crate::{
         },
     },
     schema::{collections_v2, current_collections_v2},
+    utils::{
+        dedup::CurrentTableRow, log_throttle::throttle_log, metadata_cache::COLLECTION_CREATORS,
+    },
 };
 use allocative_derive::Allocative;
 use anyhow::Context;
@@ -83,6 +86,18 @@ pub struct CurrentCollectionV2 {
     pub last_transaction_timestamp: chrono::NaiveDateTime,
 }
 
+impl CurrentTableRow for CurrentCollectionV2 {
+    type PrimaryKey = String;
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        self.collection_id.clone()
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
+
 #[derive(Debug, QueryableByName)]
 pub struct CreatorFromCollectionTableV1 {
     #[diesel(sql_type = Text)]
@@ -260,11 +275,17 @@ impl CollectionV2 {
                                     {
                                         Ok(creator) => creator,
                                         Err(_) => {
-                                            tracing::error!(
-                                                transaction_version = txn_version,
-                                                lookup_key = &table_handle,
-                                                "Failed to get collection v2 creator for table handle. You probably should backfill db."
-                                            );
+                                            if let Some(suppressed) = throttle_log(
+                                                "missing_collection_v2_creator",
+                                                &table_handle,
+                                            ) {
+                                                tracing::error!(
+                                                    transaction_version = txn_version,
+                                                    lookup_key = &table_handle,
+                                                    suppressed = suppressed,
+                                                    "Failed to get collection v2 creator for table handle. You probably should backfill db."
+                                                );
+                                            }
                                             return Ok(None);
                                         },
                                     }
@@ -332,11 +353,17 @@ impl CollectionV2 {
         query_retries: u32,
         query_retry_delay_ms: u64,
     ) -> anyhow::Result<String> {
+        if let Some(creator) = COLLECTION_CREATORS.get(table_handle) {
+            return Ok(creator);
+        }
         let mut tried = 0;
         while tried < query_retries {
             tried += 1;
             match Self::get_by_table_handle(conn, table_handle).await {
-                Ok(creator) => return Ok(creator),
+                Ok(creator) => {
+                    COLLECTION_CREATORS.insert(table_handle, creator.clone());
+                    return Ok(creator);
+                },
                 Err(_) => {
                     if tried < query_retries {
                         tokio::time::sleep(std::time::Duration::from_millis(query_retry_delay_ms))
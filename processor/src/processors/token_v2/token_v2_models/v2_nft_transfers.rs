@@ -0,0 +1,228 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use super::{v2_token_activities::PostgresTokenActivityV2, v2_token_utils::TokenStandard};
+use crate::schema::nft_transfers;
+use ahash::AHashMap;
+use bigdecimal::BigDecimal;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+const V2_TRANSFER_TYPES: [&str; 2] = ["0x1::object::TransferEvent", "0x1::object::Transfer"];
+const V1_WITHDRAW_TYPES: [&str; 2] = ["0x3::token::WithdrawEvent", "0x3::token::TokenWithdraw"];
+const V1_DEPOSIT_TYPES: [&str; 2] = ["0x3::token::DepositEvent", "0x3::token::TokenDeposit"];
+const V1_OFFER_TYPES: [&str; 2] = [
+    "0x3::token_transfers::TokenOfferEvent",
+    "0x3::token_transfers::Offer",
+];
+const V1_CLAIM_TYPES: [&str; 2] = [
+    "0x3::token_transfers::TokenClaimEvent",
+    "0x3::token_transfers::Claim",
+];
+
+pub const TRANSFER_TYPE_TRANSFER: &str = "transfer";
+pub const TRANSFER_TYPE_CLAIM: &str = "claim";
+
+/// A token moving from one account to another, whatever the token standard.
+///
+/// v2 transfers are the object's transfer events. A v1 transfer is a withdraw followed by a
+/// deposit of the same token in the same transaction, keyed by the deposit's event. Offered
+/// tokens are withdrawn into escrow, so the transfer is the claim instead, from the offerer to
+/// the claimer; a cancelled offer isn't a transfer. Mints and burns aren't transfers either.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, event_index))]
+#[diesel(table_name = nft_transfers)]
+pub struct PostgresNftTransfer {
+    pub transaction_version: i64,
+    pub event_index: i64,
+    pub token_data_id: String,
+    pub property_version_v1: BigDecimal,
+    pub from_address: String,
+    pub to_address: String,
+    pub token_amount: BigDecimal,
+    pub token_standard: String,
+    pub transfer_type: String,
+    pub entry_function_id_str: Option<String>,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl PostgresNftTransfer {
+    pub fn from_token_activities(token_activities: &[PostgresTokenActivityV2]) -> Vec<Self> {
+        let mut activities_by_version: AHashMap<i64, Vec<&PostgresTokenActivityV2>> =
+            AHashMap::new();
+        for activity in token_activities {
+            activities_by_version
+                .entry(activity.transaction_version)
+                .or_default()
+                .push(activity);
+        }
+
+        let mut transfers = vec![];
+        for (_, mut activities) in activities_by_version {
+            activities.sort_by_key(|activity| activity.event_index);
+            // Withdrawn v1 tokens waiting for their deposit, by token id
+            let mut withdrawals: AHashMap<(&str, &BigDecimal), Vec<&PostgresTokenActivityV2>> =
+                AHashMap::new();
+            for activity in activities {
+                let event_type = activity.type_.as_str();
+                let token_id = (
+                    activity.token_data_id.as_str(),
+                    &activity.property_version_v1,
+                );
+                if activity.token_standard == TokenStandard::V2.to_string() {
+                    if V2_TRANSFER_TYPES.contains(&event_type) {
+                        transfers.extend(Self::new(
+                            activity,
+                            activity.from_address.as_deref(),
+                            TRANSFER_TYPE_TRANSFER,
+                        ));
+                    }
+                } else if V1_WITHDRAW_TYPES.contains(&event_type) {
+                    withdrawals.entry(token_id).or_default().push(activity);
+                } else if V1_OFFER_TYPES.contains(&event_type) {
+                    // The offer's withdrawal went into escrow, not to another account
+                    withdrawals.entry(token_id).or_default().pop();
+                } else if V1_CLAIM_TYPES.contains(&event_type) {
+                    transfers.extend(Self::new(
+                        activity,
+                        activity.from_address.as_deref(),
+                        TRANSFER_TYPE_CLAIM,
+                    ));
+                } else if V1_DEPOSIT_TYPES.contains(&event_type) {
+                    let withdrawal = withdrawals
+                        .get_mut(&token_id)
+                        .filter(|pending| !pending.is_empty())
+                        .map(|pending| pending.remove(0));
+                    if let Some(withdrawal) = withdrawal {
+                        transfers.extend(Self::new(
+                            activity,
+                            withdrawal.from_address.as_deref(),
+                            TRANSFER_TYPE_TRANSFER,
+                        ));
+                    }
+                }
+            }
+        }
+        transfers.sort_by_key(|transfer| (transfer.transaction_version, transfer.event_index));
+        transfers
+    }
+
+    /// A transfer keyed by `activity`, if it goes from one account to another
+    fn new(
+        activity: &PostgresTokenActivityV2,
+        from_address: Option<&str>,
+        transfer_type: &str,
+    ) -> Option<Self> {
+        let from_address = from_address?;
+        let to_address = activity.to_address.as_deref()?;
+        if from_address == to_address {
+            return None;
+        }
+        Some(Self {
+            transaction_version: activity.transaction_version,
+            event_index: activity.event_index,
+            token_data_id: activity.token_data_id.clone(),
+            property_version_v1: activity.property_version_v1.clone(),
+            from_address: from_address.to_string(),
+            to_address: to_address.to_string(),
+            token_amount: activity.token_amount.clone(),
+            token_standard: activity.token_standard.clone(),
+            transfer_type: transfer_type.to_string(),
+            entry_function_id_str: activity.entry_function_id_str.clone(),
+            transaction_timestamp: activity.transaction_timestamp,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::{One, Zero};
+
+    fn activity(
+        event_index: i64,
+        type_: &str,
+        from_address: Option<&str>,
+        to_address: Option<&str>,
+    ) -> PostgresTokenActivityV2 {
+        PostgresTokenActivityV2 {
+            transaction_version: 1,
+            event_index,
+            event_account_address: String::new(),
+            token_data_id: "0xtoken".to_string(),
+            property_version_v1: BigDecimal::zero(),
+            type_: type_.to_string(),
+            from_address: from_address.map(str::to_string),
+            to_address: to_address.map(str::to_string),
+            token_amount: BigDecimal::one(),
+            before_value: None,
+            after_value: None,
+            entry_function_id_str: None,
+            token_standard: TokenStandard::V1.to_string(),
+            is_fungible_v2: None,
+            transaction_timestamp: chrono::NaiveDateTime::default(),
+            usd_value: None,
+            burner_address: None,
+            is_creator_burn: None,
+        }
+    }
+
+    fn summary(transfers: &[PostgresNftTransfer]) -> Vec<(i64, &str, &str, &str)> {
+        transfers
+            .iter()
+            .map(|transfer| {
+                (
+                    transfer.event_index,
+                    transfer.from_address.as_str(),
+                    transfer.to_address.as_str(),
+                    transfer.transfer_type.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_v1_withdraw_deposit_pair() {
+        let transfers = PostgresNftTransfer::from_token_activities(&[
+            activity(0, "0x3::token::WithdrawEvent", Some("0xa"), None),
+            activity(1, "0x3::token::DepositEvent", None, Some("0xb")),
+        ]);
+        assert_eq!(summary(&transfers), vec![(1, "0xa", "0xb", "transfer")]);
+    }
+
+    #[test]
+    fn test_v1_offer_and_claim() {
+        // The offer escrows the token, the deposit of the claim has no matching withdrawal
+        let transfers = PostgresNftTransfer::from_token_activities(&[
+            activity(0, "0x3::token::WithdrawEvent", Some("0xa"), None),
+            activity(
+                1,
+                "0x3::token_transfers::TokenOfferEvent",
+                Some("0xa"),
+                Some("0xb"),
+            ),
+            activity(2, "0x3::token::DepositEvent", None, Some("0xb")),
+            activity(
+                3,
+                "0x3::token_transfers::TokenClaimEvent",
+                Some("0xa"),
+                Some("0xb"),
+            ),
+        ]);
+        assert_eq!(summary(&transfers), vec![(3, "0xa", "0xb", "claim")]);
+    }
+
+    #[test]
+    fn test_v2_transfer_and_v1_mint() {
+        let mut transfer = activity(1, "0x1::object::TransferEvent", Some("0xa"), Some("0xb"));
+        transfer.token_standard = TokenStandard::V2.to_string();
+        let transfers = PostgresNftTransfer::from_token_activities(&[
+            activity(0, "0x3::token::DepositEvent", None, Some("0xa")),
+            transfer,
+        ]);
+        assert_eq!(summary(&transfers), vec![(1, "0xa", "0xb", "transfer")]);
+    }
+}
@@ -18,6 +18,7 @@ use crate::{
         },
     },
     schema::token_activities_v2,
+    utils::price_oracle::PriceOracle,
 };
 use allocative_derive::Allocative;
 use cedra_indexer_processor_sdk::{
@@ -25,8 +26,10 @@ use cedra_indexer_processor_sdk::{
 };
 use bigdecimal::{BigDecimal, One, ToPrimitive, Zero};
 use field_count::FieldCount;
+use itertools::Itertools;
 use parquet_derive::ParquetRecordWriter;
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TokenActivityV2 {
@@ -45,6 +48,10 @@ pub struct TokenActivityV2 {
     pub token_standard: String,
     pub is_fungible_v2: Option<bool>,
     pub transaction_timestamp: chrono::NaiveDateTime,
+    /// Sender of the transaction, for burns
+    pub burner_address: Option<String>,
+    /// Whether a burn's sender isn't the token's previous owner, None if the owner isn't known
+    pub is_creator_burn: Option<bool>,
 }
 
 /// A simplified TokenActivity (excluded common fields) to reduce code duplication
@@ -68,12 +75,24 @@ struct TokenActivityHelperV2 {
 }
 
 impl TokenActivityV2 {
+    /// Attributes a burn to the transaction's sender. Only the owner or the creator's burn
+    /// authority, a v2 `BurnRef` or v1's `burn_by_creator`, can burn a token, so a sender other
+    /// than the previous owner makes it a creator burn.
+    fn set_burner(&mut self, sender_address: &str) {
+        self.is_creator_burn = self
+            .from_address
+            .as_ref()
+            .map(|owner_address| owner_address != sender_address);
+        self.burner_address = Some(sender_address.to_string());
+    }
+
     pub async fn get_nft_v2_from_parsed_event(
         event: &Event,
         txn_version: i64,
         txn_timestamp: chrono::NaiveDateTime,
         event_index: i64,
         entry_function_id_str: &Option<String>,
+        sender_address: &str,
         token_v2_metadata: &ObjectAggregatedDataMapping,
     ) -> anyhow::Result<Option<Self>> {
         let event_type = event.type_str.clone();
@@ -91,6 +110,10 @@ impl TokenActivityV2 {
                 V2TokenEvent::TransferEvent(inner) => inner.get_object_address(),
                 _ => event_account_address.clone(),
             };
+            let is_burn = matches!(
+                token_event,
+                V2TokenEvent::BurnEvent(_) | V2TokenEvent::Burn(_)
+            );
 
             if let Some(metadata) = token_v2_metadata.get(&token_data_id) {
                 let object_core = &metadata.object.object_core;
@@ -152,7 +175,7 @@ impl TokenActivityV2 {
                         event_type: event_type.clone(),
                     },
                 };
-                return Ok(Some(Self {
+                let mut activity = Self {
                     transaction_version: txn_version,
                     event_index,
                     event_account_address,
@@ -168,7 +191,13 @@ impl TokenActivityV2 {
                     token_standard: TokenStandard::V2.to_string(),
                     is_fungible_v2: None,
                     transaction_timestamp: txn_timestamp,
-                }));
+                    burner_address: None,
+                    is_creator_burn: None,
+                };
+                if is_burn {
+                    activity.set_burner(sender_address);
+                }
+                return Ok(Some(activity));
             } else {
                 // If the object metadata isn't found in the transaction, then the token was burnt.
 
@@ -180,7 +209,7 @@ impl TokenActivityV2 {
                     None
                 };
 
-                return Ok(Some(Self {
+                let mut activity = Self {
                     transaction_version: txn_version,
                     event_index,
                     event_account_address,
@@ -196,7 +225,13 @@ impl TokenActivityV2 {
                     token_standard: TokenStandard::V2.to_string(),
                     is_fungible_v2: None,
                     transaction_timestamp: txn_timestamp,
-                }));
+                    burner_address: None,
+                    is_creator_burn: None,
+                };
+                if is_burn {
+                    activity.set_burner(sender_address);
+                }
+                return Ok(Some(activity));
             }
         }
         Ok(None)
@@ -208,6 +243,7 @@ impl TokenActivityV2 {
         txn_timestamp: chrono::NaiveDateTime,
         event_index: i64,
         entry_function_id_str: &Option<String>,
+        sender_address: &str,
         tokens_claimed: &mut TokenV1Claimed,
     ) -> anyhow::Result<Option<Self>> {
         let event_type = event.type_str.clone();
@@ -338,8 +374,12 @@ impl TokenActivityV2 {
                     helper
                 },
             };
+            let is_burn = matches!(
+                token_event,
+                TokenEvent::BurnTokenEvent(_) | TokenEvent::Burn(_)
+            );
             let token_data_id_struct = token_activity_helper.token_data_id_struct;
-            return Ok(Some(Self {
+            let mut activity = Self {
                 transaction_version: txn_version,
                 event_index,
                 event_account_address,
@@ -355,7 +395,13 @@ impl TokenActivityV2 {
                 token_standard: TokenStandard::V1.to_string(),
                 is_fungible_v2: None,
                 transaction_timestamp: txn_timestamp,
-            }));
+                burner_address: None,
+                is_creator_burn: None,
+            };
+            if is_burn {
+                activity.set_burner(sender_address);
+            }
+            return Ok(Some(activity));
         }
         Ok(None)
     }
@@ -437,6 +483,9 @@ pub struct PostgresTokenActivityV2 {
     pub token_standard: String,
     pub is_fungible_v2: Option<bool>,
     pub transaction_timestamp: chrono::NaiveDateTime,
+    pub usd_value: Option<BigDecimal>,
+    pub burner_address: Option<String>,
+    pub is_creator_burn: Option<bool>,
 }
 
 impl From<TokenActivityV2> for PostgresTokenActivityV2 {
@@ -457,6 +506,37 @@ impl From<TokenActivityV2> for PostgresTokenActivityV2 {
             token_standard: raw_item.token_standard,
             is_fungible_v2: raw_item.is_fungible_v2,
             transaction_timestamp: raw_item.transaction_timestamp,
+            usd_value: None,
+            burner_address: raw_item.burner_address,
+            is_creator_burn: raw_item.is_creator_burn,
+        }
+    }
+}
+
+impl PostgresTokenActivityV2 {
+    /// Fills in `usd_value` as `token_amount * price` for every activity whose token the
+    /// oracle has a price for. Oracle failures are logged and leave `usd_value` unset so that
+    /// a flaky price source never blocks indexing.
+    pub async fn set_usd_values(activities: &mut [Self], price_oracle: &PriceOracle) {
+        let token_data_ids: Vec<String> = activities
+            .iter()
+            .map(|activity| activity.token_data_id.clone())
+            .unique()
+            .collect();
+        match price_oracle.get_usd_prices(&token_data_ids).await {
+            Ok(prices) => {
+                for activity in activities.iter_mut() {
+                    activity.usd_value = prices
+                        .get(&activity.token_data_id)
+                        .map(|price| &activity.token_amount * price);
+                }
+            },
+            Err(e) => {
+                warn!(
+                    error = ?e,
+                    "Failed to fetch usd prices for token activities, skipping usd_value"
+                );
+            },
         }
     }
 }
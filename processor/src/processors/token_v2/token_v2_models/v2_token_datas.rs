@@ -13,20 +13,23 @@ use crate::{
         token_v2::{
             token_models::token_utils::TokenWriteSet,
             token_v2_models::v2_token_utils::{
-                TokenStandard, TokenV2, TokenV2Burned, DEFAULT_NONE,
+                Royalty, TokenStandard, TokenV2, TokenV2Burned, DEFAULT_NONE,
             },
         },
     },
     schema::current_token_datas_v2,
+    utils::{dedup::CurrentTableRow, log_throttle::throttle_log},
 };
 use allocative_derive::Allocative;
 use anyhow::Context;
 use cedra_indexer_processor_sdk::{
     cedra_protos::transaction::v1::{DeleteResource, WriteResource, WriteTableItem},
+    postgres::utils::database::{DbContext, DbPoolConnection},
     utils::convert::standardize_address,
 };
 use bigdecimal::BigDecimal;
 use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
 use field_count::FieldCount;
 use parquet_derive::ParquetRecordWriter;
 use serde::{Deserialize, Serialize};
@@ -72,17 +75,73 @@ pub struct CurrentTokenDataV2 {
     // Deprecated, but still here for backwards compatibility
     pub decimals: Option<i64>,
     pub is_deleted_v2: Option<bool>,
+    /// Royalty in basis points, of the token or else of its collection. Kept when a v2 token is
+    /// written without either royalty.
+    pub royalty_bps: Option<i64>,
+}
+
+/// Object metadata of a token v2 as saved with its current token data, used when the token's
+/// ObjectCore wasn't written in the same batch
+#[derive(Clone, Debug, Queryable)]
+pub struct CurrentTokenDataV2ObjectMetadataQuery {
+    pub token_name: String,
+    pub token_properties: serde_json::Value,
+    pub is_fungible_v2: Option<bool>,
+}
+
+impl CurrentTokenDataV2ObjectMetadataQuery {
+    /// Retrying a few times since the token could've been written in a separate thread.
+    pub async fn get_by_token_data_id(
+        conn: &mut DbPoolConnection<'_>,
+        token_data_id: &str,
+        query_retries: u32,
+        query_retry_delay_ms: u64,
+    ) -> anyhow::Result<Self> {
+        let mut tried = 0;
+        while tried < query_retries {
+            tried += 1;
+            match Self::get_by_token_data_id_impl(conn, token_data_id).await {
+                Ok(inner) => return Ok(inner),
+                Err(_) => {
+                    if tried < query_retries {
+                        tokio::time::sleep(std::time::Duration::from_millis(query_retry_delay_ms))
+                            .await;
+                    }
+                },
+            }
+        }
+        Err(anyhow::anyhow!(
+            "Failed to get token data by token data id: {}",
+            token_data_id
+        ))
+    }
+
+    async fn get_by_token_data_id_impl(
+        conn: &mut DbPoolConnection<'_>,
+        token_data_id: &str,
+    ) -> diesel::QueryResult<Self> {
+        current_token_datas_v2::table
+            .filter(current_token_datas_v2::token_data_id.eq(token_data_id))
+            .select((
+                current_token_datas_v2::token_name,
+                current_token_datas_v2::token_properties,
+                current_token_datas_v2::is_fungible_v2,
+            ))
+            .first::<Self>(conn)
+            .await
+    }
 }
 
 impl TokenDataV2 {
     // TODO: remove the useless_asref lint when new clippy nighly is released.
     #[allow(clippy::useless_asref)]
-    pub fn get_v2_from_write_resource(
+    pub async fn get_v2_from_write_resource(
         write_resource: &WriteResource,
         txn_version: i64,
         write_set_change_index: i64,
         txn_timestamp: chrono::NaiveDateTime,
         object_metadatas: &ObjectAggregatedDataMapping,
+        db_context: &mut Option<DbContext<'_>>,
     ) -> anyhow::Result<Option<(Self, CurrentTokenDataV2)>> {
         if let Some(inner) = &TokenV2::from_write_resource(write_resource)? {
             let token_data_id = standardize_address(&write_resource.address.to_string());
@@ -90,6 +149,7 @@ impl TokenDataV2 {
             let is_fungible_v2;
             // Get token properties from 0x4::property_map::PropertyMap
             let mut token_properties = serde_json::Value::Null;
+            let mut royalty_bps = None;
             if let Some(object_metadata) = object_metadatas.get(&token_data_id) {
                 let fungible_asset_metadata = object_metadata.fungible_asset_metadata.as_ref();
                 if fungible_asset_metadata.is_some() {
@@ -106,9 +166,59 @@ impl TokenDataV2 {
                 if let Some(token_identifier) = object_metadata.token_identifier.as_ref() {
                     token_name = token_identifier.get_name_trunc();
                 }
+                // Tokens without a royalty of their own pay their collection's, which is only
+                // known if the collection was written in the same transaction
+                royalty_bps = object_metadata
+                    .royalty
+                    .as_ref()
+                    .or_else(|| {
+                        object_metadatas
+                            .get(&inner.get_collection_address())
+                            .and_then(|collection| collection.royalty.as_ref())
+                    })
+                    .and_then(Royalty::get_bps);
             } else {
-                // ObjectCore should not be missing, returning from entire function early
-                return Ok(None);
+                // ObjectCore wasn't written in this batch, so the object was created in an earlier
+                // one. Fall back to what we saved for the token then.
+                let object_metadata = match db_context {
+                    None => {
+                        tracing::debug!(
+                            transaction_version = txn_version,
+                            lookup_key = &token_data_id,
+                            "Avoiding db lookup for Parquet."
+                        );
+                        None
+                    },
+                    Some(db_context) => {
+                        CurrentTokenDataV2ObjectMetadataQuery::get_by_token_data_id(
+                            &mut db_context.conn,
+                            &token_data_id,
+                            db_context.query_retries,
+                            db_context.query_retry_delay_ms,
+                        )
+                        .await
+                        .ok()
+                    },
+                };
+                let Some(object_metadata) = object_metadata else {
+                    if let Some(suppressed) =
+                        throttle_log("missing_token_object_metadata", &token_data_id)
+                    {
+                        error!(
+                            transaction_version = txn_version,
+                            lookup_key = &token_data_id,
+                            suppressed = suppressed,
+                            "Failed to get object metadata for token v2. You probably should backfill db."
+                        );
+                    }
+                    return Ok(None);
+                };
+                is_fungible_v2 = object_metadata.is_fungible_v2;
+                token_properties = object_metadata.token_properties;
+                // Names of tokens with TokenIdentifiers aren't stored in the token itself
+                if token_name.is_empty() {
+                    token_name = object_metadata.token_name;
+                }
             }
 
             let collection_id = inner.get_collection_address();
@@ -149,6 +259,7 @@ impl TokenDataV2 {
                     last_transaction_timestamp: txn_timestamp,
                     decimals: None,
                     is_deleted_v2: Some(false),
+                    royalty_bps,
                 },
             )))
         } else {
@@ -182,6 +293,7 @@ impl TokenDataV2 {
                 last_transaction_timestamp: txn_timestamp,
                 decimals: None,
                 is_deleted_v2: Some(true),
+                royalty_bps: None,
             }))
         } else {
             Ok(None)
@@ -214,6 +326,7 @@ impl TokenDataV2 {
                 last_transaction_timestamp: txn_timestamp,
                 decimals: None,
                 is_deleted_v2: Some(true),
+                royalty_bps: None,
             }))
         } else {
             Ok(None)
@@ -289,6 +402,7 @@ impl TokenDataV2 {
                         last_transaction_timestamp: txn_timestamp,
                         decimals: None,
                         is_deleted_v2: None,
+                        royalty_bps: token_data.royalty.get_bps(),
                     },
                 )));
             } else {
@@ -444,6 +558,7 @@ pub struct PostgresCurrentTokenDataV2 {
     // Deprecated, but still here for backwards compatibility
     pub decimals: Option<i64>,
     pub is_deleted_v2: Option<bool>,
+    pub royalty_bps: Option<i64>,
 }
 
 impl From<CurrentTokenDataV2> for PostgresCurrentTokenDataV2 {
@@ -464,6 +579,19 @@ impl From<CurrentTokenDataV2> for PostgresCurrentTokenDataV2 {
             last_transaction_timestamp: raw_item.last_transaction_timestamp,
             decimals: raw_item.decimals,
             is_deleted_v2: raw_item.is_deleted_v2,
+            royalty_bps: raw_item.royalty_bps,
         }
     }
 }
+
+impl CurrentTableRow for PostgresCurrentTokenDataV2 {
+    type PrimaryKey = String;
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        self.token_data_id.clone()
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use super::v2_token_activities::PostgresTokenActivityV2;
+use crate::schema::token_mints_detail;
+use ahash::AHashMap;
+use bigdecimal::BigDecimal;
+use cedra_indexer_processor_sdk::{
+    cedra_indexer_transaction_stream::utils::time::parse_timestamp,
+    cedra_protos::transaction::v1::{transaction::TxnData, Transaction},
+    utils::convert::standardize_address,
+};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::str::FromStr;
+use tracing::warn;
+
+/// Activity types `token_activities_v2` records mints as
+const MINT_ACTIVITY_TYPES: [&str; 3] = [
+    "0x4::collection::MintEvent",
+    "0x3::token::MintTokenEvent",
+    "0x3::token::Mint",
+];
+
+/// A launchpad or minting module whose events say which stage a token was minted in and what
+/// was paid for it. Fields are dotted paths into the event data.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct MintModuleConfig {
+    /// Type of the module's mint event, e.g. `0xcafe::launchpad::MintEvent`
+    pub event_type: String,
+    /// Address of the minted token. Without it, events are paired with the transaction's mints in
+    /// order.
+    #[serde(default = "MintModuleConfig::default_token_field")]
+    pub token_field: String,
+    #[serde(default = "MintModuleConfig::default_stage_field")]
+    pub stage_field: String,
+    #[serde(default = "MintModuleConfig::default_paid_amount_field")]
+    pub paid_amount_field: String,
+}
+
+impl MintModuleConfig {
+    pub fn default_token_field() -> String {
+        "token".to_string()
+    }
+
+    pub fn default_stage_field() -> String {
+        "stage".to_string()
+    }
+
+    pub fn default_paid_amount_field() -> String {
+        "paid_amount".to_string()
+    }
+}
+
+/// Mint module events of a batch, keyed by their standardized event type
+pub struct MintModules {
+    modules: AHashMap<String, MintModuleConfig>,
+}
+
+impl MintModules {
+    pub fn new(configs: &[MintModuleConfig]) -> Self {
+        Self {
+            modules: configs
+                .iter()
+                .map(|config| (standardize_type(&config.event_type), config.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+}
+
+/// Stage and price of a mint, from the event of a configured mint module. `activity_event_index`
+/// is the `event_index` of the mint in `token_activities_v2`, when it could be matched.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, event_index))]
+#[diesel(table_name = token_mints_detail)]
+pub struct PostgresTokenMintDetail {
+    pub transaction_version: i64,
+    pub event_index: i64,
+    pub mint_module_address: String,
+    pub event_type: String,
+    pub token_data_id: Option<String>,
+    pub activity_event_index: Option<i64>,
+    pub mint_stage: Option<String>,
+    pub paid_amount: Option<BigDecimal>,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl PostgresTokenMintDetail {
+    pub fn from_transactions(
+        transactions: &[Transaction],
+        token_activities: &[PostgresTokenActivityV2],
+        mint_modules: &MintModules,
+    ) -> Vec<Self> {
+        if mint_modules.is_empty() {
+            return vec![];
+        }
+        let mut mint_activities: AHashMap<i64, Vec<&PostgresTokenActivityV2>> = AHashMap::new();
+        for activity in token_activities {
+            if MINT_ACTIVITY_TYPES.contains(&activity.type_.as_str()) {
+                mint_activities
+                    .entry(activity.transaction_version)
+                    .or_default()
+                    .push(activity);
+            }
+        }
+
+        let mut mints_detail = vec![];
+        for transaction in transactions {
+            let Some(TxnData::User(user_txn)) = transaction.txn_data.as_ref() else {
+                continue;
+            };
+            let txn_version = transaction.version as i64;
+            let mut unmatched_mints = mint_activities.remove(&txn_version).unwrap_or_default();
+            unmatched_mints.sort_by_key(|activity| activity.event_index);
+            for (index, event) in user_txn.events.iter().enumerate() {
+                let event_type = standardize_type(&event.type_str);
+                let Some(config) = mint_modules.modules.get(&event_type) else {
+                    continue;
+                };
+                let data: Value = match serde_json::from_str(&event.data) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        warn!(
+                            transaction_version = txn_version,
+                            event_type = %event_type,
+                            error = ?e,
+                            "Failed to parse mint module event data"
+                        );
+                        continue;
+                    },
+                };
+
+                let token_data_id = get_field(&data, &config.token_field)
+                    .and_then(value_to_string)
+                    .map(|token| standardize_address(&token));
+                let position = match &token_data_id {
+                    Some(token_data_id) => unmatched_mints
+                        .iter()
+                        .position(|activity| &activity.token_data_id == token_data_id),
+                    None => (!unmatched_mints.is_empty()).then_some(0),
+                };
+                let activity = position.map(|position| unmatched_mints.remove(position));
+                let paid_amount = get_field(&data, &config.paid_amount_field)
+                    .and_then(value_to_string)
+                    .and_then(|amount| BigDecimal::from_str(&amount).ok());
+
+                mints_detail.push(Self {
+                    transaction_version: txn_version,
+                    event_index: index as i64,
+                    mint_module_address: event_type
+                        .split("::")
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    token_data_id: token_data_id
+                        .or_else(|| activity.map(|activity| activity.token_data_id.clone())),
+                    activity_event_index: activity.map(|activity| activity.event_index),
+                    mint_stage: get_field(&data, &config.stage_field).and_then(value_to_string),
+                    paid_amount,
+                    transaction_timestamp: parse_timestamp(
+                        transaction.timestamp.as_ref().unwrap(),
+                        txn_version,
+                    )
+                    .naive_utc(),
+                    event_type,
+                });
+            }
+        }
+        mints_detail
+    }
+}
+
+/// Standardizes the address of a `address::module::Struct` type, so configured types match
+/// event types however their addresses are written
+fn standardize_type(type_str: &str) -> String {
+    match type_str.split_once("::") {
+        Some((address, rest)) => format!("{}::{}", standardize_address(address), rest),
+        None => type_str.to_string(),
+    }
+}
+
+fn get_field<'a>(data: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(data, |value, key| value.get(key))
+}
+
+/// Event values as strings. Large integers are already strings in event data, and `Object<T>`
+/// values are unwrapped from `{"inner": address}`.
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(string) => Some(string.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(boolean) => Some(boolean.to_string()),
+        Value::Object(object) => object.get("inner").and_then(value_to_string),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::Zero;
+    use cedra_indexer_processor_sdk::cedra_protos::{
+        transaction::v1::{Event, UserTransaction},
+        util::timestamp::Timestamp,
+    };
+
+    fn mint_activity(event_index: i64, token_data_id: &str) -> PostgresTokenActivityV2 {
+        PostgresTokenActivityV2 {
+            transaction_version: 1,
+            event_index,
+            event_account_address: "0x1".to_string(),
+            token_data_id: standardize_address(token_data_id),
+            property_version_v1: BigDecimal::zero(),
+            type_: "0x4::collection::MintEvent".to_string(),
+            from_address: None,
+            to_address: None,
+            token_amount: BigDecimal::from(1),
+            before_value: None,
+            after_value: None,
+            entry_function_id_str: None,
+            token_standard: "v2".to_string(),
+            is_fungible_v2: None,
+            transaction_timestamp: chrono::NaiveDateTime::default(),
+            usd_value: None,
+            burner_address: None,
+            is_creator_burn: None,
+        }
+    }
+
+    fn event(type_str: &str, data: Value) -> Event {
+        Event {
+            type_str: type_str.to_string(),
+            data: data.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_mints_detail_from_module_events() {
+        let transaction = Transaction {
+            version: 1,
+            timestamp: Some(Timestamp::default()),
+            txn_data: Some(TxnData::User(UserTransaction {
+                events: vec![
+                    event("0x4::collection::MintEvent", serde_json::json!({})),
+                    event(
+                        "0xcafe::launchpad::MintEvent",
+                        serde_json::json!({"stage": "allowlist", "price": "100"}),
+                    ),
+                    event("0x4::collection::MintEvent", serde_json::json!({})),
+                    event(
+                        "0x000000000000000000000000000000000000000000000000000000000000cafe::launchpad::MintEvent",
+                        serde_json::json!({
+                            "token": {"inner": "0xa2"},
+                            "stage": "public",
+                            "price": "250",
+                        }),
+                    ),
+                    event("0xbeef::launchpad::MintEvent", serde_json::json!({})),
+                ],
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let activities = vec![mint_activity(0, "0xa1"), mint_activity(2, "0xa2")];
+        let mint_modules = MintModules::new(&[MintModuleConfig {
+            event_type: "0xcafe::launchpad::MintEvent".to_string(),
+            token_field: MintModuleConfig::default_token_field(),
+            stage_field: MintModuleConfig::default_stage_field(),
+            paid_amount_field: "price".to_string(),
+        }]);
+
+        let mints_detail =
+            PostgresTokenMintDetail::from_transactions(&[transaction], &activities, &mint_modules);
+        let mints_detail: Vec<(
+            i64,
+            Option<String>,
+            Option<i64>,
+            Option<String>,
+            Option<BigDecimal>,
+        )> = mints_detail
+            .into_iter()
+            .map(|detail| {
+                (
+                    detail.event_index,
+                    detail.token_data_id,
+                    detail.activity_event_index,
+                    detail.mint_stage,
+                    detail.paid_amount,
+                )
+            })
+            .collect();
+        assert_eq!(mints_detail, vec![
+            (
+                1,
+                Some(standardize_address("0xa1")),
+                Some(0),
+                Some("allowlist".to_string()),
+                Some(BigDecimal::from(100)),
+            ),
+            (
+                3,
+                Some(standardize_address("0xa2")),
+                Some(2),
+                Some("public".to_string()),
+                Some(BigDecimal::from(250)),
+            ),
+        ]);
+    }
+}
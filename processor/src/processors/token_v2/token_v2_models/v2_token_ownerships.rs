@@ -21,6 +21,7 @@ use crate::{
         },
     },
     schema::current_token_ownerships_v2,
+    utils::{dedup::CurrentTableRow, log_throttle::throttle_log},
 };
 use ahash::AHashMap;
 use allocative_derive::Allocative;
@@ -141,16 +142,8 @@ impl TokenOwnershipV2 {
         let token_data_id = token_data.token_data_id.clone();
         let owner_address = object_core.get_owner_address();
         let storage_id = token_data_id.clone();
-
-        // is_soulbound currently means if an object is completely untransferrable
-        // OR if only admin can transfer. Only the former is true soulbound but
-        // people might already be using it with the latter meaning so let's include both.
-        let is_soulbound = if object_data.untransferable.as_ref().is_some() {
-            true
-        } else {
-            !object_core.allow_ungated_transfer
-        };
-        let non_transferrable_by_owner = !object_core.allow_ungated_transfer;
+        let is_soulbound = object_data.is_soulbound();
+        let non_transferrable_by_owner = object_data.non_transferrable_by_owner();
 
         ownerships.push(Self {
             transaction_version: token_data.transaction_version,
@@ -215,7 +208,7 @@ impl TokenOwnershipV2 {
                 token_standard: TokenStandard::V2.to_string(),
                 is_fungible_v2: None,
                 transaction_timestamp: token_data.transaction_timestamp,
-                non_transferrable_by_owner: Some(is_soulbound),
+                non_transferrable_by_owner: Some(non_transferrable_by_owner),
             });
             current_ownerships.insert(
                 (
@@ -239,7 +232,7 @@ impl TokenOwnershipV2 {
                     is_fungible_v2: None,
                     last_transaction_version: token_data.transaction_version,
                     last_transaction_timestamp: token_data.transaction_timestamp,
-                    non_transferrable_by_owner: Some(is_soulbound),
+                    non_transferrable_by_owner: Some(non_transferrable_by_owner),
                 },
             );
         }
@@ -267,19 +260,13 @@ impl TokenOwnershipV2 {
                 let owner_address = object_core.get_owner_address();
                 let storage_id = token_data_id.clone();
 
-                // is_soulbound currently means if an object is completely untransferrable
-                // OR if only admin can transfer. Only the former is true soulbound but
-                // people might already be using it with the latter meaning so let's include both.
-                let is_soulbound = if object_metadatas
+                // Same flags as ObjectAggregatedData::is_soulbound, but the object core is read
+                // from the resource being written rather than from the aggregated data
+                let is_untransferable = object_metadatas
                     .get(&token_data_id)
-                    .map(|obj| obj.untransferable.as_ref())
-                    .is_some()
-                {
-                    true
-                } else {
-                    !object_core.allow_ungated_transfer
-                };
+                    .is_some_and(|obj| obj.untransferable.is_some());
                 let non_transferrable_by_owner = !object_core.allow_ungated_transfer;
+                let is_soulbound = is_untransferable || non_transferrable_by_owner;
 
                 return Ok(Some((
                     Self {
@@ -395,11 +382,17 @@ impl TokenOwnershipV2 {
                                 {
                                     Ok(nft) => nft.owner_address.clone(),
                                     Err(_) => {
-                                        tracing::error!(
-                                    transaction_version = txn_version,
-                                    lookup_key = &token_address,
-                                    "Failed to find current_token_ownership_v2 for burned token. You probably should backfill db."
-                                );
+                                        if let Some(suppressed) = throttle_log(
+                                            "missing_burned_token_ownership",
+                                            &token_address,
+                                        ) {
+                                            tracing::error!(
+                                                transaction_version = txn_version,
+                                                lookup_key = &token_address,
+                                                suppressed = suppressed,
+                                                "Failed to find current_token_ownership_v2 for burned token. You probably should backfill db."
+                                            );
+                                        }
                                         DEFAULT_OWNER_ADDRESS.to_string()
                                     },
                                 }
@@ -823,3 +816,20 @@ impl From<CurrentTokenOwnershipV2> for PostgresCurrentTokenOwnershipV2 {
         }
     }
 }
+
+impl CurrentTableRow for PostgresCurrentTokenOwnershipV2 {
+    type PrimaryKey = (String, BigDecimal, String, String);
+
+    fn primary_key(&self) -> Self::PrimaryKey {
+        (
+            self.token_data_id.clone(),
+            self.property_version_v1.clone(),
+            self.owner_address.clone(),
+            self.storage_id.clone(),
+        )
+    }
+
+    fn last_transaction_version(&self) -> i64 {
+        self.last_transaction_version
+    }
+}
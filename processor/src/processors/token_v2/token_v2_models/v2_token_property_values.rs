@@ -0,0 +1,188 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use super::{v2_token_datas::CurrentTokenDataV2, v2_token_utils::PropertyMapModel};
+use crate::{db::resources::FromWriteResource, schema::token_property_values};
+use ahash::AHashMap;
+use cedra_indexer_processor_sdk::{
+    cedra_protos::transaction::v1::{WriteResource, WriteTableItem},
+    utils::convert::{standardize_address, truncate_str},
+};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+pub const PROPERTY_KEY_LENGTH: usize = 128;
+pub const PROPERTY_TYPE_LENGTH: usize = 128;
+pub const PROPERTY_VALUE_LENGTH: usize = 512;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TokenPropertyValue {
+    pub property_key: String,
+    pub property_type: Option<String>,
+    pub property_value: String,
+}
+
+/// Every property of a token as of `last_transaction_version`. Written as a whole, replacing the
+/// token's stored properties, so keys removed from the property map are removed from
+/// `token_property_values` too.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Serialize)]
+#[diesel(primary_key(token_data_id))]
+#[diesel(table_name = token_property_values)]
+pub struct TokenPropertyValues {
+    pub token_data_id: String,
+    pub properties: Vec<TokenPropertyValue>,
+    pub last_transaction_version: i64,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+impl TokenPropertyValues {
+    /// Properties of a token v2 from its `0x4::property_map::PropertyMap`. Property maps are
+    /// written on their own when properties are mutated, so this doesn't wait for the token.
+    pub fn from_v2_property_map(
+        write_resource: &WriteResource,
+        txn_version: i64,
+        txn_timestamp: chrono::NaiveDateTime,
+    ) -> anyhow::Result<Option<Self>> {
+        let Some(property_map) = PropertyMapModel::from_write_resource(write_resource)? else {
+            return Ok(None);
+        };
+        let raw: Value = serde_json::from_str(write_resource.data.as_str())?;
+        let property_types = property_types(&raw["inner"]["data"], v2_property_type_name);
+        Ok(Some(Self {
+            token_data_id: standardize_address(&write_resource.address.to_string()),
+            properties: properties(&property_map.inner, &property_types),
+            last_transaction_version: txn_version,
+            last_transaction_timestamp: txn_timestamp,
+        }))
+    }
+
+    /// Default properties of a token v1, from the `0x3::token::TokenData` table item the current
+    /// token data was parsed from
+    pub fn from_v1_token_data(
+        table_item: &WriteTableItem,
+        current_token_data: &CurrentTokenDataV2,
+    ) -> anyhow::Result<Self> {
+        let raw: Value = serde_json::from_str(table_item.data.as_ref().unwrap().value.as_str())?;
+        let property_types = property_types(&raw["default_properties"]["map"]["data"], |typ| {
+            typ.as_str().map(str::to_string)
+        });
+        Ok(Self {
+            token_data_id: current_token_data.token_data_id.clone(),
+            properties: properties(&current_token_data.token_properties, &property_types),
+            last_transaction_version: current_token_data.last_transaction_version,
+            last_transaction_timestamp: current_token_data.last_transaction_timestamp,
+        })
+    }
+}
+
+/// Properties of a decoded property map, like `token_properties`, with their types. Keys that
+/// are the same once truncated are kept once.
+fn properties(
+    property_map: &Value,
+    property_types: &AHashMap<String, String>,
+) -> Vec<TokenPropertyValue> {
+    let Some(property_map) = property_map.as_object() else {
+        return vec![];
+    };
+    let properties: BTreeMap<String, TokenPropertyValue> = property_map
+        .iter()
+        .map(|(key, value)| {
+            let property_key = truncate_str(key, PROPERTY_KEY_LENGTH);
+            let property_value = match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            let property = TokenPropertyValue {
+                property_key: property_key.clone(),
+                property_type: property_types
+                    .get(key)
+                    .map(|typ| truncate_str(typ, PROPERTY_TYPE_LENGTH)),
+                property_value: truncate_str(&property_value, PROPERTY_VALUE_LENGTH),
+            };
+            (property_key, property)
+        })
+        .collect();
+    properties.into_values().collect()
+}
+
+/// Types of the `{key, value: {type, value}}` entries of a property map before it's decoded, by
+/// key
+fn property_types(
+    entries: &Value,
+    type_name: impl Fn(&Value) -> Option<String>,
+) -> AHashMap<String, String> {
+    entries
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let key = entry["key"].as_str()?;
+            Some((key.to_string(), type_name(&entry["value"]["type"])?))
+        })
+        .collect()
+}
+
+/// Move type of a `0x4::property_map` value, which is stored as a type code
+fn v2_property_type_name(typ: &Value) -> Option<String> {
+    let code = match typ {
+        Value::Number(code) => code.as_u64()?,
+        Value::String(code) => code.parse().ok()?,
+        _ => return None,
+    };
+    let name = match code {
+        0 => "bool",
+        1 => "u8",
+        2 => "u16",
+        3 => "u32",
+        4 => "u64",
+        5 => "u128",
+        6 => "u256",
+        7 => "address",
+        8 => "vector<u8>",
+        9 => "0x1::string::String",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_properties_with_types() {
+        let raw = json!([
+            {"key": "Rank", "value": {"type": 9, "value": "0x0642726f6e7a65"}},
+            {"key": "Level", "value": {"type": "4", "value": "0x0300000000000000"}},
+        ]);
+        let property_types = property_types(&raw, v2_property_type_name);
+        let properties = properties(
+            &json!({"Rank": "Bronze", "Level": "3", "Unknown": true}),
+            &property_types,
+        );
+        assert_eq!(properties, vec![
+            TokenPropertyValue {
+                property_key: "Level".to_string(),
+                property_type: Some("u64".to_string()),
+                property_value: "3".to_string(),
+            },
+            TokenPropertyValue {
+                property_key: "Rank".to_string(),
+                property_type: Some("0x1::string::String".to_string()),
+                property_value: "Bronze".to_string(),
+            },
+            TokenPropertyValue {
+                property_key: "Unknown".to_string(),
+                property_type: None,
+                property_value: "true".to_string(),
+            },
+        ]);
+        assert!(properties(&Value::Null, &property_types).is_empty());
+    }
+}
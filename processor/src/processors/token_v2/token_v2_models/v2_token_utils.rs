@@ -6,7 +6,7 @@
 
 use crate::processors::{
     objects::v2_object_utils::CurrentObjectPK,
-    token_v2::token_models::token_utils::{NAME_LENGTH, URI_LENGTH},
+    token_v2::token_models::token_utils::{royalty_bps, NAME_LENGTH, URI_LENGTH},
 };
 use ahash::{AHashMap, AHashSet};
 use anyhow::{Context, Result};
@@ -203,6 +203,30 @@ impl TryFrom<&WriteResource> for ConcurrentSupply {
     }
 }
 
+/// Royalty of a token, or of a collection for the tokens without their own
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Royalty {
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub numerator: BigDecimal,
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub denominator: BigDecimal,
+    payee_address: String,
+}
+
+impl TryFrom<&WriteResource> for Royalty {
+    type Error = anyhow::Error;
+
+    fn try_from(write_resource: &WriteResource) -> anyhow::Result<Self> {
+        serde_json::from_str(write_resource.data.as_str()).map_err(anyhow::Error::msg)
+    }
+}
+
+impl Royalty {
+    pub fn get_bps(&self) -> Option<i64> {
+        royalty_bps(&self.numerator, &self.denominator)
+    }
+}
+
 /* Section on Events */
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MintEvent {
@@ -6,28 +6,35 @@ use crate::{
         },
         processor_config::{DefaultProcessorConfig, ProcessorConfig},
     },
+    db::{
+        connection_pool::new_postgres_pool, partitioning::PartitionStep,
+        schema_drift::run_migrations_and_check_drift,
+    },
     processors::{
         processor_status_saver::{
             get_end_version, get_starting_version, PostgresProcessorStatusSaver,
         },
-        token_v2::{token_v2_extractor::TokenV2Extractor, token_v2_storer::TokenV2Storer},
+        token_v2::{
+            token_v2_extractor::TokenV2Extractor,
+            token_v2_models::v2_token_mints_detail::{MintModuleConfig, MintModules},
+            token_v2_storer::TokenV2Storer,
+        },
+    },
+    utils::{
+        logging::BatchSpanStep,
+        price_oracle::{PriceOracle, PriceOracleConfig},
+        supervisor::supervise_step_tasks,
+        table_flags::TableFlags,
+        table_writers::PendingWritesStep,
+        transaction_source::{check_chain_id, TransactionSourceStep},
     },
-    utils::table_flags::TableFlags,
-    MIGRATIONS,
 };
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    cedra_indexer_transaction_stream::TransactionStreamConfig,
     builder::ProcessorBuilder,
-    common_steps::{
-        TransactionStreamStep, VersionTrackerStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
-    },
-    postgres::utils::{
-        checkpoint::PostgresChainIdChecker,
-        database::{new_db_pool, run_migrations, ArcDbPool},
-    },
+    common_steps::{VersionTrackerStep, DEFAULT_UPDATE_PROCESSOR_STATUS_SECS},
+    postgres::utils::database::ArcDbPool,
     traits::{processor_trait::ProcessorTrait, IntoRunnableStep},
-    utils::chain_id_check::check_or_update_chain_id,
 };
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
@@ -41,6 +48,12 @@ pub struct TokenV2ProcessorConfig {
     pub query_retries: u32,
     #[serde(default = "TokenV2ProcessorConfig::default_query_retry_delay_ms")]
     pub query_retry_delay_ms: u64,
+    // Optional price source used to populate token_activities_v2.usd_value
+    #[serde(default)]
+    pub price_oracle: Option<PriceOracleConfig>,
+    // Mint module events used to populate token_mints_detail
+    #[serde(default)]
+    pub mint_modules: Vec<MintModuleConfig>,
 }
 
 impl TokenV2ProcessorConfig {
@@ -61,12 +74,7 @@ impl TokenV2Processor {
     pub async fn new(config: IndexerProcessorConfig) -> Result<Self> {
         match config.db_config {
             DbConfig::PostgresConfig(ref postgres_config) => {
-                let conn_pool = new_db_pool(
-                    &postgres_config.connection_string,
-                    Some(postgres_config.db_pool_size),
-                )
-                .await
-                .map_err(|e| {
+                let conn_pool = new_postgres_pool(postgres_config).await.map_err(|e| {
                     anyhow::anyhow!(
                         "Failed to create connection pool for PostgresConfig: {:?}",
                         e
@@ -95,12 +103,7 @@ impl ProcessorTrait for TokenV2Processor {
     async fn run_processor(&self) -> Result<()> {
         //  Run migrations
         if let DbConfig::PostgresConfig(ref postgres_config) = self.config.db_config {
-            run_migrations(
-                postgres_config.connection_string.clone(),
-                self.db_pool.clone(),
-                MIGRATIONS,
-            )
-            .await;
+            run_migrations_and_check_drift(postgres_config, self.db_pool.clone()).await?;
         }
 
         // Merge the starting version from config and the latest processed version from the DB
@@ -110,11 +113,7 @@ impl ProcessorTrait for TokenV2Processor {
         );
 
         // Check and update the ledger chain id to ensure we're indexing the correct chain
-        check_or_update_chain_id(
-            &self.config.transaction_stream_config,
-            &PostgresChainIdChecker::new(self.db_pool.clone()),
-        )
-        .await?;
+        check_chain_id(&self.config, self.db_pool.clone()).await?;
 
         let processor_config = match &self.config.processor_config {
             ProcessorConfig::TokenV2Processor(processor_config) => processor_config,
@@ -123,20 +122,31 @@ impl ProcessorTrait for TokenV2Processor {
         let channel_size = processor_config.default_config.channel_size;
 
         // Define processor steps
-        let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
-            starting_version,
-            request_ending_version: ending_version,
-            ..self.config.transaction_stream_config.clone()
-        })
-        .await?;
+        let transaction_stream =
+            TransactionSourceStep::new(&self.config, starting_version, ending_version).await?;
+        let price_oracle = processor_config
+            .price_oracle
+            .as_ref()
+            .map(|price_oracle_config| PriceOracle::new(price_oracle_config, self.db_pool.clone()))
+            .transpose()?;
+        let opt_in_tables = TableFlags::from_set(&processor_config.default_config.tables_to_write);
         let token_v2_extractor = TokenV2Extractor::new(
             processor_config.query_retries,
             processor_config.query_retry_delay_ms,
             self.db_pool.clone(),
+            price_oracle,
+            MintModules::new(&processor_config.mint_modules),
+            opt_in_tables,
         );
-        let opt_in_tables = TableFlags::from_set(&processor_config.default_config.tables_to_write);
+        let partition_step = PartitionStep::new(&self.config, self.db_pool.clone(), &[
+            "token_activities_v2",
+            "token_mints_detail",
+            "nft_transfers",
+        ])
+        .await?;
         let token_v2_storer = TokenV2Storer::new(
             self.db_pool.clone(),
+            self.config.table_names(),
             processor_config.clone(),
             opt_in_tables,
         );
@@ -145,13 +155,22 @@ impl ProcessorTrait for TokenV2Processor {
             DEFAULT_UPDATE_PROCESSOR_STATUS_SECS,
         );
         // Connect processor steps together
-        let (_, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
+        let (processor_builder, buffer_receiver) = ProcessorBuilder::new_with_inputless_first_step(
             transaction_stream.into_runnable_step(),
         )
-        .connect_to(token_v2_extractor.into_runnable_step(), channel_size)
-        .connect_to(token_v2_storer.into_runnable_step(), channel_size)
+        .connect_to(
+            BatchSpanStep::new(token_v2_extractor, self.name()).into_runnable_step(),
+            channel_size,
+        )
+        .connect_to(partition_step.into_runnable_step(), channel_size)
+        .connect_to(
+            BatchSpanStep::new(token_v2_storer, self.name()).into_runnable_step(),
+            channel_size,
+        )
+        .connect_to(PendingWritesStep.into_runnable_step(), channel_size)
         .connect_to(version_tracker.into_runnable_step(), channel_size)
         .end_and_return_output_receiver(channel_size);
+        supervise_step_tasks(processor_builder.join_handles);
 
         loop {
             match buffer_receiver.recv().await {
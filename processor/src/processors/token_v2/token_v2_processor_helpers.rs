@@ -23,6 +23,7 @@ use crate::{
                     CurrentTokenOwnershipV2, CurrentTokenOwnershipV2PK, NFTOwnershipV2,
                     TokenOwnershipV2,
                 },
+                v2_token_property_values::TokenPropertyValues,
                 v2_token_utils::{
                     Burn, BurnEvent, Mint, MintEvent, TokenV2Burned, TokenV2Minted, TransferEvent,
                 },
@@ -39,24 +40,34 @@ use cedra_indexer_processor_sdk::{
     utils::{convert::standardize_address, extract::get_entry_function_from_user_request},
 };
 
+/// Token v1 and v2 rows parsed from a batch of transactions, by table. Fields may be added as
+/// tables are, so the struct can't be built or destructured outside this crate.
+#[derive(Default)]
+#[non_exhaustive]
+pub struct TokenV2Batch {
+    pub collections_v2: Vec<CollectionV2>,
+    pub token_datas_v2: Vec<TokenDataV2>,
+    pub token_ownerships_v2: Vec<TokenOwnershipV2>,
+    pub current_collections_v2: Vec<CurrentCollectionV2>,
+    pub current_token_datas_v2: Vec<CurrentTokenDataV2>,
+    pub current_deleted_token_datas_v2: Vec<CurrentTokenDataV2>,
+    pub current_token_ownerships_v2: Vec<CurrentTokenOwnershipV2>,
+    pub current_deleted_token_ownerships_v2: Vec<CurrentTokenOwnershipV2>,
+    pub token_activities_v2: Vec<TokenActivityV2>,
+    pub current_token_v2_metadata: Vec<CurrentTokenV2Metadata>,
+    pub current_token_royalties_v1: Vec<CurrentTokenRoyaltyV1>,
+    pub current_token_pending_claims: Vec<CurrentTokenPendingClaim>,
+    pub token_property_values: Vec<TokenPropertyValues>,
+}
+
+/// Parses the token v1 and v2 tables out of `transactions`. Current tables are deduplicated and
+/// sorted by primary key. `db_context` looks up what earlier batches wrote when the batch doesn't
+/// have it, like the owner of a burned token; parquet processors go without.
 pub async fn parse_v2_token(
     transactions: &[Transaction],
     table_handle_to_owner: &TableHandleToOwner,
     db_context: &mut Option<DbContext<'_>>,
-) -> (
-    Vec<CollectionV2>,
-    Vec<TokenDataV2>,
-    Vec<TokenOwnershipV2>,
-    Vec<CurrentCollectionV2>,
-    Vec<CurrentTokenDataV2>,
-    Vec<CurrentTokenDataV2>,
-    Vec<CurrentTokenOwnershipV2>,
-    Vec<CurrentTokenOwnershipV2>, // deleted token ownerships
-    Vec<TokenActivityV2>,
-    Vec<CurrentTokenV2Metadata>,
-    Vec<CurrentTokenRoyaltyV1>,
-    Vec<CurrentTokenPendingClaim>,
-) {
+) -> TokenV2Batch {
     // Token V2 and V1 combined
     let mut collections_v2 = vec![];
     let mut token_datas_v2 = vec![];
@@ -85,6 +96,9 @@ pub async fn parse_v2_token(
         AHashMap::new();
     let mut current_token_royalties_v1: AHashMap<CurrentTokenDataV2PK, CurrentTokenRoyaltyV1> =
         AHashMap::new();
+    // Latest properties of each token, the earlier ones are replaced as a whole
+    let mut token_property_values: AHashMap<CurrentTokenDataV2PK, TokenPropertyValues> =
+        AHashMap::new();
     // migrating this from v1 token model as we don't have any replacement table for this
     let mut all_current_token_claims: AHashMap<
         CurrentTokenPendingClaimPK,
@@ -118,6 +132,7 @@ pub async fn parse_v2_token(
                 .as_ref()
                 .expect("Sends is not present in user txn");
             let entry_function_id_str = get_entry_function_from_user_request(user_request);
+            let sender_address = standardize_address(&user_request.sender);
 
             // Get burn events for token v2 by object
             let mut tokens_burned: TokenV2Burned = AHashMap::new();
@@ -177,6 +192,9 @@ pub async fn parse_v2_token(
                                 V2TokenResource::Untransferable(untransferable) => {
                                     aggregated_data.untransferable = Some(untransferable);
                                 },
+                                V2TokenResource::Royalty(royalty) => {
+                                    aggregated_data.royalty = Some(royalty);
+                                },
                                 _ => {},
                             }
                         }
@@ -236,6 +254,7 @@ pub async fn parse_v2_token(
                     txn_timestamp,
                     index as i64,
                     &entry_function_id_str,
+                    &sender_address,
                     &mut tokens_claimed,
                 )
                 .unwrap()
@@ -249,6 +268,7 @@ pub async fn parse_v2_token(
                     txn_timestamp,
                     index as i64,
                     &entry_function_id_str,
+                    &sender_address,
                     &token_v2_metadata_helper,
                 )
                 .await
@@ -291,6 +311,14 @@ pub async fn parse_v2_token(
                             )
                             .unwrap()
                         {
+                            token_property_values.insert(
+                                current_token_data.token_data_id.clone(),
+                                TokenPropertyValues::from_v1_token_data(
+                                    table_item,
+                                    &current_token_data,
+                                )
+                                .unwrap(),
+                            );
                             token_datas_v2.push(token_data);
                             current_token_datas_v2.insert(
                                 current_token_data.token_data_id.clone(),
@@ -415,6 +443,16 @@ pub async fn parse_v2_token(
                         }
                     },
                     Change::WriteResource(resource) => {
+                        if let Some(property_values) = TokenPropertyValues::from_v2_property_map(
+                            resource,
+                            txn_version,
+                            txn_timestamp,
+                        )
+                        .unwrap()
+                        {
+                            token_property_values
+                                .insert(property_values.token_data_id.clone(), property_values);
+                        }
                         if let Some((collection, current_collection)) =
                             CollectionV2::get_v2_from_write_resource(
                                 resource,
@@ -438,7 +476,9 @@ pub async fn parse_v2_token(
                                 wsc_index,
                                 txn_timestamp,
                                 &token_v2_metadata_helper,
+                                db_context,
                             )
+                            .await
                             .unwrap()
                         {
                             // Add NFT ownership
@@ -626,6 +666,9 @@ pub async fn parse_v2_token(
     let mut all_current_token_claims = all_current_token_claims
         .into_values()
         .collect::<Vec<CurrentTokenPendingClaim>>();
+    let mut token_property_values = token_property_values
+        .into_values()
+        .collect::<Vec<TokenPropertyValues>>();
     // Sort by PK
     current_collections_v2.sort_by(|a, b| a.collection_id.cmp(&b.collection_id));
     current_deleted_token_datas_v2.sort_by(|a, b| a.token_data_id.cmp(&b.token_data_id));
@@ -635,8 +678,9 @@ pub async fn parse_v2_token(
     current_deleted_token_ownerships_v2.sort();
     current_token_royalties_v1.sort();
     all_current_token_claims.sort();
+    token_property_values.sort_by(|a, b| a.token_data_id.cmp(&b.token_data_id));
 
-    (
+    TokenV2Batch {
         collections_v2,
         token_datas_v2,
         token_ownerships_v2,
@@ -648,6 +692,7 @@ pub async fn parse_v2_token(
         token_activities_v2,
         current_token_v2_metadata,
         current_token_royalties_v1,
-        all_current_token_claims,
-    )
+        current_token_pending_claims: all_current_token_claims,
+        token_property_values,
+    }
 }
@@ -8,17 +8,24 @@ use crate::{
             token_royalty::PostgresCurrentTokenRoyaltyV1,
         },
         token_v2_models::{
-            v2_collections::CurrentCollectionV2, v2_token_activities::PostgresTokenActivityV2,
+            v2_collection_volumes::CollectionVolumes, v2_collections::CurrentCollectionV2,
+            v2_nft_transfers::PostgresNftTransfer, v2_token_activities::PostgresTokenActivityV2,
             v2_token_datas::PostgresCurrentTokenDataV2,
+            v2_token_mints_detail::PostgresTokenMintDetail,
             v2_token_ownerships::PostgresCurrentTokenOwnershipV2,
+            v2_token_property_values::TokenPropertyValues,
         },
     },
-    schema,
+    schema, version_guard,
 };
+use bigdecimal::{BigDecimal, Zero};
 use diesel::{
+    dsl::sql,
     pg::{upsert::excluded, Pg},
     query_builder::QueryFragment,
     query_dsl::methods::FilterDsl,
+    sql_query,
+    sql_types::{Array, BigInt, Jsonb, Nullable, Numeric, Text, Timestamp},
     ExpressionMethods,
 };
 
@@ -48,7 +55,7 @@ pub fn insert_current_collections_v2_query(
             last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
             inserted_at.eq(excluded(inserted_at)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
 }
 
 pub fn insert_current_token_datas_v2_query(
@@ -75,10 +82,15 @@ pub fn insert_current_token_datas_v2_query(
             last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
             inserted_at.eq(excluded(inserted_at)),
             decimals.eq(excluded(decimals)),
+            // v2 tokens are written without a royalty when their collection's isn't known. The
+            // table name is quoted so overrides apply.
+            royalty_bps.eq(sql::<Nullable<BigInt>>(
+                r#"COALESCE(EXCLUDED.royalty_bps, "current_token_datas_v2".royalty_bps)"#,
+            )),
             // Intentionally not including is_deleted because it should always be true in this part
             // and doesn't need to override
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
 }
 
 pub fn insert_current_deleted_token_datas_v2_query(
@@ -96,7 +108,256 @@ pub fn insert_current_deleted_token_datas_v2_query(
             inserted_at.eq(excluded(inserted_at)),
             is_deleted_v2.eq(excluded(is_deleted_v2)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
+}
+
+/// Copies the creator and mutability of each collection, as it's stored, onto its tokens. Run
+/// once the batch's collections and token datas are written, it also catches up tokens that were
+/// written before their collection. Table names are quoted so overrides apply.
+pub fn update_token_datas_collection_fields_query(
+    collection_ids: Vec<String>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    sql_query(
+        r#"UPDATE "current_token_datas_v2" AS token
+        SET creator_address = collection.creator_address,
+            collection_mutable_description = collection.mutable_description,
+            collection_mutable_uri = collection.mutable_uri
+        FROM "current_collections_v2" AS collection
+        WHERE token.collection_id = ANY($1)
+            AND collection.collection_id = token.collection_id
+            AND (
+                token.creator_address IS DISTINCT FROM collection.creator_address
+                OR token.collection_mutable_description IS DISTINCT FROM collection.mutable_description
+                OR token.collection_mutable_uri IS DISTINCT FROM collection.mutable_uri
+            )"#,
+    )
+    .bind::<Array<Text>, _>(collection_ids)
+}
+
+/// Activity types `current_collection_stats_v2` counts as mints
+const COLLECTION_STATS_MINT_TYPES: [&str; 4] = [
+    "0x3::token::MintTokenEvent",
+    "0x3::token::Mint",
+    "0x4::collection::MintEvent",
+    "0x4::collection::Mint",
+];
+/// Activity types `current_collection_stats_v2` counts as burns
+const COLLECTION_STATS_BURN_TYPES: [&str; 4] = [
+    "0x3::token::BurnTokenEvent",
+    "0x3::token::Burn",
+    "0x4::collection::BurnEvent",
+    "0x4::collection::Burn",
+];
+
+/// What a batch changes in the stats of the collections it touches, as columns to bind
+#[derive(Debug, Default)]
+pub struct CollectionStatsChanges {
+    start_version: i64,
+    collection_ids: Vec<String>,
+    collection_versions: Vec<i64>,
+    collection_timestamps: Vec<chrono::NaiveDateTime>,
+    ownership_token_data_ids: Vec<String>,
+    ownership_property_versions: Vec<BigDecimal>,
+    ownership_owner_addresses: Vec<String>,
+    ownership_storage_ids: Vec<String>,
+    ownership_amounts: Vec<BigDecimal>,
+    ownership_versions: Vec<i64>,
+    ownership_timestamps: Vec<chrono::NaiveDateTime>,
+    activity_token_data_ids: Vec<String>,
+    activity_minted: Vec<BigDecimal>,
+    activity_burned: Vec<BigDecimal>,
+    activity_versions: Vec<i64>,
+    activity_timestamps: Vec<chrono::NaiveDateTime>,
+}
+
+impl CollectionStatsChanges {
+    /// `ownerships` holds the latest ownership per primary key, deleted ones included.
+    pub fn new(
+        start_version: u64,
+        collections: &[CurrentCollectionV2],
+        ownerships: &[PostgresCurrentTokenOwnershipV2],
+        activities: &[PostgresTokenActivityV2],
+    ) -> Self {
+        let mut changes = Self {
+            start_version: start_version as i64,
+            ..Default::default()
+        };
+        for collection in collections {
+            changes
+                .collection_ids
+                .push(collection.collection_id.clone());
+            changes
+                .collection_versions
+                .push(collection.last_transaction_version);
+            changes
+                .collection_timestamps
+                .push(collection.last_transaction_timestamp);
+        }
+        for ownership in ownerships {
+            changes
+                .ownership_token_data_ids
+                .push(ownership.token_data_id.clone());
+            changes
+                .ownership_property_versions
+                .push(ownership.property_version_v1.clone());
+            changes
+                .ownership_owner_addresses
+                .push(ownership.owner_address.clone());
+            changes
+                .ownership_storage_ids
+                .push(ownership.storage_id.clone());
+            changes.ownership_amounts.push(ownership.amount.clone());
+            changes
+                .ownership_versions
+                .push(ownership.last_transaction_version);
+            changes
+                .ownership_timestamps
+                .push(ownership.last_transaction_timestamp);
+        }
+        for activity in activities {
+            let (minted, burned) = if COLLECTION_STATS_MINT_TYPES.contains(&activity.type_.as_str())
+            {
+                (activity.token_amount.clone(), BigDecimal::zero())
+            } else if COLLECTION_STATS_BURN_TYPES.contains(&activity.type_.as_str()) {
+                (BigDecimal::zero(), activity.token_amount.clone())
+            } else {
+                continue;
+            };
+            changes
+                .activity_token_data_ids
+                .push(activity.token_data_id.clone());
+            changes.activity_minted.push(minted);
+            changes.activity_burned.push(burned);
+            changes.activity_versions.push(activity.transaction_version);
+            changes
+                .activity_timestamps
+                .push(activity.transaction_timestamp);
+        }
+        changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.collection_ids.is_empty()
+            && self.ownership_token_data_ids.is_empty()
+            && self.activity_token_data_ids.is_empty()
+    }
+}
+
+/// Adds what a batch changes to `current_collection_stats_v2` of the collections it touches:
+/// its mints and burns, the change in supply of its ownerships, and the owners that started or
+/// stopped holding tokens of a collection. Only the touched owners' holdings are read.
+///
+/// The ownerships the batch changes are compared against the ones stored before it, so this
+/// runs in the ownership table's write queue, right before the batch's ownerships are written.
+/// A collection's stats are only changed by batches starting after their
+/// `last_transaction_version`, so a retried batch doesn't add its changes twice. Table names are
+/// quoted so overrides apply.
+pub fn upsert_collection_stats_query(
+    changes: CollectionStatsChanges,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    sql_query(
+        r#"WITH collection_changes AS (
+            SELECT * FROM UNNEST($2::text[], $3::bigint[], $4::timestamp[])
+                AS change(collection_id, version, transaction_timestamp)
+        ), ownership_changes AS (
+            SELECT change.*, token.collection_id, COALESCE(stored.amount, 0) AS stored_amount
+            FROM UNNEST($5::text[], $6::numeric[], $7::text[], $8::text[], $9::numeric[],
+                    $10::bigint[], $11::timestamp[])
+                AS change(token_data_id, property_version_v1, owner_address, storage_id, amount,
+                    version, transaction_timestamp)
+                JOIN "current_token_datas_v2" AS token USING (token_data_id)
+                LEFT JOIN "current_token_ownerships_v2" AS stored
+                    USING (token_data_id, property_version_v1, owner_address, storage_id)
+        ), activity_changes AS (
+            SELECT token.collection_id, change.minted, change.burned, change.version,
+                change.transaction_timestamp
+            FROM UNNEST($12::text[], $13::numeric[], $14::numeric[], $15::bigint[],
+                    $16::timestamp[])
+                AS change(token_data_id, minted, burned, version, transaction_timestamp)
+                JOIN "current_token_datas_v2" AS token USING (token_data_id)
+        ), owner_changes AS (
+            SELECT changed.collection_id, changed.owner_address,
+                EXISTS (
+                    SELECT 1
+                    FROM "current_token_ownerships_v2" AS held
+                        JOIN "current_token_datas_v2" AS token USING (token_data_id)
+                    WHERE held.owner_address = changed.owner_address
+                        AND token.collection_id = changed.collection_id AND held.amount > 0
+                ) AS held_before,
+                BOOL_OR(changed.amount > 0) OR EXISTS (
+                    SELECT 1
+                    FROM "current_token_ownerships_v2" AS held
+                        JOIN "current_token_datas_v2" AS token USING (token_data_id)
+                    WHERE held.owner_address = changed.owner_address
+                        AND token.collection_id = changed.collection_id AND held.amount > 0
+                        AND NOT EXISTS (
+                            SELECT 1 FROM ownership_changes AS change
+                            WHERE change.token_data_id = held.token_data_id
+                                AND change.property_version_v1 = held.property_version_v1
+                                AND change.owner_address = held.owner_address
+                                AND change.storage_id = held.storage_id
+                        )
+                ) AS held_after
+            FROM ownership_changes AS changed
+            GROUP BY changed.collection_id, changed.owner_address
+        ), deltas AS (
+            SELECT collection_id, SUM(supply) AS supply, SUM(minted) AS minted,
+                SUM(burned) AS burned, SUM(owners) AS owners, MAX(version) AS version,
+                MAX(transaction_timestamp) AS transaction_timestamp
+            FROM (
+                SELECT collection_id, 0 AS supply, 0 AS minted, 0 AS burned, 0 AS owners,
+                    version, transaction_timestamp
+                FROM collection_changes
+                UNION ALL
+                SELECT collection_id, amount - stored_amount, 0, 0, 0, version,
+                    transaction_timestamp
+                FROM ownership_changes
+                UNION ALL
+                SELECT collection_id, 0, minted, burned, 0, version, transaction_timestamp
+                FROM activity_changes
+                UNION ALL
+                SELECT collection_id, 0, 0, 0, held_after::int - held_before::int, NULL, NULL
+                FROM owner_changes
+            ) AS changes
+            GROUP BY collection_id
+        )
+        INSERT INTO "current_collection_stats_v2" AS stats (collection_id, creator_address,
+            collection_name, current_supply, total_minted, total_burned, distinct_owners,
+            last_transaction_version, last_transaction_timestamp)
+        SELECT collection.collection_id, collection.creator_address, collection.collection_name,
+            deltas.supply, deltas.minted, deltas.burned, deltas.owners, deltas.version,
+            deltas.transaction_timestamp
+        FROM deltas JOIN "current_collections_v2" AS collection USING (collection_id)
+        ON CONFLICT (collection_id) DO UPDATE
+        SET creator_address = EXCLUDED.creator_address,
+            collection_name = EXCLUDED.collection_name,
+            current_supply = stats.current_supply + EXCLUDED.current_supply,
+            total_minted = stats.total_minted + EXCLUDED.total_minted,
+            total_burned = stats.total_burned + EXCLUDED.total_burned,
+            distinct_owners = stats.distinct_owners + EXCLUDED.distinct_owners,
+            last_transaction_version = GREATEST(stats.last_transaction_version,
+                EXCLUDED.last_transaction_version),
+            last_transaction_timestamp = GREATEST(stats.last_transaction_timestamp,
+                EXCLUDED.last_transaction_timestamp),
+            inserted_at = NOW()
+        WHERE stats.last_transaction_version < $1"#,
+    )
+    .bind::<BigInt, _>(changes.start_version)
+    .bind::<Array<Text>, _>(changes.collection_ids)
+    .bind::<Array<BigInt>, _>(changes.collection_versions)
+    .bind::<Array<Timestamp>, _>(changes.collection_timestamps)
+    .bind::<Array<Text>, _>(changes.ownership_token_data_ids)
+    .bind::<Array<Numeric>, _>(changes.ownership_property_versions)
+    .bind::<Array<Text>, _>(changes.ownership_owner_addresses)
+    .bind::<Array<Text>, _>(changes.ownership_storage_ids)
+    .bind::<Array<Numeric>, _>(changes.ownership_amounts)
+    .bind::<Array<BigInt>, _>(changes.ownership_versions)
+    .bind::<Array<Timestamp>, _>(changes.ownership_timestamps)
+    .bind::<Array<Text>, _>(changes.activity_token_data_ids)
+    .bind::<Array<Numeric>, _>(changes.activity_minted)
+    .bind::<Array<Numeric>, _>(changes.activity_burned)
+    .bind::<Array<BigInt>, _>(changes.activity_versions)
+    .bind::<Array<Timestamp>, _>(changes.activity_timestamps)
 }
 
 pub fn insert_current_token_ownerships_v2_query(
@@ -125,7 +386,7 @@ pub fn insert_current_token_ownerships_v2_query(
             inserted_at.eq(excluded(inserted_at)),
             non_transferrable_by_owner.eq(excluded(non_transferrable_by_owner)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
 }
 
 pub fn insert_current_deleted_token_ownerships_v2_query(
@@ -149,7 +410,7 @@ pub fn insert_current_deleted_token_ownerships_v2_query(
             is_fungible_v2.eq(excluded(is_fungible_v2)),
             inserted_at.eq(excluded(inserted_at)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
 }
 
 pub fn insert_token_activities_v2_query(
@@ -164,6 +425,9 @@ pub fn insert_token_activities_v2_query(
         .set((
             is_fungible_v2.eq(excluded(is_fungible_v2)),
             inserted_at.eq(excluded(inserted_at)),
+            usd_value.eq(excluded(usd_value)),
+            burner_address.eq(excluded(burner_address)),
+            is_creator_burn.eq(excluded(is_creator_burn)),
         ))
 }
 
@@ -183,7 +447,7 @@ pub fn insert_current_token_royalties_v1_query(
             last_transaction_version.eq(excluded(last_transaction_version)),
             last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
 }
 
 pub fn insert_current_token_claims_query(
@@ -211,6 +475,168 @@ pub fn insert_current_token_claims_query(
             inserted_at.eq(excluded(inserted_at)),
             token_data_id.eq(excluded(token_data_id)),
             collection_id.eq(excluded(collection_id)),
+            status.eq(excluded(status)),
+            resolution_transaction_version.eq(excluded(resolution_transaction_version)),
         ))
-        .filter(last_transaction_version.le(excluded(last_transaction_version)))
+        .filter(version_guard!(last_transaction_version))
+}
+
+/// Adds a batch's volumes to `collection_volume_leaderboard`. The activities whose collection
+/// wasn't cached are counted towards the collection of their token in `current_token_datas_v2`,
+/// so this waits for the batch's token datas to be written. Volumes are increments, so a
+/// collection's volume is only changed by batches past its `last_transaction_version`, and a
+/// retried batch doesn't add them twice. Table names are quoted so overrides apply.
+pub fn upsert_collection_volumes_query(
+    collection_volumes: CollectionVolumes,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    let mut collection_ids = vec![];
+    let mut activity_counts = vec![];
+    let mut usd_volumes = vec![];
+    let mut versions = vec![];
+    let mut timestamps = vec![];
+    for volume in collection_volumes.volumes {
+        collection_ids.push(volume.collection_id);
+        activity_counts.push(volume.activity_count);
+        usd_volumes.push(volume.usd_volume);
+        versions.push(volume.last_transaction_version);
+        timestamps.push(volume.last_transaction_timestamp);
+    }
+    let mut activity_token_data_ids = vec![];
+    let mut activity_usd_values = vec![];
+    let mut activity_versions = vec![];
+    let mut activity_timestamps = vec![];
+    for activity in collection_volumes.uncached_activities {
+        activity_token_data_ids.push(activity.token_data_id);
+        activity_usd_values.push(activity.usd_value.unwrap_or_else(BigDecimal::zero));
+        activity_versions.push(activity.transaction_version);
+        activity_timestamps.push(activity.transaction_timestamp);
+    }
+    sql_query(
+        r#"WITH volumes AS (
+            SELECT collection_id, SUM(activity_count)::bigint AS activity_count,
+                SUM(usd_volume) AS usd_volume, MAX(version) AS version,
+                MAX(transaction_timestamp) AS transaction_timestamp
+            FROM (
+                SELECT *
+                FROM UNNEST($1::text[], $2::bigint[], $3::numeric[], $4::bigint[],
+                        $5::timestamp[])
+                    AS volume(collection_id, activity_count, usd_volume, version,
+                        transaction_timestamp)
+                UNION ALL
+                SELECT token.collection_id, 1, activity.usd_value, activity.version,
+                    activity.transaction_timestamp
+                FROM UNNEST($6::text[], $7::numeric[], $8::bigint[], $9::timestamp[])
+                    AS activity(token_data_id, usd_value, version, transaction_timestamp)
+                    JOIN "current_token_datas_v2" AS token USING (token_data_id)
+            ) AS changes
+            GROUP BY collection_id
+        )
+        INSERT INTO "collection_volume_leaderboard" AS leaderboard (collection_id,
+            activity_count, usd_volume, last_transaction_version, last_transaction_timestamp)
+        SELECT collection_id, activity_count, usd_volume, version, transaction_timestamp
+        FROM volumes
+        ON CONFLICT (collection_id) DO UPDATE
+        SET activity_count = leaderboard.activity_count + EXCLUDED.activity_count,
+            usd_volume = leaderboard.usd_volume + EXCLUDED.usd_volume,
+            last_transaction_version = EXCLUDED.last_transaction_version,
+            last_transaction_timestamp = EXCLUDED.last_transaction_timestamp
+        WHERE leaderboard.last_transaction_version < EXCLUDED.last_transaction_version"#,
+    )
+    .bind::<Array<Text>, _>(collection_ids)
+    .bind::<Array<BigInt>, _>(activity_counts)
+    .bind::<Array<Numeric>, _>(usd_volumes)
+    .bind::<Array<BigInt>, _>(versions)
+    .bind::<Array<Timestamp>, _>(timestamps)
+    .bind::<Array<Text>, _>(activity_token_data_ids)
+    .bind::<Array<Numeric>, _>(activity_usd_values)
+    .bind::<Array<BigInt>, _>(activity_versions)
+    .bind::<Array<Timestamp>, _>(activity_timestamps)
+}
+
+pub fn insert_token_mints_detail_query(
+    items_to_insert: Vec<PostgresTokenMintDetail>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::token_mints_detail::dsl::*;
+
+    diesel::insert_into(schema::token_mints_detail::table)
+        .values(items_to_insert)
+        .on_conflict((transaction_version, event_index))
+        .do_nothing()
+}
+
+pub fn insert_nft_transfers_query(
+    items_to_insert: Vec<PostgresNftTransfer>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::nft_transfers::dsl::*;
+
+    diesel::insert_into(schema::nft_transfers::table)
+        .values(items_to_insert)
+        .on_conflict((transaction_version, event_index))
+        .do_nothing()
+}
+
+/// Replaces the stored properties of each token with its incoming ones, unless the stored ones
+/// are newer. Keys missing from the incoming properties are deleted in the same statement, which
+/// is why this isn't built with the query builder. Table names are quoted so overrides apply.
+pub fn insert_token_property_values_query(
+    items_to_insert: Vec<TokenPropertyValues>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    sql_query(
+        r#"WITH tokens AS (
+            SELECT * FROM jsonb_to_recordset($1) AS token(
+                token_data_id TEXT,
+                properties JSONB,
+                last_transaction_version BIGINT,
+                last_transaction_timestamp TIMESTAMP
+            )
+        ), incoming AS (
+            SELECT
+                tokens.token_data_id,
+                property.property_key,
+                property.property_type,
+                property.property_value,
+                tokens.last_transaction_version,
+                tokens.last_transaction_timestamp
+            FROM tokens, jsonb_to_recordset(tokens.properties) AS property(
+                property_key TEXT,
+                property_type TEXT,
+                property_value TEXT
+            )
+        ), removed AS (
+            DELETE FROM "token_property_values" AS stored
+            USING tokens
+            WHERE stored.token_data_id = tokens.token_data_id
+                AND stored.last_transaction_version < tokens.last_transaction_version
+                AND NOT EXISTS (
+                    SELECT 1 FROM incoming
+                    WHERE incoming.token_data_id = stored.token_data_id
+                        AND incoming.property_key = stored.property_key
+                )
+        )
+        INSERT INTO "token_property_values" (
+            token_data_id,
+            property_key,
+            property_type,
+            property_value,
+            last_transaction_version,
+            last_transaction_timestamp
+        )
+        SELECT
+            token_data_id,
+            property_key,
+            property_type,
+            property_value,
+            last_transaction_version,
+            last_transaction_timestamp
+        FROM incoming
+        ON CONFLICT (token_data_id, property_key) DO UPDATE SET
+            property_type = EXCLUDED.property_type,
+            property_value = EXCLUDED.property_value,
+            last_transaction_version = EXCLUDED.last_transaction_version,
+            last_transaction_timestamp = EXCLUDED.last_transaction_timestamp,
+            inserted_at = EXCLUDED.inserted_at
+        WHERE "token_property_values".last_transaction_version
+            <= EXCLUDED.last_transaction_version"#,
+    )
+    .bind::<Jsonb, _>(serde_json::to_value(items_to_insert).unwrap())
 }
@@ -6,24 +6,35 @@ use crate::{
             token_royalty::PostgresCurrentTokenRoyaltyV1,
         },
         token_v2_models::{
-            v2_collections::CurrentCollectionV2, v2_token_activities::PostgresTokenActivityV2,
+            v2_collection_volumes::CollectionVolumes, v2_collections::CurrentCollectionV2,
+            v2_nft_transfers::PostgresNftTransfer, v2_token_activities::PostgresTokenActivityV2,
             v2_token_datas::PostgresCurrentTokenDataV2,
+            v2_token_mints_detail::PostgresTokenMintDetail,
             v2_token_ownerships::PostgresCurrentTokenOwnershipV2,
+            v2_token_property_values::TokenPropertyValues,
         },
         token_v2_processor::TokenV2ProcessorConfig,
         token_v2_processor_queries::{
             insert_current_collections_v2_query, insert_current_deleted_token_datas_v2_query,
             insert_current_deleted_token_ownerships_v2_query, insert_current_token_claims_query,
             insert_current_token_datas_v2_query, insert_current_token_ownerships_v2_query,
-            insert_current_token_royalties_v1_query, insert_token_activities_v2_query,
+            insert_current_token_royalties_v1_query, insert_nft_transfers_query,
+            insert_token_activities_v2_query, insert_token_mints_detail_query,
+            insert_token_property_values_query, update_token_datas_collection_fields_query,
+            upsert_collection_stats_query, upsert_collection_volumes_query, CollectionStatsChanges,
         },
     },
-    utils::table_flags::{filter_data, TableFlags},
+    utils::{
+        dedup::dedup_by_pk_keep_latest,
+        table_flags::{filter_data, should_write, TableFlags},
+        table_names::TableNames,
+        table_writers::{PendingWrites, TableWriters},
+    },
 };
 use ahash::AHashMap;
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    postgres::utils::database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
@@ -35,21 +46,27 @@ pub struct TokenV2Storer
 where
     Self: Sized + Send + 'static,
 {
-    conn_pool: ArcDbPool,
     processor_config: TokenV2ProcessorConfig,
     tables_to_write: TableFlags,
+    table_writers: TableWriters,
 }
 
 impl TokenV2Storer {
     pub fn new(
         conn_pool: ArcDbPool,
+        table_names: TableNames,
         processor_config: TokenV2ProcessorConfig,
         tables_to_write: TableFlags,
     ) -> Self {
-        Self {
+        let table_writers = TableWriters::new(
             conn_pool,
+            table_names,
+            processor_config.default_config.channel_size,
+        );
+        Self {
             processor_config,
             tables_to_write,
+            table_writers,
         }
     }
 }
@@ -65,8 +82,12 @@ impl Processable for TokenV2Storer {
         Vec<PostgresTokenActivityV2>,
         Vec<PostgresCurrentTokenRoyaltyV1>,
         Vec<PostgresCurrentTokenPendingClaim>,
+        CollectionVolumes,
+        Vec<PostgresTokenMintDetail>,
+        Vec<PostgresNftTransfer>,
+        Vec<TokenPropertyValues>,
     );
-    type Output = ();
+    type Output = PendingWrites;
     type RunType = AsyncRunType;
 
     async fn process(
@@ -80,6 +101,10 @@ impl Processable for TokenV2Storer {
             Vec<PostgresTokenActivityV2>,
             Vec<PostgresCurrentTokenRoyaltyV1>,
             Vec<PostgresCurrentTokenPendingClaim>,
+            CollectionVolumes,
+            Vec<PostgresTokenMintDetail>,
+            Vec<PostgresNftTransfer>,
+            Vec<TokenPropertyValues>,
         )>,
     ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
         let (
@@ -91,6 +116,10 @@ impl Processable for TokenV2Storer {
             token_activities_v2,
             current_token_royalties_v1,
             current_token_claims,
+            collection_volumes,
+            token_mints_detail,
+            nft_transfers,
+            token_property_values,
         ) = input.data;
 
         let (
@@ -102,6 +131,10 @@ impl Processable for TokenV2Storer {
             token_activities_v2,
             current_token_royalties_v1,
             current_token_claims,
+            collection_volumes,
+            token_mints_detail,
+            nft_transfers,
+            token_property_values,
         ) = filter_datasets!(self, {
             current_collections_v2 => TableFlags::CURRENT_COLLECTIONS_V2,
             current_token_datas_v2 => TableFlags::CURRENT_TOKEN_DATAS_V2,
@@ -111,124 +144,243 @@ impl Processable for TokenV2Storer {
             token_activities_v2 => TableFlags::TOKEN_ACTIVITIES_V2,
             current_token_royalties_v1 => TableFlags::CURRENT_TOKEN_ROYALTY_V1,
             current_token_claims => TableFlags::CURRENT_TOKEN_PENDING_CLAIMS,
+            token_mints_detail => TableFlags::TOKEN_MINTS_DETAIL,
+            nft_transfers => TableFlags::NFT_TRANSFERS,
+            token_property_values => TableFlags::TOKEN_PROPERTY_VALUES,
         });
 
+        let current_collections_v2 = dedup_by_pk_keep_latest(current_collections_v2);
+        let current_token_datas_v2 = dedup_by_pk_keep_latest(current_token_datas_v2);
+        let current_deleted_token_datas_v2 =
+            dedup_by_pk_keep_latest(current_deleted_token_datas_v2);
+        let current_token_ownerships_v2 = dedup_by_pk_keep_latest(current_token_ownerships_v2);
+        let current_deleted_token_ownerships_v2 =
+            dedup_by_pk_keep_latest(current_deleted_token_ownerships_v2);
+        let current_token_royalties_v1 = dedup_by_pk_keep_latest(current_token_royalties_v1);
+        let current_token_claims = dedup_by_pk_keep_latest(current_token_claims);
+
+        // Collections whose fields are copied onto their tokens once both are written
+        let mut token_collection_ids: Vec<String> =
+            if should_write(&self.tables_to_write, TableFlags::CURRENT_TOKEN_DATAS_V2) {
+                current_collections_v2
+                    .iter()
+                    .map(|collection| collection.collection_id.clone())
+                    .chain(
+                        current_token_datas_v2
+                            .iter()
+                            .chain(&current_deleted_token_datas_v2)
+                            .map(|token_data| token_data.collection_id.clone()),
+                    )
+                    .collect()
+            } else {
+                vec![]
+            };
+        token_collection_ids.sort();
+        token_collection_ids.dedup();
+
+        // What the batch changes in the stats of its collections, with the latest change to each
+        // ownership whether it's deleted or not
+        let collection_stats_changes = if should_write(
+            &self.tables_to_write,
+            TableFlags::CURRENT_COLLECTION_STATS_V2,
+        ) {
+            let ownerships = dedup_by_pk_keep_latest(
+                current_token_ownerships_v2
+                    .iter()
+                    .chain(&current_deleted_token_ownerships_v2)
+                    .cloned()
+                    .collect(),
+            );
+            CollectionStatsChanges::new(
+                input.metadata.start_version,
+                &current_collections_v2,
+                &ownerships,
+                &token_activities_v2,
+            )
+        } else {
+            CollectionStatsChanges::default()
+        };
+
         let per_table_chunk_sizes: AHashMap<String, usize> = self
             .processor_config
             .default_config
             .per_table_chunk_sizes
             .clone();
 
-        let cc_v2 = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_current_collections_v2_query,
-            &current_collections_v2,
-            get_config_table_chunk_size::<CurrentCollectionV2>(
+        let mut pending_writes = PendingWrites::default();
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_current_collections_v2_query,
+                current_collections_v2,
                 "current_collections_v2",
-                &per_table_chunk_sizes,
-            ),
-        );
-        let ctd_v2 = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_current_token_datas_v2_query,
-            &current_token_datas_v2,
-            get_config_table_chunk_size::<PostgresCurrentTokenDataV2>(
+                get_config_table_chunk_size::<CurrentCollectionV2>(
+                    "current_collections_v2",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_current_token_datas_v2_query,
+                current_token_datas_v2,
                 "current_token_datas_v2",
-                &per_table_chunk_sizes,
-            ),
-        );
-        let cdtd_v2 = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_current_deleted_token_datas_v2_query,
-            &current_deleted_token_datas_v2,
-            get_config_table_chunk_size::<PostgresCurrentTokenDataV2>(
+                get_config_table_chunk_size::<PostgresCurrentTokenDataV2>(
+                    "current_token_datas_v2",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_current_deleted_token_datas_v2_query,
+                current_deleted_token_datas_v2,
                 "current_token_datas_v2",
-                &per_table_chunk_sizes,
-            ),
-        );
-        let cto_v2 = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_current_token_ownerships_v2_query,
-            &current_token_ownerships_v2,
-            get_config_table_chunk_size::<PostgresCurrentTokenOwnershipV2>(
+                get_config_table_chunk_size::<PostgresCurrentTokenDataV2>(
+                    "current_token_datas_v2",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        if !token_collection_ids.is_empty() {
+            self.table_writers
+                .execute_after(
+                    &mut pending_writes,
+                    update_token_datas_collection_fields_query(token_collection_ids),
+                    "current_token_datas_v2",
+                    &["current_collections_v2"],
+                )
+                .await?;
+        }
+        // Compares the batch's ownerships with the stored ones, so it's queued before they're
+        // written, and after the earlier batches' ownerships
+        if !collection_stats_changes.is_empty() {
+            self.table_writers
+                .execute_after(
+                    &mut pending_writes,
+                    upsert_collection_stats_query(collection_stats_changes),
+                    "current_token_ownerships_v2",
+                    &["current_collections_v2"],
+                )
+                .await?;
+        }
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_current_token_ownerships_v2_query,
+                current_token_ownerships_v2,
                 "current_token_ownerships_v2",
-                &per_table_chunk_sizes,
-            ),
-        );
-        let cdto_v2 = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_current_deleted_token_ownerships_v2_query,
-            &current_deleted_token_ownerships_v2,
-            get_config_table_chunk_size::<PostgresCurrentTokenOwnershipV2>(
+                get_config_table_chunk_size::<PostgresCurrentTokenOwnershipV2>(
+                    "current_token_ownerships_v2",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_current_deleted_token_ownerships_v2_query,
+                current_deleted_token_ownerships_v2,
                 "current_token_ownerships_v2",
-                &per_table_chunk_sizes,
-            ),
-        );
-        let ta_v2 = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_token_activities_v2_query,
-            &token_activities_v2,
-            get_config_table_chunk_size::<PostgresTokenActivityV2>(
+                get_config_table_chunk_size::<PostgresCurrentTokenOwnershipV2>(
+                    "current_token_ownerships_v2",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_token_activities_v2_query,
+                token_activities_v2,
                 "token_activities_v2",
-                &per_table_chunk_sizes,
-            ),
-        );
-        let ctr_v1 = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_current_token_royalties_v1_query,
-            &current_token_royalties_v1,
-            get_config_table_chunk_size::<PostgresCurrentTokenRoyaltyV1>(
+                get_config_table_chunk_size::<PostgresTokenActivityV2>(
+                    "token_activities_v2",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_current_token_royalties_v1_query,
+                current_token_royalties_v1,
                 "current_token_royalty_v1",
-                &per_table_chunk_sizes,
-            ),
-        );
-        let ctc_v1 = execute_in_chunks(
-            self.conn_pool.clone(),
-            insert_current_token_claims_query,
-            &current_token_claims,
-            get_config_table_chunk_size::<PostgresCurrentTokenPendingClaim>(
+                get_config_table_chunk_size::<PostgresCurrentTokenRoyaltyV1>(
+                    "current_token_royalty_v1",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_current_token_claims_query,
+                current_token_claims,
                 "current_token_pending_claims",
-                &per_table_chunk_sizes,
-            ),
-        );
-
-        let (
-            cc_v2_res,
-            ctd_v2_res,
-            cdtd_v2_res,
-            cto_v2_res,
-            cdto_v2_res,
-            ta_v2_res,
-            ctr_v1_res,
-            ctc_v1_res,
-        ) = tokio::join!(cc_v2, ctd_v2, cdtd_v2, cto_v2, cdto_v2, ta_v2, ctr_v1, ctc_v1);
-
-        for res in [
-            cc_v2_res,
-            ctd_v2_res,
-            cdtd_v2_res,
-            cto_v2_res,
-            cdto_v2_res,
-            ta_v2_res,
-            ctr_v1_res,
-            ctc_v1_res,
-        ] {
-            match res {
-                Ok(_) => {},
-                Err(e) => {
-                    return Err(ProcessorError::DBStoreError {
-                        message: format!(
-                            "Failed to store versions {} to {}: {:?}",
-                            input.metadata.start_version, input.metadata.end_version, e,
-                        ),
-                        query: None,
-                    })
-                },
-            }
+                get_config_table_chunk_size::<PostgresCurrentTokenPendingClaim>(
+                    "current_token_pending_claims",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        // Looks up the collections of the activities whose token wasn't cached, so it waits for
+        // the batch's token datas
+        if should_write(
+            &self.tables_to_write,
+            TableFlags::COLLECTION_VOLUME_LEADERBOARD,
+        ) && !collection_volumes.is_empty()
+        {
+            self.table_writers
+                .execute_after(
+                    &mut pending_writes,
+                    upsert_collection_volumes_query(collection_volumes),
+                    "collection_volume_leaderboard",
+                    &["current_token_datas_v2"],
+                )
+                .await?;
         }
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_token_mints_detail_query,
+                token_mints_detail,
+                "token_mints_detail",
+                get_config_table_chunk_size::<PostgresTokenMintDetail>(
+                    "token_mints_detail",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_nft_transfers_query,
+                nft_transfers,
+                "nft_transfers",
+                get_config_table_chunk_size::<PostgresNftTransfer>(
+                    "nft_transfers",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
+        self.table_writers
+            .write(
+                &mut pending_writes,
+                insert_token_property_values_query,
+                token_property_values,
+                "token_property_values",
+                get_config_table_chunk_size::<TokenPropertyValues>(
+                    "token_property_values",
+                    &per_table_chunk_sizes,
+                ),
+            )
+            .await?;
 
         Ok(Some(TransactionContext {
-            data: (),
+            data: pending_writes,
             metadata: input.metadata,
         }))
     }
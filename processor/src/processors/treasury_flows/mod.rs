@@ -0,0 +1,4 @@
+pub mod models;
+pub mod treasury_flows_extractor;
+pub mod treasury_flows_processor;
+pub mod treasury_flows_storer;
@@ -0,0 +1,185 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::{
+    processors::fungible_asset::fungible_asset_models::v2_fungible_asset_activities::FungibleAssetActivity,
+    schema::treasury_inflows,
+};
+use ahash::AHashSet;
+use anyhow::{bail, Result};
+use bigdecimal::BigDecimal;
+use cedra_indexer_processor_sdk::utils::convert::standardize_address;
+use chrono::NaiveDateTime;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+const DEPOSIT_EVENT_TYPES: [&str; 4] = [
+    "0x1::coin::DepositEvent",
+    "0x1::coin::CoinDeposit",
+    "0x1::fungible_asset::DepositEvent",
+    "0x1::fungible_asset::Deposit",
+];
+const WITHDRAW_EVENT_TYPES: [&str; 4] = [
+    "0x1::coin::WithdrawEvent",
+    "0x1::coin::CoinWithdraw",
+    "0x1::fungible_asset::WithdrawEvent",
+    "0x1::fungible_asset::Withdraw",
+];
+
+/// Addresses whose inflows the treasury flows processor tracks, like treasuries and fee
+/// collectors.
+///
+/// Example:
+/// ```yaml
+/// processor_config:
+///   type: treasury_flows_processor
+///   treasury_addresses:
+///     - "0xabc"
+/// ```
+#[derive(Clone, Debug)]
+pub struct TreasuryAddresses {
+    addresses: AHashSet<String>,
+}
+
+impl TreasuryAddresses {
+    pub fn new(addresses: &[String]) -> Result<Self> {
+        if addresses.is_empty() {
+            bail!("treasury_addresses must list at least one address");
+        }
+        Ok(Self {
+            addresses: addresses
+                .iter()
+                .map(|address| standardize_address(address))
+                .collect(),
+        })
+    }
+
+    pub fn contains(&self, address: &str) -> bool {
+        self.addresses.contains(address)
+    }
+}
+
+/// A coin or fungible asset deposit into a treasury address, with where it came from
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, event_index))]
+#[diesel(table_name = treasury_inflows)]
+pub struct TreasuryInflow {
+    pub transaction_version: i64,
+    pub event_index: i64,
+    pub treasury_address: String,
+    pub asset_type: String,
+    pub amount: BigDecimal,
+    pub token_standard: String,
+    /// Owner of the first store the transaction withdrew the same asset from, None for mints
+    pub source_address: Option<String>,
+    pub entry_function_id_str: Option<String>,
+    /// Module of the entry function that initiated the transfer, like `0x1::transaction_fee`
+    pub source_module: Option<String>,
+    pub block_height: i64,
+    pub transaction_timestamp: NaiveDateTime,
+}
+
+impl TreasuryInflow {
+    /// Inflows among the activities of a transaction. Deposits of coins migrated into a
+    /// treasury's primary store aren't inflows, they were already the treasury's.
+    pub fn from_activities(
+        activities: &[FungibleAssetActivity],
+        treasury_addresses: &TreasuryAddresses,
+    ) -> Vec<Self> {
+        activities
+            .iter()
+            .filter(|activity| {
+                activity.is_transaction_success
+                    && !activity.is_migration
+                    && DEPOSIT_EVENT_TYPES.contains(&activity.event_type.as_str())
+            })
+            .filter_map(|deposit| {
+                let treasury_address = deposit.owner_address.as_ref()?;
+                if !treasury_addresses.contains(treasury_address) {
+                    return None;
+                }
+                let asset_type = deposit.asset_type.as_ref()?;
+                let source_address = activities
+                    .iter()
+                    .find(|activity| {
+                        activity.transaction_version == deposit.transaction_version
+                            && !activity.is_gas_fee
+                            && activity.asset_type.as_ref() == Some(asset_type)
+                            && WITHDRAW_EVENT_TYPES.contains(&activity.event_type.as_str())
+                    })
+                    .and_then(|withdraw| withdraw.owner_address.clone());
+                let source_module = deposit
+                    .entry_function_id_str
+                    .as_ref()
+                    .and_then(|function| function.rsplit_once("::"))
+                    .map(|(module, _)| module.to_string());
+                Some(Self {
+                    transaction_version: deposit.transaction_version,
+                    event_index: deposit.event_index,
+                    treasury_address: treasury_address.clone(),
+                    asset_type: asset_type.clone(),
+                    amount: deposit.amount.clone()?,
+                    token_standard: deposit.token_standard.clone(),
+                    source_address,
+                    entry_function_id_str: deposit.entry_function_id_str.clone(),
+                    source_module,
+                    block_height: deposit.block_height,
+                    transaction_timestamp: deposit.transaction_timestamp,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(
+        event_index: i64,
+        owner_address: &str,
+        event_type: &str,
+        is_gas_fee: bool,
+    ) -> FungibleAssetActivity {
+        FungibleAssetActivity {
+            transaction_version: 100,
+            event_index,
+            owner_address: Some(standardize_address(owner_address)),
+            storage_id: standardize_address("0x9"),
+            asset_type: Some("0x1::cedra_coin::CedraCoin".to_string()),
+            is_frozen: None,
+            amount: Some(BigDecimal::from(10)),
+            event_type: event_type.to_string(),
+            is_gas_fee,
+            gas_fee_payer_address: None,
+            is_transaction_success: true,
+            entry_function_id_str: Some("0xdef::dex::swap".to_string()),
+            block_height: 1,
+            token_standard: "v2".to_string(),
+            transaction_timestamp: NaiveDateTime::default(),
+            storage_refund_amount: BigDecimal::from(0),
+            is_migration: false,
+        }
+    }
+
+    #[test]
+    fn test_inflows_are_attributed_to_the_withdrawing_owner() {
+        let treasury_addresses = TreasuryAddresses::new(&["0xabc".to_string()]).unwrap();
+        let activities = [
+            activity(-1, "0x5", "0x1::cedra_coin::GasFeeEvent", true),
+            activity(0, "0x7", "0x1::fungible_asset::Withdraw", false),
+            activity(1, "0xabc", "0x1::fungible_asset::Deposit", false),
+            activity(2, "0x8", "0x1::fungible_asset::Deposit", false),
+        ];
+        let inflows = TreasuryInflow::from_activities(&activities, &treasury_addresses);
+        assert_eq!(inflows.len(), 1);
+        assert_eq!(inflows[0].event_index, 1);
+        assert_eq!(inflows[0].treasury_address, standardize_address("0xabc"));
+        assert_eq!(inflows[0].source_address, Some(standardize_address("0x7")));
+        assert_eq!(inflows[0].source_module.as_deref(), Some("0xdef::dex"));
+        assert!(TreasuryAddresses::new(&[]).is_err());
+    }
+}
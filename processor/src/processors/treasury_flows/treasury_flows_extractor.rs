@@ -0,0 +1,84 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::models::{TreasuryAddresses, TreasuryInflow};
+use crate::processors::fungible_asset::{
+    fungible_asset_models::v2_fungible_asset_to_coin_mappings::{
+        FungibleAssetToCoinMapping, FungibleAssetToCoinMappings,
+    },
+    fungible_asset_processor_helpers::{get_fa_to_coin_mapping, parse_v2_coin, FungibleAssetBatch},
+};
+use ahash::AHashMap;
+use anyhow::Result;
+use cedra_indexer_processor_sdk::{
+    cedra_protos::transaction::v1::Transaction,
+    postgres::utils::database::ArcDbPool,
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+
+/// Extracts the coin and fungible asset deposits into treasury addresses
+pub struct TreasuryFlowsExtractor
+where
+    Self: Sized + Send + 'static,
+{
+    treasury_addresses: TreasuryAddresses,
+    fa_to_coin_mapping: FungibleAssetToCoinMappings,
+}
+
+impl TreasuryFlowsExtractor {
+    pub fn new(treasury_addresses: TreasuryAddresses) -> Self {
+        Self {
+            treasury_addresses,
+            fa_to_coin_mapping: AHashMap::new(),
+        }
+    }
+
+    /// Loads the mappings of the fungible assets paired with coins, so deposits of a paired
+    /// asset are attributed to the coin type like in `fungible_asset_activities`
+    pub async fn bootstrap_fa_to_coin_mapping(&mut self, db_pool: ArcDbPool) -> Result<()> {
+        let mut conn = db_pool.get().await?;
+        self.fa_to_coin_mapping = FungibleAssetToCoinMapping::get_all_mappings(&mut conn).await;
+        tracing::info!(
+            item_count = self.fa_to_coin_mapping.len(),
+            "Finished bootstrapping fungible asset to coin mapping"
+        );
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Processable for TreasuryFlowsExtractor {
+    type Input = Vec<Transaction>;
+    type Output = Vec<TreasuryInflow>;
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        transactions: TransactionContext<Vec<Transaction>>,
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        let new_fa_to_coin_mapping = get_fa_to_coin_mapping(&transactions.data).await;
+        self.fa_to_coin_mapping.extend(new_fa_to_coin_mapping);
+        let FungibleAssetBatch {
+            fungible_asset_activities,
+            ..
+        } = parse_v2_coin(&transactions.data, Some(&self.fa_to_coin_mapping)).await;
+        let treasury_inflows =
+            TreasuryInflow::from_activities(&fungible_asset_activities, &self.treasury_addresses);
+
+        Ok(Some(TransactionContext {
+            data: treasury_inflows,
+            metadata: transactions.metadata,
+        }))
+    }
+}
+
+impl AsyncStep for TreasuryFlowsExtractor {}
+
+impl NamedStep for TreasuryFlowsExtractor {
+    fn name(&self) -> String {
+        "treasury_flows_extractor".to_string()
+    }
+}
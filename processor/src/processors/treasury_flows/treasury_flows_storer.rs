@@ -0,0 +1,109 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::models::TreasuryInflow;
+use crate::{
+    config::processor_config::DefaultProcessorConfig,
+    schema,
+    utils::{
+        table_flags::{filter_data, TableFlags},
+        table_names::TableNames,
+        table_stats::execute_in_chunks_with_stats,
+    },
+};
+use ahash::AHashMap;
+use anyhow::Result;
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use diesel::{pg::Pg, query_builder::QueryFragment};
+
+pub struct TreasuryFlowsStorer
+where
+    Self: Sized + Send + 'static,
+{
+    conn_pool: ArcDbPool,
+    table_names: TableNames,
+    processor_config: DefaultProcessorConfig,
+    tables_to_write: TableFlags,
+}
+
+impl TreasuryFlowsStorer {
+    pub fn new(
+        conn_pool: ArcDbPool,
+        table_names: TableNames,
+        processor_config: DefaultProcessorConfig,
+        tables_to_write: TableFlags,
+    ) -> Self {
+        Self {
+            conn_pool,
+            table_names,
+            processor_config,
+            tables_to_write,
+        }
+    }
+}
+
+#[async_trait]
+impl Processable for TreasuryFlowsStorer {
+    type Input = Vec<TreasuryInflow>;
+    type Output = ();
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        input: TransactionContext<Self::Input>,
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        let treasury_inflows = input.data;
+
+        let per_table_chunk_sizes: AHashMap<String, usize> =
+            self.processor_config.per_table_chunk_sizes.clone();
+
+        let treasury_inflows = filter_data(
+            &self.tables_to_write,
+            TableFlags::TREASURY_INFLOWS,
+            treasury_inflows,
+        );
+
+        execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_treasury_inflows_query,
+            &treasury_inflows,
+            "treasury_inflows",
+            get_config_table_chunk_size::<TreasuryInflow>(
+                "treasury_inflows",
+                &per_table_chunk_sizes,
+            ),
+        )
+        .await?;
+
+        Ok(Some(TransactionContext {
+            data: (),
+            metadata: input.metadata,
+        }))
+    }
+}
+
+impl AsyncStep for TreasuryFlowsStorer {}
+
+impl NamedStep for TreasuryFlowsStorer {
+    fn name(&self) -> String {
+        "treasury_flows_storer".to_string()
+    }
+}
+
+pub fn insert_treasury_inflows_query(
+    items_to_insert: Vec<TreasuryInflow>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::treasury_inflows::dsl::*;
+
+    diesel::insert_into(schema::treasury_inflows::table)
+        .values(items_to_insert)
+        .on_conflict((transaction_version, event_index))
+        .do_nothing()
+}
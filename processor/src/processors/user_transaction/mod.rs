@@ -9,7 +9,8 @@ use crate::{
     processors::{
         fungible_asset::fungible_asset_models::v2_fungible_asset_utils::FeeStatement,
         user_transaction::models::{
-            signatures::Signature, user_transactions::UserTransactionModel,
+            signatures::Signature, transaction_signers::PostgresTransactionSigner,
+            user_transactions::UserTransactionModel,
         },
     },
     utils::counters::PROCESSOR_UNKNOWN_TYPE_COUNT,
@@ -63,3 +64,29 @@ pub fn user_transaction_parse(
 
     (user_transactions, signatures)
 }
+
+/// Helper function to parse the signer of every role (primary, secondary, fee payer) from the
+/// user transactions in the transaction data.
+pub fn transaction_signers_parse(transactions: &[Transaction]) -> Vec<PostgresTransactionSigner> {
+    transactions
+        .iter()
+        .filter_map(|txn| match txn.txn_data.as_ref() {
+            Some(TxnData::User(inner)) => Some((txn, inner.request.as_ref()?)),
+            _ => None,
+        })
+        .flat_map(|(txn, user_request)| {
+            user_request
+                .signature
+                .as_ref()
+                .map(|s| {
+                    PostgresTransactionSigner::from_user_transaction(
+                        s,
+                        &user_request.sender,
+                        txn.version as i64,
+                        txn.block_height as i64,
+                    )
+                })
+                .unwrap_or_default()
+        })
+        .collect()
+}
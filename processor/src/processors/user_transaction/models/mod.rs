@@ -1,6 +1,8 @@
 // Copyright © Cedra Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod sender_sequence_number_gaps;
 pub mod signature_utils;
 pub mod signatures;
+pub mod transaction_signers;
 pub mod user_transactions;
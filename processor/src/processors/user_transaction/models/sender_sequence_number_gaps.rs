@@ -0,0 +1,147 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::extra_unused_lifetimes)]
+
+use super::user_transactions::PostgresUserTransaction;
+use crate::schema::{sender_sequence_number_gaps, user_transactions};
+use ahash::AHashMap;
+use cedra_indexer_processor_sdk::postgres::utils::database::DbPoolConnection;
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+/// Sequence numbers a sender skipped between two of its indexed transactions. Sequence numbers
+/// are committed in order, so a gap means transactions that never made it into the index, e.g.
+/// because of a transaction filter or a skipped version range.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(sender, sequence_number))]
+#[diesel(table_name = sender_sequence_number_gaps)]
+pub struct PostgresSenderSequenceNumberGap {
+    pub sender: String,
+    /// Sequence number of the transaction after the gap
+    pub sequence_number: i64,
+    pub previous_sequence_number: i64,
+    pub missing_count: i64,
+    pub transaction_version: i64,
+    pub previous_transaction_version: i64,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+/// Sequence number and version of a sender's latest indexed transaction
+pub type PreviousSenderTransactions = AHashMap<String, (i64, i64)>;
+
+impl PostgresSenderSequenceNumberGap {
+    /// Gaps between the transactions of a batch, and between each sender's first transaction in
+    /// the batch and its latest one before, from `previous`
+    pub fn from_user_transactions(
+        previous: &PreviousSenderTransactions,
+        user_transactions: &[PostgresUserTransaction],
+    ) -> Vec<Self> {
+        let mut user_transactions: Vec<&PostgresUserTransaction> =
+            user_transactions.iter().collect();
+        user_transactions.sort_by_key(|user_transaction| user_transaction.version);
+
+        let mut latest = previous.clone();
+        let mut gaps = vec![];
+        for user_transaction in user_transactions {
+            let current = (user_transaction.sequence_number, user_transaction.version);
+            let Some((previous_sequence_number, previous_transaction_version)) =
+                latest.insert(user_transaction.sender.clone(), current)
+            else {
+                continue;
+            };
+            if user_transaction.sequence_number > previous_sequence_number + 1 {
+                gaps.push(Self {
+                    sender: user_transaction.sender.clone(),
+                    sequence_number: user_transaction.sequence_number,
+                    previous_sequence_number,
+                    missing_count: user_transaction.sequence_number - previous_sequence_number - 1,
+                    transaction_version: user_transaction.version,
+                    previous_transaction_version,
+                    transaction_timestamp: user_transaction.timestamp,
+                });
+            }
+        }
+        gaps
+    }
+
+    /// Loads the latest transaction before `before_version` of every sender in a batch
+    pub async fn get_previous_transactions(
+        conn: &mut DbPoolConnection<'_>,
+        user_transactions: &[PostgresUserTransaction],
+        before_version: i64,
+    ) -> diesel::QueryResult<PreviousSenderTransactions> {
+        use user_transactions::dsl;
+
+        let mut senders: Vec<&String> = user_transactions
+            .iter()
+            .map(|user_transaction| &user_transaction.sender)
+            .collect();
+        senders.sort();
+        senders.dedup();
+        let rows = dsl::user_transactions
+            .filter(dsl::sender.eq_any(senders))
+            .filter(dsl::version.lt(before_version))
+            .distinct_on(dsl::sender)
+            .order((dsl::sender, dsl::sequence_number.desc()))
+            .select((dsl::sender, dsl::sequence_number, dsl::version))
+            .load::<(String, i64, i64)>(conn)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(sender, sequence_number, version)| (sender, (sequence_number, version)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+
+    fn user_transaction(
+        version: i64,
+        sender: &str,
+        sequence_number: i64,
+    ) -> PostgresUserTransaction {
+        PostgresUserTransaction {
+            version,
+            block_height: version,
+            parent_signature_type: "ed25519_signature".to_string(),
+            sender: sender.to_string(),
+            sequence_number,
+            max_gas_amount: BigDecimal::from(1000),
+            expiration_timestamp_secs: chrono::NaiveDateTime::default(),
+            gas_unit_price: BigDecimal::from(100),
+            timestamp: chrono::NaiveDateTime::default(),
+            entry_function_id_str: "0x1::aptos_account::transfer".to_string(),
+            epoch: 1,
+            entry_function_contract_address: None,
+            entry_function_module_name: None,
+            entry_function_function_name: None,
+            latency_from_expiration: Some(0),
+        }
+    }
+
+    #[test]
+    fn test_sequence_number_gaps() {
+        let previous = PreviousSenderTransactions::from_iter([("0xa".to_string(), (4, 100))]);
+        let user_transactions = vec![
+            user_transaction(210, "0xa", 8),
+            user_transaction(200, "0xa", 5),
+            user_transaction(201, "0xb", 0),
+            user_transaction(202, "0xb", 1),
+        ];
+
+        let gaps =
+            PostgresSenderSequenceNumberGap::from_user_transactions(&previous, &user_transactions);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].sender, "0xa");
+        assert_eq!(gaps[0].sequence_number, 8);
+        assert_eq!(gaps[0].previous_sequence_number, 5);
+        assert_eq!(gaps[0].missing_count, 2);
+        assert_eq!(gaps[0].previous_transaction_version, 200);
+    }
+}
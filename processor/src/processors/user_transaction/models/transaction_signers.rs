@@ -0,0 +1,226 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![allow(clippy::extra_unused_lifetimes)]
+
+use super::signature_utils::account_signature_utils::{
+    get_account_signature_type, get_account_signature_type_from_enum,
+};
+use crate::schema::transaction_signers;
+use cedra_indexer_processor_sdk::{
+    cedra_protos::transaction::v1::{
+        account_signature::Type as AccountSignatureTypeEnum, signature::Signature as SignatureEnum,
+        AccountSignature, Signature as SignaturePb,
+    },
+    utils::convert::standardize_address,
+};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+pub const SIGNER_ROLE_PRIMARY: &str = "primary";
+pub const SIGNER_ROLE_SECONDARY: &str = "secondary";
+pub const SIGNER_ROLE_FEE_PAYER: &str = "fee_payer";
+
+/// Every account that signed a user transaction, keyed by the role it signed in. Unlike
+/// `signatures`, there is exactly one row per signer regardless of how many keys it signed with.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, signer_role, signer_index))]
+#[diesel(table_name = transaction_signers)]
+pub struct PostgresTransactionSigner {
+    pub transaction_version: i64,
+    pub signer_role: String,
+    /// Position among the secondary signers; always 0 for the primary signer and fee payer.
+    pub signer_index: i64,
+    pub transaction_block_height: i64,
+    pub signer_address: String,
+    pub signature_scheme: String,
+}
+
+impl PostgresTransactionSigner {
+    pub fn from_user_transaction(
+        s: &SignaturePb,
+        sender: &str,
+        transaction_version: i64,
+        transaction_block_height: i64,
+    ) -> Vec<Self> {
+        let signer = |role: &str, index: i64, address: &str, scheme: String| Self {
+            transaction_version,
+            signer_role: role.to_string(),
+            signer_index: index,
+            transaction_block_height,
+            signer_address: standardize_address(address),
+            signature_scheme: scheme,
+        };
+        let account_scheme = |account_signature: Option<&AccountSignature>| {
+            account_signature
+                .map(get_account_signature_type)
+                .unwrap_or_else(|| "unknown".to_string())
+        };
+
+        // The sender, then the secondary signers, of a multi-agent or fee payer signature
+        let sender_and_secondary_signers =
+            |sender_signature: Option<&AccountSignature>,
+             secondary_signer_addresses: &[String],
+             secondary_signers: &[AccountSignature]| {
+                let mut signers = vec![signer(
+                    SIGNER_ROLE_PRIMARY,
+                    0,
+                    sender,
+                    account_scheme(sender_signature),
+                )];
+                signers.extend(secondary_signer_addresses.iter().enumerate().map(
+                    |(index, address)| {
+                        signer(
+                            SIGNER_ROLE_SECONDARY,
+                            index as i64,
+                            address,
+                            account_scheme(secondary_signers.get(index)),
+                        )
+                    },
+                ));
+                signers
+            };
+
+        let Some(signature) = s.signature.as_ref() else {
+            warn!(
+                transaction_version = transaction_version,
+                "Transaction signature is missing"
+            );
+            return vec![];
+        };
+        match signature {
+            SignatureEnum::Ed25519(_) => vec![signer(
+                SIGNER_ROLE_PRIMARY,
+                0,
+                sender,
+                get_account_signature_type_from_enum(&AccountSignatureTypeEnum::Ed25519),
+            )],
+            SignatureEnum::MultiEd25519(_) => vec![signer(
+                SIGNER_ROLE_PRIMARY,
+                0,
+                sender,
+                get_account_signature_type_from_enum(&AccountSignatureTypeEnum::MultiEd25519),
+            )],
+            SignatureEnum::SingleSender(sig) => vec![signer(
+                SIGNER_ROLE_PRIMARY,
+                0,
+                sender,
+                account_scheme(sig.sender.as_ref()),
+            )],
+            SignatureEnum::MultiAgent(sig) => sender_and_secondary_signers(
+                sig.sender.as_ref(),
+                &sig.secondary_signer_addresses,
+                &sig.secondary_signers,
+            ),
+            SignatureEnum::FeePayer(sig) => {
+                let mut signers = sender_and_secondary_signers(
+                    sig.sender.as_ref(),
+                    &sig.secondary_signer_addresses,
+                    &sig.secondary_signers,
+                );
+                signers.push(signer(
+                    SIGNER_ROLE_FEE_PAYER,
+                    0,
+                    &sig.fee_payer_address,
+                    account_scheme(sig.fee_payer_signer.as_ref()),
+                ));
+                signers
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cedra_indexer_processor_sdk::cedra_protos::transaction::v1::{
+        FeePayerSignature, MultiAgentSignature,
+    };
+
+    fn account_signature(signature_type: AccountSignatureTypeEnum) -> AccountSignature {
+        AccountSignature {
+            r#type: signature_type as i32,
+            ..Default::default()
+        }
+    }
+
+    fn signers(signature: SignatureEnum) -> Vec<(String, i64, String, String)> {
+        let signature = SignaturePb {
+            signature: Some(signature),
+            ..Default::default()
+        };
+        PostgresTransactionSigner::from_user_transaction(&signature, "0x1", 100, 10)
+            .into_iter()
+            .map(|signer| {
+                (
+                    signer.signer_role,
+                    signer.signer_index,
+                    signer.signer_address,
+                    signer.signature_scheme,
+                )
+            })
+            .collect()
+    }
+
+    fn row(role: &str, index: i64, address: &str, scheme: &str) -> (String, i64, String, String) {
+        (
+            role.to_string(),
+            index,
+            standardize_address(address),
+            scheme.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_single_signer() {
+        assert_eq!(signers(SignatureEnum::Ed25519(Default::default())), vec![
+            row(SIGNER_ROLE_PRIMARY, 0, "0x1", "ed25519_signature")
+        ]);
+    }
+
+    #[test]
+    fn test_multi_ed25519_signer() {
+        let signature = SignatureEnum::MultiEd25519(Default::default());
+        assert_eq!(signers(signature), vec![row(
+            SIGNER_ROLE_PRIMARY,
+            0,
+            "0x1",
+            "multi_ed25519_signature"
+        )]);
+    }
+
+    #[test]
+    fn test_multi_agent_signers() {
+        let signature = SignatureEnum::MultiAgent(MultiAgentSignature {
+            sender: Some(account_signature(AccountSignatureTypeEnum::Ed25519)),
+            secondary_signer_addresses: vec!["0x2".to_string(), "0x3".to_string()],
+            secondary_signers: vec![
+                account_signature(AccountSignatureTypeEnum::SingleKey),
+                account_signature(AccountSignatureTypeEnum::MultiKey),
+            ],
+        });
+        assert_eq!(signers(signature), vec![
+            row(SIGNER_ROLE_PRIMARY, 0, "0x1", "ed25519_signature"),
+            row(SIGNER_ROLE_SECONDARY, 0, "0x2", "single_key_signature"),
+            row(SIGNER_ROLE_SECONDARY, 1, "0x3", "multi_key_signature"),
+        ]);
+    }
+
+    #[test]
+    fn test_fee_payer_signers() {
+        let signature = SignatureEnum::FeePayer(FeePayerSignature {
+            sender: Some(account_signature(AccountSignatureTypeEnum::Ed25519)),
+            secondary_signer_addresses: vec!["0x2".to_string()],
+            // A secondary signer without a signature is still a signer
+            secondary_signers: vec![],
+            fee_payer_address: "0x4".to_string(),
+            fee_payer_signer: Some(account_signature(AccountSignatureTypeEnum::SingleKey)),
+        });
+        assert_eq!(signers(signature), vec![
+            row(SIGNER_ROLE_PRIMARY, 0, "0x1", "ed25519_signature"),
+            row(SIGNER_ROLE_SECONDARY, 0, "0x2", "unknown"),
+            row(SIGNER_ROLE_FEE_PAYER, 0, "0x4", "single_key_signature"),
+        ]);
+    }
+}
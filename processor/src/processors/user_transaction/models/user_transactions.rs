@@ -136,6 +136,16 @@ impl UserTransaction {
         )
     }
 
+    /// Milliseconds from the commit to the expiration of the transaction, how much longer it could
+    /// have waited in mempool. Wallets set expirations a fixed time after submitting, so this is
+    /// that time less the latency of getting the transaction committed.
+    pub fn latency_from_expiration(&self) -> i64 {
+        parse_timestamp(&self.expiration_timestamp_secs, self.txn_version)
+            .naive_utc()
+            .signed_duration_since(self.block_timestamp)
+            .num_milliseconds()
+    }
+
     /// Empty vec if signature is None
     pub fn get_signatures(
         user_request: &UserTransactionRequest,
@@ -182,6 +192,7 @@ pub struct ParquetUserTransaction {
     pub storage_refund_octa: u64,
     pub is_transaction_success: bool,
     pub num_signatures: i64,
+    pub latency_from_expiration: i64,
 }
 
 impl NamedTable for ParquetUserTransaction {
@@ -196,6 +207,7 @@ impl HasVersion for ParquetUserTransaction {
 
 impl From<UserTransaction> for ParquetUserTransaction {
     fn from(user_transaction: UserTransaction) -> Self {
+        let latency_from_expiration = user_transaction.latency_from_expiration();
         Self {
             txn_version: user_transaction.txn_version,
             block_height: user_transaction.block_height,
@@ -213,6 +225,7 @@ impl From<UserTransaction> for ParquetUserTransaction {
             storage_refund_octa: user_transaction.storage_refund_octa,
             is_transaction_success: user_transaction.is_transaction_success,
             num_signatures: user_transaction.num_signatures,
+            latency_from_expiration,
         }
     }
 }
@@ -236,10 +249,12 @@ pub struct PostgresUserTransaction {
     pub entry_function_contract_address: Option<String>,
     pub entry_function_module_name: Option<String>,
     pub entry_function_function_name: Option<String>,
+    pub latency_from_expiration: Option<i64>,
 }
 
 impl From<UserTransaction> for PostgresUserTransaction {
     fn from(user_transaction: UserTransaction) -> Self {
+        let latency_from_expiration = user_transaction.latency_from_expiration();
         Self {
             version: user_transaction.txn_version,
             block_height: user_transaction.block_height,
@@ -259,6 +274,7 @@ impl From<UserTransaction> for PostgresUserTransaction {
             entry_function_contract_address: user_transaction.entry_function_contract_address,
             entry_function_module_name: user_transaction.entry_function_module_name,
             entry_function_function_name: user_transaction.entry_function_function_name,
+            latency_from_expiration: Some(latency_from_expiration),
         }
     }
 }
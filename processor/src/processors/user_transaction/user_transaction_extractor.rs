@@ -1,6 +1,9 @@
 use crate::processors::user_transaction::{
-    models::{signatures::PostgresSignature, user_transactions::PostgresUserTransaction},
-    user_transaction_parse,
+    models::{
+        signatures::PostgresSignature, transaction_signers::PostgresTransactionSigner,
+        user_transactions::PostgresUserTransaction,
+    },
+    transaction_signers_parse, user_transaction_parse,
 };
 use cedra_indexer_processor_sdk::{
     cedra_protos::transaction::v1::Transaction,
@@ -17,16 +20,18 @@ where
 #[async_trait]
 impl Processable for UserTransactionExtractor {
     type Input = Vec<Transaction>;
-    type Output = (Vec<PostgresUserTransaction>, Vec<PostgresSignature>);
+    type Output = (
+        Vec<PostgresUserTransaction>,
+        Vec<PostgresSignature>,
+        Vec<PostgresTransactionSigner>,
+    );
     type RunType = AsyncRunType;
 
     async fn process(
         &mut self,
         item: TransactionContext<Vec<Transaction>>,
-    ) -> Result<
-        Option<TransactionContext<(Vec<PostgresUserTransaction>, Vec<PostgresSignature>)>>,
-        ProcessorError,
-    > {
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        let transaction_signers = transaction_signers_parse(&item.data);
         let (user_transactions, signatures) = user_transaction_parse(item.data);
 
         let postgres_user_transactions = user_transactions
@@ -40,7 +45,11 @@ impl Processable for UserTransactionExtractor {
             .collect();
 
         Ok(Some(TransactionContext {
-            data: (postgres_user_transactions, postgres_signatures),
+            data: (
+                postgres_user_transactions,
+                postgres_signatures,
+                transaction_signers,
+            ),
             metadata: item.metadata,
         }))
     }
@@ -2,15 +2,21 @@ use crate::{
     config::processor_config::DefaultProcessorConfig,
     filter_datasets,
     processors::user_transaction::models::{
-        signatures::PostgresSignature, user_transactions::PostgresUserTransaction,
+        sender_sequence_number_gaps::PostgresSenderSequenceNumberGap,
+        signatures::PostgresSignature, transaction_signers::PostgresTransactionSigner,
+        user_transactions::PostgresUserTransaction,
     },
     schema,
-    utils::table_flags::{filter_data, TableFlags},
+    utils::{
+        table_flags::{filter_data, should_write, TableFlags},
+        table_names::TableNames,
+        table_stats::execute_in_chunks_with_stats,
+    },
 };
 use ahash::AHashMap;
 use anyhow::Result;
 use cedra_indexer_processor_sdk::{
-    postgres::utils::database::{execute_in_chunks, get_config_table_chunk_size, ArcDbPool},
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
     utils::errors::ProcessorError,
@@ -27,6 +33,7 @@ where
     Self: Sized + Send + 'static,
 {
     conn_pool: ArcDbPool,
+    table_names: TableNames,
     processor_config: DefaultProcessorConfig,
     tables_to_write: TableFlags,
 }
@@ -34,11 +41,13 @@ where
 impl UserTransactionStorer {
     pub fn new(
         conn_pool: ArcDbPool,
+        table_names: TableNames,
         processor_config: DefaultProcessorConfig,
         tables_to_write: TableFlags,
     ) -> Self {
         Self {
             conn_pool,
+            table_names,
             processor_config,
             tables_to_write,
         }
@@ -47,41 +56,93 @@ impl UserTransactionStorer {
 
 #[async_trait]
 impl Processable for UserTransactionStorer {
-    type Input = (Vec<PostgresUserTransaction>, Vec<PostgresSignature>);
+    type Input = (
+        Vec<PostgresUserTransaction>,
+        Vec<PostgresSignature>,
+        Vec<PostgresTransactionSigner>,
+    );
     type Output = ();
     type RunType = AsyncRunType;
 
     async fn process(
         &mut self,
-        input: TransactionContext<(Vec<PostgresUserTransaction>, Vec<PostgresSignature>)>,
+        input: TransactionContext<Self::Input>,
     ) -> Result<Option<TransactionContext<()>>, ProcessorError> {
-        let (user_txns, signatures) = input.data;
+        let (user_txns, signatures, transaction_signers) = input.data;
 
         let per_table_chunk_sizes: AHashMap<String, usize> =
             self.processor_config.per_table_chunk_sizes.clone();
 
-        let (user_txns, signatures) = filter_datasets!(self, {
+        // Compared to the senders' transactions before the batch, so this doesn't depend on
+        // whether user_transactions itself is written
+        let sequence_number_gaps = if should_write(
+            &self.tables_to_write,
+            TableFlags::SENDER_SEQUENCE_NUMBER_GAPS,
+        ) {
+            self.get_sequence_number_gaps(&user_txns, input.metadata.start_version as i64)
+                .await
+                .map_err(|e| ProcessorError::DBStoreError {
+                    message: format!(
+                        "Failed to get sequence number gaps for versions {} to {}: {:?}",
+                        input.metadata.start_version, input.metadata.end_version, e,
+                    ),
+                    query: None,
+                })?
+        } else {
+            vec![]
+        };
+
+        let (user_txns, signatures, transaction_signers) = filter_datasets!(self, {
             user_txns => TableFlags::USER_TRANSACTIONS,
             signatures => TableFlags::SIGNATURES,
+            transaction_signers => TableFlags::TRANSACTION_SIGNERS,
         });
 
-        let ut_res = execute_in_chunks(
+        let ut_res = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_user_transactions_query,
             &user_txns,
+            "user_transactions",
             get_config_table_chunk_size::<PostgresUserTransaction>(
                 "user_transactions",
                 &per_table_chunk_sizes,
             ),
         );
-        let s_res = execute_in_chunks(
+        let s_res = execute_in_chunks_with_stats(
             self.conn_pool.clone(),
+            &self.table_names,
             insert_signatures_query,
             &signatures,
+            "signatures",
             get_config_table_chunk_size::<PostgresSignature>("signatures", &per_table_chunk_sizes),
         );
 
-        futures::try_join!(ut_res, s_res)?;
+        let ts_res = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_transaction_signers_query,
+            &transaction_signers,
+            "transaction_signers",
+            get_config_table_chunk_size::<PostgresTransactionSigner>(
+                "transaction_signers",
+                &per_table_chunk_sizes,
+            ),
+        );
+
+        let ssng_res = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_sender_sequence_number_gaps_query,
+            &sequence_number_gaps,
+            "sender_sequence_number_gaps",
+            get_config_table_chunk_size::<PostgresSenderSequenceNumberGap>(
+                "sender_sequence_number_gaps",
+                &per_table_chunk_sizes,
+            ),
+        );
+
+        futures::try_join!(ut_res, s_res, ts_res, ssng_res)?;
 
         Ok(Some(TransactionContext {
             data: (),
@@ -90,6 +151,28 @@ impl Processable for UserTransactionStorer {
     }
 }
 
+impl UserTransactionStorer {
+    async fn get_sequence_number_gaps(
+        &self,
+        user_txns: &[PostgresUserTransaction],
+        before_version: i64,
+    ) -> Result<Vec<PostgresSenderSequenceNumberGap>> {
+        if user_txns.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut conn = self.conn_pool.get().await?;
+        let previous = PostgresSenderSequenceNumberGap::get_previous_transactions(
+            &mut conn,
+            user_txns,
+            before_version,
+        )
+        .await?;
+        Ok(PostgresSenderSequenceNumberGap::from_user_transactions(
+            &previous, user_txns,
+        ))
+    }
+}
+
 impl AsyncStep for UserTransactionStorer {}
 
 impl NamedStep for UserTransactionStorer {
@@ -132,3 +215,27 @@ pub fn insert_signatures_query(
             inserted_at.eq(excluded(inserted_at)),
         ))
 }
+
+pub fn insert_transaction_signers_query(
+    items_to_insert: Vec<PostgresTransactionSigner>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::transaction_signers::dsl::*;
+    diesel::insert_into(schema::transaction_signers::table)
+        .values(items_to_insert)
+        .on_conflict((transaction_version, signer_role, signer_index))
+        .do_update()
+        .set((
+            signature_scheme.eq(excluded(signature_scheme)),
+            inserted_at.eq(excluded(inserted_at)),
+        ))
+}
+
+pub fn insert_sender_sequence_number_gaps_query(
+    items_to_insert: Vec<PostgresSenderSequenceNumberGap>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::sender_sequence_number_gaps::dsl::*;
+    diesel::insert_into(schema::sender_sequence_number_gaps::table)
+        .values(items_to_insert)
+        .on_conflict((sender, sequence_number))
+        .do_nothing()
+}
@@ -0,0 +1,4 @@
+pub mod models;
+pub mod validator_transaction_extractor;
+pub mod validator_transaction_processor;
+pub mod validator_transaction_storer;
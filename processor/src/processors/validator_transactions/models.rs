@@ -0,0 +1,150 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+
+use crate::{
+    processors::events::events_model::EVENT_TYPE_MAX_LENGTH,
+    schema::{validator_transaction_events, validator_transactions},
+};
+use cedra_indexer_processor_sdk::{
+    cedra_indexer_transaction_stream::utils::time::parse_timestamp,
+    cedra_protos::transaction::v1::{
+        transaction::TxnData, validator_transaction::ValidatorTransactionType, Transaction,
+    },
+    utils::convert::{standardize_address, truncate_str},
+};
+use chrono::NaiveDateTime;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+pub const OBSERVED_JWK_UPDATE: &str = "observed_jwk_update";
+pub const DKG_UPDATE: &str = "dkg_update";
+pub const UNKNOWN: &str = "unknown";
+
+/// A validator transaction with the fields of its payload. JWK updates carry the observed keys of
+/// an OIDC provider and DKG updates the transcript of the epoch's distributed key generation.
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version))]
+#[diesel(table_name = validator_transactions)]
+pub struct ValidatorTransaction {
+    pub transaction_version: i64,
+    pub block_height: i64,
+    pub epoch: i64,
+    pub validator_transaction_type: String,
+    pub jwk_issuer: Option<String>,
+    pub jwk_version: Option<i64>,
+    pub num_jwks: Option<i64>,
+    /// Indices of the validators that signed the observed JWKs
+    pub jwk_signer_indices: Option<serde_json::Value>,
+    pub dkg_epoch: Option<i64>,
+    pub dkg_author: Option<String>,
+    pub dkg_transcript_bytes: Option<i64>,
+    pub num_events: i64,
+    pub is_transaction_success: bool,
+    pub transaction_timestamp: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, event_index))]
+#[diesel(table_name = validator_transaction_events)]
+pub struct ValidatorTransactionEvent {
+    pub transaction_version: i64,
+    pub event_index: i64,
+    pub sequence_number: i64,
+    pub creation_number: i64,
+    pub account_address: String,
+    pub type_: String,
+    pub indexed_type: String,
+    pub data: serde_json::Value,
+    pub transaction_timestamp: NaiveDateTime,
+}
+
+impl ValidatorTransaction {
+    /// The validator transaction and its events, `None` for other transaction types
+    pub fn from_transaction(
+        transaction: &Transaction,
+    ) -> Option<(Self, Vec<ValidatorTransactionEvent>)> {
+        let inner = match transaction.txn_data.as_ref()? {
+            TxnData::Validator(inner) => inner,
+            _ => return None,
+        };
+
+        let txn_version = transaction.version as i64;
+        let transaction_info = transaction
+            .info
+            .as_ref()
+            .expect("Transaction info doesn't exist!");
+        let transaction_timestamp =
+            parse_timestamp(transaction.timestamp.as_ref().unwrap(), txn_version).naive_utc();
+
+        let mut validator_transaction = Self {
+            transaction_version: txn_version,
+            block_height: transaction.block_height as i64,
+            epoch: transaction.epoch as i64,
+            validator_transaction_type: UNKNOWN.to_string(),
+            jwk_issuer: None,
+            jwk_version: None,
+            num_jwks: None,
+            jwk_signer_indices: None,
+            dkg_epoch: None,
+            dkg_author: None,
+            dkg_transcript_bytes: None,
+            num_events: inner.events.len() as i64,
+            is_transaction_success: transaction_info.success,
+            transaction_timestamp,
+        };
+        match inner.validator_transaction_type.as_ref() {
+            Some(ValidatorTransactionType::ObservedJwkUpdate(update)) => {
+                validator_transaction.validator_transaction_type = OBSERVED_JWK_UPDATE.to_string();
+                if let Some(jwks) = update.observed_jwks.as_ref() {
+                    validator_transaction.jwk_issuer = Some(jwks.issuer.clone());
+                    validator_transaction.jwk_version = Some(jwks.version as i64);
+                    validator_transaction.num_jwks = Some(jwks.jwks.len() as i64);
+                }
+                validator_transaction.jwk_signer_indices = update
+                    .multi_sig
+                    .as_ref()
+                    .map(|multi_sig| serde_json::json!(multi_sig.signer_indices));
+            },
+            Some(ValidatorTransactionType::DkgUpdate(update)) => {
+                validator_transaction.validator_transaction_type = DKG_UPDATE.to_string();
+                if let Some(transcript) = update.dkg_transcript.as_ref() {
+                    validator_transaction.dkg_epoch = Some(transcript.epoch as i64);
+                    validator_transaction.dkg_author =
+                        Some(standardize_address(&transcript.author));
+                    validator_transaction.dkg_transcript_bytes =
+                        Some(transcript.payload.len() as i64);
+                }
+            },
+            None => {
+                tracing::warn!(
+                    transaction_version = txn_version,
+                    "Validator transaction type is missing",
+                );
+            },
+        }
+
+        let events = inner
+            .events
+            .iter()
+            .enumerate()
+            .map(|(index, event)| {
+                let key = event.key.as_ref().unwrap();
+                ValidatorTransactionEvent {
+                    transaction_version: txn_version,
+                    event_index: index as i64,
+                    sequence_number: event.sequence_number as i64,
+                    creation_number: key.creation_number as i64,
+                    account_address: standardize_address(&key.account_address),
+                    type_: event.type_str.clone(),
+                    indexed_type: truncate_str(&event.type_str, EVENT_TYPE_MAX_LENGTH),
+                    data: serde_json::from_str(&event.data).unwrap_or(serde_json::Value::Null),
+                    transaction_timestamp,
+                }
+            })
+            .collect();
+        Some((validator_transaction, events))
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::models::{ValidatorTransaction, ValidatorTransactionEvent};
+use anyhow::Result;
+use cedra_indexer_processor_sdk::{
+    cedra_protos::transaction::v1::Transaction,
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+
+/// Extracts validator transactions and their events
+pub struct ValidatorTransactionExtractor
+where
+    Self: Sized + Send + 'static, {}
+
+#[async_trait]
+impl Processable for ValidatorTransactionExtractor {
+    type Input = Vec<Transaction>;
+    type Output = (Vec<ValidatorTransaction>, Vec<ValidatorTransactionEvent>);
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        transactions: TransactionContext<Vec<Transaction>>,
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        let mut validator_transactions = vec![];
+        let mut validator_transaction_events = vec![];
+
+        for transaction in transactions.data.iter() {
+            if let Some((validator_transaction, events)) =
+                ValidatorTransaction::from_transaction(transaction)
+            {
+                validator_transactions.push(validator_transaction);
+                validator_transaction_events.extend(events);
+            }
+        }
+
+        Ok(Some(TransactionContext {
+            data: (validator_transactions, validator_transaction_events),
+            metadata: transactions.metadata,
+        }))
+    }
+}
+
+impl AsyncStep for ValidatorTransactionExtractor {}
+
+impl NamedStep for ValidatorTransactionExtractor {
+    fn name(&self) -> String {
+        "validator_transaction_extractor".to_string()
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::models::{ValidatorTransaction, ValidatorTransactionEvent};
+use crate::{
+    config::processor_config::DefaultProcessorConfig,
+    filter_datasets, schema,
+    utils::{
+        table_flags::{filter_data, TableFlags},
+        table_names::TableNames,
+        table_stats::execute_in_chunks_with_stats,
+    },
+};
+use ahash::AHashMap;
+use anyhow::Result;
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::{get_config_table_chunk_size, ArcDbPool},
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use diesel::{pg::Pg, query_builder::QueryFragment};
+
+pub struct ValidatorTransactionStorer
+where
+    Self: Sized + Send + 'static,
+{
+    conn_pool: ArcDbPool,
+    table_names: TableNames,
+    processor_config: DefaultProcessorConfig,
+    tables_to_write: TableFlags,
+}
+
+impl ValidatorTransactionStorer {
+    pub fn new(
+        conn_pool: ArcDbPool,
+        table_names: TableNames,
+        processor_config: DefaultProcessorConfig,
+        tables_to_write: TableFlags,
+    ) -> Self {
+        Self {
+            conn_pool,
+            table_names,
+            processor_config,
+            tables_to_write,
+        }
+    }
+}
+
+#[async_trait]
+impl Processable for ValidatorTransactionStorer {
+    type Input = (Vec<ValidatorTransaction>, Vec<ValidatorTransactionEvent>);
+    type Output = ();
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        input: TransactionContext<Self::Input>,
+    ) -> Result<Option<TransactionContext<Self::Output>>, ProcessorError> {
+        let (validator_transactions, validator_transaction_events) = input.data;
+
+        let per_table_chunk_sizes: AHashMap<String, usize> =
+            self.processor_config.per_table_chunk_sizes.clone();
+
+        let (validator_transactions, validator_transaction_events) = filter_datasets!(self, {
+            validator_transactions => TableFlags::VALIDATOR_TRANSACTIONS,
+            validator_transaction_events => TableFlags::VALIDATOR_TRANSACTION_EVENTS,
+        });
+
+        let validator_transactions_res = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_validator_transactions_query,
+            &validator_transactions,
+            "validator_transactions",
+            get_config_table_chunk_size::<ValidatorTransaction>(
+                "validator_transactions",
+                &per_table_chunk_sizes,
+            ),
+        );
+        let validator_transaction_events_res = execute_in_chunks_with_stats(
+            self.conn_pool.clone(),
+            &self.table_names,
+            insert_validator_transaction_events_query,
+            &validator_transaction_events,
+            "validator_transaction_events",
+            get_config_table_chunk_size::<ValidatorTransactionEvent>(
+                "validator_transaction_events",
+                &per_table_chunk_sizes,
+            ),
+        );
+
+        futures::try_join!(validator_transactions_res, validator_transaction_events_res)?;
+
+        Ok(Some(TransactionContext {
+            data: (),
+            metadata: input.metadata,
+        }))
+    }
+}
+
+impl AsyncStep for ValidatorTransactionStorer {}
+
+impl NamedStep for ValidatorTransactionStorer {
+    fn name(&self) -> String {
+        "validator_transaction_storer".to_string()
+    }
+}
+
+pub fn insert_validator_transactions_query(
+    items_to_insert: Vec<ValidatorTransaction>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::validator_transactions::dsl::*;
+
+    diesel::insert_into(schema::validator_transactions::table)
+        .values(items_to_insert)
+        .on_conflict(transaction_version)
+        .do_nothing()
+}
+
+pub fn insert_validator_transaction_events_query(
+    items_to_insert: Vec<ValidatorTransactionEvent>,
+) -> impl QueryFragment<Pg> + diesel::query_builder::QueryId + Send {
+    use schema::validator_transaction_events::dsl::*;
+
+    diesel::insert_into(schema::validator_transaction_events::table)
+        .values(items_to_insert)
+        .on_conflict((transaction_version, event_index))
+        .do_nothing()
+}
@@ -0,0 +1,188 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use ahash::AHashMap;
+use anyhow::{bail, Context};
+use cedra_indexer_processor_sdk::utils::convert::standardize_address;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// Longest label the `counterparty_label` columns hold.
+const MAX_LABEL_LENGTH: usize = 100;
+
+/// Labels of known entities, like exchanges, bridges or team wallets, that rows are tagged with
+/// in `counterparty_label`.
+///
+/// The file is a CSV of `address,label` rows, with an optional `address,label` header, or a JSON
+/// array of `{"address": ..., "label": ...}` objects if it ends with `.json`. Labels are read at
+/// startup, so the processor has to be restarted to pick up changes.
+///
+/// Example:
+/// ```yaml
+/// address_labels:
+///   path: "/etc/indexer/address_labels.csv"
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AddressLabelsConfig {
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddressLabel {
+    address: String,
+    label: String,
+}
+
+/// Labeled addresses touched by each transaction version, by address.
+pub type TransactionLabels = AHashMap<i64, BTreeMap<String, String>>;
+
+#[derive(Clone, Debug, Default)]
+pub struct AddressLabels {
+    labels: AHashMap<String, String>,
+}
+
+impl AddressLabels {
+    pub fn load(config: &AddressLabelsConfig) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(&config.path)
+            .with_context(|| format!("Failed to read address labels from {}", config.path))?;
+        let is_json = Path::new(&config.path)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        let address_labels = if is_json {
+            serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse address labels in {}", config.path))?
+        } else {
+            parse_csv(&contents)
+                .with_context(|| format!("Failed to parse address labels in {}", config.path))?
+        };
+        let labels = Self::from_labels(address_labels)?;
+        tracing::info!(
+            path = config.path,
+            label_count = labels.labels.len(),
+            "Loaded address labels"
+        );
+        Ok(labels)
+    }
+
+    fn from_labels(address_labels: Vec<AddressLabel>) -> anyhow::Result<Self> {
+        let mut labels = AHashMap::new();
+        for AddressLabel { address, label } in address_labels {
+            if label.is_empty() || label.len() > MAX_LABEL_LENGTH {
+                bail!(
+                    "Label of {address} must be between 1 and {MAX_LABEL_LENGTH} bytes: {label:?}"
+                );
+            }
+            let address = standardize_address(&address);
+            if let Some(previous) = labels.insert(address.clone(), label) {
+                bail!("Address {address} is labeled more than once, first as {previous:?}");
+            }
+        }
+        Ok(Self { labels })
+    }
+
+    pub fn label(&self, address: &str) -> Option<&str> {
+        self.labels.get(address).map(String::as_str)
+    }
+
+    /// Labeled addresses among the `(transaction_version, address)` pairs, by version.
+    pub fn transaction_labels<'a>(
+        &self,
+        addresses: impl IntoIterator<Item = (i64, &'a str)>,
+    ) -> TransactionLabels {
+        let mut transaction_labels = TransactionLabels::new();
+        for (txn_version, address) in addresses {
+            if let Some(label) = self.label(address) {
+                transaction_labels
+                    .entry(txn_version)
+                    .or_default()
+                    .insert(address.to_string(), label.to_string());
+            }
+        }
+        transaction_labels
+    }
+}
+
+/// Label of the known entity `address` dealt with in the transaction, that is a labeled address
+/// touched by the transaction other than `address`. The lowest address wins if there are several.
+pub fn counterparty_label(
+    transaction_labels: &TransactionLabels,
+    txn_version: i64,
+    address: &str,
+) -> Option<String> {
+    transaction_labels
+        .get(&txn_version)?
+        .iter()
+        .find(|(labeled_address, _)| labeled_address.as_str() != address)
+        .map(|(_, label)| label.clone())
+}
+
+fn parse_csv(contents: &str) -> anyhow::Result<Vec<AddressLabel>> {
+    let mut address_labels = vec![];
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (address, label) = line
+            .split_once(',')
+            .with_context(|| format!("Line {} isn't an address,label row", i + 1))?;
+        let (address, label) = (address.trim(), unquote(label.trim()));
+        if address_labels.is_empty() && address.eq_ignore_ascii_case("address") {
+            continue;
+        }
+        address_labels.push(AddressLabel {
+            address: address.to_string(),
+            label,
+        });
+    }
+    Ok(address_labels)
+}
+
+/// `field` without the quotes of a quoted CSV field, which may contain commas
+fn unquote(field: &str) -> String {
+    match field
+        .strip_prefix('"')
+        .and_then(|field| field.strip_suffix('"'))
+    {
+        Some(field) => field.replace("\"\"", "\""),
+        None => field.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counterparty_label() {
+        let labels = AddressLabels::from_labels(
+            parse_csv("address,label\n0xb,Bridge\n0x0a,\"Exchange, hot wallet\"\n").unwrap(),
+        )
+        .unwrap();
+        let exchange = standardize_address("0xa");
+        let bridge = standardize_address("0xb");
+        let user = standardize_address("0xc");
+        assert_eq!(labels.label(&exchange), Some("Exchange, hot wallet"));
+
+        let transaction_labels = labels.transaction_labels([
+            (1, user.as_str()),
+            (1, exchange.as_str()),
+            (1, bridge.as_str()),
+            (2, user.as_str()),
+        ]);
+        assert_eq!(
+            counterparty_label(&transaction_labels, 1, &user).as_deref(),
+            Some("Exchange, hot wallet")
+        );
+        assert_eq!(
+            counterparty_label(&transaction_labels, 1, &exchange).as_deref(),
+            Some("Bridge")
+        );
+        assert_eq!(counterparty_label(&transaction_labels, 2, &user), None);
+
+        assert!(
+            AddressLabels::from_labels(parse_csv("0xa,Exchange\n0x0a,Bridge").unwrap()).is_err()
+        );
+    }
+}
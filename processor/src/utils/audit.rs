@@ -0,0 +1,373 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Spot audit of a version range for `processor audit`, comparing the rows a processor would
+//! write for the range against the rows stored in Postgres.
+//!
+//! While the audit is on, its `WriteSink` replaces the database. It counts the rows it's
+//! given and looks up the ones without a stored row of the same primary key. Tables with a
+//! `transaction_version` column hold rows of the range only, so their stored rows in the range
+//! are counted too, which catches rows the processor doesn't produce anymore. Current tables can
+//! only be checked for missing rows, since their rows may have been written outside the range.
+
+use crate::{
+    config::{
+        db_config::DbConfig,
+        indexer_processor_config::IndexerProcessorConfig,
+        processor_mode::{ProcessorMode, TestingConfig},
+    },
+    utils::{
+        conflict_simulation::{primary_key_columns, quote_literal},
+        table_names::{quote_identifier, TableNames},
+        write_sink::{add_write_sink, remove_write_sink, TableBatch, WriteSink},
+    },
+};
+use ahash::AHashMap;
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::ArcDbPool, server_framework::RunnableConfig,
+    utils::errors::ProcessorError,
+};
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Jsonb, Text},
+    QueryableByName,
+};
+use diesel_async::RunQueryDsl;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::{
+    fmt::Write,
+    sync::{Arc, Mutex},
+};
+use tracing::warn;
+
+/// Column of tables holding a row per version they were written at
+const VERSION_COLUMN: &str = "transaction_version";
+/// Missing rows listed per table, the rest are only counted
+const MAX_REPORTED_MISSING_ROWS: usize = 20;
+
+#[derive(QueryableByName)]
+struct MissingRow {
+    #[diesel(sql_type = Text)]
+    primary_key: String,
+}
+
+#[derive(QueryableByName)]
+struct RowCount {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(Debug, Default)]
+pub struct TableAudit {
+    pub rows_expected: usize,
+    pub missing_count: usize,
+    /// Primary keys of the first missing rows
+    pub missing: Vec<String>,
+    /// Rows stored in the range, for tables with a `transaction_version` column
+    pub rows_stored: Option<i64>,
+}
+
+impl TableAudit {
+    /// Stored rows in the range the processor didn't produce
+    pub fn unexpected_count(&self) -> usize {
+        self.rows_stored.map_or(0, |rows_stored| {
+            (rows_stored as usize).saturating_sub(self.rows_expected - self.missing_count)
+        })
+    }
+}
+
+#[derive(Default)]
+struct Audit {
+    tables: AHashMap<&'static str, TableAudit>,
+    /// Tables whose stored rows in the range are counted once the range is processed
+    versioned_tables: Vec<&'static str>,
+    conn_pool: Option<ArcDbPool>,
+}
+
+/// Tables audited so far, `None` while no audit is running
+static AUDIT: Lazy<Mutex<Option<Audit>>> = Lazy::new(|| Mutex::new(None));
+
+/// Looks up the rows of every batch instead of writing them
+struct AuditSink {
+    table_names: TableNames,
+}
+
+#[async_trait]
+impl WriteSink for AuditSink {
+    fn name(&self) -> &'static str {
+        "audit"
+    }
+
+    fn replaces_database(&self) -> bool {
+        true
+    }
+
+    async fn before_write(
+        &self,
+        conn_pool: &ArcDbPool,
+        batch: &TableBatch<'_>,
+    ) -> Result<Vec<usize>, ProcessorError> {
+        audit_rows(
+            conn_pool.clone(),
+            &self.table_names,
+            batch.json()?,
+            batch.table_name,
+        )
+        .await?;
+        Ok(vec![])
+    }
+}
+
+/// Processes `starting_version` through `ending_version` without writing, and reports the rows
+/// of every table that differ from what's stored.
+pub async fn audit(
+    mut config: IndexerProcessorConfig,
+    starting_version: u64,
+    ending_version: u64,
+) -> Result<String> {
+    if !matches!(config.db_config, DbConfig::PostgresConfig(_)) {
+        bail!("audit only checks processors writing to Postgres");
+    }
+    // Testing mode doesn't save checkpoints, so the processor's progress isn't touched
+    config.processor_mode = ProcessorMode::Testing(TestingConfig {
+        override_starting_version: starting_version,
+        ending_version: Some(ending_version),
+    });
+    config.supervisor_config.max_restarts = 0;
+    *AUDIT.lock().unwrap() = Some(Audit::default());
+    let table_names = config.table_names();
+    let sink = Arc::new(AuditSink {
+        table_names: table_names.clone(),
+    });
+    add_write_sink(sink.clone())?;
+    let result = config.run().await;
+    remove_write_sink(sink.name());
+    let audit = AUDIT.lock().unwrap().take().unwrap_or_default();
+    result?;
+
+    let mut tables = audit.tables;
+    if let Some(conn_pool) = audit.conn_pool {
+        let mut conn = conn_pool
+            .get()
+            .await
+            .context("Failed to get a connection to count stored rows")?;
+        for table_name in audit.versioned_tables {
+            let table = quote_identifier(&table_names.output_table_name(table_name));
+            let rows_stored = sql_query(format!(
+                "SELECT COUNT(*) AS count FROM {table} \
+                 WHERE {VERSION_COLUMN} BETWEEN $1 AND $2"
+            ))
+            .bind::<BigInt, _>(starting_version as i64)
+            .bind::<BigInt, _>(ending_version as i64)
+            .get_result::<RowCount>(&mut conn)
+            .await
+            .with_context(|| format!("Failed to count the stored rows of {table_name}"))?
+            .count;
+            tables.entry(table_name).or_default().rows_stored = Some(rows_stored);
+        }
+    }
+    Ok(format_report(starting_version, ending_version, tables))
+}
+
+fn format_report(
+    starting_version: u64,
+    ending_version: u64,
+    tables: AHashMap<&'static str, TableAudit>,
+) -> String {
+    let mut tables: Vec<_> = tables.into_iter().collect();
+    tables.sort_by_key(|(table_name, _)| *table_name);
+    let discrepancy_count: usize = tables
+        .iter()
+        .map(|(_, table)| table.missing_count + table.unexpected_count())
+        .sum();
+    let mut report = String::new();
+    writeln!(
+        report,
+        "Versions {starting_version} to {ending_version}: {discrepancy_count} rows differ from \
+         what's stored"
+    )
+    .unwrap();
+    for (table_name, table) in tables {
+        write!(
+            report,
+            "{table_name}: {} rows expected, {} missing",
+            table.rows_expected, table.missing_count
+        )
+        .unwrap();
+        if let Some(rows_stored) = table.rows_stored {
+            write!(
+                report,
+                ", {rows_stored} stored, {} unexpected",
+                table.unexpected_count()
+            )
+            .unwrap();
+        }
+        writeln!(report).unwrap();
+        for primary_key in &table.missing {
+            writeln!(report, "  missing ({primary_key})").unwrap();
+        }
+        if table.missing_count > table.missing.len() {
+            writeln!(
+                report,
+                "  ... and {} more",
+                table.missing_count - table.missing.len()
+            )
+            .unwrap();
+        }
+    }
+    report
+}
+
+fn record_audit(
+    table_name: &'static str,
+    conn_pool: ArcDbPool,
+    rows_expected: usize,
+    missing: Vec<String>,
+    is_versioned: bool,
+) {
+    let mut audit = AUDIT.lock().unwrap();
+    let Some(audit) = audit.as_mut() else {
+        return;
+    };
+    audit.conn_pool.get_or_insert(conn_pool);
+    if is_versioned && !audit.versioned_tables.contains(&table_name) {
+        audit.versioned_tables.push(table_name);
+    }
+    let table = audit.tables.entry(table_name).or_default();
+    table.rows_expected += rows_expected;
+    table.missing_count += missing.len();
+    let room = MAX_REPORTED_MISSING_ROWS.saturating_sub(table.missing.len());
+    table.missing.extend(missing.into_iter().take(room));
+}
+
+/// Looks up the rows of `items_to_insert` that have no stored row in `table_name` with the same
+/// primary key, instead of writing them.
+pub async fn audit_rows<T: Serialize>(
+    conn_pool: ArcDbPool,
+    table_names: &TableNames,
+    items_to_insert: &[T],
+    table_name: &'static str,
+) -> Result<(), ProcessorError> {
+    let rows = serde_json::to_value(items_to_insert).map_err(|e| ProcessorError::DBStoreError {
+        message: format!("Failed to serialize rows of {table_name}: {e:?}"),
+        query: None,
+    })?;
+    let Some(first_row) = rows
+        .as_array()
+        .and_then(|rows| rows.first())
+        .and_then(|row| row.as_object())
+    else {
+        return Ok(());
+    };
+    let is_versioned = first_row.contains_key(VERSION_COLUMN);
+
+    let store_error = |e: diesel::result::Error| ProcessorError::DBStoreError {
+        message: format!("Failed to audit the rows of {table_name}: {e:?}"),
+        query: None,
+    };
+    let mut conn = conn_pool
+        .get()
+        .await
+        .map_err(|e| ProcessorError::DBStoreError {
+            message: format!("Failed to get a connection to audit {table_name}: {e:?}"),
+            query: None,
+        })?;
+    let table = quote_identifier(&table_names.output_table_name(table_name));
+    let primary_key_columns = primary_key_columns(&mut conn, &table)
+        .await
+        .map_err(store_error)?;
+    if primary_key_columns.is_empty()
+        || primary_key_columns
+            .iter()
+            .any(|column| !first_row.contains_key(column))
+    {
+        warn!(
+            table_name = table_name,
+            primary_key_columns = ?primary_key_columns,
+            "Rows don't carry the table's primary key, only counting them"
+        );
+        record_audit(
+            table_name,
+            conn_pool.clone(),
+            items_to_insert.len(),
+            vec![],
+            is_versioned,
+        );
+        return Ok(());
+    }
+
+    let missing = sql_query(missing_rows_query(&table, &primary_key_columns))
+        .bind::<Jsonb, _>(rows)
+        .load::<MissingRow>(&mut conn)
+        .await
+        .map_err(store_error)?
+        .into_iter()
+        .map(|row| row.primary_key)
+        .collect();
+    drop(conn);
+    record_audit(
+        table_name,
+        conn_pool.clone(),
+        items_to_insert.len(),
+        missing,
+        is_versioned,
+    );
+    Ok(())
+}
+
+/// Primary keys of the rows bound as a JSON array that `table` has no row for
+fn missing_rows_query(table: &str, primary_key_columns: &[String]) -> String {
+    let primary_key = primary_key_columns
+        .iter()
+        .map(|column| format!("incoming.item->>{}", quote_literal(column)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let join_condition = primary_key_columns
+        .iter()
+        .map(|column| {
+            format!(
+                "stored.{}::text = incoming.item->>{}",
+                quote_identifier(column),
+                quote_literal(column)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    format!(
+        "SELECT concat_ws(', ', {primary_key}) AS primary_key \
+         FROM jsonb_array_elements($1) AS incoming(item) \
+         WHERE NOT EXISTS (SELECT 1 FROM {table} AS stored WHERE {join_condition})"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_counts_missing_and_unexpected_rows() {
+        let tables = AHashMap::from_iter([
+            ("events", TableAudit {
+                rows_expected: 10,
+                missing_count: 2,
+                missing: vec!["100, 0".to_string(), "100, 1".to_string()],
+                rows_stored: Some(9),
+            }),
+            ("current_objects", TableAudit {
+                rows_expected: 4,
+                ..Default::default()
+            }),
+        ]);
+        assert_eq!(
+            format_report(100, 200, tables),
+            "Versions 100 to 200: 3 rows differ from what's stored\n\
+             current_objects: 4 rows expected, 0 missing\n\
+             events: 10 rows expected, 2 missing, 9 stored, 1 unexpected\n  \
+             missing (100, 0)\n  \
+             missing (100, 1)\n"
+        );
+    }
+}
@@ -0,0 +1,500 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Synthetic transaction batches to benchmark extractors with, see `benches/extractors.rs`.
+
+use cedra_indexer_processor_sdk::{
+    cedra_protos::{
+        transaction::v1::{
+            signature::{Signature as SignatureEnum, Type as SignatureTypeEnum},
+            transaction::{TransactionType, TxnData},
+            transaction_payload::{Payload, Type as PayloadTypeEnum},
+            write_set_change::{Change as WriteSetChangeEnum, Type as WriteSetChangeTypeEnum},
+            Ed25519Signature, EntryFunctionId, EntryFunctionPayload, Event, EventKey,
+            EventSizeInfo, MoveModuleId, MoveStructTag, Signature, Transaction, TransactionInfo,
+            TransactionPayload, TransactionSizeInfo, UserTransaction, UserTransactionRequest,
+            WriteOpSizeInfo, WriteResource, WriteSetChange, WriteTableData, WriteTableItem,
+        },
+        util::timestamp::Timestamp,
+    },
+    traits::Processable,
+    types::transaction_context::{TransactionContext, TransactionMetadata},
+    utils::errors::ProcessorError,
+};
+use prost::Message;
+use serde_json::json;
+
+const FA_METADATA_ADDRESS: &str = "0xa";
+const TABLE_MODULE_ADDRESS: &str = "0xcafe";
+// Offsets keeping the synthetic accounts, stores, collections, tokens and tables apart
+const STORE_ADDRESS_OFFSET: u64 = 1 << 32;
+const COLLECTION_ADDRESS_OFFSET: u64 = 2 << 32;
+const TOKEN_ADDRESS_OFFSET: u64 = 3 << 32;
+const TABLE_HANDLE_OFFSET: u64 = 4 << 32;
+
+/// Relative weights of the kinds of user transactions in a synthetic batch.
+#[derive(Clone, Copy, Debug)]
+pub struct TransactionMix {
+    /// Fungible asset transfers between primary stores
+    pub coin_transfers: u32,
+    /// Token v2 mints into a collection
+    pub nft_mints: u32,
+    /// Writes of a single table item
+    pub table_writes: u32,
+}
+
+impl Default for TransactionMix {
+    fn default() -> Self {
+        Self {
+            coin_transfers: 70,
+            nft_mints: 10,
+            table_writes: 20,
+        }
+    }
+}
+
+/// Generates batches of user transactions shaped like the node streams them: entry function
+/// payloads, ed25519 signatures, events with their fee statement and the write set changes the
+/// events refer to. The same seed always generates the same transactions.
+pub struct SyntheticTransactionGenerator {
+    mix: TransactionMix,
+    num_accounts: u64,
+    num_collections: u64,
+    num_tables: u64,
+    next_version: u64,
+    random_state: u64,
+}
+
+impl SyntheticTransactionGenerator {
+    pub fn new(mix: TransactionMix, seed: u64) -> Self {
+        Self {
+            mix,
+            num_accounts: 10_000,
+            num_collections: 100,
+            num_tables: 100,
+            next_version: 1,
+            // xorshift never leaves zero
+            random_state: seed.max(1),
+        }
+    }
+
+    pub fn next_batch(&mut self, batch_size: usize) -> TransactionContext<Vec<Transaction>> {
+        let start_version = self.next_version;
+        let transactions: Vec<Transaction> =
+            (0..batch_size).map(|_| self.next_transaction()).collect();
+        TransactionContext {
+            metadata: TransactionMetadata {
+                start_version,
+                end_version: self.next_version - 1,
+                start_transaction_timestamp: transactions.first().and_then(|txn| txn.timestamp),
+                end_transaction_timestamp: transactions.last().and_then(|txn| txn.timestamp),
+                total_size_in_bytes: transactions
+                    .iter()
+                    .map(|txn| txn.encoded_len() as u64)
+                    .sum(),
+            },
+            data: transactions,
+        }
+    }
+
+    fn next_transaction(&mut self) -> Transaction {
+        let version = self.next_version;
+        self.next_version += 1;
+        let total_weight =
+            u64::from(self.mix.coin_transfers + self.mix.nft_mints + self.mix.table_writes).max(1);
+        let pick = self.next_random() % total_weight;
+        if pick < u64::from(self.mix.coin_transfers) {
+            self.coin_transfer(version)
+        } else if pick < u64::from(self.mix.coin_transfers + self.mix.nft_mints) {
+            self.nft_mint(version)
+        } else {
+            self.table_write(version)
+        }
+    }
+
+    fn coin_transfer(&mut self, version: u64) -> Transaction {
+        let sender = self.next_random() % self.num_accounts;
+        let receiver =
+            (sender + 1 + self.next_random() % (self.num_accounts - 1)) % self.num_accounts;
+        let amount = 1 + self.next_random() % 1_000_000_000;
+        let (sender_store, receiver_store) = (
+            address(STORE_ADDRESS_OFFSET + sender),
+            address(STORE_ADDRESS_OFFSET + receiver),
+        );
+        let mut changes = vec![];
+        for (owner, store) in [(sender, &sender_store), (receiver, &receiver_store)] {
+            changes.push(object_core(store, &address(owner)));
+            changes.push(write_resource(
+                store,
+                "0x1",
+                "fungible_asset",
+                "FungibleStore",
+                json!({
+                    "metadata": {"inner": FA_METADATA_ADDRESS},
+                    "balance": (self.next_random() % 1_000_000_000_000).to_string(),
+                    "frozen": false,
+                }),
+            ));
+        }
+        let events = vec![
+            event(
+                &sender_store,
+                "0x1::fungible_asset::Withdraw",
+                json!({"store": sender_store, "amount": amount.to_string()}),
+            ),
+            event(
+                &receiver_store,
+                "0x1::fungible_asset::Deposit",
+                json!({"store": receiver_store, "amount": amount.to_string()}),
+            ),
+        ];
+        user_transaction(
+            version,
+            &address(sender),
+            ("0x1", "primary_fungible_store", "transfer"),
+            vec![
+                json!({"inner": FA_METADATA_ADDRESS}).to_string(),
+                address(receiver),
+                amount.to_string(),
+            ],
+            events,
+            changes,
+        )
+    }
+
+    fn nft_mint(&mut self, version: u64) -> Transaction {
+        let collection_index = self.next_random() % self.num_collections;
+        let creator = address(collection_index);
+        let collection = address(COLLECTION_ADDRESS_OFFSET + collection_index);
+        let token = address(TOKEN_ADDRESS_OFFSET + version);
+        let index = (version / self.num_collections).to_string();
+        let name = format!("Token #{index}");
+        let changes = vec![
+            object_core(&token, &creator),
+            write_resource(
+                &token,
+                "0x4",
+                "token",
+                "Token",
+                json!({
+                    "collection": {"inner": collection},
+                    "description": "",
+                    "index": index,
+                    "mutation_events": {
+                        "counter": "0",
+                        "guid": {"id": {"addr": token, "creation_num": "1125899906842627"}},
+                    },
+                    "name": "",
+                    "uri": format!("https://example.com/{collection_index}/{index}.json"),
+                }),
+            ),
+            write_resource(
+                &token,
+                "0x4",
+                "token",
+                "TokenIdentifiers",
+                json!({
+                    "index": {"value": index},
+                    "name": {"padding": "0x", "value": name},
+                }),
+            ),
+            write_resource(
+                &collection,
+                "0x4",
+                "collection",
+                "ConcurrentSupply",
+                json!({
+                    "current_supply": {"max_value": u64::MAX.to_string(), "value": index},
+                    "total_minted": {"max_value": u64::MAX.to_string(), "value": index},
+                }),
+            ),
+        ];
+        let events = vec![event(
+            &collection,
+            "0x4::collection::Mint",
+            json!({"collection": collection, "index": {"value": index}, "token": token}),
+        )];
+        user_transaction(
+            version,
+            &creator,
+            ("0x4", "cedra_token", "mint"),
+            vec![
+                format!("\"Collection {collection_index}\""),
+                format!("\"{name}\""),
+            ],
+            events,
+            changes,
+        )
+    }
+
+    fn table_write(&mut self, version: u64) -> Transaction {
+        let sender = self.next_random() % self.num_accounts;
+        let handle = address(TABLE_HANDLE_OFFSET + self.next_random() % self.num_tables);
+        let value = self.next_random() % 1_000_000;
+        let changes = vec![WriteSetChange {
+            r#type: WriteSetChangeTypeEnum::WriteTableItem as i32,
+            change: Some(WriteSetChangeEnum::WriteTableItem(WriteTableItem {
+                state_key_hash: state_key_hash(&handle, &address(sender)),
+                handle,
+                // The BCS bytes of an address are the address itself
+                key: address(sender),
+                data: Some(WriteTableData {
+                    key: json!(address(sender)).to_string(),
+                    key_type: "address".to_string(),
+                    value: json!({"value": value.to_string(), "updated_at": version.to_string()})
+                        .to_string(),
+                    value_type: format!("{TABLE_MODULE_ADDRESS}::registry::Entry"),
+                }),
+            })),
+        }];
+        user_transaction(
+            version,
+            &address(sender),
+            (TABLE_MODULE_ADDRESS, "registry", "set"),
+            vec![value.to_string()],
+            vec![],
+            changes,
+        )
+    }
+
+    fn next_random(&mut self) -> u64 {
+        let mut x = self.random_state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.random_state = x;
+        x
+    }
+}
+
+fn address(index: u64) -> String {
+    format!("0x{index:064x}")
+}
+
+/// Made up, but unique per resource like the real hash
+fn state_key_hash(address: &str, resource: &str) -> Vec<u8> {
+    use sha2::Digest;
+    sha2::Sha256::digest(format!("{address}::{resource}")).to_vec()
+}
+
+fn write_resource(
+    address: &str,
+    module_address: &str,
+    module: &str,
+    name: &str,
+    data: serde_json::Value,
+) -> WriteSetChange {
+    let type_str = format!("{module_address}::{module}::{name}");
+    WriteSetChange {
+        r#type: WriteSetChangeTypeEnum::WriteResource as i32,
+        change: Some(WriteSetChangeEnum::WriteResource(WriteResource {
+            address: address.to_string(),
+            state_key_hash: state_key_hash(address, &type_str),
+            r#type: Some(MoveStructTag {
+                address: module_address.to_string(),
+                module: module.to_string(),
+                name: name.to_string(),
+                generic_type_params: vec![],
+            }),
+            type_str,
+            data: data.to_string(),
+        })),
+    }
+}
+
+fn object_core(object: &str, owner: &str) -> WriteSetChange {
+    write_resource(
+        object,
+        "0x1",
+        "object",
+        "ObjectCore",
+        json!({
+            "allow_ungated_transfer": true,
+            "guid_creation_num": "1125899906842625",
+            "owner": owner,
+            "transfer_events": {
+                "counter": "0",
+                "guid": {"id": {"addr": object, "creation_num": "1125899906842624"}},
+            },
+        }),
+    )
+}
+
+fn event(account_address: &str, type_str: &str, data: serde_json::Value) -> Event {
+    Event {
+        key: Some(EventKey {
+            creation_number: 0,
+            account_address: account_address.to_string(),
+        }),
+        type_str: type_str.to_string(),
+        data: data.to_string(),
+        ..Default::default()
+    }
+}
+
+fn user_transaction(
+    version: u64,
+    sender: &str,
+    (module_address, module, function): (&str, &str, &str),
+    arguments: Vec<String>,
+    mut events: Vec<Event>,
+    changes: Vec<WriteSetChange>,
+) -> Transaction {
+    let gas_used = 5 + version % 20;
+    events.push(event(
+        "0x0",
+        "0x1::transaction_fee::FeeStatement",
+        json!({
+            "execution_gas_units": "2",
+            "io_gas_units": (gas_used - 2).to_string(),
+            "storage_fee_octas": "0",
+            "storage_fee_refund_octas": "0",
+            "total_charge_gas_units": gas_used.to_string(),
+        }),
+    ));
+    let size_info = TransactionSizeInfo {
+        transaction_bytes: 250,
+        event_size_info: events
+            .iter()
+            .map(|event| EventSizeInfo {
+                type_tag_bytes: event.type_str.len() as u32,
+                total_bytes: (event.type_str.len() + event.data.len()) as u32,
+            })
+            .collect(),
+        write_op_size_info: changes
+            .iter()
+            .map(|change| WriteOpSizeInfo {
+                key_bytes: 64,
+                value_bytes: change.encoded_len() as u32,
+            })
+            .collect(),
+    };
+    let timestamp = Timestamp {
+        // Four transactions per second from 2024-05-01T12:00:00Z
+        seconds: 1714564800 + (version / 4) as i64,
+        nanos: (version % 4 * 250_000_000) as i32,
+    };
+    Transaction {
+        version,
+        epoch: 1 + version / 1_000_000,
+        block_height: version / 10,
+        timestamp: Some(timestamp),
+        r#type: TransactionType::User as i32,
+        info: Some(TransactionInfo {
+            hash: state_key_hash(sender, &version.to_string()),
+            gas_used,
+            success: true,
+            vm_status: "Executed successfully".to_string(),
+            changes,
+            ..Default::default()
+        }),
+        size_info: Some(size_info),
+        txn_data: Some(TxnData::User(UserTransaction {
+            request: Some(UserTransactionRequest {
+                sender: sender.to_string(),
+                sequence_number: version,
+                max_gas_amount: 200_000,
+                gas_unit_price: 100,
+                expiration_timestamp_secs: Some(Timestamp {
+                    seconds: timestamp.seconds + 600,
+                    nanos: 0,
+                }),
+                payload: Some(TransactionPayload {
+                    r#type: PayloadTypeEnum::EntryFunctionPayload as i32,
+                    payload: Some(Payload::EntryFunctionPayload(EntryFunctionPayload {
+                        function: Some(EntryFunctionId {
+                            module: Some(MoveModuleId {
+                                address: module_address.to_string(),
+                                name: module.to_string(),
+                            }),
+                            name: function.to_string(),
+                        }),
+                        arguments,
+                        entry_function_id_str: format!("{module_address}::{module}::{function}"),
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                }),
+                signature: Some(Signature {
+                    r#type: SignatureTypeEnum::Ed25519 as i32,
+                    signature: Some(SignatureEnum::Ed25519(Ed25519Signature {
+                        public_key: state_key_hash(sender, "public_key"),
+                        signature: [state_key_hash(sender, "r"), state_key_hash(sender, "s")]
+                            .concat(),
+                    })),
+                }),
+            }),
+            events,
+        })),
+        ..Default::default()
+    }
+}
+
+/// Number of rows in an extractor's output, whichever tables it's split into.
+pub trait RowCount {
+    fn row_count(&self) -> usize;
+}
+
+impl<T> RowCount for Vec<T> {
+    fn row_count(&self) -> usize {
+        self.len()
+    }
+}
+
+macro_rules! impl_row_count_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: RowCount),+> RowCount for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn row_count(&self) -> usize {
+                let ($($name,)+) = self;
+                0 $(+ $name.row_count())+
+            }
+        }
+    };
+}
+
+impl_row_count_for_tuple!(A, B);
+impl_row_count_for_tuple!(A, B, C);
+impl_row_count_for_tuple!(A, B, C, D);
+impl_row_count_for_tuple!(A, B, C, D, E);
+impl_row_count_for_tuple!(A, B, C, D, E, F);
+impl_row_count_for_tuple!(A, B, C, D, E, F, G);
+impl_row_count_for_tuple!(A, B, C, D, E, F, G, H);
+impl_row_count_for_tuple!(A, B, C, D, E, F, G, H, I);
+impl_row_count_for_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_row_count_for_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_row_count_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+impl_row_count_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M);
+impl_row_count_for_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
+
+/// Runs `extractor` on `batch` like the pipeline would, returning how many rows it extracted.
+pub async fn run_extractor<E>(
+    extractor: &mut E,
+    batch: TransactionContext<Vec<Transaction>>,
+) -> Result<usize, ProcessorError>
+where
+    E: Processable<Input = Vec<Transaction>>,
+    E::Output: RowCount,
+{
+    Ok(extractor
+        .process(batch)
+        .await?
+        .map_or(0, |output| output.data.row_count()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator_is_deterministic() {
+        let batch = |seed| {
+            SyntheticTransactionGenerator::new(TransactionMix::default(), seed).next_batch(100)
+        };
+        let (first, second) = (batch(7), batch(7));
+        assert_eq!(first.data, second.data);
+        assert_eq!(first.metadata.start_version, 1);
+        assert_eq!(first.metadata.end_version, 100);
+        assert_ne!(first.data, batch(8).data);
+    }
+}
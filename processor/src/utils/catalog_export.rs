@@ -0,0 +1,266 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Describes the tables the processors write as dbt sources, so downstream catalogs can be
+//! regenerated on every release instead of being kept in sync by hand.
+
+use crate::{
+    config::{
+        explain_config::{is_parquet_processor, processor_table_flags},
+        processor_config::{ProcessorConfig, ProcessorName},
+    },
+    db::schema_drift::{expected_tables, ExpectedTable},
+    parquet_processors::{ParquetTypeEnum, ParquetTypeStructs, ParquetTypeTrait},
+};
+use anyhow::Result;
+use parquet::{basic::ConvertedType, schema::types::Type};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use strum::IntoEnumIterator;
+
+pub const DEFAULT_POSTGRES_SOURCE_NAME: &str = "cedra_indexer";
+pub const DEFAULT_PARQUET_SOURCE_NAME: &str = "cedra_indexer_parquet";
+
+/// `sources.yml` as read by dbt
+#[derive(Debug, Serialize)]
+pub struct DbtSources {
+    pub version: u32,
+    pub sources: Vec<DbtSource>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DbtSource {
+    pub name: String,
+    pub description: String,
+    pub tables: Vec<DbtTable>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DbtTable {
+    pub name: String,
+    pub description: String,
+    pub meta: DbtTableMeta,
+    pub columns: Vec<DbtColumn>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DbtTableMeta {
+    pub processors: Vec<String>,
+    /// Empty for parquet tables, files have no keys
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub primary_key: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DbtColumn {
+    pub name: String,
+    /// Unset for types the export doesn't know
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<String>,
+    pub meta: DbtColumnMeta,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DbtColumnMeta {
+    pub nullable: bool,
+}
+
+/// Postgres tables each processor can write, opt-in tables included
+fn postgres_tables_by_processor() -> BTreeMap<String, BTreeSet<String>> {
+    let mut tables: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for processor in ProcessorName::iter().filter(|p| !is_parquet_processor(*p)) {
+        let table_names: Vec<String> = match processor {
+            ProcessorName::EventsProcessor => vec![
+                "events".to_string(),
+                "event_attributes".to_string(),
+                "events_compacted".to_string(),
+            ],
+            _ => processor_table_flags(processor)
+                .iter_names()
+                .map(|(name, _)| match name {
+                    // The only table whose flag isn't named after it
+                    "TABLE_METADATA" => "table_metadatas".to_string(),
+                    _ => name.to_lowercase(),
+                })
+                .collect(),
+        };
+        for table_name in table_names {
+            tables
+                .entry(table_name)
+                .or_default()
+                .insert(processor.to_string());
+        }
+    }
+    tables
+}
+
+/// Parquet tables each parquet processor can write
+fn parquet_tables_by_processor() -> BTreeMap<String, BTreeSet<String>> {
+    let mut tables: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for processor in ProcessorName::iter().filter(|p| is_parquet_processor(*p)) {
+        for table_name in ProcessorConfig::table_names(&processor) {
+            tables
+                .entry(table_name)
+                .or_default()
+                .insert(processor.to_string());
+        }
+    }
+    tables
+}
+
+fn postgres_columns(table: &ExpectedTable) -> Vec<DbtColumn> {
+    table
+        .columns
+        .iter()
+        .map(|column| DbtColumn {
+            name: column.name.clone(),
+            // Array types are named after their element with a leading underscore
+            data_type: column
+                .sql_type()
+                .map(|sql_type| match sql_type.strip_prefix('_') {
+                    Some(element_type) => format!("{element_type}[]"),
+                    None => sql_type,
+                }),
+            meta: DbtColumnMeta {
+                nullable: column.nullable,
+            },
+        })
+        .collect()
+}
+
+fn parquet_columns(schema: &Type) -> Vec<DbtColumn> {
+    schema
+        .get_fields()
+        .iter()
+        .map(|field| {
+            let data_type = match field.get_basic_info().converted_type() {
+                _ if !field.is_primitive() => "GROUP".to_string(),
+                ConvertedType::NONE => field.get_physical_type().to_string(),
+                converted_type => converted_type.to_string(),
+            };
+            DbtColumn {
+                name: field.name().to_string(),
+                data_type: Some(data_type),
+                meta: DbtColumnMeta {
+                    nullable: field.is_optional(),
+                },
+            }
+        })
+        .collect()
+}
+
+fn table_description(processors: &BTreeSet<String>) -> String {
+    format!(
+        "Written by {}",
+        processors.iter().cloned().collect::<Vec<_>>().join(", ")
+    )
+}
+
+/// Builds the dbt sources for every table the processors write, with column types taken from
+/// `schema.rs` for Postgres and from the row structs for parquet.
+pub fn dbt_sources(postgres_source_name: &str, parquet_source_name: &str) -> Result<DbtSources> {
+    let expected_tables: BTreeMap<String, ExpectedTable> = expected_tables()
+        .into_iter()
+        .map(|table| (table.name.clone(), table))
+        .collect();
+    let mut postgres_tables = vec![];
+    for (table_name, processors) in postgres_tables_by_processor() {
+        let table = expected_tables.get(&table_name).ok_or_else(|| {
+            anyhow::anyhow!("{table_name} is written by a processor but isn't in schema.rs")
+        })?;
+        postgres_tables.push(DbtTable {
+            description: table_description(&processors),
+            meta: DbtTableMeta {
+                processors: processors.into_iter().collect(),
+                primary_key: table.primary_key.clone(),
+            },
+            columns: postgres_columns(table),
+            name: table_name,
+        });
+    }
+
+    let mut parquet_tables = vec![];
+    for (table_name, processors) in parquet_tables_by_processor() {
+        let parquet_type = ParquetTypeEnum::iter()
+            .find(|parquet_type| parquet_type.to_string() == table_name)
+            .ok_or_else(|| anyhow::anyhow!("{table_name} has no parquet type"))?;
+        let schema = ParquetTypeStructs::default_for_type(&parquet_type).schema();
+        parquet_tables.push(DbtTable {
+            description: table_description(&processors),
+            meta: DbtTableMeta {
+                processors: processors.into_iter().collect(),
+                primary_key: vec![],
+            },
+            columns: parquet_columns(&schema),
+            name: table_name,
+        });
+    }
+
+    Ok(DbtSources {
+        version: 2,
+        sources: vec![
+            DbtSource {
+                name: postgres_source_name.to_string(),
+                description: "Tables written by the Postgres processors".to_string(),
+                tables: postgres_tables,
+            },
+            DbtSource {
+                name: parquet_source_name.to_string(),
+                description: "Tables written by the parquet processors, one directory per table"
+                    .to_string(),
+                tables: parquet_tables,
+            },
+        ],
+    })
+}
+
+/// `dbt_sources` as the YAML of a `sources.yml` file
+pub fn dbt_sources_yaml(postgres_source_name: &str, parquet_source_name: &str) -> Result<String> {
+    Ok(serde_yaml::to_string(&dbt_sources(
+        postgres_source_name,
+        parquet_source_name,
+    )?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dbt_sources_cover_every_processor_table() {
+        let sources =
+            dbt_sources(DEFAULT_POSTGRES_SOURCE_NAME, DEFAULT_PARQUET_SOURCE_NAME).unwrap();
+        let postgres = &sources.sources[0];
+        let events = postgres
+            .tables
+            .iter()
+            .find(|table| table.name == "events")
+            .unwrap();
+        assert_eq!(events.meta.processors, vec!["events_processor"]);
+        assert_eq!(events.meta.primary_key, vec![
+            "transaction_version",
+            "event_index"
+        ]);
+        let account_address = events
+            .columns
+            .iter()
+            .find(|column| column.name == "account_address")
+            .unwrap();
+        assert_eq!(account_address.data_type.as_deref(), Some("varchar(66)"));
+        assert!(!account_address.meta.nullable);
+
+        let parquet = &sources.sources[1];
+        let parquet_events = parquet
+            .tables
+            .iter()
+            .find(|table| table.name == "events")
+            .unwrap();
+        assert_eq!(parquet_events.meta.processors, vec![
+            "parquet_events_processor"
+        ]);
+        assert!(parquet_events
+            .columns
+            .iter()
+            .any(|column| column.name == "txn_version"));
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    config::{indexer_processor_config::IndexerProcessorConfig, processor_mode::ProcessorMode},
+    utils::{
+        logging::{log_filter, set_log_level},
+        table_flags::{set_reloaded_tables_to_write, TableFlags},
+    },
+};
+use anyhow::{Context, Result};
+use cedra_indexer_processor_sdk::cedra_indexer_transaction_stream::TransactionStreamConfig;
+use once_cell::sync::Lazy;
+use std::{path::Path, sync::RwLock};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+/// Transaction stream config from the latest reload that changed the transaction filter. Runs
+/// started after the reload use its filter.
+static RELOADED_TRANSACTION_STREAM_CONFIG: Lazy<RwLock<Option<TransactionStreamConfig>>> =
+    Lazy::new(|| RwLock::new(None));
+
+/// Notified when a reload needs the running step graph to be rebuilt.
+pub static RESTART_REQUESTED: Lazy<Notify> = Lazy::new(Notify::new);
+
+pub fn reloaded_transaction_stream_config() -> Option<TransactionStreamConfig> {
+    RELOADED_TRANSACTION_STREAM_CONFIG.read().unwrap().clone()
+}
+
+/// Reads the `server_config` section of the processor config file.
+pub fn load_server_config(config_path: &Path) -> Result<IndexerProcessorConfig> {
+    let contents = std::fs::read_to_string(config_path)
+        .with_context(|| format!("Failed to read config file {}", config_path.display()))?;
+    let config: serde_yaml::Value = serde_yaml::from_str(&contents)?;
+    let server_config = config
+        .get("server_config")
+        .cloned()
+        .context("Config file has no server_config")?;
+    Ok(serde_yaml::from_value(server_config)?)
+}
+
+/// Applies the parts of a reloaded config that can change while the processor is running:
+/// - `log_level` applies to everything logged from then on. Removing it goes back to `RUST_LOG`.
+/// - `tables_to_write` takes effect from the next batch written.
+/// - A changed transaction filter restarts the step graph from the last committed version, the
+///   stream has to be reopened for the filter to apply.
+///
+/// Everything else still needs a process restart. Returns false if the reloaded config was
+/// rejected.
+pub fn apply_reloaded_config(
+    current: &IndexerProcessorConfig,
+    reloaded: &IndexerProcessorConfig,
+) -> bool {
+    let processor_name = current.processor_config.name();
+    if reloaded.processor_config.name() != processor_name {
+        warn!(
+            processor_name = processor_name,
+            reloaded_processor_name = reloaded.processor_config.name(),
+            "Reloaded config is for a different processor, ignoring it"
+        );
+        return false;
+    }
+
+    if reloaded.log_level != current.log_level {
+        let applied = log_filter(reloaded.log_level.as_deref()).and_then(set_log_level);
+        if let Err(e) = applied {
+            warn!(error = ?e, "Failed to apply reloaded log_level, ignoring the reloaded config");
+            return false;
+        }
+        info!(
+            processor_name = processor_name,
+            log_level = ?reloaded.log_level,
+            "Applied reloaded log_level"
+        );
+    }
+
+    match reloaded.processor_config.tables_to_write() {
+        Some(tables_to_write)
+            if Some(tables_to_write) != current.processor_config.tables_to_write() =>
+        {
+            info!(
+                processor_name = processor_name,
+                tables_to_write = ?tables_to_write,
+                "Applying reloaded tables_to_write"
+            );
+            set_reloaded_tables_to_write(TableFlags::from_set(tables_to_write));
+        },
+        _ => {},
+    }
+
+    let filter_changed =
+        serde_json::to_value(&reloaded.transaction_stream_config.transaction_filter).ok()
+            != serde_json::to_value(&current.transaction_stream_config.transaction_filter).ok();
+    if filter_changed {
+        info!(
+            processor_name = processor_name,
+            "Transaction filter changed, restarting from the last committed version"
+        );
+        *RELOADED_TRANSACTION_STREAM_CONFIG.write().unwrap() =
+            Some(reloaded.transaction_stream_config.clone());
+        RESTART_REQUESTED.notify_waiters();
+    }
+    true
+}
+
+/// Reloads the config file every time the process receives SIGHUP, except in sharded mode.
+#[cfg(unix)]
+pub fn spawn_config_reloader(config_path: std::path::PathBuf) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    tokio::spawn(async move {
+        let mut current = match load_server_config(&config_path) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!(error = ?e, "Failed to load config, hot reload is disabled");
+                return;
+            },
+        };
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(e) => {
+                warn!(error = ?e, "Failed to listen for SIGHUP, hot reload is disabled");
+                return;
+            },
+        };
+        while hangups.recv().await.is_some() {
+            // The shards of a sharded run would share the reloaded state and restart together
+            if matches!(current.processor_mode, ProcessorMode::Sharded(_)) {
+                warn!("Received SIGHUP, config reloads are ignored in sharded mode");
+                continue;
+            }
+            info!(config_path = ?config_path, "Received SIGHUP, reloading config");
+            match load_server_config(&config_path) {
+                Ok(reloaded) => {
+                    if apply_reloaded_config(&current, &reloaded) {
+                        current = reloaded;
+                    }
+                },
+                Err(e) => warn!(error = ?e, "Failed to reload config, keeping the current one"),
+            }
+        }
+    });
+}
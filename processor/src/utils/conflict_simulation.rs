@@ -0,0 +1,365 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Dry run of a version range for `processor explain-conflicts`, reporting the rows of current
+//! tables that data of a lower version would overwrite.
+//!
+//! While the simulation is on, its `WriteSink` replaces the database. It looks up the
+//! stored rows sharing a primary key with the rows it's given and records those whose
+//! `last_transaction_version` columns are higher. Upserts filtered on the version skip such rows,
+//! but unfiltered ones regress them, which is what a backfill reprocessing old versions over
+//! newer data runs into.
+
+use crate::{
+    config::{
+        db_config::DbConfig,
+        indexer_processor_config::IndexerProcessorConfig,
+        processor_mode::{ProcessorMode, TestingConfig},
+    },
+    utils::{
+        table_names::{quote_identifier, TableNames},
+        write_sink::{add_write_sink, remove_write_sink, TableBatch, WriteSink},
+    },
+};
+use ahash::AHashMap;
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::{ArcDbPool, DbPoolConnection},
+    server_framework::RunnableConfig,
+    utils::errors::ProcessorError,
+};
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Jsonb, Text},
+    QueryableByName,
+};
+use diesel_async::RunQueryDsl;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::{
+    collections::BTreeSet,
+    fmt::Write,
+    sync::{Arc, Mutex},
+};
+use tracing::warn;
+
+/// Columns holding the version a row was last written at, e.g. `last_transaction_version` or
+/// `last_transaction_version_v1`
+const VERSION_COLUMN_PREFIX: &str = "last_transaction_version";
+/// Conflicts listed per table, the rest are only counted
+const MAX_REPORTED_CONFLICTS: usize = 20;
+
+#[derive(Clone, Debug, QueryableByName)]
+pub struct Conflict {
+    #[diesel(sql_type = Text)]
+    pub primary_key: String,
+    #[diesel(sql_type = Text)]
+    pub version_column: String,
+    #[diesel(sql_type = BigInt)]
+    pub stored_version: i64,
+    #[diesel(sql_type = BigInt)]
+    pub incoming_version: i64,
+}
+
+#[derive(Debug, Default)]
+pub struct TableConflicts {
+    pub rows_checked: usize,
+    pub conflict_count: usize,
+    pub conflicts: Vec<Conflict>,
+}
+
+#[derive(QueryableByName)]
+struct PrimaryKeyColumn {
+    #[diesel(sql_type = Text)]
+    column_name: String,
+}
+
+/// Conflicts found per current table, `None` while the simulation is off
+static CONFLICTS: Lazy<Mutex<Option<AHashMap<&'static str, TableConflicts>>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// Checks the batches of current tables for conflicts instead of writing them
+struct ConflictSimulationSink {
+    table_names: TableNames,
+}
+
+#[async_trait]
+impl WriteSink for ConflictSimulationSink {
+    fn name(&self) -> &'static str {
+        "conflict_simulation"
+    }
+
+    fn replaces_database(&self) -> bool {
+        true
+    }
+
+    async fn before_write(
+        &self,
+        conn_pool: &ArcDbPool,
+        batch: &TableBatch<'_>,
+    ) -> Result<Vec<usize>, ProcessorError> {
+        simulate_upsert(
+            conn_pool.clone(),
+            &self.table_names,
+            batch.json()?,
+            batch.table_name,
+        )
+        .await?;
+        Ok(vec![])
+    }
+}
+
+/// Processes `starting_version` through `ending_version` without writing, and reports the rows
+/// of current tables that would be overwritten by data of a lower version.
+pub async fn explain_conflicts(
+    mut config: IndexerProcessorConfig,
+    starting_version: u64,
+    ending_version: u64,
+) -> Result<String> {
+    if !matches!(config.db_config, DbConfig::PostgresConfig(_)) {
+        bail!("explain-conflicts only simulates processors writing to Postgres");
+    }
+    if starting_version > ending_version {
+        bail!("starting version {starting_version} is after ending version {ending_version}");
+    }
+    // Testing mode doesn't save checkpoints, so the processor's progress isn't touched
+    config.processor_mode = ProcessorMode::Testing(TestingConfig {
+        override_starting_version: starting_version,
+        ending_version: Some(ending_version),
+    });
+    config.supervisor_config.max_restarts = 0;
+    *CONFLICTS.lock().unwrap() = Some(AHashMap::new());
+    let sink = Arc::new(ConflictSimulationSink {
+        table_names: config.table_names(),
+    });
+    add_write_sink(sink.clone())?;
+    let result = config.run().await;
+    remove_write_sink(sink.name());
+    let conflicts = CONFLICTS.lock().unwrap().take().unwrap_or_default();
+    result?;
+
+    Ok(format_report(starting_version, ending_version, conflicts))
+}
+
+fn format_report(
+    starting_version: u64,
+    ending_version: u64,
+    conflicts: AHashMap<&'static str, TableConflicts>,
+) -> String {
+    let mut tables: Vec<_> = conflicts.into_iter().collect();
+    tables.sort_by_key(|(table_name, _)| *table_name);
+    let conflict_count: usize = tables.iter().map(|(_, table)| table.conflict_count).sum();
+    let mut report = String::new();
+    writeln!(
+        report,
+        "Versions {starting_version} to {ending_version}: {conflict_count} rows of current tables \
+         would be overwritten by a lower version"
+    )
+    .unwrap();
+    for (table_name, table) in tables {
+        writeln!(
+            report,
+            "{table_name}: {} of {} rows checked",
+            table.conflict_count, table.rows_checked
+        )
+        .unwrap();
+        for conflict in &table.conflicts {
+            writeln!(
+                report,
+                "  ({}) {} stored at {}, incoming at {}",
+                conflict.primary_key,
+                conflict.version_column,
+                conflict.stored_version,
+                conflict.incoming_version
+            )
+            .unwrap();
+        }
+        if table.conflict_count > table.conflicts.len() {
+            writeln!(
+                report,
+                "  ... and {} more",
+                table.conflict_count - table.conflicts.len()
+            )
+            .unwrap();
+        }
+    }
+    report
+}
+
+fn record_conflicts(table_name: &'static str, rows_checked: usize, conflicts: Vec<Conflict>) {
+    let mut all_conflicts = CONFLICTS.lock().unwrap();
+    let Some(all_conflicts) = all_conflicts.as_mut() else {
+        return;
+    };
+    let table = all_conflicts.entry(table_name).or_default();
+    table.rows_checked += rows_checked;
+    table.conflict_count += conflicts.len();
+    let room = MAX_REPORTED_CONFLICTS.saturating_sub(table.conflicts.len());
+    table.conflicts.extend(conflicts.into_iter().take(room));
+}
+
+/// Looks up the stored rows the upsert of `items_to_insert` into `table_name` would overwrite
+/// with a lower version, instead of running it. Tables without a `last_transaction_version`
+/// column aren't current tables and are skipped.
+pub async fn simulate_upsert<T: Serialize>(
+    conn_pool: ArcDbPool,
+    table_names: &TableNames,
+    items_to_insert: &[T],
+    table_name: &'static str,
+) -> Result<(), ProcessorError> {
+    let rows = serde_json::to_value(items_to_insert).map_err(|e| ProcessorError::DBStoreError {
+        message: format!("Failed to serialize rows of {table_name}: {e:?}"),
+        query: None,
+    })?;
+    let Some(first_row) = rows
+        .as_array()
+        .and_then(|rows| rows.first())
+        .and_then(|row| row.as_object())
+    else {
+        return Ok(());
+    };
+    let version_columns: BTreeSet<String> = first_row
+        .keys()
+        .filter(|column| column.starts_with(VERSION_COLUMN_PREFIX))
+        .cloned()
+        .collect();
+    if version_columns.is_empty() {
+        return Ok(());
+    }
+
+    let store_error = |e: diesel::result::Error| ProcessorError::DBStoreError {
+        message: format!("Failed to simulate the upsert into {table_name}: {e:?}"),
+        query: None,
+    };
+    let mut conn = conn_pool
+        .get()
+        .await
+        .map_err(|e| ProcessorError::DBStoreError {
+            message: format!("Failed to get a connection to simulate {table_name}: {e:?}"),
+            query: None,
+        })?;
+    let table = quote_identifier(&table_names.output_table_name(table_name));
+    let primary_key_columns = primary_key_columns(&mut conn, &table)
+        .await
+        .map_err(store_error)?;
+    if primary_key_columns.is_empty()
+        || primary_key_columns
+            .iter()
+            .any(|column| !first_row.contains_key(column))
+    {
+        warn!(
+            table_name = table_name,
+            primary_key_columns = ?primary_key_columns,
+            "Rows don't carry the table's primary key, skipping the conflict check"
+        );
+        return Ok(());
+    }
+
+    let conflicts = sql_query(conflict_query(
+        &table,
+        &primary_key_columns,
+        &version_columns,
+    ))
+    .bind::<Jsonb, _>(rows)
+    .load::<Conflict>(&mut conn)
+    .await
+    .map_err(store_error)?;
+    record_conflicts(table_name, items_to_insert.len(), conflicts);
+    Ok(())
+}
+
+/// Primary key columns of the quoted `table`, in key order
+pub(crate) async fn primary_key_columns(
+    conn: &mut DbPoolConnection<'_>,
+    table: &str,
+) -> diesel::QueryResult<Vec<String>> {
+    Ok(sql_query(
+        "SELECT a.attname::text AS column_name
+        FROM pg_index i
+        JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey)
+        WHERE i.indrelid = $1::regclass AND i.indisprimary
+        ORDER BY array_position(i.indkey::int2[], a.attnum)",
+    )
+    .bind::<Text, _>(table)
+    .load::<PrimaryKeyColumn>(conn)
+    .await?
+    .into_iter()
+    .map(|column| column.column_name)
+    .collect())
+}
+
+/// Stored rows of `table` with a higher version than the incoming rows bound as a JSON array,
+/// matched by primary key
+fn conflict_query(
+    table: &str,
+    primary_key_columns: &[String],
+    version_columns: &BTreeSet<String>,
+) -> String {
+    let primary_key = primary_key_columns
+        .iter()
+        .map(|column| format!("stored.{}::text", quote_identifier(column)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let join_condition = primary_key_columns
+        .iter()
+        .map(|column| {
+            format!(
+                "stored.{}::text = incoming.item->>{}",
+                quote_identifier(column),
+                quote_literal(column)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    version_columns
+        .iter()
+        .map(|column| {
+            let incoming_version = format!("(incoming.item->>{})::bigint", quote_literal(column));
+            format!(
+                "SELECT concat_ws(', ', {primary_key}) AS primary_key, \
+                 {} AS version_column, \
+                 stored.{column_identifier} AS stored_version, \
+                 {incoming_version} AS incoming_version \
+                 FROM jsonb_array_elements($1) AS incoming(item) \
+                 JOIN {table} AS stored ON {join_condition} \
+                 WHERE stored.{column_identifier} > {incoming_version}",
+                quote_literal(column),
+                column_identifier = quote_identifier(column),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ")
+}
+
+pub(crate) fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflict_query_checks_every_version_column() {
+        let query = conflict_query(
+            "\"current_fungible_asset_balances\"",
+            &["storage_id".to_string()],
+            &BTreeSet::from([
+                "last_transaction_version_v1".to_string(),
+                "last_transaction_version_v2".to_string(),
+            ]),
+        );
+        let selects: Vec<&str> = query.split(" UNION ALL ").collect();
+        assert_eq!(selects.len(), 2);
+        assert!(selects[0].contains(
+            "JOIN \"current_fungible_asset_balances\" AS stored ON \
+             stored.\"storage_id\"::text = incoming.item->>'storage_id'"
+        ));
+        assert!(selects[0].ends_with(
+            "WHERE stored.\"last_transaction_version_v1\" > \
+             (incoming.item->>'last_transaction_version_v1')::bigint"
+        ));
+        assert!(selects[1].contains("'last_transaction_version_v2' AS version_column"));
+    }
+}
@@ -177,6 +177,146 @@ pub static NUM_TRANSACTIONS_FILTERED_OUT_COUNT: Lazy<IntCounterVec> = Lazy::new(
     .unwrap()
 });
 
+/// Version through which every table written by a processor is committed
+pub static PROCESSOR_CONSISTENCY_WATERMARK: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "indexer_processor_consistency_watermark",
+        "Version through which every table written by a processor is committed",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Heartbeats that failed to be written, retried on the processor's next status save
+pub static PROCESSOR_HEARTBEAT_FAILURES_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_heartbeat_failures",
+        "Heartbeats that failed to be written, retried on the processor's next status save",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Writes queued for the replica that it hasn't applied yet
+pub static REPLICA_PENDING_WRITES: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "indexer_processor_replica_pending_writes",
+        "Writes queued for the replica that it hasn't applied yet",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Time between the primary committing a write and the replica applying it
+pub static REPLICA_WRITE_LAG_IN_SECS: Lazy<GaugeVec> = Lazy::new(|| {
+    register_gauge_vec!(
+        "indexer_processor_replica_write_lag_in_secs",
+        "Time between the primary committing a write and the replica applying it",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Writes to the replica that failed
+pub static REPLICA_WRITE_ERRORS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_replica_write_errors",
+        "Writes to the replica that failed",
+        &["processor_name", "table_name"]
+    )
+    .unwrap()
+});
+
+/// Writes dropped because the replica's queue was full
+pub static REPLICA_WRITES_DROPPED_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_replica_writes_dropped",
+        "Writes dropped because the replica's queue was full",
+        &["processor_name", "table_name"]
+    )
+    .unwrap()
+});
+
+/// Rows the storers wrote to a table, by whether they were written or ignored on conflict
+pub static TABLE_WRITE_ROWS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_table_write_rows",
+        "Rows the storers wrote to a table, by whether they were written or ignored on conflict",
+        &["table_name", "result"]
+    )
+    .unwrap()
+});
+
+/// Rows of current tables not written because the stored row has a higher version
+pub static STALE_WRITES_REJECTED_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_stale_writes_rejected",
+        "Rows of current tables not written because the stored row has a higher version",
+        &["table_name"]
+    )
+    .unwrap()
+});
+
+/// Rows that violated a data quality rule, whether or not they were quarantined
+pub static DATA_QUALITY_VIOLATIONS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_data_quality_violations",
+        "Rows that violated a data quality rule, whether or not they were quarantined",
+        &["table_name", "rule"]
+    )
+    .unwrap()
+});
+
+/// JSON values replaced by a json_column_caps rule for being larger than its `max_bytes`
+pub static JSON_VALUES_CAPPED_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_json_values_capped",
+        "JSON values replaced by a json_column_caps rule for being larger than its max_bytes",
+        &["table_name", "column"]
+    )
+    .unwrap()
+});
+
+/// Lookups in the shared metadata cache, by cache and whether the key was cached
+pub static METADATA_CACHE_LOOKUPS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_metadata_cache_lookups",
+        "Lookups in the shared metadata cache, by cache and whether the key was cached",
+        &["cache", "result"]
+    )
+    .unwrap()
+});
+
+/// Runs of scheduled maintenance jobs, by whether they succeeded
+pub static SCHEDULED_JOB_RUNS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_scheduled_job_runs",
+        "Runs of scheduled maintenance jobs, by processor, job and result",
+        &["processor_name", "job_name", "result"]
+    )
+    .unwrap()
+});
+
+/// Number of times the supervisor has restarted a processor after a failure
+pub static PROCESSOR_RESTART_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_restart_count",
+        "Number of times the supervisor has restarted a processor after a failure",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of times the transaction stream moved off a failed gRPC endpoint
+pub static GRPC_STREAM_FAILOVER_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_grpc_stream_failover_count",
+        "Number of times the transaction stream moved off a failed gRPC endpoint",
+        &["processor_name", "endpoint"]
+    )
+    .unwrap()
+});
+
 /// Size of the channel containing transactions fetched from GRPC, waiting to be processed
 pub static FETCHER_THREAD_CHANNEL_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!(
@@ -245,6 +385,16 @@ pub static PARQUET_PROCESSOR_DATA_GAP_COUNT: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Parquet status saves that failed, by table, retried on the next poll
+pub static PARQUET_STATUS_SAVE_FAILURES_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_parquet_status_save_failures",
+        "Parquet status saves that failed, retried on the next poll",
+        &["table_name"]
+    )
+    .unwrap()
+});
+
 /// GRPC latency.
 pub static GRPC_LATENCY_BY_PROCESSOR_IN_SECS: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
@@ -265,6 +415,16 @@ pub static PROCESSOR_UNKNOWN_TYPE_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Warnings throttled by `throttle_log`, logged or not
+pub static THROTTLED_LOG_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_processor_throttled_log_count",
+        "Number of warnings of a kind that repeats per row, including the ones not logged",
+        &["kind"]
+    )
+    .unwrap()
+});
+
 /// Parquet struct size
 pub static PARQUET_STRUCT_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
     register_int_gauge_vec!("indexer_parquet_struct_size", "Parquet struct size", &[
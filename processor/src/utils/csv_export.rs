@@ -0,0 +1,301 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! `processor export-csv` runs a Postgres processor over a version range and writes the rows it
+//! would store to one CSV or TSV file per table, locally or to a GCS bucket, instead of writing
+//! them to the database. It's meant for one-off extracts that aren't worth a parquet pipeline.
+//!
+//! Files hold every row written in the range, so a current table can have a row per batch that
+//! changed it, told apart by `last_transaction_version`. Rows are kept in memory until the range
+//! is processed.
+
+use crate::{
+    config::{
+        db_config::DbConfig,
+        indexer_processor_config::IndexerProcessorConfig,
+        processor_mode::{ProcessorMode, TestingConfig},
+    },
+    utils::write_sink::{add_write_sink, remove_write_sink, TableBatch, WriteSink},
+};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::ArcDbPool, server_framework::RunnableConfig,
+    utils::errors::ProcessorError,
+};
+use clap::ValueEnum;
+use google_cloud_storage::{
+    client::{Client as GCSClient, ClientConfig as GcsClientConfig},
+    http::objects::upload::{Media, UploadObjectRequest, UploadType},
+};
+use hyper::Body;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum CsvFormat {
+    #[default]
+    Csv,
+    Tsv,
+}
+
+impl CsvFormat {
+    fn delimiter(&self) -> char {
+        match self {
+            Self::Csv => ',',
+            Self::Tsv => '\t',
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Tsv => "tsv",
+        }
+    }
+}
+
+/// Where the files are written, from `--out`
+#[derive(Debug, PartialEq)]
+pub enum ExportTarget {
+    Local(PathBuf),
+    /// `gs://<bucket>/<prefix>`, authenticated with `GOOGLE_APPLICATION_CREDENTIALS`
+    Gcs {
+        bucket: String,
+        prefix: String,
+    },
+}
+
+impl ExportTarget {
+    pub fn parse(out: &str) -> Result<Self> {
+        let Some(path) = out.strip_prefix("gs://") else {
+            return Ok(Self::Local(PathBuf::from(out)));
+        };
+        let (bucket, prefix) = path.split_once('/').unwrap_or((path, ""));
+        if bucket.is_empty() {
+            bail!("{out} names no bucket");
+        }
+        Ok(Self::Gcs {
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+/// Rows of one table, formatted
+#[derive(Debug, Default)]
+struct TableRows {
+    /// Columns of the table's first row, which every row is written in
+    columns: Vec<String>,
+    contents: String,
+}
+
+#[derive(Debug)]
+struct CsvExport {
+    format: CsvFormat,
+    tables: BTreeMap<&'static str, TableRows>,
+}
+
+/// Rows exported per table, `None` while no export is running
+static CSV_EXPORT: Lazy<Mutex<Option<CsvExport>>> = Lazy::new(|| Mutex::new(None));
+
+/// Adds every batch to the export instead of writing it
+struct CsvExportSink;
+
+#[async_trait]
+impl WriteSink for CsvExportSink {
+    fn name(&self) -> &'static str {
+        "csv_export"
+    }
+
+    fn replaces_database(&self) -> bool {
+        true
+    }
+
+    async fn before_write(
+        &self,
+        _conn_pool: &ArcDbPool,
+        batch: &TableBatch<'_>,
+    ) -> Result<Vec<usize>, ProcessorError> {
+        export_rows(batch.table_name, batch.json()?)?;
+        Ok(vec![])
+    }
+}
+
+/// `value` as a field, quoted when it has the delimiter, a quote or a line break in it
+fn format_field(value: &Value, delimiter: char) -> String {
+    let text = match value {
+        Value::Null => return String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    };
+    if text.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+fn format_line<'a>(fields: impl Iterator<Item = &'a Value>, delimiter: char) -> String {
+    let mut line = fields
+        .map(|value| format_field(value, delimiter))
+        .collect::<Vec<_>>()
+        .join(&delimiter.to_string());
+    line.push('\n');
+    line
+}
+
+/// Adds the rows a storer would've written to `table_name` to the table's file
+pub fn export_rows<T: Serialize>(
+    table_name: &'static str,
+    rows: &[T],
+) -> Result<(), ProcessorError> {
+    let mut export = CSV_EXPORT.lock().unwrap();
+    let Some(export) = export.as_mut() else {
+        return Ok(());
+    };
+    let delimiter = export.format.delimiter();
+    let table = export.tables.entry(table_name).or_default();
+    for row in rows {
+        let row = match serde_json::to_value(row) {
+            Ok(Value::Object(row)) => row,
+            _ => {
+                return Err(ProcessorError::ProcessError {
+                    message: format!("Rows of {table_name} can't be exported as CSV"),
+                })
+            },
+        };
+        if table.columns.is_empty() {
+            table.columns = row.keys().cloned().collect();
+            let header: Vec<Value> = table.columns.iter().cloned().map(Value::from).collect();
+            table
+                .contents
+                .push_str(&format_line(header.iter(), delimiter));
+        }
+        let fields = table
+            .columns
+            .iter()
+            .map(|column| row.get(column).unwrap_or(&Value::Null));
+        table.contents.push_str(&format_line(fields, delimiter));
+    }
+    Ok(())
+}
+
+/// Processes `starting_version` through `ending_version` and writes the rows of every table to
+/// `<table>.csv`, or `.tsv`, under `target`. Returns where the files were written.
+pub async fn export_csv(
+    mut config: IndexerProcessorConfig,
+    starting_version: u64,
+    ending_version: u64,
+    format: CsvFormat,
+    target: &ExportTarget,
+) -> Result<Vec<String>> {
+    if !matches!(config.db_config, DbConfig::PostgresConfig(_)) {
+        bail!("export-csv only exports processors writing to Postgres");
+    }
+    // Testing mode doesn't save checkpoints, so the processor's progress isn't touched
+    config.processor_mode = ProcessorMode::Testing(TestingConfig {
+        override_starting_version: starting_version,
+        ending_version: Some(ending_version),
+    });
+    config.supervisor_config.max_restarts = 0;
+    *CSV_EXPORT.lock().unwrap() = Some(CsvExport {
+        format,
+        tables: BTreeMap::new(),
+    });
+    add_write_sink(Arc::new(CsvExportSink))?;
+    let result = config.run().await;
+    remove_write_sink(CsvExportSink.name());
+    let export = CSV_EXPORT.lock().unwrap().take();
+    result?;
+
+    let tables = export.map(|export| export.tables).unwrap_or_default();
+    let mut written = vec![];
+    match target {
+        ExportTarget::Local(out_dir) => {
+            std::fs::create_dir_all(out_dir)
+                .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+            for (table_name, rows) in tables {
+                let path = out_dir.join(format!("{table_name}.{}", format.extension()));
+                std::fs::write(&path, rows.contents)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                written.push(path.display().to_string());
+            }
+        },
+        ExportTarget::Gcs { bucket, prefix } => {
+            let gcs_config = GcsClientConfig::default()
+                .with_auth()
+                .await
+                .context("Failed to create GCS client config")?;
+            let client = GCSClient::new(gcs_config);
+            for (table_name, rows) in tables {
+                let file_name = format!("{table_name}.{}", format.extension());
+                let object_name = if prefix.is_empty() {
+                    file_name
+                } else {
+                    format!("{prefix}/{file_name}")
+                };
+                client
+                    .upload_object(
+                        &UploadObjectRequest {
+                            bucket: bucket.clone(),
+                            ..Default::default()
+                        },
+                        Body::from(rows.contents),
+                        &UploadType::Simple(Media::new(object_name.clone())),
+                    )
+                    .await
+                    .with_context(|| format!("Failed to upload {object_name} to {bucket}"))?;
+                written.push(format!("gs://{bucket}/{object_name}"));
+            }
+        },
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_line() {
+        let fields = [
+            json!("0x1"),
+            json!(null),
+            json!(42),
+            json!("a, \"quoted\" name"),
+            json!({"key": "value"}),
+        ];
+        assert_eq!(
+            format_line(fields.iter(), ','),
+            "0x1,,42,\"a, \"\"quoted\"\" name\",\"{\"\"key\"\":\"\"value\"\"}\"\n"
+        );
+        assert_eq!(
+            format_line(fields.iter(), '\t'),
+            "0x1\t\t42\t\"a, \"\"quoted\"\" name\"\t\"{\"\"key\"\":\"\"value\"\"}\"\n"
+        );
+    }
+
+    #[test]
+    fn test_export_target() {
+        assert_eq!(
+            ExportTarget::parse("extracts/").unwrap(),
+            ExportTarget::Local(PathBuf::from("extracts/"))
+        );
+        assert_eq!(
+            ExportTarget::parse("gs://bucket/requests/1234/").unwrap(),
+            ExportTarget::Gcs {
+                bucket: "bucket".to_string(),
+                prefix: "requests/1234".to_string(),
+            }
+        );
+        assert!(ExportTarget::parse("gs://").is_err());
+    }
+}
@@ -0,0 +1,259 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Data dictionary of the tables the processors write, as Markdown or HTML, for `processor
+//! export-data-dictionary`. Tables and column types are the ones the dbt sources export reads,
+//! with the transaction proto field a column is parsed from where it's a plain copy of one.
+
+use crate::utils::catalog_export::{
+    dbt_sources, DbtTable, DEFAULT_PARQUET_SOURCE_NAME, DEFAULT_POSTGRES_SOURCE_NAME,
+};
+use anyhow::Result;
+use clap::ValueEnum;
+use std::fmt::Write;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum DictionaryFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+/// Proto fields of columns found in many tables, by column name
+const COMMON_SOURCE_FIELDS: &[(&str, &str)] = &[
+    ("transaction_version", "Transaction.version"),
+    ("txn_version", "Transaction.version"),
+    ("last_transaction_version", "Transaction.version"),
+    ("version", "Transaction.version"),
+    ("transaction_block_height", "Transaction.block_height"),
+    ("block_height", "Transaction.block_height"),
+    ("epoch", "Transaction.epoch"),
+    ("transaction_timestamp", "Transaction.timestamp"),
+    ("block_timestamp", "Transaction.timestamp"),
+    ("last_transaction_timestamp", "Transaction.timestamp"),
+    ("write_set_change_index", "index in TransactionInfo.changes"),
+    ("event_index", "index in the transaction's events"),
+];
+
+/// Proto fields of columns of one table, by table and column name. They take precedence over
+/// `COMMON_SOURCE_FIELDS`.
+const TABLE_SOURCE_FIELDS: &[(&str, &str, &str)] = &[
+    ("events", "sequence_number", "Event.sequence_number"),
+    ("events", "creation_number", "Event.key.creation_number"),
+    ("events", "account_address", "Event.key.account_address"),
+    ("events", "type", "Event.type_str"),
+    ("events", "data", "Event.data"),
+    ("transactions", "hash", "TransactionInfo.hash"),
+    ("transactions", "type", "Transaction.type"),
+    (
+        "transactions",
+        "state_change_hash",
+        "TransactionInfo.state_change_hash",
+    ),
+    (
+        "transactions",
+        "event_root_hash",
+        "TransactionInfo.event_root_hash",
+    ),
+    (
+        "transactions",
+        "state_checkpoint_hash",
+        "TransactionInfo.state_checkpoint_hash",
+    ),
+    ("transactions", "gas_used", "TransactionInfo.gas_used"),
+    ("transactions", "success", "TransactionInfo.success"),
+    ("transactions", "vm_status", "TransactionInfo.vm_status"),
+    (
+        "transactions",
+        "accumulator_root_hash",
+        "TransactionInfo.accumulator_root_hash",
+    ),
+    (
+        "user_transactions",
+        "sender",
+        "UserTransactionRequest.sender",
+    ),
+    (
+        "user_transactions",
+        "sequence_number",
+        "UserTransactionRequest.sequence_number",
+    ),
+    (
+        "user_transactions",
+        "max_gas_amount",
+        "UserTransactionRequest.max_gas_amount",
+    ),
+    (
+        "user_transactions",
+        "gas_unit_price",
+        "UserTransactionRequest.gas_unit_price",
+    ),
+    (
+        "user_transactions",
+        "expiration_timestamp_secs",
+        "UserTransactionRequest.expiration_timestamp_secs",
+    ),
+];
+
+/// Proto field `column_name` of `table_name` is copied from, None if it's derived
+fn source_field(table_name: &str, column_name: &str) -> Option<&'static str> {
+    TABLE_SOURCE_FIELDS
+        .iter()
+        .find(|(table, column, _)| *table == table_name && *column == column_name)
+        .map(|(_, _, field)| *field)
+        .or_else(|| {
+            COMMON_SOURCE_FIELDS
+                .iter()
+                .find(|(column, _)| *column == column_name)
+                .map(|(_, field)| *field)
+        })
+}
+
+/// Cells of a table's columns: name, type, nullable and source field
+fn column_rows(table: &DbtTable) -> Vec<[String; 4]> {
+    table
+        .columns
+        .iter()
+        .map(|column| {
+            [
+                column.name.clone(),
+                column.data_type.clone().unwrap_or_default(),
+                if column.meta.nullable { "yes" } else { "no" }.to_string(),
+                source_field(&table.name, &column.name)
+                    .unwrap_or_default()
+                    .to_string(),
+            ]
+        })
+        .collect()
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn markdown_table(out: &mut String, table: &DbtTable) {
+    writeln!(out, "### {}\n", table.name).unwrap();
+    writeln!(out, "Processors: {}\n", table.meta.processors.join(", ")).unwrap();
+    if !table.meta.primary_key.is_empty() {
+        writeln!(out, "Primary key: {}\n", table.meta.primary_key.join(", ")).unwrap();
+    }
+    writeln!(out, "| Column | Type | Nullable | Source field |").unwrap();
+    writeln!(out, "| --- | --- | --- | --- |").unwrap();
+    for cells in column_rows(table) {
+        let cells: Vec<String> = cells.iter().map(|cell| escape_markdown(cell)).collect();
+        writeln!(out, "| {} |", cells.join(" | ")).unwrap();
+    }
+    writeln!(out).unwrap();
+}
+
+fn html_table(out: &mut String, table: &DbtTable) {
+    writeln!(out, "<h3 id=\"{0}\">{0}</h3>", escape_html(&table.name)).unwrap();
+    writeln!(
+        out,
+        "<p>Processors: {}</p>",
+        escape_html(&table.meta.processors.join(", "))
+    )
+    .unwrap();
+    if !table.meta.primary_key.is_empty() {
+        writeln!(
+            out,
+            "<p>Primary key: {}</p>",
+            escape_html(&table.meta.primary_key.join(", "))
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "<table>\n<tr><th>Column</th><th>Type</th><th>Nullable</th><th>Source field</th></tr>"
+    )
+    .unwrap();
+    for cells in column_rows(table) {
+        let cells: Vec<String> = cells
+            .iter()
+            .map(|cell| format!("<td>{}</td>", escape_html(cell)))
+            .collect();
+        writeln!(out, "<tr>{}</tr>", cells.concat()).unwrap();
+    }
+    writeln!(out, "</table>").unwrap();
+}
+
+/// Every table the processors write, with its processors, primary key and columns. Columns list
+/// their type, whether they're nullable and the proto field they're copied from, left empty for
+/// columns derived from several fields or from Move data.
+pub fn data_dictionary(format: DictionaryFormat) -> Result<String> {
+    let sources = dbt_sources(DEFAULT_POSTGRES_SOURCE_NAME, DEFAULT_PARQUET_SOURCE_NAME)?;
+    let mut out = String::new();
+    match format {
+        DictionaryFormat::Markdown => {
+            writeln!(out, "# Data dictionary\n").unwrap();
+            for source in &sources.sources {
+                writeln!(out, "## {}\n\n{}\n", source.name, source.description).unwrap();
+                for table in &source.tables {
+                    markdown_table(&mut out, table);
+                }
+            }
+        },
+        DictionaryFormat::Html => {
+            writeln!(
+                out,
+                "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Data \
+                 dictionary</title></head>\n<body>\n<h1>Data dictionary</h1>"
+            )
+            .unwrap();
+            for source in &sources.sources {
+                writeln!(
+                    out,
+                    "<h2>{}</h2>\n<p>{}</p>",
+                    escape_html(&source.name),
+                    escape_html(&source.description)
+                )
+                .unwrap();
+                for table in &source.tables {
+                    html_table(&mut out, table);
+                }
+            }
+            writeln!(out, "</body>\n</html>").unwrap();
+        },
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_dictionary_lists_source_fields() {
+        assert_eq!(
+            source_field("events", "sequence_number"),
+            Some("Event.sequence_number")
+        );
+        assert_eq!(
+            source_field("user_transactions", "sequence_number"),
+            Some("UserTransactionRequest.sequence_number")
+        );
+        assert_eq!(
+            source_field(
+                "current_fungible_asset_balances",
+                "last_transaction_version"
+            ),
+            Some("Transaction.version")
+        );
+        assert_eq!(
+            source_field("current_fungible_asset_balances", "amount"),
+            None
+        );
+
+        let markdown = data_dictionary(DictionaryFormat::Markdown).unwrap();
+        assert!(markdown.contains("### events\n\nProcessors: events_processor\n"));
+        assert!(markdown.contains("| sequence_number | int8 | no | Event.sequence_number |"));
+        let html = data_dictionary(DictionaryFormat::Html).unwrap();
+        assert!(html.contains("<h3 id=\"events\">events</h3>"));
+    }
+}
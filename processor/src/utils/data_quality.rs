@@ -0,0 +1,371 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Assertions on the rows written to Postgres, checked for every batch before it's written.
+//! Violations are counted in `indexer_processor_data_quality_violations`, by table and rule.
+//! Rules with `quarantine` also keep the violating rows out of their table and write them to
+//! `quarantined_rows` instead, so they can be inspected and replayed once the cause is fixed. A
+//! batch that is processed again quarantines its rows again.
+
+use crate::{
+    db::{
+        quarantined_rows::{insert_quarantined_rows_query, QuarantinedRow},
+        schema_drift::expected_tables,
+    },
+    utils::{
+        counters::DATA_QUALITY_VIOLATIONS_COUNT,
+        table_names::TableNames,
+        table_stats::{write_batch, ChunkedRows},
+        write_sink::{add_write_sink, remove_write_sink, write_sinks, TableBatch, WriteSink},
+    },
+};
+use ahash::{AHashMap, AHashSet};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::ArcDbPool, utils::errors::ProcessorError,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{str::FromStr, sync::Arc};
+
+/// An assertion on one column of a table.
+///
+/// Example:
+/// ```yaml
+/// data_quality_rules:
+///   - name: non_negative_shares
+///     table: current_delegator_balances
+///     column: shares
+///     assertion:
+///       type: non_negative
+///   - name: v1_supply_set
+///     table: current_token_datas_v2
+///     column: supply
+///     assertion:
+///       type: not_null
+///     when:
+///       column: is_fungible_v2
+///       equals: null
+///   - name: owner_address_format
+///     table: current_token_ownerships_v2
+///     column: owner_address
+///     assertion:
+///       type: matches
+///       pattern: "^0x[0-9a-f]{64}$"
+///     quarantine: true
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DataQualityRule {
+    /// Label of the rule in metrics and `quarantined_rows`
+    pub name: String,
+    pub table: String,
+    pub column: String,
+    pub assertion: Assertion,
+    /// Only checks the rows where another column has a given value
+    #[serde(default)]
+    pub when: Option<RuleCondition>,
+    /// Writes violating rows to `quarantined_rows` instead of their table
+    #[serde(default)]
+    pub quarantine: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    NotNull,
+    /// Numbers, including numeric columns, are `>= 0`
+    NonNegative,
+    /// Text matches the regex `pattern`
+    Matches {
+        pattern: String,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleCondition {
+    pub column: String,
+    /// Value of the column as it's serialized, numeric columns are strings
+    pub equals: Value,
+}
+
+/// A rule with its pattern compiled
+#[derive(Debug)]
+struct CompiledRule {
+    rule: DataQualityRule,
+    pattern: Option<Regex>,
+}
+
+impl CompiledRule {
+    /// Whether `row` violates the rule. Null values only violate `not_null`, so that rules
+    /// about a value don't also require it to be set.
+    fn is_violated_by(&self, row: &Value) -> bool {
+        if let Some(condition) = &self.rule.when {
+            if row.get(&condition.column).unwrap_or(&Value::Null) != &condition.equals {
+                return false;
+            }
+        }
+        let value = row.get(&self.rule.column).unwrap_or(&Value::Null);
+        match (&self.rule.assertion, value) {
+            (Assertion::NotNull, value) => value.is_null(),
+            (_, Value::Null) => false,
+            (Assertion::NonNegative, Value::Number(number)) => {
+                number.as_f64().map_or(true, |number| number < 0.0)
+            },
+            (Assertion::NonNegative, Value::String(number)) => {
+                BigDecimal::from_str(number).map_or(true, |number| number < BigDecimal::zero())
+            },
+            (Assertion::NonNegative, _) => true,
+            (Assertion::Matches { .. }, Value::String(text)) => {
+                !self.pattern.as_ref().unwrap().is_match(text)
+            },
+            (Assertion::Matches { .. }, value) => {
+                !self.pattern.as_ref().unwrap().is_match(&value.to_string())
+            },
+        }
+    }
+}
+
+/// Quarantined rows written per statement
+const QUARANTINED_ROWS_CHUNK_SIZE: usize = 1000;
+
+/// Compiled rules by table name
+type CompiledRules = AHashMap<String, Vec<CompiledRule>>;
+
+/// Checks that the rules name tables and columns of `schema.rs` and that their patterns compile
+fn compile_rules(rules: &[DataQualityRule]) -> Result<CompiledRules> {
+    let tables: AHashMap<String, AHashSet<String>> = expected_tables()
+        .into_iter()
+        .map(|table| {
+            let columns = table.columns.into_iter().map(|column| column.name);
+            (table.name, columns.collect())
+        })
+        .collect();
+    let mut names = AHashSet::new();
+    let mut compiled = CompiledRules::new();
+    for rule in rules {
+        if !names.insert(rule.name.as_str()) {
+            bail!("Data quality rule {} is defined more than once", rule.name);
+        }
+        let Some(columns) = tables.get(&rule.table) else {
+            bail!(
+                "Data quality rule {} names {}, which isn't a table",
+                rule.name,
+                rule.table
+            );
+        };
+        let condition_column = rule.when.as_ref().map(|condition| &condition.column);
+        for column in std::iter::once(&rule.column).chain(condition_column) {
+            if !columns.contains(column) {
+                bail!(
+                    "Data quality rule {} names {column}, which isn't a column of {}",
+                    rule.name,
+                    rule.table
+                );
+            }
+        }
+        let pattern = match &rule.assertion {
+            Assertion::Matches { pattern } => Some(
+                Regex::new(pattern)
+                    .with_context(|| format!("Invalid pattern of rule {}", rule.name))?,
+            ),
+            _ => None,
+        };
+        compiled
+            .entry(rule.table.clone())
+            .or_default()
+            .push(CompiledRule {
+                rule: rule.clone(),
+                pattern,
+            });
+    }
+    Ok(compiled)
+}
+
+pub fn validate_data_quality_rules(rules: &[DataQualityRule]) -> Result<()> {
+    compile_rules(rules).map(|_| ())
+}
+
+/// Checks the batches written with the rules. Quarantined rows are written to `quarantined_rows`
+/// under its name in `table_names`.
+pub fn set_data_quality_rules(rules: &[DataQualityRule], table_names: TableNames) -> Result<()> {
+    if rules.is_empty() {
+        remove_write_sink(DATA_QUALITY_SINK);
+        return Ok(());
+    }
+    add_write_sink(Arc::new(DataQualitySink {
+        rules: compile_rules(rules)?,
+        table_names,
+    }))
+}
+
+const DATA_QUALITY_SINK: &str = "data_quality";
+
+/// Checks every batch against the rules of its table, and holds back the rows a rule
+/// quarantines
+struct DataQualitySink {
+    rules: CompiledRules,
+    table_names: TableNames,
+}
+
+#[async_trait]
+impl WriteSink for DataQualitySink {
+    fn name(&self) -> &'static str {
+        DATA_QUALITY_SINK
+    }
+
+    async fn before_write(
+        &self,
+        conn_pool: &ArcDbPool,
+        batch: &TableBatch<'_>,
+    ) -> Result<Vec<usize>, ProcessorError> {
+        let Some(table_rules) = self.rules.get(batch.table_name) else {
+            return Ok(vec![]);
+        };
+        let check = check_rows(batch.table_name, table_rules, batch.json()?);
+        if check.quarantined_rows.is_empty() {
+            return Ok(vec![]);
+        }
+        let rows = ChunkedRows {
+            rows: check.quarantined_rows.iter().collect(),
+            build_query: insert_quarantined_rows_query,
+            chunk_size: QUARANTINED_ROWS_CHUNK_SIZE,
+            table_names: self.table_names.clone(),
+        };
+        write_batch(
+            conn_pool.clone(),
+            &write_sinks(),
+            TableBatch::new("quarantined_rows", &rows),
+        )
+        .await?;
+        Ok(check.quarantined.into_iter().collect())
+    }
+}
+
+/// Outcome of checking a batch of rows against the rules of their table
+#[derive(Debug, Default)]
+pub struct BatchCheck {
+    /// Indexes of the rows kept out of the table
+    quarantined: AHashSet<usize>,
+    pub quarantined_rows: Vec<QuarantinedRow>,
+}
+
+impl BatchCheck {
+    pub fn is_quarantined(&self, index: usize) -> bool {
+        self.quarantined.contains(&index)
+    }
+}
+
+/// Checks `rows` against the rules of `table_name` and counts the violations. A row violating
+/// several quarantine rules is quarantined once, under the first of them.
+fn check_rows<T: Serialize>(
+    table_name: &str,
+    table_rules: &[CompiledRule],
+    rows: &[T],
+) -> BatchCheck {
+    let mut check = BatchCheck::default();
+    for (index, row) in rows.iter().enumerate() {
+        let Ok(row) = serde_json::to_value(row) else {
+            continue;
+        };
+        for compiled in table_rules {
+            if !compiled.is_violated_by(&row) {
+                continue;
+            }
+            DATA_QUALITY_VIOLATIONS_COUNT
+                .with_label_values(&[table_name, &compiled.rule.name])
+                .inc();
+            if compiled.rule.quarantine && check.quarantined.insert(index) {
+                check.quarantined_rows.push(QuarantinedRow {
+                    table_name: table_name.to_string(),
+                    rule_name: compiled.rule.name.clone(),
+                    row_data: row.clone(),
+                });
+            }
+        }
+    }
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(name: &str, column: &str, assertion: Assertion, quarantine: bool) -> DataQualityRule {
+        DataQualityRule {
+            name: name.to_string(),
+            table: "current_delegator_balances".to_string(),
+            column: column.to_string(),
+            assertion,
+            when: None,
+            quarantine,
+        }
+    }
+
+    #[test]
+    fn test_check_rows() {
+        let rules = compile_rules(&[
+            rule("non_negative", "shares", Assertion::NonNegative, true),
+            rule(
+                "pool_address_format",
+                "pool_address",
+                Assertion::Matches {
+                    pattern: "^0x[0-9a-f]{64}$".to_string(),
+                },
+                false,
+            ),
+        ])
+        .unwrap();
+        let address = format!("0x{}", "a".repeat(64));
+        let rows = vec![
+            json!({"shares": "10.5", "pool_address": address}),
+            json!({"shares": "-1", "pool_address": address}),
+            json!({"shares": null, "pool_address": "0xA"}),
+        ];
+
+        let check = check_rows(
+            "current_delegator_balances",
+            &rules["current_delegator_balances"],
+            &rows,
+        );
+        assert!(!check.is_quarantined(0));
+        assert!(check.is_quarantined(1));
+        assert!(!check.is_quarantined(2));
+        assert_eq!(check.quarantined_rows.len(), 1);
+        assert_eq!(check.quarantined_rows[0].rule_name, "non_negative");
+        assert_eq!(check.quarantined_rows[0].row_data, rows[1]);
+    }
+
+    #[test]
+    fn test_condition_and_not_null() {
+        let mut v1_supply = rule("v1_supply_set", "shares", Assertion::NotNull, false);
+        v1_supply.when = Some(RuleCondition {
+            column: "pool_address".to_string(),
+            equals: json!("0x1"),
+        });
+        let rules = compile_rules(&[v1_supply]).unwrap();
+        let rule = &rules["current_delegator_balances"][0];
+        assert!(rule.is_violated_by(&json!({"shares": null, "pool_address": "0x1"})));
+        assert!(!rule.is_violated_by(&json!({"shares": null, "pool_address": "0x2"})));
+        assert!(!rule.is_violated_by(&json!({"shares": "0", "pool_address": "0x1"})));
+    }
+
+    #[test]
+    fn test_rules_must_name_columns() {
+        assert!(compile_rules(&[rule("r", "missing", Assertion::NotNull, false)]).is_err());
+        assert!(compile_rules(&[rule(
+            "r",
+            "shares",
+            Assertion::Matches {
+                pattern: "(".to_string()
+            },
+            false
+        )])
+        .is_err());
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use ahash::AHashMap;
+use std::{collections::hash_map::Entry, hash::Hash, mem};
+
+/// A row written to a `current_*` table, where only the latest write per primary key matters.
+pub trait CurrentTableRow {
+    type PrimaryKey: Clone + Eq + Hash + Ord;
+
+    fn primary_key(&self) -> Self::PrimaryKey;
+
+    fn last_transaction_version(&self) -> i64;
+}
+
+/// Collapses rows sharing a primary key down to the one with the highest
+/// `last_transaction_version`. Ties go to the row that appears last, matching what a sequence
+/// of upserts would have left behind. Only the highest version per primary key survives the
+/// upserts anyway, so storers run their current table rows through this before writing them.
+///
+/// The output is sorted by primary key so that concurrent chunks acquire row locks in a
+/// consistent order.
+pub fn dedup_by_pk_keep_latest<T: CurrentTableRow>(rows: Vec<T>) -> Vec<T> {
+    let mut latest: AHashMap<T::PrimaryKey, T> = AHashMap::with_capacity(rows.len());
+    for row in rows {
+        match latest.entry(row.primary_key()) {
+            Entry::Occupied(mut existing) => {
+                if row.last_transaction_version() >= existing.get().last_transaction_version() {
+                    existing.insert(row);
+                }
+            },
+            Entry::Vacant(vacant) => {
+                vacant.insert(row);
+            },
+        }
+    }
+    let mut deduped: Vec<(T::PrimaryKey, T)> = latest.into_iter().collect();
+    deduped.sort_by(|(a, _), (b, _)| a.cmp(b));
+    deduped.into_iter().map(|(_, row)| row).collect()
+}
+
+/// Versions recently written to a `current_*` table, kept across batches so that a row whose
+/// version hasn't advanced since it was last written can be skipped instead of upserted again.
+///
+/// Memory is bounded by keeping two generations of at most `capacity / 2` keys each. When the
+/// newer generation fills up it replaces the older one, so keys that keep getting written stay
+/// in the window while ones that went quiet age out.
+pub struct RecentWrites<K> {
+    generation_capacity: usize,
+    current: AHashMap<K, i64>,
+    previous: AHashMap<K, i64>,
+}
+
+impl<K: Clone + Eq + Hash> RecentWrites<K> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            generation_capacity: (capacity / 2).max(1),
+            current: AHashMap::new(),
+            previous: AHashMap::new(),
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<i64> {
+        self.current
+            .get(key)
+            .or_else(|| self.previous.get(key))
+            .copied()
+    }
+
+    /// Drops rows that were already written at the same or a later version
+    pub fn filter_unchanged<T: CurrentTableRow<PrimaryKey = K>>(&self, rows: Vec<T>) -> Vec<T> {
+        rows.into_iter()
+            .filter(|row| {
+                self.get(&row.primary_key())
+                    .map_or(true, |version| row.last_transaction_version() > version)
+            })
+            .collect()
+    }
+
+    /// Remembers rows once they're in the database. Must only be called after the write succeeded.
+    pub fn record<T: CurrentTableRow<PrimaryKey = K>>(&mut self, rows: &[T]) {
+        for row in rows {
+            let key = row.primary_key();
+            let version = row
+                .last_transaction_version()
+                .max(self.get(&key).unwrap_or(i64::MIN));
+            if !self.current.contains_key(&key) && self.current.len() >= self.generation_capacity {
+                self.previous = mem::take(&mut self.current);
+            }
+            self.current.insert(key, version);
+        }
+    }
+}
+
+/// Drops unchanged rows when a dedup window is configured, otherwise returns `rows` as is
+pub fn filter_recent_writes<T: CurrentTableRow>(
+    window: &Option<RecentWrites<T::PrimaryKey>>,
+    rows: Vec<T>,
+) -> Vec<T> {
+    match window {
+        Some(window) => window.filter_unchanged(rows),
+        None => rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (key, version, value)
+    #[derive(Debug, PartialEq)]
+    struct Row(&'static str, i64, i64);
+
+    impl CurrentTableRow for Row {
+        type PrimaryKey = &'static str;
+
+        fn primary_key(&self) -> Self::PrimaryKey {
+            self.0
+        }
+
+        fn last_transaction_version(&self) -> i64 {
+            self.1
+        }
+    }
+
+    #[test]
+    fn test_keeps_highest_version_per_key() {
+        let rows = vec![
+            Row("b", 2, 1),
+            Row("a", 5, 2),
+            Row("b", 7, 3),
+            Row("a", 3, 4),
+        ];
+        assert_eq!(dedup_by_pk_keep_latest(rows), vec![
+            Row("a", 5, 2),
+            Row("b", 7, 3)
+        ]);
+    }
+
+    #[test]
+    fn test_same_version_keeps_last_row() {
+        let rows = vec![Row("a", 5, 1), Row("a", 5, 2)];
+        assert_eq!(dedup_by_pk_keep_latest(rows), vec![Row("a", 5, 2)]);
+    }
+
+    #[test]
+    fn test_recent_writes_skips_rows_that_did_not_advance() {
+        let mut window = RecentWrites::new(4);
+        window.record(&[Row("a", 5, 1), Row("b", 3, 1)]);
+        let rows = vec![Row("a", 5, 2), Row("b", 4, 2), Row("c", 1, 2)];
+        assert_eq!(window.filter_unchanged(rows), vec![
+            Row("b", 4, 2),
+            Row("c", 1, 2)
+        ]);
+    }
+
+    #[test]
+    fn test_recent_writes_is_bounded_and_keeps_hot_keys() {
+        let mut window = RecentWrites::new(4);
+        window.record(&[Row("hot", 1, 0), Row("a", 1, 0)]);
+        window.record(&[Row("hot", 2, 0), Row("b", 1, 0)]);
+        window.record(&[Row("hot", 3, 0), Row("c", 1, 0)]);
+        assert!(window.current.len() + window.previous.len() <= 4);
+        assert_eq!(window.get(&"hot"), Some(3));
+        assert_eq!(window.get(&"a"), None);
+    }
+}
@@ -0,0 +1,516 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Writes the rows the storers commit to gzipped JSON lines files as well, one per table and
+//! version window, locally or to a GCS bucket. It's a lighter alternative to the parquet
+//! processors for deployments that just want files.
+//!
+//! Rows are appended to `<table>/<first>_<last>.jsonl.gz.partial` as they're committed, each
+//! write as its own gzip member, and the file is renamed to `<table>/<first>_<last>.jsonl.gz`,
+//! or uploaded, once the processor status is saved past `<last>`. The files are written by a
+//! dedicated thread, and synced before each processor status save. A row's window is that of its
+//! version, e.g. its `transaction_version` or `last_transaction_version`. Tables whose rows have
+//! no version, like `table_metadatas`, aren't written to files. Batches reprocessed after a
+//! restart are appended again, so a window can hold a row more than once.
+
+use crate::utils::{
+    csv_export::ExportTarget,
+    log_throttle::throttle_log,
+    write_sink::{add_write_sink, TableBatch, WriteSink},
+};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use cedra_indexer_processor_sdk::utils::errors::ProcessorError;
+use flate2::{write::GzEncoder, Compression};
+use google_cloud_storage::{
+    client::{Client as GCSClient, ClientConfig as GcsClientConfig},
+    http::objects::upload::{Media, UploadObjectRequest, UploadType},
+};
+use hyper::Body;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+
+const PARTIAL_EXTENSION: &str = ".jsonl.gz.partial";
+/// Writes queued for the writer thread before storers wait for it
+const MAX_PENDING_WRITES: usize = 100;
+/// Columns holding a row's version, in the order they're looked up
+const VERSION_COLUMNS: [&str; 6] = [
+    "transaction_version",
+    "last_transaction_version",
+    "version",
+    "transaction_version_created",
+    "first_transaction_version",
+    "epoch_end_version",
+];
+
+/// Example:
+/// ```yaml
+/// file_sink:
+///   out: "gs://indexer-files/mainnet"
+///   version_window: 1000000
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileSinkConfig {
+    /// Local directory or `gs://<bucket>/<prefix>`, authenticated with
+    /// `GOOGLE_APPLICATION_CREDENTIALS`
+    pub out: String,
+    /// Versions per file
+    #[serde(default = "FileSinkConfig::default_version_window")]
+    pub version_window: u64,
+    /// Where files are written until they're uploaded to GCS, a temporary directory by default
+    #[serde(default)]
+    pub staging_dir: Option<PathBuf>,
+}
+
+impl FileSinkConfig {
+    pub const fn default_version_window() -> u64 {
+        1_000_000
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.version_window == 0 {
+            bail!("file_sink version_window must be positive");
+        }
+        ExportTarget::parse(&self.out).map(|_| ())
+    }
+}
+
+enum SinkCommand {
+    /// JSON lines of a table's rows, per first version of their window
+    Append {
+        table_name: String,
+        windows: Vec<(u64, String)>,
+    },
+    /// Syncs the files written since the last sync
+    Sync { done: oneshot::Sender<Result<()>> },
+    /// Closes the windows `last_success_version` is at or past the end of. Local files are
+    /// renamed, and the partial files to upload are sent back.
+    Finish {
+        last_success_version: u64,
+        done: oneshot::Sender<Result<Vec<(String, u64)>>>,
+    },
+}
+
+#[derive(Default)]
+struct PartialFile {
+    /// Open once this run appended to it
+    file: Option<File>,
+    /// Appended to since the last sync
+    dirty: bool,
+}
+
+/// Owns the partial files. Runs on its own thread, so storers never wait on disk IO.
+struct SinkWriter {
+    version_window: u64,
+    /// Where partial files are written
+    dir: PathBuf,
+    /// Whether finished files are renamed in place rather than uploaded
+    is_local: bool,
+    /// Partial files per table and first version of their window
+    windows: BTreeMap<(String, u64), PartialFile>,
+    /// First failed append. Appends stop there, and the next sync returns it.
+    error: Option<anyhow::Error>,
+}
+
+impl SinkWriter {
+    fn append(&mut self, table_name: String, windows: Vec<(u64, String)>) -> Result<()> {
+        for (first_version, lines) in windows {
+            let path = partial_path(&self.dir, &table_name, first_version, self.version_window);
+            let partial = self
+                .windows
+                .entry((table_name.clone(), first_version))
+                .or_default();
+            let file = match partial.file.take() {
+                Some(file) => partial.file.insert(file),
+                None => partial.file.insert(open_partial_file(&path)?),
+            };
+            append_gzip_member(file, lines.as_bytes())
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            partial.dirty = true;
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        for partial in self.windows.values_mut().filter(|partial| partial.dirty) {
+            if let Some(file) = &partial.file {
+                file.sync_data()?;
+            }
+            partial.dirty = false;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, last_success_version: u64) -> Result<Vec<(String, u64)>> {
+        let finished: Vec<(String, u64)> = self
+            .windows
+            .keys()
+            .filter(|(_, first_version)| {
+                first_version.saturating_add(self.version_window - 1) <= last_success_version
+            })
+            .cloned()
+            .collect();
+        for window in &finished {
+            self.windows.remove(window);
+        }
+        if !self.is_local {
+            return Ok(finished);
+        }
+        for (table_name, first_version) in &finished {
+            let partial = partial_path(&self.dir, table_name, *first_version, self.version_window);
+            let path = self.dir.join(table_name).join(format!(
+                "{}.jsonl.gz",
+                window_name(*first_version, self.version_window)
+            ));
+            std::fs::rename(&partial, &path)
+                .with_context(|| format!("Failed to rename {}", partial.display()))?;
+        }
+        Ok(vec![])
+    }
+
+    fn run(mut self, mut receiver: mpsc::Receiver<SinkCommand>) {
+        while let Some(command) = receiver.blocking_recv() {
+            match command {
+                SinkCommand::Append {
+                    table_name,
+                    windows,
+                } => {
+                    if self.error.is_none() {
+                        self.error = self.append(table_name, windows).err();
+                    }
+                },
+                SinkCommand::Sync { done } => {
+                    let _ = done.send(self.sync());
+                },
+                SinkCommand::Finish {
+                    last_success_version,
+                    done,
+                } => {
+                    let _ = done.send(self.finish(last_success_version));
+                },
+            }
+        }
+    }
+}
+
+struct FileSink {
+    version_window: u64,
+    target: ExportTarget,
+    /// Where partial files are written
+    dir: PathBuf,
+    sender: mpsc::Sender<SinkCommand>,
+    /// Shared by the uploads of every window
+    gcs_client: Option<GCSClient>,
+}
+
+static FILE_SINK: OnceCell<FileSink> = OnceCell::new();
+
+/// Starts writing committed rows to files. Partial files left by an earlier run are picked up,
+/// so their windows are finished like the ones this run opens.
+pub async fn start_file_sink(config: &FileSinkConfig) -> Result<()> {
+    config.validate()?;
+    let target = ExportTarget::parse(&config.out)?;
+    let (dir, gcs_client) = match &target {
+        ExportTarget::Local(out_dir) => (out_dir.clone(), None),
+        ExportTarget::Gcs { .. } => {
+            let gcs_config = GcsClientConfig::default()
+                .with_auth()
+                .await
+                .context("Failed to create GCS client config")?;
+            let dir = config
+                .staging_dir
+                .clone()
+                .unwrap_or_else(|| std::env::temp_dir().join("indexer-file-sink"));
+            (dir, Some(GCSClient::new(gcs_config)))
+        },
+    };
+    let mut windows = BTreeMap::new();
+    if dir.exists() {
+        for table_dir in
+            std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+        {
+            let table_dir = table_dir?.path();
+            let (Some(table_name), true) = (table_dir.file_name(), table_dir.is_dir()) else {
+                continue;
+            };
+            for file in std::fs::read_dir(&table_dir)? {
+                let file_name = file?.file_name();
+                if let Some(first_version) = file_name
+                    .to_str()
+                    .and_then(|name| name.strip_suffix(PARTIAL_EXTENSION))
+                    .and_then(|window| window.split_once('_'))
+                    .and_then(|(first, _)| first.parse().ok())
+                {
+                    let table_name = table_name.to_string_lossy().to_string();
+                    windows.insert((table_name, first_version), PartialFile::default());
+                }
+            }
+        }
+    }
+    let (sender, receiver) = mpsc::channel(MAX_PENDING_WRITES);
+    let writer = SinkWriter {
+        version_window: config.version_window,
+        dir: dir.clone(),
+        is_local: matches!(target, ExportTarget::Local(_)),
+        windows,
+        error: None,
+    };
+    let sink = FileSink {
+        version_window: config.version_window,
+        target,
+        dir,
+        sender,
+        gcs_client,
+    };
+    if FILE_SINK.set(sink).is_err() {
+        bail!("The file sink is already running");
+    }
+    tokio::task::spawn_blocking(move || writer.run(receiver));
+    add_write_sink(Arc::new(FileRowSink))
+}
+
+/// Appends the rows of every batch to the files once they're committed
+struct FileRowSink;
+
+#[async_trait]
+impl WriteSink for FileRowSink {
+    fn name(&self) -> &'static str {
+        "file_sink"
+    }
+
+    async fn committed(
+        &self,
+        batch: &TableBatch<'_>,
+        written: &[usize],
+    ) -> Result<(), ProcessorError> {
+        let rows = batch.json()?;
+        let rows: Vec<&Value> = written.iter().map(|index| &rows[*index]).collect();
+        sink_rows(batch.table_name, &rows).await
+    }
+}
+
+fn row_version(row: &Value) -> Option<u64> {
+    VERSION_COLUMNS
+        .iter()
+        .find_map(|column| row.get(column)?.as_u64())
+}
+
+/// File name of the window starting at `first_version`, without its extension
+fn window_name(first_version: u64, version_window: u64) -> String {
+    format!(
+        "{first_version}_{}",
+        first_version.saturating_add(version_window - 1)
+    )
+}
+
+fn partial_path(dir: &Path, table_name: &str, first_version: u64, version_window: u64) -> PathBuf {
+    dir.join(table_name).join(format!(
+        "{}{PARTIAL_EXTENSION}",
+        window_name(first_version, version_window)
+    ))
+}
+
+fn open_partial_file(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))
+}
+
+fn append_gzip_member(file: &mut File, contents: &[u8]) -> Result<()> {
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder.write_all(contents)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+fn writer_stopped() -> anyhow::Error {
+    anyhow::anyhow!("The file sink writer stopped")
+}
+
+/// Queues the rows committed to `table_name` for the files of their windows
+async fn sink_rows(table_name: &str, rows: &[&Value]) -> Result<(), ProcessorError> {
+    let Some(sink) = FILE_SINK.get() else {
+        return Ok(());
+    };
+    let to_error = |e: anyhow::Error| ProcessorError::DBStoreError {
+        message: format!("Failed to write {table_name} rows to the file sink: {e:?}"),
+        query: None,
+    };
+    // Lines per window, in the order of the rows
+    let mut windows: Vec<(u64, String)> = vec![];
+    for row in rows {
+        let Some(version) = row_version(row) else {
+            if let Some(suppressed) = throttle_log("file_sink_unversioned_rows", table_name) {
+                warn!(
+                    table_name = table_name,
+                    suppressed = suppressed,
+                    "Rows without a version aren't written to the file sink"
+                );
+            }
+            continue;
+        };
+        let first_version = version - version % sink.version_window;
+        let index = match windows
+            .iter()
+            .position(|(first, _)| *first == first_version)
+        {
+            Some(index) => index,
+            None => {
+                windows.push((first_version, String::new()));
+                windows.len() - 1
+            },
+        };
+        windows[index].1.push_str(&row.to_string());
+        windows[index].1.push('\n');
+    }
+    if windows.is_empty() {
+        return Ok(());
+    }
+    sink.sender
+        .send(SinkCommand::Append {
+            table_name: table_name.to_string(),
+            windows,
+        })
+        .await
+        .map_err(|_| to_error(writer_stopped()))
+}
+
+/// Syncs the rows queued so far to disk. Called before the processor status is saved, so the
+/// files hold every version the status covers.
+pub async fn sync_file_sink() -> Result<()> {
+    let Some(sink) = FILE_SINK.get() else {
+        return Ok(());
+    };
+    let (done, synced) = oneshot::channel();
+    sink.sender
+        .send(SinkCommand::Sync { done })
+        .await
+        .map_err(|_| writer_stopped())?;
+    synced.await.map_err(|_| writer_stopped())?
+}
+
+/// Finishes the files of the windows `last_success_version` is at or past the end of, once the
+/// processor status is saved at it. Their rows are all written, since storers commit a batch
+/// before its status is saved.
+pub async fn finish_file_sink_windows(last_success_version: u64) -> Result<()> {
+    let Some(sink) = FILE_SINK.get() else {
+        return Ok(());
+    };
+    let (done, finished) = oneshot::channel();
+    sink.sender
+        .send(SinkCommand::Finish {
+            last_success_version,
+            done,
+        })
+        .await
+        .map_err(|_| writer_stopped())?;
+    let to_upload = finished.await.map_err(|_| writer_stopped())??;
+    let (ExportTarget::Gcs { bucket, prefix }, Some(gcs_client)) = (&sink.target, &sink.gcs_client)
+    else {
+        return Ok(());
+    };
+    for (table_name, first_version) in to_upload {
+        let partial = partial_path(&sink.dir, &table_name, first_version, sink.version_window);
+        let file_name = format!(
+            "{}.jsonl.gz",
+            window_name(first_version, sink.version_window)
+        );
+        let contents = tokio::fs::read(&partial)
+            .await
+            .with_context(|| format!("Failed to read {}", partial.display()))?;
+        let object_name = if prefix.is_empty() {
+            format!("{table_name}/{file_name}")
+        } else {
+            format!("{prefix}/{table_name}/{file_name}")
+        };
+        gcs_client
+            .upload_object(
+                &UploadObjectRequest {
+                    bucket: bucket.clone(),
+                    ..Default::default()
+                },
+                Body::from(contents),
+                &UploadType::Simple(Media::new(object_name.clone())),
+            )
+            .await
+            .with_context(|| format!("Failed to upload {object_name} to {bucket}"))?;
+        tokio::fs::remove_file(&partial)
+            .await
+            .with_context(|| format!("Failed to remove {}", partial.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::MultiGzDecoder;
+    use serde_json::json;
+    use std::io::Read;
+
+    #[test]
+    fn test_row_version() {
+        assert_eq!(row_version(&json!({"transaction_version": 12})), Some(12));
+        assert_eq!(
+            row_version(&json!({"last_transaction_version": 7, "version": 3})),
+            Some(7)
+        );
+        assert_eq!(row_version(&json!({"epoch_end_version": 9})), Some(9));
+        assert_eq!(row_version(&json!({"collection_id": "0x1"})), None);
+        assert_eq!(window_name(2_000_000, 1_000_000), "2000000_2999999");
+    }
+
+    #[test]
+    fn test_writer_appends_syncs_and_finishes_windows() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = SinkWriter {
+            version_window: 100,
+            dir: dir.path().to_path_buf(),
+            is_local: true,
+            windows: BTreeMap::new(),
+            error: None,
+        };
+        let line = |version: u64| format!("{{\"transaction_version\":{version}}}\n");
+        writer
+            .append("events".to_string(), vec![(0, line(1)), (100, line(100))])
+            .unwrap();
+        writer
+            .append("events".to_string(), vec![(0, line(2))])
+            .unwrap();
+        let partial = partial_path(dir.path(), "events", 0, 100);
+        assert_eq!(partial, dir.path().join("events/0_99.jsonl.gz.partial"));
+        assert!(writer.windows.values().all(|partial| partial.dirty));
+        writer.sync().unwrap();
+        assert!(writer.windows.values().all(|partial| !partial.dirty));
+
+        // Only the first window ends at or before version 150
+        assert!(writer.finish(150).unwrap().is_empty());
+        assert!(!partial.exists());
+        assert!(partial_path(dir.path(), "events", 100, 100).exists());
+        let mut contents = String::new();
+        MultiGzDecoder::new(File::open(dir.path().join("events/0_99.jsonl.gz")).unwrap())
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, format!("{}{}", line(1), line(2)));
+    }
+}
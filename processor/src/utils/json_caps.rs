@@ -0,0 +1,224 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Caps on the size of JSON columns written to Postgres. A value whose serialized JSON is larger
+//! than its cap is replaced, before the row is checked or written, by an object flagging it as
+//! truncated with the SHA-256 of the original JSON, its size, and its first `max_bytes` unless
+//! the cap redacts it. Replaced values are counted in `indexer_processor_json_values_capped`.
+
+use crate::{db::schema_drift::expected_tables, utils::counters::JSON_VALUES_CAPPED_COUNT};
+use ahash::AHashMap;
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::{borrow::Cow, sync::RwLock};
+use tracing::warn;
+
+/// A cap on the size of one JSON column of a table.
+///
+/// Example:
+/// ```yaml
+/// json_column_caps:
+///   - table: transactions
+///     column: payload
+///     max_bytes: 65536
+///   - table: current_token_datas_v2
+///     column: token_properties
+///     max_bytes: 16384
+///     redact: true
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonColumnCap {
+    pub table: String,
+    pub column: String,
+    /// Values larger than this when serialized are replaced
+    pub max_bytes: usize,
+    /// Leaves the start of the value out of its replacement
+    #[serde(default)]
+    pub redact: bool,
+}
+
+/// Caps by table name
+static JSON_COLUMN_CAPS: Lazy<RwLock<AHashMap<String, Vec<JsonColumnCap>>>> =
+    Lazy::new(|| RwLock::new(AHashMap::new()));
+
+/// Checks that the caps name JSON columns of `schema.rs`, each at most once
+fn group_caps(caps: &[JsonColumnCap]) -> Result<AHashMap<String, Vec<JsonColumnCap>>> {
+    let tables = expected_tables();
+    let mut grouped: AHashMap<String, Vec<JsonColumnCap>> = AHashMap::new();
+    for cap in caps {
+        let Some(table) = tables.iter().find(|table| table.name == cap.table) else {
+            bail!("json_column_caps names {}, which isn't a table", cap.table);
+        };
+        let Some(column) = table
+            .columns
+            .iter()
+            .find(|column| column.name == cap.column)
+        else {
+            bail!(
+                "json_column_caps names {}, which isn't a column of {}",
+                cap.column,
+                cap.table
+            );
+        };
+        if column.udt_name != Some("jsonb") {
+            bail!(
+                "json_column_caps names {}.{}, which isn't a JSON column",
+                cap.table,
+                cap.column
+            );
+        }
+        let table_caps = grouped.entry(cap.table.clone()).or_default();
+        if table_caps.iter().any(|other| other.column == cap.column) {
+            bail!(
+                "json_column_caps caps {}.{} more than once",
+                cap.table,
+                cap.column
+            );
+        }
+        table_caps.push(cap.clone());
+    }
+    Ok(grouped)
+}
+
+pub fn validate_json_column_caps(caps: &[JsonColumnCap]) -> Result<()> {
+    group_caps(caps).map(|_| ())
+}
+
+pub fn set_json_column_caps(caps: &[JsonColumnCap]) -> Result<()> {
+    *JSON_COLUMN_CAPS.write().unwrap() = group_caps(caps)?;
+    Ok(())
+}
+
+/// Replacement of `value` if it's larger than the cap, None if it fits
+fn capped_value(cap: &JsonColumnCap, value: &Value) -> Option<Value> {
+    if value.is_null() {
+        return None;
+    }
+    let serialized = serde_json::to_string(value).ok()?;
+    if serialized.len() <= cap.max_bytes {
+        return None;
+    }
+    let mut replacement = json!({
+        "truncated": true,
+        "sha256": format!("0x{}", hex::encode(Sha256::digest(serialized.as_bytes()))),
+        "size_bytes": serialized.len(),
+    });
+    if !cap.redact {
+        let mut end = cap.max_bytes;
+        while !serialized.is_char_boundary(end) {
+            end -= 1;
+        }
+        replacement["preview"] = Value::String(serialized[..end].to_string());
+    }
+    Some(replacement)
+}
+
+/// Replaces the values of a row's capped columns that are over their cap, returns whether any was
+fn cap_row(table_name: &str, caps: &[JsonColumnCap], row: &mut Value) -> bool {
+    let mut capped = false;
+    for cap in caps {
+        let Some(value) = row.get_mut(&cap.column) else {
+            continue;
+        };
+        if let Some(replacement) = capped_value(cap, value) {
+            *value = replacement;
+            capped = true;
+            JSON_VALUES_CAPPED_COUNT
+                .with_label_values(&[table_name, &cap.column])
+                .inc();
+        }
+    }
+    capped
+}
+
+/// `rows` with their JSON values over the caps of `table_name` replaced. Borrows `rows` when the
+/// table has no caps or no value is over them.
+pub fn cap_json_columns<'a, T>(table_name: &str, rows: &'a [T]) -> Cow<'a, [T]>
+where
+    T: Serialize + DeserializeOwned + Clone,
+{
+    let caps = JSON_COLUMN_CAPS.read().unwrap();
+    let Some(table_caps) = caps.get(table_name) else {
+        return Cow::Borrowed(rows);
+    };
+    let mut capped_rows: Option<Vec<T>> = None;
+    for (index, row) in rows.iter().enumerate() {
+        let Ok(mut value) = serde_json::to_value(row) else {
+            continue;
+        };
+        if !cap_row(table_name, table_caps, &mut value) {
+            continue;
+        }
+        match serde_json::from_value(value) {
+            Ok(capped_row) => {
+                capped_rows.get_or_insert_with(|| rows.to_vec())[index] = capped_row;
+            },
+            Err(e) => warn!(
+                table_name = table_name,
+                error = ?e,
+                "Failed to cap the JSON columns of a row, it's written as is"
+            ),
+        }
+    }
+    match capped_rows {
+        Some(capped_rows) => Cow::Owned(capped_rows),
+        None => Cow::Borrowed(rows),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cap(max_bytes: usize, redact: bool) -> JsonColumnCap {
+        JsonColumnCap {
+            table: "transactions".to_string(),
+            column: "payload".to_string(),
+            max_bytes,
+            redact,
+        }
+    }
+
+    #[test]
+    fn test_cap_row() {
+        let payload = json!({"arguments": ["é".repeat(20)]});
+        let serialized = serde_json::to_string(&payload).unwrap();
+        let mut row = json!({"version": 1, "payload": payload.clone()});
+
+        assert!(!cap_row("transactions", &[cap(1000, false)], &mut row));
+        assert_eq!(row["payload"], payload);
+
+        // The preview doesn't split the two bytes of an é
+        assert!(cap_row("transactions", &[cap(16, false)], &mut row));
+        assert_eq!(row["payload"]["truncated"], json!(true));
+        assert_eq!(row["payload"]["size_bytes"], json!(serialized.len()));
+        assert_eq!(row["payload"]["preview"], json!(&serialized[..15]));
+        assert_eq!(
+            row["payload"]["sha256"],
+            json!(format!(
+                "0x{}",
+                hex::encode(Sha256::digest(serialized.as_bytes()))
+            ))
+        );
+
+        let mut row = json!({"version": 1, "payload": payload});
+        assert!(cap_row("transactions", &[cap(16, true)], &mut row));
+        assert!(row["payload"].get("preview").is_none());
+
+        let mut row = json!({"version": 1, "payload": null});
+        assert!(!cap_row("transactions", &[cap(0, true)], &mut row));
+    }
+
+    #[test]
+    fn test_group_caps() {
+        assert!(group_caps(&[cap(16, false)]).is_ok());
+        assert!(group_caps(&[cap(16, false), cap(32, true)]).is_err());
+        let mut not_json = cap(16, false);
+        not_json.column = "hash".to_string();
+        assert!(group_caps(&[not_json]).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Throttles warnings that repeat for every row of a bad range, like lookups of table handles
+//! the database has no metadata for until it's backfilled. The first warning of each kind and
+//! key is logged as a sample, then at most one every `LOG_INTERVAL`, with the number dropped
+//! since. Every warning is counted in `indexer_processor_throttled_log_count`, logged or not.
+
+use crate::utils::counters::THROTTLED_LOG_COUNT;
+use ahash::AHashMap;
+use once_cell::sync::Lazy;
+use std::{
+    mem,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+const LOG_INTERVAL: Duration = Duration::from_secs(60);
+/// Keys tracked per kind. Once a kind has that many, the keys it hasn't seen yet share one entry
+const MAX_KEYS_PER_KIND: usize = 10_000;
+const OTHER_KEYS: &str = "*";
+
+struct KeyLog {
+    last_logged: Instant,
+    /// Warnings dropped since the last one logged
+    suppressed: u64,
+}
+
+static KEY_LOGS: Lazy<Mutex<AHashMap<&'static str, AHashMap<String, KeyLog>>>> =
+    Lazy::new(|| Mutex::new(AHashMap::new()));
+
+/// Whether a warning of `kind` about `key`, e.g. a table handle, should be logged. Returns the
+/// number of the same warnings dropped since the last one logged, or None to drop this one.
+///
+/// ```ignore
+/// if let Some(suppressed) = throttle_log("missing_collection_creator", &table_handle) {
+///     tracing::error!(lookup_key = &table_handle, suppressed = suppressed, "...");
+/// }
+/// ```
+pub fn throttle_log(kind: &'static str, key: &str) -> Option<u64> {
+    THROTTLED_LOG_COUNT.with_label_values(&[kind]).inc();
+    let mut key_logs = KEY_LOGS.lock().unwrap();
+    check_key(key_logs.entry(kind).or_default(), key, Instant::now())
+}
+
+fn check_key(key_logs: &mut AHashMap<String, KeyLog>, key: &str, now: Instant) -> Option<u64> {
+    let key = if key_logs.len() >= MAX_KEYS_PER_KIND && !key_logs.contains_key(key) {
+        OTHER_KEYS
+    } else {
+        key
+    };
+    match key_logs.get_mut(key) {
+        Some(log) if now.duration_since(log.last_logged) < LOG_INTERVAL => {
+            log.suppressed += 1;
+            None
+        },
+        Some(log) => {
+            log.last_logged = now;
+            Some(mem::take(&mut log.suppressed))
+        },
+        None => {
+            key_logs.insert(key.to_string(), KeyLog {
+                last_logged: now,
+                suppressed: 0,
+            });
+            Some(0)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_key() {
+        let mut key_logs = AHashMap::new();
+        let start = Instant::now();
+        assert_eq!(check_key(&mut key_logs, "0xa", start), Some(0));
+        assert_eq!(check_key(&mut key_logs, "0xa", start), None);
+        assert_eq!(check_key(&mut key_logs, "0xa", start), None);
+        assert_eq!(check_key(&mut key_logs, "0xb", start), Some(0));
+        assert_eq!(
+            check_key(&mut key_logs, "0xa", start + LOG_INTERVAL),
+            Some(2)
+        );
+        assert_eq!(check_key(&mut key_logs, "0xa", start + LOG_INTERVAL), None);
+    }
+}
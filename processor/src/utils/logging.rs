@@ -0,0 +1,348 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Log output of the processor. JSON lines carry the batch they were logged for as top-level
+//! `processor`, `start_version`, `end_version` and `table` fields, taken from the spans that steps
+//! and table writes run in, so logs can be matched to the version range they're about.
+
+use cedra_indexer_processor_sdk::{
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use clap::ValueEnum;
+use once_cell::sync::OnceCell;
+use serde_json::{Map, Value};
+use std::fmt;
+use tracing::{
+    error_span,
+    field::{Field, Visit},
+    span, Event, Instrument, Span, Subscriber,
+};
+use tracing_subscriber::{
+    fmt::{format::Writer, FmtContext, FormatEvent, FormatFields},
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    reload,
+    util::SubscriberInitExt,
+    EnvFilter, Layer, Registry,
+};
+
+/// Span fields added to the JSON lines of every event logged in the span
+const CONTEXT_FIELDS: &[&str] = &["processor", "start_version", "end_version", "table"];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum LogFormat {
+    /// One JSON object per line, with the batch context as top-level fields
+    #[default]
+    Json,
+    /// Human readable lines, with the batch context as span prefixes
+    Text,
+}
+
+/// Replaces the filter of the global subscriber once it's set up
+static LOG_FILTER: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
+/// Sets up the global subscriber, in place of the SDK's. The level is read from `RUST_LOG`, and
+/// defaults to `info`, until the config sets one with `set_log_level`.
+pub fn setup_logging(format: LogFormat) {
+    let (env_filter, handle) = reload::Layer::new(log_filter(None).unwrap());
+    let _ = LOG_FILTER.set(handle);
+    let registry = tracing_subscriber::registry().with(env_filter);
+    match format {
+        LogFormat::Json => registry
+            .with(BatchContextLayer)
+            .with(tracing_subscriber::fmt::layer().event_format(JsonWithBatchContext))
+            .init(),
+        LogFormat::Text => registry
+            .with(tracing_subscriber::fmt::layer().with_thread_names(true))
+            .init(),
+    }
+}
+
+/// The filter of `log_level` directives, or else of `RUST_LOG`, or else `info`
+pub fn log_filter(log_level: Option<&str>) -> Result<EnvFilter> {
+    match log_level {
+        Some(log_level) => EnvFilter::try_new(log_level)
+            .with_context(|| format!("Invalid log_level {log_level:?}")),
+        None => Ok(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"))),
+    }
+}
+
+/// Filters everything logged from now on with `filter`. Does nothing if `setup_logging` didn't
+/// set up the subscriber.
+pub fn set_log_level(filter: EnvFilter) -> Result<()> {
+    if let Some(handle) = LOG_FILTER.get() {
+        handle
+            .reload(filter)
+            .context("Failed to apply the log level")?;
+    }
+    Ok(())
+}
+
+/// Span of a processor's batch, whose fields are added to everything logged while processing it.
+/// Context spans are at the error level, so `RUST_LOG` can't filter them out from under the
+/// events they're for.
+pub fn batch_span(processor_name: &str, start_version: u64, end_version: u64) -> Span {
+    error_span!(
+        "batch",
+        processor = processor_name,
+        start_version,
+        end_version
+    )
+}
+
+/// Span of a write to a table, within the span of its batch
+pub fn table_span(table_name: &str) -> Span {
+    error_span!("table_write", table = table_name)
+}
+
+/// Collects the fields of a span or event as JSON values
+struct JsonVisitor<'a> {
+    fields: &'a mut Map<String, Value>,
+    /// Only collects these fields, when set
+    only: Option<&'static [&'static str]>,
+}
+
+impl JsonVisitor<'_> {
+    fn insert(&mut self, field: &Field, value: Value) {
+        if self.only.map_or(true, |only| only.contains(&field.name())) {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for JsonVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, Value::from(format!("{value:?}")));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, Value::from(value));
+    }
+}
+
+/// Context fields of a span and its parents, kept in the span's extensions
+#[derive(Clone, Debug, Default)]
+struct BatchContext(Map<String, Value>);
+
+/// Keeps the `BatchContext` of every span, so that formatting an event only has to look at the
+/// span it's logged in
+struct BatchContextLayer;
+
+impl<S> Layer<S> for BatchContextLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut context = span
+            .parent()
+            .and_then(|parent| parent.extensions().get::<BatchContext>().cloned())
+            .unwrap_or_default();
+        attrs.record(&mut JsonVisitor {
+            fields: &mut context.0,
+            only: Some(CONTEXT_FIELDS),
+        });
+        if !context.0.is_empty() {
+            span.extensions_mut().insert(context);
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let mut context = extensions.remove::<BatchContext>().unwrap_or_default();
+        values.record(&mut JsonVisitor {
+            fields: &mut context.0,
+            only: Some(CONTEXT_FIELDS),
+        });
+        if !context.0.is_empty() {
+            extensions.insert(context);
+        }
+    }
+}
+
+/// Formats events as JSON lines, with the `BatchContext` of their span as top-level fields.
+/// Fields of the event itself take precedence.
+struct JsonWithBatchContext;
+
+impl<S, N> FormatEvent<S, N> for JsonWithBatchContext
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = Map::new();
+        fields.insert(
+            "timestamp".to_string(),
+            Value::from(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Micros, true)),
+        );
+        fields.insert("level".to_string(), Value::from(metadata.level().as_str()));
+        if let Some(file) = metadata.file() {
+            fields.insert("filename".to_string(), Value::from(file));
+        }
+        if let Some(line) = metadata.line() {
+            fields.insert("line_number".to_string(), Value::from(line));
+        }
+        if let Some(thread_name) = std::thread::current().name() {
+            fields.insert("threadName".to_string(), Value::from(thread_name));
+        }
+        if let Some(span) = ctx.lookup_current() {
+            if let Some(context) = span.extensions().get::<BatchContext>() {
+                fields.extend(context.0.clone());
+            }
+        }
+        event.record(&mut JsonVisitor {
+            fields: &mut fields,
+            only: None,
+        });
+        writeln!(writer, "{}", Value::Object(fields))
+    }
+}
+
+/// Runs a step's processing of each batch in the batch's `batch_span`.
+///
+/// ```ignore
+/// ProcessorBuilder::new_with_inputless_first_step(transaction_stream.into_runnable_step())
+///     .connect_to(BatchSpanStep::new(extractor, self.name()).into_runnable_step(), channel_size)
+/// ```
+pub struct BatchSpanStep<S> {
+    step: S,
+    processor_name: String,
+}
+
+impl<S: AsyncStep> BatchSpanStep<S> {
+    pub fn new(step: S, processor_name: &str) -> Self {
+        Self {
+            step,
+            processor_name: processor_name.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: AsyncStep> Processable for BatchSpanStep<S> {
+    type Input = S::Input;
+    type Output = S::Output;
+    type RunType = AsyncRunType;
+
+    async fn init(&mut self) {
+        self.step.init().await
+    }
+
+    async fn process(
+        &mut self,
+        input: TransactionContext<S::Input>,
+    ) -> Result<Option<TransactionContext<S::Output>>, ProcessorError> {
+        let span = batch_span(
+            &self.processor_name,
+            input.metadata.start_version,
+            input.metadata.end_version,
+        );
+        self.step.process(input).instrument(span).await
+    }
+
+    async fn cleanup(
+        &mut self,
+    ) -> Result<Option<Vec<TransactionContext<S::Output>>>, ProcessorError> {
+        self.step.cleanup().await
+    }
+}
+
+impl<S: AsyncStep> AsyncStep for BatchSpanStep<S> {}
+
+impl<S: AsyncStep> NamedStep for BatchSpanStep<S> {
+    fn name(&self) -> String {
+        self.step.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_lines_carry_batch_context() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::registry().with(BatchContextLayer).with(
+            tracing_subscriber::fmt::layer()
+                .event_format(JsonWithBatchContext)
+                .with_writer(move || writer.clone()),
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            let _batch = batch_span("events_processor", 100, 199).entered();
+            tracing::info!("Processing batch");
+            let _table = table_span("events").entered();
+            tracing::warn!(rows = 3, "Writing rows");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["processor"], "events_processor");
+        assert_eq!(lines[0]["start_version"], 100);
+        assert_eq!(lines[0]["end_version"], 199);
+        assert_eq!(lines[0]["message"], "Processing batch");
+        assert!(lines[0].get("table").is_none());
+        assert_eq!(lines[1]["table"], "events");
+        assert_eq!(lines[1]["start_version"], 100);
+        assert_eq!(lines[1]["rows"], 3);
+        assert_eq!(lines[1]["level"], "WARN");
+    }
+
+    #[test]
+    fn test_log_filter() {
+        assert!(log_filter(Some("warn,processor=debug")).is_ok());
+        assert!(log_filter(Some("processor=loud")).is_err());
+    }
+}
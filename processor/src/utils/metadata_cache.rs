@@ -0,0 +1,131 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Process-wide cache of the metadata processors otherwise look up in the DB when it wasn't
+//! written in the batch being processed: collection creators by v1 table handle, and current
+//! objects by address. It outlives supervisor restarts and is shared by every processor running
+//! in the process, so a lookup made once isn't repeated for every batch touching the same key.
+//!
+//! Collection creators never change, so they're cached as found. Objects do change owner, so
+//! the objects processor writes every current object it produces through to the cache, which
+//! keeps entries at least as fresh as the rows it writes to `current_objects`.
+
+use crate::{
+    processors::objects::v2_objects_models::CurrentObject,
+    utils::counters::METADATA_CACHE_LOOKUPS_COUNT,
+};
+use ahash::AHashMap;
+use cedra_indexer_processor_sdk::utils::convert::standardize_address;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+};
+
+/// Example:
+/// ```yaml
+/// metadata_cache:
+///   max_entries: 1000000
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetadataCacheConfig {
+    /// Entries kept per cache, past this a cache starts over
+    #[serde(default = "MetadataCacheConfig::default_max_entries")]
+    pub max_entries: usize,
+}
+
+impl MetadataCacheConfig {
+    pub const fn default_max_entries() -> usize {
+        500_000
+    }
+}
+
+/// Entries kept per cache, 0 while the cache is off
+static MAX_ENTRIES: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_metadata_cache_config(config: Option<&MetadataCacheConfig>) {
+    MAX_ENTRIES.store(
+        config.map_or(0, |config| config.max_entries),
+        Ordering::Relaxed,
+    );
+}
+
+/// Bounded lookup by address or table handle. Keys are standardized, so they match however the
+/// caller formatted them.
+pub struct MetadataCache<V> {
+    name: &'static str,
+    entries: Mutex<AHashMap<String, V>>,
+}
+
+impl<V: Clone> MetadataCache<V> {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            entries: Mutex::new(AHashMap::new()),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<V> {
+        if MAX_ENTRIES.load(Ordering::Relaxed) == 0 {
+            return None;
+        }
+        let value = self
+            .entries
+            .lock()
+            .unwrap()
+            .get(&standardize_address(key))
+            .cloned();
+        let result = if value.is_some() { "hit" } else { "miss" };
+        METADATA_CACHE_LOOKUPS_COUNT
+            .with_label_values(&[self.name, result])
+            .inc();
+        value
+    }
+
+    pub fn insert(&self, key: &str, value: V) {
+        let max_entries = MAX_ENTRIES.load(Ordering::Relaxed);
+        if max_entries == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= max_entries {
+            entries.clear();
+        }
+        entries.insert(standardize_address(key), value);
+    }
+}
+
+/// Creator addresses of v1 collections, by the table handle of their creator's `Collections`
+pub static COLLECTION_CREATORS: Lazy<MetadataCache<String>> =
+    Lazy::new(|| MetadataCache::new("collection_creators"));
+
+/// Latest state of objects, by object address
+pub static CURRENT_OBJECTS: Lazy<MetadataCache<CurrentObject>> =
+    Lazy::new(|| MetadataCache::new("current_objects"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_cache() {
+        let cache = MetadataCache::new("metadata_cache_test");
+        set_metadata_cache_config(None);
+        cache.insert("0x1", "0xcreator".to_string());
+        assert_eq!(cache.get("0x1"), None);
+
+        set_metadata_cache_config(Some(&MetadataCacheConfig { max_entries: 2 }));
+        cache.insert("0x1", "0xcreator".to_string());
+        assert_eq!(
+            cache.get(&format!("0x{:0>64}", "1")),
+            Some("0xcreator".to_string())
+        );
+        cache.insert("0x2", "0xcreator".to_string());
+        cache.insert("0x3", "0xcreator".to_string());
+        assert_eq!(cache.get("0x1"), None);
+        assert_eq!(cache.get("0x3"), Some("0xcreator".to_string()));
+        set_metadata_cache_config(None);
+    }
+}
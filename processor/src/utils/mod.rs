@@ -1,2 +1,35 @@
+pub mod address_labels;
+pub mod audit;
+#[cfg(any(test, feature = "bench"))]
+pub mod bench;
+pub mod catalog_export;
+pub mod config_reload;
+pub mod conflict_simulation;
 pub mod counters;
+pub mod csv_export;
+pub mod data_dictionary;
+pub mod data_quality;
+pub mod dedup;
+pub mod file_sink;
+pub mod json_caps;
+pub mod log_throttle;
+pub mod logging;
+pub mod metadata_cache;
+pub mod outbox;
+pub mod price_oracle;
+pub mod processing_context;
+pub mod ranged_run;
+pub mod replica;
+pub mod scheduler;
+pub mod sharded_run;
+pub mod supervisor;
 pub mod table_flags;
+pub mod table_names;
+pub mod table_stats;
+pub mod table_watermarks;
+pub mod table_writers;
+pub mod transaction_recorder;
+pub mod transaction_source;
+pub mod version_guard;
+pub mod write_order;
+pub mod write_sink;
@@ -0,0 +1,259 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::db::indexer_outbox::IndexerOutboxEntryQuery;
+use anyhow::Context;
+use cedra_indexer_processor_sdk::postgres::utils::database::{new_db_pool, ArcDbPool};
+use diesel_async::{scoped_futures::ScopedFutureExt, AsyncConnection};
+use google_cloud_googleapis::pubsub::v1::PubsubMessage;
+use google_cloud_pubsub::{
+    client::{Client as PubSubClient, ClientConfig as PubSubClientConfig},
+    publisher::Publisher,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Duration};
+use tracing::{debug, info, warn};
+use url::Url;
+
+/// Connections the relay keeps open, it only runs one transaction at a time.
+const OUTBOX_RELAY_DB_POOL_SIZE: u32 = 2;
+
+/// Publishes a "new data available" signal for every checkpoint the processor saves. The entry
+/// is written to `indexer_outbox` in the same transaction as `processor_status`, so there is
+/// exactly one entry per committed version range, and a relay task delivers the entries in
+/// order to the configured sink.
+///
+/// Delivery is at least once: an entry published right before the relay crashes is published
+/// again on restart. Consumers can drop duplicates by the entry's `id`.
+///
+/// Example:
+/// ```yaml
+/// outbox:
+///   sink:
+///     type: webhook
+///     url: "https://jobs.example.com/indexer-updates"
+///   poll_interval_ms: 1000
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutboxConfig {
+    pub sink: OutboxSinkConfig,
+    /// How long the relay waits before looking for new entries once it has published them all
+    #[serde(default = "OutboxConfig::default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    #[serde(default = "OutboxConfig::default_max_entries_per_poll")]
+    pub max_entries_per_poll: i64,
+}
+
+impl OutboxConfig {
+    pub const fn default_poll_interval_ms() -> u64 {
+        1000
+    }
+
+    pub const fn default_max_entries_per_poll() -> i64 {
+        100
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutboxSinkConfig {
+    /// POSTs every entry as JSON to `url`. Any non-2xx response is retried.
+    Webhook(WebhookSinkConfig),
+    /// Publishes every entry as a JSON message to a Pub/Sub topic, with the processor name in
+    /// the `processor` attribute. Credentials are read from the environment.
+    PubSub(PubSubSinkConfig),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookSinkConfig {
+    pub url: Url,
+    #[serde(default = "WebhookSinkConfig::default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl WebhookSinkConfig {
+    pub const fn default_timeout_ms() -> u64 {
+        5000
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PubSubSinkConfig {
+    /// Topic id, e.g. `indexer-updates`, or the full `projects/<project>/topics/<topic>` name
+    pub topic: String,
+}
+
+enum OutboxSink {
+    Webhook { client: reqwest::Client, url: Url },
+    PubSub { publisher: Publisher },
+}
+
+impl OutboxSink {
+    async fn new(config: &OutboxSinkConfig) -> anyhow::Result<Self> {
+        match config {
+            OutboxSinkConfig::Webhook(webhook_config) => {
+                let client = reqwest::Client::builder()
+                    .timeout(Duration::from_millis(webhook_config.timeout_ms))
+                    .build()
+                    .context("Failed to build outbox webhook http client")?;
+                Ok(Self::Webhook {
+                    client,
+                    url: webhook_config.url.clone(),
+                })
+            },
+            OutboxSinkConfig::PubSub(pubsub_config) => {
+                let client_config = PubSubClientConfig::default()
+                    .with_auth()
+                    .await
+                    .context("Failed to authenticate outbox Pub/Sub client")?;
+                let client = PubSubClient::new(client_config)
+                    .await
+                    .context("Failed to create outbox Pub/Sub client")?;
+                let publisher = client.topic(&pubsub_config.topic).new_publisher(None);
+                Ok(Self::PubSub { publisher })
+            },
+        }
+    }
+
+    async fn publish(&self, entry: &IndexerOutboxEntryQuery) -> anyhow::Result<()> {
+        match self {
+            Self::Webhook { client, url } => {
+                client
+                    .post(url.clone())
+                    .json(entry)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .with_context(|| format!("Failed to post outbox entry {}", entry.id))?;
+            },
+            Self::PubSub { publisher } => {
+                let message = PubsubMessage {
+                    data: serde_json::to_vec(entry)?,
+                    attributes: HashMap::from([("processor".to_string(), entry.processor.clone())]),
+                    ..Default::default()
+                };
+                publisher
+                    .publish(message)
+                    .await
+                    .get()
+                    .await
+                    .with_context(|| format!("Failed to publish outbox entry {}", entry.id))?;
+            },
+        }
+        Ok(())
+    }
+}
+
+struct OutboxRelay {
+    processor_name: &'static str,
+    conn_pool: ArcDbPool,
+    sink: OutboxSink,
+    max_entries_per_poll: i64,
+}
+
+impl OutboxRelay {
+    async fn new(
+        processor_name: &'static str,
+        connection_string: &str,
+        config: &OutboxConfig,
+    ) -> anyhow::Result<Self> {
+        let conn_pool = new_db_pool(connection_string, Some(OUTBOX_RELAY_DB_POOL_SIZE))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create outbox relay connection pool: {e:?}"))?;
+        Ok(Self {
+            processor_name,
+            conn_pool,
+            sink: OutboxSink::new(&config.sink).await?,
+            max_entries_per_poll: config.max_entries_per_poll,
+        })
+    }
+
+    /// Publishes the oldest unpublished entries in order, stopping at the first one that fails,
+    /// and returns how many were published. Entries stay locked while they're published, so a
+    /// second relay of the same processor waits instead of publishing them again.
+    async fn relay_once(&self) -> anyhow::Result<usize> {
+        let mut conn = self
+            .conn_pool
+            .get()
+            .await
+            .context("Failed to get a connection for the outbox relay")?;
+        let (published, result) = conn
+            .transaction::<_, anyhow::Error, _>(|conn| {
+                async move {
+                    let entries = IndexerOutboxEntryQuery::lock_unpublished(
+                        self.processor_name,
+                        self.max_entries_per_poll,
+                        conn,
+                    )
+                    .await?;
+                    let mut published = Vec::with_capacity(entries.len());
+                    let mut result = Ok(());
+                    for entry in &entries {
+                        if let Err(e) = self.sink.publish(entry).await {
+                            result = Err(e);
+                            break;
+                        }
+                        published.push(entry.id);
+                    }
+                    // Entries published before a failure are still marked, so they aren't
+                    // published again on the next poll.
+                    if !published.is_empty() {
+                        IndexerOutboxEntryQuery::mark_published(&published, conn).await?;
+                    }
+                    Ok((published.len(), result))
+                }
+                .scope_boxed()
+            })
+            .await?;
+        result.map(|()| published)
+    }
+}
+
+/// Spawns the task delivering `indexer_outbox` entries of the processor to the configured sink.
+/// It keeps retrying on errors, entries are left unpublished until they're delivered.
+pub fn spawn_outbox_relay(
+    processor_name: &'static str,
+    connection_string: String,
+    config: OutboxConfig,
+) {
+    tokio::spawn(async move {
+        let poll_interval = Duration::from_millis(config.poll_interval_ms);
+        let relay = loop {
+            match OutboxRelay::new(processor_name, &connection_string, &config).await {
+                Ok(relay) => break relay,
+                Err(e) => {
+                    warn!(
+                        processor_name = processor_name,
+                        error = ?e,
+                        "Failed to start outbox relay, retrying"
+                    );
+                    tokio::time::sleep(poll_interval).await;
+                },
+            }
+        };
+        info!(processor_name = processor_name, "Outbox relay started");
+        loop {
+            match relay.relay_once().await {
+                Ok(published) if published > 0 => {
+                    debug!(
+                        processor_name = processor_name,
+                        published = published,
+                        "Published outbox entries"
+                    );
+                },
+                Ok(_) => tokio::time::sleep(poll_interval).await,
+                Err(e) => {
+                    warn!(
+                        processor_name = processor_name,
+                        error = ?e,
+                        "Failed to publish outbox entries"
+                    );
+                    tokio::time::sleep(poll_interval).await;
+                },
+            }
+        }
+    });
+}
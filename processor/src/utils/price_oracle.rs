@@ -0,0 +1,355 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::schema::asset_usd_prices;
+use ahash::AHashMap;
+use anyhow::Context;
+use bigdecimal::BigDecimal;
+use cedra_indexer_processor_sdk::postgres::utils::database::ArcDbPool;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use std::{
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use url::Url;
+
+/// Max number of asset ids sent in a single REST price request.
+const REST_PRICE_REQUEST_BATCH_SIZE: usize = 100;
+
+/// Where USD prices used to compute notional values on activities come from.
+///
+/// Prices are expressed per smallest on-chain unit of the asset, i.e. the same unit as the
+/// amount stored on the activity row, so `usd_value = amount * usd_price`.
+///
+/// REST prices are cached for `cache_ttl_secs`, including the ids the endpoint has no price for,
+/// so the assets every batch touches aren't requested again for every batch.
+///
+/// Example:
+/// ```yaml
+/// price_oracle:
+///   type: rest
+///   endpoint: "https://prices.example.com/v1/usd"
+///   cache_ttl_secs: 60
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PriceOracleConfig {
+    /// GET `<endpoint>?ids=<id1>,<id2>,...`, expecting a JSON object of `id -> price`.
+    /// Prices may be encoded as JSON numbers or strings.
+    Rest(RestPriceOracleConfig),
+    /// Read prices from the `asset_usd_prices` table in the processor database, for every batch.
+    Table,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RestPriceOracleConfig {
+    pub endpoint: Url,
+    #[serde(default = "RestPriceOracleConfig::default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default = "RestPriceOracleConfig::default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+}
+
+impl RestPriceOracleConfig {
+    pub const fn default_timeout_ms() -> u64 {
+        2000
+    }
+
+    pub const fn default_cache_ttl_secs() -> u64 {
+        60
+    }
+}
+
+enum PriceSource {
+    Rest {
+        client: reqwest::Client,
+        endpoint: Url,
+    },
+    Table {
+        conn_pool: ArcDbPool,
+    },
+}
+
+/// A price as of when it was fetched, `None` if the source had none
+struct CachedPrice {
+    price: Option<BigDecimal>,
+    fetched_at: Instant,
+}
+
+pub struct PriceOracle {
+    source: PriceSource,
+    cache_ttl: Duration,
+    cache: Mutex<AHashMap<String, CachedPrice>>,
+}
+
+impl PriceOracle {
+    pub fn new(config: &PriceOracleConfig, conn_pool: ArcDbPool) -> anyhow::Result<Self> {
+        let (source, cache_ttl) = match config {
+            PriceOracleConfig::Rest(rest_config) => {
+                let client = reqwest::Client::builder()
+                    .timeout(Duration::from_millis(rest_config.timeout_ms))
+                    .build()
+                    .context("Failed to build price oracle http client")?;
+                let source = PriceSource::Rest {
+                    client,
+                    endpoint: rest_config.endpoint.clone(),
+                };
+                (source, Duration::from_secs(rest_config.cache_ttl_secs))
+            },
+            PriceOracleConfig::Table => (PriceSource::Table { conn_pool }, Duration::ZERO),
+        };
+        Ok(Self {
+            source,
+            cache_ttl,
+            cache: Mutex::new(AHashMap::new()),
+        })
+    }
+
+    /// Returns the USD price for every asset id the oracle knows about. Unknown ids are
+    /// simply missing from the result.
+    pub async fn get_usd_prices(
+        &self,
+        asset_ids: &[String],
+    ) -> anyhow::Result<AHashMap<String, BigDecimal>> {
+        self.get_usd_prices_at(asset_ids, Instant::now()).await
+    }
+
+    async fn get_usd_prices_at(
+        &self,
+        asset_ids: &[String],
+        now: Instant,
+    ) -> anyhow::Result<AHashMap<String, BigDecimal>> {
+        let mut prices = AHashMap::new();
+        let mut missing = vec![];
+        {
+            let cache = self.cache.lock().unwrap();
+            for asset_id in asset_ids {
+                match cache.get(asset_id) {
+                    Some(cached) if now.duration_since(cached.fetched_at) < self.cache_ttl => {
+                        if let Some(price) = &cached.price {
+                            prices.insert(asset_id.clone(), price.clone());
+                        }
+                    },
+                    _ => missing.push(asset_id.clone()),
+                }
+            }
+        }
+        if missing.is_empty() {
+            return Ok(prices);
+        }
+
+        let fetched = self.source.fetch(&missing).await?;
+        let mut cache = self.cache.lock().unwrap();
+        cache.retain(|_, cached| now.duration_since(cached.fetched_at) < self.cache_ttl);
+        for asset_id in missing {
+            let price = fetched.get(&asset_id).cloned();
+            if let Some(price) = &price {
+                prices.insert(asset_id.clone(), price.clone());
+            }
+            if !self.cache_ttl.is_zero() {
+                cache.insert(asset_id, CachedPrice {
+                    price,
+                    fetched_at: now,
+                });
+            }
+        }
+        Ok(prices)
+    }
+}
+
+impl PriceSource {
+    async fn fetch(&self, asset_ids: &[String]) -> anyhow::Result<AHashMap<String, BigDecimal>> {
+        match self {
+            Self::Rest { client, endpoint } => {
+                let mut prices = AHashMap::new();
+                for ids in asset_ids.chunks(REST_PRICE_REQUEST_BATCH_SIZE) {
+                    let response: serde_json::Map<String, serde_json::Value> = client
+                        .get(endpoint.clone())
+                        .query(&[("ids", ids.join(","))])
+                        .send()
+                        .await
+                        .context("Price oracle request failed")?
+                        .error_for_status()
+                        .context("Price oracle returned an error status")?
+                        .json()
+                        .await
+                        .context("Failed to parse price oracle response")?;
+                    for (asset_id, value) in response {
+                        if let Some(price) = parse_price(&value) {
+                            prices.insert(asset_id, price);
+                        }
+                    }
+                }
+                Ok(prices)
+            },
+            Self::Table { conn_pool } => {
+                let mut conn = conn_pool
+                    .get()
+                    .await
+                    .context("Failed to get connection from pool")?;
+                let rows = asset_usd_prices::table
+                    .filter(asset_usd_prices::asset_id.eq_any(asset_ids))
+                    .select((asset_usd_prices::asset_id, asset_usd_prices::usd_price))
+                    .load::<(String, BigDecimal)>(&mut conn)
+                    .await
+                    .context("Failed to query asset_usd_prices")?;
+                Ok(rows.into_iter().collect())
+            },
+        }
+    }
+}
+
+fn parse_price(value: &serde_json::Value) -> Option<BigDecimal> {
+    match value {
+        serde_json::Value::String(s) => BigDecimal::from_str(s).ok(),
+        serde_json::Value::Number(n) => BigDecimal::from_str(&n.to_string()).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MIGRATIONS;
+    use cedra_indexer_processor_sdk::{
+        postgres::utils::database::{new_db_pool, run_migrations},
+        testing_framework::database::{PostgresTestDatabase, TestDatabase},
+    };
+    use diesel_async::{
+        pooled_connection::{bb8::Pool, AsyncDieselConnectionManager},
+        AsyncPgConnection,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// Serves every request with `status` and `body`, counting the requests
+    async fn serve(status: &'static str, body: &'static str) -> (Url, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint =
+            Url::parse(&format!("http://{}/usd", listener.local_addr().unwrap())).unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let served = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0; 4096];
+                let _ = stream.read(&mut request).await;
+                served.fetch_add(1, Ordering::SeqCst);
+                let response = format!(
+                    "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: \
+                     {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (endpoint, requests)
+    }
+
+    fn rest_oracle(endpoint: Url, cache_ttl_secs: u64) -> PriceOracle {
+        let config = PriceOracleConfig::Rest(RestPriceOracleConfig {
+            endpoint,
+            timeout_ms: RestPriceOracleConfig::default_timeout_ms(),
+            cache_ttl_secs,
+        });
+        // The pool is only used by the table source, and bb8 only connects on checkout
+        let manager =
+            AsyncDieselConnectionManager::<AsyncPgConnection>::new("postgresql://localhost/unused");
+        let conn_pool = Arc::new(Pool::builder().build_unchecked(manager));
+        PriceOracle::new(&config, conn_pool).unwrap()
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_rest_prices_are_cached_until_they_expire() {
+        let (endpoint, requests) = serve("200 OK", r#"{"0xa": "1.5", "0xb": 2}"#).await;
+        let oracle = rest_oracle(endpoint, 60);
+        let start = Instant::now();
+
+        let prices = oracle
+            .get_usd_prices_at(&ids(&["0xa", "0xb", "0xc"]), start)
+            .await
+            .unwrap();
+        assert_eq!(prices["0xa"], BigDecimal::from_str("1.5").unwrap());
+        assert_eq!(prices["0xb"], BigDecimal::from(2));
+        assert!(!prices.contains_key("0xc"));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Cached, including the id without a price
+        let cached = oracle
+            .get_usd_prices_at(&ids(&["0xa", "0xc"]), start + Duration::from_secs(59))
+            .await
+            .unwrap();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // Expired
+        oracle
+            .get_usd_prices_at(&ids(&["0xa"]), start + Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rest_failure_is_not_cached() {
+        let (endpoint, requests) = serve("500 Internal Server Error", "{}").await;
+        let oracle = rest_oracle(endpoint, 60);
+        let start = Instant::now();
+
+        assert!(oracle
+            .get_usd_prices_at(&ids(&["0xa"]), start)
+            .await
+            .is_err());
+        assert!(oracle
+            .get_usd_prices_at(&ids(&["0xa"]), start)
+            .await
+            .is_err());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        assert!(oracle.cache.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_table_prices() {
+        let mut db = PostgresTestDatabase::new();
+        db.setup().await.unwrap();
+        let conn_pool = new_db_pool(db.get_db_url().as_str(), Some(10))
+            .await
+            .expect("Failed to create connection pool");
+        run_migrations(db.get_db_url(), conn_pool.clone(), MIGRATIONS).await;
+        diesel::insert_into(asset_usd_prices::table)
+            .values((
+                asset_usd_prices::asset_id.eq("0xa"),
+                asset_usd_prices::usd_price.eq(BigDecimal::from_str("0.25").unwrap()),
+            ))
+            .execute(&mut conn_pool.get().await.unwrap())
+            .await
+            .unwrap();
+
+        let oracle = PriceOracle::new(&PriceOracleConfig::Table, conn_pool.clone()).unwrap();
+        let prices = oracle.get_usd_prices(&ids(&["0xa", "0xb"])).await.unwrap();
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices["0xa"], BigDecimal::from_str("0.25").unwrap());
+
+        // Read again for every batch
+        diesel::update(asset_usd_prices::table)
+            .set(asset_usd_prices::usd_price.eq(BigDecimal::from(1)))
+            .execute(&mut conn_pool.get().await.unwrap())
+            .await
+            .unwrap();
+        let prices = oracle.get_usd_prices(&ids(&["0xa"])).await.unwrap();
+        assert_eq!(prices["0xa"], BigDecimal::from(1));
+    }
+}
@@ -0,0 +1,37 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    config::{indexer_processor_config::IndexerProcessorConfig, processor_mode::ProcessorMode},
+    utils::table_names::TableNames,
+};
+
+/// What a processor is processing, for steps whose output is merged with other networks' output
+/// downstream and needs to be told apart. Only parquet output is tagged with it: a Postgres
+/// database holds a single chain, whose id is stored in `ledger_infos` and checked at startup, so
+/// Postgres tables have no `chain_id` column and networks are told apart by database.
+#[derive(Clone, Debug, Default)]
+pub struct ProcessingContext {
+    /// Chain id of the transaction stream, checked at startup. Unset when reading local
+    /// transactions, whose chain isn't checked.
+    pub chain_id: Option<u64>,
+    pub network: Option<String>,
+    pub processor_name: String,
+    /// Whether parquet uploads are checkpointed for the processor to resume from. Only in the
+    /// default mode, since backfills and tests don't resume from the processor's status.
+    pub checkpoint_uploads: bool,
+    /// Names the tables' files are laid out under
+    pub table_names: TableNames,
+}
+
+impl ProcessingContext {
+    pub fn new(config: &IndexerProcessorConfig, chain_id: Option<u64>) -> Self {
+        Self {
+            chain_id,
+            network: config.network.clone(),
+            processor_name: config.processor_config.name().to_string(),
+            checkpoint_uploads: matches!(config.processor_mode, ProcessorMode::Default(_)),
+            table_names: config.table_names(),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bookkeeping of `processor_mode: ranged` runs, which process a version range once and exit
+//! with a summary instead of a checkpoint, for schedulers like Airflow.
+//!
+//! Versions count as processed once the version tracker saves a status past them, and as
+//! streamed once the transaction source hands a batch of them on. Versions of the range that
+//! weren't streamed, or that the run didn't get through, are reported as gaps. Every attempt
+//! the supervisor restarts processes the range from its start again, so the summary covers the
+//! last attempt only.
+
+use crate::{config::processor_mode::RangedConfig, utils::table_stats::TableWriteStats};
+use ahash::AHashMap;
+use anyhow::{bail, Result};
+use cedra_indexer_processor_sdk::{
+    cedra_protos::transaction::v1::Transaction, types::transaction_context::TransactionContext,
+};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::{collections::BTreeMap, sync::Mutex, time::Duration};
+
+#[derive(Debug, Default)]
+struct RangedRun {
+    /// Version ranges the transaction source streamed, inclusive
+    streamed: Vec<(u64, u64)>,
+    /// Last version saved per processor status, parquet processors saving one per table
+    last_success_versions: AHashMap<String, u64>,
+    rows_per_table: BTreeMap<&'static str, i64>,
+}
+
+/// Progress of the current attempt, `None` outside of ranged runs
+static RANGED_RUN: Lazy<Mutex<Option<RangedRun>>> = Lazy::new(|| Mutex::new(None));
+
+/// Printed to stdout as a JSON line when a ranged run ends
+#[derive(Debug, Serialize)]
+pub struct RangedRunSummary {
+    pub processor: String,
+    pub starting_version: u64,
+    pub ending_version: u64,
+    pub success: bool,
+    /// Last version every table got through, if any
+    pub last_success_version: Option<u64>,
+    pub duration_secs: f64,
+    /// Rows written to each Postgres table
+    pub rows_per_table: BTreeMap<String, i64>,
+    /// Versions of the range that weren't processed, as inclusive `[start, end]` ranges
+    pub gaps: Vec<(u64, u64)>,
+    pub error: Option<String>,
+}
+
+/// Clears the progress of previous attempts, called before every attempt
+pub fn start_ranged_attempt() {
+    *RANGED_RUN.lock().unwrap() = Some(RangedRun::default());
+}
+
+pub fn record_streamed_batches(batches: &[TransactionContext<Vec<Transaction>>]) {
+    if let Some(run) = RANGED_RUN.lock().unwrap().as_mut() {
+        run.streamed.extend(
+            batches
+                .iter()
+                .map(|batch| (batch.metadata.start_version, batch.metadata.end_version)),
+        );
+    }
+}
+
+pub fn record_ranged_status(processor_id: &str, last_success_version: u64) {
+    if let Some(run) = RANGED_RUN.lock().unwrap().as_mut() {
+        run.last_success_versions
+            .insert(processor_id.to_string(), last_success_version);
+    }
+}
+
+pub fn record_ranged_table_stats(table_stats: &[(&'static str, TableWriteStats)]) {
+    if let Some(run) = RANGED_RUN.lock().unwrap().as_mut() {
+        for (table_name, stats) in table_stats {
+            *run.rows_per_table.entry(table_name).or_default() += stats.rows_written;
+        }
+    }
+}
+
+/// Versions of `starting_version` through `ending_version` outside of the `streamed` ranges or
+/// past `last_success_version`
+fn find_gaps(
+    starting_version: u64,
+    ending_version: u64,
+    mut streamed: Vec<(u64, u64)>,
+    last_success_version: Option<u64>,
+) -> Vec<(u64, u64)> {
+    let processed_through = match last_success_version {
+        Some(version) => version.min(ending_version),
+        None => return vec![(starting_version, ending_version)],
+    };
+    streamed.sort_unstable();
+    let mut gaps = vec![];
+    let mut next_version = starting_version;
+    for (start, end) in streamed {
+        if start > processed_through {
+            break;
+        }
+        if start > next_version {
+            gaps.push((next_version, start - 1));
+        }
+        next_version = next_version.max(end.saturating_add(1));
+    }
+    if next_version <= processed_through {
+        gaps.push((next_version, processed_through));
+        next_version = processed_through + 1;
+    }
+    if next_version <= ending_version {
+        gaps.push((next_version, ending_version));
+    }
+    gaps
+}
+
+/// Prints the summary of the ranged run that ended with `result`, and fails unless the run
+/// succeeded without gaps
+pub fn finish_ranged_run(
+    processor_name: &str,
+    config: &RangedConfig,
+    duration: Duration,
+    result: Result<()>,
+) -> Result<()> {
+    let run = RANGED_RUN.lock().unwrap().take().unwrap_or_default();
+    let last_success_version = run.last_success_versions.values().min().copied();
+    let gaps = find_gaps(
+        config.starting_version,
+        config.ending_version,
+        run.streamed,
+        last_success_version,
+    );
+    let summary = RangedRunSummary {
+        processor: processor_name.to_string(),
+        starting_version: config.starting_version,
+        ending_version: config.ending_version,
+        success: result.is_ok() && gaps.is_empty(),
+        last_success_version,
+        duration_secs: duration.as_secs_f64(),
+        rows_per_table: run
+            .rows_per_table
+            .into_iter()
+            .map(|(table_name, rows)| (table_name.to_string(), rows))
+            .collect(),
+        gaps,
+        error: result.as_ref().err().map(|e| format!("{e:#}")),
+    };
+    println!("{}", serde_json::to_string(&summary)?);
+    result?;
+    if !summary.gaps.is_empty() {
+        bail!(
+            "Ranged run of versions {} to {} left gaps {:?}",
+            config.starting_version,
+            config.ending_version,
+            summary.gaps
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_gaps() {
+        assert!(find_gaps(100, 199, vec![(150, 199), (100, 149)], Some(199)).is_empty());
+        assert_eq!(
+            find_gaps(100, 199, vec![(100, 119), (130, 179)], Some(179)),
+            vec![(120, 129), (180, 199)]
+        );
+        assert_eq!(find_gaps(100, 199, vec![(100, 149)], None), vec![(
+            100, 199
+        )]);
+    }
+}
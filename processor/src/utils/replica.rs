@@ -0,0 +1,202 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    utils::{
+        counters::{
+            REPLICA_PENDING_WRITES, REPLICA_WRITES_DROPPED_COUNT, REPLICA_WRITE_ERRORS_COUNT,
+            REPLICA_WRITE_LAG_IN_SECS,
+        },
+        write_sink::{add_write_sink, TableBatch, WriteSink},
+    },
+    MIGRATIONS,
+};
+use async_trait::async_trait;
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::{new_db_pool, run_migrations, ArcDbPool},
+    utils::errors::ProcessorError,
+};
+use futures::{future::BoxFuture, FutureExt};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// How long the writer waits before connecting to the replica again.
+const REPLICA_CONNECT_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Writes every batch the storers commit to a second database as well, e.g. a warm standby in
+/// another region where logical replication slots aren't available.
+///
+/// Writes are queued after the primary commits and applied in order by a background task, so
+/// the replica never slows the processor down. It's best effort: writes are dropped when the
+/// queue is full or the replica is unreachable, and failed writes aren't retried. The replica's
+/// `processor_status` is saved behind the batches queued before it, so a processor failing over
+/// to the replica resumes from what the replica received.
+///
+/// Example:
+/// ```yaml
+/// replica:
+///   connection_string: "postgresql://postgres:@standby.europe-west1:5432/example"
+///   max_pending_writes: 10000
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReplicaConfig {
+    pub connection_string: String,
+    #[serde(default = "ReplicaConfig::default_db_pool_size")]
+    pub db_pool_size: u32,
+    /// Writes queued for the replica before new ones are dropped
+    #[serde(default = "ReplicaConfig::default_max_pending_writes")]
+    pub max_pending_writes: usize,
+}
+
+impl ReplicaConfig {
+    pub const fn default_db_pool_size() -> u32 {
+        30
+    }
+
+    pub const fn default_max_pending_writes() -> usize {
+        1000
+    }
+}
+
+type ReplicaQuery = Box<dyn FnOnce(ArcDbPool) -> BoxFuture<'static, anyhow::Result<()>> + Send>;
+
+struct ReplicaWrite {
+    table_name: &'static str,
+    queued_at: Instant,
+    execute: ReplicaQuery,
+}
+
+struct ReplicaWriter {
+    processor_name: &'static str,
+    sender: mpsc::Sender<ReplicaWrite>,
+}
+
+static REPLICA_WRITER: OnceCell<ReplicaWriter> = OnceCell::new();
+
+/// Starts the task writing to the replica. Writes queued before it connects wait in the queue.
+pub fn spawn_replica_writer(
+    processor_name: &'static str,
+    config: ReplicaConfig,
+) -> anyhow::Result<()> {
+    let (sender, receiver) = mpsc::channel(config.max_pending_writes.max(1));
+    if REPLICA_WRITER
+        .set(ReplicaWriter {
+            processor_name,
+            sender,
+        })
+        .is_err()
+    {
+        warn!("Replica writer is already running");
+        return Ok(());
+    }
+    tokio::spawn(run_replica_writer(processor_name, config, receiver));
+    add_write_sink(Arc::new(ReplicaSink))
+}
+
+/// Queues the rows of every batch for the replica once the primary committed them
+struct ReplicaSink;
+
+#[async_trait]
+impl WriteSink for ReplicaSink {
+    fn name(&self) -> &'static str {
+        "replica"
+    }
+
+    async fn committed(
+        &self,
+        batch: &TableBatch<'_>,
+        written: &[usize],
+    ) -> Result<(), ProcessorError> {
+        let write = batch.write(written);
+        replicate(batch.table_name, move |replica_pool| {
+            async move {
+                write(replica_pool)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| anyhow::anyhow!("{e:?}"))
+            }
+            .boxed()
+        });
+        Ok(())
+    }
+}
+
+/// Queues a write to the replica, `execute` runs it against the replica's pool. Does nothing if
+/// there's no replica.
+pub fn replicate(
+    table_name: &'static str,
+    execute: impl FnOnce(ArcDbPool) -> BoxFuture<'static, anyhow::Result<()>> + Send + 'static,
+) {
+    let Some(writer) = REPLICA_WRITER.get() else {
+        return;
+    };
+    let write = ReplicaWrite {
+        table_name,
+        queued_at: Instant::now(),
+        execute: Box::new(execute),
+    };
+    match writer.sender.try_send(write) {
+        Ok(()) => REPLICA_PENDING_WRITES
+            .with_label_values(&[writer.processor_name])
+            .inc(),
+        Err(_) => REPLICA_WRITES_DROPPED_COUNT
+            .with_label_values(&[writer.processor_name, table_name])
+            .inc(),
+    }
+}
+
+async fn run_replica_writer(
+    processor_name: &'static str,
+    config: ReplicaConfig,
+    mut receiver: mpsc::Receiver<ReplicaWrite>,
+) {
+    let db_pool = loop {
+        match new_db_pool(&config.connection_string, Some(config.db_pool_size)).await {
+            Ok(db_pool) => break db_pool,
+            Err(e) => {
+                warn!(
+                    processor_name = processor_name,
+                    error = ?e,
+                    "Failed to connect to the replica, retrying"
+                );
+                tokio::time::sleep(REPLICA_CONNECT_RETRY_DELAY).await;
+            },
+        }
+    };
+    run_migrations(
+        config.connection_string.clone(),
+        db_pool.clone(),
+        MIGRATIONS,
+    )
+    .await;
+    info!(processor_name = processor_name, "Replica writer is running");
+
+    while let Some(write) = receiver.recv().await {
+        REPLICA_PENDING_WRITES
+            .with_label_values(&[processor_name])
+            .dec();
+        match (write.execute)(db_pool.clone()).await {
+            Ok(()) => REPLICA_WRITE_LAG_IN_SECS
+                .with_label_values(&[processor_name])
+                .set(write.queued_at.elapsed().as_secs_f64()),
+            Err(e) => {
+                REPLICA_WRITE_ERRORS_COUNT
+                    .with_label_values(&[processor_name, write.table_name])
+                    .inc();
+                warn!(
+                    processor_name = processor_name,
+                    table_name = write.table_name,
+                    error = ?e,
+                    "Failed to write to the replica"
+                );
+            },
+        }
+    }
+}
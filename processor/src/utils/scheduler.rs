@@ -0,0 +1,233 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Periodic maintenance jobs, like pruning, snapshotting, reconciliation or refreshing stats,
+//! run next to the processor instead of each in its own interval loop. Processors register a job
+//! by name with a default interval, which `scheduled_jobs` can override or turn off.
+//!
+//! Jobs outlive supervisor restarts. A job registered again, e.g. by a restarted processor,
+//! replaces the previous registration at its next run instead of running twice. Runs of a job
+//! never overlap: one that takes longer than the interval delays the next. Failed runs are
+//! logged and counted in `indexer_processor_scheduled_job_runs`, and the job runs again at its
+//! next interval.
+
+use crate::utils::counters::SCHEDULED_JOB_RUNS_COUNT;
+use ahash::AHashMap;
+use anyhow::{bail, Result};
+use futures::future::BoxFuture;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+/// Overrides of a registered job, by job name.
+///
+/// Example:
+/// ```yaml
+/// scheduled_jobs:
+///   job_name:
+///     interval_secs: 3600
+///   other_job_name:
+///     enabled: false
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduledJobConfig {
+    #[serde(default = "ScheduledJobConfig::default_enabled")]
+    pub enabled: bool,
+    /// Replaces the interval the job was registered with
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+}
+
+impl ScheduledJobConfig {
+    pub const fn default_enabled() -> bool {
+        true
+    }
+}
+
+impl Default for ScheduledJobConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            interval_secs: None,
+        }
+    }
+}
+
+type JobFn = Arc<dyn Fn() -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+static JOB_CONFIGS: Lazy<RwLock<HashMap<String, ScheduledJobConfig>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Latest registration of every running job
+static JOBS: Lazy<Mutex<AHashMap<&'static str, JobFn>>> = Lazy::new(|| Mutex::new(AHashMap::new()));
+
+pub fn validate_scheduled_jobs(configs: &HashMap<String, ScheduledJobConfig>) -> Result<()> {
+    for (job_name, config) in configs {
+        if config.interval_secs == Some(0) {
+            bail!("interval_secs of scheduled job {job_name} must be positive");
+        }
+    }
+    Ok(())
+}
+
+pub fn set_scheduled_job_configs(configs: &HashMap<String, ScheduledJobConfig>) -> Result<()> {
+    validate_scheduled_jobs(configs)?;
+    *JOB_CONFIGS.write().unwrap() = configs.clone();
+    Ok(())
+}
+
+/// Interval a job runs at, None if it's turned off
+fn job_interval(job_name: &str, default_interval: Duration) -> Option<Duration> {
+    let config = JOB_CONFIGS
+        .read()
+        .unwrap()
+        .get(job_name)
+        .cloned()
+        .unwrap_or_default();
+    config.enabled.then(|| {
+        config
+            .interval_secs
+            .map_or(default_interval, Duration::from_secs)
+    })
+}
+
+/// Runs `run` every `default_interval`, or the interval configured for `job_name`, starting one
+/// interval from now.
+///
+/// ```ignore
+/// register_job(self.name(), "prune_example", Duration::from_secs(3600), move || {
+///     let db_pool = db_pool.clone();
+///     async move { prune_example(db_pool).await }.boxed()
+/// });
+/// ```
+pub fn register_job<F>(
+    processor_name: &'static str,
+    job_name: &'static str,
+    default_interval: Duration,
+    run: F,
+) where
+    F: Fn() -> BoxFuture<'static, Result<()>> + Send + Sync + 'static,
+{
+    let Some(interval) = job_interval(job_name, default_interval) else {
+        info!(
+            processor_name = processor_name,
+            job_name = job_name,
+            "Scheduled job is turned off"
+        );
+        return;
+    };
+    if JOBS
+        .lock()
+        .unwrap()
+        .insert(job_name, Arc::new(run))
+        .is_some()
+    {
+        return;
+    }
+    info!(
+        processor_name = processor_name,
+        job_name = job_name,
+        interval_secs = interval.as_secs_f64(),
+        "Scheduled job registered"
+    );
+    tokio::spawn(run_job(processor_name, job_name, interval));
+}
+
+async fn run_job(processor_name: &'static str, job_name: &'static str, interval: Duration) {
+    let mut ticks = tokio::time::interval(interval);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes right away
+    ticks.tick().await;
+    loop {
+        ticks.tick().await;
+        let Some(run) = JOBS.lock().unwrap().get(job_name).cloned() else {
+            return;
+        };
+        let result = match run().await {
+            Ok(()) => "success",
+            Err(e) => {
+                warn!(
+                    processor_name = processor_name,
+                    job_name = job_name,
+                    error = ?e,
+                    "Scheduled job failed, it runs again at its next interval"
+                );
+                "error"
+            },
+        };
+        SCHEDULED_JOB_RUNS_COUNT
+            .with_label_values(&[processor_name, job_name, result])
+            .inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_job_interval() {
+        set_scheduled_job_configs(&HashMap::from([
+            ("scheduler_test_slower".to_string(), ScheduledJobConfig {
+                enabled: true,
+                interval_secs: Some(60),
+            }),
+            ("scheduler_test_off".to_string(), ScheduledJobConfig {
+                enabled: false,
+                interval_secs: None,
+            }),
+        ]))
+        .unwrap();
+        let default_interval = Duration::from_secs(10);
+        assert_eq!(
+            job_interval("scheduler_test_slower", default_interval),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(job_interval("scheduler_test_off", default_interval), None);
+        assert_eq!(
+            job_interval("scheduler_test_default", default_interval),
+            Some(default_interval)
+        );
+        assert!(set_scheduled_job_configs(&HashMap::from([(
+            "scheduler_test_zero".to_string(),
+            ScheduledJobConfig {
+                enabled: true,
+                interval_secs: Some(0),
+            }
+        )]))
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_registering_again_replaces_the_job() {
+        let first_runs = Arc::new(AtomicUsize::new(0));
+        let second_runs = Arc::new(AtomicUsize::new(0));
+        for runs in [first_runs.clone(), second_runs.clone()] {
+            register_job(
+                "scheduler_test",
+                "scheduler_test_replaced",
+                Duration::from_millis(10),
+                move || {
+                    let runs = runs.clone();
+                    async move {
+                        runs.fetch_add(1, Ordering::SeqCst);
+                        Ok(())
+                    }
+                    .boxed()
+                },
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(first_runs.load(Ordering::SeqCst), 0);
+        assert!(second_runs.load(Ordering::SeqCst) >= 2);
+    }
+}
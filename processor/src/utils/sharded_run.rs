@@ -0,0 +1,301 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! `processor_mode: sharded` runs, which split the versions into ranges of `range_size` dealt
+//! round robin to `shard_count` shards. Shard `i` processes ranges `i`, `i + shard_count`,
+//! `i + 2 * shard_count` and so on, each with its own run of the processor in the shard mode.
+//!
+//! Every shard processes its ranges in order, so all of its versions before the next one it
+//! hasn't processed are done. The merged watermark is the lowest of these next versions, minus
+//! one, and is saved as the processor's `processor_status` whenever it moves. A run restarted
+//! after a crash starts over from the merged watermark, reprocessing what shards got through past
+//! it. A shard retrying a range resumes from its `processor_shard_status` row.
+//!
+//! Shards run in the same process, so they would share the process-wide state of a run. Table
+//! stats aren't recorded during sharded runs, and config reloads are ignored.
+
+use crate::{
+    config::{
+        db_config::DbConfig,
+        indexer_processor_config::IndexerProcessorConfig,
+        processor_config::ProcessorName,
+        processor_mode::{ProcessorMode, ShardRange, ShardedConfig},
+    },
+    db::{connection_pool::new_postgres_pool, schema_drift::run_migrations_and_check_drift},
+    processors::processor_status_saver::save_merged_watermark,
+    utils::{
+        config_reload::{reloaded_transaction_stream_config, RESTART_REQUESTED},
+        supervisor::supervise,
+        table_stats::disable_table_stats,
+    },
+};
+use anyhow::{bail, Context, Result};
+use cedra_indexer_processor_sdk::postgres::models::processor_status::ProcessorStatusQuery;
+use chrono::NaiveDateTime;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use tracing::info;
+
+/// Processors whose rows only depend on the transactions of a batch. The others read back rows
+/// written for earlier versions, like the stake processor's epoch-end snapshots or the token v2
+/// processor's collection stats, which ranges processed out of order would get wrong.
+const SHARDABLE_PROCESSORS: [ProcessorName; 10] = [
+    ProcessorName::AccountRestorationProcessor,
+    ProcessorName::AccountTransactionsProcessor,
+    ProcessorName::DefaultProcessor,
+    ProcessorName::EventsProcessor,
+    ProcessorName::GasFeeProcessor,
+    ProcessorName::MonitoringProcessor,
+    ProcessorName::RawTransactionsProcessor,
+    ProcessorName::ResourceWatcherProcessor,
+    ProcessorName::TreasuryFlowsProcessor,
+    ProcessorName::ValidatorTransactionProcessor,
+];
+
+#[derive(Debug)]
+struct ShardProgress {
+    /// Next version of the shard's ranges it hasn't processed
+    next_version: u64,
+    /// Last version the shard saved a status for, with its timestamp
+    last_success: Option<(u64, Option<NaiveDateTime>)>,
+}
+
+#[derive(Debug)]
+struct ShardedRun {
+    starting_version: u64,
+    shards: Vec<ShardProgress>,
+    /// Merged watermark saved last
+    watermark: Option<u64>,
+}
+
+/// Progress of the shards, `None` outside of sharded runs
+static SHARDED_RUN: Lazy<Mutex<Option<ShardedRun>>> = Lazy::new(|| Mutex::new(None));
+
+/// Versions shard `shard_index` processes in its `round`th range, None once the ranges are past
+/// the ending version
+fn shard_range(
+    config: &ShardedConfig,
+    starting_version: u64,
+    shard_index: u64,
+    round: u64,
+) -> Option<ShardRange> {
+    let range_index = round
+        .checked_mul(config.shard_count)?
+        .checked_add(shard_index)?;
+    let range_start = starting_version.checked_add(range_index.checked_mul(config.range_size)?)?;
+    let mut range_end = range_start.saturating_add(config.range_size - 1);
+    if let Some(ending_version) = config.ending_version {
+        if range_start > ending_version {
+            return None;
+        }
+        range_end = range_end.min(ending_version);
+    }
+    Some(ShardRange {
+        shard_index,
+        starting_version: range_start,
+        ending_version: range_end,
+    })
+}
+
+/// Next version of a shard that processed all of its ranges
+fn finished_version(config: &ShardedConfig) -> u64 {
+    config
+        .ending_version
+        .map_or(u64::MAX, |ending_version| ending_version.saturating_add(1))
+}
+
+/// Last version every shard got through, None while it's before `starting_version`
+fn merged_watermark(starting_version: u64, shards: &[ShardProgress]) -> Option<u64> {
+    shards
+        .iter()
+        .map(|shard| shard.next_version)
+        .min()
+        .filter(|next_version| *next_version > starting_version)
+        .map(|next_version| next_version - 1)
+}
+
+/// Moves a shard's next version to `next_version` and returns the merged watermark, with the
+/// timestamp of its transaction if known, when it moved
+fn advance_shard(
+    shard_index: u64,
+    next_version: u64,
+    last_success: Option<(u64, Option<NaiveDateTime>)>,
+) -> Option<(u64, Option<NaiveDateTime>)> {
+    let mut run = SHARDED_RUN.lock().unwrap();
+    let run = run.as_mut()?;
+    let shard = run.shards.get_mut(shard_index as usize)?;
+    shard.next_version = shard.next_version.max(next_version);
+    if last_success.is_some() {
+        shard.last_success = last_success;
+    }
+    let watermark = merged_watermark(run.starting_version, &run.shards)?;
+    if run.watermark.is_some_and(|saved| saved >= watermark) {
+        return None;
+    }
+    run.watermark = Some(watermark);
+    let timestamp = run
+        .shards
+        .iter()
+        .filter_map(|shard| shard.last_success)
+        .find(|(version, _)| *version == watermark)
+        .and_then(|(_, timestamp)| timestamp);
+    Some((watermark, timestamp))
+}
+
+/// Records a status saved by a shard, and returns the merged watermark if it moved
+pub fn record_shard_status(
+    shard_index: u64,
+    last_success_version: u64,
+    last_transaction_timestamp: Option<NaiveDateTime>,
+) -> Option<(u64, Option<NaiveDateTime>)> {
+    advance_shard(
+        shard_index,
+        last_success_version + 1,
+        Some((last_success_version, last_transaction_timestamp)),
+    )
+}
+
+/// Runs the processor of `config` with its versions split across shards, until every shard
+/// processed its ranges through the ending version, or forever without one.
+pub async fn run_sharded(
+    config: &IndexerProcessorConfig,
+    sharded_config: &ShardedConfig,
+) -> Result<()> {
+    let DbConfig::PostgresConfig(ref postgres_config) = config.db_config else {
+        bail!("Sharded mode only runs processors writing to Postgres");
+    };
+    if sharded_config.shard_count == 0 || sharded_config.range_size == 0 {
+        bail!("shard_count and range_size of sharded mode must be positive");
+    }
+    let processor_name = config.processor_config.name();
+    if !SHARDABLE_PROCESSORS.contains(&ProcessorName::from(&config.processor_config)) {
+        bail!("{processor_name} reads rows of earlier versions and can't run in sharded mode");
+    }
+    disable_table_stats();
+    let db_pool = new_postgres_pool(postgres_config)
+        .await
+        .context("Failed to create connection pool for the sharded run")?;
+    // Up front, so the shards starting together don't all apply pending migrations at once
+    run_migrations_and_check_drift(postgres_config, db_pool.clone()).await?;
+    let status = {
+        let mut conn = db_pool.get().await?;
+        ProcessorStatusQuery::get_by_processor(processor_name, &mut conn)
+            .await
+            .context("Failed to query processor_status table")?
+    };
+    let starting_version = match status {
+        Some(status) => {
+            (status.last_success_version as u64 + 1).max(sharded_config.initial_starting_version)
+        },
+        None => sharded_config.initial_starting_version,
+    };
+    info!(
+        processor_name = processor_name,
+        starting_version = starting_version,
+        shard_count = sharded_config.shard_count,
+        range_size = sharded_config.range_size,
+        "Starting sharded run"
+    );
+    *SHARDED_RUN.lock().unwrap() = Some(ShardedRun {
+        starting_version,
+        shards: (0..sharded_config.shard_count)
+            .map(|shard_index| ShardProgress {
+                // A shard without ranges has nothing left to process
+                next_version: shard_range(sharded_config, starting_version, shard_index, 0)
+                    .map_or(finished_version(sharded_config), |range| {
+                        range.starting_version
+                    }),
+                last_success: None,
+            })
+            .collect(),
+        watermark: None,
+    });
+
+    let shards = (0..sharded_config.shard_count).map(|shard_index| {
+        let db_pool = db_pool.clone();
+        async move {
+            for round in 0.. {
+                let Some(range) = shard_range(sharded_config, starting_version, shard_index, round)
+                else {
+                    break;
+                };
+                info!(
+                    processor_name = processor_name,
+                    shard_index = shard_index,
+                    starting_version = range.starting_version,
+                    ending_version = range.ending_version,
+                    "Shard starting range"
+                );
+                let ending_version = range.ending_version;
+                supervise(
+                    processor_name,
+                    &config.supervisor_config,
+                    false,
+                    &RESTART_REQUESTED,
+                    || {
+                        let mut config = config.clone();
+                        if let Some(reloaded) = reloaded_transaction_stream_config() {
+                            config.transaction_stream_config.transaction_filter =
+                                reloaded.transaction_filter;
+                        }
+                        config.processor_mode = ProcessorMode::Shard(range.clone());
+                        config.run_once()
+                    },
+                )
+                .await
+                .with_context(|| {
+                    format!("Shard {shard_index} failed to process up to version {ending_version}")
+                })?;
+            }
+            // Every version of the shard is processed, so it no longer holds the watermark back
+            if let Some((watermark, timestamp)) =
+                advance_shard(shard_index, finished_version(sharded_config), None)
+            {
+                save_merged_watermark(processor_name, watermark, timestamp, db_pool).await?;
+            }
+            anyhow::Ok(())
+        }
+    });
+    let result = futures::future::try_join_all(shards).await.map(|_| ());
+    *SHARDED_RUN.lock().unwrap() = None;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_ranges_and_merged_watermark() {
+        let config = ShardedConfig {
+            shard_count: 2,
+            range_size: 10,
+            initial_starting_version: 0,
+            ending_version: Some(34),
+        };
+        let ranges = |shard_index| {
+            (0..)
+                .map_while(|round| shard_range(&config, 100, shard_index, round))
+                .map(|range| (range.starting_version, range.ending_version))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ranges(0), vec![(100, 109), (120, 129)]);
+        assert_eq!(ranges(1), vec![(110, 119), (130, 134)]);
+        assert!(shard_range(&config, 200, 0, 0).is_none());
+
+        let shard = |next_version| ShardProgress {
+            next_version,
+            last_success: None,
+        };
+        assert_eq!(merged_watermark(100, &[shard(100), shard(115)]), None);
+        assert_eq!(merged_watermark(100, &[shard(105), shard(115)]), Some(104));
+        // Shard 0 finished its first range and hasn't saved a status in its second one yet
+        assert_eq!(merged_watermark(100, &[shard(110), shard(120)]), Some(109));
+        // Both shards processed their ranges through the ending version
+        let finished = finished_version(&config);
+        assert_eq!(
+            merged_watermark(100, &[shard(finished), shard(finished)]),
+            Some(34)
+        );
+    }
+}
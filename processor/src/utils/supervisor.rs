@@ -0,0 +1,244 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    config::supervisor_config::SupervisorConfig, utils::counters::PROCESSOR_RESTART_COUNT,
+};
+use anyhow::Result;
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{sync::Notify, task::JoinHandle, time::Instant};
+use tracing::{error, info, warn};
+
+type StepTasks = Arc<Mutex<Vec<JoinHandle<()>>>>;
+
+tokio::task_local! {
+    /// Step tasks spawned by the supervised run on this task
+    static RUN_STEP_TASKS: StepTasks;
+}
+
+/// Hands the tasks the SDK spawned for a processor's steps to the supervisor running it, which
+/// stops them once the run ends. Outside a supervised run they're left running, as the SDK
+/// leaves them.
+pub fn supervise_step_tasks(join_handles: Vec<JoinHandle<()>>) {
+    let _ = RUN_STEP_TASKS.try_with(|step_tasks| step_tasks.lock().unwrap().extend(join_handles));
+}
+
+/// Stops every step task a run handed over and waits until they're all gone, so no step of the
+/// run is still writing once the next one starts
+async fn stop_step_tasks(step_tasks: &StepTasks) {
+    let step_tasks = std::mem::take(&mut *step_tasks.lock().unwrap());
+    for step_task in step_tasks {
+        step_task.abort();
+        let _ = step_task.await;
+    }
+}
+
+/// Runs the processor produced by `run_once` until it finishes, restarting it with exponential
+/// backoff when it fails.
+///
+/// Each run is spawned on its own task so that a panic is caught rather than taking down the
+/// process. The SDK spawns a task per step, which outlives the run that spawned it, so runs hand
+/// them over with `supervise_step_tasks`. When a run ends, its step tasks are aborted and awaited
+/// before the next run builds a new graph, so two runs never write at the same time.
+///
+/// A processor without an ending version is never expected to finish, so if
+/// `restart_on_completion` is set, a run returning `Ok` is treated as a failure too.
+///
+/// Notifying `restart_requested` stops the current run and starts a new one right away, without
+/// counting it as a failure.
+pub async fn supervise<F, Fut>(
+    processor_name: &str,
+    config: &SupervisorConfig,
+    restart_on_completion: bool,
+    restart_requested: &Notify,
+    mut run_once: F,
+) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    let healthy_run = Duration::from_secs(config.healthy_run_secs);
+    let mut restarts = 0;
+    loop {
+        let started_at = Instant::now();
+        let step_tasks = StepTasks::default();
+        let mut run = tokio::spawn(RUN_STEP_TASKS.scope(step_tasks.clone(), run_once()));
+        let result = tokio::select! {
+            result = &mut run => result,
+            _ = restart_requested.notified() => {
+                info!(processor_name = processor_name, "Restart requested, stopping processor");
+                run.abort();
+                let _ = run.await;
+                stop_step_tasks(&step_tasks).await;
+                continue;
+            },
+        };
+        stop_step_tasks(&step_tasks).await;
+        let error = match result {
+            Ok(Ok(())) if !restart_on_completion => return Ok(()),
+            Ok(Ok(())) => anyhow::anyhow!("Processor stopped without reaching an ending version"),
+            Ok(Err(e)) => e,
+            Err(join_error) if join_error.is_panic() => {
+                anyhow::anyhow!("Processor panicked: {:?}", join_error)
+            },
+            Err(join_error) => anyhow::anyhow!("Processor task was cancelled: {:?}", join_error),
+        };
+
+        if started_at.elapsed() >= healthy_run {
+            restarts = 0;
+        }
+        if restarts >= config.max_restarts {
+            error!(
+                processor_name = processor_name,
+                restarts = restarts,
+                error = ?error,
+                "Processor failed and restart limit is reached, exiting"
+            );
+            return Err(error);
+        }
+        restarts += 1;
+
+        let backoff = config.backoff(restarts);
+        warn!(
+            processor_name = processor_name,
+            restart = restarts,
+            backoff_ms = backoff.as_millis() as u64,
+            error = ?error,
+            "Processor failed, restarting from the last committed version"
+        );
+        PROCESSOR_RESTART_COUNT
+            .with_label_values(&[processor_name])
+            .inc();
+        tokio::time::sleep(backoff).await;
+        info!(
+            processor_name = processor_name,
+            restart = restarts,
+            "Restarting processor"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+
+    fn test_config(max_restarts: u32) -> SupervisorConfig {
+        SupervisorConfig {
+            max_restarts,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 1,
+            healthy_run_secs: 300,
+        }
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let config = SupervisorConfig {
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1000,
+            ..Default::default()
+        };
+        assert_eq!(config.backoff(1), Duration::from_millis(100));
+        assert_eq!(config.backoff(3), Duration::from_millis(400));
+        assert_eq!(config.backoff(5), Duration::from_millis(1000));
+        assert_eq!(config.backoff(u32::MAX), Duration::from_millis(1000));
+    }
+
+    #[tokio::test]
+    async fn test_restarts_after_error_and_panic() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let result = supervise(
+            "test_processor",
+            &test_config(5),
+            false,
+            &Notify::new(),
+            || {
+                let attempts = attempts.clone();
+                async move {
+                    match attempts.fetch_add(1, Ordering::SeqCst) {
+                        0 => Err(anyhow::anyhow!("step failed")),
+                        1 => panic!("step panicked"),
+                        _ => Ok(()),
+                    }
+                }
+            },
+        )
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_restarts() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let result = supervise(
+            "test_processor",
+            &test_config(2),
+            true,
+            &Notify::new(),
+            || {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async { Ok(()) }
+            },
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_run_writers_are_stopped_before_restart() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        // Held by the failed run's writer while it's running
+        let writer_alive = Arc::new(());
+        // Writes of the failed run's writer seen by the next run
+        let overlapping_writes = Arc::new(AtomicU32::new(0));
+        let writes = Arc::new(AtomicU32::new(0));
+        let result = supervise(
+            "test_processor",
+            &test_config(5),
+            false,
+            &Notify::new(),
+            || {
+                let attempts = attempts.clone();
+                let writer_alive = writer_alive.clone();
+                let overlapping_writes = overlapping_writes.clone();
+                let writes = writes.clone();
+                async move {
+                    if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                        let writes = writes.clone();
+                        let writer = tokio::spawn(async move {
+                            let _writer_alive = writer_alive;
+                            loop {
+                                writes.fetch_add(1, Ordering::SeqCst);
+                                tokio::time::sleep(Duration::from_millis(1)).await;
+                            }
+                        });
+                        supervise_step_tasks(vec![writer]);
+                        tokio::time::sleep(Duration::from_millis(5)).await;
+                        return Err(anyhow::anyhow!("storer failed"));
+                    }
+                    let before = writes.load(Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    overlapping_writes
+                        .store(writes.load(Ordering::SeqCst) - before, Ordering::SeqCst);
+                    drop(writer_alive);
+                    Ok(())
+                }
+            },
+        )
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(overlapping_writes.load(Ordering::SeqCst), 0);
+        assert_eq!(Arc::strong_count(&writer_alive), 1);
+    }
+}
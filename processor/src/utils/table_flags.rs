@@ -1,10 +1,14 @@
 use bitflags::bitflags;
-use std::collections::HashSet;
+use once_cell::sync::Lazy;
+use std::{collections::HashSet, sync::RwLock};
 
 bitflags! {
     #[derive(Debug, Clone, Copy, Eq, PartialEq)]
     pub struct TableFlags: u128 {
-       // Default Processor: 1-10
+        // Sections are by the processor writing the tables. Bits aren't stored anywhere, tables
+        // are selected by name, so they can be moved to grow a section. 111-127 are free.
+
+        // Default Processor: 1-10
         const TRANSACTIONS = 1 << 1;
         const WRITE_SET_CHANGES = 1 << 2;
         const MOVE_RESOURCES = 1 << 3;
@@ -13,8 +17,9 @@ bitflags! {
         const MOVE_MODULES = 1 << 6;
         const CURRENT_TABLE_ITEMS = 1 << 7;
         const BLOCK_METADATA_TRANSACTIONS = 1 << 8;
+        const WRITE_SET_CHANGE_STATS = 1 << 9;
 
-        // Fungible Asset Processor: 11-20
+        // Fungible Asset Processor: 11-25
         const FUNGIBLE_ASSET_BALANCES = 1 << 11;
         const CURRENT_FUNGIBLE_ASSET_BALANCES = 1 << 12;
         const FUNGIBLE_ASSET_ACTIVITIES = 1 << 13;
@@ -22,29 +27,40 @@ bitflags! {
         const CURRENT_UNIFIED_FUNGIBLE_ASSET_BALANCES = 1 << 15;
         const CURRENT_FUNGIBLE_ASSET_BALANCES_LEGACY = 1 << 16;
         const FUNGIBLE_ASSET_TO_COIN_MAPPINGS = 1 << 17;
+        const COIN_INFO_MUTATIONS = 1 << 18;
+        const COIN_HOLDER_COUNTS = 1 << 19;
+        const COIN_STORE_FREEZE_EVENTS = 1 << 20;
+        const CURRENT_COIN_BALANCES = 1 << 21;
         // TODO:: Add new v1 to v2 fa mapping table when migrating fa processor
 
-        // Objects Processor: 21-30
-        const OBJECTS = 1 << 21;
-        const CURRENT_OBJECTS = 1 << 22;
+        // Objects Processor: 26-30
+        const OBJECTS = 1 << 26;
+        const CURRENT_OBJECTS = 1 << 27;
+        const DELETED_OBJECTS = 1 << 28;
+        const CURRENT_OBJECT_COUNTS_BY_OWNER = 1 << 29;
 
-        // Ans Processor: 31-40
+        // Ans Processor: 31-35
         const CURRENT_ANS_LOOKUP_V2 = 1 << 31;
         const CURRENT_ANS_PRIMARY_NAME_V2 = 1 << 32;
         const ANS_LOOKUP_V2 = 1 << 33;
+        const ANS_REVENUE_EVENTS = 1 << 34;
+
+        // Stake Processor: 36-50
+        const DELEGATED_STAKING_ACTIVITIES = 1 << 36;
+        const DELEGATED_STAKING_POOLS = 1 << 37;
+        const DELEGATED_STAKING_POOL_BALANCES = 1 << 38;
+        const CURRENT_DELEGATED_STAKING_POOL_BALANCES = 1 << 39;
+        const DELEGATOR_BALANCES = 1 << 40;
+        const CURRENT_DELEGATOR_BALANCES = 1 << 41;
+        const CURRENT_DELEGATED_VOTER = 1 << 42;
+        const CURRENT_STAKING_POOL_VOTER = 1 << 43;
+        const PROPOSAL_VOTES = 1 << 44;
+        const EPOCH_END_DELEGATOR_BALANCES = 1 << 45;
+        const POOL_COMMISSION_HISTORY = 1 << 46;
+        const POOL_OPERATOR_HISTORY = 1 << 47;
+        const GOVERNANCE_PARTICIPATION = 1 << 48;
 
-        // Stake Processor: 41-50
-        const DELEGATED_STAKING_ACTIVITIES = 1 << 41;
-        const DELEGATED_STAKING_POOLS = 1 << 42;
-        const DELEGATED_STAKING_POOL_BALANCES = 1 << 43;
-        const CURRENT_DELEGATED_STAKING_POOL_BALANCES = 1 << 44;
-        const DELEGATOR_BALANCES = 1 << 45;
-        const CURRENT_DELEGATOR_BALANCES = 1 << 46;
-        const CURRENT_DELEGATED_VOTER = 1 << 47;
-        const CURRENT_STAKING_POOL_VOTER = 1 << 48;
-        const PROPOSAL_VOTES = 1 << 49;
-
-        // Token V2 Processor: 51-60
+        // Token V2 Processor: 51-70
         const TOKEN_ACTIVITIES_V2 = 1 << 51;
         const CURRENT_TOKEN_OWNERSHIPS_V2 = 1 << 52;
         const CURRENT_TOKEN_DATAS_V2 = 1 << 53;
@@ -55,54 +71,147 @@ bitflags! {
         const TOKEN_OWNERSHIPS_V2 = 1 << 58;
         const TOKEN_DATAS_V2 = 1 << 59;
         const CURRENT_TOKEN_ROYALTY_V1 = 1 << 60;
+        const TOKEN_PROPERTY_VALUES = 1 << 61;
+        const COLLECTION_VOLUME_LEADERBOARD = 1 << 62;
+        const TOKEN_MINTS_DETAIL = 1 << 63;
+        const NFT_TRANSFERS = 1 << 64;
+        const CURRENT_COLLECTION_STATS_V2 = 1 << 65;
+
+        // User Transactions and Signatures: 71-75
+        const USER_TRANSACTIONS = 1 << 71;
+        const SIGNATURES = 1 << 72;
+        const TRANSACTION_SIGNERS = 1 << 73;
+        const SENDER_SEQUENCE_NUMBER_GAPS = 1 << 74;
 
-        // User Transactions and Signatures: 61-70
-        const USER_TRANSACTIONS = 1 << 61;
-        const SIGNATURES = 1 << 62;
+        // Validator Transaction Processor: 76-78
+        const VALIDATOR_TRANSACTIONS = 1 << 76;
+        const VALIDATOR_TRANSACTION_EVENTS = 1 << 77;
 
-        // Account Transaction Processor: 71-80
-        const ACCOUNT_TRANSACTIONS = 1 << 71;
+        // Account Transaction Processor: 79-80
+        const ACCOUNT_TRANSACTIONS = 1 << 79;
 
-        // Events 81-90
+        // Events: 81-82
         const EVENTS = 1 << 81;
 
-        // transaction metadata 91-100
-        const WRITE_SET_SIZE = 1 << 91;
+        // Transaction metadata: 83-84
+        const WRITE_SET_SIZE = 1 << 83;
+
+        // Account Restoration Processor: 85-90
+        const AUTH_KEY_ACCOUNT_ADDRESSES = 1 << 85;
+        const PUBLIC_KEY_AUTH_KEYS = 1 << 86;
+        const AUTH_KEY_SCHEMES = 1 << 87;
+
+        // Processors writing a single table: 91-100
+        const GAS_FEES = 1 << 91;
+        const RAW_TRANSACTIONS = 1 << 92;
+        const WATCHED_RESOURCES = 1 << 93;
+        const TREASURY_INFLOWS = 1 << 94;
 
-        // Deprecated Tables 101-110
+        // Deprecated Tables: 101-110
         const COIN_SUPPLY = 1 << 101;
         const CURRENT_ANS_LOOKUP = 1 << 102;
         const CURRENT_ANS_PRIMARY_NAME = 1 << 103;
         const ANS_PRIMARY_NAME_V2 = 1 << 104;
         const ANS_LOOKUP = 1 << 105;
         const ANS_PRIMARY_NAME = 1 << 106;
-
-        // Account Restoration Processor: 111-120
-        const AUTH_KEY_ACCOUNT_ADDRESSES = 1 << 111;
-        const PUBLIC_KEY_AUTH_KEYS = 1 << 112;
-        const GAS_FEES = 1 << 123;
     }
 }
 
 impl TableFlags {
+    /// Aggregates that cost extra queries per batch, and legacy tables duplicating newer ones.
+    /// They're only written when listed in `tables_to_write`, an empty set doesn't turn them on.
+    pub const OPT_IN: Self = Self::COIN_HOLDER_COUNTS
+        .union(Self::COLLECTION_VOLUME_LEADERBOARD)
+        .union(Self::CURRENT_COIN_BALANCES)
+        .union(Self::CURRENT_OBJECT_COUNTS_BY_OWNER)
+        .union(Self::SENDER_SEQUENCE_NUMBER_GAPS);
+
+    /// Flags of a `tables_to_write` set, see `TableSelection` for `*` and `!` entries
     pub fn from_set(set: &HashSet<String>) -> Self {
-        let mut flags = TableFlags::empty();
-        for table in set {
+        let selection = TableSelection::parse(set);
+        let mut flags = if selection.all {
+            TableFlags::all().difference(TableFlags::OPT_IN)
+        } else {
+            TableFlags::empty()
+        };
+        for table in selection.included {
             if let Some(flag) = TableFlags::from_name(table) {
                 flags |= flag;
             }
         }
+        for table in selection.excluded {
+            if let Some(flag) = TableFlags::from_name(table) {
+                flags -= flag;
+            }
+        }
         flags
     }
 }
 
+/// Selects every table with `*`
+pub const ALL_TABLES: &str = "*";
+/// Prefix of a table a selection leaves out, like `!EVENTS`
+pub const EXCLUDED_TABLE_PREFIX: char = '!';
+
+/// A set of table names like `tables_to_write` or `backfill_table`, e.g. `["*", "!EVENTS"]`.
+/// `*` selects every table the processor writes when nothing is listed, so opt-in tables still
+/// have to be named. Exclusions win over the other entries, and a set of only exclusions starts
+/// from every table too.
+#[derive(Debug, Default, PartialEq)]
+pub struct TableSelection<'a> {
+    pub all: bool,
+    pub included: Vec<&'a str>,
+    pub excluded: Vec<&'a str>,
+}
+
+impl<'a> TableSelection<'a> {
+    pub fn parse(set: &'a HashSet<String>) -> Self {
+        let mut selection = Self::default();
+        for table in set {
+            if table == ALL_TABLES {
+                selection.all = true;
+            } else if let Some(table) = table.strip_prefix(EXCLUDED_TABLE_PREFIX) {
+                selection.excluded.push(table);
+            } else {
+                selection.included.push(table);
+            }
+        }
+        selection.all |= selection.included.is_empty() && !selection.excluded.is_empty();
+        selection
+    }
+
+    /// Table names the selection names, to check they're tables
+    pub fn named_tables(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.included.iter().chain(&self.excluded).copied()
+    }
+}
+
+/// `tables_to_write` from the latest config reload. Once set, it takes precedence over the flags
+/// the steps were built with, so tables can be turned on or off without restarting.
+static RELOADED_TABLES_TO_WRITE: Lazy<RwLock<Option<TableFlags>>> = Lazy::new(|| RwLock::new(None));
+
+pub fn set_reloaded_tables_to_write(tables_to_write: TableFlags) {
+    *RELOADED_TABLES_TO_WRITE.write().unwrap() = Some(tables_to_write);
+}
+
+/// Whether the table of `flag` is written: it's listed in `tables_to_write`, or the set is empty
+/// and the table isn't opt-in.
+pub fn should_write(tables_to_write: &TableFlags, flag: TableFlags) -> bool {
+    let tables_to_write = RELOADED_TABLES_TO_WRITE
+        .read()
+        .unwrap()
+        .unwrap_or(*tables_to_write);
+    tables_to_write.contains(flag)
+        || (tables_to_write.is_empty() && !TableFlags::OPT_IN.intersects(flag))
+}
+
 /**
  * This is a helper function to filter data based on the tables_to_write set.
- * If the tables_to_write set is empty or contains the flag, return the data so that they are written to the database.
+ * If the table should be written (see `should_write`), return the data so that they are written to the database.
  * Otherwise, return an empty vector so that they are not written to the database.
  */
 pub fn filter_data<T>(tables_to_write: &TableFlags, flag: TableFlags, data: Vec<T>) -> Vec<T> {
-    if tables_to_write.is_empty() || tables_to_write.contains(flag) {
+    if should_write(tables_to_write, flag) {
         data
     } else {
         vec![]
@@ -120,3 +229,28 @@ macro_rules! filter_datasets {
         )
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(tables: &[&str]) -> HashSet<String> {
+        tables.iter().map(|table| table.to_string()).collect()
+    }
+
+    #[test]
+    fn test_wildcard_and_exclusions() {
+        let flags = TableFlags::from_set(&set(&["*", "!EVENTS"]));
+        assert!(flags.contains(TableFlags::TRANSACTIONS));
+        assert!(!flags.contains(TableFlags::EVENTS));
+        assert!(!flags.intersects(TableFlags::OPT_IN));
+
+        let flags = TableFlags::from_set(&set(&["*", "COIN_HOLDER_COUNTS"]));
+        assert!(flags.contains(TableFlags::EVENTS | TableFlags::COIN_HOLDER_COUNTS));
+
+        assert_eq!(
+            TableFlags::from_set(&set(&["!EVENTS"])),
+            TableFlags::from_set(&set(&["*", "!EVENTS"]))
+        );
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Names the output tables are written under, when `table_name_overrides` renames them.
+//!
+//! Postgres inserts are built against the tables of `schema.rs`, so the override is applied to
+//! the rendered SQL. Parquet files are laid out under the overridden name instead of the table's.
+
+use diesel::{
+    pg::{Pg, PgQueryBuilder},
+    query_builder::{AstPass, QueryBuilder, QueryFragment, QueryId},
+    QueryResult,
+};
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+
+/// The processor's `table_name_overrides`, handed to everything that writes its tables. Cheap to
+/// clone.
+#[derive(Clone, Debug, Default)]
+pub struct TableNames(Arc<HashMap<String, String>>);
+
+impl TableNames {
+    pub fn new(overrides: HashMap<String, String>) -> Self {
+        Self(Arc::new(overrides))
+    }
+
+    /// Name `table_name` is written under
+    pub fn output_table_name<'a>(&self, table_name: &'a str) -> Cow<'a, str> {
+        match self.0.get(table_name) {
+            Some(output_name) => Cow::Owned(output_name.clone()),
+            None => Cow::Borrowed(table_name),
+        }
+    }
+
+    /// `query`, written to the overridden names of the tables it references
+    pub fn rename<Q>(&self, query: Q) -> RenamedTables<Q> {
+        RenamedTables {
+            query,
+            table_names: self.clone(),
+        }
+    }
+}
+
+/// `name` as a quoted identifier, `schema.table` names are quoted per part
+pub(crate) fn quote_identifier(name: &str) -> String {
+    name.split('.')
+        .map(|part| format!("\"{}\"", part.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// `sql` with the quoted names of overridden tables replaced. Diesel quotes every identifier, so
+/// unquoted text like string literals isn't touched.
+fn rename_tables(sql: String, overrides: &HashMap<String, String>) -> String {
+    overrides
+        .iter()
+        .fold(sql, |sql, (table_name, output_name)| {
+            sql.replace(
+                &quote_identifier(table_name),
+                &quote_identifier(output_name),
+            )
+        })
+}
+
+/// A query written to the overridden names of the tables it references, see
+/// `TableNames::rename`. Only the SQL is changed, binds are collected from the wrapped query as
+/// they are.
+pub struct RenamedTables<Q> {
+    query: Q,
+    table_names: TableNames,
+}
+
+impl<Q> QueryId for RenamedTables<Q> {
+    type QueryId = ();
+
+    // The SQL of a query type depends on the overrides it's written with, so prepared statements
+    // are cached by their SQL instead
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<Q: QueryFragment<Pg>> QueryFragment<Pg> for RenamedTables<Q> {
+    fn walk_ast<'b>(&'b self, pass: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        self.query.walk_ast(pass)
+    }
+
+    fn to_sql(&self, out: &mut PgQueryBuilder, backend: &Pg) -> QueryResult<()> {
+        let overrides = &self.table_names.0;
+        if overrides.is_empty() {
+            return self.query.to_sql(out, backend);
+        }
+        let mut query_builder = PgQueryBuilder::default();
+        self.query.to_sql(&mut query_builder, backend)?;
+        out.push_sql(&rename_tables(query_builder.finish(), overrides));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_tables() {
+        let overrides = HashMap::from([
+            ("events".to_string(), "chain_events_v2".to_string()),
+            (
+                "current_objects".to_string(),
+                "green.current_objects".to_string(),
+            ),
+        ]);
+        assert_eq!(
+            rename_tables(
+                r#"INSERT INTO "events" ("type", "data") VALUES ($1, $2) ON CONFLICT DO NOTHING"#
+                    .to_string(),
+                &overrides,
+            ),
+            r#"INSERT INTO "chain_events_v2" ("type", "data") VALUES ($1, $2) ON CONFLICT DO NOTHING"#
+        );
+        assert_eq!(
+            rename_tables(
+                r#"INSERT INTO "current_objects" ("object_address") VALUES ($1) WHERE "current_objects"."last_transaction_version" <= excluded."last_transaction_version""#
+                    .to_string(),
+                &overrides,
+            ),
+            r#"INSERT INTO "green"."current_objects" ("object_address") VALUES ($1) WHERE "green"."current_objects"."last_transaction_version" <= excluded."last_transaction_version""#
+        );
+    }
+
+    #[test]
+    fn test_output_table_name() {
+        let table_names = TableNames::new(HashMap::from([(
+            "events".to_string(),
+            "chain_events_v2".to_string(),
+        )]));
+        assert_eq!(table_names.output_table_name("events"), "chain_events_v2");
+        assert_eq!(
+            table_names.output_table_name("transactions"),
+            "transactions"
+        );
+        assert_eq!(TableNames::default().output_table_name("events"), "events");
+    }
+}
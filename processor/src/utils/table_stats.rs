@@ -0,0 +1,280 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::utils::{
+    counters::TABLE_WRITE_ROWS_COUNT,
+    json_caps::cap_json_columns,
+    logging::table_span,
+    table_names::TableNames,
+    version_guard::record_stale_writes,
+    write_sink::{write_sinks, OwnedWrite, TableBatch, TableRows, WriteSinks},
+};
+use ahash::{AHashMap, AHashSet};
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::{execute_with_better_error, ArcDbPool},
+    utils::errors::ProcessorError,
+};
+use diesel::{associations::Identifiable, pg::Pg, query_builder::QueryFragment};
+use futures::FutureExt;
+use once_cell::sync::Lazy;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    cmp::Ordering,
+    sync::{
+        atomic::{self, AtomicBool},
+        Mutex,
+    },
+};
+use tracing::Instrument;
+
+/// Rows that can be ordered by their primary key, which is every row diesel can identify.
+pub trait PrimaryKeyOrd {
+    fn cmp_primary_key(&self, other: &Self) -> Ordering;
+}
+
+impl<T> PrimaryKeyOrd for T
+where
+    for<'a> &'a T: Identifiable,
+    for<'a> <&'a T as Identifiable>::Id: Ord,
+{
+    fn cmp_primary_key(&self, other: &Self) -> Ordering {
+        self.id().cmp(&other.id())
+    }
+}
+
+/// What the storers wrote to a table since stats were last saved.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TableWriteStats {
+    pub rows_written: i64,
+    /// Rows the upsert skipped, because of `do_nothing` or a version filter on conflict.
+    pub conflicts_ignored: i64,
+    /// Size of the rows serialized as JSON, a proxy for how much the table grows. Only measured
+    /// with `measure_table_bytes`, 0 otherwise.
+    pub bytes_written: i64,
+}
+
+impl TableWriteStats {
+    fn add(&mut self, other: &Self) {
+        self.rows_written += other.rows_written;
+        self.conflicts_ignored += other.conflicts_ignored;
+        self.bytes_written += other.bytes_written;
+    }
+}
+
+static PENDING_TABLE_STATS: Lazy<Mutex<AHashMap<&'static str, TableWriteStats>>> =
+    Lazy::new(|| Mutex::new(AHashMap::new()));
+
+/// Set by sharded runs. Their shards share the pending stats, so a shard saving its status would
+/// take the rows the others wrote as its own.
+static TABLE_STATS_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Stops recording table stats for the rest of the process.
+pub fn disable_table_stats() {
+    TABLE_STATS_DISABLED.store(true, atomic::Ordering::Relaxed);
+}
+
+/// Whether `bytes_written` is measured. Rows are serialized just for it unless a sink already
+/// did, so it's off unless configured.
+static MEASURE_TABLE_BYTES: AtomicBool = AtomicBool::new(false);
+
+pub fn set_measure_table_bytes(enabled: bool) {
+    MEASURE_TABLE_BYTES.store(enabled, atomic::Ordering::Relaxed);
+}
+
+pub fn record_table_stats(table_name: &'static str, stats: TableWriteStats) {
+    if TABLE_STATS_DISABLED.load(atomic::Ordering::Relaxed) {
+        return;
+    }
+    PENDING_TABLE_STATS
+        .lock()
+        .unwrap()
+        .entry(table_name)
+        .or_default()
+        .add(&stats);
+}
+
+/// Takes the stats recorded since the last call, sorted by table name.
+pub fn take_pending_table_stats() -> Vec<(&'static str, TableWriteStats)> {
+    let mut stats: Vec<_> = std::mem::take(&mut *PENDING_TABLE_STATS.lock().unwrap())
+        .into_iter()
+        .collect();
+    stats.sort_by_key(|(table_name, _)| *table_name);
+    stats
+}
+
+/// Like `execute_in_chunks`, but also records the rows written to `table_name`, so that they end
+/// up in `indexer_table_stats`, `indexer_table_stats_daily` and `indexer_table_stats_hourly` when
+/// the processor status is saved, and counts them in `indexer_processor_table_write_rows`.
+///
+/// Rows are inserted in primary key order. Processors sharing a database then lock the rows of
+/// an upsert in the same order, so their batches wait on each other instead of deadlocking.
+/// Tables renamed in `table_names` are written under their new name, and JSON values
+/// over the table's `json_column_caps` are replaced first. The rows then go through the
+/// `write_sink`s, like the data quality rules or the replica. Everything logged while writing is
+/// logged in the table's `table_span`.
+///
+/// Storers writing tables that depend on each other, see `write_order`, write a batch's
+/// dependencies first and only write the rest once those succeeded, or use `TableWriters`.
+pub async fn execute_in_chunks_with_stats<U, T>(
+    conn: ArcDbPool,
+    table_names: &TableNames,
+    build_query: fn(Vec<T>) -> U,
+    items_to_insert: &[T],
+    table_name: &'static str,
+    chunk_size: usize,
+) -> Result<(), ProcessorError>
+where
+    U: QueryFragment<Pg> + diesel::query_builder::QueryId + Send + 'static,
+    T: Serialize + DeserializeOwned + Clone + PrimaryKeyOrd + Send + Sync + 'static,
+{
+    if items_to_insert.is_empty() {
+        return Ok(());
+    }
+    let capped_items = cap_json_columns(table_name, items_to_insert);
+    let mut sorted_items: Vec<&T> = capped_items.iter().collect();
+    sorted_items.sort_by(|a, b| T::cmp_primary_key(a, b));
+    let rows = ChunkedRows {
+        rows: sorted_items,
+        build_query,
+        chunk_size: chunk_size.max(1),
+        table_names: table_names.clone(),
+    };
+    write_batch(conn, &write_sinks(), TableBatch::new(table_name, &rows))
+        .instrument(table_span(table_name))
+        .await
+}
+
+/// Rows written with a storer's query, `chunk_size` at a time
+pub struct ChunkedRows<'a, T, U> {
+    pub rows: Vec<&'a T>,
+    pub build_query: fn(Vec<T>) -> U,
+    pub chunk_size: usize,
+    pub table_names: TableNames,
+}
+
+impl<T, U> TableRows for ChunkedRows<'_, T, U>
+where
+    U: QueryFragment<Pg> + diesel::query_builder::QueryId + Send + 'static,
+    T: Serialize + Clone + Send + Sync + 'static,
+{
+    fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn to_json(&self) -> serde_json::Result<Vec<Value>> {
+        self.rows.iter().map(serde_json::to_value).collect()
+    }
+
+    fn write(&self, indices: &[usize]) -> OwnedWrite {
+        let chunks: Vec<Vec<T>> = indices
+            .chunks(self.chunk_size)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|index| T::clone(self.rows[*index]))
+                    .collect()
+            })
+            .collect();
+        let build_query = self.build_query;
+        let table_names = self.table_names.clone();
+        Box::new(move |conn: ArcDbPool| {
+            async move {
+                let rows_written = futures::future::try_join_all(chunks.into_iter().map(|chunk| {
+                    execute_with_better_error(conn.clone(), table_names.rename(build_query(chunk)))
+                }))
+                .await?;
+                Ok::<usize, ProcessorError>(rows_written.into_iter().sum())
+            }
+            .boxed()
+        })
+    }
+}
+
+/// Writes a batch through `sinks` and records its stats
+pub async fn write_batch(
+    conn: ArcDbPool,
+    sinks: &WriteSinks,
+    batch: TableBatch<'_>,
+) -> Result<(), ProcessorError> {
+    if batch.is_empty() {
+        return Ok(());
+    }
+    if let Some(replacement) = sinks.database_replacement() {
+        replacement.before_write(&conn, &batch).await?;
+        return Ok(());
+    }
+    let mut held_back = AHashSet::new();
+    for sink in sinks.iter() {
+        held_back.extend(sink.before_write(&conn, &batch).await?);
+    }
+    let written: Vec<usize> = (0..batch.len())
+        .filter(|index| !held_back.contains(index))
+        .collect();
+    let rows_written = batch.write(&written)(conn).await?;
+    for sink in sinks.iter() {
+        sink.committed(&batch, &written).await?;
+    }
+
+    let table_name = batch.table_name;
+    let rows_skipped = written.len().saturating_sub(rows_written);
+    record_stale_writes(table_name, rows_skipped);
+    TABLE_WRITE_ROWS_COUNT
+        .with_label_values(&[table_name, "written"])
+        .inc_by(rows_written as u64);
+    TABLE_WRITE_ROWS_COUNT
+        .with_label_values(&[table_name, "conflict_ignored"])
+        .inc_by(rows_skipped as u64);
+    let bytes_written = if MEASURE_TABLE_BYTES.load(atomic::Ordering::Relaxed) {
+        batch
+            .json()
+            .map(|rows| {
+                written
+                    .iter()
+                    .map(|index| rows[*index].to_string().len())
+                    .sum::<usize>()
+            })
+            .unwrap_or_default()
+    } else {
+        0
+    };
+    record_table_stats(table_name, TableWriteStats {
+        rows_written: rows_written as i64,
+        conflicts_ignored: rows_skipped as i64,
+        bytes_written: bytes_written as i64,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_stats_are_summed_and_taken() {
+        let stats = TableWriteStats {
+            rows_written: 3,
+            conflicts_ignored: 1,
+            bytes_written: 100,
+        };
+        record_table_stats("table_stats_test_b", stats);
+        record_table_stats("table_stats_test_a", stats);
+        record_table_stats("table_stats_test_b", stats);
+
+        let pending: Vec<_> = take_pending_table_stats()
+            .into_iter()
+            .filter(|(table_name, _)| table_name.starts_with("table_stats_test_"))
+            .collect();
+        assert_eq!(pending, vec![
+            ("table_stats_test_a", stats),
+            ("table_stats_test_b", TableWriteStats {
+                rows_written: 6,
+                conflicts_ignored: 2,
+                bytes_written: 200,
+            }),
+        ]);
+        assert!(take_pending_table_stats()
+            .iter()
+            .all(|(table_name, _)| !table_name.starts_with("table_stats_test_")));
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use cedra_indexer_processor_sdk::types::transaction_context::{
+    TransactionContext, TransactionMetadata,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+/// Tracks the last committed batch of every table a processor writes, so that a single
+/// "all tables consistent through version V" watermark can be derived from them.
+///
+/// Tables are keyed by `K` (e.g. a table name or `ParquetTypeEnum`). Every table the processor
+/// writes starts out at the version before the run's starting version, so a table that hasn't
+/// committed yet holds the watermark where the previous run left it.
+#[derive(Debug)]
+pub struct TableWatermarks<K> {
+    committed: HashMap<K, TransactionContext<()>>,
+    /// Tables that haven't committed since the run started
+    uncommitted: HashSet<K>,
+}
+
+impl<K: Eq + Hash> TableWatermarks<K> {
+    /// Watermarks of `tables`, the tables the processor writes
+    pub fn new(tables: impl IntoIterator<Item = K>) -> Self {
+        Self {
+            committed: HashMap::new(),
+            uncommitted: tables.into_iter().collect(),
+        }
+    }
+
+    /// Last committed batch for a table, if any.
+    pub fn get(&self, table: &K) -> Option<&TransactionContext<()>> {
+        self.committed.get(table)
+    }
+
+    /// Records that `table` has committed everything through `metadata.end_version`.
+    pub fn record(&mut self, table: K, metadata: &TransactionMetadata) {
+        self.uncommitted.remove(&table);
+        self.committed
+            .entry(table)
+            .and_modify(|e| e.metadata = metadata.clone())
+            .or_insert(TransactionContext {
+                data: (),
+                metadata: metadata.clone(),
+            });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &TransactionContext<()>)> {
+        self.committed.iter()
+    }
+
+    /// The committed batch with the lowest end version across all tables. Every table is
+    /// committed at least through this batch's `end_version`. None until every table committed
+    /// once, the watermark isn't past the starting version before that.
+    pub fn consistent_through(&self) -> Option<&TransactionContext<()>> {
+        if !self.uncommitted.is_empty() {
+            return None;
+        }
+        self.committed
+            .values()
+            .min_by_key(|context| context.metadata.end_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(start_version: u64, end_version: u64) -> TransactionMetadata {
+        TransactionMetadata {
+            start_version,
+            end_version,
+            ..Default::default()
+        }
+    }
+
+    fn consistent_end_version(watermarks: &TableWatermarks<&str>) -> Option<u64> {
+        watermarks
+            .consistent_through()
+            .map(|context| context.metadata.end_version)
+    }
+
+    #[test]
+    fn test_consistent_through_is_slowest_table() {
+        let mut watermarks = TableWatermarks::new(["events", "transactions"]);
+        assert_eq!(consistent_end_version(&watermarks), None);
+
+        watermarks.record("events", &metadata(0, 99));
+        // Transactions haven't committed yet
+        assert_eq!(consistent_end_version(&watermarks), None);
+        watermarks.record("transactions", &metadata(0, 49));
+        assert_eq!(consistent_end_version(&watermarks), Some(49));
+
+        watermarks.record("transactions", &metadata(50, 149));
+        assert_eq!(consistent_end_version(&watermarks), Some(99));
+        let transactions = watermarks.get(&"transactions").unwrap();
+        assert_eq!(transactions.metadata.start_version, 50);
+    }
+}
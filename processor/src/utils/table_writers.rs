@@ -0,0 +1,435 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::utils::{
+    table_names::TableNames,
+    table_stats::{execute_in_chunks_with_stats, PrimaryKeyOrd},
+    write_order::write_dependencies,
+    write_sink::write_sinks,
+};
+use ahash::AHashMap;
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::{execute_with_better_error, ArcDbPool},
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use diesel::{pg::Pg, query_builder::QueryFragment};
+use futures::future::BoxFuture;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::{
+    sync::{mpsc, oneshot, watch},
+    task::JoinSet,
+};
+
+type WriteResult = Result<(), ProcessorError>;
+/// A write run on the connection pool of the writers
+type TableWrite = Box<dyn FnOnce(ArcDbPool) -> BoxFuture<'static, WriteResult> + Send>;
+type QueuedWrite = (TableWrite, oneshot::Sender<WriteResult>);
+
+/// One write queue per table, each drained by its own task. Batches are written to a table in
+/// the order they're queued, but a slow table no longer holds up the writes of the others, nor
+/// the next batches of faster tables. The tasks belong to the step owning the writers: they're
+/// aborted when it's dropped, and the step fails with their error once one of them stops.
+///
+/// The next batch can be queued before the previous one is written, so storers that read back
+/// what earlier batches committed can't use this. That's why the fungible asset, objects, stake
+/// and user transaction storers still write each batch in their step: they diff against the
+/// stored balances, objects, stake state and senders' transactions before writing.
+///
+/// A batch's write to a table waits for its writes to the tables the table depends on in
+/// `WRITE_DEPENDENCIES`, queued before it, and isn't run if one of them failed. Storers queue
+/// tables after their dependencies. Once a write to a table fails, its queue stops: the writes
+/// queued behind it fail without running, so a write never runs on top of an earlier batch's
+/// missing one.
+pub struct TableWriters {
+    conn_pool: ArcDbPool,
+    table_names: TableNames,
+    channel_size: usize,
+    queues: AHashMap<&'static str, mpsc::Sender<QueuedWrite>>,
+    tasks: JoinSet<WriteResult>,
+}
+
+impl TableWriters {
+    pub fn new(conn_pool: ArcDbPool, table_names: TableNames, channel_size: usize) -> Self {
+        Self {
+            conn_pool,
+            table_names,
+            channel_size: channel_size.max(1),
+            queues: AHashMap::new(),
+            tasks: JoinSet::new(),
+        }
+    }
+
+    fn queue(&mut self, table_name: &'static str) -> mpsc::Sender<QueuedWrite> {
+        let channel_size = self.channel_size;
+        let conn_pool = self.conn_pool.clone();
+        let tasks = &mut self.tasks;
+        self.queues
+            .entry(table_name)
+            .or_insert_with(|| {
+                let (sender, receiver) = mpsc::channel::<QueuedWrite>(channel_size);
+                tasks.spawn(drain_queue(table_name, receiver, conn_pool));
+                sender
+            })
+            .clone()
+    }
+
+    /// Fails with the error of the first queue that stopped
+    fn check_queues(&mut self) -> Result<(), ProcessorError> {
+        match self.tasks.try_join_next() {
+            None => Ok(()),
+            Some(Ok(result)) => result,
+            Some(Err(e)) => Err(ProcessorError::DBStoreError {
+                message: format!("Table write queue panicked: {e:?}"),
+                query: None,
+            }),
+        }
+    }
+
+    /// Queues the rows behind the table's earlier batches, like `execute_in_chunks_with_stats`,
+    /// to be written once this batch's writes to the table's dependencies succeeded. Waits only
+    /// when the table's queue is full.
+    pub async fn write<U, T>(
+        &mut self,
+        pending_writes: &mut PendingWrites,
+        build_query: fn(Vec<T>) -> U,
+        items_to_insert: Vec<T>,
+        table_name: &'static str,
+        chunk_size: usize,
+    ) -> Result<(), ProcessorError>
+    where
+        U: QueryFragment<Pg> + diesel::query_builder::QueryId + Send + 'static,
+        T: Serialize + DeserializeOwned + Clone + PrimaryKeyOrd + Send + Sync + 'static,
+    {
+        if items_to_insert.is_empty() {
+            pending_writes.tables.push(table_name);
+            return Ok(());
+        }
+        let dependencies = pending_writes.dependencies(table_name);
+        let table_names = self.table_names.clone();
+        let write: TableWrite = Box::new(move |conn_pool| {
+            Box::pin(async move {
+                wait_for_dependencies(table_name, dependencies).await?;
+                execute_in_chunks_with_stats(
+                    conn_pool,
+                    &table_names,
+                    build_query,
+                    &items_to_insert,
+                    table_name,
+                    chunk_size,
+                )
+                .await
+            })
+        });
+        self.enqueue(pending_writes, table_name, write).await
+    }
+
+    /// Queues a statement behind the table's earlier batches that first waits for this batch's
+    /// writes to the `after` tables, for statements that read what those writes commit, and to
+    /// the table's dependencies like `write`. Unlike `write`, the statement doesn't go through the
+    /// `WriteSink`s, and isn't run at all while one of them replaces the database.
+    pub async fn execute_after<U>(
+        &mut self,
+        pending_writes: &mut PendingWrites,
+        query: U,
+        table_name: &'static str,
+        after: &[&'static str],
+    ) -> Result<(), ProcessorError>
+    where
+        U: QueryFragment<Pg> + diesel::query_builder::QueryId + Send + 'static,
+    {
+        if write_sinks().replaces_database() {
+            return Ok(());
+        }
+        let (dependencies, writes) = std::mem::take(&mut pending_writes.writes)
+            .into_iter()
+            .partition(|(dependency, _)| after.contains(dependency));
+        pending_writes.writes = writes;
+        let outcomes = pending_writes.dependencies(table_name);
+        let query = self.table_names.rename(query);
+        let write: TableWrite = Box::new(move |conn_pool| {
+            Box::pin(async move {
+                // Failed dependencies fail this write, which is how they're reported
+                PendingWrites {
+                    writes: dependencies,
+                    ..Default::default()
+                }
+                .wait()
+                .await?;
+                wait_for_dependencies(table_name, outcomes).await?;
+                execute_with_better_error(conn_pool, query)
+                    .await
+                    .map(|_| ())
+            })
+        });
+        self.enqueue(pending_writes, table_name, write).await
+    }
+
+    async fn enqueue(
+        &mut self,
+        pending_writes: &mut PendingWrites,
+        table_name: &'static str,
+        write: TableWrite,
+    ) -> Result<(), ProcessorError> {
+        self.check_queues()?;
+        let (done, written) = oneshot::channel();
+        let (succeeded_sender, succeeded) = watch::channel(None);
+        let write: TableWrite = Box::new(move |conn_pool| {
+            Box::pin(async move {
+                let result = write(conn_pool).await;
+                let _ = succeeded_sender.send(Some(result.is_ok()));
+                result
+            })
+        });
+        self.queue(table_name)
+            .send((write, done))
+            .await
+            .map_err(|_| ProcessorError::DBStoreError {
+                message: format!("Write queue of {table_name} is closed"),
+                query: None,
+            })?;
+        pending_writes.tables.push(table_name);
+        pending_writes.writes.push((table_name, written));
+        pending_writes.outcomes.push((table_name, succeeded));
+        Ok(())
+    }
+}
+
+/// Runs the writes queued to a table in order, until one fails. The writes queued behind it,
+/// and any queued later, fail without running, and the queue stops with the failed write's
+/// error.
+async fn drain_queue(
+    table_name: &'static str,
+    mut receiver: mpsc::Receiver<QueuedWrite>,
+    conn_pool: ArcDbPool,
+) -> WriteResult {
+    while let Some((write, done)) = receiver.recv().await {
+        let result = write(conn_pool.clone()).await;
+        let failure = result.as_ref().err().map(|e| format!("{e:?}"));
+        // The batch may have been dropped after an error in another table
+        let _ = done.send(result);
+        if let Some(failure) = failure {
+            receiver.close();
+            while let Some((_, done)) = receiver.recv().await {
+                let _ = done.send(Err(ProcessorError::DBStoreError {
+                    message: format!(
+                        "Not writing {table_name} since an earlier write to it failed"
+                    ),
+                    query: None,
+                }));
+            }
+            return Err(ProcessorError::DBStoreError {
+                message: format!("Write to {table_name} failed: {failure}"),
+                query: None,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Whether a queued write succeeded, once it's done
+type WriteOutcome = watch::Receiver<Option<bool>>;
+
+/// Waits for the writes a table's write depends on, and fails if one of them did. The failed
+/// write reports its own error.
+async fn wait_for_dependencies(
+    table_name: &'static str,
+    dependencies: Vec<(&'static str, WriteOutcome)>,
+) -> WriteResult {
+    for (dependency, mut outcome) in dependencies {
+        let succeeded = match outcome.wait_for(Option::is_some).await {
+            Ok(succeeded) => *succeeded == Some(true),
+            // Dropped without an outcome, so it never ran
+            Err(_) => false,
+        };
+        if !succeeded {
+            return Err(ProcessorError::DBStoreError {
+                message: format!(
+                    "Not writing {table_name} since the write to {dependency} it depends on failed"
+                ),
+                query: None,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Writes of one batch that were queued but may not have finished yet
+#[derive(Debug, Default)]
+pub struct PendingWrites {
+    writes: Vec<(&'static str, oneshot::Receiver<WriteResult>)>,
+    outcomes: Vec<(&'static str, WriteOutcome)>,
+    /// Tables the batch got to so far, including the ones it had no rows for
+    tables: Vec<&'static str>,
+}
+
+impl PendingWrites {
+    /// Outcomes of the writes queued so far to the tables `table_name` depends on. Panics if the
+    /// batch didn't get to one of them yet, since this write wouldn't wait for it.
+    fn dependencies(&self, table_name: &str) -> Vec<(&'static str, WriteOutcome)> {
+        let dependencies = write_dependencies(table_name);
+        for dependency in dependencies {
+            assert!(
+                self.tables.contains(dependency),
+                "{table_name} is queued before {dependency}, which it depends on"
+            );
+        }
+        self.outcomes
+            .iter()
+            .filter(|(table, _)| dependencies.contains(table))
+            .cloned()
+            .collect()
+    }
+
+    pub async fn wait(self) -> Result<(), ProcessorError> {
+        for (table_name, written) in self.writes {
+            written.await.map_err(|_| ProcessorError::DBStoreError {
+                message: format!("Write to {table_name} was dropped"),
+                query: None,
+            })??;
+        }
+        Ok(())
+    }
+}
+
+/// Waits for each batch's queued writes before passing it on, so versions are only tracked once
+/// every table of the batch is written. Batches arrive in order, so they're also released in
+/// order.
+#[derive(Default)]
+pub struct PendingWritesStep;
+
+#[async_trait]
+impl Processable for PendingWritesStep {
+    type Input = PendingWrites;
+    type Output = ();
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        input: TransactionContext<PendingWrites>,
+    ) -> Result<Option<TransactionContext<()>>, ProcessorError> {
+        input
+            .data
+            .wait()
+            .await
+            .map_err(|e| ProcessorError::DBStoreError {
+                message: format!(
+                    "Failed to store versions {} to {}: {:?}",
+                    input.metadata.start_version, input.metadata.end_version, e,
+                ),
+                query: None,
+            })?;
+        Ok(Some(TransactionContext {
+            data: (),
+            metadata: input.metadata,
+        }))
+    }
+}
+
+impl AsyncStep for PendingWritesStep {}
+
+impl NamedStep for PendingWritesStep {
+    fn name(&self) -> String {
+        "PendingWritesStep".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel_async::{
+        pooled_connection::{bb8::Pool, AsyncDieselConnectionManager},
+        AsyncPgConnection,
+    };
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_failed_write_stops_the_queue() {
+        // The writes below don't connect, and bb8 only connects on checkout
+        let manager =
+            AsyncDieselConnectionManager::<AsyncPgConnection>::new("postgresql://localhost/unused");
+        let mut table_writers = TableWriters::new(
+            Arc::new(Pool::builder().build_unchecked(manager)),
+            TableNames::default(),
+            10,
+        );
+        let mut pending_writes = PendingWrites::default();
+        let failing: TableWrite = Box::new(|_| {
+            Box::pin(async {
+                Err(ProcessorError::DBStoreError {
+                    message: "failed".to_string(),
+                    query: None,
+                })
+            })
+        });
+        table_writers
+            .enqueue(&mut pending_writes, "events", failing)
+            .await
+            .unwrap();
+        assert!(pending_writes.wait().await.is_err());
+
+        // The step fails with the stopped queue's error
+        let error = loop {
+            match table_writers.check_queues() {
+                Ok(()) => tokio::task::yield_now().await,
+                Err(e) => break e,
+            }
+        };
+        assert!(format!("{error:?}").contains("Write to events failed"));
+        let succeeding: TableWrite = Box::new(|_| Box::pin(async { Ok(()) }));
+        assert!(table_writers
+            .enqueue(&mut PendingWrites::default(), "events", succeeding)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_dependencies() {
+        let (collections_sender, collections) = watch::channel(None);
+        let (token_datas_sender, token_datas) = watch::channel(None);
+        let waiting = tokio::spawn(wait_for_dependencies("current_token_ownerships_v2", vec![
+            ("current_collections_v2", collections),
+            ("current_token_datas_v2", token_datas.clone()),
+        ]));
+        collections_sender.send(Some(true)).unwrap();
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        token_datas_sender.send(Some(true)).unwrap();
+        assert!(waiting.await.unwrap().is_ok());
+
+        token_datas_sender.send(Some(false)).unwrap();
+        assert!(wait_for_dependencies("token_activities_v2", vec![(
+            "current_token_datas_v2",
+            token_datas
+        )])
+        .await
+        .is_err());
+
+        let (dropped_sender, dropped) = watch::channel(None);
+        drop(dropped_sender);
+        assert!(wait_for_dependencies("token_activities_v2", vec![(
+            "current_token_datas_v2",
+            dropped
+        )])
+        .await
+        .is_err());
+    }
+
+    #[test]
+    fn test_dependencies_are_queued_first() {
+        let mut pending_writes = PendingWrites::default();
+        // No rows for the collections, so nothing to wait for
+        pending_writes.tables.push("current_collections_v2");
+        assert!(pending_writes
+            .dependencies("current_token_datas_v2")
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "current_token_datas_v2 is queued before current_collections_v2")]
+    fn test_dependent_queued_first_panics() {
+        PendingWrites::default().dependencies("current_token_datas_v2");
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! `processor record` fetches transactions from the transaction stream and writes them as
+//! fixtures, one `<version>.json` file per transaction, serialized like the ones under
+//! `integration-tests/src/sdk_tests/test_transactions`. The same files can be replayed with
+//! `local_transactions_config`.
+
+use crate::config::indexer_processor_config::IndexerProcessorConfig;
+use anyhow::{bail, Context, Result};
+use cedra_indexer_processor_sdk::{
+    cedra_indexer_transaction_stream::{TransactionStream, TransactionStreamConfig},
+    cedra_protos::transaction::v1::Transaction,
+};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Parses `<start>-<end>` or `<start>..<end>`, inclusive, or a single version
+pub fn parse_version_range(versions: &str) -> Result<(u64, u64)> {
+    let parse = |version: &str| {
+        version
+            .trim()
+            .parse::<u64>()
+            .with_context(|| format!("Invalid version {version:?} in {versions:?}"))
+    };
+    let (starting_version, ending_version) = match versions
+        .split_once("..")
+        .or_else(|| versions.split_once('-'))
+    {
+        Some((start, end)) => (parse(start)?, parse(end)?),
+        None => (parse(versions)?, parse(versions)?),
+    };
+    if starting_version > ending_version {
+        bail!("Starting version {starting_version} is after ending version {ending_version}");
+    }
+    Ok((starting_version, ending_version))
+}
+
+/// Writes the transactions from `starting_version` through `ending_version` to `out_dir`, from
+/// the transaction stream of `config`. Returns the files written.
+pub async fn record_transactions(
+    config: &IndexerProcessorConfig,
+    starting_version: u64,
+    ending_version: u64,
+    out_dir: &Path,
+) -> Result<Vec<PathBuf>> {
+    let mut stream_config = TransactionStreamConfig {
+        starting_version: Some(starting_version),
+        request_ending_version: Some(ending_version),
+        ..config.transaction_stream_config.clone()
+    };
+    if let Some(auth_token_source) = &config.auth_token_source {
+        stream_config.auth_token = auth_token_source.read_token()?;
+    }
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create fixture directory {}", out_dir.display()))?;
+
+    let mut stream = TransactionStream::new(stream_config).await?;
+    let mut paths = vec![];
+    let mut next_version = starting_version;
+    while next_version <= ending_version {
+        let response = stream.get_next_transaction_batch().await?;
+        for transaction in &response.transactions {
+            if (starting_version..=ending_version).contains(&transaction.version) {
+                paths.push(write_fixture(out_dir, transaction)?);
+            }
+        }
+        next_version = response.end_version + 1;
+        if stream.is_end_of_stream() {
+            break;
+        }
+    }
+    if next_version <= ending_version {
+        bail!(
+            "Transaction stream ended at version {}, before ending version {ending_version}",
+            next_version.saturating_sub(1)
+        );
+    }
+    info!(
+        starting_version,
+        ending_version,
+        out_dir = %out_dir.display(),
+        fixture_count = paths.len(),
+        "Recorded transactions"
+    );
+    Ok(paths)
+}
+
+fn write_fixture(out_dir: &Path, transaction: &Transaction) -> Result<PathBuf> {
+    let path = out_dir.join(format!("{}.json", transaction.version));
+    let json = serde_json::to_string_pretty(transaction)
+        .with_context(|| format!("Failed to serialize transaction {}", transaction.version))?;
+    std::fs::write(&path, json + "\n")
+        .with_context(|| format!("Failed to write fixture {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version_range() {
+        assert_eq!(parse_version_range("1000-1010").unwrap(), (1000, 1010));
+        assert_eq!(parse_version_range("1000..1010").unwrap(), (1000, 1010));
+        assert_eq!(parse_version_range("42").unwrap(), (42, 42));
+        assert!(parse_version_range("1010-1000").is_err());
+        assert!(parse_version_range("1000-").is_err());
+    }
+
+    #[test]
+    fn test_fixture_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let transaction = Transaction {
+            version: 7,
+            epoch: 2,
+            ..Default::default()
+        };
+        let path = write_fixture(dir.path(), &transaction).unwrap();
+        assert_eq!(path, dir.path().join("7.json"));
+        let read: Transaction = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        assert_eq!(read, transaction);
+    }
+}
@@ -0,0 +1,948 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    config::{
+        auth_token_config::AuthTokenSourceConfig,
+        indexer_processor_config::IndexerProcessorConfig,
+        local_transactions_config::LocalTransactionsConfig,
+        processor_mode::BootStrapConfig,
+        stream_failover_config::{StreamEndpointConfig, StreamFailoverConfig},
+    },
+    utils::{counters::GRPC_STREAM_FAILOVER_COUNT, ranged_run::record_streamed_batches},
+};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use cedra_indexer_processor_sdk::{
+    cedra_indexer_transaction_stream::{
+        utils::time::parse_timestamp, TransactionStream, TransactionStreamConfig,
+    },
+    cedra_protos::transaction::v1::Transaction,
+    common_steps::TransactionStreamStep,
+    postgres::utils::{checkpoint::PostgresChainIdChecker, database::ArcDbPool},
+    traits::{
+        pollable_async_step::PollableAsyncRunType, NamedStep, PollableAsyncStep, Processable,
+    },
+    types::transaction_context::{TransactionContext, TransactionMetadata},
+    utils::{chain_id_check::check_or_update_chain_id, errors::ProcessorError},
+};
+use prost::Message;
+use std::{
+    collections::VecDeque,
+    future::Future,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// First step of every processor, streaming transactions from gRPC or, if
+/// `local_transactions_config` is set, from local files. With `stream_failover_config`, the
+/// gRPC stream moves between endpoints as they fail, and with `auth_token_source` it reconnects
+/// when the auth token rotates.
+pub enum TransactionSourceStep {
+    Grpc(TransactionStreamStep),
+    Failover(FailoverTransactionStreamStep),
+    Local(LocalTransactionStreamStep),
+}
+
+impl TransactionSourceStep {
+    pub async fn new(
+        config: &IndexerProcessorConfig,
+        starting_version: Option<u64>,
+        ending_version: Option<u64>,
+    ) -> Result<Self> {
+        match &config.local_transactions_config {
+            Some(local_config) => {
+                validate_local_transactions(config)?;
+                Ok(Self::Local(LocalTransactionStreamStep::new(
+                    local_config,
+                    starting_version,
+                    ending_version,
+                )?))
+            },
+            None if config.stream_failover_config.is_some()
+                || config.auth_token_source.is_some() =>
+            {
+                Ok(Self::Failover(FailoverTransactionStreamStep::new(
+                    config,
+                    starting_version,
+                    ending_version,
+                )))
+            },
+            None => Ok(Self::Grpc(
+                TransactionStreamStep::new(TransactionStreamConfig {
+                    starting_version,
+                    request_ending_version: ending_version,
+                    ..config.transaction_stream_config.clone()
+                })
+                .await?,
+            )),
+        }
+    }
+}
+
+#[async_trait]
+impl Processable for TransactionSourceStep {
+    type Input = ();
+    type Output = Vec<Transaction>;
+    type RunType = PollableAsyncRunType;
+
+    async fn process(
+        &mut self,
+        item: TransactionContext<()>,
+    ) -> Result<Option<TransactionContext<Vec<Transaction>>>, ProcessorError> {
+        match self {
+            Self::Grpc(step) => step.process(item).await,
+            Self::Failover(step) => step.process(item).await,
+            Self::Local(step) => step.process(item).await,
+        }
+    }
+}
+
+#[async_trait]
+impl PollableAsyncStep for TransactionSourceStep {
+    fn poll_interval(&self) -> Duration {
+        match self {
+            Self::Grpc(step) => step.poll_interval(),
+            Self::Failover(step) => step.poll_interval(),
+            Self::Local(step) => step.poll_interval(),
+        }
+    }
+
+    async fn poll(
+        &mut self,
+    ) -> Result<Option<Vec<TransactionContext<Vec<Transaction>>>>, ProcessorError> {
+        let batches = match self {
+            Self::Grpc(step) => step.poll().await,
+            Self::Failover(step) => step.poll().await,
+            Self::Local(step) => step.poll().await,
+        }?;
+        if let Some(batches) = &batches {
+            record_streamed_batches(batches);
+        }
+        Ok(batches)
+    }
+
+    async fn should_continue_polling(&mut self) -> bool {
+        match self {
+            Self::Grpc(step) => step.should_continue_polling().await,
+            Self::Failover(step) => step.should_continue_polling().await,
+            Self::Local(step) => step.should_continue_polling().await,
+        }
+    }
+}
+
+impl NamedStep for TransactionSourceStep {
+    fn name(&self) -> String {
+        match self {
+            Self::Grpc(step) => step.name(),
+            Self::Failover(step) => step.name(),
+            Self::Local(step) => step.name(),
+        }
+    }
+}
+
+/// Streams from `transaction_stream_config`'s endpoint and the endpoints of
+/// `stream_failover_config`, resuming on another endpoint at the next version when the current
+/// one fails. With `auth_token_source`, the stream also resumes at the next version when the
+/// token changes.
+///
+/// Failed endpoints cool down with exponential backoff. Their health is only checked passively,
+/// by connecting and streaming, see `StreamFailoverConfig`.
+pub struct FailoverTransactionStreamStep {
+    processor_name: String,
+    stream_config: TransactionStreamConfig,
+    endpoints: Vec<StreamEndpoint>,
+    unhealthy_cooldown: Duration,
+    max_unhealthy_cooldown: Duration,
+    auth_token_source: Option<AuthTokenSourceConfig>,
+    auth_token_read_at: Instant,
+    active: Option<ActiveStream>,
+    next_version: Option<u64>,
+    ending_version: Option<u64>,
+}
+
+struct StreamEndpoint {
+    config: StreamEndpointConfig,
+    unhealthy_until: Option<Instant>,
+    /// Errors since a batch was last streamed from the endpoint
+    consecutive_failures: u32,
+}
+
+impl StreamEndpoint {
+    fn covers(&self, version: u64) -> bool {
+        self.config.min_version.map_or(true, |min| version >= min)
+            && self.config.max_version.map_or(true, |max| version <= max)
+    }
+
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until.map_or(true, |until| now >= until)
+    }
+}
+
+struct ActiveStream {
+    endpoint_index: usize,
+    // Locked like the SDK's step does, as the stream isn't Sync
+    stream: Mutex<TransactionStream>,
+}
+
+impl FailoverTransactionStreamStep {
+    pub fn new(
+        config: &IndexerProcessorConfig,
+        starting_version: Option<u64>,
+        ending_version: Option<u64>,
+    ) -> Self {
+        let stream_config = config.transaction_stream_config.clone();
+        let (failover_endpoints, unhealthy_cooldown_secs, max_unhealthy_cooldown_secs) =
+            match &config.stream_failover_config {
+                Some(failover_config) => (
+                    failover_config.endpoints.clone(),
+                    failover_config.unhealthy_cooldown_secs,
+                    failover_config.max_unhealthy_cooldown_secs,
+                ),
+                None => (
+                    vec![],
+                    StreamFailoverConfig::default_unhealthy_cooldown_secs(),
+                    StreamFailoverConfig::default_max_unhealthy_cooldown_secs(),
+                ),
+            };
+        let primary = StreamEndpointConfig {
+            indexer_grpc_data_service_address: stream_config
+                .indexer_grpc_data_service_address
+                .clone(),
+            // Uses the stream config's token, which is replaced as `auth_token_source` rotates
+            auth_token: None,
+            min_version: None,
+            max_version: None,
+        };
+        let endpoints = std::iter::once(primary)
+            .chain(failover_endpoints)
+            .map(|config| StreamEndpoint {
+                config,
+                unhealthy_until: None,
+                consecutive_failures: 0,
+            })
+            .collect();
+        Self {
+            processor_name: config.processor_config.name().to_string(),
+            stream_config,
+            endpoints,
+            unhealthy_cooldown: Duration::from_secs(unhealthy_cooldown_secs),
+            max_unhealthy_cooldown: Duration::from_secs(max_unhealthy_cooldown_secs),
+            auth_token_source: config.auth_token_source.clone(),
+            auth_token_read_at: Instant::now(),
+            active: None,
+            next_version: starting_version,
+            ending_version,
+        }
+    }
+
+    /// Re-reads the auth token from `auth_token_source` if it's been `refresh_interval_secs`, or
+    /// now if `force`. Returns whether the token changed.
+    fn refresh_auth_token(&mut self, force: bool) -> bool {
+        let Some(auth_token_source) = &self.auth_token_source else {
+            return false;
+        };
+        let refresh_interval = Duration::from_secs(auth_token_source.refresh_interval_secs);
+        if !force && self.auth_token_read_at.elapsed() < refresh_interval {
+            return false;
+        }
+        self.auth_token_read_at = Instant::now();
+        match auth_token_source.read_token() {
+            Ok(auth_token) if auth_token != self.stream_config.auth_token => {
+                info!("Transaction stream auth token changed");
+                self.stream_config.auth_token = auth_token;
+                true
+            },
+            Ok(_) => false,
+            Err(e) => {
+                warn!(
+                    error = ?e,
+                    "Failed to read the transaction stream auth token, keeping the current one"
+                );
+                false
+            },
+        }
+    }
+
+    /// Opens a stream at the next version on the first endpoint that accepts it. Leaves the stream
+    /// closed if every endpoint covering the version fails or is cooling down, `poll_interval`
+    /// then waits for the first one to be tried again.
+    async fn connect(&mut self) -> Result<(), ProcessorError> {
+        // The stream may have failed because the token expired
+        self.refresh_auth_token(true);
+        let version = self.next_version.unwrap_or_default();
+        if !self
+            .endpoints
+            .iter()
+            .any(|endpoint| endpoint.covers(version))
+        {
+            return Err(ProcessorError::ProcessError {
+                message: format!("No transaction stream endpoint covers version {version}"),
+            });
+        }
+        for endpoint_index in endpoint_candidates(&self.endpoints, version, Instant::now()) {
+            let endpoint = &self.endpoints[endpoint_index].config;
+            let request_ending_version = match (endpoint.max_version, self.ending_version) {
+                (Some(max_version), Some(ending_version)) => Some(max_version.min(ending_version)),
+                (max_version, ending_version) => max_version.or(ending_version),
+            };
+            let stream_config = TransactionStreamConfig {
+                indexer_grpc_data_service_address: endpoint
+                    .indexer_grpc_data_service_address
+                    .clone(),
+                auth_token: endpoint
+                    .auth_token
+                    .clone()
+                    .unwrap_or_else(|| self.stream_config.auth_token.clone()),
+                starting_version: self.next_version,
+                request_ending_version,
+                ..self.stream_config.clone()
+            };
+            match TransactionStream::new(stream_config).await {
+                Ok(stream) => {
+                    info!(
+                        endpoint = %endpoint.indexer_grpc_data_service_address,
+                        starting_version = ?self.next_version,
+                        request_ending_version,
+                        "Streaming transactions"
+                    );
+                    self.active = Some(ActiveStream {
+                        endpoint_index,
+                        stream: Mutex::new(stream),
+                    });
+                    return Ok(());
+                },
+                Err(e) => {
+                    warn!(
+                        endpoint = %endpoint.indexer_grpc_data_service_address,
+                        error = ?e,
+                        "Failed to connect to transaction stream endpoint"
+                    );
+                    self.mark_unhealthy(endpoint_index);
+                },
+            }
+        }
+        warn!(
+            version,
+            retry_in = ?self.poll_interval(),
+            "No transaction stream endpoint can be reached, waiting for one to cool down"
+        );
+        Ok(())
+    }
+
+    fn mark_unhealthy(&mut self, endpoint_index: usize) {
+        let endpoint = &mut self.endpoints[endpoint_index];
+        endpoint.consecutive_failures += 1;
+        let cooldown = unhealthy_cooldown(
+            self.unhealthy_cooldown,
+            self.max_unhealthy_cooldown,
+            endpoint.consecutive_failures,
+        );
+        endpoint.unhealthy_until = Some(Instant::now() + cooldown);
+        GRPC_STREAM_FAILOVER_COUNT
+            .with_label_values(&[
+                self.processor_name.as_str(),
+                endpoint.config.indexer_grpc_data_service_address.as_str(),
+            ])
+            .inc();
+    }
+}
+
+/// Cooldown of an endpoint after its `consecutive_failures`th error in a row, doubling from
+/// `cooldown` up to `max_cooldown`
+fn unhealthy_cooldown(
+    cooldown: Duration,
+    max_cooldown: Duration,
+    consecutive_failures: u32,
+) -> Duration {
+    cooldown
+        .saturating_mul(2_u32.saturating_pow(consecutive_failures.saturating_sub(1)))
+        .min(max_cooldown)
+}
+
+/// Endpoints to stream `version` from, in order. Endpoints that don't cover it or are cooling
+/// down are left out, and ones restricted to a version range go first.
+fn endpoint_candidates(endpoints: &[StreamEndpoint], version: u64, now: Instant) -> Vec<usize> {
+    let mut candidates: Vec<usize> = (0..endpoints.len())
+        .filter(|&index| endpoints[index].covers(version) && endpoints[index].is_healthy(now))
+        .collect();
+    candidates.sort_by_key(|&index| endpoints[index].config.max_version.is_none());
+    candidates
+}
+
+/// How long until an endpoint covering `version` can be tried again, zero if one can be now
+fn retry_delay(endpoints: &[StreamEndpoint], version: u64, now: Instant) -> Duration {
+    endpoints
+        .iter()
+        .filter(|endpoint| endpoint.covers(version))
+        .map(|endpoint| {
+            endpoint
+                .unhealthy_until
+                .map_or(Duration::ZERO, |until| until.saturating_duration_since(now))
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+#[async_trait]
+impl Processable for FailoverTransactionStreamStep {
+    type Input = ();
+    type Output = Vec<Transaction>;
+    type RunType = PollableAsyncRunType;
+
+    async fn process(
+        &mut self,
+        _item: TransactionContext<()>,
+    ) -> Result<Option<TransactionContext<Vec<Transaction>>>, ProcessorError> {
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl PollableAsyncStep for FailoverTransactionStreamStep {
+    /// Polls again right away while streaming, and otherwise once an endpoint is done cooling down
+    fn poll_interval(&self) -> Duration {
+        if self.active.is_some() {
+            return Duration::ZERO;
+        }
+        retry_delay(
+            &self.endpoints,
+            self.next_version.unwrap_or_default(),
+            Instant::now(),
+        )
+    }
+
+    async fn poll(
+        &mut self,
+    ) -> Result<Option<Vec<TransactionContext<Vec<Transaction>>>>, ProcessorError> {
+        if self.refresh_auth_token(false)
+            && self.active.as_ref().is_some_and(|active| {
+                self.endpoints[active.endpoint_index]
+                    .config
+                    .auth_token
+                    .is_none()
+            })
+        {
+            info!(
+                next_version = ?self.next_version,
+                "Reconnecting to the transaction stream with the new auth token"
+            );
+            self.active = None;
+        }
+        if self.active.is_none() {
+            self.connect().await?;
+        }
+        let Some(active) = self.active.as_ref() else {
+            return Ok(None);
+        };
+        let endpoint_index = active.endpoint_index;
+        let mut stream = active.stream.lock().await;
+        let result = stream.get_next_transaction_batch().await;
+        let is_end_of_stream = stream.is_end_of_stream();
+        drop(stream);
+
+        match result {
+            Ok(response) => {
+                self.endpoints[endpoint_index].consecutive_failures = 0;
+                self.next_version = Some(response.end_version + 1);
+                // An endpoint restricted to a version range ends its stream at the end of it
+                if is_end_of_stream {
+                    self.active = None;
+                }
+                Ok(Some(vec![TransactionContext {
+                    data: response.transactions,
+                    metadata: TransactionMetadata {
+                        start_version: response.start_version,
+                        end_version: response.end_version,
+                        start_transaction_timestamp: response.start_txn_timestamp,
+                        end_transaction_timestamp: response.end_txn_timestamp,
+                        total_size_in_bytes: response.size_in_bytes,
+                    },
+                }]))
+            },
+            Err(e) => {
+                let endpoint = &self.endpoints[endpoint_index].config;
+                warn!(
+                    endpoint = %endpoint.indexer_grpc_data_service_address,
+                    next_version = ?self.next_version,
+                    error = ?e,
+                    "Transaction stream failed, failing over"
+                );
+                self.mark_unhealthy(endpoint_index);
+                self.active = None;
+                Ok(None)
+            },
+        }
+    }
+
+    async fn should_continue_polling(&mut self) -> bool {
+        match (self.next_version, self.ending_version) {
+            (Some(next_version), Some(ending_version)) => next_version <= ending_version,
+            _ => true,
+        }
+    }
+}
+
+impl NamedStep for FailoverTransactionStreamStep {
+    fn name(&self) -> String {
+        "FailoverTransactionStreamStep".to_string()
+    }
+}
+
+/// Checks the chain id against the one stored in the database and returns it, except for local
+/// transactions that don't carry one.
+pub async fn check_chain_id(
+    config: &IndexerProcessorConfig,
+    db_pool: ArcDbPool,
+) -> Result<Option<u64>> {
+    if config.local_transactions_config.is_some() {
+        warn!("Reading local transactions, skipping the chain id check");
+        return Ok(None);
+    }
+    let chain_id = check_or_update_chain_id(
+        &config.transaction_stream_config,
+        &PostgresChainIdChecker::new(db_pool),
+    )
+    .await?;
+    Ok(Some(chain_id))
+}
+
+/// Starting version of a processor without a checkpoint, resolving `start_at` and
+/// `start_at_timestamp` through the transaction stream.
+pub async fn resolve_bootstrap_version(
+    config: &IndexerProcessorConfig,
+    bootstrap_config: &BootStrapConfig,
+) -> Result<u64> {
+    if bootstrap_config.start_at.is_none() && bootstrap_config.start_at_timestamp.is_none() {
+        return Ok(bootstrap_config.initial_starting_version);
+    }
+    if bootstrap_config.start_at.is_some() && bootstrap_config.start_at_timestamp.is_some() {
+        bail!("Only one of start_at and start_at_timestamp can be set");
+    }
+    if config.local_transactions_config.is_some() {
+        bail!("start_at and start_at_timestamp can't be used with local transactions");
+    }
+    let stream_config = &config.transaction_stream_config;
+    let latest = get_stream_transaction(stream_config, None).await?;
+    let version = match bootstrap_config.start_at_timestamp {
+        Some(timestamp) => {
+            find_first_version_at(latest.version, timestamp, |version| async move {
+                let transaction = get_stream_transaction(stream_config, Some(version)).await?;
+                Ok(transaction_time(&transaction))
+            })
+            .await?
+        },
+        None => latest.version,
+    };
+    info!(
+        start_at = ?bootstrap_config.start_at,
+        start_at_timestamp = ?bootstrap_config.start_at_timestamp,
+        version,
+        "Resolved starting version"
+    );
+    Ok(version)
+}
+
+/// The transaction at `version`, or the latest one when no version is given, in which case the
+/// stream starts at the chain tip.
+async fn get_stream_transaction(
+    stream_config: &TransactionStreamConfig,
+    version: Option<u64>,
+) -> Result<Transaction> {
+    let mut stream = TransactionStream::new(TransactionStreamConfig {
+        starting_version: version,
+        request_ending_version: version,
+        ..stream_config.clone()
+    })
+    .await?;
+    stream
+        .get_next_transaction_batch()
+        .await?
+        .transactions
+        .into_iter()
+        .next()
+        .with_context(|| format!("Transaction stream returned no transaction at {version:?}"))
+}
+
+fn transaction_time(transaction: &Transaction) -> chrono::DateTime<chrono::Utc> {
+    transaction
+        .timestamp
+        .as_ref()
+        .map(|timestamp| parse_timestamp(timestamp, transaction.version as i64))
+        .unwrap_or_default()
+}
+
+/// Binary search for the first version at or after `timestamp`, as timestamps only go up with
+/// versions. Past the latest transaction, that's the latest version.
+async fn find_first_version_at<F, Fut>(
+    latest_version: u64,
+    timestamp: chrono::DateTime<chrono::Utc>,
+    get_time: F,
+) -> Result<u64>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<chrono::DateTime<chrono::Utc>>>,
+{
+    let (mut low, mut high) = (0, latest_version);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if get_time(mid).await? < timestamp {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+/// The transaction filter is applied by the gRPC stream, so local transactions can't be filtered
+pub fn validate_local_transactions(config: &IndexerProcessorConfig) -> Result<()> {
+    if config.local_transactions_config.is_some()
+        && config
+            .transaction_stream_config
+            .transaction_filter
+            .is_some()
+    {
+        bail!("transaction_filter can't be used with local transactions");
+    }
+    Ok(())
+}
+
+/// Sends the transactions of local files in version order, then stops. Files are read as the
+/// batches before them are sent, one at a time in path order.
+///
+/// Fixtures usually skip versions, so each batch covers the versions since the end of the
+/// previous one, as a gRPC response does, to keep the version tracker from seeing gaps.
+pub struct LocalTransactionStreamStep {
+    /// Files not read yet
+    files: VecDeque<PathBuf>,
+    /// Transactions read but not sent yet
+    transactions: VecDeque<Transaction>,
+    batch_size: usize,
+    starting_version: u64,
+    ending_version: u64,
+    /// Last version read from the files so far
+    last_read_version: Option<u64>,
+    /// Start of the next batch, the first version read unless a starting version is given
+    next_version: Option<u64>,
+}
+
+impl LocalTransactionStreamStep {
+    pub fn new(
+        config: &LocalTransactionsConfig,
+        starting_version: Option<u64>,
+        ending_version: Option<u64>,
+    ) -> Result<Self> {
+        let files = list_files(&config.path)?;
+        info!(
+            path = ?config.path,
+            num_files = files.len(),
+            "Streaming local transactions"
+        );
+        Ok(Self {
+            files: files.into(),
+            transactions: VecDeque::new(),
+            batch_size: config.batch_size.max(1),
+            starting_version: starting_version.unwrap_or(0),
+            ending_version: ending_version.unwrap_or(u64::MAX),
+            last_read_version: None,
+            next_version: starting_version,
+        })
+    }
+
+    /// Reads files until there's a full batch or none are left
+    fn read_next_files(&mut self) -> Result<()> {
+        while self.transactions.len() < self.batch_size {
+            let Some(path) = self.files.pop_front() else {
+                return Ok(());
+            };
+            let mut transactions = read_transactions(&path)?;
+            transactions.sort_by_key(|txn| txn.version);
+            transactions.dedup_by_key(|txn| txn.version);
+            let (Some(first), Some(last)) = (transactions.first(), transactions.last()) else {
+                continue;
+            };
+            if let Some(last_read_version) = self.last_read_version {
+                if first.version <= last_read_version {
+                    bail!(
+                        "{path:?} starts at version {}, which isn't after the files before it, \
+                         which end at version {last_read_version}",
+                        first.version
+                    );
+                }
+            }
+            self.last_read_version = Some(last.version);
+            // The files after this one are past the ending version too
+            if last.version > self.ending_version {
+                self.files.clear();
+            }
+            let versions = self.starting_version..=self.ending_version;
+            self.transactions.extend(
+                transactions
+                    .into_iter()
+                    .filter(|txn| versions.contains(&txn.version)),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Processable for LocalTransactionStreamStep {
+    type Input = ();
+    type Output = Vec<Transaction>;
+    type RunType = PollableAsyncRunType;
+
+    async fn process(
+        &mut self,
+        _item: TransactionContext<()>,
+    ) -> Result<Option<TransactionContext<Vec<Transaction>>>, ProcessorError> {
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl PollableAsyncStep for LocalTransactionStreamStep {
+    fn poll_interval(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    async fn poll(
+        &mut self,
+    ) -> Result<Option<Vec<TransactionContext<Vec<Transaction>>>>, ProcessorError> {
+        self.read_next_files()
+            .map_err(|e| ProcessorError::ProcessError {
+                message: format!("Failed to read local transactions: {e:?}"),
+            })?;
+        let batch_size = self.batch_size.min(self.transactions.len());
+        if batch_size == 0 {
+            return Ok(None);
+        }
+        let batch: Vec<Transaction> = self.transactions.drain(..batch_size).collect();
+        let (first, last) = (&batch[0], &batch[batch.len() - 1]);
+        let metadata = TransactionMetadata {
+            start_version: self.next_version.unwrap_or(first.version),
+            end_version: last.version,
+            start_transaction_timestamp: first.timestamp,
+            end_transaction_timestamp: last.timestamp,
+            total_size_in_bytes: batch.iter().map(|txn| txn.encoded_len() as u64).sum(),
+        };
+        self.next_version = Some(last.version + 1);
+        Ok(Some(vec![TransactionContext {
+            data: batch,
+            metadata,
+        }]))
+    }
+
+    async fn should_continue_polling(&mut self) -> bool {
+        !self.transactions.is_empty() || !self.files.is_empty()
+    }
+}
+
+impl NamedStep for LocalTransactionStreamStep {
+    fn name(&self) -> String {
+        "LocalTransactionStreamStep".to_string()
+    }
+}
+
+/// Files under `path`, in path order so that the load is deterministic
+fn list_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = vec![];
+    for entry in std::fs::read_dir(path)
+        .with_context(|| format!("Failed to read transactions directory {path:?}"))?
+    {
+        let entry_path = entry?.path();
+        if entry_path.is_dir() {
+            files.extend(list_files(&entry_path)?);
+        } else {
+            files.push(entry_path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn read_transactions(path: &Path) -> Result<Vec<Transaction>> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read transactions file {path:?}"))?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => parse_json_transactions(&bytes)
+            .with_context(|| format!("Failed to parse transactions file {path:?}")),
+        Some("pb") => decode_protobuf_transactions(&bytes)
+            .with_context(|| format!("Failed to decode transactions file {path:?}")),
+        _ => {
+            warn!(path = ?path, "Skipping file that isn't .json or .pb");
+            Ok(vec![])
+        },
+    }
+}
+
+fn parse_json_transactions(bytes: &[u8]) -> Result<Vec<Transaction>> {
+    match serde_json::from_slice::<serde_json::Value>(bytes)? {
+        serde_json::Value::Array(values) => values
+            .into_iter()
+            .map(|value| Ok(serde_json::from_value(value)?))
+            .collect(),
+        value => Ok(vec![serde_json::from_value(value)?]),
+    }
+}
+
+fn decode_protobuf_transactions(mut bytes: &[u8]) -> Result<Vec<Transaction>> {
+    let mut transactions = vec![];
+    while !bytes.is_empty() {
+        transactions.push(Transaction::decode_length_delimited(&mut bytes)?);
+    }
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(version: u64) -> Transaction {
+        Transaction {
+            version,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decode_protobuf_transactions() {
+        let mut bytes = vec![];
+        for version in [3, 4] {
+            transaction(version)
+                .encode_length_delimited(&mut bytes)
+                .unwrap();
+        }
+        let versions: Vec<u64> = decode_protobuf_transactions(&bytes)
+            .unwrap()
+            .iter()
+            .map(|txn| txn.version)
+            .collect();
+        assert_eq!(versions, vec![3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_local_stream_batches_in_version_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let write_file = |name: &str, versions: &[u64]| {
+            let mut bytes = vec![];
+            for version in versions {
+                transaction(*version)
+                    .encode_length_delimited(&mut bytes)
+                    .unwrap();
+            }
+            std::fs::write(dir.path().join(name), bytes).unwrap();
+        };
+        write_file("a.pb", &[3, 1, 2]);
+        write_file("b.pb", &[5, 4, 9]);
+        write_file("c.pb", &[12]);
+
+        let mut step = LocalTransactionStreamStep::new(
+            &LocalTransactionsConfig {
+                path: dir.path().to_path_buf(),
+                batch_size: 2,
+            },
+            Some(2),
+            Some(5),
+        )
+        .unwrap();
+        let mut batches = vec![];
+        while step.should_continue_polling().await {
+            for context in step.poll().await.unwrap().unwrap() {
+                batches.push((
+                    context.metadata.start_version,
+                    context.metadata.end_version,
+                    context.data.len(),
+                ));
+            }
+        }
+        assert_eq!(batches, vec![(2, 3, 2), (4, 5, 2)]);
+
+        // Files whose versions go back can't be streamed in order
+        write_file("d.pb", &[11]);
+        let mut step = LocalTransactionStreamStep::new(
+            &LocalTransactionsConfig {
+                path: dir.path().to_path_buf(),
+                batch_size: 10,
+            },
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(step.poll().await.is_err());
+    }
+
+    #[test]
+    fn test_endpoint_candidates() {
+        let endpoint = |address: &str, max_version: Option<u64>, unhealthy: bool| StreamEndpoint {
+            config: StreamEndpointConfig {
+                indexer_grpc_data_service_address: url::Url::parse(address).unwrap(),
+                auth_token: None,
+                min_version: None,
+                max_version,
+            },
+            unhealthy_until: unhealthy.then(|| Instant::now() + Duration::from_secs(60)),
+            consecutive_failures: unhealthy as u32,
+        };
+        let endpoints = vec![
+            endpoint("https://primary.com", None, false),
+            endpoint("https://archive.com", Some(100), false),
+            endpoint("https://backup.com", None, false),
+        ];
+        let now = Instant::now();
+        assert_eq!(endpoint_candidates(&endpoints, 50, now), vec![1, 0, 2]);
+        assert_eq!(endpoint_candidates(&endpoints, 101, now), vec![0, 2]);
+
+        let endpoints = vec![
+            endpoint("https://primary.com", None, true),
+            endpoint("https://backup.com", None, false),
+        ];
+        assert_eq!(endpoint_candidates(&endpoints, 101, now), vec![1]);
+        assert_eq!(retry_delay(&endpoints, 101, now), Duration::ZERO);
+
+        let endpoints = vec![endpoint("https://primary.com", None, true)];
+        assert!(endpoint_candidates(&endpoints, 101, now).is_empty());
+        assert!(retry_delay(&endpoints, 101, now) > Duration::from_secs(59));
+    }
+
+    #[test]
+    fn test_unhealthy_cooldown_backs_off() {
+        let cooldown = |consecutive_failures| {
+            unhealthy_cooldown(
+                Duration::from_secs(60),
+                Duration::from_secs(900),
+                consecutive_failures,
+            )
+            .as_secs()
+        };
+        assert_eq!(cooldown(1), 60);
+        assert_eq!(cooldown(2), 120);
+        assert_eq!(cooldown(4), 480);
+        assert_eq!(cooldown(5), 900);
+        assert_eq!(cooldown(64), 900);
+    }
+
+    #[tokio::test]
+    async fn test_find_first_version_at() {
+        // A block every 10 versions, one second apart
+        let get_time = |version: u64| async move {
+            Ok(chrono::DateTime::from_timestamp((version / 10) as i64, 0).unwrap())
+        };
+        let at = |seconds| chrono::DateTime::from_timestamp(seconds, 0).unwrap();
+        assert_eq!(find_first_version_at(99, at(0), get_time).await.unwrap(), 0);
+        assert_eq!(
+            find_first_version_at(99, at(3), get_time).await.unwrap(),
+            30
+        );
+        assert_eq!(
+            find_first_version_at(99, at(60), get_time).await.unwrap(),
+            99
+        );
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Upserts into current tables only overwrite a stored row with one of the same or a higher
+//! version, so batches written out of order, like those of parallel backfills, can't regress a
+//! row. Rows the guard keeps out are counted in `indexer_processor_stale_writes_rejected`.
+
+use crate::{db::schema_drift::expected_tables, utils::counters::STALE_WRITES_REJECTED_COUNT};
+use ahash::AHashSet;
+use once_cell::sync::Lazy;
+
+/// Columns holding the version a row was last written at, e.g. `last_transaction_version` or
+/// `last_transaction_version_v1`
+const VERSION_COLUMN_PREFIX: &str = "last_transaction_version";
+
+/// Tables with a version column, whose upserts are guarded
+static VERSIONED_TABLES: Lazy<AHashSet<String>> = Lazy::new(|| {
+    expected_tables()
+        .into_iter()
+        .filter(|table| {
+            table
+                .columns
+                .iter()
+                .any(|column| column.name.starts_with(VERSION_COLUMN_PREFIX))
+        })
+        .map(|table| table.name)
+        .collect()
+});
+
+/// `DO UPDATE` filter of an upsert that keeps the stored row when the incoming one has a lower
+/// version. With `nullable`, a stored row without a version is always overwritten.
+///
+/// ```ignore
+/// diesel::insert_into(schema::current_objects::table)
+///     .values(items_to_insert)
+///     .on_conflict(object_address)
+///     .do_update()
+///     .set(/* ... */)
+///     .filter(version_guard!(last_transaction_version))
+/// ```
+#[macro_export]
+macro_rules! version_guard {
+    (nullable $column:expr) => {
+        diesel::BoolExpressionMethods::or(
+            diesel::ExpressionMethods::is_null($column),
+            $crate::version_guard!($column),
+        )
+    };
+    ($column:expr) => {
+        diesel::ExpressionMethods::le($column, diesel::pg::upsert::excluded($column))
+    };
+}
+
+pub fn is_versioned_table(table_name: &str) -> bool {
+    VERSIONED_TABLES.contains(table_name)
+}
+
+/// Counts the rows of a write to `table_name` that weren't written. Upserts into versioned tables
+/// only skip rows through their version guard, since rows are deduplicated by primary key first.
+pub fn record_stale_writes(table_name: &str, rows_skipped: usize) {
+    if rows_skipped > 0 && is_versioned_table(table_name) {
+        STALE_WRITES_REJECTED_COUNT
+            .with_label_values(&[table_name])
+            .inc_by(rows_skipped as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versioned_tables() {
+        assert!(is_versioned_table("current_token_datas_v2"));
+        assert!(is_versioned_table("current_fungible_asset_balances"));
+        assert!(!is_versioned_table("token_activities_v2"));
+    }
+}
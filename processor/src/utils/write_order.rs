@@ -0,0 +1,64 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Order in which the tables of a batch are written. Some tables reference rows of others the
+//! way a foreign key would, without a constraint enforcing it: token datas name their
+//! collection, and ownerships, activities, royalties, claims and property values their token
+//! data. `TableWriters` only starts a batch's write to a table once the batch's writes to the
+//! tables it depends on succeeded. A batch that fails part way then never leaves a row whose
+//! referenced row is missing, and its retry writes the tables in the same order. Storers get to
+//! a table's dependencies before the table, even when they have no rows for them, which
+//! `TableWriters` checks.
+//!
+//! Tables without dependencies are written concurrently, and a table's later batches don't
+//! wait for the tables it depends on, since earlier batches of those were written first.
+
+/// Tables and the tables of the same processor they reference or are computed from
+pub const WRITE_DEPENDENCIES: &[(&str, &[&str])] = &[
+    ("current_token_datas_v2", &["current_collections_v2"]),
+    ("current_token_ownerships_v2", &["current_token_datas_v2"]),
+    ("token_activities_v2", &["current_token_datas_v2"]),
+    ("current_token_royalty_v1", &["current_token_datas_v2"]),
+    ("current_token_pending_claims", &["current_token_datas_v2"]),
+    ("token_property_values", &["current_token_datas_v2"]),
+];
+
+/// Tables whose writes a batch's write to `table_name` waits for
+pub fn write_dependencies(table_name: &str) -> &'static [&'static str] {
+    WRITE_DEPENDENCIES
+        .iter()
+        .find(|(table, _)| *table == table_name)
+        .map_or(&[], |(_, dependencies)| dependencies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{explain_config::processor_table_flags, processor_config::ProcessorName},
+        utils::table_flags::TableFlags,
+    };
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_write_dependencies_are_written_by_the_same_processor() {
+        let flag = |table: &str| TableFlags::from_name(&table.to_uppercase()).unwrap();
+        for (table, dependencies) in WRITE_DEPENDENCIES {
+            let processors: Vec<ProcessorName> = ProcessorName::iter()
+                .filter(|processor| processor_table_flags(*processor).contains(flag(table)))
+                .collect();
+            assert!(
+                !processors.is_empty(),
+                "{table} isn't written by a processor"
+            );
+            for processor in processors {
+                for dependency in *dependencies {
+                    assert!(
+                        processor_table_flags(processor).contains(flag(dependency)),
+                        "{processor} writes {table} but not {dependency}"
+                    );
+                }
+            }
+        }
+    }
+}
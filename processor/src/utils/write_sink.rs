@@ -0,0 +1,208 @@
+// Copyright © Cedra Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! What happens to the rows storers write besides the database write itself. Dry runs that take
+//! the database's place, row checks and copies of committed rows each implement `WriteSink`, and
+//! every batch written with `execute_in_chunks_with_stats` goes through the sinks added to the
+//! process:
+//! - If a sink replaces the database, like a CSV export, it's the only one to see the batch, and
+//!   nothing is written. Only one such sink can be added at a time.
+//! - Otherwise every sink sees the batch before it's written, and can hold rows back from it.
+//! - Once the rest is committed, every sink sees the rows that were written.
+//!
+//! Rows a sink writes to other tables, like `quarantined_rows`, go through the sinks as well.
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use cedra_indexer_processor_sdk::{
+    postgres::utils::database::ArcDbPool, utils::errors::ProcessorError,
+};
+use futures::future::BoxFuture;
+use once_cell::sync::{Lazy, OnceCell};
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+
+/// A write of some of a batch's rows with the storer's query, which can run against any pool
+/// once the batch is gone. Returns the rows written.
+pub type OwnedWrite =
+    Box<dyn FnOnce(ArcDbPool) -> BoxFuture<'static, Result<usize, ProcessorError>> + Send>;
+
+/// The rows of a batch, with their type erased so that sinks can be trait objects
+pub trait TableRows: Send + Sync {
+    fn len(&self) -> usize;
+
+    fn to_json(&self) -> serde_json::Result<Vec<Value>>;
+
+    /// Writes the rows at `indices`, in chunks
+    fn write(&self, indices: &[usize]) -> OwnedWrite;
+}
+
+/// Rows of one table on their way to the database, in the order they're written
+pub struct TableBatch<'a> {
+    pub table_name: &'static str,
+    rows: &'a dyn TableRows,
+    json: OnceCell<Vec<Value>>,
+}
+
+impl<'a> TableBatch<'a> {
+    pub fn new(table_name: &'static str, rows: &'a dyn TableRows) -> Self {
+        Self {
+            table_name,
+            rows,
+            json: OnceCell::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The rows as they're written, serialized once for all the sinks that need them
+    pub fn json(&self) -> Result<&[Value], ProcessorError> {
+        self.json
+            .get_or_try_init(|| self.rows.to_json())
+            .map(Vec::as_slice)
+            .map_err(|e| ProcessorError::DBStoreError {
+                message: format!("Failed to serialize rows of {}: {e:?}", self.table_name),
+                query: None,
+            })
+    }
+
+    pub fn write(&self, indices: &[usize]) -> OwnedWrite {
+        self.rows.write(indices)
+    }
+}
+
+#[async_trait]
+pub trait WriteSink: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Whether the sink handles batches instead of the database. Storers then also skip the
+    /// statements they run besides their row writes.
+    fn replaces_database(&self) -> bool {
+        false
+    }
+
+    /// Sees a batch before it's written. Returns the indices of the rows to hold back from the
+    /// database.
+    async fn before_write(
+        &self,
+        _conn_pool: &ArcDbPool,
+        _batch: &TableBatch<'_>,
+    ) -> Result<Vec<usize>, ProcessorError> {
+        Ok(vec![])
+    }
+
+    /// Sees the rows of a batch at `written` once they're committed.
+    async fn committed(
+        &self,
+        _batch: &TableBatch<'_>,
+        _written: &[usize],
+    ) -> Result<(), ProcessorError> {
+        Ok(())
+    }
+}
+
+/// Sinks in the order they were added
+#[derive(Clone, Default)]
+pub struct WriteSinks(Vec<Arc<dyn WriteSink>>);
+
+impl WriteSinks {
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn WriteSink>> {
+        self.0.iter()
+    }
+
+    /// The sink handling batches instead of the database, if any
+    pub fn database_replacement(&self) -> Option<&Arc<dyn WriteSink>> {
+        self.0.iter().find(|sink| sink.replaces_database())
+    }
+
+    /// Whether nothing is written to the database, so storers skip their other statements too
+    pub fn replaces_database(&self) -> bool {
+        self.database_replacement().is_some()
+    }
+
+    /// Adds `sink` after the others, replacing one of the same name. Only one sink can replace
+    /// the database.
+    pub fn add(&mut self, sink: Arc<dyn WriteSink>) -> Result<()> {
+        self.0.retain(|added| added.name() != sink.name());
+        if let Some(replacement) = self.database_replacement() {
+            if sink.replaces_database() {
+                bail!(
+                    "{} can't run while {} replaces the database",
+                    sink.name(),
+                    replacement.name()
+                );
+            }
+        }
+        self.0.push(sink);
+        Ok(())
+    }
+}
+
+static WRITE_SINKS: Lazy<RwLock<WriteSinks>> = Lazy::new(|| RwLock::new(WriteSinks::default()));
+
+/// Adds a sink to the writes of every table, replacing one of the same name.
+pub fn add_write_sink(sink: Arc<dyn WriteSink>) -> Result<()> {
+    WRITE_SINKS.write().unwrap().add(sink)
+}
+
+pub fn remove_write_sink(name: &str) {
+    WRITE_SINKS
+        .write()
+        .unwrap()
+        .0
+        .retain(|sink| sink.name() != name);
+}
+
+/// The sinks added to this process
+pub fn write_sinks() -> WriteSinks {
+    WRITE_SINKS.read().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestSink {
+        name: &'static str,
+        replaces_database: bool,
+    }
+
+    impl WriteSink for TestSink {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn replaces_database(&self) -> bool {
+            self.replaces_database
+        }
+    }
+
+    fn sink(name: &'static str, replaces_database: bool) -> Arc<dyn WriteSink> {
+        Arc::new(TestSink {
+            name,
+            replaces_database,
+        })
+    }
+
+    #[test]
+    fn test_only_one_sink_replaces_the_database() {
+        let mut sinks = WriteSinks::default();
+        sinks.add(sink("replica", false)).unwrap();
+        assert!(!sinks.replaces_database());
+        sinks.add(sink("audit", true)).unwrap();
+        assert!(sinks.add(sink("csv_export", true)).is_err());
+        // Adding a sink again replaces it
+        sinks.add(sink("audit", true)).unwrap();
+        sinks.add(sink("file_sink", false)).unwrap();
+
+        let names: Vec<_> = sinks.iter().map(|sink| sink.name()).collect();
+        assert_eq!(names, vec!["replica", "audit", "file_sink"]);
+        assert_eq!(sinks.database_replacement().unwrap().name(), "audit");
+    }
+}